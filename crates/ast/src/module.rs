@@ -1,5 +1,5 @@
 use bumpalo::Bump;
-use roc_load::{ExecutionMode, LoadConfig, LoadedModule, Threading};
+use roc_load::{ExecutionMode, LoadConfig, LoadedModule, OptLevel, Threading};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::DEFAULT_PALETTE;
 use roc_target::TargetInfo;
@@ -18,6 +18,7 @@ pub fn load_module(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        opt_level: OptLevel::Normal,
     };
 
     let arena = Bump::new();
@@ -10,7 +10,7 @@ use roc_region::all::{Loc, Region};
 use roc_solve::module::Solved;
 use roc_types::subs::{
     self, AliasVariables, Content, Descriptor, FlatType, Mark, OptVariable, Rank, RecordFields,
-    Subs, SubsSlice, UnionLambdas, UnionTags, Variable, VariableSubsSlice,
+    Subs, SubsSlice, TupleElems, UnionLambdas, UnionTags, Variable, VariableSubsSlice,
 };
 use roc_types::types::{
     gather_fields_unsorted_iter, Alias, AliasKind, Category, ErrorType, PatternCategory, Polarity,
@@ -1373,6 +1373,11 @@ fn adjust_rank_content(
                     Rank::toplevel()
                 }
 
+                EmptyTuple => {
+                    // THEORY: an empty tuple never needs to get generalized
+                    Rank::toplevel()
+                }
+
                 EmptyTagUnion => Rank::toplevel(),
 
                 Record(fields, ext_var) => {
@@ -1386,6 +1391,17 @@ fn adjust_rank_content(
                     rank
                 }
 
+                Tuple(elems, ext_var) => {
+                    let mut rank = adjust_rank(subs, young_mark, visit_mark, group_rank, *ext_var);
+
+                    for index in elems.iter_variables() {
+                        let var = subs[index];
+                        rank = rank.max(adjust_rank(subs, young_mark, visit_mark, group_rank, var));
+                    }
+
+                    rank
+                }
+
                 TagUnion(tags, ext_var) => {
                     let mut rank = adjust_rank(subs, young_mark, visit_mark, group_rank, *ext_var);
 
@@ -1564,7 +1580,7 @@ fn instantiate_rigids_help(
                     }
                 }
 
-                EmptyRecord | EmptyTagUnion => {}
+                EmptyRecord | EmptyTuple | EmptyTagUnion => {}
 
                 Record(fields, ext_var) => {
                     for index in fields.iter_variables() {
@@ -1575,6 +1591,15 @@ fn instantiate_rigids_help(
                     instantiate_rigids_help(subs, max_rank, pools, ext_var);
                 }
 
+                Tuple(elems, ext_var) => {
+                    for index in elems.iter_variables() {
+                        let var = subs[index];
+                        instantiate_rigids_help(subs, max_rank, pools, var);
+                    }
+
+                    instantiate_rigids_help(subs, max_rank, pools, ext_var);
+                }
+
                 TagUnion(tags, ext_var) => {
                     for (_, index) in tags.iter_all() {
                         let slice = subs[index];
@@ -1744,7 +1769,7 @@ fn deep_copy_var_help(
                     Func(arg_vars, new_closure_var, new_ret_var)
                 }
 
-                same @ EmptyRecord | same @ EmptyTagUnion => same,
+                same @ EmptyRecord | same @ EmptyTuple | same @ EmptyTagUnion => same,
 
                 Record(fields, ext_var) => {
                     let record_fields = {
@@ -1787,6 +1812,42 @@ fn deep_copy_var_help(
                     )
                 }
 
+                Tuple(elems, ext_var) => {
+                    let tuple_elems = {
+                        let mut new_vars = Vec::with_capacity(elems.len());
+
+                        for index in elems.iter_variables() {
+                            let var = subs[index];
+                            let copy_var = deep_copy_var_help(subs, max_rank, pools, var);
+
+                            new_vars.push(copy_var);
+                        }
+
+                        let elem_index_start = subs.tuple_elem_indices.len() as u32;
+                        let variables_start = subs.variables.len() as u32;
+
+                        let mut length = 0;
+
+                        for ((i1, _), var) in elems.iter_all().zip(new_vars) {
+                            subs.tuple_elem_indices.push(subs[i1]);
+                            subs.variables.push(var);
+
+                            length += 1;
+                        }
+
+                        TupleElems {
+                            length,
+                            elem_index_start,
+                            variables_start,
+                        }
+                    };
+
+                    Tuple(
+                        tuple_elems,
+                        deep_copy_var_help(subs, max_rank, pools, ext_var),
+                    )
+                }
+
                 TagUnion(tags, ext_var) => {
                     let mut new_variable_slices = Vec::with_capacity(tags.len());
 
@@ -14,8 +14,8 @@ use roc_load::{
 use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::{
-    cli::Problems,
-    report::{RenderTarget, DEFAULT_PALETTE},
+    cli::{Problems, ReportFormat},
+    report::DEFAULT_PALETTE,
 };
 use roc_target::TargetInfo;
 use std::time::{Duration, Instant};
@@ -72,6 +72,7 @@ pub fn build_file<'a>(
     wasm_dev_stack_bytes: Option<u32>,
     roc_cache_dir: RocCacheDir<'_>,
     order: BuildOrdering,
+    format: ReportFormat,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let compilation_start = Instant::now();
     let target_info = TargetInfo::from(target);
@@ -86,11 +87,11 @@ pub fn build_file<'a>(
 
     let load_config = LoadConfig {
         target_info,
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
+        render: format.render_target(),
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
+        opt_level: code_gen_options.opt_level,
     };
     let load_result = roc_load::load_and_monomorphize(
         arena,
@@ -274,7 +275,7 @@ pub fn build_file<'a>(
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = program::report_problems_monomorphized(&mut loaded);
+    let problems = program::report_problems_monomorphized(&mut loaded, format);
     let loaded = loaded;
 
     enum HostRebuildTiming {
@@ -507,6 +508,7 @@ pub fn check_file<'a>(
     emit_timings: bool,
     roc_cache_dir: RocCacheDir<'_>,
     threading: Threading,
+    format: ReportFormat,
 ) -> Result<(Problems, Duration), LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
@@ -519,11 +521,11 @@ pub fn check_file<'a>(
 
     let load_config = LoadConfig {
         target_info,
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
+        render: format.render_target(),
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        opt_level: OptLevel::Normal,
     };
     let mut loaded = roc_load::load_and_typecheck(
         arena,
@@ -577,7 +579,7 @@ pub fn check_file<'a>(
     }
 
     Ok((
-        program::report_problems_typechecked(&mut loaded),
+        program::report_problems_typechecked(&mut loaded, format),
         compilation_end,
     ))
 }
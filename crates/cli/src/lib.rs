@@ -13,7 +13,7 @@ use roc_load::{ExpectMetadata, LoadingProblem, Threading};
 use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
-use roc_reporting::cli::Problems;
+use roc_reporting::cli::{diagnostics_document, Diagnostic, Problems, ReportFormat};
 use std::env;
 use std::ffi::{CString, OsStr};
 use std::io;
@@ -32,7 +32,9 @@ use tempfile::TempDir;
 
 pub mod build;
 mod format;
+mod watch;
 pub use format::format;
+pub use watch::watch;
 
 use crate::build::{BuildFileError, BuildOrdering};
 
@@ -49,7 +51,14 @@ pub const CMD_VERSION: &str = "version";
 pub const CMD_FORMAT: &str = "format";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
+pub const CMD_LSP: &str = "lsp";
 pub const CMD_GEN_STUB_LIB: &str = "gen-stub-lib";
+pub const CMD_PACKAGE: &str = "package";
+pub const CMD_VENDOR: &str = "vendor";
+pub const CMD_CACHE: &str = "cache";
+pub const CMD_LIST: &str = "list";
+pub const CMD_VERIFY: &str = "verify";
+pub const CMD_CLEAN: &str = "clean";
 
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_BUNDLE: &str = "bundle";
@@ -65,6 +74,18 @@ pub const FLAG_LINKER: &str = "linker";
 pub const FLAG_PREBUILT: &str = "prebuilt-platform";
 pub const FLAG_CHECK: &str = "check";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
+pub const FLAG_FORMAT: &str = "format";
+pub const FLAG_WATCH: &str = "watch";
+pub const FLAG_FILTER: &str = "filter";
+pub const FLAG_FAIL_FAST: &str = "fail-fast";
+pub const FLAG_DOC: &str = "doc";
+pub const FLAG_REPORT: &str = "report";
+pub const FLAG_REPORT_FORMAT: &str = "report-format";
+pub const FLAG_DIR: &str = "dir";
+pub const FLAG_ARCHIVE: &str = "archive";
+pub const FLAG_REMOVE: &str = "remove";
+pub const FLAG_OLDER_THAN: &str = "older-than";
+pub const FLAG_MAX_SIZE: &str = "max-size";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_FILE: &str = "GLUE_FILE";
@@ -125,6 +146,18 @@ pub fn build_app<'a>() -> Command<'a> {
         .validator(|s| s.parse::<u32>())
         .required(false);
 
+    let flag_format = Arg::new(FLAG_FORMAT)
+        .long(FLAG_FORMAT)
+        .help("How to print problems\n(`json` and `sarif` print one document to stdout, for tools like code review bots.)")
+        .possible_values(["text", "json", "sarif"])
+        .default_value("text")
+        .required(false);
+
+    let flag_watch = Arg::new(FLAG_WATCH)
+        .long(FLAG_WATCH)
+        .help("Start over whenever a .roc file in the app's directory, or in the directory of a local package it uses, changes")
+        .required(false);
+
     let roc_file_to_run = Arg::new(ROC_FILE)
         .help("The .roc file of an app to run")
         .allow_invalid_utf8(true)
@@ -153,6 +186,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
             .arg(flag_wasm_stack_size_kb.clone())
+            .arg(flag_format.clone())
             .arg(
                 Arg::new(FLAG_TARGET)
                     .long(FLAG_TARGET)
@@ -201,6 +235,42 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_watch.clone())
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the `expect`s whose module name, or first line of source, contains this text")
+                    .takes_value(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_FAIL_FAST)
+                    .long(FLAG_FAIL_FAST)
+                    .help("Stop after the first `expect` that fails")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_DOC)
+                    .long(FLAG_DOC)
+                    .help("Also test the ```roc examples in doc comments.\nLines like `expr # ==> value` check that `expr == value`.")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_REPORT)
+                    .long(FLAG_REPORT)
+                    .help("Also write a report of every `expect` that ran to this file, for CI dashboards")
+                    .allow_invalid_utf8(true)
+                    .takes_value(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_REPORT_FORMAT)
+                    .long(FLAG_REPORT_FORMAT)
+                    .help("The format of the --report file")
+                    .possible_values(["junit", "json"])
+                    .default_value("junit")
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file for the main module")
@@ -223,6 +293,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_watch.clone())
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone())
         )
@@ -260,6 +331,8 @@ pub fn build_app<'a>() -> Command<'a> {
             .about("Check the code for problems, but don’t build or run it")
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_format)
+            .arg(flag_watch)
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file of an app to check")
@@ -274,10 +347,13 @@ pub fn build_app<'a>() -> Command<'a> {
                 .arg(Arg::new(DIRECTORY_OR_FILES)
                     .multiple_values(true)
                     .required(false)
-                    .help("The directory or files to build documentation for")
+                    .help("The package module, or the directory or files to build documentation for")
                     .allow_invalid_utf8(true)
                 )
         )
+        .subcommand(Command::new(CMD_LSP)
+            .about("Start a language server, which editors talk to over stdio using the Language Server Protocol")
+        )
         .subcommand(Command::new(CMD_GLUE)
             .about("Generate glue code between a platform's Roc API and its host language")
             .arg(
@@ -288,11 +364,87 @@ pub fn build_app<'a>() -> Command<'a> {
             )
             .arg(
                 Arg::new(GLUE_FILE)
-                    .help("The filename for the generated glue code\n(Currently, this must be a .rs file because only Rust glue generation is supported so far.)")
+                    .help("The filename for the generated glue code\n(The extension picks the language: .rs for Rust, .h for C, or .zig for Zig.)")
                     .allow_invalid_utf8(true)
                     .required(true)
             )
         )
+        .subcommand(Command::new(CMD_PACKAGE)
+            .about("Work with the packages a Roc project depends on")
+            .subcommand_required(true)
+            .subcommand(Command::new(CMD_BUILD)
+                .about("Create an archive of a whole package, starting from its `package` module: the modules it exposes, the modules they import, and the files listed in its assets.txt.\nThe archive includes a manifest of its files and their hashes, and it gets checked with `roc check` after unpacking it.")
+                .arg(
+                    Arg::new(FLAG_ARCHIVE)
+                        .long(FLAG_ARCHIVE)
+                        .help("The kind of archive to create")
+                        .possible_values([".tar", ".tar.gz", ".tar.br"])
+                        .default_value(".tar.br")
+                        .required(false),
+                )
+                .arg(
+                    Arg::new(ROC_FILE)
+                        .help("The package's .roc file")
+                        .allow_invalid_utf8(true)
+                        .required(false)
+                        .default_value(DEFAULT_ROC_FILENAME),
+                )
+            )
+            .subcommand(Command::new(CMD_VENDOR)
+                .about("Save the tarballs of all the packages an app or package depends on into a directory.\nWith ROC_PACKAGE_MIRROR=file:///path/to/that/directory, it then builds without a network connection.")
+                .arg(
+                    Arg::new(FLAG_DIR)
+                        .long(FLAG_DIR)
+                        .help("The directory to save the tarballs in")
+                        .takes_value(true)
+                        .allow_invalid_utf8(true)
+                        .default_value("vendor")
+                        .required(false),
+                )
+                .arg(
+                    Arg::new(ROC_FILE)
+                        .help("The .roc file of the app or package")
+                        .allow_invalid_utf8(true)
+                        .required(false)
+                        .default_value(DEFAULT_ROC_FILENAME),
+                )
+            )
+        )
+        .subcommand(Command::new(CMD_CACHE)
+            .about("Manage the packages that builds have downloaded")
+            .subcommand_required(true)
+            .subcommand(Command::new(CMD_LIST)
+                .about("List the packages in the cache, with their sizes and when they were last used")
+            )
+            .subcommand(Command::new(CMD_VERIFY)
+                .about("Check that the files of each package in the cache haven't changed since it was downloaded, and that none of them were only partly installed.\nThis compares them to a record that is kept in the cache too, so it catches accidental changes rather than deliberate tampering.\nPackages without a record, including ones installed by older versions of roc, are downloaded again by the next build that uses them.")
+                .arg(
+                    Arg::new(FLAG_REMOVE)
+                        .long(FLAG_REMOVE)
+                        .help("Remove the packages that fail, so the next build downloads them again")
+                        .required(false),
+                )
+            )
+            .subcommand(Command::new(CMD_CLEAN)
+                .about("Remove packages and cached module types from the cache. Without any limits, this removes all of them.\nSet ROC_NO_TYPE_CACHE to stop builds from caching module types at all.")
+                .arg(
+                    Arg::new(FLAG_OLDER_THAN)
+                        .long(FLAG_OLDER_THAN)
+                        .help("Remove the packages and module types that no build has used in this many days")
+                        .takes_value(true)
+                        .validator(|s| s.parse::<u64>())
+                        .required(false),
+                )
+                .arg(
+                    Arg::new(FLAG_MAX_SIZE)
+                        .long(FLAG_MAX_SIZE)
+                        .help("Remove the least recently used packages, and then module types, until each of them takes up at most this many megabytes")
+                        .takes_value(true)
+                        .validator(|s| s.parse::<u64>())
+                        .required(false),
+                )
+            )
+        )
         .subcommand(Command::new(CMD_GEN_STUB_LIB)
             .about("Generate a stubbed shared library that can be used for linking a platform binary.\nThe stubbed library has prototypes, but no function bodies.\n\nNote: This command will be removed in favor of just using `roc build` once all platforms support the surgical linker")
             .arg(
@@ -340,6 +492,305 @@ pub fn build_app<'a>() -> Command<'a> {
     }
 }
 
+/// `roc package build`
+pub fn package_build(matches: &ArgMatches) -> io::Result<i32> {
+    use roc_packaging::cache;
+    use roc_packaging::https;
+    use roc_packaging::tarball::{self, MANIFEST_FILENAME};
+
+    let start_time = Instant::now();
+    let path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+    let compression = Compression::try_from(matches.value_of(FLAG_ARCHIVE).unwrap()).unwrap();
+
+    if compression == Compression::Brotli {
+        println!("Compressing with Brotli at maximum quality level…\n\n(Note: Brotli compression can take awhile! Using --{FLAG_ARCHIVE} .tar.gz takes less time, but usually produces a significantly larger output file.)\n");
+    }
+
+    let bundle = tarball::build_package(path, compression)?;
+    let created_path = path.with_file_name(&bundle.filename);
+
+    // Unpack the archive the way a download of it would be, so anything missing from it
+    // shows up now rather than for the people depending on the package.
+    let unpacked_dir = tempfile::tempdir()?;
+    let file_url = format!("file://{}", created_path.canonicalize()?.display());
+
+    match https::download_and_hash(&file_url, unpacked_dir.path(), u64::MAX) {
+        Ok(hash) if bundle.filename.starts_with(&format!("{hash}.")) => {}
+        Ok(hash) => {
+            eprintln!(
+                "The archive {} unpacked with the hash {hash}, which doesn't match its filename.",
+                created_path.display()
+            );
+
+            return Ok(1);
+        }
+        Err(problem) => {
+            eprintln!(
+                "I couldn't unpack the archive {}: {:?}",
+                created_path.display(),
+                problem
+            );
+
+            return Ok(1);
+        }
+    }
+
+    for exposed_module in bundle.exposed_modules.iter() {
+        let arena = Bump::new();
+
+        match build::check_file(
+            &arena,
+            unpacked_dir.path().join(exposed_module),
+            false,
+            RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            Threading::AllAvailable,
+            ReportFormat::Text,
+        ) {
+            Ok((problems, _)) if problems.errors == 0 => {}
+            Ok((problems, _)) => {
+                eprintln!(
+                    "\n{} had {} when checked from the archive, so it wasn't ready to publish. The archive is at {}",
+                    exposed_module.display(),
+                    if problems.errors == 1 {
+                        "1 error".to_string()
+                    } else {
+                        format!("{} errors", problems.errors)
+                    },
+                    created_path.display()
+                );
+
+                return Ok(1);
+            }
+            Err(LoadingProblem::FormattedReport(report)) => {
+                print_formatted_report(ReportFormat::Text, &report, exposed_module);
+
+                return Ok(1);
+            }
+            Err(other) => {
+                eprintln!(
+                    "I couldn't check {} from the archive: {:?}",
+                    exposed_module.display(),
+                    other
+                );
+
+                return Ok(1);
+            }
+        }
+    }
+
+    let manifest = std::fs::read_to_string(unpacked_dir.path().join(MANIFEST_FILENAME))?;
+    let total_time_ms = start_time.elapsed().as_millis();
+
+    // The package module only needs to be named in the URL if it isn't the default one
+    let fragment = match path.file_name().and_then(|name| name.to_str()) {
+        Some(DEFAULT_ROC_FILENAME) | None => String::new(),
+        Some(name) => format!("#{name}"),
+    };
+
+    println!(
+        "Bundled {} files of \x1B[33m{}\x1B[39m into the following archive in {total_time_ms} ms:\n\n\t\x1B[33m{}\x1B[39m\n\nManifest:\n\n{manifest}\nTo publish the package, upload the archive to some URL and then add it as a dependency with:\n\n\t\x1B[32m\"https://your-url-goes-here/{}{fragment}\"\x1B[39m\n",
+        bundle.files.len(),
+        path.to_string_lossy(),
+        created_path.to_string_lossy(),
+        bundle.filename,
+    );
+
+    Ok(0)
+}
+
+/// `roc package vendor`
+pub fn vendor(matches: &ArgMatches) -> io::Result<i32> {
+    use roc_packaging::mirror::{Mirrors, ROC_PACKAGE_MIRROR};
+
+    let roc_file_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+    let dest_dir = Path::new(matches.value_of_os(FLAG_DIR).unwrap());
+    let mirrors = Mirrors::from_env()?;
+
+    match roc_packaging::vendor::vendor(roc_file_path, dest_dir, &mirrors) {
+        Ok(urls) => {
+            for url in urls.iter() {
+                println!("Vendored \u{001b}[36m{url}\u{001b}[0m");
+            }
+
+            println!(
+                "\n{} packages are in {}. To build with them instead of downloading anything, set:\n\n    {}=file://{}\n",
+                urls.len(),
+                dest_dir.display(),
+                ROC_PACKAGE_MIRROR,
+                dest_dir.canonicalize()?.display()
+            );
+
+            Ok(0)
+        }
+        Err(problem) => {
+            eprintln!(
+                "I couldn't vendor the packages of {}: {:?}",
+                roc_file_path.display(),
+                problem
+            );
+
+            Ok(1)
+        }
+    }
+}
+
+/// `roc cache list`, `roc cache verify` and `roc cache clean`
+pub fn manage_cache(matches: &ArgMatches) -> io::Result<i32> {
+    use roc_packaging::cache::{self, CleanLimits, Verification};
+    use std::time::{Duration, SystemTime};
+
+    let cache_dir = cache::roc_cache_dir();
+    let megabytes = |bytes: u64| format!("{:.1} MB", bytes as f64 / 1_000_000.0);
+
+    match matches.subcommand() {
+        Some((CMD_LIST, _)) => {
+            let packages = cache::list(&cache_dir)?;
+            let now = SystemTime::now();
+
+            for package in packages.iter() {
+                let last_used = match package.last_used {
+                    Some(time) => {
+                        let days = now.duration_since(time).unwrap_or_default().as_secs() / 86_400;
+
+                        match days {
+                            0 => "used today".to_string(),
+                            1 => "used 1 day ago".to_string(),
+                            _ => format!("used {days} days ago"),
+                        }
+                    }
+                    None => "partly installed".to_string(),
+                };
+
+                println!(
+                    "\u{001b}[36m{}\u{001b}[0m\n    {}, {}\n    {}",
+                    package.url.as_deref().unwrap_or("(unknown URL)"),
+                    megabytes(package.bytes),
+                    last_used,
+                    package.dir.display()
+                );
+            }
+
+            println!(
+                "\n{} packages, {} in total, are in {}",
+                packages.len(),
+                megabytes(packages.iter().map(|package| package.bytes).sum()),
+                cache_dir.display()
+            );
+
+            Ok(0)
+        }
+        Some((CMD_VERIFY, matches)) => {
+            let remove = matches.is_present(FLAG_REMOVE);
+            let mut failures = 0;
+
+            for package in cache::list(&cache_dir)? {
+                let problem = match cache::verify(&package)? {
+                    Verification::Intact => continue,
+                    Verification::Partial => {
+                        "has no record, so it was only partly installed or was installed by an older roc"
+                            .to_string()
+                    }
+                    Verification::Tampered {
+                        changed,
+                        missing,
+                        added,
+                    } => {
+                        let mut lines = Vec::new();
+
+                        lines.extend(changed.iter().map(|path| format!("changed: {path}")));
+                        lines.extend(missing.iter().map(|path| format!("missing: {path}")));
+                        lines.extend(added.iter().map(|path| format!("added:   {path}")));
+
+                        format!(
+                            "has files that changed since it was installed:\n        {}",
+                            lines.join("\n        ")
+                        )
+                    }
+                };
+
+                failures += 1;
+
+                eprintln!(
+                    "\u{001b}[31m{}\u{001b}[0m {}\n    {}",
+                    package.url.as_deref().unwrap_or("(unknown URL)"),
+                    package.dir.display(),
+                    problem
+                );
+
+                if remove {
+                    if cache::remove(&package)? {
+                        eprintln!("    Removed it, so the next build will download it again.");
+                    } else {
+                        eprintln!("    Another roc is using it right now, so it wasn't removed.");
+                    }
+                }
+            }
+
+            if failures == 0 {
+                println!("Every package in {} is intact.", cache_dir.display());
+
+                Ok(0)
+            } else {
+                if !remove {
+                    eprintln!("\nTo download these packages again, run `roc {CMD_CACHE} {CMD_VERIFY} --{FLAG_REMOVE}`.");
+                }
+
+                Ok(1)
+            }
+        }
+        Some((CMD_CLEAN, matches)) => {
+            let limits = CleanLimits {
+                older_than: matches
+                    .value_of_t::<u64>(FLAG_OLDER_THAN)
+                    .ok()
+                    .map(|days| Duration::from_secs(days * 86_400)),
+                max_bytes: matches
+                    .value_of_t::<u64>(FLAG_MAX_SIZE)
+                    .ok()
+                    .map(|megabytes| megabytes * 1_000_000),
+            };
+
+            let removed = cache::clean(&cache_dir, limits)?;
+
+            for package in removed.iter() {
+                println!(
+                    "Removed {}",
+                    package
+                        .url
+                        .as_deref()
+                        .unwrap_or_else(|| package.dir.to_str().unwrap_or("?"))
+                );
+            }
+
+            println!(
+                "\nRemoved {} packages, which freed {}.",
+                removed.len(),
+                megabytes(removed.iter().map(|package| package.bytes).sum())
+            );
+
+            if let Some(types_dir) = RocCacheDir::Persistent(&cache_dir).types_dir() {
+                let removed = cache::clean_types(&types_dir, limits)?;
+
+                if removed > 0 {
+                    println!("Removed {removed} cached module types.");
+                }
+            }
+
+            Ok(0)
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// The `--format` to report problems in. Commands without that flag always report text.
+pub fn report_format(matches: &ArgMatches) -> ReportFormat {
+    match matches.try_get_one::<String>(FLAG_FORMAT) {
+        Ok(Some(format)) if format == "json" => ReportFormat::Json,
+        Ok(Some(format)) if format == "sarif" => ReportFormat::Sarif,
+        _ => ReportFormat::Text,
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum BuildConfig {
     BuildOnly,
@@ -363,6 +814,8 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     use roc_gen_llvm::llvm::build::LlvmBackendMode;
     use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError};
     use roc_packaging::cache;
+    use roc_repl_expect::report::{json_report, junit_report};
+    use roc_repl_expect::run::ExpectOptions;
     use roc_target::TargetInfo;
 
     let start_time = Instant::now();
@@ -424,7 +877,12 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         render: roc_reporting::report::RenderTarget::ColorTerminal,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading,
-        exec_mode: ExecutionMode::Test,
+        exec_mode: if matches.is_present(FLAG_DOC) {
+            ExecutionMode::DocTest
+        } else {
+            ExecutionMode::Test
+        },
+        opt_level,
     };
     let load_result = roc_load::load_and_monomorphize(
         arena,
@@ -437,25 +895,39 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     let mut loaded = match load_result {
         Ok(loaded) => loaded,
         Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
-            return handle_loading_problem(problem);
+            return handle_loading_problem(problem, ReportFormat::Text, path);
         }
         Err(LoadMonomorphizedError::ErrorModule(module)) => {
-            return handle_error_module(module, start_time.elapsed(), filename, false);
+            return handle_error_module(
+                module,
+                start_time.elapsed(),
+                filename,
+                false,
+                ReportFormat::Text,
+            );
         }
     };
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded, ReportFormat::Text);
 
     let mut expectations = std::mem::take(&mut loaded.expectations);
 
     let interns = loaded.interns.clone();
 
-    let (lib, expects, layout_interner) = roc_repl_expect::run::expect_mono_module_to_dylib(
-        arena,
-        target.clone(),
-        loaded,
-        opt_level,
-        LlvmBackendMode::CliTest,
-    )
+    let (lib, expects, layout_interner) = match opt_level {
+        // `roc test --dev` uses the dev backend, like `roc build --dev` does
+        OptLevel::Development => {
+            roc_repl_expect::run::expect_mono_module_to_dylib_dev(arena, target.clone(), loaded)
+        }
+        OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
+            roc_repl_expect::run::expect_mono_module_to_dylib(
+                arena,
+                target.clone(),
+                loaded,
+                opt_level,
+                LlvmBackendMode::CliTest,
+            )
+        }
+    }
     .unwrap();
 
     // Print warnings before running tests.
@@ -476,7 +948,12 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
 
     let mut writer = std::io::stdout();
 
-    let (failed, passed) = roc_repl_expect::run::run_toplevel_expects(
+    let options = ExpectOptions {
+        filter: matches.value_of(FLAG_FILTER),
+        fail_fast: matches.is_present(FLAG_FAIL_FAST),
+    };
+
+    let summary = roc_repl_expect::run::run_toplevel_expects(
         &mut writer,
         roc_reporting::report::RenderTarget::ColorTerminal,
         arena,
@@ -485,14 +962,46 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         &lib,
         &mut expectations,
         expects,
+        options,
     )
     .unwrap();
 
     let total_time = start_time.elapsed();
 
+    if let Some(report_path) = matches.value_of_os(FLAG_REPORT) {
+        let report = match matches.value_of(FLAG_REPORT_FORMAT) {
+            Some("json") => json_report(&summary).to_string(),
+            _ => junit_report(&summary),
+        };
+
+        std::fs::write(report_path, report)?;
+    }
+
+    if matches.is_present(FLAG_TIME) {
+        for outcome in summary.outcomes.iter() {
+            println!(
+                "{:9.3} ms   {}:{}   {}",
+                outcome.duration.as_secs_f64() * 1000.0,
+                outcome.module_name,
+                outcome.line,
+                outcome.name,
+            );
+        }
+    }
+
+    let failed = summary.failed();
+    let passed = summary.passed();
+
     if failed == 0 && passed == 0 {
         // TODO print this in a more nicely formatted way!
-        println!("No expectations were found.");
+        if summary.filtered_out > 0 {
+            println!(
+                "No expectations matched the filter, out of {}.",
+                summary.filtered_out
+            );
+        } else {
+            println!("No expectations were found.");
+        }
 
         // If no tests ran, treat that as an error. This is perhaps
         // briefly annoying at the very beginning of a project when
@@ -507,11 +1016,21 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
             31 // red
         };
 
-        println!(
-            "\n\x1B[{failed_color}m{failed}\x1B[39m failed and \x1B[32m{passed}\x1B[39m passed in {} ms.\n",
+        print!(
+            "\n\x1B[{failed_color}m{failed}\x1B[39m failed and \x1B[32m{passed}\x1B[39m passed in {} ms",
             total_time.as_millis(),
         );
 
+        if summary.skipped > 0 {
+            print!(", skipping {} after the first failure", summary.skipped);
+        }
+
+        if summary.filtered_out > 0 {
+            print!(" ({} filtered out)", summary.filtered_out);
+        }
+
+        println!(".\n");
+
         Ok((failed > 0) as i32)
     }
 }
@@ -623,6 +1142,7 @@ pub fn build(
     };
     let emit_debug_info = matches.is_present(FLAG_DEBUG);
     let emit_timings = matches.is_present(FLAG_TIME);
+    let format = report_format(matches);
 
     let threading = match matches
         .value_of(FLAG_MAX_THREADS)
@@ -687,6 +1207,7 @@ pub fn build(
         wasm_dev_stack_bytes,
         roc_cache_dir,
         build_ordering,
+        format,
     );
 
     match res_binary_path {
@@ -709,8 +1230,13 @@ pub fn build(
                     // since the process is about to exit anyway.
                     // std::mem::forget(arena);
 
-                    print_problems(problems, total_time);
-                    println!(" while successfully building:\n\n    {generated_filename}");
+                    if format == ReportFormat::Text {
+                        print_problems(problems, total_time);
+                        println!(" while successfully building:\n\n    {generated_filename}");
+                    } else {
+                        // stdout is reserved for the problems document
+                        eprintln!("Successfully built:\n\n    {generated_filename}");
+                    }
 
                     // Return a nonzero exit code if there were problems
                     Ok(problems.exit_code())
@@ -756,9 +1282,11 @@ pub fn build(
             }
         }
         Err(BuildFileError::ErrorModule { module, total_time }) => {
-            handle_error_module(module, total_time, filename, true)
+            handle_error_module(module, total_time, filename, true, format)
+        }
+        Err(BuildFileError::LoadingProblem(problem)) => {
+            handle_loading_problem(problem, format, Path::new(filename))
         }
-        Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
     }
 }
 
@@ -767,10 +1295,15 @@ fn handle_error_module(
     total_time: std::time::Duration,
     filename: &OsStr,
     print_run_anyway_hint: bool,
+    format: ReportFormat,
 ) -> io::Result<i32> {
     debug_assert!(module.total_problems() > 0);
 
-    let problems = roc_build::program::report_problems_typechecked(&mut module);
+    let problems = roc_build::program::report_problems_typechecked(&mut module, format);
+
+    if format != ReportFormat::Text {
+        return Ok(problems.exit_code());
+    }
 
     print_problems(problems, total_time);
 
@@ -789,21 +1322,38 @@ fn handle_error_module(
     Ok(problems.exit_code())
 }
 
-fn handle_loading_problem(problem: LoadingProblem) -> io::Result<i32> {
+fn handle_loading_problem(
+    problem: LoadingProblem,
+    format: ReportFormat,
+    filename: &Path,
+) -> io::Result<i32> {
     match problem {
         LoadingProblem::FormattedReport(report) => {
-            print!("{}", report);
+            print_formatted_report(format, &report, filename);
             Ok(1)
         }
         _ => {
             // TODO: tighten up the types here, we should always end up with a
             // formatted report from load.
-            print!("Failed with error: {:?}", problem);
+            let report = format!("Failed with error: {:?}", problem);
+            print_formatted_report(format, &report, filename);
             Ok(1)
         }
     }
 }
 
+/// Prints a report the loader rendered by itself, like a parse error.
+pub fn print_formatted_report(format: ReportFormat, report: &str, filename: &Path) {
+    match format {
+        ReportFormat::Text => print!("{}", report),
+        ReportFormat::Json | ReportFormat::Sarif => {
+            let diagnostic = Diagnostic::from_formatted_report(report, filename);
+
+            println!("{}", diagnostics_document(format, &[diagnostic]));
+        }
+    }
+}
+
 fn print_problems(problems: Problems, total_time: std::time::Duration) {
     const GREEN: usize = 32;
     const YELLOW: usize = 33;
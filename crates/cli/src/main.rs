@@ -2,15 +2,17 @@
 use roc_build::link::LinkType;
 use roc_cli::build::check_file;
 use roc_cli::{
-    build_app, format, test, BuildConfig, FormatMode, Target, CMD_BUILD, CMD_CHECK, CMD_DEV,
-    CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_LIB, FLAG_NO_LINK, FLAG_TARGET, FLAG_TIME,
-    GLUE_FILE, ROC_FILE,
+    build_app, format, manage_cache, package_build, print_formatted_report, report_format, test,
+    vendor, watch, BuildConfig, FormatMode, Target, CMD_BUILD, CMD_CACHE, CMD_CHECK, CMD_DEV,
+    CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_LSP, CMD_PACKAGE, CMD_REPL,
+    CMD_RUN, CMD_TEST, CMD_VENDOR, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_LIB,
+    FLAG_NO_LINK, FLAG_TARGET, FLAG_TIME, FLAG_WATCH, GLUE_FILE, ROC_FILE,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
 use roc_load::{LoadingProblem, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_reporting::cli::ReportFormat;
 use std::fs::{self, FileType};
 use std::io;
 use std::path::{Path, PathBuf};
@@ -47,6 +49,9 @@ fn main() -> io::Result<()> {
                 Ok(0)
             }
         }
+        Some((CMD_RUN | CMD_CHECK | CMD_TEST, matches)) if matches.is_present(FLAG_WATCH) => {
+            watch(Path::new(matches.value_of_os(ROC_FILE).unwrap()))
+        }
         Some((CMD_RUN, matches)) => {
             if matches.is_present(ROC_FILE) {
                 build(
@@ -90,14 +95,20 @@ fn main() -> io::Result<()> {
             let input_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
             let output_path = Path::new(matches.value_of_os(GLUE_FILE).unwrap());
 
-            if Some("rs") == output_path.extension().and_then(OsStr::to_str) {
+            if roc_glue::load::Language::from_path(output_path).is_some() {
                 roc_glue::generate(input_path, output_path)
             } else {
-                eprintln!("Currently, `roc glue` only supports generating Rust glue files (with the .rs extension). In the future, the plan is to decouple `roc glue` from any particular output format, by having it accept a second .roc file which gets executed as a plugin to generate glue code for any desired language. However, this has not yet been implemented, and for now only .rs is supported.");
+                eprintln!("Currently, `roc glue` only supports generating Rust (.rs), C (.h) and Zig (.zig) glue files, so the output file needs one of those extensions. In the future, the plan is to decouple `roc glue` from any particular output format, by having it accept a second .roc file which gets executed as a plugin to generate glue code for any desired language. However, this has not yet been implemented.");
 
                 Ok(1)
             }
         }
+        Some((CMD_PACKAGE, matches)) => match matches.subcommand() {
+            Some((CMD_BUILD, matches)) => package_build(matches),
+            Some((CMD_VENDOR, matches)) => vendor(matches),
+            _ => unreachable!(),
+        },
+        Some((CMD_CACHE, matches)) => manage_cache(matches),
         Some((CMD_GEN_STUB_LIB, matches)) => {
             let input_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
             let target: Target = matches.value_of_t(FLAG_TARGET).unwrap_or_default();
@@ -131,6 +142,7 @@ fn main() -> io::Result<()> {
             let arena = bumpalo::Bump::new();
 
             let emit_timings = matches.is_present(FLAG_TIME);
+            let problems_format = report_format(matches);
             let filename = matches.value_of_os(ROC_FILE).unwrap();
             let roc_file_path = PathBuf::from(filename);
             let threading = match matches
@@ -149,7 +161,12 @@ fn main() -> io::Result<()> {
                 emit_timings,
                 RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
                 threading,
+                problems_format,
             ) {
+                Ok((problems, _)) if problems_format != ReportFormat::Text => {
+                    // The problems were already printed, as a document of their own
+                    Ok(problems.exit_code())
+                }
                 Ok((problems, total_time)) => {
                     println!(
                         "\x1B[{}m{}\x1B[39m {} and \x1B[{}m{}\x1B[39m {} found in {} ms.",
@@ -182,7 +199,7 @@ fn main() -> io::Result<()> {
                 }
 
                 Err(LoadingProblem::FormattedReport(report)) => {
-                    print!("{}", report);
+                    print_formatted_report(problems_format, &report, Path::new(filename));
 
                     Ok(1)
                 }
@@ -192,6 +209,7 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_REPL, _)) => Ok(roc_repl_cli::main()),
+        Some((CMD_LSP, _)) => Ok(roc_lang_srv::run()?),
         Some((CMD_EDIT, matches)) => {
             match matches
                 .values_of_os(DIRECTORY_OR_FILES)
@@ -0,0 +1,490 @@
+//! Re-run a `roc` command whenever one of the Roc files it depends on changes.
+use crate::FLAG_WATCH;
+use bumpalo::Bump;
+use roc_parse::ast::{Collection, ExtractSpaces, Header, Spaced};
+use roc_parse::header::{PackageEntry, To};
+use roc_parse::module::parse_header;
+use roc_parse::state::State;
+use roc_region::all::Loc;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, SystemTime};
+use std::{env, fs, io, thread};
+
+/// How often to look for changes. Polling keeps this portable,
+/// and a Roc project has few enough files that it's cheap.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Run this `roc` invocation again (without `--watch`) in a child process, and
+/// start it over whenever a .roc file changes in the directory of `roc_file_path`
+/// or in the directory of one of the local packages it depends on.
+///
+/// For `roc run`, the child process runs the app, so the previous run is
+/// killed before the next one starts. The run gets the terminal while it's going,
+/// so Ctrl-C stops the app and a second Ctrl-C stops watching.
+/// This only returns if something goes wrong.
+pub fn watch(roc_file_path: &Path) -> io::Result<i32> {
+    let exe = env::current_exe()?;
+    let args = args_without_flag(env::args_os().skip(1), FLAG_WATCH);
+
+    loop {
+        let snapshot = Snapshot::take(roc_file_path);
+        let mut run = Run::spawn(Command::new(&exe).args(&args))?;
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            if let Some(status) = run.try_wait()? {
+                println!(
+                    "\n\x1B[36m{}\x1B[39m",
+                    match status.code() {
+                        Some(code) => format!("Exited with code {code}. Waiting for changes…"),
+                        None => "Exited. Waiting for changes…".to_string(),
+                    }
+                );
+            }
+
+            if Snapshot::take(roc_file_path) != snapshot {
+                // Editors often write a file in several steps, so give them time to finish
+                thread::sleep(POLL_INTERVAL);
+
+                break;
+            }
+        }
+
+        run.kill()?;
+
+        println!(
+            "\n\x1B[36m{}\x1B[39m\nA file changed, so I'm starting over.\n",
+            "─".repeat(80)
+        );
+    }
+}
+
+/// One run of the command. On unix it gets a process group of its own, because
+/// `roc run` forks the app (see `roc_dev_native`): killing just the child
+/// would leave the app running, but killing the group gets it too.
+struct Run {
+    child: Child,
+    status: Option<ExitStatus>,
+}
+
+impl Run {
+    fn spawn(command: &mut Command) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            command.process_group(0);
+
+            // Runs in the child between fork and exec, after it has its own group
+            unsafe {
+                command.pre_exec(|| {
+                    give_terminal_to(libc::getpgrp());
+
+                    Ok(())
+                });
+            }
+        }
+
+        Ok(Run {
+            child: command.spawn()?,
+            status: None,
+        })
+    }
+
+    /// The exit status, the first time we see that the child has exited
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_some() {
+            return Ok(None);
+        }
+
+        self.status = self.child.try_wait()?;
+
+        if self.status.is_some() {
+            take_back_terminal();
+        }
+
+        Ok(self.status)
+    }
+
+    /// Kill everything in the run, including what's left after the child itself has exited.
+    fn kill(mut self) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            // The group's id is the child's pid. It may be gone already, which is fine.
+            unsafe { libc::killpg(self.child.id() as libc::pid_t, libc::SIGKILL) };
+        }
+
+        #[cfg(not(unix))]
+        {
+            // It may have exited in the meantime, in which case there's nothing to kill
+            let _ = self.child.kill();
+        }
+
+        if self.status.is_none() {
+            self.child.wait()?;
+            take_back_terminal();
+        }
+
+        Ok(())
+    }
+}
+
+/// Make `pgrp` the foreground process group of the terminal, if stdin is one,
+/// so it can read from the terminal and gets the signals for keys like Ctrl-C.
+#[cfg(unix)]
+fn give_terminal_to(pgrp: libc::pid_t) {
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) == 1 {
+            // Changing the foreground group from a background group sends SIGTTOU,
+            // which would stop us.
+            let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+
+            libc::tcsetpgrp(libc::STDIN_FILENO, pgrp);
+            libc::signal(libc::SIGTTOU, previous);
+        }
+    }
+}
+
+fn take_back_terminal() {
+    #[cfg(unix)]
+    give_terminal_to(unsafe { libc::getpgrp() });
+}
+
+/// Remove the first `--<flag>` that comes before `--`, which separates the arguments for the app.
+fn args_without_flag<I: Iterator<Item = OsString>>(args: I, flag: &str) -> Vec<OsString> {
+    let flag = OsString::from(format!("--{flag}"));
+    let mut removed = false;
+    let mut for_app = false;
+
+    args.filter(|arg| {
+        if for_app || removed {
+            true
+        } else if arg == "--" {
+            for_app = true;
+            true
+        } else if *arg == flag {
+            removed = true;
+            false
+        } else {
+            true
+        }
+    })
+    .collect()
+}
+
+/// The modification times of all the .roc files being watched
+#[derive(PartialEq, Eq)]
+struct Snapshot(BTreeMap<PathBuf, Option<SystemTime>>);
+
+impl Snapshot {
+    fn take(roc_file_path: &Path) -> Self {
+        let mut files = BTreeMap::new();
+
+        for dir in watched_dirs(roc_file_path) {
+            collect_roc_files(&dir, &mut files);
+        }
+
+        Snapshot(files)
+    }
+}
+
+/// Find the .roc files in `dir`, and in the subdirectories that can hold nested modules.
+/// A module like `Json.Decode` lives in `Json/Decode.roc`, so those are the directories
+/// named like a module; that way we don't walk through things like `target/` or `node_modules/`.
+fn collect_roc_files(dir: &Path, files: &mut BTreeMap<PathBuf, Option<SystemTime>>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // The directory may be in the middle of being renamed; try again next time.
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = path.file_name().and_then(OsStr::to_str).unwrap_or("");
+
+        if name.starts_with('.') {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                if is_module_name_part(name) {
+                    collect_roc_files(&path, files);
+                }
+            }
+            Ok(_) if path.extension() == Some(OsStr::new("roc")) => {
+                let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+
+                files.insert(path, modified);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_module_name_part(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().map_or(false, char::is_uppercase) && chars.all(char::is_alphanumeric)
+}
+
+/// The directory of the main module, plus the directories of the local packages
+/// named in its header. Packages downloaded from a URL can't change, so they aren't watched.
+fn watched_dirs(roc_file_path: &Path) -> Vec<PathBuf> {
+    let root_dir = match roc_file_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => canonicalize(dir),
+        _ => canonicalize(Path::new(".")),
+    };
+    let mut dirs = vec![root_dir.clone()];
+
+    let arena = Bump::new();
+    let src = match fs::read(roc_file_path) {
+        Ok(src) => arena.alloc_slice_copy(&src),
+        Err(_) => return dirs,
+    };
+
+    // While the header is being edited it may not parse; the directory of the
+    // main module is still watched, so we'll notice when it's fixed.
+    let package_names = match parse_header(&arena, State::new(src)) {
+        Ok((module, _)) => match module.header {
+            Header::App(header) => {
+                let mut names = header
+                    .packages
+                    .map(|packages| package_paths(packages.item))
+                    .unwrap_or_default();
+
+                if let To::NewPackage(name) = header.provides.to.value {
+                    names.push(name.as_str());
+                }
+
+                names
+            }
+            Header::Package(header) => package_paths(header.packages.item),
+            Header::Platform(header) => package_paths(header.packages.item),
+            Header::Interface(_) | Header::Hosted(_) => Vec::new(),
+        },
+        Err(_) => Vec::new(),
+    };
+
+    for name in package_names {
+        if roc_packaging::cache::is_package_url(name) || name.starts_with("http://") {
+            continue;
+        }
+
+        let package_path = root_dir.join(name);
+        let package_dir = if package_path.is_dir() {
+            canonicalize(&package_path)
+        } else {
+            match package_path.parent() {
+                Some(dir) => canonicalize(dir),
+                None => continue,
+            }
+        };
+
+        // A package inside another watched directory still needs its own entry, since only
+        // the subdirectories named like modules get walked.
+        if !dirs.contains(&package_dir) {
+            dirs.push(package_dir);
+        }
+    }
+
+    dirs
+}
+
+/// Resolve `..` and symlinks where possible, so overlapping directories can be told apart
+fn canonicalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn package_paths<'a>(packages: Collection<'a, Loc<Spaced<'a, PackageEntry<'a>>>>) -> Vec<&'a str> {
+    packages
+        .iter()
+        .map(|entry| {
+            entry
+                .value
+                .extract_spaces()
+                .item
+                .package_name
+                .value
+                .as_str()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_strings(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn removes_first_watch_flag() {
+        let args = os_strings(&["run", "--watch", "main.roc"]);
+
+        assert_eq!(
+            args_without_flag(args.into_iter(), FLAG_WATCH),
+            os_strings(&["run", "main.roc"])
+        );
+    }
+
+    #[test]
+    fn keeps_watch_flag_for_app() {
+        let args = os_strings(&["run", "main.roc", "--", "--watch"]);
+
+        assert_eq!(
+            args_without_flag(args.clone().into_iter(), FLAG_WATCH),
+            args
+        );
+
+        let args = os_strings(&["run", "--watch", "main.roc", "--", "--watch"]);
+
+        assert_eq!(
+            args_without_flag(args.into_iter(), FLAG_WATCH),
+            os_strings(&["run", "main.roc", "--", "--watch"])
+        );
+    }
+
+    #[test]
+    fn watches_local_packages_but_not_urls() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = canonicalize(tmp.path());
+
+        for dir in ["app", "platform", "lib"] {
+            fs::create_dir(root.join(dir)).unwrap();
+        }
+
+        fs::write(root.join("platform").join("main.roc"), "").unwrap();
+        fs::write(
+            root.join("app").join("main.roc"),
+            indoc::indoc!(
+                r#"
+                app "test"
+                    packages {
+                        pf: "../platform/main.roc",
+                        lib: "../lib",
+                        json: "https://example.com/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br",
+                    }
+                    imports []
+                    provides [main] to pf
+
+                main = ""
+                "#
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            watched_dirs(&root.join("app").join("main.roc")),
+            vec![root.join("app"), root.join("platform"), root.join("lib")]
+        );
+    }
+
+    #[test]
+    fn watches_package_inside_app_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = canonicalize(tmp.path());
+
+        fs::create_dir(root.join("platform")).unwrap();
+        fs::write(
+            root.join("main.roc"),
+            indoc::indoc!(
+                r#"
+                app "test"
+                    packages { pf: "platform/main.roc" }
+                    imports []
+                    provides [main] to pf
+
+                main = ""
+                "#
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            watched_dirs(&root.join("main.roc")),
+            vec![root.clone(), root.join("platform")]
+        );
+    }
+
+    #[test]
+    fn only_collects_roc_files_in_module_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = canonicalize(tmp.path());
+
+        for dir in ["Json", "target", ".git"] {
+            fs::create_dir(root.join(dir)).unwrap();
+        }
+
+        for file in [
+            "main.roc",
+            "README.md",
+            "Json/Decode.roc",
+            "target/Generated.roc",
+            ".git/Hidden.roc",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut files = BTreeMap::new();
+        collect_roc_files(&root, &mut files);
+
+        assert_eq!(
+            files.into_keys().collect::<Vec<_>>(),
+            vec![root.join("Json").join("Decode.roc"), root.join("main.roc")]
+        );
+    }
+
+    /// Whether the process is gone, or a zombie waiting for its new parent to reap it
+    #[cfg(target_os = "linux")]
+    fn is_dead(pid: &str) -> bool {
+        match fs::read_to_string(format!("/proc/{pid}/stat")) {
+            // The state comes right after the command name, which is in parentheses
+            Ok(stat) => stat[stat.rfind(')').unwrap()..].starts_with(") Z"),
+            Err(_) => true,
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn kill_stops_forked_processes() {
+        use std::io::{BufRead, BufReader};
+        use std::process::Stdio;
+        use std::time::Instant;
+
+        // Like `roc run`, this forks the process that does the actual work
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "sleep 100 & echo $!; wait"])
+            .stdout(Stdio::piped());
+
+        let mut run = Run::spawn(&mut command).unwrap();
+        let mut grandchild = String::new();
+
+        BufReader::new(run.child.stdout.take().unwrap())
+            .read_line(&mut grandchild)
+            .unwrap();
+
+        let grandchild = grandchild.trim().to_string();
+
+        assert!(!is_dead(&grandchild));
+
+        run.kill().unwrap();
+
+        let start = Instant::now();
+
+        while !is_dead(&grandchild) {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "process {grandchild} is still running"
+            );
+
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
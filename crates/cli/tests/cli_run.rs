@@ -10,8 +10,9 @@ extern crate roc_module;
 #[cfg(test)]
 mod cli_run {
     use cli_utils::helpers::{
-        extract_valgrind_errors, file_path_from_root, fixture_file, fixtures_dir, known_bad_file,
-        run_cmd, run_roc, run_with_valgrind, strip_colors, Out, ValgrindError, ValgrindErrorXWhat,
+        build_roc_bin_cached, extract_valgrind_errors, file_path_from_root, fixture_file,
+        fixtures_dir, known_bad_file, run_cmd, run_roc, run_with_valgrind, strip_colors, Out,
+        ValgrindError, ValgrindErrorXWhat,
     };
     use const_format::concatcp;
     use indoc::indoc;
@@ -54,6 +55,8 @@ mod cli_run {
     const PREBUILT_PLATFORM: &str = concatcp!("--", roc_cli::FLAG_PREBUILT, "=true");
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
+    #[allow(dead_code)]
+    const WATCH_FLAG: &str = concatcp!("--", roc_cli::FLAG_WATCH);
 
     #[derive(Debug)]
     enum CliMode {
@@ -1217,6 +1220,121 @@ mod cli_run {
         );
     }
 
+    /// The fields of /proc/<pid>/stat after the command name (state, parent pid, group, ...),
+    /// or None if the process is gone
+    #[cfg(target_os = "linux")]
+    fn proc_stat(pid: u32) -> Option<Vec<String>> {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+        // The command name is in parentheses, and may contain spaces
+        let after_name = &stat[stat.rfind(')')? + 1..];
+
+        Some(after_name.split_whitespace().map(str::to_string).collect())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn is_running(pid: u32) -> bool {
+        // A zombie is dead, just waiting for its parent to reap it
+        proc_stat(pid).map_or(false, |fields| fields[0] != "Z")
+    }
+
+    /// A running Roc app (`roc run` executes it from a memfd) below `ancestor` in the process tree
+    #[cfg(target_os = "linux")]
+    fn running_app_below(ancestor: u32, except: Option<u32>) -> Option<u32> {
+        let pids = std::fs::read_dir("/proc")
+            .unwrap()
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok());
+
+        let is_below = |mut pid: u32| {
+            while let Some(fields) = proc_stat(pid) {
+                pid = fields[1].parse().unwrap();
+
+                if pid == ancestor {
+                    return true;
+                }
+            }
+
+            false
+        };
+
+        let is_app = |pid: u32| {
+            std::fs::read_link(format!("/proc/{pid}/exe")).map_or(false, |exe| {
+                exe.to_string_lossy().contains("roc_file_descriptor")
+            })
+        };
+
+        pids.filter(|&pid| Some(pid) != except && is_app(pid) && is_running(pid))
+            .find(|&pid| is_below(pid))
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg(target_os = "linux")]
+    fn watch_stops_previous_run() {
+        use std::process::{Command, Stdio};
+        use std::time::{Duration, Instant};
+
+        let app_file = fixture_file("watch", "Main.roc");
+        let src = std::fs::read(&app_file).unwrap();
+
+        let mut watcher = Command::new(build_roc_bin_cached())
+            .args([CMD_RUN, WATCH_FLAG, app_file.to_str().unwrap()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let wait_for_app = |except: Option<u32>| {
+            let start = Instant::now();
+
+            loop {
+                if let Some(app) = running_app_below(watcher.id(), except) {
+                    return app;
+                }
+
+                assert!(
+                    start.elapsed() < Duration::from_secs(300),
+                    "the app never started"
+                );
+
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        };
+
+        let first_app = wait_for_app(None);
+
+        // Writing the same contents is enough to change the modification time
+        std::fs::write(&app_file, &src).unwrap();
+
+        let second_app = wait_for_app(Some(first_app));
+        let first_app_still_running = is_running(first_app);
+
+        // Clean up before asserting. The run has a process group of its own,
+        // so it doesn't go away with the watcher.
+        let run_group = proc_stat(second_app).unwrap()[2].clone();
+
+        watcher.kill().unwrap();
+        watcher.wait().unwrap();
+
+        Command::new("kill")
+            .args(["-KILL", "--", &format!("-{run_group}")])
+            .status()
+            .unwrap();
+
+        if first_app_still_running {
+            Command::new("kill")
+                .args(["-KILL", &first_app.to_string()])
+                .status()
+                .unwrap();
+        }
+
+        assert!(
+            !first_app_still_running,
+            "the app from before the change is still running"
+        );
+    }
+
     #[test]
     #[serial(multi_dep_thunk)]
     #[cfg_attr(windows, ignore)]
@@ -1365,6 +1483,40 @@ mod cli_run {
         );
     }
 
+    #[test]
+    fn unused_import_as_json() {
+        let out = run_roc(
+            [
+                CMD_CHECK,
+                known_bad_file("UnusedImport.roc").to_str().unwrap(),
+                "--format",
+                "json",
+            ],
+            &[],
+            &[],
+        );
+
+        let document: serde_json::Value = serde_json::from_str(out.stdout.trim()).unwrap();
+        assert_eq!(document["errors"], 0);
+        assert_eq!(document["warnings"], 1);
+
+        let diagnostic = &document["diagnostics"][0];
+        assert_eq!(diagnostic["title"], "UNUSED IMPORT");
+        assert_eq!(diagnostic["severity"], "warning");
+        assert!(diagnostic["file"]
+            .as_str()
+            .unwrap()
+            .replace('\\', "/")
+            .ends_with("tests/known_bad/UnusedImport.roc"));
+        assert_eq!(
+            (&diagnostic["start"], &diagnostic["end"]),
+            (
+                &serde_json::json!({ "line": 3, "column": 14 }),
+                &serde_json::json!({ "line": 3, "column": 30 })
+            )
+        );
+    }
+
     #[test]
     fn unknown_generates_with() {
         check_compile_error(
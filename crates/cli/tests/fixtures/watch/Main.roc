@@ -0,0 +1,11 @@
+app "watch"
+    packages { pf: "../multi-dep-str/platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+# Never finishes, so the app is still running when the watcher starts over
+spin : U64 -> Str
+spin = \n -> spin (Num.addWrap n 1)
+
+main : Str
+main = spin 0
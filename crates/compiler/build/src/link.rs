@@ -1524,6 +1524,47 @@ pub fn llvm_module_to_dylib(
     unsafe { Library::new(path) }
 }
 
+/// Links an object file produced by the dev backend into a dylib, together with the builtins,
+/// which the dev backend does not include in its output.
+pub fn dev_object_to_dylib(object_bytes: &[u8], target: &Triple) -> Result<Library, Error> {
+    let dir = tempfile::tempdir().unwrap();
+    let app_o_file = dir.path().join("app.o");
+
+    std::fs::write(&app_o_file, object_bytes).expect("Writing .o file failed");
+
+    let builtins_host_tempfile =
+        bitcode::host_unix_tempfile().expect("failed to write host builtins object to tempfile");
+
+    // Link app.o into a dylib - e.g. app.so or app.dylib
+    let (mut child, dylib_path) = link(
+        target,
+        app_o_file.clone(),
+        &[
+            app_o_file.to_str().unwrap(),
+            builtins_host_tempfile.path().to_str().unwrap(),
+        ],
+        LinkType::Dylib,
+    )
+    .unwrap();
+
+    let exit_status = child.wait().unwrap();
+
+    assert!(
+        exit_status.success(),
+        "\n___________\nLinking command failed with status {:?}:\n\n  {:?}\n___________\n",
+        exit_status,
+        child
+    );
+
+    // Extend the lifetime of the tempfile so it doesn't get dropped
+    // (and thus deleted) before the linking process is done using it!
+    drop(builtins_host_tempfile);
+
+    let path = dylib_path.as_path().to_str().unwrap();
+
+    unsafe { Library::new(path) }
+}
+
 pub fn preprocess_host_wasm32(host_input_path: &Path, preprocessed_host_path: &Path) {
     let host_input = host_input_path.to_str().unwrap();
     let output_file = preprocessed_host_path.to_str().unwrap();
@@ -4,7 +4,7 @@ use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{EntryPoint, ExpectMetadata, LoadedModule, MonomorphizedModule};
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
-use roc_reporting::cli::{report_problems, Problems};
+use roc_reporting::cli::{report_problems, Problems, ReportFormat};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
@@ -17,8 +17,12 @@ pub struct CodeGenTiming {
     pub code_gen: Duration,
 }
 
-pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Problems {
+pub fn report_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    format: ReportFormat,
+) -> Problems {
     report_problems(
+        format,
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
@@ -27,8 +31,9 @@ pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Proble
     )
 }
 
-pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
+pub fn report_problems_typechecked(loaded: &mut LoadedModule, format: ReportFormat) -> Problems {
     report_problems(
+        format,
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
@@ -86,6 +91,7 @@ pub fn gen_from_mono_module<'a>(
             target,
             preprocessed_host_path,
             wasm_dev_stack_bytes,
+            code_gen_options.opt_level,
         ),
         CodeGenBackend::Llvm => {
             gen_from_mono_module_llvm(arena, loaded, roc_file_path, target, code_gen_options)
@@ -423,6 +429,7 @@ fn gen_from_mono_module_dev<'a>(
     target: &target_lexicon::Triple,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    opt_level: OptLevel,
 ) -> GenFromMono<'a> {
     use target_lexicon::Architecture;
 
@@ -432,9 +439,10 @@ fn gen_from_mono_module_dev<'a>(
             loaded,
             preprocessed_host_path,
             wasm_dev_stack_bytes,
+            opt_level,
         ),
         Architecture::X86_64 | Architecture::Aarch64(_) => {
-            gen_from_mono_module_dev_assembly(arena, loaded, target)
+            gen_from_mono_module_dev_assembly(arena, loaded, target, opt_level)
         }
         _ => todo!(),
     }
@@ -447,12 +455,13 @@ pub fn gen_from_mono_module_dev<'a>(
     target: &target_lexicon::Triple,
     _host_input_path: &Path,
     _wasm_dev_stack_bytes: Option<u32>,
+    opt_level: OptLevel,
 ) -> GenFromMono<'a> {
     use target_lexicon::Architecture;
 
     match target.architecture {
         Architecture::X86_64 | Architecture::Aarch64(_) => {
-            gen_from_mono_module_dev_assembly(arena, loaded, target)
+            gen_from_mono_module_dev_assembly(arena, loaded, target, opt_level)
         }
         _ => todo!(),
     }
@@ -464,6 +473,7 @@ fn gen_from_mono_module_dev_wasm32<'a>(
     loaded: MonomorphizedModule<'a>,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    opt_level: OptLevel,
 ) -> GenFromMono<'a> {
     let code_gen_start = Instant::now();
     let MonomorphizedModule {
@@ -481,14 +491,6 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         .copied()
         .collect::<MutSet<_>>();
 
-    let env = roc_gen_wasm::Env {
-        arena,
-        layout_interner: &layout_interner,
-        module_id,
-        exposed_to_host,
-        stack_bytes: wasm_dev_stack_bytes.unwrap_or(roc_gen_wasm::Env::DEFAULT_STACK_BYTES),
-    };
-
     let host_bytes = std::fs::read(preprocessed_host_path).unwrap_or_else(|_| {
         internal_error!(
             "Failed to read host object file {}! Try setting --prebuilt-platform=false",
@@ -505,6 +507,17 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         )
     });
 
+    let env = roc_gen_wasm::Env {
+        arena,
+        layout_interner: &layout_interner,
+        module_id,
+        exposed_to_host,
+        stack_bytes: wasm_dev_stack_bytes.unwrap_or(roc_gen_wasm::Env::DEFAULT_STACK_BYTES),
+        runs_expects: matches!(opt_level, OptLevel::Development),
+        // If we can't scan the host's code, just avoid bulk memory instructions
+        bulk_memory: host_module.code.uses_bulk_memory().unwrap_or(false),
+    };
+
     let final_binary_bytes =
         roc_gen_wasm::build_app_binary(&env, &mut interns, host_module, procedures);
 
@@ -525,11 +538,18 @@ fn gen_from_mono_module_dev_assembly<'a>(
     arena: &'a bumpalo::Bump,
     loaded: MonomorphizedModule<'a>,
     target: &target_lexicon::Triple,
+    opt_level: OptLevel,
 ) -> GenFromMono<'a> {
     let code_gen_start = Instant::now();
 
     let lazy_literals = true;
     let generate_allocators = false; // provided by the platform
+    let mode = match opt_level {
+        OptLevel::Development => roc_gen_dev::AssemblyBackendMode::BinaryDev,
+        OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
+            roc_gen_dev::AssemblyBackendMode::Binary
+        }
+    };
 
     let MonomorphizedModule {
         module_id,
@@ -547,6 +567,7 @@ fn gen_from_mono_module_dev_assembly<'a>(
         exposed_to_host: exposed_to_host.values.keys().copied().collect(),
         lazy_literals,
         generate_allocators,
+        mode,
     };
 
     let module_object = roc_gen_dev::build_module(&env, &mut interns, target, procedures);
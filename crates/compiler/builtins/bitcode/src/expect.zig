@@ -2,6 +2,9 @@ const std = @import("std");
 const builtin = @import("builtin");
 
 const Atomic = std.atomic.Atomic;
+const str = @import("str.zig");
+const RocStr = str.RocStr;
+const RocList = @import("list.zig").RocList;
 
 const O_RDWR: c_int = 2;
 const O_CREAT: c_int = 64;
@@ -100,3 +103,186 @@ pub fn notifyParentExpect(shared_buffer: [*]u8) callconv(.C) void {
 pub fn notifyParentDbg(shared_buffer: [*]u8) callconv(.C) void {
     notifyParent(shared_buffer, 2);
 }
+
+// The functions below let a backend write an expect frame with plain function calls,
+// instead of emitting the loads and stores itself. The dev backend uses them.
+// See `clone_to_shared_memory` in gen_llvm for the shape of a frame.
+
+// region start (u32), region end (u32), module id (u32)
+const FRAME_HEADER_SIZE: usize = 3 * @sizeOf(u32);
+
+// the offset of the value (usize), its specialized variable (u32)
+const FRAME_LOOKUP_SIZE: usize = @sizeOf(usize) + @sizeOf(u32);
+
+fn sharedBufferState(shared_buffer: [*]u8) [*]usize {
+    return @ptrCast([*]usize, @alignCast(@alignOf(usize), shared_buffer));
+}
+
+fn writeUnaligned(comptime T: type, shared_buffer: [*]u8, offset: usize, value: T) void {
+    @memcpy(shared_buffer + offset, @ptrCast([*]const u8, &value), @sizeOf(T));
+}
+
+/// Writes the header of a new frame, and reserves space for its lookups.
+/// The region is passed as its start offset in the low 32 bits and its end offset in the high 32 bits.
+/// Returns the offset at which the value of the first lookup should be written.
+pub fn expectFrameStart(shared_buffer: [*]u8, region: u64, module_id: u32, lookup_count: usize) callconv(.C) usize {
+    const offset = sharedBufferState(shared_buffer)[1];
+
+    writeUnaligned(u64, shared_buffer, offset, region);
+    writeUnaligned(u32, shared_buffer, offset + @sizeOf(u64), module_id);
+
+    return offset + FRAME_HEADER_SIZE + lookup_count * FRAME_LOOKUP_SIZE;
+}
+
+/// Records where the value of a lookup of the current frame was written, and its specialized variable.
+pub fn expectFrameLookup(shared_buffer: [*]u8, index: usize, value_offset: usize, variable: u32) callconv(.C) void {
+    const offset = sharedBufferState(shared_buffer)[1] + FRAME_HEADER_SIZE + index * FRAME_LOOKUP_SIZE;
+
+    writeUnaligned(usize, shared_buffer, offset, value_offset);
+    writeUnaligned(u32, shared_buffer, offset + @sizeOf(usize), variable);
+}
+
+/// Copies a value that does not point to the heap into the current frame.
+/// Returns the offset just after the value.
+pub fn expectFrameCopy(shared_buffer: [*]u8, offset: usize, value: [*]const u8, size: usize) callconv(.C) usize {
+    @memcpy(shared_buffer + offset, value, size);
+
+    return offset + size;
+}
+
+// How `expectFrameClone` copies a value, as a sequence of words. Nested shapes are preceded by
+// their length in words, so they can be skipped over.
+//
+// SHAPE_COPY size                                   plain bytes, without any pointers
+// SHAPE_SKIP                                        nothing gets written, e.g. for functions
+// SHAPE_STR                                         a Str
+// SHAPE_LIST element_width shape_len shape          a List
+// SHAPE_STRUCT field_count (field_offset shape_len shape)*
+// SHAPE_UNION size tag_id_offset tag_id_size tag_count (shape_len shape)*
+//                                                   a non-recursive tag union, with a shape per tag
+// SHAPE_BOX inner_size shape_len shape              a Box
+const SHAPE_COPY: u64 = 0;
+const SHAPE_SKIP: u64 = 1;
+const SHAPE_STR: u64 = 2;
+const SHAPE_LIST: u64 = 3;
+const SHAPE_STRUCT: u64 = 4;
+const SHAPE_UNION: u64 = 5;
+const SHAPE_BOX: u64 = 6;
+
+/// Copies a value that may point to the heap into the current frame. Whatever it points to goes
+/// right after the value, with the pointers replaced by offsets into the frame.
+/// Returns the offset just after everything that was written.
+pub fn expectFrameClone(shared_buffer: [*]u8, offset: usize, value: [*]const u8, size: usize, shape: [*]const u64, shape_len: usize) callconv(.C) usize {
+    return cloneShape(shared_buffer, offset, offset + size, value, shape[0..shape_len]);
+}
+
+fn cloneShape(shared_buffer: [*]u8, offset: usize, extra_offset: usize, value: [*]const u8, shape: []const u64) usize {
+    switch (shape[0]) {
+        SHAPE_COPY => {
+            @memcpy(shared_buffer + offset, value, @intCast(usize, shape[1]));
+
+            return extra_offset;
+        },
+        SHAPE_SKIP => return extra_offset,
+        SHAPE_STR => {
+            const string = @ptrCast(*const RocStr, @alignCast(@alignOf(RocStr), value)).*;
+
+            return str.strCloneTo(string, shared_buffer, offset, extra_offset);
+        },
+        SHAPE_LIST => {
+            const list = @ptrCast(*const RocList, @alignCast(@alignOf(RocList), value)).*;
+            const element_width = @intCast(usize, shape[1]);
+            const element_shape = shape[3..][0..@intCast(usize, shape[2])];
+
+            // we only copy the elements we actually have (and skip extra capacity)
+            const relative = [3]usize{ extra_offset, list.len(), list.len() };
+            @memcpy(shared_buffer + offset, @ptrCast([*]const u8, &relative), @sizeOf([3]usize));
+
+            // Whatever the elements point to goes after all of the elements
+            var rest_offset = extra_offset + list.len() * element_width;
+
+            if (list.bytes) |elements| {
+                var i: usize = 0;
+                while (i < list.len()) : (i += 1) {
+                    const element_offset = extra_offset + i * element_width;
+                    rest_offset = cloneShape(shared_buffer, element_offset, rest_offset, elements + i * element_width, element_shape);
+                }
+            }
+
+            return rest_offset;
+        },
+        SHAPE_STRUCT => {
+            const field_count = @intCast(usize, shape[1]);
+            var rest_offset = extra_offset;
+            var index: usize = 2;
+
+            var i: usize = 0;
+            while (i < field_count) : (i += 1) {
+                const field_offset = @intCast(usize, shape[index]);
+                const field_shape = shape[index + 2 ..][0..@intCast(usize, shape[index + 1])];
+                rest_offset = cloneShape(shared_buffer, offset + field_offset, rest_offset, value + field_offset, field_shape);
+                index += 2 + field_shape.len;
+            }
+
+            return rest_offset;
+        },
+        SHAPE_UNION => {
+            const size = @intCast(usize, shape[1]);
+            const tag_id_offset = @intCast(usize, shape[2]);
+            const tag_id_size = @intCast(usize, shape[3]);
+
+            // The tag id gets copied along with the payload
+            @memcpy(shared_buffer + offset, value, size);
+
+            var tag_id: usize = 0;
+            var i: usize = 0;
+            while (i < tag_id_size) : (i += 1) {
+                tag_id |= @intCast(usize, value[tag_id_offset + i]) << @intCast(u6, 8 * i);
+            }
+
+            // Skip over the shapes of the tags before this one
+            var index: usize = 5;
+            i = 0;
+            while (i < tag_id) : (i += 1) {
+                index += 1 + @intCast(usize, shape[index]);
+            }
+            const payload_shape = shape[index + 1 ..][0..@intCast(usize, shape[index])];
+
+            return cloneShape(shared_buffer, offset, extra_offset, value, payload_shape);
+        },
+        SHAPE_BOX => {
+            const inner_size = @intCast(usize, shape[1]);
+            const inner_shape = shape[3..][0..@intCast(usize, shape[2])];
+
+            var inner: [*]const u8 = undefined;
+            @memcpy(@ptrCast([*]u8, &inner), value, @sizeOf(usize));
+
+            // The boxed value goes right after the box, and whatever it points to after that
+            writeUnaligned(usize, shared_buffer, offset, extra_offset);
+
+            return cloneShape(shared_buffer, extra_offset, extra_offset + inner_size, inner, inner_shape);
+        },
+        else => unreachable,
+    }
+}
+
+/// Completes the current frame, which ends at the given offset.
+pub fn expectFrameFinish(shared_buffer: [*]u8, offset: usize) callconv(.C) void {
+    const state = sharedBufferState(shared_buffer);
+
+    state[0] += 1;
+    state[1] = offset;
+}
+
+/// A roc_panic for test runners that cannot catch a crash: report it, and stop.
+pub fn expectPanic(c_ptr: *anyopaque, tag_id: u32) callconv(.C) noreturn {
+    const message = @ptrCast(*RocStr, @alignCast(@alignOf(RocStr), c_ptr));
+    const stderr = std.io.getStdErr().writer();
+
+    switch (tag_id) {
+        0 => stderr.print("Roc crashed with:\n\n    {s}\n", .{message.asSlice()}) catch unreachable,
+        else => stderr.print("The program crashed with:\n\n    {s}\n", .{message.asSlice()}) catch unreachable,
+    }
+
+    std.process.exit(1);
+}
@@ -174,6 +174,12 @@ comptime {
         exportUtilsFn(expect.expectFailedStartSharedFile, "expect_failed_start_shared_file");
         exportUtilsFn(expect.notifyParentExpect, "notify_parent_expect");
         exportUtilsFn(expect.notifyParentDbg, "notify_parent_dbg");
+        exportUtilsFn(expect.expectFrameStart, "expect_frame_start");
+        exportUtilsFn(expect.expectFrameLookup, "expect_frame_lookup");
+        exportUtilsFn(expect.expectFrameCopy, "expect_frame_copy");
+        exportUtilsFn(expect.expectFrameClone, "expect_frame_clone");
+        exportUtilsFn(expect.expectFrameFinish, "expect_frame_finish");
+        exportUtilsFn(expect.expectPanic, "expect_panic");
 
         // sets the buffer used for expect failures
         @export(expect.setSharedBuffer, .{ .name = "set_shared_buffer", .linkage = .Weak });
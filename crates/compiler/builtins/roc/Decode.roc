@@ -23,6 +23,7 @@ interface Decode
         string,
         list,
         record,
+        tag,
         custom,
         decodeWith,
         fromBytesPartial,
@@ -46,6 +47,7 @@ interface Decode
             F32,
             F64,
             Dec,
+            Nat,
         },
         Bool.{ Bool },
     ]
@@ -77,6 +79,7 @@ DecoderFormatting has
     string : Decoder Str fmt | fmt has DecoderFormatting
     list : Decoder elem fmt -> Decoder (List elem) fmt | fmt has DecoderFormatting
     record : state, (state, Str -> [Keep (Decoder state fmt), Skip]), (state -> Result val DecodeError) -> Decoder val fmt | fmt has DecoderFormatting
+    tag : (Str -> [Keep state, Skip]), (state, Nat -> [Keep (Decoder state fmt), Skip]), (state -> Result val DecodeError) -> Decoder val fmt | fmt has DecoderFormatting
 
 custom : (List U8, fmt -> DecodeResult val) -> Decoder val fmt | fmt has DecoderFormatting
 custom = \decode -> @Decoder decode
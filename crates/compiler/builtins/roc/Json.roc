@@ -76,6 +76,7 @@ Json := {} has [
              string: decodeString,
              list: decodeList,
              record: decodeRecord,
+             tag: decodeTag,
          },
      ]
 
@@ -414,6 +415,12 @@ openBrace = \bytes -> parseExactChar bytes '{'
 closingBrace : List U8 -> DecodeResult {}
 closingBrace = \bytes -> parseExactChar bytes '}'
 
+openBracket : List U8 -> DecodeResult {}
+openBracket = \bytes -> parseExactChar bytes '['
+
+closingBracket : List U8 -> DecodeResult {}
+closingBracket = \bytes -> parseExactChar bytes ']'
+
 recordKey : List U8 -> DecodeResult Str
 recordKey = \bytes -> jsonString bytes
 
@@ -463,3 +470,47 @@ decodeRecord = \initialState, stepField, finalizer -> Decode.custom \bytes, @Jso
         when finalizer endStateResult is
             Ok val -> { result: Ok val, rest: afterRecordBytes }
             Err e -> { result: Err e, rest: afterRecordBytes }
+
+decodeTag = \stepTag, stepElem, finalizer -> Decode.custom \bytes, @Json {} ->
+        # Decodes `A v1 v2` from `{"A":[v1,v2]}`, mirroring `encodeTag`.
+        # NB: the stepper function must be passed explicitly until #2894 is resolved.
+        decodeElems = \stepper, state, index, elemBytes ->
+            { val: newState, rest: beforeCommaOrBreak } <- tryDecode
+                    (
+                        when stepper state index is
+                            Skip ->
+                                { result: Err TooShort, rest: elemBytes }
+
+                            Keep decoder ->
+                                Decode.decodeWith elemBytes decoder (@Json {})
+                    )
+
+            { result: commaResult, rest: nextBytes } = comma beforeCommaOrBreak
+
+            when commaResult is
+                Ok {} -> decodeElems stepElem newState (index + 1) nextBytes
+                Err _ -> { result: Ok newState, rest: nextBytes }
+
+        { rest: afterBraceBytes } <- bytes |> openBrace |> tryDecode
+        { val: tagName, rest: afterTagNameBytes } <- recordKey afterBraceBytes |> tryDecode
+        { rest: afterColonBytes } <- colon afterTagNameBytes |> tryDecode
+        { rest: afterBracketBytes } <- openBracket afterColonBytes |> tryDecode
+
+        when stepTag tagName is
+            Skip ->
+                { result: Err TooShort, rest: afterBracketBytes }
+
+            Keep initialState ->
+                { val: endState, rest: beforeClosingBracketBytes } <- tryDecode
+                        (
+                            when List.first afterBracketBytes is
+                                Ok ']' -> { result: Ok initialState, rest: afterBracketBytes }
+                                _ -> decodeElems stepElem initialState 0 afterBracketBytes
+                        )
+
+                { rest: afterPayloadBytes } <- beforeClosingBracketBytes |> closingBracket |> tryDecode
+                { rest: afterTagBytes } <- afterPayloadBytes |> closingBrace |> tryDecode
+
+                when finalizer endState is
+                    Ok val -> { result: Ok val, rest: afterTagBytes }
+                    Err e -> { result: Err e, rest: afterTagBytes }
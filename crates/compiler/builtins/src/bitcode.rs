@@ -423,6 +423,12 @@ pub const UTILS_EXPECT_FAILED_START_SHARED_FILE: &str =
 pub const UTILS_EXPECT_READ_ENV_SHARED_BUFFER: &str = "roc_builtins.utils.read_env_shared_buffer";
 pub const NOTIFY_PARENT_EXPECT: &str = "roc_builtins.utils.notify_parent_expect";
 pub const NOTIFY_PARENT_DBG: &str = "roc_builtins.utils.notify_parent_dbg";
+pub const UTILS_EXPECT_FRAME_START: &str = "roc_builtins.utils.expect_frame_start";
+pub const UTILS_EXPECT_FRAME_LOOKUP: &str = "roc_builtins.utils.expect_frame_lookup";
+pub const UTILS_EXPECT_FRAME_COPY: &str = "roc_builtins.utils.expect_frame_copy";
+pub const UTILS_EXPECT_FRAME_CLONE: &str = "roc_builtins.utils.expect_frame_clone";
+pub const UTILS_EXPECT_FRAME_FINISH: &str = "roc_builtins.utils.expect_frame_finish";
+pub const UTILS_EXPECT_PANIC: &str = "roc_builtins.utils.expect_panic";
 
 pub const UTILS_LONGJMP: &str = "longjmp";
 pub const UTILS_SETJMP: &str = "setjmp";
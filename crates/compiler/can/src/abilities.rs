@@ -90,7 +90,7 @@ impl AbilityMemberData<Resolved> {
 pub type SpecializationLambdaSets = VecMap<u8, Variable>;
 
 /// A particular specialization of an ability member.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MemberSpecializationInfo<Phase: ResolvePhase> {
     _phase: std::marker::PhantomData<Phase>,
     pub symbol: Symbol,
@@ -122,6 +122,15 @@ pub struct ImplKey {
     pub ability_member: Symbol,
 }
 
+impl ImplKey {
+    fn remap_symbols(self, f: &mut impl FnMut(Symbol) -> Symbol) -> Self {
+        Self {
+            opaque: f(self.opaque),
+            ability_member: f(self.ability_member),
+        }
+    }
+}
+
 /// Fully-resolved implementation of an ability member for an opaque type.
 /// This is only fully known after type solving of the owning module.
 #[derive(Clone, Debug)]
@@ -485,6 +494,76 @@ impl IAbilitiesStore<Resolved> {
     pub fn deserialize(bytes: &[u8]) -> (Self, usize) {
         serialize::deserialize(bytes)
     }
+
+    /// Rewrites every [Symbol] in the store; see [roc_types::subs::Subs::remap_symbols].
+    pub fn remap_symbols(&mut self, f: &mut impl FnMut(Symbol) -> Symbol) {
+        let Self {
+            members_of_ability,
+            specialization_to_root,
+            ability_members,
+            declared_implementations,
+            specializations,
+            next_specialization_id: _,
+            resolved_specializations,
+        } = self;
+
+        *members_of_ability = std::mem::take(members_of_ability)
+            .into_iter()
+            .map(|(ability, members)| (f(ability), members.into_iter().map(&mut *f).collect()))
+            .collect();
+
+        *specialization_to_root = std::mem::take(specialization_to_root)
+            .into_iter()
+            .map(|(specialization, impl_key)| (f(specialization), impl_key.remap_symbols(f)))
+            .collect();
+
+        *ability_members = std::mem::take(ability_members)
+            .into_iter()
+            .map(|(member, mut data)| {
+                data.parent_ability = f(data.parent_ability);
+                (f(member), data)
+            })
+            .collect();
+
+        *declared_implementations = std::mem::take(declared_implementations)
+            .into_iter()
+            .map(|(impl_key, member_impl)| {
+                let member_impl = match member_impl {
+                    MemberImpl::Impl(symbol) => MemberImpl::Impl(f(symbol)),
+                    MemberImpl::Error => MemberImpl::Error,
+                };
+                (impl_key.remap_symbols(f), member_impl)
+            })
+            .collect();
+
+        *specializations = std::mem::take(specializations)
+            .into_iter()
+            .map(|(symbol, mut info)| {
+                info.symbol = f(info.symbol);
+                (f(symbol), info)
+            })
+            .collect();
+
+        for specialization in resolved_specializations.values_mut() {
+            *specialization = f(*specialization);
+        }
+    }
+}
+
+/// Rewrites every [Symbol] in resolved implementations; see [AbilitiesStore::remap_symbols].
+pub fn remap_solved_implementations(
+    solved_implementations: &mut ResolvedImplementations,
+    f: &mut impl FnMut(Symbol) -> Symbol,
+) {
+    *solved_implementations = std::mem::take(solved_implementations)
+        .into_iter()
+        .map(|(impl_key, mut resolved_impl)| {
+            if let ResolvedImpl::Impl(specialization) = &mut resolved_impl {
+                specialization.symbol = f(specialization.symbol);
+            }
+            (impl_key.remap_symbols(f), resolved_impl)
+        })
+        .collect();
 }
 
 pub use serialize::deserialize_solved_implementations;
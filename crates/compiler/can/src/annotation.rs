@@ -2,6 +2,7 @@ use crate::env::Env;
 use crate::procedure::References;
 use crate::scope::{PendingAbilitiesInScope, Scope};
 use roc_collections::{ImMap, MutSet, SendMap, VecMap, VecSet};
+use roc_error_macros::internal_error;
 use roc_module::ident::{Ident, Lowercase, TagName};
 use roc_module::symbol::Symbol;
 use roc_parse::ast::{AssignedField, ExtractSpaces, Pattern, Tag, TypeAnnotation, TypeHeader};
@@ -448,8 +449,14 @@ pub fn find_type_def_symbols(
             As(actual, _, _) => {
                 stack.push(&actual.value);
             }
-            Tuple { fields: _, ext: _ } => {
-                todo!("find_type_def_symbols: Tuple");
+            Tuple { fields, ext } => {
+                for t in fields.items.iter() {
+                    stack.push(&t.value);
+                }
+
+                for t in ext.iter() {
+                    stack.push(&t.value);
+                }
             }
             Record { fields, ext } => {
                 let mut inner_stack = Vec::with_capacity(fields.items.len());
@@ -872,8 +879,41 @@ fn can_annotation_help(
             }
         }
 
-        Tuple { fields: _, ext: _ } => {
-            todo!("tuple");
+        Tuple { fields, ext } => {
+            let ext_type = match ext {
+                Some(_) => TypeExtension::Open(Box::new(can_extension_type(
+                    env,
+                    pol,
+                    scope,
+                    var_store,
+                    introduced_variables,
+                    local_aliases,
+                    references,
+                    ext,
+                    roc_problem::can::ExtensionTypeKind::Tuple,
+                ))),
+                None => TypeExtension::Closed,
+            };
+
+            let mut elem_types = VecMap::with_capacity(fields.items.len());
+
+            for (index, loc_elem) in fields.items.iter().enumerate() {
+                let elem_type = can_annotation_help(
+                    env,
+                    pol,
+                    &loc_elem.value,
+                    loc_elem.region,
+                    scope,
+                    var_store,
+                    introduced_variables,
+                    local_aliases,
+                    references,
+                );
+
+                elem_types.insert(index, elem_type);
+            }
+
+            Type::Tuple(elem_types, ext_type)
         }
         Record { fields, ext } => {
             let ext_type = can_extension_type(
@@ -1102,6 +1142,10 @@ fn can_extension_type<'a>(
             Type::EmptyRec | Type::Record(..) | Type::Variable(..) | Type::Error
         )
     }
+    fn valid_tuple_ext_type(typ: &Type) -> bool {
+        // Include erroneous types so that we don't overreport errors.
+        matches!(typ, Type::Tuple(..) | Type::Variable(..) | Type::Error)
+    }
     fn valid_tag_ext_type(typ: &Type) -> bool {
         matches!(
             typ,
@@ -1113,6 +1157,7 @@ fn can_extension_type<'a>(
 
     let valid_extension_type: fn(&Type) -> bool = match ext_problem_kind {
         ExtensionTypeKind::Record => valid_record_ext_type,
+        ExtensionTypeKind::Tuple => valid_tuple_ext_type,
         ExtensionTypeKind::TagUnion => valid_tag_ext_type,
     };
 
@@ -1162,6 +1207,9 @@ fn can_extension_type<'a>(
         }
         None => match ext_problem_kind {
             ExtensionTypeKind::Record => Type::EmptyRec,
+            ExtensionTypeKind::Tuple => {
+                internal_error!("closed tuples have no extension type")
+            }
             ExtensionTypeKind::TagUnion => {
                 // In negative positions a missing extension variable forces a closed tag union;
                 // otherwise, open-in-output-position means we give the tag an inference variable.
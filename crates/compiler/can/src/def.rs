@@ -1994,6 +1994,16 @@ fn pattern_to_vars_by_symbol(
             }
         }
 
+        TupleDestructure { destructs, .. } => {
+            for destruct in destructs {
+                pattern_to_vars_by_symbol(
+                    vars_by_symbol,
+                    &destruct.value.typ.1.value,
+                    destruct.value.typ.0,
+                );
+            }
+        }
+
         List {
             patterns, elem_var, ..
         } => {
@@ -80,6 +80,8 @@ enum IndexCtor<'a> {
     Opaque,
     /// Index a record type. The arguments are the types of the record fields.
     Record(&'a [Lowercase]),
+    /// Index a tuple type. The arguments are the types of the tuple elements, in order.
+    Tuple,
     /// Index a guard constructor. The arguments are a faux guard pattern, and then the real
     /// pattern being guarded. E.g. `A B if g` becomes Guard { [True, (A B)] }.
     Guard,
@@ -112,6 +114,7 @@ impl<'a> IndexCtor<'a> {
             }
             RenderAs::Opaque => Self::Opaque,
             RenderAs::Record(fields) => Self::Record(fields),
+            RenderAs::Tuple => Self::Tuple,
             RenderAs::Guard => Self::Guard,
         }
     }
@@ -182,6 +185,16 @@ fn index_var(
 
                     return Ok(field_types);
                 }
+                FlatType::Tuple(elems, ext) => {
+                    debug_assert!(matches!(ctor, IndexCtor::Tuple));
+
+                    let elem_types = elems
+                        .sorted_iterator(subs, *ext)
+                        .map(|(_, elem)| elem)
+                        .collect();
+
+                    return Ok(elem_types);
+                }
                 FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
                     let tag_ctor = match ctor {
                         IndexCtor::Tag(name) => name,
@@ -213,6 +226,9 @@ fn index_var(
                     };
                     return Ok(std::iter::repeat(Variable::NULL).take(num_fields).collect());
                 }
+                FlatType::EmptyTuple => {
+                    internal_error!("empty tuples are not indexable")
+                }
                 FlatType::EmptyTagUnion => {
                     internal_error!("empty tag unions are not indexable")
                 }
@@ -342,6 +358,30 @@ fn sketch_pattern(pattern: &crate::pattern::Pattern) -> SketchedPattern {
             SP::KnownCtor(union, tag_id, patterns)
         }
 
+        TupleDestructure { destructs, .. } => {
+            let tag_id = TagId(0);
+            let mut patterns = std::vec::Vec::with_capacity(destructs.len());
+
+            for Loc {
+                value: destruct,
+                region: _,
+            } in destructs
+            {
+                patterns.push(sketch_pattern(&destruct.typ.1.value));
+            }
+
+            let union = Union {
+                render_as: RenderAs::Tuple,
+                alternatives: vec![Ctor {
+                    name: CtorName::Tag(TagName("#Tuple".into())),
+                    tag_id,
+                    arity: destructs.len(),
+                }],
+            };
+
+            SP::KnownCtor(union, tag_id, patterns)
+        }
+
         List {
             patterns,
             list_var: _,
@@ -15,7 +15,7 @@ use roc_collections::soa::Index;
 use roc_collections::{SendMap, VecMap, VecSet};
 use roc_error_macros::internal_error;
 use roc_module::called_via::CalledVia;
-use roc_module::ident::{ForeignSymbol, Lowercase, TagName};
+use roc_module::ident::{ForeignSymbol, IndexOrField, Lowercase, TagName};
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
 use roc_parse::ast::{self, Defs, EscapedChar, StrLiteral};
@@ -180,9 +180,23 @@ pub enum Expr {
         loc_expr: Box<Loc<Expr>>,
         field: Lowercase,
     },
-    /// field accessor as a function, e.g. (.foo) expr
+    /// tuple or field accessor as a function, e.g. (.foo) expr or (.1) expr
     Accessor(AccessorData),
 
+    Tuple {
+        tuple_var: Variable,
+        elems: Vec<(Variable, Box<Loc<Expr>>)>,
+    },
+
+    /// Look up exactly one element on a tuple, e.g. (expr).1.
+    TupleAccess {
+        tuple_var: Variable,
+        ext_var: Variable,
+        elem_var: Variable,
+        loc_expr: Box<Loc<Expr>>,
+        index: usize,
+    },
+
     Update {
         record_var: Variable,
         ext_var: Variable,
@@ -298,6 +312,8 @@ impl Expr {
             Self::EmptyRecord => Category::Record,
             Self::Access { field, .. } => Category::Access(field.clone()),
             Self::Accessor(data) => Category::Accessor(data.field.clone()),
+            Self::Tuple { .. } => Category::Tuple,
+            Self::TupleAccess { index, .. } => Category::TupleAccess(*index),
             Self::Update { .. } => Category::Record,
             Self::Tag {
                 name, arguments, ..
@@ -377,7 +393,8 @@ pub struct AccessorData {
     pub closure_var: Variable,
     pub ext_var: Variable,
     pub field_var: Variable,
-    pub field: Lowercase,
+    /// Either a record field name (`.foo`) or a tuple element index (`.1`)
+    pub field: IndexOrField,
 }
 
 impl AccessorData {
@@ -399,12 +416,21 @@ impl AccessorData {
         // into
         //
         // (\r -> r.foo)
-        let body = Expr::Access {
-            record_var,
-            ext_var,
-            field_var,
-            loc_expr: Box::new(Loc::at_zero(Expr::Var(record_symbol, record_var))),
-            field,
+        let body = match field {
+            IndexOrField::Index(index) => Expr::TupleAccess {
+                tuple_var: record_var,
+                ext_var,
+                elem_var: field_var,
+                loc_expr: Box::new(Loc::at_zero(Expr::Var(record_symbol, record_var))),
+                index,
+            },
+            IndexOrField::Field(field) => Expr::Access {
+                record_var,
+                ext_var,
+                field_var,
+                loc_expr: Box::new(Loc::at_zero(Expr::Var(record_symbol, record_var))),
+                field,
+            },
         };
 
         let loc_body = Loc::at_zero(body);
@@ -620,8 +646,32 @@ pub fn canonicalize_expr<'a>(
                 }
             }
         }
-        ast::Expr::Tuple(_fields) => {
-            todo!("canonicalize tuple");
+        ast::Expr::Tuple(fields) => {
+            let mut can_elems = Vec::with_capacity(fields.len());
+            let mut references = References::new();
+
+            for loc_elem in fields.iter() {
+                let (can_expr, elem_out) =
+                    canonicalize_expr(env, var_store, scope, loc_elem.region, &loc_elem.value);
+
+                references.union_mut(&elem_out.references);
+
+                can_elems.push((var_store.fresh(), Box::new(can_expr)));
+            }
+
+            let output = Output {
+                references,
+                tail_call: None,
+                ..Default::default()
+            };
+
+            (
+                Tuple {
+                    tuple_var: var_store.fresh(),
+                    elems: can_elems,
+                },
+                output,
+            )
         }
         ast::Expr::RecordUpdate {
             fields,
@@ -1024,12 +1074,42 @@ pub fn canonicalize_expr<'a>(
                 ext_var: var_store.fresh(),
                 closure_var: var_store.fresh(),
                 field_var: var_store.fresh(),
-                field: (*field).into(),
+                field: IndexOrField::Field((*field).into()),
             }),
             Output::default(),
         ),
-        ast::Expr::TupleAccess(_record_expr, _field) => todo!("handle TupleAccess"),
-        ast::Expr::TupleAccessorFunction(_) => todo!("handle TupleAccessorFunction"),
+        ast::Expr::TupleAccess(tuple_expr, field) => {
+            let (loc_expr, output) = canonicalize_expr(env, var_store, scope, region, tuple_expr);
+
+            match parse_tuple_index(env, field, region) {
+                Ok(index) => (
+                    TupleAccess {
+                        tuple_var: var_store.fresh(),
+                        ext_var: var_store.fresh(),
+                        elem_var: var_store.fresh(),
+                        loc_expr: Box::new(loc_expr),
+                        index,
+                    },
+                    output,
+                ),
+                Err(error) => (error, output),
+            }
+        }
+        ast::Expr::TupleAccessorFunction(index) => match parse_tuple_index(env, index, region) {
+            Ok(index) => (
+                Accessor(AccessorData {
+                    name: scope.gen_unique_symbol(),
+                    function_var: var_store.fresh(),
+                    record_var: var_store.fresh(),
+                    ext_var: var_store.fresh(),
+                    closure_var: var_store.fresh(),
+                    field_var: var_store.fresh(),
+                    field: IndexOrField::Index(index),
+                }),
+                Output::default(),
+            ),
+            Err(error) => (error, Output::default()),
+        },
         ast::Expr::Tag(tag) => {
             let variant_var = var_store.fresh();
             let ext_var = var_store.fresh();
@@ -1705,6 +1785,17 @@ fn canonicalize_field<'a>(
     }
 }
 
+/// The parser accepts any run of digits as a tuple index, so it may not fit in a `usize`
+fn parse_tuple_index(env: &mut Env<'_>, index: &str, region: Region) -> Result<usize, Expr> {
+    index.parse().map_err(|_| {
+        let error = roc_problem::can::RuntimeError::InvalidTupleIndex(index.into(), region);
+
+        env.problems.push(Problem::RuntimeError(error.clone()));
+
+        Expr::RuntimeError(error)
+    })
+}
+
 fn canonicalize_var_lookup(
     env: &mut Env<'_>,
     var_store: &mut VarStore,
@@ -2057,6 +2148,24 @@ pub fn inline_calls(var_store: &mut VarStore, expr: Expr) -> Expr {
             todo!("Inlining for Access with record_var {:?}, ext_var {:?}, field_var {:?}, loc_expr {:?}, field {:?}", record_var, ext_var, field_var, loc_expr, field);
         }
 
+        Tuple { tuple_var, elems } => {
+            todo!(
+                "Inlining for Tuple with tuple_var {:?} and elems {:?}",
+                tuple_var,
+                elems
+            );
+        }
+
+        TupleAccess {
+            tuple_var,
+            ext_var,
+            elem_var,
+            loc_expr,
+            index,
+        } => {
+            todo!("Inlining for TupleAccess with tuple_var {:?}, ext_var {:?}, elem_var {:?}, loc_expr {:?}, index {:?}", tuple_var, ext_var, elem_var, loc_expr, index);
+        }
+
         Tag {
             tag_union_var: variant_var,
             ext_var,
@@ -2877,6 +2986,7 @@ pub(crate) fn get_lookup_symbols(expr: &Expr) -> Vec<ExpectLookup> {
                 stack.push(&argument.1.value);
             }
             Expr::Access { loc_expr, .. }
+            | Expr::TupleAccess { loc_expr, .. }
             | Expr::Closure(ClosureData {
                 loc_body: loc_expr, ..
             }) => {
@@ -2885,6 +2995,9 @@ pub(crate) fn get_lookup_symbols(expr: &Expr) -> Vec<ExpectLookup> {
             Expr::Record { fields, .. } => {
                 stack.extend(fields.iter().map(|(_, field)| &field.loc_expr.value));
             }
+            Expr::Tuple { elems, .. } => {
+                stack.extend(elems.iter().map(|(_, elem)| &elem.value));
+            }
             Expr::Expect {
                 loc_continuation, ..
             }
@@ -918,6 +918,15 @@ fn fix_values_captured_in_closure_pattern(
                 }
             }
         }
+        TupleDestructure { destructs, .. } => {
+            for loc_destruct in destructs.iter_mut() {
+                fix_values_captured_in_closure_pattern(
+                    &mut loc_destruct.value.typ.1.value,
+                    no_capture_symbols,
+                    closure_captures,
+                )
+            }
+        }
         List { patterns, .. } => {
             for loc_pat in patterns.patterns.iter_mut() {
                 fix_values_captured_in_closure_pattern(
@@ -1185,7 +1194,17 @@ fn fix_values_captured_in_closure_expr(
             }
         }
 
-        Access { loc_expr, .. } => {
+        Tuple { elems, .. } => {
+            for (_var, expr) in elems.iter_mut() {
+                fix_values_captured_in_closure_expr(
+                    &mut expr.value,
+                    no_capture_symbols,
+                    closure_captures,
+                );
+            }
+        }
+
+        Access { loc_expr, .. } | TupleAccess { loc_expr, .. } => {
             fix_values_captured_in_closure_expr(
                 &mut loc_expr.value,
                 no_capture_symbols,
@@ -1215,7 +1234,7 @@ fn fix_values_captured_in_closure_expr(
 }
 
 /// Type state for a single module.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TypeState {
     pub subs: Subs,
     pub exposed_vars_by_symbol: Vec<(Symbol, Variable)>,
@@ -1262,4 +1281,23 @@ impl TypeState {
             total_offset,
         )
     }
+
+    /// Rewrites every [Symbol] mentioned by this state; see [Subs::remap_symbols].
+    pub fn remap_symbols(&mut self, f: &mut impl FnMut(Symbol) -> Symbol) {
+        let Self {
+            subs,
+            exposed_vars_by_symbol,
+            abilities,
+            solved_implementations,
+        } = self;
+
+        subs.remap_symbols(f);
+
+        for (symbol, _) in exposed_vars_by_symbol.iter_mut() {
+            *symbol = f(*symbol);
+        }
+
+        abilities.remap_symbols(f);
+        crate::abilities::remap_solved_implementations(solved_implementations, f);
+    }
 }
@@ -57,6 +57,11 @@ pub enum Pattern {
         ext_var: Variable,
         destructs: Vec<Loc<RecordDestruct>>,
     },
+    TupleDestructure {
+        whole_var: Variable,
+        ext_var: Variable,
+        destructs: Vec<Loc<TupleDestruct>>,
+    },
     List {
         list_var: Variable,
         elem_var: Variable,
@@ -98,6 +103,7 @@ impl Pattern {
             AppliedTag { whole_var, .. } => Some(*whole_var),
             UnwrappedOpaque { whole_var, .. } => Some(*whole_var),
             RecordDestructure { whole_var, .. } => Some(*whole_var),
+            TupleDestructure { whole_var, .. } => Some(*whole_var),
             List {
                 list_var: whole_var,
                 ..
@@ -129,6 +135,9 @@ impl Pattern {
             | MalformedPattern(..)
             | AbilityMemberSpecialization { .. } => true,
             RecordDestructure { destructs, .. } => destructs.is_empty(),
+            TupleDestructure { destructs, .. } => destructs
+                .iter()
+                .all(|d| d.value.typ.1.value.surely_exhaustive()),
             List { patterns, .. } => patterns.surely_exhaustive(),
             AppliedTag { .. }
             | NumLiteral(..)
@@ -156,6 +165,7 @@ impl Pattern {
             UnwrappedOpaque { opaque, .. } => C::Opaque(*opaque),
             RecordDestructure { destructs, .. } if destructs.is_empty() => C::EmptyRecord,
             RecordDestructure { .. } => C::Record,
+            TupleDestructure { .. } => C::Tuple,
             List { .. } => C::List,
             NumLiteral(..) => C::Num,
             IntLiteral(..) => C::Int,
@@ -211,6 +221,13 @@ pub struct RecordDestruct {
     pub typ: DestructType,
 }
 
+#[derive(Clone, Debug)]
+pub struct TupleDestruct {
+    pub var: Variable,
+    pub destruct_index: usize,
+    pub typ: (Variable, Loc<Pattern>),
+}
+
 #[derive(Clone, Debug)]
 pub enum DestructType {
     Required,
@@ -530,8 +547,38 @@ pub fn canonicalize_pattern<'a>(
             )
         }
 
-        Tuple(_patterns) => {
-            todo!("canonicalize_pattern: Tuple")
+        Tuple(patterns) => {
+            let ext_var = var_store.fresh();
+            let whole_var = var_store.fresh();
+            let mut destructs = Vec::with_capacity(patterns.len());
+
+            for (i, loc_pattern) in patterns.iter().enumerate() {
+                let can_guard = canonicalize_pattern(
+                    env,
+                    var_store,
+                    scope,
+                    output,
+                    pattern_type,
+                    &loc_pattern.value,
+                    loc_pattern.region,
+                    permit_shadows,
+                );
+
+                destructs.push(Loc {
+                    region: loc_pattern.region,
+                    value: TupleDestruct {
+                        destruct_index: i,
+                        var: var_store.fresh(),
+                        typ: (var_store.fresh(), can_guard),
+                    },
+                });
+            }
+
+            Pattern::TupleDestructure {
+                whole_var,
+                ext_var,
+                destructs,
+            }
         }
 
         RecordDestructure(patterns) => {
@@ -843,6 +890,10 @@ impl<'a> BindingsFromPattern<'a> {
                             let it = destructs.iter().rev().map(Destruct);
                             stack.extend(it);
                         }
+                        TupleDestructure { destructs, .. } => {
+                            let it = destructs.iter().rev().map(|d| Pattern(&d.value.typ.1));
+                            stack.extend(it);
+                        }
                         NumLiteral(..)
                         | IntLiteral(..)
                         | FloatLiteral(..)
@@ -1,7 +1,7 @@
 //! Traversals over the can ast.
 
 use roc_module::{ident::Lowercase, symbol::Symbol};
-use roc_region::all::{Loc, Region};
+use roc_region::all::{Loc, Position, Region};
 use roc_types::{subs::Variable, types::MemberImpl};
 
 use crate::{
@@ -237,6 +237,19 @@ pub fn walk_expr<V: Visitor>(visitor: &mut V, expr: &Expr, var: Variable) {
             ext_var: _,
         } => visitor.visit_expr(&loc_expr.value, loc_expr.region, *field_var),
         Expr::Accessor(AccessorData { .. }) => { /* terminal */ }
+        Expr::Tuple {
+            tuple_var: _,
+            elems,
+        } => elems
+            .iter()
+            .for_each(|(var, elem)| visitor.visit_expr(&elem.value, elem.region, *var)),
+        Expr::TupleAccess {
+            elem_var,
+            loc_expr,
+            index: _,
+            tuple_var: _,
+            ext_var: _,
+        } => visitor.visit_expr(&loc_expr.value, loc_expr.region, *elem_var),
         Expr::OpaqueWrapFunction(OpaqueWrapFunctionData { .. }) => { /* terminal */ }
         Expr::Update {
             record_var: _,
@@ -486,6 +499,10 @@ pub fn walk_pattern<V: Visitor>(visitor: &mut V, pattern: &Pattern) {
         RecordDestructure { destructs, .. } => destructs
             .iter()
             .for_each(|d| visitor.visit_record_destruct(&d.value, d.region)),
+        TupleDestructure { destructs, .. } => destructs.iter().for_each(|d| {
+            let (var, lp) = &d.value.typ;
+            visitor.visit_pattern(&lp.value, lp.region, Some(*var))
+        }),
         List {
             patterns, elem_var, ..
         } => patterns
@@ -553,6 +570,150 @@ pub fn find_type_at(region: Region, decls: &Declarations) -> Option<Variable> {
     visitor.typ
 }
 
+/// Attempts to find the innermost expression or pattern containing `position`, returning its
+/// region and type.
+pub fn find_closest_type_at(
+    position: Position,
+    decls: &Declarations,
+) -> Option<(Region, Variable)> {
+    let mut visitor = Finder {
+        position,
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    return visitor.found;
+
+    struct Finder {
+        position: Position,
+        found: Option<(Region, Variable)>,
+    }
+
+    impl Visitor for Finder {
+        fn should_visit(&mut self, region: Region) -> bool {
+            region.contains_pos(self.position)
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if region.contains_pos(self.position) {
+                self.found = Some((region, var));
+
+                walk_expr(self, expr, var);
+            }
+        }
+
+        fn visit_pattern(&mut self, pat: &Pattern, region: Region, opt_var: Option<Variable>) {
+            if region.contains_pos(self.position) {
+                if let Some(var) = opt_var {
+                    self.found = Some((region, var));
+                }
+
+                walk_pattern(self, pat);
+            }
+        }
+    }
+}
+
+/// Attempts to find the innermost symbol that is referenced or introduced at `position`.
+pub fn find_symbol_at(position: Position, decls: &Declarations) -> Option<Loc<Symbol>> {
+    let mut visitor = Finder {
+        position,
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    return visitor.found;
+
+    struct Finder {
+        position: Position,
+        found: Option<Loc<Symbol>>,
+    }
+
+    impl Visitor for Finder {
+        fn should_visit(&mut self, region: Region) -> bool {
+            region.contains_pos(self.position)
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if region.contains_pos(self.position) {
+                match expr {
+                    &Expr::Var(symbol, _)
+                    | &Expr::AbilityMember(symbol, _, _)
+                    | &Expr::OpaqueRef { name: symbol, .. } => {
+                        self.found = Some(Loc::at(region, symbol));
+                    }
+                    _ => {}
+                }
+
+                walk_expr(self, expr, var);
+            }
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            if region.contains_pos(self.position) {
+                match pattern {
+                    &Pattern::Identifier(symbol)
+                    | &Pattern::Shadowed(_, _, symbol)
+                    | &Pattern::AbilityMemberSpecialization { ident: symbol, .. } => {
+                        self.found = Some(Loc::at(region, symbol));
+                    }
+                    _ => {}
+                }
+
+                walk_pattern(self, pattern);
+            }
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            if region.contains_pos(self.position) {
+                self.found = Some(Loc::at(region, destruct.symbol));
+
+                walk_record_destruct(self, destruct);
+            }
+        }
+    }
+}
+
+/// Attempts to find the region where `symbol` is introduced, either by a declaration or a pattern.
+pub fn find_declaration_region(symbol: Symbol, decls: &Declarations) -> Option<Region> {
+    let mut visitor = Finder {
+        symbol,
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    return visitor.found;
+
+    struct Finder {
+        symbol: Symbol,
+        found: Option<Region>,
+    }
+
+    impl Visitor for Finder {
+        fn should_visit(&mut self, _region: Region) -> bool {
+            self.found.is_none()
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            match pattern {
+                Pattern::Identifier(symbol)
+                | Pattern::Shadowed(_, _, symbol)
+                | Pattern::AbilityMemberSpecialization { ident: symbol, .. }
+                    if *symbol == self.symbol =>
+                {
+                    self.found.get_or_insert(region);
+                }
+                _ => walk_pattern(self, pattern),
+            }
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            if destruct.symbol == self.symbol {
+                self.found.get_or_insert(region);
+            } else {
+                walk_record_destruct(self, destruct);
+            }
+        }
+    }
+}
+
 /// Given an ability Foo has foo : ..., returns (T, foo1) if the symbol at the given region is a
 /// symbol foo1 that specializes foo for T. Otherwise if the symbol is foo but the specialization
 /// is unknown, (Foo, foo) is returned. Otherwise [None] is returned.
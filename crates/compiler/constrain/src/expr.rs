@@ -24,7 +24,7 @@ use roc_can::traverse::symbols_introduced_from_pattern;
 use roc_collections::all::{HumanIndex, MutMap, SendMap};
 use roc_collections::soa::{Index, Slice};
 use roc_collections::VecMap;
-use roc_module::ident::Lowercase;
+use roc_module::ident::{IndexOrField, Lowercase};
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{IllegalCycleMark, Variable};
@@ -251,6 +251,43 @@ pub fn constrain_expr(
                 constraints.exists(field_vars, and_constraint)
             }
         }
+        Expr::Tuple { tuple_var, elems } => {
+            let mut elem_types = VecMap::with_capacity(elems.len());
+            let mut elem_vars = Vec::with_capacity(elems.len());
+
+            // Constraints need capacity for each elem
+            // + 1 for the tuple itself + 1 for tuple var
+            let mut tuple_constraints = Vec::with_capacity(2 + elems.len());
+
+            for (i, (elem_var, loc_expr)) in elems.iter().enumerate() {
+                let (elem_type, elem_con) =
+                    constrain_field(types, constraints, env, *elem_var, loc_expr);
+
+                elem_vars.push(*elem_var);
+                elem_types.insert(i, elem_type);
+
+                tuple_constraints.push(elem_con);
+            }
+
+            let tuple_type = {
+                let typ = types.from_old_type(&Type::Tuple(elem_types, TypeExtension::Closed));
+                constraints.push_type(types, typ)
+            };
+
+            let tuple_con = constraints.equal_types_with_storage(
+                tuple_type,
+                expected,
+                Category::Tuple,
+                region,
+                *tuple_var,
+            );
+
+            tuple_constraints.push(tuple_con);
+            elem_vars.push(*tuple_var);
+
+            let and_constraint = constraints.and_constraint(tuple_constraints);
+            constraints.exists(elem_vars, and_constraint)
+        }
         Update {
             record_var,
             ext_var,
@@ -1114,6 +1151,49 @@ pub fn constrain_expr(
                 [constraint, eq, record_con],
             )
         }
+        TupleAccess {
+            tuple_var,
+            ext_var,
+            elem_var,
+            loc_expr,
+            index,
+        } => {
+            let ext_var = *ext_var;
+            let ext_type = Type::Variable(ext_var);
+            let elem_var = *elem_var;
+            let elem_type = Type::Variable(elem_var);
+
+            let mut tup_elem_types = VecMap::with_capacity(1);
+
+            tup_elem_types.insert(*index, elem_type);
+
+            let tuple_type = {
+                let typ = types.from_old_type(&Type::Tuple(
+                    tup_elem_types,
+                    TypeExtension::from_type(ext_type),
+                ));
+                constraints.push_type(types, typ)
+            };
+            let tuple_expected = constraints.push_expected_type(NoExpectation(tuple_type));
+
+            let category = Category::TupleAccess(*index);
+
+            let tuple_con =
+                constraints.equal_types_var(*tuple_var, tuple_expected, category.clone(), region);
+
+            let expected_tuple = constraints.push_expected_type(NoExpectation(tuple_type));
+            let constraint = constrain_expr(
+                types,
+                constraints,
+                env,
+                region,
+                &loc_expr.value,
+                expected_tuple,
+            );
+
+            let eq = constraints.equal_types_var(elem_var, expected, category, region);
+            constraints.exists_many([*tuple_var, elem_var, ext_var], [constraint, eq, tuple_con])
+        }
         Accessor(AccessorData {
             name: closure_name,
             function_var,
@@ -1128,10 +1208,19 @@ pub fn constrain_expr(
             let field_var = *field_var;
             let field_type = Variable(field_var);
 
-            let mut field_types = SendMap::default();
-            let label = field.clone();
-            field_types.insert(label, RecordField::Demanded(field_type.clone()));
-            let record_type = Type::Record(field_types, TypeExtension::from_type(ext_type));
+            let record_type = match field {
+                IndexOrField::Field(field) => {
+                    let mut field_types = SendMap::default();
+                    let label = field.clone();
+                    field_types.insert(label, RecordField::Demanded(field_type.clone()));
+                    Type::Record(field_types, TypeExtension::from_type(ext_type))
+                }
+                IndexOrField::Index(index) => {
+                    let mut field_types = VecMap::with_capacity(1);
+                    field_types.insert(*index, field_type.clone());
+                    Type::Tuple(field_types, TypeExtension::from_type(ext_type))
+                }
+            };
             let record_type_index = {
                 let typ = types.from_old_type(&record_type);
                 constraints.push_type(types, typ)
@@ -3,7 +3,7 @@ use crate::expr::{constrain_expr, Env};
 use roc_can::constraint::{Constraint, Constraints, PExpectedTypeIndex, TypeOrVar};
 use roc_can::expected::{Expected, PExpected};
 use roc_can::pattern::Pattern::{self, *};
-use roc_can::pattern::{DestructType, ListPatterns, RecordDestruct};
+use roc_can::pattern::{DestructType, ListPatterns, RecordDestruct, TupleDestruct};
 use roc_collections::all::{HumanIndex, SendMap};
 use roc_collections::VecMap;
 use roc_module::ident::Lowercase;
@@ -108,6 +108,32 @@ fn headers_from_annotation_help(
             _ => false,
         },
 
+        TupleDestructure { destructs, .. } => match annotation.value.shallow_dealias() {
+            Type::Tuple(elems, _) => {
+                for loc_destruct in destructs {
+                    let destruct = &loc_destruct.value;
+
+                    if let Some(elem_type) = elems.get(&destruct.destruct_index) {
+                        let is_valid = headers_from_annotation_help(
+                            types,
+                            constraints,
+                            &destruct.typ.1.value,
+                            &Loc::at(annotation.region, elem_type),
+                            headers,
+                        );
+
+                        if !is_valid {
+                            return false;
+                        }
+                    } else {
+                        return false;
+                    }
+                }
+                true
+            }
+            _ => false,
+        },
+
         List { .. } => {
             // There are no interesting headers to introduce for list patterns, since the only
             // exhaustive list pattern is
@@ -551,6 +577,94 @@ pub fn constrain_pattern(
             state.constraints.push(record_con);
         }
 
+        TupleDestructure {
+            whole_var,
+            ext_var,
+            destructs,
+        } => {
+            state.vars.push(*whole_var);
+            state.vars.push(*ext_var);
+            let ext_type = Type::Variable(*ext_var);
+
+            let mut elem_types: VecMap<usize, Type> = VecMap::default();
+
+            for Loc {
+                value:
+                    TupleDestruct {
+                        destruct_index: index,
+                        var,
+                        typ,
+                    },
+                ..
+            } in destructs
+            {
+                let pat_type = Type::Variable(*var);
+                let pat_type_index = constraints.push_variable(*var);
+                let expected =
+                    constraints.push_pat_expected_type(PExpected::NoExpectation(pat_type_index));
+
+                let (guard_var, loc_guard) = typ;
+                let elem_type = {
+                    let guard_type = constraints.push_variable(*guard_var);
+                    let expected_pat = constraints.push_pat_expected_type(PExpected::ForReason(
+                        PReason::PatternGuard,
+                        pat_type_index,
+                        loc_guard.region,
+                    ));
+
+                    state.constraints.push(constraints.pattern_presence(
+                        guard_type,
+                        expected_pat,
+                        PatternCategory::PatternGuard,
+                        region,
+                    ));
+                    state.vars.push(*guard_var);
+
+                    constrain_pattern(
+                        types,
+                        constraints,
+                        env,
+                        &loc_guard.value,
+                        loc_guard.region,
+                        expected,
+                        state,
+                    );
+
+                    pat_type
+                };
+
+                elem_types.insert(*index, elem_type);
+
+                state.vars.push(*var);
+            }
+
+            let tuple_type = {
+                let typ = types
+                    .from_old_type(&Type::Tuple(elem_types, TypeExtension::from_type(ext_type)));
+                constraints.push_type(types, typ)
+            };
+
+            let whole_var_index = constraints.push_variable(*whole_var);
+            let expected_tuple =
+                constraints.push_expected_type(Expected::NoExpectation(tuple_type));
+            let whole_con = constraints.equal_types(
+                whole_var_index,
+                expected_tuple,
+                Category::Storage(std::file!(), std::line!()),
+                region,
+            );
+
+            let tuple_con = constraints.pattern_presence(
+                whole_var_index,
+                expected,
+                PatternCategory::Tuple,
+                region,
+            );
+
+            state.constraints.push(whole_con);
+            state.constraints.push(tuple_con);
+        }
+
         List {
             list_var,
             elem_var,
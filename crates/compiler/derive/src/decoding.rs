@@ -1,16 +1,17 @@
 //! Derivers for the `Decoding` ability.
 
 use roc_can::expr::{
-    AnnotatedMark, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
+    AnnotatedMark, ClosureData, Expr, Field, IntValue, Recursive, WhenBranch, WhenBranchPattern,
 };
 use roc_can::pattern::Pattern;
 use roc_collections::SendMap;
 use roc_derive_key::decoding::FlatDecodableKey;
 use roc_error_macros::internal_error;
 use roc_module::called_via::CalledVia;
-use roc_module::ident::Lowercase;
+use roc_module::ident::{Lowercase, TagName};
 use roc_module::symbol::Symbol;
 use roc_region::all::{Loc, Region};
+use roc_types::num::{IntBound, IntLitWidth};
 use roc_types::subs::{
     Content, ExhaustiveMark, FlatType, GetSubsSlice, LambdaSet, OptVariable, RecordFields,
     RedundantMark, SubsSlice, UnionLambdas, UnionTags, Variable,
@@ -28,6 +29,7 @@ pub(crate) fn derive_decoder(
     let (body, body_type) = match key {
         FlatDecodableKey::List() => decoder_list(env, def_symbol),
         FlatDecodableKey::Record(fields) => decoder_record(env, def_symbol, fields),
+        FlatDecodableKey::TagUnion(tags) => decoder_tag_union(env, def_symbol, tags),
     };
 
     let specialization_lambda_sets =
@@ -236,384 +238,38 @@ fn decoder_record_step_field(
         //                 }
         //     )
 
-        let this_custom_callback_var;
-        let custom_callback_ret_var;
-        let custom_callback = {
-            // \bytes, fmt ->
-            //     when Decode.decodeWith bytes Decode.decoder fmt is
-            //         rec ->
-            //             {
-            //                 rest: rec.rest,
-            //                 result: when rec.result is
-            //                     Ok val -> Ok {state & first: Ok val},
-            //                     Err err -> Err err
-            //             }
-            let bytes_arg_symbol = env.new_symbol("bytes");
-            let fmt_arg_symbol = env.new_symbol("fmt");
-            let bytes_arg_var = env.subs.fresh_unnamed_flex_var();
-            let fmt_arg_var = env.subs.fresh_unnamed_flex_var();
-
-            // rec.result : [Ok field_var, Err DecodeError]
-            let rec_dot_result = {
-                let tag_union = FlatType::TagUnion(
-                    UnionTags::for_result(env.subs, field_var, decode_err_var),
-                    Variable::EMPTY_TAG_UNION,
-                );
-
-                synth_var(env.subs, Content::Structure(tag_union))
-            };
-
-            // rec : { rest: List U8, result: (typeof rec.result) }
-            let rec_var = {
-                let fields = RecordFields::insert_into_subs(
-                    env.subs,
-                    [
-                        ("rest".into(), RecordField::Required(Variable::LIST_U8)),
-                        ("result".into(), RecordField::Required(rec_dot_result)),
-                    ],
-                );
-                let record = FlatType::Record(fields, Variable::EMPTY_RECORD);
-
-                synth_var(env.subs, Content::Structure(record))
-            };
-
-            // `Decode.decoder` for the field's value
-            let decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
-            let decode_with_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODE_WITH);
-            let lambda_set_var = env.subs.fresh_unnamed_flex_var();
-            let this_decode_with_var = {
-                let subs_slice = SubsSlice::insert_into_subs(
-                    env.subs,
-                    [bytes_arg_var, decoder_var, fmt_arg_var],
-                );
-                let this_decode_with_var = synth_var(
-                    env.subs,
-                    Content::Structure(FlatType::Func(subs_slice, lambda_set_var, rec_var)),
-                );
-
-                env.unify(decode_with_var, this_decode_with_var);
-
-                this_decode_with_var
-            };
-
-            // The result of decoding this field's value - either the updated state, or a decoding error.
-            let when_expr_var = {
-                let flat_type = FlatType::TagUnion(
-                    UnionTags::for_result(env.subs, state_record_var, decode_err_var),
-                    Variable::EMPTY_TAG_UNION,
-                );
-
-                synth_var(env.subs, Content::Structure(flat_type))
-            };
-
-            // What our decoder passed to `Decode.custom` returns - the result of decoding the
-            // field's value, and the remaining bytes.
-            custom_callback_ret_var = {
-                let rest_field = RecordField::Required(Variable::LIST_U8);
-                let result_field = RecordField::Required(when_expr_var);
-                let flat_type = FlatType::Record(
-                    RecordFields::insert_into_subs(
-                        env.subs,
-                        [("rest".into(), rest_field), ("result".into(), result_field)],
-                    ),
-                    Variable::EMPTY_RECORD,
-                );
-
-                synth_var(env.subs, Content::Structure(flat_type))
-            };
-
-            let custom_callback_body = {
-                let rec_symbol = env.new_symbol("rec");
-
-                // # Uses a single-branch `when` because `let` is more expensive to monomorphize
-                // # due to checks for polymorphic expressions, and `rec` would be polymorphic.
-                // when Decode.decodeWith bytes Decode.decoder fmt is
-                //     rec ->
-                //         {
-                //             rest: rec.rest,
-                //             result: when rec.result is
-                //                 Ok val -> Ok {state & first: Ok val},
-                //                 Err err -> Err err
-                //         }
-                let branch_body = {
-                    let result_val = {
-                        // result: when rec.result is
-                        //     Ok val -> Ok {state & first: Ok val},
-                        //     Err err -> Err err
-                        let ok_val_symbol = env.new_symbol("val");
-                        let err_val_symbol = env.new_symbol("err");
-                        let ok_branch_expr = {
-                            // Ok {state & first: Ok val},
-                            let mut updates = SendMap::default();
-
-                            updates.insert(
-                                field_name.clone(),
-                                Field {
-                                    var: result_field_var,
-                                    region: Region::zero(),
-                                    loc_expr: Box::new(Loc::at_zero(Expr::Tag {
-                                        tag_union_var: result_field_var,
-                                        ext_var: env.new_ext_var(ExtensionKind::TagUnion),
-                                        name: "Ok".into(),
-                                        arguments: vec![(
-                                            field_var,
-                                            Loc::at_zero(Expr::Var(ok_val_symbol, field_var)),
-                                        )],
-                                    })),
-                                },
-                            );
-
-                            let updated_record = Expr::Update {
-                                record_var: state_record_var,
-                                ext_var: env.new_ext_var(ExtensionKind::Record),
-                                symbol: state_arg_symbol,
-                                updates,
-                            };
-
-                            Expr::Tag {
-                                tag_union_var: when_expr_var,
-                                ext_var: env.new_ext_var(ExtensionKind::TagUnion),
-                                name: "Ok".into(),
-                                arguments: vec![(state_record_var, Loc::at_zero(updated_record))],
-                            }
-                        };
-
-                        let branches = vec![
-                            // Ok val -> Ok {state & first: Ok val},
-                            WhenBranch {
-                                patterns: vec![WhenBranchPattern {
-                                    pattern: Loc::at_zero(Pattern::AppliedTag {
-                                        whole_var: rec_dot_result,
-                                        ext_var: Variable::EMPTY_TAG_UNION,
-                                        tag_name: "Ok".into(),
-                                        arguments: vec![(
-                                            field_var,
-                                            Loc::at_zero(Pattern::Identifier(ok_val_symbol)),
-                                        )],
-                                    }),
-                                    degenerate: false,
-                                }],
-                                value: Loc::at_zero(ok_branch_expr),
-                                guard: None,
-                                redundant: RedundantMark::known_non_redundant(),
-                            },
-                            // Err err -> Err err
-                            WhenBranch {
-                                patterns: vec![WhenBranchPattern {
-                                    pattern: Loc::at_zero(Pattern::AppliedTag {
-                                        whole_var: rec_dot_result,
-                                        ext_var: Variable::EMPTY_TAG_UNION,
-                                        tag_name: "Err".into(),
-                                        arguments: vec![(
-                                            decode_err_var,
-                                            Loc::at_zero(Pattern::Identifier(err_val_symbol)),
-                                        )],
-                                    }),
-                                    degenerate: false,
-                                }],
-                                value: Loc::at_zero(Expr::Tag {
-                                    tag_union_var: when_expr_var,
-                                    ext_var: env.new_ext_var(ExtensionKind::TagUnion),
-                                    name: "Err".into(),
-                                    arguments: vec![(
-                                        decode_err_var,
-                                        Loc::at_zero(Expr::Var(err_val_symbol, decode_err_var)),
-                                    )],
-                                }),
-                                guard: None,
-                                redundant: RedundantMark::known_non_redundant(),
-                            },
-                        ];
-
-                        // when rec.result is
-                        //     Ok val -> Ok {state & first: Ok val},
-                        //     Err err -> Err err
-                        Expr::When {
-                            loc_cond: Box::new(Loc::at_zero(Expr::Access {
-                                record_var: rec_var,
-                                ext_var: env.new_ext_var(ExtensionKind::Record),
-                                field_var: rec_dot_result,
-                                loc_expr: Box::new(Loc::at_zero(Expr::Var(rec_symbol, rec_var))),
-                                field: "result".into(),
-                            })),
-                            cond_var: rec_dot_result,
-                            expr_var: when_expr_var,
-                            region: Region::zero(),
-                            branches,
-                            branches_cond_var: rec_dot_result,
-                            exhaustive: ExhaustiveMark::known_exhaustive(),
-                        }
-                    };
-
-                    // {
-                    //     rest: rec.rest,
-                    //     result: when rec.result is
-                    //         Ok val -> Ok {state & first: Ok val},
-                    //         Err err -> Err err
-                    // }
-                    let mut fields_map = SendMap::default();
-
-                    fields_map.insert(
-                        "rest".into(),
-                        Field {
-                            var: Variable::LIST_U8,
-                            region: Region::zero(),
-                            loc_expr: Box::new(Loc::at_zero(Expr::Access {
-                                record_var: rec_var,
-                                ext_var: env.new_ext_var(ExtensionKind::Record),
-                                field_var: Variable::LIST_U8,
-                                loc_expr: Box::new(Loc::at_zero(Expr::Var(rec_symbol, rec_var))),
-                                field: "rest".into(),
-                            })),
-                        },
-                    );
-
-                    // result: when rec.result is
-                    //     Ok val -> Ok {state & first: Ok val},
-                    //     Err err -> Err err
-                    fields_map.insert(
-                        "result".into(),
-                        Field {
-                            var: when_expr_var,
-                            region: Region::zero(),
-                            loc_expr: Box::new(Loc::at_zero(result_val)),
-                        },
-                    );
-
-                    Expr::Record {
-                        record_var: custom_callback_ret_var,
-                        fields: fields_map,
-                    }
-                };
-
-                let branch = WhenBranch {
-                    patterns: vec![WhenBranchPattern {
-                        pattern: Loc::at_zero(Pattern::Identifier(rec_symbol)),
-                        degenerate: false,
-                    }],
-                    value: Loc::at_zero(branch_body),
-                    guard: None,
-                    redundant: RedundantMark::known_non_redundant(),
-                };
-
-                let condition_expr = Expr::Call(
-                    Box::new((
-                        this_decode_with_var,
-                        Loc::at_zero(Expr::Var(Symbol::DECODE_DECODE_WITH, this_decode_with_var)),
-                        lambda_set_var,
-                        rec_var,
-                    )),
-                    vec![
-                        (
-                            Variable::LIST_U8,
-                            Loc::at_zero(Expr::Var(bytes_arg_symbol, Variable::LIST_U8)),
-                        ),
-                        (
-                            decoder_var,
-                            Loc::at_zero(Expr::AbilityMember(
-                                Symbol::DECODE_DECODER,
-                                None,
-                                decoder_var,
-                            )),
-                        ),
-                        (
-                            fmt_arg_var,
-                            Loc::at_zero(Expr::Var(fmt_arg_symbol, fmt_arg_var)),
-                        ),
-                    ],
-                    CalledVia::Space,
+        let (decode_custom, decode_custom_ret_var) = decode_into_state_custom(
+            env,
+            field_var,
+            state_record_var,
+            decode_err_var,
+            vec![(state_arg_symbol, state_record_var)],
+            |env, val| {
+                // {state & first: Ok val}
+                let mut updates = SendMap::default();
+
+                updates.insert(
+                    field_name.clone(),
+                    Field {
+                        var: result_field_var,
+                        region: Region::zero(),
+                        loc_expr: Box::new(Loc::at_zero(Expr::Tag {
+                            tag_union_var: result_field_var,
+                            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                            name: "Ok".into(),
+                            arguments: vec![(field_var, Loc::at_zero(val))],
+                        })),
+                    },
                 );
 
-                // when Decode.decodeWith bytes Decode.decoder fmt is
-                Expr::When {
-                    loc_cond: Box::new(Loc::at_zero(condition_expr)),
-                    cond_var: rec_var,
-                    expr_var: custom_callback_ret_var,
-                    region: Region::zero(),
-                    branches: vec![branch],
-                    branches_cond_var: rec_var,
-                    exhaustive: ExhaustiveMark::known_exhaustive(),
+                Expr::Update {
+                    record_var: state_record_var,
+                    ext_var: env.new_ext_var(ExtensionKind::Record),
+                    symbol: state_arg_symbol,
+                    updates,
                 }
-            };
-
-            let custom_closure_symbol = env.new_symbol("customCallback");
-            this_custom_callback_var = env.subs.fresh_unnamed_flex_var();
-            let custom_callback_lambda_set_var = {
-                let content = Content::LambdaSet(LambdaSet {
-                    solved: UnionLambdas::insert_into_subs(
-                        env.subs,
-                        [(custom_closure_symbol, [state_record_var])],
-                    ),
-                    recursion_var: OptVariable::NONE,
-                    unspecialized: Default::default(),
-                    ambient_function: this_custom_callback_var,
-                });
-                let custom_callback_lambda_set_var = synth_var(env.subs, content);
-                let subs_slice =
-                    SubsSlice::insert_into_subs(env.subs, [bytes_arg_var, fmt_arg_var]);
-
-                env.subs.set_content(
-                    this_custom_callback_var,
-                    Content::Structure(FlatType::Func(
-                        subs_slice,
-                        custom_callback_lambda_set_var,
-                        custom_callback_ret_var,
-                    )),
-                );
-
-                custom_callback_lambda_set_var
-            };
-
-            // \bytes, fmt -> …
-            Expr::Closure(ClosureData {
-                function_type: this_custom_callback_var,
-                closure_type: custom_callback_lambda_set_var,
-                return_type: custom_callback_ret_var,
-                name: custom_closure_symbol,
-                captured_symbols: vec![(state_arg_symbol, state_record_var)],
-                recursive: Recursive::NotRecursive,
-                arguments: vec![
-                    (
-                        bytes_arg_var,
-                        AnnotatedMark::known_exhaustive(),
-                        Loc::at_zero(Pattern::Identifier(bytes_arg_symbol)),
-                    ),
-                    (
-                        fmt_arg_var,
-                        AnnotatedMark::known_exhaustive(),
-                        Loc::at_zero(Pattern::Identifier(fmt_arg_symbol)),
-                    ),
-                ],
-                loc_body: Box::new(Loc::at_zero(custom_callback_body)),
-            })
-        };
-
-        let decode_custom_ret_var = env.subs.fresh_unnamed_flex_var();
-        let decode_custom = {
-            let decode_custom_var = env.import_builtin_symbol_var(Symbol::DECODE_CUSTOM);
-            let decode_custom_closure_var = env.subs.fresh_unnamed_flex_var();
-            let this_decode_custom_var = {
-                let subs_slice = SubsSlice::insert_into_subs(env.subs, [this_custom_callback_var]);
-                let flat_type =
-                    FlatType::Func(subs_slice, decode_custom_closure_var, decode_custom_ret_var);
-
-                synth_var(env.subs, Content::Structure(flat_type))
-            };
-
-            env.unify(decode_custom_var, this_decode_custom_var);
-
-            // Decode.custom \bytes, fmt -> …
-            Expr::Call(
-                Box::new((
-                    this_decode_custom_var,
-                    Loc::at_zero(Expr::Var(Symbol::DECODE_CUSTOM, this_decode_custom_var)),
-                    decode_custom_closure_var,
-                    decode_custom_ret_var,
-                )),
-                vec![(this_custom_callback_var, Loc::at_zero(custom_callback))],
-                CalledVia::Space,
-            )
-        };
+            },
+        );
 
         env.unify(keep_payload_var, decode_custom_ret_var);
 
@@ -738,6 +394,384 @@ fn decoder_record_step_field(
     (expr, function_type)
 }
 
+// Example:
+// Decode.custom \bytes, fmt ->
+//     # Uses a single-branch `when` because `let` is more expensive to monomorphize
+//     # due to checks for polymorphic expressions, and `rec` would be polymorphic.
+//     when Decode.decodeWith bytes Decode.decoder fmt is
+//         rec ->
+//             {
+//                 rest: rec.rest,
+//                 result: when rec.result is
+//                     Ok val -> Ok (updateState val),
+//                     Err err -> Err err
+//             }
+//
+// where `updateState` builds the new decoding state from the decoded value, and `captures` are
+// the (sorted) symbols it needs from the enclosing scope.
+fn decode_into_state_custom(
+    env: &mut Env,
+    val_var: Variable,
+    state_var: Variable,
+    decode_err_var: Variable,
+    captures: Vec<(Symbol, Variable)>,
+    update_state: impl FnOnce(&mut Env, Expr) -> Expr,
+) -> (Expr, Variable) {
+    let this_custom_callback_var;
+    let custom_callback_ret_var;
+    let custom_callback = {
+        // \bytes, fmt ->
+        //     when Decode.decodeWith bytes Decode.decoder fmt is
+        //         rec ->
+        //             {
+        //                 rest: rec.rest,
+        //                 result: when rec.result is
+        //                     Ok val -> Ok (updateState val),
+        //                     Err err -> Err err
+        //             }
+        let bytes_arg_symbol = env.new_symbol("bytes");
+        let fmt_arg_symbol = env.new_symbol("fmt");
+        let bytes_arg_var = env.subs.fresh_unnamed_flex_var();
+        let fmt_arg_var = env.subs.fresh_unnamed_flex_var();
+
+        // rec.result : [Ok val_var, Err DecodeError]
+        let rec_dot_result = {
+            let tag_union = FlatType::TagUnion(
+                UnionTags::for_result(env.subs, val_var, decode_err_var),
+                Variable::EMPTY_TAG_UNION,
+            );
+
+            synth_var(env.subs, Content::Structure(tag_union))
+        };
+
+        // rec : { rest: List U8, result: (typeof rec.result) }
+        let rec_var = {
+            let fields = RecordFields::insert_into_subs(
+                env.subs,
+                [
+                    ("rest".into(), RecordField::Required(Variable::LIST_U8)),
+                    ("result".into(), RecordField::Required(rec_dot_result)),
+                ],
+            );
+            let record = FlatType::Record(fields, Variable::EMPTY_RECORD);
+
+            synth_var(env.subs, Content::Structure(record))
+        };
+
+        // `Decode.decoder` for the value
+        let decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
+        let decode_with_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODE_WITH);
+        let lambda_set_var = env.subs.fresh_unnamed_flex_var();
+        let this_decode_with_var = {
+            let subs_slice =
+                SubsSlice::insert_into_subs(env.subs, [bytes_arg_var, decoder_var, fmt_arg_var]);
+            let this_decode_with_var = synth_var(
+                env.subs,
+                Content::Structure(FlatType::Func(subs_slice, lambda_set_var, rec_var)),
+            );
+
+            env.unify(decode_with_var, this_decode_with_var);
+
+            this_decode_with_var
+        };
+
+        // The result of decoding the value - either the updated state, or a decoding error.
+        let when_expr_var = {
+            let flat_type = FlatType::TagUnion(
+                UnionTags::for_result(env.subs, state_var, decode_err_var),
+                Variable::EMPTY_TAG_UNION,
+            );
+
+            synth_var(env.subs, Content::Structure(flat_type))
+        };
+
+        // What our decoder passed to `Decode.custom` returns - the result of decoding the
+        // value, and the remaining bytes.
+        custom_callback_ret_var = {
+            let rest_field = RecordField::Required(Variable::LIST_U8);
+            let result_field = RecordField::Required(when_expr_var);
+            let flat_type = FlatType::Record(
+                RecordFields::insert_into_subs(
+                    env.subs,
+                    [("rest".into(), rest_field), ("result".into(), result_field)],
+                ),
+                Variable::EMPTY_RECORD,
+            );
+
+            synth_var(env.subs, Content::Structure(flat_type))
+        };
+
+        let custom_callback_body = {
+            let rec_symbol = env.new_symbol("rec");
+
+            // # Uses a single-branch `when` because `let` is more expensive to monomorphize
+            // # due to checks for polymorphic expressions, and `rec` would be polymorphic.
+            // when Decode.decodeWith bytes Decode.decoder fmt is
+            //     rec ->
+            //         {
+            //             rest: rec.rest,
+            //             result: when rec.result is
+            //                 Ok val -> Ok (updateState val),
+            //                 Err err -> Err err
+            //         }
+            let branch_body = {
+                let result_val = {
+                    // result: when rec.result is
+                    //     Ok val -> Ok (updateState val),
+                    //     Err err -> Err err
+                    let ok_val_symbol = env.new_symbol("val");
+                    let err_val_symbol = env.new_symbol("err");
+                    let ok_branch_expr = {
+                        // Ok (updateState val),
+                        let updated_state = update_state(env, Expr::Var(ok_val_symbol, val_var));
+
+                        Expr::Tag {
+                            tag_union_var: when_expr_var,
+                            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                            name: "Ok".into(),
+                            arguments: vec![(state_var, Loc::at_zero(updated_state))],
+                        }
+                    };
+
+                    let branches = vec![
+                        // Ok val -> Ok (updateState val),
+                        WhenBranch {
+                            patterns: vec![WhenBranchPattern {
+                                pattern: Loc::at_zero(Pattern::AppliedTag {
+                                    whole_var: rec_dot_result,
+                                    ext_var: Variable::EMPTY_TAG_UNION,
+                                    tag_name: "Ok".into(),
+                                    arguments: vec![(
+                                        val_var,
+                                        Loc::at_zero(Pattern::Identifier(ok_val_symbol)),
+                                    )],
+                                }),
+                                degenerate: false,
+                            }],
+                            value: Loc::at_zero(ok_branch_expr),
+                            guard: None,
+                            redundant: RedundantMark::known_non_redundant(),
+                        },
+                        // Err err -> Err err
+                        WhenBranch {
+                            patterns: vec![WhenBranchPattern {
+                                pattern: Loc::at_zero(Pattern::AppliedTag {
+                                    whole_var: rec_dot_result,
+                                    ext_var: Variable::EMPTY_TAG_UNION,
+                                    tag_name: "Err".into(),
+                                    arguments: vec![(
+                                        decode_err_var,
+                                        Loc::at_zero(Pattern::Identifier(err_val_symbol)),
+                                    )],
+                                }),
+                                degenerate: false,
+                            }],
+                            value: Loc::at_zero(Expr::Tag {
+                                tag_union_var: when_expr_var,
+                                ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                                name: "Err".into(),
+                                arguments: vec![(
+                                    decode_err_var,
+                                    Loc::at_zero(Expr::Var(err_val_symbol, decode_err_var)),
+                                )],
+                            }),
+                            guard: None,
+                            redundant: RedundantMark::known_non_redundant(),
+                        },
+                    ];
+
+                    // when rec.result is
+                    //     Ok val -> Ok (updateState val),
+                    //     Err err -> Err err
+                    Expr::When {
+                        loc_cond: Box::new(Loc::at_zero(Expr::Access {
+                            record_var: rec_var,
+                            ext_var: env.new_ext_var(ExtensionKind::Record),
+                            field_var: rec_dot_result,
+                            loc_expr: Box::new(Loc::at_zero(Expr::Var(rec_symbol, rec_var))),
+                            field: "result".into(),
+                        })),
+                        cond_var: rec_dot_result,
+                        expr_var: when_expr_var,
+                        region: Region::zero(),
+                        branches,
+                        branches_cond_var: rec_dot_result,
+                        exhaustive: ExhaustiveMark::known_exhaustive(),
+                    }
+                };
+
+                // {
+                //     rest: rec.rest,
+                //     result: when rec.result is
+                //         Ok val -> Ok (updateState val),
+                //         Err err -> Err err
+                // }
+                let mut fields_map = SendMap::default();
+
+                fields_map.insert(
+                    "rest".into(),
+                    Field {
+                        var: Variable::LIST_U8,
+                        region: Region::zero(),
+                        loc_expr: Box::new(Loc::at_zero(Expr::Access {
+                            record_var: rec_var,
+                            ext_var: env.new_ext_var(ExtensionKind::Record),
+                            field_var: Variable::LIST_U8,
+                            loc_expr: Box::new(Loc::at_zero(Expr::Var(rec_symbol, rec_var))),
+                            field: "rest".into(),
+                        })),
+                    },
+                );
+
+                // result: when rec.result is
+                //     Ok val -> Ok (updateState val),
+                //     Err err -> Err err
+                fields_map.insert(
+                    "result".into(),
+                    Field {
+                        var: when_expr_var,
+                        region: Region::zero(),
+                        loc_expr: Box::new(Loc::at_zero(result_val)),
+                    },
+                );
+
+                Expr::Record {
+                    record_var: custom_callback_ret_var,
+                    fields: fields_map,
+                }
+            };
+
+            let branch = WhenBranch {
+                patterns: vec![WhenBranchPattern {
+                    pattern: Loc::at_zero(Pattern::Identifier(rec_symbol)),
+                    degenerate: false,
+                }],
+                value: Loc::at_zero(branch_body),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            };
+
+            let condition_expr = Expr::Call(
+                Box::new((
+                    this_decode_with_var,
+                    Loc::at_zero(Expr::Var(Symbol::DECODE_DECODE_WITH, this_decode_with_var)),
+                    lambda_set_var,
+                    rec_var,
+                )),
+                vec![
+                    (
+                        Variable::LIST_U8,
+                        Loc::at_zero(Expr::Var(bytes_arg_symbol, Variable::LIST_U8)),
+                    ),
+                    (
+                        decoder_var,
+                        Loc::at_zero(Expr::AbilityMember(
+                            Symbol::DECODE_DECODER,
+                            None,
+                            decoder_var,
+                        )),
+                    ),
+                    (
+                        fmt_arg_var,
+                        Loc::at_zero(Expr::Var(fmt_arg_symbol, fmt_arg_var)),
+                    ),
+                ],
+                CalledVia::Space,
+            );
+
+            // when Decode.decodeWith bytes Decode.decoder fmt is
+            Expr::When {
+                loc_cond: Box::new(Loc::at_zero(condition_expr)),
+                cond_var: rec_var,
+                expr_var: custom_callback_ret_var,
+                region: Region::zero(),
+                branches: vec![branch],
+                branches_cond_var: rec_var,
+                exhaustive: ExhaustiveMark::known_exhaustive(),
+            }
+        };
+
+        let custom_closure_symbol = env.new_symbol("customCallback");
+        this_custom_callback_var = env.subs.fresh_unnamed_flex_var();
+        let custom_callback_lambda_set_var = {
+            let content = Content::LambdaSet(LambdaSet {
+                solved: UnionLambdas::insert_into_subs(
+                    env.subs,
+                    [(custom_closure_symbol, captures.iter().map(|(_, var)| *var))],
+                ),
+                recursion_var: OptVariable::NONE,
+                unspecialized: Default::default(),
+                ambient_function: this_custom_callback_var,
+            });
+            let custom_callback_lambda_set_var = synth_var(env.subs, content);
+            let subs_slice = SubsSlice::insert_into_subs(env.subs, [bytes_arg_var, fmt_arg_var]);
+
+            env.subs.set_content(
+                this_custom_callback_var,
+                Content::Structure(FlatType::Func(
+                    subs_slice,
+                    custom_callback_lambda_set_var,
+                    custom_callback_ret_var,
+                )),
+            );
+
+            custom_callback_lambda_set_var
+        };
+
+        // \bytes, fmt -> …
+        Expr::Closure(ClosureData {
+            function_type: this_custom_callback_var,
+            closure_type: custom_callback_lambda_set_var,
+            return_type: custom_callback_ret_var,
+            name: custom_closure_symbol,
+            captured_symbols: captures,
+            recursive: Recursive::NotRecursive,
+            arguments: vec![
+                (
+                    bytes_arg_var,
+                    AnnotatedMark::known_exhaustive(),
+                    Loc::at_zero(Pattern::Identifier(bytes_arg_symbol)),
+                ),
+                (
+                    fmt_arg_var,
+                    AnnotatedMark::known_exhaustive(),
+                    Loc::at_zero(Pattern::Identifier(fmt_arg_symbol)),
+                ),
+            ],
+            loc_body: Box::new(Loc::at_zero(custom_callback_body)),
+        })
+    };
+
+    let decode_custom_ret_var = env.subs.fresh_unnamed_flex_var();
+    let decode_custom = {
+        let decode_custom_var = env.import_builtin_symbol_var(Symbol::DECODE_CUSTOM);
+        let decode_custom_closure_var = env.subs.fresh_unnamed_flex_var();
+        let this_decode_custom_var = {
+            let subs_slice = SubsSlice::insert_into_subs(env.subs, [this_custom_callback_var]);
+            let flat_type =
+                FlatType::Func(subs_slice, decode_custom_closure_var, decode_custom_ret_var);
+
+            synth_var(env.subs, Content::Structure(flat_type))
+        };
+
+        env.unify(decode_custom_var, this_decode_custom_var);
+
+        // Decode.custom \bytes, fmt -> …
+        Expr::Call(
+            Box::new((
+                this_decode_custom_var,
+                Loc::at_zero(Expr::Var(Symbol::DECODE_CUSTOM, this_decode_custom_var)),
+                decode_custom_closure_var,
+                decode_custom_ret_var,
+            )),
+            vec![(this_custom_callback_var, Loc::at_zero(custom_callback))],
+            CalledVia::Space,
+        )
+    };
+
+    (decode_custom, decode_custom_ret_var)
+}
+
 // Example:
 // finalizer = \rec ->
 //     when rec.first is
@@ -997,6 +1031,713 @@ fn decoder_record_initial_state(
     )
 }
 
+/// A tag of the union being decoded, along with the type variables the derived decoder uses for it.
+struct DecodedTag {
+    name: TagName,
+    /// The decoded type of each payload element, e.g. `a` and `b` for `A a b`.
+    payload_vars: Vec<Variable>,
+    /// The type of each payload element in the decoding state, e.g. `Result a [NoElem]`.
+    elem_vars: Vec<Variable>,
+}
+
+// Implements decoding of a tag union. For example, for
+//
+//   [A a b, B, C c]
+//
+// we'd like to generate an impl like
+//
+// decoder : Decoder [A a b, B, C c] fmt | a has Decoding, b has Decoding, c has Decoding, fmt has DecoderFormatting
+// decoder =
+//     # The decoding state is the tag union itself, where each payload element is yet to be decoded.
+//     stepTag : Str -> [Keep [A (Result a [NoElem]) (Result b [NoElem]), B, C (Result c [NoElem])], Skip]
+//     stepTag = \tag ->
+//         when tag is
+//             "A" -> Keep (A (Err NoElem) (Err NoElem))
+//             "B" -> Keep B
+//             "C" -> Keep (C (Err NoElem))
+//             _ -> Skip
+//
+//     stepElem = \state, index ->
+//         when state is
+//             A e0 e1 ->
+//                 when index is
+//                     0 ->
+//                         Keep (Decode.custom \bytes, fmt ->
+//                             when Decode.decodeWith bytes Decode.decoder fmt is
+//                                 rec ->
+//                                     {
+//                                         rest: rec.rest,
+//                                         result: when rec.result is
+//                                             Ok val -> Ok (A (Ok val) e1),
+//                                             Err err -> Err err
+//                                     })
+//                     1 -> Keep (Decode.custom ... Ok val -> Ok (A e0 (Ok val)) ...)
+//                     _ -> Skip
+//             B -> Skip
+//             C e0 ->
+//                 when index is
+//                     0 -> Keep (Decode.custom ... Ok val -> Ok (C (Ok val)) ...)
+//                     _ -> Skip
+//
+//     finalizer = \state ->
+//         when state is
+//             A (Ok v0) (Ok v1) -> Ok (A v0 v1)
+//             B -> Ok B
+//             C (Ok v0) -> Ok (C v0)
+//             _ -> Err TooShort
+//
+//     Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.tag stepTag stepElem finalizer) fmt
+fn decoder_tag_union(
+    env: &mut Env,
+    _def_symbol: Symbol,
+    tags: Vec<(TagName, u16)>,
+) -> (Expr, Variable) {
+    // The state of decoding, e.g. [A (Result a [NoElem]) (Result b [NoElem]), B, C (Result c [NoElem])]
+    let (state_var, no_elem_var, decoded_tags) = decoder_tag_union_state(env, tags);
+
+    // finalizer = ...
+    let (finalizer, finalizer_var, decode_err_var) =
+        decoder_tag_union_finalizer(env, state_var, &decoded_tags);
+
+    // stepTag = ...
+    let (step_tag, step_tag_var) =
+        decoder_tag_union_step_tag(env, state_var, no_elem_var, &decoded_tags);
+
+    // stepElem = ...
+    let (step_elem, step_elem_var) =
+        decoder_tag_union_step_elem(env, state_var, &decoded_tags, decode_err_var);
+
+    // Build up the type of `Decode.tag` we expect
+    let tag_decoder_var = env.subs.fresh_unnamed_flex_var();
+    let decode_tag_lambda_set = env.subs.fresh_unnamed_flex_var();
+    let decode_tag_var = env.import_builtin_symbol_var(Symbol::DECODE_TAG);
+    let this_decode_tag_var = {
+        let flat_type = FlatType::Func(
+            SubsSlice::insert_into_subs(env.subs, [step_tag_var, step_elem_var, finalizer_var]),
+            decode_tag_lambda_set,
+            tag_decoder_var,
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    env.unify(decode_tag_var, this_decode_tag_var);
+
+    // Decode.tag stepTag stepElem finalizer
+    let call_decode_tag = Expr::Call(
+        Box::new((
+            this_decode_tag_var,
+            Loc::at_zero(Expr::AbilityMember(
+                Symbol::DECODE_TAG,
+                None,
+                this_decode_tag_var,
+            )),
+            decode_tag_lambda_set,
+            tag_decoder_var,
+        )),
+        vec![
+            (step_tag_var, Loc::at_zero(step_tag)),
+            (step_elem_var, Loc::at_zero(step_elem)),
+            (finalizer_var, Loc::at_zero(finalizer)),
+        ],
+        CalledVia::Space,
+    );
+
+    let bytes_sym = env.new_symbol("bytes");
+    let fmt_sym = env.new_symbol("fmt");
+    let fmt_var = env.subs.fresh_unnamed_flex_var();
+
+    wrap_in_decode_custom_decode_with(
+        env,
+        bytes_sym,
+        (fmt_sym, fmt_var),
+        vec![],
+        (call_decode_tag, tag_decoder_var),
+    )
+}
+
+// Example:
+// [A (Result a [NoElem]) (Result b [NoElem]), B, C (Result c [NoElem])]
+//
+// Also returns the `[NoElem]` variable.
+fn decoder_tag_union_state(
+    env: &mut Env,
+    tags: Vec<(TagName, u16)>,
+) -> (Variable, Variable, Vec<DecodedTag>) {
+    let subs = &mut env.subs;
+    let no_elem_var = {
+        let union_tags = UnionTags::tag_without_arguments(subs, "NoElem".into());
+
+        synth_var(
+            subs,
+            Content::Structure(FlatType::TagUnion(union_tags, Variable::EMPTY_TAG_UNION)),
+        )
+    };
+
+    let decoded_tags: Vec<_> = tags
+        .into_iter()
+        .map(|(name, arity)| {
+            let payload_vars: Vec<_> = std::iter::repeat_with(|| subs.fresh_unnamed_flex_var())
+                .take(arity as usize)
+                .collect();
+            let elem_vars = payload_vars
+                .iter()
+                .map(|&payload_var| {
+                    let union_tags = UnionTags::for_result(subs, payload_var, no_elem_var);
+
+                    synth_var(
+                        subs,
+                        Content::Structure(FlatType::TagUnion(
+                            union_tags,
+                            Variable::EMPTY_TAG_UNION,
+                        )),
+                    )
+                })
+                .collect();
+
+            DecodedTag {
+                name,
+                payload_vars,
+                elem_vars,
+            }
+        })
+        .collect();
+
+    let union_tags = UnionTags::insert_into_subs(
+        subs,
+        decoded_tags
+            .iter()
+            .map(|tag| (tag.name.clone(), tag.elem_vars.clone())),
+    );
+    let state_var = synth_var(
+        subs,
+        Content::Structure(FlatType::TagUnion(union_tags, Variable::EMPTY_TAG_UNION)),
+    );
+
+    (state_var, no_elem_var, decoded_tags)
+}
+
+// Example:
+// stepTag = \tag ->
+//     when tag is
+//         "A" -> Keep (A (Err NoElem) (Err NoElem))
+//         "B" -> Keep B
+//         "C" -> Keep (C (Err NoElem))
+//         _ -> Skip
+fn decoder_tag_union_step_tag(
+    env: &mut Env,
+    state_var: Variable,
+    no_elem_var: Variable,
+    decoded_tags: &[DecodedTag],
+) -> (Expr, Variable) {
+    let tag_arg_symbol = env.new_symbol("tag");
+    let keep_or_skip_var = keep_or_skip_var(env, state_var);
+
+    // +1 because of the default branch.
+    let mut branches = Vec::with_capacity(decoded_tags.len() + 1);
+
+    for tag in decoded_tags {
+        // A (Err NoElem) (Err NoElem)
+        let arguments = tag
+            .elem_vars
+            .iter()
+            .map(|&elem_var| {
+                let no_elem = Expr::Tag {
+                    tag_union_var: no_elem_var,
+                    ext_var: Variable::EMPTY_TAG_UNION,
+                    name: "NoElem".into(),
+                    arguments: Vec::new(),
+                };
+                let err = Expr::Tag {
+                    tag_union_var: elem_var,
+                    ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                    name: "Err".into(),
+                    arguments: vec![(no_elem_var, Loc::at_zero(no_elem))],
+                };
+
+                (elem_var, Loc::at_zero(err))
+            })
+            .collect();
+
+        let initial_state = Expr::Tag {
+            tag_union_var: state_var,
+            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+            name: tag.name.clone(),
+            arguments,
+        };
+
+        // "A" -> Keep (A (Err NoElem) (Err NoElem))
+        branches.push(WhenBranch {
+            patterns: vec![WhenBranchPattern {
+                pattern: Loc::at_zero(Pattern::StrLiteral(tag.name.0.as_str().into())),
+                degenerate: false,
+            }],
+            value: Loc::at_zero(Expr::Tag {
+                tag_union_var: keep_or_skip_var,
+                ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                name: "Keep".into(),
+                arguments: vec![(state_var, Loc::at_zero(initial_state))],
+            }),
+            guard: None,
+            redundant: RedundantMark::known_non_redundant(),
+        });
+    }
+
+    // _ -> Skip
+    branches.push(WhenBranch {
+        patterns: vec![WhenBranchPattern {
+            pattern: Loc::at_zero(Pattern::Underscore),
+            degenerate: false,
+        }],
+        value: Loc::at_zero(skip(env, keep_or_skip_var)),
+        guard: None,
+        redundant: RedundantMark::known_non_redundant(),
+    });
+
+    // when tag is
+    let body = Expr::When {
+        loc_cond: Box::new(Loc::at_zero(Expr::Var(tag_arg_symbol, Variable::STR))),
+        cond_var: Variable::STR,
+        expr_var: keep_or_skip_var,
+        region: Region::zero(),
+        branches,
+        branches_cond_var: Variable::STR,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    let step_tag_symbol = env.new_symbol("stepTag");
+
+    build_step_closure(
+        env,
+        step_tag_symbol,
+        vec![(tag_arg_symbol, Variable::STR)],
+        (body, keep_or_skip_var),
+    )
+}
+
+// Example:
+// stepElem = \state, index ->
+//     when state is
+//         A e0 e1 ->
+//             when index is
+//                 0 -> Keep (Decode.custom ... Ok val -> Ok (A (Ok val) e1) ...)
+//                 1 -> Keep (Decode.custom ... Ok val -> Ok (A e0 (Ok val)) ...)
+//                 _ -> Skip
+//         B -> Skip
+//         C e0 ->
+//             when index is
+//                 0 -> Keep (Decode.custom ... Ok val -> Ok (C (Ok val)) ...)
+//                 _ -> Skip
+fn decoder_tag_union_step_elem(
+    env: &mut Env,
+    state_var: Variable,
+    decoded_tags: &[DecodedTag],
+    decode_err_var: Variable,
+) -> (Expr, Variable) {
+    let state_arg_symbol = env.new_symbol("state");
+    let index_arg_symbol = env.new_symbol("index");
+    let keep_payload_var = env.subs.fresh_unnamed_flex_var();
+    let keep_or_skip_var = keep_or_skip_var(env, keep_payload_var);
+
+    let body = if decoded_tags.iter().all(|tag| tag.elem_vars.is_empty()) {
+        // There are no payloads to decode, and possibly no tags to match the state against.
+        skip(env, keep_or_skip_var)
+    } else {
+        let mut branches = Vec::with_capacity(decoded_tags.len());
+
+        for tag in decoded_tags {
+            // e0 e1
+            let elem_symbols: Vec<_> = (0..tag.elem_vars.len())
+                .map(|index| env.new_symbol(&format!("e{}", index)))
+                .collect();
+
+            let branch_body = if tag.elem_vars.is_empty() {
+                skip(env, keep_or_skip_var)
+            } else {
+                // +1 because of the default branch.
+                let mut index_branches = Vec::with_capacity(tag.elem_vars.len() + 1);
+
+                for (index, (&payload_var, &elem_var)) in tag
+                    .payload_vars
+                    .iter()
+                    .zip(tag.elem_vars.iter())
+                    .enumerate()
+                {
+                    let mut captures: Vec<_> = elem_symbols
+                        .iter()
+                        .zip(tag.elem_vars.iter())
+                        .enumerate()
+                        .filter(|(i, _)| *i != index)
+                        .map(|(_, (&symbol, &var))| (symbol, var))
+                        .collect();
+                    captures.sort_by_key(|(symbol, _)| *symbol);
+
+                    let (decode_custom, decode_custom_ret_var) = decode_into_state_custom(
+                        env,
+                        payload_var,
+                        state_var,
+                        decode_err_var,
+                        captures,
+                        |env, val| {
+                            // A (Ok val) e1
+                            let mut val = Some(val);
+                            let arguments = elem_symbols
+                                .iter()
+                                .zip(tag.elem_vars.iter())
+                                .enumerate()
+                                .map(|(i, (&symbol, &var))| {
+                                    let arg = if i == index {
+                                        Expr::Tag {
+                                            tag_union_var: elem_var,
+                                            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                                            name: "Ok".into(),
+                                            arguments: vec![(
+                                                payload_var,
+                                                Loc::at_zero(val.take().unwrap()),
+                                            )],
+                                        }
+                                    } else {
+                                        Expr::Var(symbol, var)
+                                    };
+
+                                    (var, Loc::at_zero(arg))
+                                })
+                                .collect();
+
+                            Expr::Tag {
+                                tag_union_var: state_var,
+                                ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                                name: tag.name.clone(),
+                                arguments,
+                            }
+                        },
+                    );
+
+                    env.unify(keep_payload_var, decode_custom_ret_var);
+
+                    // 0 -> Keep (Decode.custom ...)
+                    index_branches.push(WhenBranch {
+                        patterns: vec![WhenBranchPattern {
+                            pattern: Loc::at_zero(Pattern::IntLiteral(
+                                Variable::NAT,
+                                Variable::NATURAL,
+                                index.to_string().into_boxed_str(),
+                                IntValue::I128((index as i128).to_ne_bytes()),
+                                IntBound::Exact(IntLitWidth::Nat),
+                            )),
+                            degenerate: false,
+                        }],
+                        value: Loc::at_zero(Expr::Tag {
+                            tag_union_var: keep_or_skip_var,
+                            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                            name: "Keep".into(),
+                            arguments: vec![(decode_custom_ret_var, Loc::at_zero(decode_custom))],
+                        }),
+                        guard: None,
+                        redundant: RedundantMark::known_non_redundant(),
+                    });
+                }
+
+                // _ -> Skip
+                index_branches.push(WhenBranch {
+                    patterns: vec![WhenBranchPattern {
+                        pattern: Loc::at_zero(Pattern::Underscore),
+                        degenerate: false,
+                    }],
+                    value: Loc::at_zero(skip(env, keep_or_skip_var)),
+                    guard: None,
+                    redundant: RedundantMark::known_non_redundant(),
+                });
+
+                // when index is
+                Expr::When {
+                    loc_cond: Box::new(Loc::at_zero(Expr::Var(index_arg_symbol, Variable::NAT))),
+                    cond_var: Variable::NAT,
+                    expr_var: keep_or_skip_var,
+                    region: Region::zero(),
+                    branches: index_branches,
+                    branches_cond_var: Variable::NAT,
+                    exhaustive: ExhaustiveMark::known_exhaustive(),
+                }
+            };
+
+            // A e0 e1 -> ...
+            branches.push(WhenBranch {
+                patterns: vec![WhenBranchPattern {
+                    pattern: Loc::at_zero(Pattern::AppliedTag {
+                        whole_var: state_var,
+                        ext_var: Variable::EMPTY_TAG_UNION,
+                        tag_name: tag.name.clone(),
+                        arguments: elem_symbols
+                            .iter()
+                            .zip(tag.elem_vars.iter())
+                            .map(|(&symbol, &var)| (var, Loc::at_zero(Pattern::Identifier(symbol))))
+                            .collect(),
+                    }),
+                    degenerate: false,
+                }],
+                value: Loc::at_zero(branch_body),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            });
+        }
+
+        // when state is
+        Expr::When {
+            loc_cond: Box::new(Loc::at_zero(Expr::Var(state_arg_symbol, state_var))),
+            cond_var: state_var,
+            expr_var: keep_or_skip_var,
+            region: Region::zero(),
+            branches,
+            branches_cond_var: state_var,
+            exhaustive: ExhaustiveMark::known_exhaustive(),
+        }
+    };
+
+    let step_elem_symbol = env.new_symbol("stepElem");
+
+    build_step_closure(
+        env,
+        step_elem_symbol,
+        vec![
+            (state_arg_symbol, state_var),
+            (index_arg_symbol, Variable::NAT),
+        ],
+        (body, keep_or_skip_var),
+    )
+}
+
+// Example:
+// finalizer = \state ->
+//     when state is
+//         A (Ok v0) (Ok v1) -> Ok (A v0 v1)
+//         B -> Ok B
+//         C (Ok v0) -> Ok (C v0)
+//         _ -> Err TooShort
+fn decoder_tag_union_finalizer(
+    env: &mut Env,
+    state_var: Variable,
+    decoded_tags: &[DecodedTag],
+) -> (Expr, Variable, Variable) {
+    let state_arg_symbol = env.new_symbol("state");
+    let decode_err_var = {
+        let flat_type = FlatType::TagUnion(
+            UnionTags::tag_without_arguments(env.subs, "TooShort".into()),
+            Variable::EMPTY_TAG_UNION,
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    // The decoded tag union, e.g. [A a b, B, C c]
+    let decoded_var = {
+        let union_tags = UnionTags::insert_into_subs(
+            env.subs,
+            decoded_tags
+                .iter()
+                .map(|tag| (tag.name.clone(), tag.payload_vars.clone())),
+        );
+
+        synth_var(
+            env.subs,
+            Content::Structure(FlatType::TagUnion(union_tags, Variable::EMPTY_TAG_UNION)),
+        )
+    };
+
+    let return_type_var = {
+        let flat_type = FlatType::TagUnion(
+            UnionTags::for_result(env.subs, decoded_var, decode_err_var),
+            Variable::EMPTY_TAG_UNION,
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    // +1 because of the default branch.
+    let mut branches = Vec::with_capacity(decoded_tags.len() + 1);
+
+    for tag in decoded_tags {
+        // v0 v1
+        let payload_symbols: Vec<_> = (0..tag.payload_vars.len())
+            .map(|index| env.new_symbol(&format!("v{}", index)))
+            .collect();
+
+        // A (Ok v0) (Ok v1)
+        let pattern = Pattern::AppliedTag {
+            whole_var: state_var,
+            ext_var: Variable::EMPTY_TAG_UNION,
+            tag_name: tag.name.clone(),
+            arguments: (payload_symbols.iter())
+                .zip(tag.payload_vars.iter())
+                .zip(tag.elem_vars.iter())
+                .map(|((&symbol, &payload_var), &elem_var)| {
+                    let ok_pattern = Pattern::AppliedTag {
+                        whole_var: elem_var,
+                        ext_var: Variable::EMPTY_TAG_UNION,
+                        tag_name: "Ok".into(),
+                        arguments: vec![(payload_var, Loc::at_zero(Pattern::Identifier(symbol)))],
+                    };
+
+                    (elem_var, Loc::at_zero(ok_pattern))
+                })
+                .collect(),
+        };
+
+        // Ok (A v0 v1)
+        let decoded = Expr::Tag {
+            tag_union_var: decoded_var,
+            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+            name: tag.name.clone(),
+            arguments: (payload_symbols.iter())
+                .zip(tag.payload_vars.iter())
+                .map(|(&symbol, &var)| (var, Loc::at_zero(Expr::Var(symbol, var))))
+                .collect(),
+        };
+        let ok_decoded = Expr::Tag {
+            tag_union_var: return_type_var,
+            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+            name: "Ok".into(),
+            arguments: vec![(decoded_var, Loc::at_zero(decoded))],
+        };
+
+        branches.push(WhenBranch {
+            patterns: vec![WhenBranchPattern {
+                pattern: Loc::at_zero(pattern),
+                degenerate: false,
+            }],
+            value: Loc::at_zero(ok_decoded),
+            guard: None,
+            redundant: RedundantMark::known_non_redundant(),
+        });
+    }
+
+    // Only add the default branch if some element may have failed to decode, or there are no
+    // tags at all; otherwise it would be redundant.
+    if decoded_tags.is_empty() || decoded_tags.iter().any(|tag| !tag.elem_vars.is_empty()) {
+        // _ -> Err TooShort
+        branches.push(WhenBranch {
+            patterns: vec![WhenBranchPattern {
+                pattern: Loc::at_zero(Pattern::Underscore),
+                degenerate: false,
+            }],
+            value: Loc::at_zero(Expr::Tag {
+                tag_union_var: return_type_var,
+                ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                name: "Err".into(),
+                arguments: vec![(
+                    decode_err_var,
+                    Loc::at_zero(Expr::Tag {
+                        tag_union_var: decode_err_var,
+                        ext_var: Variable::EMPTY_TAG_UNION,
+                        name: "TooShort".into(),
+                        arguments: Vec::new(),
+                    }),
+                )],
+            }),
+            guard: None,
+            redundant: RedundantMark::known_non_redundant(),
+        });
+    }
+
+    // when state is
+    let body = Expr::When {
+        loc_cond: Box::new(Loc::at_zero(Expr::Var(state_arg_symbol, state_var))),
+        cond_var: state_var,
+        expr_var: return_type_var,
+        region: Region::zero(),
+        branches,
+        branches_cond_var: state_var,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    let finalizer_symbol = env.new_symbol("finalizer");
+    let (finalizer, finalizer_var) = build_step_closure(
+        env,
+        finalizer_symbol,
+        vec![(state_arg_symbol, state_var)],
+        (body, return_type_var),
+    );
+
+    (finalizer, finalizer_var, decode_err_var)
+}
+
+// [Keep payload, Skip]
+fn keep_or_skip_var(env: &mut Env, keep_payload_var: Variable) -> Variable {
+    let keep_payload_subs_slice = SubsSlice::insert_into_subs(env.subs, [keep_payload_var]);
+    let flat_type = FlatType::TagUnion(
+        UnionTags::insert_slices_into_subs(
+            env.subs,
+            [
+                ("Keep".into(), keep_payload_subs_slice),
+                ("Skip".into(), Default::default()),
+            ],
+        ),
+        Variable::EMPTY_TAG_UNION,
+    );
+
+    synth_var(env.subs, Content::Structure(flat_type))
+}
+
+fn skip(env: &mut Env, keep_or_skip_var: Variable) -> Expr {
+    Expr::Tag {
+        tag_union_var: keep_or_skip_var,
+        ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+        name: "Skip".into(),
+        arguments: Vec::new(),
+    }
+}
+
+// Builds a top-level, non-capturing closure `\arg1, arg2, ... -> body` named `fn_name`.
+fn build_step_closure(
+    env: &mut Env,
+    fn_name: Symbol,
+    arguments: Vec<(Symbol, Variable)>,
+    body: (Expr, Variable),
+) -> (Expr, Variable) {
+    let (body, body_var) = body;
+
+    // Create fn_var for ambient capture; we fix it up below.
+    let fn_var = synth_var(env.subs, Content::Error);
+    let fn_clos_var = {
+        let lambda_set = LambdaSet {
+            solved: UnionLambdas::tag_without_arguments(env.subs, fn_name),
+            recursion_var: OptVariable::NONE,
+            unspecialized: Default::default(),
+            ambient_function: fn_var,
+        };
+
+        synth_var(env.subs, Content::LambdaSet(lambda_set))
+    };
+
+    let args_slice = SubsSlice::insert_into_subs(env.subs, arguments.iter().map(|(_, var)| *var));
+    env.subs.set_content(
+        fn_var,
+        Content::Structure(FlatType::Func(args_slice, fn_clos_var, body_var)),
+    );
+
+    let closure = Expr::Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: body_var,
+        name: fn_name,
+        captured_symbols: Vec::new(),
+        recursive: Recursive::NotRecursive,
+        arguments: arguments
+            .into_iter()
+            .map(|(symbol, var)| {
+                (
+                    var,
+                    AnnotatedMark::known_exhaustive(),
+                    Loc::at_zero(Pattern::Identifier(symbol)),
+                )
+            })
+            .collect(),
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    (closure, fn_var)
+}
+
 fn decoder_list(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable) {
     // Build
     //
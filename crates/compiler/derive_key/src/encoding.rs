@@ -105,6 +105,9 @@ impl FlatEncodable {
                 }
                 FlatType::EmptyRecord => Ok(Key(FlatEncodableKey::Record(vec![]))),
                 FlatType::EmptyTagUnion => Ok(Key(FlatEncodableKey::TagUnion(vec![]))),
+                FlatType::Tuple(..) | FlatType::EmptyTuple => {
+                    Err(Underivable) // yet
+                }
                 //
                 FlatType::Func(..) => Err(Underivable),
             },
@@ -102,6 +102,9 @@ impl FlatHash {
                 ))),
                 FlatType::EmptyRecord => Ok(Key(FlatHashKey::Record(vec![]))),
                 FlatType::EmptyTagUnion => Ok(Key(FlatHashKey::TagUnion(vec![]))),
+                FlatType::Tuple(..) | FlatType::EmptyTuple => {
+                    Err(Underivable) // yet
+                }
                 //
                 FlatType::Func(..) => Err(Underivable),
             },
@@ -835,6 +835,11 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         ret_reg64(buf, AArch64GeneralReg::LR)
     }
 
+    #[inline(always)]
+    fn trap(buf: &mut Vec<'_, u8>) {
+        brk(buf, 0)
+    }
+
     fn and_reg64_reg64_reg64(
         _buf: &mut Vec<'_, u8>,
         _dst: AArch64GeneralReg,
@@ -1285,6 +1290,14 @@ fn ret_reg64(buf: &mut Vec<'_, u8>, xn: AArch64GeneralReg) {
     buf.extend(inst.bytes());
 }
 
+/// `BRK #imm16` -> Raise a breakpoint exception.
+#[inline(always)]
+fn brk(buf: &mut Vec<'_, u8>, imm16: u16) {
+    let inst = 0xD420_0000u32 | ((imm16 as u32) << 5);
+
+    buf.extend(inst.to_le_bytes());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1509,4 +1522,9 @@ mod tests {
             ALL_GENERAL_REGS
         );
     }
+
+    #[test]
+    fn test_brk() {
+        disassembler_test!(brk, |imm| format!("brk #0x{:x}", imm), [TEST_U16]);
+    }
 }
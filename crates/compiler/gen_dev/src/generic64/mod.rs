@@ -1,17 +1,19 @@
 use crate::{
-    single_register_floats, single_register_int_builtins, single_register_integers, Backend, Env,
-    Relocation,
+    single_register_floats, single_register_int_builtins, single_register_integers,
+    AssemblyBackendMode, Backend, Env, Relocation,
 };
 use bumpalo::collections::Vec;
 use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
 use roc_collections::all::MutMap;
 use roc_error_macros::internal_error;
-use roc_module::symbol::{Interns, Symbol};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::code_gen_help::CodeGenHelp;
 use roc_mono::ir::{
-    BranchInfo, JoinPointId, ListLiteralElement, Literal, Param, ProcLayout, SelfRecursive, Stmt,
+    BranchInfo, JoinPointId, ListLiteralElement, Literal, LookupType, Param, ProcLayout,
+    SelfRecursive, Stmt,
 };
 use roc_mono::layout::{Builtin, Layout, TagIdIntType, UnionLayout};
+use roc_region::all::Region;
 use roc_target::TargetInfo;
 use std::marker::PhantomData;
 
@@ -344,6 +346,9 @@ pub trait Assembler<GeneralReg: RegTrait, FloatReg: RegTrait>: Sized + Copy {
     fn set_if_overflow(buf: &mut Vec<'_, u8>, dst: GeneralReg);
 
     fn ret(buf: &mut Vec<'_, u8>);
+
+    /// Stops the program, for code that must never be reached.
+    fn trap(buf: &mut Vec<'_, u8>);
 }
 
 pub trait RegTrait:
@@ -776,6 +781,50 @@ impl<
         }
     }
 
+    fn build_expect_frame(
+        &mut self,
+        condition: Option<&Symbol>,
+        module_id: ModuleId,
+        region: Region,
+        lookups: &[Symbol],
+        variables: &[LookupType],
+        notify: Option<&str>,
+    ) {
+        let condition = match condition {
+            Some(condition) => condition,
+            None => return self.write_expect_frame(module_id, region, lookups, variables, notify),
+        };
+
+        // The frame is written with function calls, which may move symbols around.
+        // Put everything on the stack so that skipping the frame leaves the storage as the calls do.
+        self.storage_manager.free_all_to_stack(&mut self.buf);
+        let cond_reg = self
+            .storage_manager
+            .load_to_general_reg(&mut self.buf, condition);
+        self.storage_manager.free_all_to_stack(&mut self.buf);
+
+        // Skip the frame if the condition holds.
+        // Since we don't know the offset yet, set it to 0 and overwrite later.
+        let jne_location = self.buf.len();
+        let start_offset = ASM::jne_reg64_imm64_imm32(&mut self.buf, cond_reg, 0, 0);
+
+        let mut base_storage = self.storage_manager.clone();
+        self.write_expect_frame(module_id, region, lookups, variables, notify);
+
+        // Everything the frame needed is freed again, but the stack space it used must be kept.
+        base_storage.update_stack_size(self.storage_manager.stack_size());
+        base_storage.update_fn_call_stack_size(self.storage_manager.fn_call_stack_size());
+        self.storage_manager = base_storage;
+
+        // Overwrite the original jne with the correct offset.
+        let mut tmp = bumpalo::vec![in self.env.arena];
+        let jne_offset = self.buf.len() - start_offset;
+        ASM::jne_reg64_imm64_imm32(&mut tmp, cond_reg, 0, jne_offset as i32);
+        for (i, byte) in tmp.iter().enumerate() {
+            self.buf[jne_location + i] = *byte;
+        }
+    }
+
     fn build_num_abs(&mut self, dst: &Symbol, src: &Symbol, layout: &Layout<'a>) {
         match layout {
             Layout::Builtin(Builtin::Int(IntWidth::I64 | IntWidth::U64)) => {
@@ -1324,6 +1373,10 @@ impl<
         self.free_symbol(&Symbol::DEV_TMP5);
     }
 
+    fn build_unreachable(&mut self) {
+        ASM::trap(&mut self.buf);
+    }
+
     fn build_ptr_cast(&mut self, dst: &Symbol, src: &Symbol) {
         let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
         self.storage_manager
@@ -1693,6 +1746,251 @@ impl<
         CC: CallConv<GeneralReg, FloatReg, ASM>,
     > Backend64Bit<'a, GeneralReg, FloatReg, ASM, CC>
 {
+    /// Writes a frame for an expect or dbg to the shared memory.
+    /// Mirrors `clone_to_shared_memory` in gen_llvm, but leaves the bookkeeping to the builtins.
+    fn write_expect_frame(
+        &mut self,
+        module_id: ModuleId,
+        region: Region,
+        lookups: &[Symbol],
+        variables: &[LookupType],
+        notify: Option<&str>,
+    ) {
+        let u32_layout = Layout::Builtin(Builtin::Int(IntWidth::U32));
+        let u64_layout = Layout::Builtin(Builtin::Int(IntWidth::U64));
+        let target_info = self.storage_manager.target_info();
+
+        let mut shapes = std::vec::Vec::with_capacity(lookups.len());
+        for lookup in lookups {
+            let layout = *self
+                .layout_map
+                .get(lookup)
+                .unwrap_or_else(|| internal_error!("expect lookup {:?} has no layout", lookup));
+            let mut shape = std::vec::Vec::new();
+            self.expect_clone_shape(&layout, &mut shape);
+
+            shapes.push((layout, shape));
+        }
+
+        let shared_memory = Symbol::DEV_TMP;
+        let start_fn = match self.env.mode {
+            AssemblyBackendMode::BinaryDev => bitcode::UTILS_EXPECT_FAILED_START_SHARED_FILE,
+            _ => bitcode::UTILS_EXPECT_FAILED_START_SHARED_BUFFER,
+        };
+        self.build_fn_call(&shared_memory, start_fn.to_string(), &[], &[], &u64_layout);
+
+        // The offset into the shared memory moves along as values are written.
+        // It alternates between two symbols, since a call can not write to one of its arguments.
+        let mut offset = Symbol::DEV_TMP4;
+        let mut next_offset = Symbol::DEV_TMP5;
+
+        let region_bits = region.start().offset as u64 | (region.end().offset as u64) << 32;
+        let module_id: u32 = unsafe { std::mem::transmute(module_id) };
+        self.load_int_literal(&Symbol::DEV_TMP2, &u64_layout, region_bits);
+        self.load_int_literal(&Symbol::DEV_TMP3, &u32_layout, module_id as u64);
+        self.load_int_literal(&next_offset, &u64_layout, lookups.len() as u64);
+        self.build_fn_call(
+            &offset,
+            bitcode::UTILS_EXPECT_FRAME_START.to_string(),
+            &[
+                shared_memory,
+                Symbol::DEV_TMP2,
+                Symbol::DEV_TMP3,
+                next_offset,
+            ],
+            &[u64_layout, u64_layout, u32_layout, u64_layout],
+            &u64_layout,
+        );
+        self.free_symbol(&Symbol::DEV_TMP2);
+        self.free_symbol(&Symbol::DEV_TMP3);
+        self.free_symbol(&next_offset);
+
+        for (index, ((lookup, variable), (layout, shape))) in
+            lookups.iter().zip(variables).zip(shapes).enumerate()
+        {
+            self.load_int_literal(&Symbol::DEV_TMP2, &u64_layout, index as u64);
+            self.load_int_literal(&Symbol::DEV_TMP3, &u32_layout, variable.index() as u64);
+            self.build_fn_call(
+                &next_offset,
+                bitcode::UTILS_EXPECT_FRAME_LOOKUP.to_string(),
+                &[shared_memory, Symbol::DEV_TMP2, offset, Symbol::DEV_TMP3],
+                &[u64_layout, u64_layout, u64_layout, u32_layout],
+                &Layout::UNIT,
+            );
+            self.free_symbol(&Symbol::DEV_TMP2);
+            self.free_symbol(&Symbol::DEV_TMP3);
+
+            let stack_size = layout.stack_size(self.env.layout_interner, target_info);
+
+            if stack_size == 0 {
+                continue;
+            }
+
+            self.build_ptr_cast(&Symbol::DEV_TMP2, lookup);
+            self.load_int_literal(&Symbol::DEV_TMP3, &u64_layout, stack_size as u64);
+
+            if layout.safe_to_memcpy(self.env.layout_interner) {
+                self.build_fn_call(
+                    &next_offset,
+                    bitcode::UTILS_EXPECT_FRAME_COPY.to_string(),
+                    &[shared_memory, offset, Symbol::DEV_TMP2, Symbol::DEV_TMP3],
+                    &[u64_layout, u64_layout, u64_layout, u64_layout],
+                    &u64_layout,
+                );
+            } else {
+                // Whatever the value points to goes right after the value itself.
+                let shape_array = self.unique_symbol();
+                let shape_ptr = self.unique_symbol();
+                let shape_len = self.unique_symbol();
+                self.load_u64_array(&shape_array, &shape);
+                self.build_ptr_cast(&shape_ptr, &shape_array);
+                self.load_int_literal(&shape_len, &u64_layout, shape.len() as u64);
+                self.build_fn_call(
+                    &next_offset,
+                    bitcode::UTILS_EXPECT_FRAME_CLONE.to_string(),
+                    &[
+                        shared_memory,
+                        offset,
+                        Symbol::DEV_TMP2,
+                        Symbol::DEV_TMP3,
+                        shape_ptr,
+                        shape_len,
+                    ],
+                    &[u64_layout; 6],
+                    &u64_layout,
+                );
+                self.free_symbol(&shape_array);
+                self.free_symbol(&shape_ptr);
+                self.free_symbol(&shape_len);
+            }
+            self.free_symbol(&Symbol::DEV_TMP2);
+            self.free_symbol(&Symbol::DEV_TMP3);
+            self.free_symbol(&offset);
+            std::mem::swap(&mut offset, &mut next_offset);
+        }
+
+        self.build_fn_call(
+            &next_offset,
+            bitcode::UTILS_EXPECT_FRAME_FINISH.to_string(),
+            &[shared_memory, offset],
+            &[u64_layout, u64_layout],
+            &Layout::UNIT,
+        );
+        self.free_symbol(&offset);
+
+        if let Some(notify) = notify {
+            self.build_fn_call(
+                &next_offset,
+                notify.to_string(),
+                &[shared_memory],
+                &[u64_layout],
+                &Layout::UNIT,
+            );
+        }
+        self.free_symbol(&shared_memory);
+    }
+
+    /// Describes how `expect_frame_clone` copies a value of this layout into the shared memory.
+    /// See expect.zig for the format.
+    fn expect_clone_shape(&self, layout: &Layout<'a>, shape: &mut std::vec::Vec<u64>) {
+        const SHAPE_COPY: u64 = 0;
+        const SHAPE_SKIP: u64 = 1;
+        const SHAPE_STR: u64 = 2;
+        const SHAPE_LIST: u64 = 3;
+        const SHAPE_STRUCT: u64 = 4;
+        const SHAPE_UNION: u64 = 5;
+        const SHAPE_BOX: u64 = 6;
+
+        let interner = self.env.layout_interner;
+        let target_info = self.storage_manager.target_info();
+
+        // Each nested shape is preceded by its length, which is only known once it's written.
+        let nested = |backend: &Self, shape: &mut std::vec::Vec<u64>, layout: &Layout<'a>| {
+            let len_index = shape.len();
+            shape.push(0);
+            backend.expect_clone_shape(layout, shape);
+            shape[len_index] = (shape.len() - len_index - 1) as u64;
+        };
+
+        match layout {
+            // Functions are never shown, so there is no need to copy them
+            Layout::LambdaSet(_) => {
+                shape.push(SHAPE_SKIP);
+            }
+            _ if layout.safe_to_memcpy(interner) => {
+                let size = layout.stack_size(interner, target_info);
+                shape.extend([SHAPE_COPY, size as u64]);
+            }
+            Layout::Builtin(Builtin::Str) => {
+                shape.push(SHAPE_STR);
+            }
+            Layout::Builtin(Builtin::List(elem_layout)) => {
+                let elem_width = elem_layout.stack_size(interner, target_info);
+                shape.extend([SHAPE_LIST, elem_width as u64]);
+                nested(self, shape, elem_layout);
+            }
+            Layout::Struct { field_layouts, .. } => {
+                shape.extend([SHAPE_STRUCT, field_layouts.len() as u64]);
+                let mut field_offset = 0;
+                for field_layout in field_layouts.iter() {
+                    shape.push(field_offset as u64);
+                    nested(self, shape, field_layout);
+                    field_offset += field_layout.stack_size(interner, target_info);
+                }
+            }
+            Layout::Union(union_layout @ UnionLayout::NonRecursive(tags)) => {
+                let size = layout.stack_size(interner, target_info);
+                let tag_id_offset = union_layout
+                    .tag_id_offset(interner, target_info)
+                    .unwrap_or(0);
+                let tag_id_size = union_layout.discriminant().stack_size();
+                shape.extend([
+                    SHAPE_UNION,
+                    size as u64,
+                    tag_id_offset as u64,
+                    tag_id_size as u64,
+                    tags.len() as u64,
+                ]);
+                for field_layouts in tags.iter() {
+                    nested(self, shape, &Layout::struct_no_name_order(field_layouts));
+                }
+            }
+            Layout::Boxed(inner_layout) => {
+                let inner_size = inner_layout.stack_size(interner, target_info);
+                shape.extend([SHAPE_BOX, inner_size as u64]);
+                nested(self, shape, inner_layout);
+            }
+            x => todo!("showing the value of {:?} in an expect", x),
+        }
+    }
+
+    /// Stores the words in a new stack allocation for the symbol.
+    fn load_u64_array(&mut self, sym: &Symbol, words: &[u64]) {
+        let base_offset = self
+            .storage_manager
+            .claim_stack_area(sym, 8 * words.len() as u32);
+        self.storage_manager
+            .with_tmp_general_reg(&mut self.buf, |_storage_manager, buf, reg| {
+                for (i, word) in words.iter().enumerate() {
+                    ASM::mov_reg64_imm64(buf, reg, *word as i64);
+                    ASM::mov_base32_reg64(buf, base_offset + 8 * i as i32, reg);
+                }
+            });
+    }
+
+    /// A fresh symbol for a temporary value, for when the `DEV_TMP` symbols are all taken.
+    fn unique_symbol(&mut self) -> Symbol {
+        let module_id = self.env.module_id;
+        let ident_ids = self.interns.all_ident_ids.get_mut(&module_id).unwrap();
+
+        Symbol::new(module_id, ident_ids.gen_unique())
+    }
+
+    /// Loads an integer constant into a symbol.
+    fn load_int_literal(&mut self, sym: &Symbol, layout: &Layout<'a>, value: u64) {
+        self.load_literal(sym, layout, &Literal::Int((value as i128).to_ne_bytes()));
+    }
+
     /// Updates a jump instruction to a new offset and returns the number of bytes written.
     fn update_jmp_imm32_offset(
         &mut self,
@@ -1400,6 +1400,11 @@ impl Assembler<X86_64GeneralReg, X86_64FloatReg> for X86_64Assembler {
         ret(buf);
     }
 
+    #[inline(always)]
+    fn trap(buf: &mut Vec<'_, u8>) {
+        ud2(buf);
+    }
+
     fn set_if_overflow(buf: &mut Vec<'_, u8>, dst: X86_64GeneralReg) {
         seto_reg64(buf, dst);
     }
@@ -2176,6 +2181,12 @@ fn ret(buf: &mut Vec<'_, u8>) {
     buf.push(0xC3);
 }
 
+/// `UD2` -> Raise an invalid opcode exception.
+#[inline(always)]
+fn ud2(buf: &mut Vec<'_, u8>) {
+    buf.extend([0x0F, 0x0B]);
+}
+
 /// `SUB r/m64, imm32` -> Subtract imm32 sign-extended to 64-bits from r/m64.
 #[inline(always)]
 fn sub_reg64_imm32(buf: &mut Vec<'_, u8>, dst: X86_64GeneralReg, imm: i32) {
@@ -2688,6 +2699,11 @@ mod tests {
         disassembler_test!(ret, || "ret");
     }
 
+    #[test]
+    fn test_ud2() {
+        disassembler_test!(ud2, || "ud2");
+    }
+
     #[test]
     fn test_sub_reg64_imm32() {
         disassembler_test!(
@@ -14,18 +14,41 @@ use roc_module::low_level::{LowLevel, LowLevelWrapperType};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::code_gen_help::CodeGenHelp;
 use roc_mono::ir::{
-    BranchInfo, CallType, Expr, JoinPointId, ListLiteralElement, Literal, Param, Proc, ProcLayout,
-    SelfRecursive, Stmt,
+    BranchInfo, CallType, Expr, JoinPointId, ListLiteralElement, Literal, LookupType, Param, Proc,
+    ProcLayout, SelfRecursive, Stmt,
 };
 use roc_mono::layout::{
     Builtin, Layout, LayoutId, LayoutIds, STLayoutInterner, TagIdIntType, UnionLayout,
 };
+use roc_region::all::Region;
 
 mod generic64;
 mod object_builder;
 pub use object_builder::build_module;
 mod run_roc;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssemblyBackendMode {
+    /// Expects and dbg statements are compiled away.
+    Binary,
+    /// Failed expects and dbg statements are reported to the parent process (e.g. `roc dev`)
+    /// through a shared file.
+    BinaryDev,
+    /// Failed expects and dbg statements are written to the buffer that the test runner
+    /// provides with `set_shared_buffer`. Crashes exit the process.
+    Test,
+}
+
+impl AssemblyBackendMode {
+    fn runs_expects(self) -> bool {
+        match self {
+            AssemblyBackendMode::Binary => false,
+            AssemblyBackendMode::BinaryDev => true,
+            AssemblyBackendMode::Test => true,
+        }
+    }
+}
+
 pub struct Env<'a> {
     pub arena: &'a Bump,
     pub layout_interner: &'a STLayoutInterner<'a>,
@@ -33,6 +56,7 @@ pub struct Env<'a> {
     pub exposed_to_host: MutSet<Symbol>,
     pub lazy_literals: bool,
     pub generate_allocators: bool,
+    pub mode: AssemblyBackendMode,
 }
 
 // These relocations likely will need a length.
@@ -212,7 +236,91 @@ trait Backend<'a> {
                 self.build_jump(id, args, arg_layouts.into_bump_slice(), ret_layout);
                 self.free_symbols(stmt);
             }
-            x => todo!("the statement, {:?}", x),
+            Stmt::Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                if self.env().mode.runs_expects() {
+                    self.load_literal_symbols(&[*symbol]);
+                    // The parent finds a dbg by its symbol, which takes the place of the region.
+                    let region = unsafe { std::mem::transmute::<_, Region>(*symbol) };
+                    self.build_expect_frame(
+                        None,
+                        symbol.module_id(),
+                        region,
+                        &[*symbol],
+                        &[*variable],
+                        Some(bitcode::NOTIFY_PARENT_DBG),
+                    );
+                }
+                self.free_symbols(stmt);
+                self.build_stmt(remainder, ret_layout);
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            }
+            | Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                if self.env().mode.runs_expects() {
+                    self.load_literal_symbols(&[*condition]);
+                    self.load_literal_symbols(lookups);
+                    // Only `roc dev` waits on the child to render a failed `expect`.
+                    // The test runner reads all of the frames once the test is done.
+                    let notify = match (stmt, self.env().mode) {
+                        (Stmt::Expect { .. }, AssemblyBackendMode::BinaryDev) => {
+                            Some(bitcode::NOTIFY_PARENT_EXPECT)
+                        }
+                        _ => None,
+                    };
+                    self.build_expect_frame(
+                        Some(condition),
+                        condition.module_id(),
+                        *region,
+                        lookups,
+                        variables,
+                        notify,
+                    );
+                }
+                self.free_symbols(stmt);
+                self.build_stmt(remainder, ret_layout);
+            }
+            Stmt::Crash(msg, tag) => {
+                self.load_literal_symbols(&[*msg]);
+
+                // roc_panic takes the message by pointer, and the tag as a u32.
+                let u64_layout = Layout::Builtin(Builtin::Int(IntWidth::U64));
+                let u32_layout = Layout::Builtin(Builtin::Int(IntWidth::U32));
+                self.build_ptr_cast(&Symbol::DEV_TMP, msg);
+                self.load_literal(
+                    &Symbol::DEV_TMP2,
+                    &u32_layout,
+                    &Literal::Int((*tag as u32 as i128).to_ne_bytes()),
+                );
+                self.build_fn_call(
+                    &Symbol::DEV_TMP3,
+                    "roc_panic".to_string(),
+                    &[Symbol::DEV_TMP, Symbol::DEV_TMP2],
+                    &[u64_layout, u32_layout],
+                    &Layout::UNIT,
+                );
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+                self.free_symbol(&Symbol::DEV_TMP3);
+
+                // roc_panic must not return, but in case the host's does, stop here.
+                self.build_unreachable();
+                self.free_symbols(stmt);
+            }
         }
     }
     // build_switch generates a instructions for a switch statement.
@@ -756,6 +864,20 @@ trait Backend<'a> {
         ret_layout: &Layout<'a>,
     );
 
+    /// build_expect_frame writes the values of the lookups to the memory shared with the parent
+    /// process, in the format that `roc_repl_expect` reads. If there is a condition, the frame is
+    /// only written when the condition is false. `notify` is the builtin that hands the frame
+    /// over to the parent, if it should be called right away.
+    fn build_expect_frame(
+        &mut self,
+        condition: Option<&Symbol>,
+        module_id: ModuleId,
+        region: Region,
+        lookups: &[Symbol],
+        variables: &[LookupType],
+        notify: Option<&str>,
+    );
+
     /// build_num_abs stores the absolute value of src into dst.
     fn build_num_abs(&mut self, dst: &Symbol, src: &Symbol, layout: &Layout<'a>);
 
@@ -871,6 +993,9 @@ trait Backend<'a> {
     /// build_refcount_getptr loads the pointer to the reference count of src into dst.
     fn build_ptr_cast(&mut self, dst: &Symbol, src: &Symbol);
 
+    /// build_unreachable stops the program, for code that must never be reached.
+    fn build_unreachable(&mut self);
+
     /// literal_map gets the map from symbol to literal and layout, used for lazy loading and literal folding.
     fn literal_map(&mut self) -> &mut MutMap<Symbol, (*const Literal<'a>, *const Layout<'a>)>;
 
@@ -1107,11 +1232,34 @@ trait Backend<'a> {
                 }
             }
 
-            Stmt::Dbg { .. } => todo!("dbg not implemented in the dev backend"),
-            Stmt::Expect { .. } => todo!("expect is not implemented in the dev backend"),
-            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the dev backend"),
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                self.set_last_seen(*symbol, stmt);
+                self.scan_ast(remainder);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            }
+            | Stmt::ExpectFx {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                self.set_last_seen(*condition, stmt);
+                for sym in *lookups {
+                    self.set_last_seen(*sym, stmt);
+                }
+                self.scan_ast(remainder);
+            }
 
-            Stmt::Crash(..) => todo!("crash is not implemented in the dev backend"),
+            Stmt::Crash(msg, _) => {
+                self.set_last_seen(*msg, stmt);
+            }
         }
     }
 
@@ -12,6 +12,7 @@ use roc_mono::ir::{
     Param, Proc, ProcLayout, Stmt,
 };
 use roc_mono::layout::{Builtin, Layout, LayoutIds, TagIdIntType, UnionLayout};
+use roc_region::all::Region;
 use roc_std::RocDec;
 
 use roc_wasm_module::linking::{DataSymbol, WasmObjectSymbol};
@@ -28,8 +29,8 @@ use crate::layout::{CallConv, ReturnMethod, WasmLayout};
 use crate::low_level::{call_higher_order_lowlevel, LowLevelCall};
 use crate::storage::{AddressValue, Storage, StoredValue, StoredVarKind};
 use crate::{
-    copy_memory, CopyMemoryConfig, Env, DEBUG_SETTINGS, MEMORY_NAME, PTR_SIZE, PTR_TYPE,
-    TARGET_INFO,
+    copy_memory, zero_memory, CopyMemoryConfig, Env, DEBUG_SETTINGS, MEMORY_NAME, PTR_SIZE,
+    PTR_TYPE, TARGET_INFO,
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -112,6 +113,9 @@ impl<'a> WasmBackend<'a> {
         let mut called_fns = BitVec::repeat(false, host_function_count);
         called_fns.extend(std::iter::repeat(true).take(proc_lookup.len()));
 
+        let mut code_builder = CodeBuilder::new(env.arena);
+        code_builder.bulk_memory = env.bulk_memory;
+
         WasmBackend {
             env,
             interns,
@@ -130,7 +134,7 @@ impl<'a> WasmBackend<'a> {
             // Function-level data
             block_depth: 0,
             joinpoint_label_map: MutMap::default(),
-            code_builder: CodeBuilder::new(env.arena),
+            code_builder,
             storage: Storage::new(env.arena),
         }
     }
@@ -219,6 +223,11 @@ impl<'a> WasmBackend<'a> {
                         sym_index as u32,
                         global_value_addr,
                     );
+                    self.module.data.apply_relocs_u32(
+                        &self.module.reloc_data,
+                        sym_index as u32,
+                        global_value_addr,
+                    );
 
                     let global_index = self.module.global.count;
                     self.module.global.append(Global {
@@ -714,9 +723,24 @@ impl<'a> WasmBackend<'a> {
 
             Stmt::Refcounting(modify, following) => self.stmt_refcounting(modify, following),
 
-            Stmt::Dbg { .. } => todo!("dbg is not implemented in the wasm backend"),
-            Stmt::Expect { .. } => todo!("expect is not implemented in the wasm backend"),
-            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the wasm backend"),
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => self.stmt_dbg(*symbol, remainder),
+
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                remainder,
+                ..
+            }
+            | Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                remainder,
+                ..
+            } => self.stmt_expect(*condition, *region, lookups, remainder),
 
             Stmt::Crash(sym, tag) => self.stmt_crash(*sym, *tag),
         }
@@ -988,6 +1012,149 @@ impl<'a> WasmBackend<'a> {
         self.stmt(rc_stmt);
     }
 
+    /// Call the host import `roc_dbg(lookup: *const RocLookup)`.
+    /// See `write_lookups` for the layout of `RocLookup`.
+    fn stmt_dbg(&mut self, symbol: Symbol, remainder: &'a Stmt<'a>) {
+        if self.env.runs_expects && !self.host_provides_fn("roc_dbg") {
+            self.stmt_internal_error(
+                "This program uses `dbg`, but the platform's host does not provide `roc_dbg`",
+            );
+        } else if self.env.runs_expects {
+            let (lookups_ptr, lookups_offset) = self.write_lookups(&[symbol]);
+
+            self.code_builder.get_local(lookups_ptr);
+            if lookups_offset > 0 {
+                self.code_builder.i32_const(lookups_offset as i32);
+                self.code_builder.i32_add();
+            }
+            self.call_host_fn_after_loading_args("roc_dbg", 1, false);
+        }
+
+        self.stmt(remainder);
+    }
+
+    /// If the condition is false, call the host import
+    /// `roc_expect_failed(module: *const RocStr, region_start: u32, region_end: u32, lookups: *const RocLookup, lookup_count: u32)`.
+    /// The region is a byte range in the source of `module`.
+    fn stmt_expect(
+        &mut self,
+        condition: Symbol,
+        region: Region,
+        lookups: &'a [Symbol],
+        remainder: &'a Stmt<'a>,
+    ) {
+        if self.env.runs_expects && !self.host_provides_fn("roc_expect_failed") {
+            self.storage
+                .load_symbols(&mut self.code_builder, &[condition]);
+            self.code_builder.i32_eqz();
+            self.code_builder.if_();
+            self.stmt_internal_error(
+                "An `expect` failed, but the platform's host does not provide `roc_expect_failed` to report it",
+            );
+            self.code_builder.end();
+        } else if self.env.runs_expects {
+            let module_name = self
+                .env
+                .arena
+                .alloc_str(self.interns.module_name(condition.module_id()).as_str());
+            let (module_ptr, module_offset) = self.storage.allocate_anonymous_stack_memory(12, 4);
+            self.expr_string_literal(module_name, module_ptr, module_offset);
+
+            // Write the lookups outside of the `if` block, where the VM stack is still accessible
+            let (lookups_ptr, lookups_offset) = self.write_lookups(lookups);
+
+            self.storage
+                .load_symbols(&mut self.code_builder, &[condition]);
+            self.code_builder.i32_eqz();
+            self.code_builder.if_();
+
+            self.code_builder.get_local(module_ptr);
+            if module_offset > 0 {
+                self.code_builder.i32_const(module_offset as i32);
+                self.code_builder.i32_add();
+            }
+            self.code_builder.i32_const(region.start().offset as i32);
+            self.code_builder.i32_const(region.end().offset as i32);
+            self.code_builder.get_local(lookups_ptr);
+            if lookups_offset > 0 {
+                self.code_builder.i32_const(lookups_offset as i32);
+                self.code_builder.i32_add();
+            }
+            self.code_builder.i32_const(lookups.len() as i32);
+            self.call_host_fn_after_loading_args("roc_expect_failed", 5, false);
+
+            self.code_builder.end();
+        }
+
+        self.stmt(remainder);
+    }
+
+    /// Write an array of `RocLookup` to the stack frame, one for each symbol
+    ///
+    /// ```c
+    /// struct RocLookup {
+    ///     struct RocStr name;  // the variable name
+    ///     uint8_t *value;      // the value, in the same representation as a Roc function argument
+    ///     uint32_t size;       // the size of the value in bytes
+    /// };
+    /// ```
+    fn write_lookups(&mut self, symbols: &[Symbol]) -> (LocalId, u32) {
+        const LOOKUP_SIZE: u32 = 12 + 2 * PTR_SIZE;
+
+        let (array_ptr, array_offset) = self
+            .storage
+            .allocate_anonymous_stack_memory(LOOKUP_SIZE * symbols.len() as u32, PTR_SIZE);
+
+        for (i, symbol) in symbols.iter().enumerate() {
+            let lookup_offset = array_offset + i as u32 * LOOKUP_SIZE;
+
+            let name = self.env.arena.alloc_str(symbol.as_str(self.interns));
+            self.expr_string_literal(name, array_ptr, lookup_offset);
+
+            let storage = self.storage.get(symbol).to_owned();
+            let (value_ptr, value_offset, size) = match storage {
+                StoredValue::StackMemory { location, size, .. } => {
+                    let (local_id, offset) =
+                        location.local_and_offset(self.storage.stack_frame_pointer);
+                    (local_id, offset, size)
+                }
+                StoredValue::VirtualMachineStack { size, .. } | StoredValue::Local { size, .. } => {
+                    // Primitives don't have an address, so copy them to the stack frame
+                    let alignment = self.storage.symbol_layouts[symbol]
+                        .alignment_bytes(self.env.layout_interner, TARGET_INFO);
+                    let (local_id, offset) = self
+                        .storage
+                        .allocate_anonymous_stack_memory(size, alignment);
+                    self.storage.copy_value_to_memory(
+                        &mut self.code_builder,
+                        local_id,
+                        offset,
+                        *symbol,
+                    );
+                    (local_id, offset, size)
+                }
+            };
+
+            // value
+            self.code_builder.get_local(array_ptr);
+            self.code_builder.get_local(value_ptr);
+            if value_offset > 0 {
+                self.code_builder.i32_const(value_offset as i32);
+                self.code_builder.i32_add();
+            }
+            self.code_builder
+                .i32_store(Align::Bytes4, lookup_offset + 12);
+
+            // size
+            self.code_builder.get_local(array_ptr);
+            self.code_builder.i32_const(size as i32);
+            self.code_builder
+                .i32_store(Align::Bytes4, lookup_offset + 12 + PTR_SIZE);
+        }
+
+        (array_ptr, array_offset)
+    }
+
     pub fn stmt_internal_error(&mut self, msg: &'a str) {
         let msg_sym = self.create_symbol("panic_str");
         let msg_storage = self.storage.allocate_var(
@@ -1346,6 +1513,12 @@ impl<'a> WasmBackend<'a> {
         low_level_call.generate(self);
     }
 
+    /// Check whether the host defines or imports a function.
+    /// Some, like `roc_dbg`, are optional, so we can't just assume they exist.
+    fn host_provides_fn(&self, name: &str) -> bool {
+        self.host_lookup.iter().any(|(fn_name, _)| *fn_name == name)
+    }
+
     /// Generate a call instruction to a host function or Zig builtin.
     pub fn call_host_fn_after_loading_args(
         &mut self,
@@ -1586,12 +1759,7 @@ impl<'a> WasmBackend<'a> {
 
             // Store 12 bytes of zeros { elements: null, length: 0, capacity: 0 }
             debug_assert_eq!(Builtin::LIST_WORDS, 3);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i64_const(0);
-            self.code_builder.i64_store(Align::Bytes4, offset);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i32_const(0);
-            self.code_builder.i32_store(Align::Bytes4, offset + 8);
+            zero_memory(&mut self.code_builder, local_id, offset, 12, 4);
         } else {
             internal_error!("Unexpected storage for {:?}", sym)
         }
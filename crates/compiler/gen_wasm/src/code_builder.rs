@@ -5,7 +5,7 @@ use roc_wasm_module::linking::IndexRelocType;
 
 use roc_error_macros::internal_error;
 use roc_module::symbol::Symbol;
-use roc_wasm_module::opcodes::{OpCode, OpCode::*};
+use roc_wasm_module::opcodes::{MiscOp, OpCode, OpCode::*};
 use roc_wasm_module::serialize::SerialBuffer;
 use roc_wasm_module::{
     round_up_to_alignment, Align, LocalId, RelocationEntry, ValueType, WasmModule,
@@ -106,6 +106,9 @@ pub struct CodeBuilder<'a> {
     /// Relocations for calls to JS imports
     /// When we remove unused imports, the live ones are re-indexed
     import_relocations: Vec<'a, (usize, u32)>,
+
+    /// Whether we can use instructions from the bulk memory proposal
+    pub bulk_memory: bool,
 }
 
 #[allow(clippy::new_without_default)]
@@ -127,6 +130,7 @@ impl<'a> CodeBuilder<'a> {
             inner_length: Vec::with_capacity_in(5, arena),
             vm_block_stack,
             import_relocations: Vec::with_capacity_in(0, arena),
+            bulk_memory: false,
         }
     }
 
@@ -552,6 +556,13 @@ impl<'a> CodeBuilder<'a> {
         );
     }
 
+    /// Instruction with the MISC prefix (bulk memory and saturating truncation)
+    fn inst_misc(&mut self, misc_op: MiscOp, pops: usize, push: bool) {
+        self.inst_base(MISC, pops, push);
+        self.code.encode_u32(misc_op as u32);
+        log_instruction!("{:10}		{:?}", format!("{:?}", misc_op), self.vm_block_stack);
+    }
+
     fn inst_mem(&mut self, opcode: OpCode, pops: usize, push: bool, align: Align, offset: u32) {
         self.inst_base(opcode, pops, push);
         self.code.push(align as u8);
@@ -876,4 +887,77 @@ impl<'a> CodeBuilder<'a> {
     instruction_no_args!(i64_reinterpret_f64, I64REINTERPRETF64, 1, true);
     instruction_no_args!(f32_reinterpret_i32, F32REINTERPRETI32, 1, true);
     instruction_no_args!(f64_reinterpret_i64, F64REINTERPRETI64, 1, true);
+
+    /// Pops destination address, source address, and size in bytes
+    pub fn memory_copy(&mut self) {
+        self.inst_misc(MiscOp::MEMORYCOPY, 3, false);
+        self.code.push(0); // destination memory index
+        self.code.push(0); // source memory index
+    }
+    /// Pops destination address, byte value, and size in bytes
+    pub fn memory_fill(&mut self) {
+        self.inst_misc(MiscOp::MEMORYFILL, 3, false);
+        self.code.push(0); // memory index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{copy_memory, zero_memory, CopyMemoryConfig};
+
+    fn misc_ops(code_builder: &CodeBuilder) -> std::vec::Vec<u8> {
+        code_builder
+            .code
+            .windows(2)
+            .filter(|pair| pair[0] == MISC as u8)
+            .map(|pair| pair[1])
+            .collect()
+    }
+
+    fn build(bulk_memory: bool, size: u32) -> (std::vec::Vec<u8>, std::vec::Vec<u8>) {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        code_builder.bulk_memory = bulk_memory;
+
+        zero_memory(&mut code_builder, LocalId(0), 4, size, 4);
+        let fill = misc_ops(&code_builder);
+
+        code_builder.clear();
+        copy_memory(
+            &mut code_builder,
+            CopyMemoryConfig {
+                from_ptr: LocalId(0),
+                from_offset: 0,
+                to_ptr: LocalId(1),
+                to_offset: 4,
+                size,
+                alignment_bytes: 4,
+            },
+        );
+        let copy = misc_ops(&code_builder);
+
+        (fill, copy)
+    }
+
+    #[test]
+    fn bulk_memory_instructions() {
+        assert_eq!(
+            build(true, 64),
+            (
+                vec![MiscOp::MEMORYFILL as u8],
+                vec![MiscOp::MEMORYCOPY as u8]
+            )
+        );
+    }
+
+    #[test]
+    fn no_bulk_memory_for_small_sizes() {
+        assert_eq!(build(true, 12), (vec![], vec![]));
+    }
+
+    #[test]
+    fn no_bulk_memory_when_disabled() {
+        assert_eq!(build(false, 64), (vec![], vec![]));
+    }
 }
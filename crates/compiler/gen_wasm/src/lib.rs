@@ -47,6 +47,12 @@ pub struct Env<'a> {
     pub module_id: ModuleId,
     pub exposed_to_host: MutSet<Symbol>,
     pub stack_bytes: u32,
+    /// Generate code for `dbg` and inline `expect`, reporting to the host imports
+    /// `roc_dbg` and `roc_expect_failed`. When this is off, they are skipped entirely.
+    pub runs_expects: bool,
+    /// Use `memory.copy` for large copies. Only safe if the runtime supports
+    /// the bulk memory proposal, which we know is true if the host uses it.
+    pub bulk_memory: bool,
 }
 
 impl Env<'_> {
@@ -201,6 +207,9 @@ pub struct CopyMemoryConfig {
     alignment_bytes: u32,
 }
 
+/// Below this size, unrolled loads and stores are smaller and faster than `memory.copy` or `memory.fill`
+const MIN_BULK_MEMORY_COPY_SIZE: u32 = 32;
+
 pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     if config.from_ptr == config.to_ptr && config.from_offset == config.to_offset {
         return;
@@ -209,6 +218,22 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
         return;
     }
 
+    if code_builder.bulk_memory && config.size >= MIN_BULK_MEMORY_COPY_SIZE {
+        code_builder.get_local(config.to_ptr);
+        if config.to_offset != 0 {
+            code_builder.i32_const(config.to_offset as i32);
+            code_builder.i32_add();
+        }
+        code_builder.get_local(config.from_ptr);
+        if config.from_offset != 0 {
+            code_builder.i32_const(config.from_offset as i32);
+            code_builder.i32_add();
+        }
+        code_builder.i32_const(config.size as i32);
+        code_builder.memory_copy();
+        return;
+    }
+
     let alignment = Align::from(config.alignment_bytes);
     let mut i = 0;
     while config.size - i >= 8 {
@@ -234,6 +259,48 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     }
 }
 
+/// Write `size` bytes of zeros at `to_offset` from the address in `to_ptr`
+pub fn zero_memory(
+    code_builder: &mut CodeBuilder,
+    to_ptr: LocalId,
+    to_offset: u32,
+    size: u32,
+    alignment_bytes: u32,
+) {
+    if code_builder.bulk_memory && size >= MIN_BULK_MEMORY_COPY_SIZE {
+        code_builder.get_local(to_ptr);
+        if to_offset != 0 {
+            code_builder.i32_const(to_offset as i32);
+            code_builder.i32_add();
+        }
+        code_builder.i32_const(0);
+        code_builder.i32_const(size as i32);
+        code_builder.memory_fill();
+        return;
+    }
+
+    let alignment = Align::from(alignment_bytes);
+    let mut i = 0;
+    while size - i >= 8 {
+        code_builder.get_local(to_ptr);
+        code_builder.i64_const(0);
+        code_builder.i64_store(alignment, i + to_offset);
+        i += 8;
+    }
+    if size - i >= 4 {
+        code_builder.get_local(to_ptr);
+        code_builder.i32_const(0);
+        code_builder.i32_store(alignment, i + to_offset);
+        i += 4;
+    }
+    while size - i > 0 {
+        code_builder.get_local(to_ptr);
+        code_builder.i32_const(0);
+        code_builder.i32_store8(alignment, i + to_offset);
+        i += 1;
+    }
+}
+
 pub struct WasmDebugSettings {
     proc_start_end: bool,
     user_procs_ir: bool,
@@ -70,7 +70,7 @@ fn write_types_for_module_dummy(output_path: &Path) {
 fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path: &Path) {
     use roc_can::module::TypeState;
     use roc_load_internal::file::{LoadingProblem, Threading};
-    use roc_reporting::cli::report_problems;
+    use roc_reporting::cli::{report_problems, ReportFormat};
 
     let arena = Bump::new();
     let cwd = std::env::current_dir().unwrap();
@@ -101,6 +101,7 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
     };
 
     let problems = report_problems(
+        ReportFormat::Text,
         module.total_problems(),
         &module.sources,
         &module.interns,
@@ -21,6 +21,7 @@ pub use roc_load_internal::file::{
     EntryPoint, ExecutionMode, ExpectMetadata, Expectations, LoadConfig, LoadResult, LoadStart,
     LoadedModule, LoadingProblem, MonomorphizedModule, Phase, Threading,
 };
+pub use roc_mono::ir::OptLevel;
 
 #[allow(clippy::too_many_arguments)]
 fn load<'a>(
@@ -65,6 +66,7 @@ pub fn load_single_threaded<'a>(
         render,
         palette,
         exec_mode,
+        OptLevel::Normal,
         roc_cache_dir,
     )
 }
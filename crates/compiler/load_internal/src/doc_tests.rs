@@ -0,0 +1,404 @@
+//! Turns the ```` ```roc ```` code blocks in a module's doc comments into top-level `expect`s,
+//! so `roc test --doc` notices when an example stops compiling or stops being true.
+//!
+//! ```text
+//! ## ```roc
+//! ## list = [1, 2, 3]
+//! ## List.len list # ==> 3
+//! ## ```
+//! ```
+//!
+//! Each code block becomes one `expect`. Its defs stay defs, a line like `expr # ==> value` is
+//! checked with an inline `expect expr == value`, and any other expression is evaluated but not
+//! checked.
+//!
+//! The code is parsed in place, from a copy of the source where everything else is blanked
+//! out, so every region points at the doc comment it came from. That way failures and parse
+//! errors are reported right where the example is written.
+use bumpalo::Bump;
+use roc_module::called_via::{BinOp, CalledVia};
+use roc_parse::ast::{Defs, Expr, Pattern, ValueDef};
+use roc_parse::expr::{test_parse_expr, toplevel_defs};
+use roc_parse::parser::{EExpr, Parser, SyntaxError};
+use roc_parse::state::State;
+use roc_region::all::{Loc, Position, Region};
+
+const ASSERTION: &[u8] = b"# ==>";
+
+/// A line of a code block, without the leading `##`.
+#[derive(Clone, Copy)]
+struct Line {
+    start: usize,
+    end: usize,
+}
+
+enum Statement<'a> {
+    Defs(Defs<'a>),
+    Assertion(Loc<Expr<'a>>),
+    Expr(Loc<Expr<'a>>),
+}
+
+/// Adds an `expect` to `defs` for each ```` ```roc ```` block in the doc comments after
+/// `body_start`, the end of the module header.
+pub(crate) fn add_doc_tests<'a>(
+    arena: &'a Bump,
+    src: &'a [u8],
+    body_start: usize,
+    defs: &mut Defs<'a>,
+) -> Result<(), SyntaxError<'a>> {
+    let blocks = code_blocks(src, body_start);
+
+    if blocks.is_empty() {
+        return Ok(());
+    }
+
+    // Only the line breaks are kept, so positions still map to the same lines and columns
+    let blank: Vec<u8> = src
+        .iter()
+        .map(|&byte| if byte == b'\n' { b'\n' } else { b' ' })
+        .collect();
+
+    for lines in blocks {
+        let statements = parse_block(arena, src, &blank, &lines)?;
+
+        if statements.is_empty() {
+            continue;
+        }
+
+        let start = Position::new(lines[0].start as u32);
+        let end = Position::new(lines[lines.len() - 1].end as u32);
+
+        // A block that checks nothing still has to compile, so it ends with `Bool.true`
+        let mut condition = Loc::at(
+            Region::from_pos(end),
+            Expr::Var {
+                module_name: "Bool",
+                ident: "true",
+            },
+        );
+
+        for statement in statements.into_iter().rev() {
+            condition = match statement {
+                Statement::Defs(block_defs) => {
+                    let region = Region::span_across(&block_defs.regions[0], &condition.region);
+
+                    Loc::at(
+                        region,
+                        Expr::Defs(arena.alloc(block_defs), arena.alloc(condition)),
+                    )
+                }
+                Statement::Assertion(assertion) => {
+                    let region = Region::span_across(&assertion.region, &condition.region);
+
+                    Loc::at(
+                        region,
+                        Expr::Expect(arena.alloc(assertion), arena.alloc(condition)),
+                    )
+                }
+                Statement::Expr(expr) => {
+                    // `(\_ -> rest) expr`, since a `_ = expr` def would be reported as unused
+                    let region = Region::span_across(&expr.region, &condition.region);
+                    let pattern = Loc::at(expr.region, Pattern::Underscore(""));
+                    let closure = Loc::at(
+                        condition.region,
+                        Expr::Closure(arena.alloc([pattern]), arena.alloc(condition)),
+                    );
+
+                    Loc::at(
+                        region,
+                        Expr::Apply(
+                            arena.alloc(closure),
+                            arena.alloc([&*arena.alloc(expr)]),
+                            CalledVia::Space,
+                        ),
+                    )
+                }
+            };
+        }
+
+        let region = Region::new(start, end);
+
+        defs.push_value_def(
+            ValueDef::Expect {
+                condition: arena.alloc(Loc::at(region, condition.value)),
+                preceding_comment: Region::from_pos(start),
+            },
+            region,
+            &[],
+            &[],
+        );
+    }
+
+    Ok(())
+}
+
+/// The lines of each ```` ```roc ```` block in a top-level doc comment. Blocks that are never
+/// closed aren't tested, just like they'd never end in the rendered docs.
+fn code_blocks(src: &[u8], body_start: usize) -> Vec<Vec<Line>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<Line>> = None;
+    let mut line_start = body_start;
+
+    // The header may end partway through a line
+    if body_start > 0 && src[body_start - 1] != b'\n' {
+        match src[body_start..].iter().position(|&byte| byte == b'\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return blocks,
+        }
+    }
+
+    while line_start < src.len() {
+        let line_end = src[line_start..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map(|newline| line_start + newline)
+            .unwrap_or(src.len());
+        let line = &src[line_start..line_end];
+
+        let content_start = if line.starts_with(b"## ") {
+            Some(line_start + 3)
+        } else if line.starts_with(b"##") {
+            Some(line_start + 2)
+        } else {
+            None
+        };
+
+        match (content_start, current.as_mut()) {
+            (Some(start), Some(lines)) => {
+                let end = trim_end(src, start, line_end);
+
+                if src[start + indent(src, Line { start, end })..end].starts_with(b"```") {
+                    blocks.push(current.take().unwrap());
+                } else {
+                    lines.push(Line { start, end });
+                }
+            }
+            (Some(start), None) => {
+                let end = trim_end(src, start, line_end);
+
+                if &src[start + indent(src, Line { start, end })..end] == b"```roc" {
+                    current = Some(Vec::new());
+                }
+            }
+            (None, _) => {
+                current = None;
+            }
+        }
+
+        line_start = line_end + 1;
+    }
+
+    blocks
+}
+
+fn trim_end(src: &[u8], start: usize, mut end: usize) -> usize {
+    while end > start && src[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+
+    end
+}
+
+fn indent(src: &[u8], line: Line) -> usize {
+    src[line.start..line.end]
+        .iter()
+        .take_while(|&&byte| byte == b' ')
+        .count()
+}
+
+/// Groups the lines of a block into statements. A statement starts on a line with the
+/// indentation of the block, and continues on any lines indented further.
+fn statements(src: &[u8], lines: &[Line]) -> Vec<Vec<Line>> {
+    let code_lines = lines.iter().filter(|line| line.start < line.end);
+    let base_indent = match code_lines.map(|line| indent(src, *line)).min() {
+        Some(base_indent) => base_indent,
+        None => return Vec::new(),
+    };
+
+    let mut statements: Vec<Vec<Line>> = Vec::new();
+
+    for line in lines {
+        if line.start == line.end {
+            continue;
+        }
+
+        match statements.last_mut() {
+            Some(statement) if indent(src, *line) > base_indent => statement.push(*line),
+            _ => statements.push(vec![*line]),
+        }
+    }
+
+    statements
+}
+
+/// Parses a block into a list of defs and assertions. Neighbouring defs are parsed together,
+/// so that an annotation and its body end up in the same def.
+fn parse_block<'a>(
+    arena: &'a Bump,
+    src: &[u8],
+    blank: &[u8],
+    lines: &[Line],
+) -> Result<Vec<Statement<'a>>, SyntaxError<'a>> {
+    let mut parsed = Vec::new();
+    let mut run: Vec<Vec<Line>> = Vec::new();
+
+    for statement in statements(src, lines) {
+        let (bytes, assertion) = unblank(arena, src, blank, &statement);
+
+        match assertion {
+            Some(marker) => {
+                if !run.is_empty() {
+                    parsed.extend(parse_run(arena, src, blank, std::mem::take(&mut run))?);
+                }
+
+                let assertion = parse_assertion(arena, blank, bytes, marker)
+                    .map_err(|fail| SyntaxError::Expr(fail, start_of(&statement)))?;
+
+                parsed.push(Statement::Assertion(assertion));
+            }
+            None => run.push(statement),
+        }
+    }
+
+    if !run.is_empty() {
+        parsed.extend(parse_run(arena, src, blank, run)?);
+    }
+
+    Ok(parsed)
+}
+
+/// `expr # ==> value` becomes `expr == value`. Both sides are parsed on their own, so
+/// operators like `|>` or `==` in them can't change what is being compared.
+fn parse_assertion<'a>(
+    arena: &'a Bump,
+    blank: &[u8],
+    bytes: &'a [u8],
+    marker: usize,
+) -> Result<Loc<Expr<'a>>, EExpr<'a>> {
+    let after_marker = marker + ASSERTION.len();
+
+    let expr_end = trim_end(bytes, 0, marker);
+    let expr = test_parse_expr(0, arena, State::new(&bytes[..expr_end]))?;
+
+    let value_bytes = arena.alloc_slice_copy(bytes);
+    value_bytes[..after_marker].copy_from_slice(&blank[..after_marker]);
+    let value = test_parse_expr(0, arena, State::new(value_bytes))?;
+
+    let region = Region::span_across(&expr.region, &value.region);
+    let op_region = Region::new(
+        Position::new(marker as u32),
+        Position::new(after_marker as u32),
+    );
+
+    Ok(Loc::at(
+        region,
+        Expr::BinOps(
+            arena.alloc([(expr, Loc::at(op_region, BinOp::Equals))]),
+            arena.alloc(value),
+        ),
+    ))
+}
+
+/// Statements that aren't assertions. They're usually all defs, but there can be expressions
+/// on their own too.
+fn parse_run<'a>(
+    arena: &'a Bump,
+    src: &[u8],
+    blank: &[u8],
+    run: Vec<Vec<Line>>,
+) -> Result<Vec<Statement<'a>>, SyntaxError<'a>> {
+    let all_lines: Vec<Line> = run.iter().flatten().copied().collect();
+    let (bytes, _) = unblank(arena, src, blank, &all_lines);
+
+    if let Some(defs) = parse_defs(arena, bytes) {
+        return Ok(vec![Statement::Defs(defs)]);
+    }
+
+    let mut parsed = Vec::new();
+    let mut defs = Defs::default();
+
+    for statement in run {
+        let (bytes, _) = unblank(arena, src, blank, &statement);
+
+        let defs_error = match toplevel_defs().parse(arena, State::new(bytes), 0) {
+            Ok((_, statement_defs, state))
+                if !statement_defs.is_empty() && state.has_reached_end() =>
+            {
+                append_defs(&mut defs, &statement_defs);
+                continue;
+            }
+            Ok(_) => None,
+            Err((_, fail)) => Some(fail),
+        };
+
+        match test_parse_expr(0, arena, State::new(bytes)) {
+            Ok(loc_expr) => {
+                if !defs.is_empty() {
+                    parsed.push(Statement::Defs(std::mem::take(&mut defs)));
+                }
+
+                parsed.push(Statement::Expr(loc_expr));
+            }
+            Err(fail) => {
+                return Err(SyntaxError::Expr(
+                    defs_error.unwrap_or(fail),
+                    start_of(&statement),
+                ));
+            }
+        }
+    }
+
+    if !defs.is_empty() {
+        parsed.push(Statement::Defs(defs));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_defs<'a>(arena: &'a Bump, bytes: &'a [u8]) -> Option<Defs<'a>> {
+    match toplevel_defs().parse(arena, State::new(bytes), 0) {
+        Ok((_, defs, state)) if !defs.is_empty() && state.has_reached_end() => Some(defs),
+        _ => None,
+    }
+}
+
+fn append_defs<'a>(defs: &mut Defs<'a>, other: &Defs<'a>) {
+    for (def, region) in other.defs().zip(other.regions.iter()) {
+        match def {
+            Ok(type_def) => defs.push_type_def(*type_def, *region, &[], &[]),
+            Err(value_def) => defs.push_value_def(*value_def, *region, &[], &[]),
+        }
+    }
+}
+
+fn start_of(lines: &[Line]) -> Position {
+    Position::new(lines[0].start as u32)
+}
+
+/// A copy of the source with only these lines filled back in, along with the position of the
+/// first `# ==>` in them, if there is one.
+fn unblank<'a>(
+    arena: &'a Bump,
+    src: &[u8],
+    blank: &[u8],
+    lines: &[Line],
+) -> (&'a [u8], Option<usize>) {
+    let end = lines[lines.len() - 1].end;
+    let bytes = arena.alloc_slice_copy(&blank[..end]);
+    let mut assertion = None;
+
+    for line in lines.iter() {
+        let code = &mut bytes[line.start..line.end];
+
+        code.copy_from_slice(&src[line.start..line.end]);
+
+        if assertion.is_none() {
+            assertion = code
+                .windows(ASSERTION.len())
+                .position(|window| window == ASSERTION)
+                .map(|index| line.start + index);
+        }
+    }
+
+    (bytes, assertion)
+}
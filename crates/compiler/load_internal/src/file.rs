@@ -19,8 +19,8 @@ use roc_constrain::module::constrain_module;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE,
-    ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_LOAD_LOG,
+    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_INLINING, ROC_PRINT_IR_AFTER_REFCOUNT,
+    ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_LOAD_LOG,
 };
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::internal_error;
@@ -32,8 +32,8 @@ use roc_module::symbol::{
     PackageQualified, Symbol,
 };
 use roc_mono::ir::{
-    CapturedSymbols, ExternalSpecializations, PartialProc, Proc, ProcLayout, Procs, ProcsBase,
-    UpdateModeIds,
+    CapturedSymbols, ExternalSpecializations, OptLevel, PartialProc, Proc, ProcLayout, Procs,
+    ProcsBase, UpdateModeIds,
 };
 use roc_mono::layout::{
     CapturesNiche, LambdaName, Layout, LayoutCache, LayoutProblem, STLayoutInterner,
@@ -67,6 +67,8 @@ use std::str::from_utf8_unchecked;
 use std::sync::Arc;
 use std::{env, fs};
 
+use crate::doc_tests;
+use crate::type_cache::{TypeCache, TypeCacheEntry};
 pub use crate::work::Phase;
 use crate::work::{DepCycle, Dependencies};
 
@@ -97,6 +99,9 @@ pub struct LoadConfig {
     pub palette: Palette,
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
+    /// With [`OptLevel::Optimize`], small procs are inlined and constants are folded in the mono IR.
+    /// [`OptLevel::Size`] skips that, because inlining copies a proc's body into every call site.
+    pub opt_level: OptLevel,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -108,20 +113,28 @@ pub enum ExecutionMode {
     /// Test is like [`ExecutionMode::ExecutableIfCheck`], but rather than producing a proper
     /// executable, run tests.
     Test,
+    /// Like [`ExecutionMode::Test`], but the ```` ```roc ```` code blocks in doc comments
+    /// become tests too.
+    DocTest,
 }
 
 impl ExecutionMode {
     fn goal_phase(&self) -> Phase {
         match self {
             ExecutionMode::Executable => Phase::MakeSpecializations,
-            ExecutionMode::Check | ExecutionMode::ExecutableIfCheck | ExecutionMode::Test => {
-                Phase::SolveTypes
-            }
+            ExecutionMode::Check
+            | ExecutionMode::ExecutableIfCheck
+            | ExecutionMode::Test
+            | ExecutionMode::DocTest => Phase::SolveTypes,
         }
     }
 
     fn build_if_checks(&self) -> bool {
-        matches!(self, Self::ExecutableIfCheck | Self::Test)
+        matches!(self, Self::ExecutableIfCheck | Self::Test | Self::DocTest)
+    }
+
+    fn runs_tests(&self) -> bool {
+        matches!(self, Self::Test | Self::DocTest)
     }
 }
 
@@ -273,8 +286,9 @@ fn start_phase<'a>(
             Phase::Parse => {
                 // parse the file
                 let header = state.module_cache.headers.remove(&module_id).unwrap();
+                let doc_tests = matches!(state.exec_mode, ExecutionMode::DocTest);
 
-                BuildTask::Parse { header }
+                BuildTask::Parse { header, doc_tests }
             }
             Phase::CanonicalizeAndConstrain => {
                 // canonicalize the file
@@ -376,6 +390,15 @@ fn start_phase<'a>(
                     state.cached_types.lock().contains_key(&module_id)
                 };
 
+                if let Some(type_cache) = state.type_cache.as_mut() {
+                    type_cache.add_module(
+                        module_id,
+                        &parsed.header_type,
+                        parsed.src,
+                        parsed.imported_modules.keys(),
+                    );
+                }
+
                 BuildTask::CanonicalizeAndConstrain {
                     parsed,
                     dep_idents,
@@ -406,6 +429,10 @@ fn start_phase<'a>(
                 } = constrained;
 
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
+                let type_cache = state
+                    .type_cache
+                    .as_ref()
+                    .and_then(|type_cache| type_cache.entry(module_id));
 
                 BuildTask::solve_module(
                     module,
@@ -421,6 +448,7 @@ fn start_phase<'a>(
                     dep_idents,
                     declarations,
                     state.cached_types.clone(),
+                    type_cache,
                     derived_module,
                 )
             }
@@ -452,8 +480,7 @@ fn start_phase<'a>(
 
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
 
-                let build_expects =
-                    matches!(state.exec_mode, ExecutionMode::Test) && expectations.is_some();
+                let build_expects = state.exec_mode.runs_tests() && expectations.is_some();
 
                 BuildTask::BuildPendingSpecializations {
                     layout_cache,
@@ -990,6 +1017,7 @@ struct State<'a> {
     pub render: RenderTarget,
     pub palette: Palette,
     pub exec_mode: ExecutionMode,
+    pub opt_level: OptLevel,
 
     /// All abilities across all modules.
     pub world_abilities: WorldAbilities,
@@ -999,6 +1027,9 @@ struct State<'a> {
     // cached types (used for builtin modules, could include packages in the future too)
     cached_types: CachedTypeState,
 
+    /// Solved types of user modules, cached on disk between runs
+    type_cache: Option<TypeCache>,
+
     layout_interner: Arc<GlobalInterner<'a, Layout<'a>>>,
 }
 
@@ -1017,10 +1048,12 @@ impl<'a> State<'a> {
         arc_modules: Arc<Mutex<PackageModuleIds<'a>>>,
         ident_ids_by_module: SharedIdentIdsByModule,
         cached_types: MutMap<ModuleId, TypeState>,
+        roc_cache_dir: RocCacheDir<'_>,
         render: RenderTarget,
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        opt_level: OptLevel,
     ) -> Self {
         let arc_shorthands = Arc::new(Mutex::new(MutMap::default()));
         let cache_dir = roc_packaging::cache::roc_cache_dir();
@@ -1051,9 +1084,15 @@ impl<'a> State<'a> {
             timings: MutMap::default(),
             layout_caches: std::vec::Vec::with_capacity(number_of_workers),
             cached_types: Arc::new(Mutex::new(cached_types)),
+            // Doc tests add expects that aren't in the source, which is what the cache is keyed on
+            type_cache: match exec_mode {
+                ExecutionMode::DocTest => None,
+                _ => TypeCache::new(roc_cache_dir),
+            },
             render,
             palette,
             exec_mode,
+            opt_level,
             make_specializations_pass: MakeSpecializationsPass::Pass(1),
             world_abilities: Default::default(),
             layout_interner: GlobalInterner::with_capacity(128),
@@ -1143,6 +1182,7 @@ enum BuildTask<'a> {
     },
     Parse {
         header: ModuleHeader<'a>,
+        doc_tests: bool,
     },
     CanonicalizeAndConstrain {
         parsed: ParsedModule<'a>,
@@ -1166,6 +1206,7 @@ enum BuildTask<'a> {
         declarations: Declarations,
         dep_idents: IdentIdsByModule,
         cached_subs: CachedTypeState,
+        type_cache: Option<TypeCacheEntry>,
         derived_module: SharedDerivedModule,
     },
     BuildPendingSpecializations {
@@ -1284,6 +1325,7 @@ pub fn load_and_typecheck_str<'a>(
         palette,
         threading,
         exec_mode: ExecutionMode::Check,
+        opt_level: OptLevel::Normal,
     };
 
     match load(
@@ -1543,6 +1585,7 @@ pub fn load<'a>(
             load_config.render,
             load_config.palette,
             load_config.exec_mode,
+            load_config.opt_level,
             roc_cache_dir,
         ),
         Threads::Many(threads) => load_multi_threaded(
@@ -1555,6 +1598,7 @@ pub fn load<'a>(
             load_config.palette,
             threads,
             load_config.exec_mode,
+            load_config.opt_level,
             roc_cache_dir,
         ),
     }
@@ -1570,6 +1614,7 @@ pub fn load_single_threaded<'a>(
     render: RenderTarget,
     palette: Palette,
     exec_mode: ExecutionMode,
+    opt_level: OptLevel,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -1597,10 +1642,12 @@ pub fn load_single_threaded<'a>(
         arc_modules,
         ident_ids_by_module,
         cached_types,
+        roc_cache_dir,
         render,
         palette,
         number_of_workers,
         exec_mode,
+        opt_level,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1883,6 +1930,7 @@ fn load_multi_threaded<'a>(
     palette: Palette,
     available_threads: usize,
     exec_mode: ExecutionMode,
+    opt_level: OptLevel,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -1925,10 +1973,12 @@ fn load_multi_threaded<'a>(
         arc_modules,
         ident_ids_by_module,
         cached_types,
+        roc_cache_dir,
         render,
         palette,
         num_workers,
         exec_mode,
+        opt_level,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2162,7 +2212,7 @@ fn worker_task<'a>(
                             BuildTask::LoadModule { module_name, .. } => {
                                 format!("BuildTask::LoadModule({:?})", module_name)
                             }
-                            BuildTask::Parse { header } => {
+                            BuildTask::Parse { header, .. } => {
                                 format!("BuildTask::Parse({})", header.module_path.display())
                             }
                             BuildTask::CanonicalizeAndConstrain { parsed, .. } => format!(
@@ -2358,7 +2408,7 @@ fn update<'a>(
 
                 for (shorthand, package_name) in header.packages.iter() {
                     let package_str = package_name.as_str();
-                    let shorthand_path = if package_str.starts_with("https://") {
+                    let shorthand_path = if cache::is_package_url(package_str) {
                         #[cfg(not(target_family = "wasm"))]
                         {
                             let url = package_str;
@@ -2659,6 +2709,10 @@ fn update<'a>(
             log!("solved types for {:?}", module_id);
             module_timing.end_time = Instant::now();
 
+            if let Some(type_cache) = state.type_cache.as_mut() {
+                type_cache.add_solved(module_id, &ident_ids);
+            }
+
             state
                 .module_cache
                 .type_problems
@@ -2696,7 +2750,7 @@ fn update<'a>(
 
             let add_to_host_exposed = is_host_exposed &&
                 // During testing, we don't need to expose anything to the host.
-                !matches!(state.exec_mode, ExecutionMode::Test);
+                !state.exec_mode.runs_tests();
 
             if add_to_host_exposed {
                 state.exposed_to_host.values.extend(
@@ -3012,6 +3066,22 @@ fn update<'a>(
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_SPECIALIZATION);
                     debug_check_ir!(state, arena, &layout_interner, ROC_CHECK_MONO_IR);
 
+                    // Not for OptLevel::Size: inlining trades code size for speed
+                    if let OptLevel::Optimize = state.opt_level {
+                        let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
+
+                        Proc::inline_and_fold_constants(
+                            arena,
+                            module_id,
+                            ident_ids,
+                            &mut update_mode_ids,
+                            &mut state.procedures,
+                        );
+
+                        debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_INLINING);
+                        debug_check_ir!(state, arena, &layout_interner, ROC_CHECK_MONO_IR);
+                    }
+
                     let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
 
                     Proc::insert_reset_reuse_operations(
@@ -3187,12 +3257,16 @@ fn log_layout_stats(module_id: ModuleId, layout_cache: &LayoutCache) {
 
 fn finish_specialization<'a>(
     arena: &'a Bump,
-    state: State<'a>,
+    mut state: State<'a>,
     subs: Subs,
     layout_interner: STLayoutInterner<'a>,
     exposed_to_host: ExposedToHost,
     module_expectations: VecMap<ModuleId, Expectations>,
 ) -> Result<MonomorphizedModule<'a>, LoadingProblem<'a>> {
+    if let Some(type_cache) = state.type_cache.take() {
+        type_cache.finish();
+    }
+
     if false {
         println!(
             "total Type clones: {} ",
@@ -3245,7 +3319,7 @@ fn finish_specialization<'a>(
 
     let entry_point = {
         match exec_mode {
-            ExecutionMode::Test => EntryPoint::Test,
+            ExecutionMode::Test | ExecutionMode::DocTest => EntryPoint::Test,
             ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck => {
                 use PlatformPath::*;
 
@@ -3366,6 +3440,10 @@ fn finish(
     documentation: MutMap<ModuleId, ModuleDocumentation>,
     abilities_store: AbilitiesStore,
 ) -> LoadedModule {
+    if let Some(type_cache) = state.type_cache.take() {
+        type_cache.finish();
+    }
+
     let module_ids = Arc::try_unwrap(state.arc_modules)
         .unwrap_or_else(|_| panic!("There were still outstanding Arc references to module_ids"))
         .into_inner()
@@ -4112,10 +4190,10 @@ fn load_packages<'a>(
 
         // find the `package` or `platform` module on disk,
         // downloading it into a cache dir first if necessary.
-        let root_module_path = if src.starts_with("https://") {
+        let root_module_path = if cache::is_package_url(src) {
             #[cfg(not(target_family = "wasm"))]
             {
-                // If this is a HTTPS package, synchronously download it
+                // If this is a package URL, synchronously download it
                 // to the cache before proceeding.
 
                 // TODO we should do this async; however, with the current
@@ -4554,6 +4632,7 @@ impl<'a> BuildTask<'a> {
         dep_idents: IdentIdsByModule,
         declarations: Declarations,
         cached_subs: CachedTypeState,
+        type_cache: Option<TypeCacheEntry>,
         derived_module: SharedDerivedModule,
     ) -> Self {
         let exposed_by_module = exposed_types.retain_modules(imported_modules.keys());
@@ -4575,6 +4654,7 @@ impl<'a> BuildTask<'a> {
             dep_idents,
             module_timing,
             cached_subs,
+            type_cache,
             derived_module,
         }
     }
@@ -4947,10 +5027,11 @@ fn run_solve<'a>(
     constraints: Constraints,
     constraint: ConstraintSoa,
     pending_derives: PendingDerives,
-    var_store: VarStore,
+    mut var_store: VarStore,
     decls: Declarations,
     dep_idents: IdentIdsByModule,
     cached_types: CachedTypeState,
+    type_cache: Option<TypeCacheEntry>,
     derived_module: SharedDerivedModule,
 ) -> Msg<'a> {
     let solve_start = Instant::now();
@@ -4992,16 +5073,72 @@ fn run_solve<'a>(
                 ),
             }
         } else {
-            run_solve_solve(
-                exposed_for_module,
-                types,
-                constraints,
-                constraint,
-                pending_derives,
-                var_store,
-                module,
-                derived_module,
-            )
+            let vars = var_store.peek();
+
+            match type_cache
+                .as_ref()
+                .and_then(|entry| entry.read(&ident_ids, vars))
+            {
+                Some(TypeState {
+                    subs,
+                    exposed_vars_by_symbol,
+                    abilities,
+                    solved_implementations,
+                }) => (
+                    Solved(subs),
+                    solved_implementations,
+                    exposed_vars_by_symbol,
+                    vec![],
+                    abilities,
+                ),
+                None => {
+                    let (
+                        solved_subs,
+                        solved_implementations,
+                        exposed_vars_by_symbol,
+                        problems,
+                        abilities_store,
+                    ) = run_solve_solve(
+                        exposed_for_module,
+                        types,
+                        constraints,
+                        constraint,
+                        pending_derives,
+                        var_store,
+                        module,
+                        derived_module,
+                    );
+
+                    match type_cache {
+                        Some(entry) if problems.is_empty() => {
+                            let type_state = TypeState {
+                                subs: solved_subs.into_inner(),
+                                exposed_vars_by_symbol,
+                                abilities: abilities_store,
+                                solved_implementations,
+                            };
+
+                            // The cache is only an optimization, so failing to write it is fine
+                            let _ = entry.write(&ident_ids, vars, &type_state);
+
+                            (
+                                Solved(type_state.subs),
+                                type_state.solved_implementations,
+                                type_state.exposed_vars_by_symbol,
+                                problems,
+                                type_state.abilities,
+                            )
+                        }
+                        _ => (
+                            solved_subs,
+                            solved_implementations,
+                            exposed_vars_by_symbol,
+                            problems,
+                            abilities_store,
+                        ),
+                    }
+                }
+            }
         }
     };
 
@@ -5332,12 +5469,16 @@ fn canonicalize_and_constrain<'a>(
     }
 }
 
-fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, LoadingProblem<'a>> {
+fn parse<'a>(
+    arena: &'a Bump,
+    header: ModuleHeader<'a>,
+    doc_tests: bool,
+) -> Result<Msg<'a>, LoadingProblem<'a>> {
     let mut module_timing = header.module_timing;
     let parse_start = Instant::now();
     let source = header.parse_state.original_bytes();
     let parse_state = header.parse_state;
-    let parsed_defs = match module_defs().parse(arena, parse_state.clone(), 0) {
+    let mut parsed_defs = match module_defs().parse(arena, parse_state.clone(), 0) {
         Ok((_, success, _state)) => success,
         Err((_, fail)) => {
             return Err(LoadingProblem::ParsingFailed(
@@ -5346,6 +5487,17 @@ fn parse<'a>(arena: &'a Bump, header: ModuleHeader<'a>) -> Result<Msg<'a>, Loadi
         }
     };
 
+    // The builtins are tested by the compiler's own test suite
+    if doc_tests && !header.module_id.is_builtin() {
+        let body_start = parse_state.pos().offset as usize;
+
+        if let Err(fail) = doc_tests::add_doc_tests(arena, source, body_start, &mut parsed_defs) {
+            return Err(LoadingProblem::ParsingFailed(
+                fail.into_file_error(header.module_path, &parse_state),
+            ));
+        }
+    }
+
     // Record the parse end time once, to avoid checking the time a second time
     // immediately afterward (for the beginning of canonicalization).
     let parse_end = Instant::now();
@@ -6070,7 +6222,7 @@ fn run_task<'a>(
             ident_ids_by_module,
         )
         .map(|HeaderOutput { msg, .. }| msg),
-        Parse { header } => parse(arena, header),
+        Parse { header, doc_tests } => parse(arena, header, doc_tests),
         CanonicalizeAndConstrain {
             parsed,
             module_ids,
@@ -6106,6 +6258,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            type_cache,
             derived_module,
         } => Ok(run_solve(
             module,
@@ -6120,6 +6273,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            type_cache,
             derived_module,
         )),
         BuildPendingSpecializations {
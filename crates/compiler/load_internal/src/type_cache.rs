@@ -0,0 +1,465 @@
+//! An on-disk cache of solved types, so that interface modules whose source and dependencies did
+//! not change since the last run don't have to be solved again.
+//!
+//! An entry is keyed by a hash of the module's source together with the keys of the modules it
+//! imports, so changing a module invalidates everything that (transitively) depends on it.
+//!
+//! User modules get their [ModuleId]s and [IdentId]s in whatever order the load threads happen to
+//! get to them, so the [Symbol]s in a cached [TypeState] mean nothing on their own. Next to the
+//! types, an entry records which module (by its key) and which ident (by name) every non-builtin
+//! symbol refers to, and these get mapped onto this run's symbols when the entry is read back.
+//!
+//! The cache is bounded in size and `roc cache clean` empties it. Setting [ROC_NO_TYPE_CACHE]
+//! turns it off.
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use roc_can::module::TypeState;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{IdentId, IdentIds, ModuleId, Symbol};
+use roc_packaging::cache::RocCacheDir;
+use roc_parse::header::HeaderType;
+use roc_serialize::bytes;
+
+/// Bump this whenever the layout of an entry changes. The layout of the serialized types is
+/// covered by the compiler hash.
+const VERSION: u32 = 1;
+
+/// Set this environment variable to neither read nor write the cache.
+const ROC_NO_TYPE_CACHE: &str = "ROC_NO_TYPE_CACHE";
+
+/// Once the entries take up more than this, the least recently used ones get removed.
+const MAX_BYTES: u64 = 256 * 1_000_000; // MB
+
+const MAGIC: &[u8; 8] = b"roctypes";
+const CHECKSUM_LEN: usize = blake3::OUT_LEN;
+
+/// The occurrence of idents generated by the compiler, which can't be looked up by name.
+const GENERATED: u32 = u32::MAX;
+
+type Key = blake3::Hash;
+
+/// The coordinator's view of the cache during one load.
+#[derive(Debug)]
+pub(crate) struct TypeCache {
+    dir: PathBuf,
+    compiler: Key,
+    /// Modules we can identify across runs, and whether their types get cached.
+    keys: MutMap<ModuleId, (Key, bool)>,
+    /// The idents of every module that has been solved so far, as they were right after solving.
+    solved: MutMap<ModuleId, Arc<IdentIds>>,
+    /// Whether a worker wrote an entry during this load.
+    wrote: Arc<AtomicBool>,
+}
+
+impl TypeCache {
+    pub fn new(roc_cache_dir: RocCacheDir<'_>) -> Option<Self> {
+        if env::var_os(ROC_NO_TYPE_CACHE).is_some() {
+            return None;
+        }
+
+        Some(Self {
+            dir: roc_cache_dir.types_dir()?,
+            compiler: compiler_hash()?,
+            keys: MutMap::default(),
+            solved: MutMap::default(),
+            wrote: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Called once the load is done. If it added entries, evict the least recently used ones,
+    /// so the cache doesn't grow without bound. Loads that only read from the cache leave it
+    /// alone, since going through the whole directory every time would slow them down.
+    pub fn finish(self) {
+        if !self.wrote.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Failing to evict is no reason to fail the build.
+        #[cfg(not(target_family = "wasm"))]
+        let _ = roc_packaging::cache::clean_types(
+            &self.dir,
+            roc_packaging::cache::CleanLimits {
+                older_than: None,
+                max_bytes: Some(MAX_BYTES),
+            },
+        );
+    }
+
+    /// Computes the key of a module that is about to be canonicalized. All of its imports have
+    /// been canonicalized at this point, so their keys are known.
+    pub fn add_module<'a>(
+        &mut self,
+        module_id: ModuleId,
+        header_type: &HeaderType,
+        src: &str,
+        imports: impl Iterator<Item = &'a ModuleId>,
+    ) {
+        let cacheable = match header_type {
+            HeaderType::Interface { .. } => true,
+            HeaderType::Hosted { .. } => false,
+            // The types of apps and platforms depend on each other, and builtins are cached
+            // within the compiler itself
+            HeaderType::App { .. }
+            | HeaderType::Platform { .. }
+            | HeaderType::Package { .. }
+            | HeaderType::Builtin { .. } => return,
+        };
+
+        let mut import_keys = Vec::new();
+
+        for import in imports {
+            if import.is_builtin() {
+                // builtins are covered by the compiler hash
+                continue;
+            }
+
+            match self.keys.get(import) {
+                Some((key, _)) => import_keys.push(*key.as_bytes()),
+                None => return,
+            }
+        }
+
+        import_keys.sort_unstable();
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.compiler.as_bytes());
+        hasher.update(&(src.len() as u64).to_le_bytes());
+        hasher.update(src.as_bytes());
+
+        for key in import_keys {
+            hasher.update(&key);
+        }
+
+        self.keys.insert(module_id, (hasher.finalize(), cacheable));
+    }
+
+    pub fn add_solved(&mut self, module_id: ModuleId, ident_ids: &IdentIds) {
+        if self.keys.contains_key(&module_id) {
+            self.solved.insert(module_id, Arc::new(ident_ids.clone()));
+        }
+    }
+
+    /// The cache entry for a module that is about to be solved, if its types can be cached.
+    pub fn entry(&self, module_id: ModuleId) -> Option<TypeCacheEntry> {
+        let (key, cacheable) = self.keys.get(&module_id)?;
+
+        if !cacheable {
+            return None;
+        }
+
+        let modules = self
+            .solved
+            .iter()
+            .map(|(id, ident_ids)| (*id, (self.keys[id].0, Arc::clone(ident_ids))))
+            .collect();
+
+        Some(TypeCacheEntry {
+            path: self.dir.join(key.to_hex().as_str()),
+            home: (module_id, *key),
+            modules,
+            wrote: Arc::clone(&self.wrote),
+        })
+    }
+}
+
+/// Everything a worker needs to read or write the cache entry of a single module.
+#[derive(Debug)]
+pub(crate) struct TypeCacheEntry {
+    path: PathBuf,
+    home: (ModuleId, Key),
+    modules: MutMap<ModuleId, (Key, Arc<IdentIds>)>,
+    wrote: Arc<AtomicBool>,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Header {
+    home_idents: u32,
+    vars: u32,
+    modules: u32,
+    symbols: u32,
+    names: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ModuleEntry {
+    key: [u8; blake3::OUT_LEN],
+    idents: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct SymbolEntry {
+    symbol: Symbol,
+    module: u32,
+    /// Which of the idents with this name it is; a name can be in the IdentIds more than once.
+    /// [GENERATED] for generated idents, whose name is their index.
+    occurrence: u32,
+    name_start: u32,
+    name_len: u32,
+}
+
+impl TypeCacheEntry {
+    /// Reads back the types of the home module, if they were cached, and every symbol they
+    /// mention still exists. `home_idents` and `vars` are what canonicalization produced in
+    /// this run, which must line up with the run that wrote the entry.
+    pub fn read(&self, home_idents: &IdentIds, vars: u32) -> Option<TypeState> {
+        let file = fs::read(&self.path).ok()?;
+        let words = aligned(&file);
+        // Safety: the words hold at least file.len() bytes, and any byte pattern is a valid u8.
+        let bytes = unsafe { std::slice::from_raw_parts(words.as_ptr() as *const u8, file.len()) };
+
+        // Make sure the entry is intact before we deserialize values straight out of it
+        let offset = MAGIC.len() + CHECKSUM_LEN;
+        if bytes.len() < offset
+            || &bytes[..MAGIC.len()] != MAGIC
+            || blake3::hash(&bytes[offset..]) != bytes[MAGIC.len()..offset]
+        {
+            return None;
+        }
+
+        let (header, offset) = bytes::deserialize_slice::<Header>(bytes, 1, offset);
+        let header = header[0];
+
+        if header.home_idents as usize != home_idents.len() || header.vars != vars {
+            return None;
+        }
+
+        let (module_entries, offset) =
+            bytes::deserialize_slice::<ModuleEntry>(bytes, header.modules as usize, offset);
+        let (symbol_entries, offset) =
+            bytes::deserialize_slice::<SymbolEntry>(bytes, header.symbols as usize, offset);
+        let (names, offset) = bytes::deserialize_slice::<u8>(bytes, header.names as usize, offset);
+        let (_, offset) = bytes::deserialize_slice::<u64>(bytes, 0, offset);
+
+        let mut modules = Vec::with_capacity(module_entries.len());
+        for entry in module_entries {
+            let (module_id, ident_ids) = self.find_module(Key::from(entry.key), home_idents)?;
+
+            if ident_ids.len() != entry.idents as usize {
+                return None;
+            }
+
+            modules.push((module_id, ident_ids));
+        }
+
+        let mut symbols = MutMap::default();
+        for entry in symbol_entries {
+            let (module_id, ident_ids) = modules.get(entry.module as usize)?;
+            let name_start = entry.name_start as usize;
+            let name = names.get(name_start..name_start + entry.name_len as usize)?;
+            let name = std::str::from_utf8(name).ok()?;
+            let ident_id = lookup(ident_ids, name, entry.occurrence)?;
+
+            symbols.insert(entry.symbol, Symbol::new(*module_id, ident_id));
+        }
+
+        let (mut type_state, _) = TypeState::deserialize(&bytes[offset..]);
+
+        let mut complete = true;
+        type_state.remap_symbols(&mut |symbol| {
+            if symbol.is_builtin() {
+                symbol
+            } else {
+                symbols.get(&symbol).copied().unwrap_or_else(|| {
+                    complete = false;
+                    symbol
+                })
+            }
+        });
+
+        if complete {
+            #[cfg(not(target_family = "wasm"))]
+            roc_packaging::cache::touch_type_entry(&self.path);
+        }
+
+        complete.then_some(type_state)
+    }
+
+    /// Writes the types of the home module to the cache. Does nothing if they mention a symbol
+    /// that can't be found again in a later run, e.g. one from the derived module.
+    pub fn write(
+        &self,
+        home_idents: &IdentIds,
+        vars: u32,
+        type_state: &TypeState,
+    ) -> io::Result<()> {
+        let mut module_entries: Vec<ModuleEntry> = Vec::new();
+        let mut module_indices: MutMap<ModuleId, u32> = MutMap::default();
+        let mut symbol_entries: Vec<SymbolEntry> = Vec::new();
+        let mut seen: MutSet<Symbol> = MutSet::default();
+        let mut names: Vec<u8> = Vec::new();
+        let mut complete = true;
+
+        type_state.clone().remap_symbols(&mut |symbol| {
+            if symbol.is_builtin() || !seen.insert(symbol) {
+                return symbol;
+            }
+
+            let module_id = symbol.module_id();
+            let (key, ident_ids) = match self.find_idents(module_id, home_idents) {
+                Some(found) => found,
+                None => {
+                    complete = false;
+                    return symbol;
+                }
+            };
+
+            let ident_id = symbol.ident_id();
+            let (name, occurrence) = match occurrence_of(ident_ids, ident_id) {
+                Some(found) => found,
+                None => {
+                    complete = false;
+                    return symbol;
+                }
+            };
+
+            let module = *module_indices.entry(module_id).or_insert_with(|| {
+                module_entries.push(ModuleEntry {
+                    key: *key.as_bytes(),
+                    idents: ident_ids.len() as u32,
+                });
+
+                module_entries.len() as u32 - 1
+            });
+
+            symbol_entries.push(SymbolEntry {
+                symbol,
+                module,
+                occurrence,
+                name_start: names.len() as u32,
+                name_len: name.len() as u32,
+            });
+            names.extend_from_slice(name.as_bytes());
+
+            symbol
+        });
+
+        if !complete {
+            return Ok(());
+        }
+
+        let header = Header {
+            home_idents: home_idents.len() as u32,
+            vars,
+            modules: module_entries.len() as u32,
+            symbols: symbol_entries.len() as u32,
+            names: names.len() as u32,
+        };
+
+        let mut buf = Vec::new();
+        let mut written = bytes::serialize_slice(MAGIC, &mut buf, 0)?;
+        written = bytes::serialize_slice(&[0u8; CHECKSUM_LEN], &mut buf, written)?;
+        written = bytes::serialize_slice(&[header], &mut buf, written)?;
+        written = bytes::serialize_slice(&module_entries, &mut buf, written)?;
+        written = bytes::serialize_slice(&symbol_entries, &mut buf, written)?;
+        written = bytes::serialize_slice(&names, &mut buf, written)?;
+        // the types align their contents relative to where they start
+        bytes::serialize_slice::<u64>(&[], &mut buf, written)?;
+        type_state.serialize(&mut buf)?;
+
+        let checksum = blake3::hash(&buf[MAGIC.len() + CHECKSUM_LEN..]);
+        buf[MAGIC.len()..][..CHECKSUM_LEN].copy_from_slice(checksum.as_bytes());
+
+        // Write to a temporary file first, so other roc processes never see half an entry
+        let dir = self.path.parent().unwrap();
+        fs::create_dir_all(dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(&buf)?;
+        file.persist(&self.path).map_err(|e| e.error)?;
+        self.wrote.store(true, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    fn find_idents<'a>(
+        &'a self,
+        module_id: ModuleId,
+        home_idents: &'a IdentIds,
+    ) -> Option<(Key, &'a IdentIds)> {
+        if module_id == self.home.0 {
+            Some((self.home.1, home_idents))
+        } else {
+            let (key, ident_ids) = self.modules.get(&module_id)?;
+            Some((*key, ident_ids.as_ref()))
+        }
+    }
+
+    fn find_module<'a>(
+        &'a self,
+        key: Key,
+        home_idents: &'a IdentIds,
+    ) -> Option<(ModuleId, &'a IdentIds)> {
+        if key == self.home.1 {
+            return Some((self.home.0, home_idents));
+        }
+
+        let mut found = self
+            .modules
+            .iter()
+            .filter(|(_, (module_key, _))| *module_key == key);
+
+        match (found.next(), found.next()) {
+            (Some((module_id, (_, ident_ids))), None) => Some((*module_id, ident_ids.as_ref())),
+            // Two modules with the same source and imports; we can't tell which one is meant
+            _ => None,
+        }
+    }
+}
+
+fn occurrence_of(ident_ids: &IdentIds, ident_id: IdentId) -> Option<(&str, u32)> {
+    let name = ident_ids.get_name(ident_id)?;
+    let occurrence = match ident_ids.get_id_many(name).position(|id| id == ident_id) {
+        Some(occurrence) => occurrence as u32,
+        None => GENERATED,
+    };
+
+    Some((name, occurrence))
+}
+
+fn lookup(ident_ids: &IdentIds, name: &str, occurrence: u32) -> Option<IdentId> {
+    if occurrence == GENERATED {
+        // Entries are untrusted, so the index may be out of bounds
+        let ident_id = ident_ids.get_id_by_index(name.parse().ok()?)?;
+
+        (ident_ids.get_name(ident_id)? == name).then_some(ident_id)
+    } else {
+        ident_ids.get_id_many(name).nth(occurrence as usize)
+    }
+}
+
+/// Entries are deserialized in place, so they must be aligned like the values in them.
+fn aligned(file: &[u8]) -> Vec<u64> {
+    let mut words = vec![0u64; (file.len() + 7) / 8];
+
+    for (word, chunk) in words.iter_mut().zip(file.chunks(8)) {
+        let mut bytes = [0; 8];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        *word = u64::from_ne_bytes(bytes);
+    }
+
+    words
+}
+
+/// Types are cached in the compiler's in-memory representation, and the builtins they refer to
+/// are baked into the compiler, so an entry can only be used by the exact compiler that wrote it.
+fn compiler_hash() -> Option<Key> {
+    let exe = std::env::current_exe().ok()?;
+    let metadata = fs::metadata(&exe).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&VERSION.to_le_bytes());
+    hasher.update(exe.to_string_lossy().as_bytes());
+    hasher.update(&metadata.len().to_le_bytes());
+    hasher.update(&modified.as_nanos().to_le_bytes());
+
+    Some(hasher.finalize())
+}
@@ -21,6 +21,7 @@ use roc_load_internal::file::{ExecutionMode, LoadConfig, Threading};
 use roc_load_internal::file::{LoadResult, LoadStart, LoadedModule, LoadingProblem};
 use roc_module::ident::ModuleName;
 use roc_module::symbol::{Interns, ModuleId};
+use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
 use roc_problem::can::Problem;
 use roc_region::all::LineInfo;
@@ -39,13 +40,29 @@ fn load_and_typecheck(
     exposed_types: ExposedByModule,
     target_info: TargetInfo,
 ) -> Result<LoadedModule, LoadingProblem> {
+    load_and_typecheck_help(
+        arena,
+        filename,
+        exposed_types,
+        target_info,
+        RocCacheDir::Disallowed,
+    )
+}
+
+fn load_and_typecheck_help<'a>(
+    arena: &'a Bump,
+    filename: PathBuf,
+    exposed_types: ExposedByModule,
+    target_info: TargetInfo,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<LoadedModule, LoadingProblem<'a>> {
     use LoadResult::*;
 
     let load_start = LoadStart::from_path(
         arena,
         filename,
         RenderTarget::Generic,
-        RocCacheDir::Disallowed,
+        roc_cache_dir,
         DEFAULT_PALETTE,
     )?;
     let load_config = LoadConfig {
@@ -54,6 +71,7 @@ fn load_and_typecheck(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Check,
+        opt_level: OptLevel::Normal,
     };
 
     match roc_load_internal::file::load(
@@ -61,7 +79,7 @@ fn load_and_typecheck(
         load_start,
         exposed_types,
         Default::default(), // these tests will re-compile the builtins
-        RocCacheDir::Disallowed,
+        roc_cache_dir,
         load_config,
     )? {
         Monomorphized(_) => unreachable!(""),
@@ -537,6 +555,121 @@ fn iface_dep_types() {
     );
 }
 
+#[test]
+#[cfg(unix)]
+fn reuse_cached_types() {
+    use std::os::unix::fs::MetadataExt;
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let packages_dir = cache_dir.path().join("packages");
+    let types_dir = cache_dir.path().join("types");
+
+    let load = |module_name: &str| {
+        let filename = fixtures_dir()
+            .join("interface_with_deps")
+            .join(format!("{}.roc", module_name));
+        let arena = Bump::new();
+
+        load_and_typecheck_help(
+            &arena,
+            filename,
+            Default::default(),
+            TARGET_INFO,
+            RocCacheDir::Persistent(&packages_dir),
+        )
+        .unwrap()
+    };
+
+    // Entries are replaced rather than written in place, so they get a new inode when they're
+    // written again.
+    let cache_entries = || -> HashMap<PathBuf, u64> {
+        std::fs::read_dir(&types_dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path(), entry.metadata().unwrap().ino())
+            })
+            .collect()
+    };
+
+    let primary_types = hashmap! {
+        "blah2" => "Float *",
+        "blah3" => "Str",
+        "str" => "Str",
+        "alwaysThree" => "* -> Float *",
+        "identity" => "a -> a",
+        "z" => "Float *",
+        "w" => "Dep1.Identity {}",
+        "succeed" => "a -> Dep1.Identity a",
+        "yay" => "Res.Res {} err",
+        "withDefault" => "Res.Res a err, a -> a",
+    };
+
+    expect_types(load("Primary"), primary_types.clone());
+    let written = cache_entries();
+    // Primary, Dep1, Dep2, Dep3.Blah and Res
+    assert_eq!(written.len(), 5);
+
+    // Dep1 gets a different ModuleId when it is loaded from here
+    expect_types(load("ImportAlias"), hashmap! { "unit" => "Dep1.Unit" });
+    expect_types(load("Primary"), primary_types);
+
+    // Nothing had to be solved again; only ImportAlias was added
+    let entries = cache_entries();
+    assert_eq!(entries.len(), 6);
+    for (path, inode) in written {
+        assert_eq!(
+            entries[&path],
+            inode,
+            "{} was written again",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn evict_cached_types_only_after_writing() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let packages_dir = cache_dir.path().join("packages");
+    let types_dir = cache_dir.path().join("types");
+
+    let load = || {
+        let filename = fixtures_dir()
+            .join("interface_with_deps")
+            .join("Primary.roc");
+        let arena = Bump::new();
+
+        load_and_typecheck_help(
+            &arena,
+            filename,
+            Default::default(),
+            TARGET_INFO,
+            RocCacheDir::Persistent(&packages_dir),
+        )
+        .unwrap();
+    };
+
+    // A sparse file, so it's over the size limit without taking up the space
+    let huge = types_dir.join("huge");
+    let add_huge_entry = || {
+        std::fs::create_dir_all(&types_dir).unwrap();
+        let file = std::fs::File::create(&huge).unwrap();
+        file.set_len(300_000_000).unwrap();
+    };
+
+    // This load writes entries, so the cache gets evicted, least recently used first
+    add_huge_entry();
+    load();
+    assert!(!huge.exists());
+    assert_eq!(std::fs::read_dir(&types_dir).unwrap().count(), 5);
+
+    // Everything is read from the cache this time, so nothing gets evicted
+    add_huge_entry();
+    load();
+    assert!(huge.exists());
+    assert_eq!(std::fs::read_dir(&types_dir).unwrap().count(), 6);
+}
+
 #[test]
 fn app_dep_types() {
     let subs_by_module = Default::default();
@@ -1081,3 +1214,173 @@ fn nested_module_has_incorrect_name() {
         err
     );
 }
+
+/// Loads a module the way `roc test --doc` does, and returns the source of each top-level expect.
+fn doc_test_expects(subdir: &str, src: &str) -> Result<Vec<String>, String> {
+    let arena = Bump::new();
+    let tmp = format!("tmp/{}", subdir);
+    let dir = roc_test_utils::TmpDir::new(&tmp);
+    let filename = dir.path().join("Main.roc");
+
+    std::fs::write(&filename, src).unwrap();
+
+    let load_start = LoadStart::from_path(
+        &arena,
+        filename,
+        RenderTarget::Generic,
+        RocCacheDir::Disallowed,
+        DEFAULT_PALETTE,
+    )
+    .map_err(|problem| format!("{:?}", problem))?;
+    let load_config = LoadConfig {
+        target_info: TARGET_INFO,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::DocTest,
+        opt_level: OptLevel::Normal,
+    };
+
+    let mut module = match roc_load_internal::file::load(
+        &arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Disallowed,
+        load_config,
+    ) {
+        Ok(LoadResult::Monomorphized(module)) => module,
+        Ok(LoadResult::TypeChecked(_)) => unreachable!(),
+        Err(LoadingProblem::FormattedReport(report)) => return Err(report),
+        Err(problem) => return Err(format!("{:?}", problem)),
+    };
+
+    let home = module.module_id;
+    assert_eq!(
+        module.can_problems.remove(&home).unwrap_or_default(),
+        vec![]
+    );
+    assert!(module
+        .type_problems
+        .remove(&home)
+        .unwrap_or_default()
+        .is_empty());
+
+    let mut regions: Vec<_> = module.toplevel_expects.pure.values().copied().collect();
+    regions.sort_by_key(|region| region.start().offset);
+
+    Ok(regions
+        .into_iter()
+        .map(|region| src[region.start().offset as usize..region.end().offset as usize].to_string())
+        .collect())
+}
+
+#[test]
+fn doc_tests_become_expects() {
+    let src = indoc!(
+        r#"
+        interface Main exposes [len] imports []
+
+        ## The number of elements.
+        ##
+        ## ```roc
+        ## list : List U8
+        ## list = [1, 2, 3]
+        ##
+        ## len list # ==> 3
+        ## List.append list 4
+        ## ```
+        ##
+        ## This one is only rendered:
+        ##
+        ## ```
+        ## len nope
+        ## ```
+        len : List U8 -> Nat
+        len = \list -> List.len list
+
+        expect len [] == 0
+        "#
+    );
+
+    assert_eq!(
+        doc_test_expects("doc_tests_become_expects", src).unwrap(),
+        vec![
+            indoc!(
+                r#"
+                list : List U8
+                ## list = [1, 2, 3]
+                ##
+                ## len list # ==> 3
+                ## List.append list 4"#
+            )
+            .to_string(),
+            "expect len [] == 0".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn doc_test_parse_problem() {
+    let src = indoc!(
+        r#"
+        interface Main exposes [len] imports []
+
+        ## ```roc
+        ## len [1, 2 # ==> 2
+        ## ```
+        len : List U8 -> Nat
+        len = \list -> List.len list
+        "#
+    );
+
+    let report = doc_test_expects("doc_test_parse_problem", src).unwrap_err();
+
+    assert_eq!(
+        report,
+        indoc!(
+            "
+            ── UNFINISHED LIST ─────────────────────── tmp/doc_test_parse_problem/Main.roc ─
+
+            I am partway through started parsing a list, but I got stuck here:
+
+            4│  ## len [1, 2 # ==> 2
+                            ^
+
+            I was expecting to see a closing square bracket before this, so try
+            adding a ] and see if that helps?
+
+            Note: When I get stuck like this, it usually means that there is a
+            missing parenthesis or bracket somewhere earlier. It could also be a
+            stray keyword or operator."
+        )
+    );
+}
+
+#[test]
+fn doc_test_assertion_sides_keep_their_operators() {
+    let src = indoc!(
+        r#"
+        interface Main exposes [len] imports []
+
+        ## ```roc
+        ## [1, 2] |> len # ==> 2
+        ## len [] == 0 # ==> Bool.true
+        ## Bool.true && Bool.false # ==> Bool.false
+        ## ```
+        len : List U8 -> Nat
+        len = \list -> List.len list
+        "#
+    );
+
+    assert_eq!(
+        doc_test_expects("doc_test_assertion_sides_keep_their_operators", src).unwrap(),
+        vec![indoc!(
+            r#"
+            [1, 2] |> len # ==> 2
+            ## len [] == 0 # ==> Bool.true
+            ## Bool.true && Bool.false # ==> Bool.false"#
+        )
+        .to_string()]
+    );
+}
@@ -40,6 +40,23 @@ pub struct Lowercase(IdentStr);
 #[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Uppercase(IdentStr);
 
+/// The name of a field of a record, or the index of an element of a tuple,
+/// such as the `foo` in `.foo` or the `1` in `.1`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IndexOrField {
+    Field(Lowercase),
+    Index(usize),
+}
+
+impl fmt::Display for IndexOrField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexOrField::Field(name) => name.fmt(f),
+            IndexOrField::Index(index) => index.fmt(f),
+        }
+    }
+}
+
 /// A string representing a foreign (linked-in) symbol
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
 pub struct ForeignSymbol(IdentStr);
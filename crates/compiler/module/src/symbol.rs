@@ -666,6 +666,13 @@ impl IdentIds {
             .map(|i| IdentId(i as u32))
     }
 
+    /// The ident at this index, if there is one
+    pub fn get_id_by_index(&self, index: u32) -> Option<IdentId> {
+        self.interner
+            .try_get(index as usize)
+            .map(|_| IdentId(index))
+    }
+
     pub fn get_name(&self, id: IdentId) -> Option<&str> {
         self.interner.try_get(id.0 as usize)
     }
@@ -1508,11 +1515,12 @@ define_builtins! {
         20 DECODE_STRING: "string"
         21 DECODE_LIST: "list"
         22 DECODE_RECORD: "record"
-        23 DECODE_CUSTOM: "custom"
-        24 DECODE_DECODE_WITH: "decodeWith"
-        25 DECODE_FROM_BYTES_PARTIAL: "fromBytesPartial"
-        26 DECODE_FROM_BYTES: "fromBytes"
-        27 DECODE_MAP_RESULT: "mapResult"
+        23 DECODE_TAG: "tag"
+        24 DECODE_CUSTOM: "custom"
+        25 DECODE_DECODE_WITH: "decodeWith"
+        26 DECODE_FROM_BYTES_PARTIAL: "fromBytesPartial"
+        27 DECODE_FROM_BYTES: "fromBytes"
+        28 DECODE_MAP_RESULT: "mapResult"
     }
     13 HASH: "Hash" => {
         0 HASH_HASH_ABILITY: "Hash" exposed_type=true
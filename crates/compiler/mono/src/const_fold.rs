@@ -0,0 +1,578 @@
+//! Constant folding and dead code elimination for the mono IR.
+//!
+//! Arithmetic, bitwise operations and comparisons on literals are evaluated at compile time,
+//! switches on a known value are replaced by the branch that would be taken, and `let`s and
+//! join points that are never used are removed. Operations that could panic at runtime (for
+//! instance an overflowing `Num.add`) are left alone, so the program behaves the same.
+
+use crate::inc_dec::occurring_variables_expr;
+use crate::ir::{BranchInfo, Call, CallType, Expr, JoinPointId, Literal, Stmt};
+use crate::layout::{Builtin, Layout, TagIdIntType, UnionLayout};
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::all::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+pub fn fold_constants<'a>(arena: &'a Bump, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+    let mut env = Env {
+        arena,
+        literals: MutMap::default(),
+        tags: MutMap::default(),
+    };
+
+    let mut stmt = env.fold_stmt(stmt);
+
+    // removing one `let` can make the symbols it used dead too
+    loop {
+        let mut uses = Uses::default();
+        uses.stmt(stmt);
+
+        let mut changed = false;
+        stmt = remove_dead_code(arena, &uses, stmt, &mut changed);
+
+        if !changed {
+            return stmt;
+        }
+    }
+}
+
+struct Env<'a> {
+    arena: &'a Bump,
+    /// Symbols that are known to be a literal
+    literals: MutMap<Symbol, (Literal<'a>, Layout<'a>)>,
+    /// Symbols that are known to be a particular tag
+    tags: MutMap<Symbol, (UnionLayout<'a>, TagIdIntType)>,
+}
+
+impl<'a> Env<'a> {
+    fn fold_stmt(&mut self, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+        use Stmt::*;
+
+        let arena = self.arena;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                let expr = match self.fold_expr(expr, layout) {
+                    Some(literal) => Expr::Literal(literal),
+                    None => expr.clone(),
+                };
+
+                match &expr {
+                    Expr::Literal(literal) => {
+                        self.literals.insert(*symbol, (*literal, *layout));
+                    }
+                    Expr::Tag {
+                        tag_layout, tag_id, ..
+                    } => {
+                        self.tags.insert(*symbol, (*tag_layout, *tag_id));
+                    }
+                    _ => {}
+                }
+
+                arena.alloc(Let(*symbol, expr, *layout, self.fold_stmt(cont)))
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let known = self
+                    .literals
+                    .get(cond_symbol)
+                    .and_then(|(literal, _)| switch_value(literal));
+
+                if let Some(value) = known {
+                    let branch = branches
+                        .iter()
+                        .find(|(branch_value, _, _)| *branch_value == value)
+                        .map(|(_, _, branch)| branch)
+                        .unwrap_or(default_branch.1);
+
+                    return self.fold_stmt(branch);
+                }
+
+                let branches = Vec::from_iter_in(
+                    branches.iter().map(|(value, info, branch)| {
+                        (*value, info.clone(), self.fold_stmt(branch).clone())
+                    }),
+                    arena,
+                );
+                let default_branch = (default_branch.0.clone(), self.fold_stmt(default_branch.1));
+
+                arena.alloc(Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: branches.into_bump_slice(),
+                    default_branch,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => arena.alloc(Join {
+                id: *id,
+                parameters,
+                body: self.fold_stmt(body),
+                remainder: self.fold_stmt(remainder),
+            }),
+            Refcounting(modify, cont) => arena.alloc(Refcounting(*modify, self.fold_stmt(cont))),
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(Expect {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: self.fold_stmt(remainder),
+            }),
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: self.fold_stmt(remainder),
+            }),
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => arena.alloc(Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: self.fold_stmt(remainder),
+            }),
+            Ret(_) | Jump(_, _) | Crash(_, _) => stmt,
+        }
+    }
+
+    fn fold_expr(&self, expr: &Expr<'a>, layout: &Layout<'a>) -> Option<Literal<'a>> {
+        match expr {
+            Expr::Call(Call {
+                call_type: CallType::LowLevel { op, .. },
+                arguments,
+            }) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.literals.get(argument).copied())
+                    .collect::<Option<std::vec::Vec<_>>>()?;
+
+                fold_low_level(*op, &arguments, layout)
+            }
+            Expr::GetTagId {
+                structure,
+                union_layout,
+            } => match self.tags.get(structure) {
+                Some((tag_layout, tag_id)) if tag_layout == union_layout => {
+                    Some(Literal::Int((*tag_id as i128).to_ne_bytes()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The value that a switch on this literal compares its branches against
+fn switch_value(literal: &Literal) -> Option<u64> {
+    match literal {
+        Literal::Int(bytes) => Some(i128::from_ne_bytes(*bytes) as u64),
+        Literal::Bool(value) => Some(*value as u64),
+        Literal::Byte(value) => Some(*value as u64),
+        _ => None,
+    }
+}
+
+fn fold_low_level<'a>(
+    op: LowLevel,
+    arguments: &[(Literal<'a>, Layout<'a>)],
+    layout: &Layout<'a>,
+) -> Option<Literal<'a>> {
+    use Literal::*;
+    use LowLevel::*;
+
+    match (op, arguments) {
+        (
+            NumAdd | NumAddWrap | NumAddSaturated | NumSub | NumSubWrap | NumSubSaturated | NumMul
+            | NumMulWrap | NumMulSaturated | NumBitwiseAnd | NumBitwiseOr | NumBitwiseXor,
+            [(Int(a), _), (Int(b), _)],
+        ) => match layout {
+            Layout::Builtin(Builtin::Int(width)) => {
+                let result =
+                    fold_int(op, *width, i128::from_ne_bytes(*a), i128::from_ne_bytes(*b))?;
+                Some(Int(result.to_ne_bytes()))
+            }
+            _ => None,
+        },
+        (NumNeg, [(Int(a), _)]) => match layout {
+            Layout::Builtin(Builtin::Int(width)) => {
+                let result = i128::from_ne_bytes(*a).checked_neg()?;
+                in_range(*width, result).then(|| Int(result.to_ne_bytes()))
+            }
+            _ => None,
+        },
+        (NumAdd | NumSub | NumMul, [(Float(a), _), (Float(b), _)]) => match layout {
+            Layout::Builtin(Builtin::Float(width)) => {
+                let result = match op {
+                    NumAdd => to_width(*width, to_width(*width, *a) + to_width(*width, *b)),
+                    NumSub => to_width(*width, to_width(*width, *a) - to_width(*width, *b)),
+                    _ => to_width(*width, to_width(*width, *a) * to_width(*width, *b)),
+                };
+                Some(Float(result))
+            }
+            _ => None,
+        },
+        (NumNeg, [(Float(a), _)]) => match layout {
+            Layout::Builtin(Builtin::Float(width)) => Some(Float(-to_width(*width, *a))),
+            _ => None,
+        },
+        (NumGt | NumGte | NumLt | NumLte | Eq | NotEq, [(a, a_layout), (b, _)]) => {
+            let ordering = match (a, b, a_layout) {
+                (Int(a), Int(b), Layout::Builtin(Builtin::Int(_))) => {
+                    i128::from_ne_bytes(*a).partial_cmp(&i128::from_ne_bytes(*b))
+                }
+                (Float(a), Float(b), Layout::Builtin(Builtin::Float(width))) => {
+                    to_width(*width, *a).partial_cmp(&to_width(*width, *b))
+                }
+                (Bool(a), Bool(b), _) if matches!(op, Eq | NotEq) => a.partial_cmp(b),
+                (Byte(a), Byte(b), _) if matches!(op, Eq | NotEq) => a.partial_cmp(b),
+                _ => return None,
+            };
+
+            // comparisons involving NaN are always false, except for `!=`
+            let result = match ordering {
+                Some(ordering) => match op {
+                    NumGt => ordering.is_gt(),
+                    NumGte => ordering.is_ge(),
+                    NumLt => ordering.is_lt(),
+                    NumLte => ordering.is_le(),
+                    Eq => ordering.is_eq(),
+                    _ => ordering.is_ne(),
+                },
+                None => matches!(op, NotEq),
+            };
+
+            Some(Bool(result))
+        }
+        (And, [(Bool(a), _), (Bool(b), _)]) => Some(Bool(*a && *b)),
+        (Or, [(Bool(a), _), (Bool(b), _)]) => Some(Bool(*a || *b)),
+        (Not, [(Bool(a), _)]) => Some(Bool(!*a)),
+        _ => None,
+    }
+}
+
+/// Evaluate an integer operation, or return `None` if it would panic at runtime
+/// (or its result does not fit in an `i128`)
+fn fold_int(op: LowLevel, width: IntWidth, a: i128, b: i128) -> Option<i128> {
+    use LowLevel::*;
+
+    let result = match op {
+        NumAdd | NumAddWrap | NumAddSaturated => a.checked_add(b)?,
+        NumSub | NumSubWrap | NumSubSaturated => a.checked_sub(b)?,
+        NumMul | NumMulWrap | NumMulSaturated => a.checked_mul(b)?,
+        NumBitwiseAnd => a & b,
+        NumBitwiseOr => a | b,
+        NumBitwiseXor => a ^ b,
+        _ => return None,
+    };
+
+    if in_range(width, result) {
+        return Some(result);
+    }
+
+    // 128-bit results that overflow do not fit in an i128 either
+    let bits = width.stack_size() * 8;
+
+    match op {
+        NumAddWrap | NumSubWrap | NumMulWrap if bits < 128 => {
+            let modulus = 1i128 << bits;
+            let wrapped = result.rem_euclid(modulus);
+
+            if width.is_signed() && wrapped >= modulus / 2 {
+                Some(wrapped - modulus)
+            } else {
+                Some(wrapped)
+            }
+        }
+        NumAddSaturated | NumSubSaturated | NumMulSaturated if bits < 128 => {
+            let (min, max) = int_range(width);
+            Some(result.clamp(min, max))
+        }
+        _ => None,
+    }
+}
+
+/// The values of an integer width that can be represented by an `i128`
+fn int_range(width: IntWidth) -> (i128, i128) {
+    match width {
+        IntWidth::I128 => (i128::MIN, i128::MAX),
+        IntWidth::U128 => (0, i128::MAX),
+        _ => {
+            let bits = width.stack_size() * 8;
+
+            if width.is_signed() {
+                (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+            } else {
+                (0, (1 << bits) - 1)
+            }
+        }
+    }
+}
+
+fn in_range(width: IntWidth, value: i128) -> bool {
+    let (min, max) = int_range(width);
+    (min..=max).contains(&value)
+}
+
+/// Round a float to the precision it has at runtime
+fn to_width(width: FloatWidth, value: f64) -> f64 {
+    match width {
+        FloatWidth::F32 => value as f32 as f64,
+        FloatWidth::F64 => value,
+    }
+}
+
+/// Symbols and join points that are used somewhere in a proc body
+#[derive(Default)]
+struct Uses {
+    symbols: MutSet<Symbol>,
+    join_points: MutSet<JoinPointId>,
+}
+
+impl Uses {
+    fn stmt(&mut self, stmt: &Stmt<'_>) {
+        use Stmt::*;
+
+        match stmt {
+            Let(_, expr, _, cont) => {
+                occurring_variables_expr(expr, &mut self.symbols);
+                self.stmt(cont);
+            }
+            Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                self.symbols.insert(*cond_symbol);
+
+                for (_, info, branch) in branches.iter() {
+                    self.branch_info(info);
+                    self.stmt(branch);
+                }
+
+                self.branch_info(&default_branch.0);
+                self.stmt(default_branch.1);
+            }
+            Ret(symbol) | Crash(symbol, _) => {
+                self.symbols.insert(*symbol);
+            }
+            Refcounting(modify, cont) => {
+                self.symbols.insert(modify.get_symbol());
+                self.stmt(cont);
+            }
+            Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            }
+            | ExpectFx {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                self.symbols.insert(*condition);
+                self.symbols.extend(lookups.iter().copied());
+                self.stmt(remainder);
+            }
+            Dbg {
+                symbol, remainder, ..
+            } => {
+                self.symbols.insert(*symbol);
+                self.stmt(remainder);
+            }
+            Join {
+                body, remainder, ..
+            } => {
+                self.stmt(body);
+                self.stmt(remainder);
+            }
+            Jump(id, arguments) => {
+                self.join_points.insert(*id);
+                self.symbols.extend(arguments.iter().copied());
+            }
+        }
+    }
+
+    fn branch_info(&mut self, info: &BranchInfo) {
+        if let BranchInfo::Constructor { scrutinee, .. } = info {
+            self.symbols.insert(*scrutinee);
+        }
+    }
+}
+
+/// Expressions that can be removed when their result is unused. Calls are never removed,
+/// because they may have effects or panic.
+fn is_pure(expr: &Expr) -> bool {
+    use Expr::*;
+
+    match expr {
+        Literal(_)
+        | Tag { .. }
+        | Struct(_)
+        | StructAtIndex { .. }
+        | GetTagId { .. }
+        | UnionAtIndex { .. }
+        | Array { .. }
+        | EmptyArray
+        | ExprBox { .. }
+        | ExprUnbox { .. } => true,
+        Call(_) | Reuse { .. } | Reset { .. } | RuntimeErrorFunction(_) => false,
+    }
+}
+
+fn remove_dead_code<'a>(
+    arena: &'a Bump,
+    uses: &Uses,
+    stmt: &'a Stmt<'a>,
+    changed: &mut bool,
+) -> &'a Stmt<'a> {
+    use Stmt::*;
+
+    match stmt {
+        Let(symbol, expr, _, cont) if !uses.symbols.contains(symbol) && is_pure(expr) => {
+            *changed = true;
+            remove_dead_code(arena, uses, cont, changed)
+        }
+        Let(symbol, expr, layout, cont) => arena.alloc(Let(
+            *symbol,
+            expr.clone(),
+            *layout,
+            remove_dead_code(arena, uses, cont, changed),
+        )),
+        Join { id, remainder, .. } if !uses.join_points.contains(id) => {
+            *changed = true;
+            remove_dead_code(arena, uses, remainder, changed)
+        }
+        Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => arena.alloc(Join {
+            id: *id,
+            parameters,
+            body: remove_dead_code(arena, uses, body, changed),
+            remainder: remove_dead_code(arena, uses, remainder, changed),
+        }),
+        Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => {
+            let branches = Vec::from_iter_in(
+                branches.iter().map(|(value, info, branch)| {
+                    let branch = remove_dead_code(arena, uses, branch, changed);
+                    (*value, info.clone(), branch.clone())
+                }),
+                arena,
+            );
+            let default_branch = (
+                default_branch.0.clone(),
+                remove_dead_code(arena, uses, default_branch.1, changed),
+            );
+
+            arena.alloc(Switch {
+                cond_symbol: *cond_symbol,
+                cond_layout: *cond_layout,
+                branches: branches.into_bump_slice(),
+                default_branch,
+                ret_layout: *ret_layout,
+            })
+        }
+        Refcounting(modify, cont) => arena.alloc(Refcounting(
+            *modify,
+            remove_dead_code(arena, uses, cont, changed),
+        )),
+        Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => arena.alloc(Expect {
+            condition: *condition,
+            region: *region,
+            lookups,
+            variables,
+            remainder: remove_dead_code(arena, uses, remainder, changed),
+        }),
+        ExpectFx {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => arena.alloc(ExpectFx {
+            condition: *condition,
+            region: *region,
+            lookups,
+            variables,
+            remainder: remove_dead_code(arena, uses, remainder, changed),
+        }),
+        Dbg {
+            symbol,
+            variable,
+            remainder,
+        } => arena.alloc(Dbg {
+            symbol: *symbol,
+            variable: *variable,
+            remainder: remove_dead_code(arena, uses, remainder, changed),
+        }),
+        Ret(_) | Jump(_, _) | Crash(_, _) => stmt,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::fold_int;
+    use roc_builtins::bitcode::IntWidth;
+    use roc_module::low_level::LowLevel::*;
+
+    #[test]
+    fn fold_int_overflow() {
+        assert_eq!(fold_int(NumAdd, IntWidth::U8, 200, 55), Some(255));
+        assert_eq!(fold_int(NumAdd, IntWidth::U8, 200, 56), None);
+        assert_eq!(fold_int(NumAddWrap, IntWidth::U8, 200, 56), Some(0));
+        assert_eq!(fold_int(NumSubWrap, IntWidth::I8, -128, 1), Some(127));
+        assert_eq!(fold_int(NumMulWrap, IntWidth::I16, 300, 300), Some(24464));
+        assert_eq!(fold_int(NumSubSaturated, IntWidth::U32, 3, 10), Some(0));
+        assert_eq!(fold_int(NumAddSaturated, IntWidth::I8, 100, 100), Some(127));
+        assert_eq!(fold_int(NumAddWrap, IntWidth::I128, i128::MAX, 1), None);
+        assert_eq!(
+            fold_int(NumAddSaturated, IntWidth::U128, i128::MAX, 1),
+            None
+        );
+        assert_eq!(fold_int(NumBitwiseXor, IntWidth::I8, -1, 0x0f), Some(-16));
+    }
+}
@@ -0,0 +1,718 @@
+//! Inlines calls to small, non-recursive procedures into their callers, and then folds
+//! constants in the result. This pass only runs for optimized builds, after specialization
+//! and before reset/reuse and refcount insertion.
+//!
+//! Procs are visited callee-first, so a proc that has already had its own calls inlined can in
+//! turn be inlined into its callers. Procs that are (mutually) recursive are never inlined.
+
+use crate::ir::{
+    BranchInfo, Call, CallSpecId, CallType, Expr, HigherOrderLowLevel, JoinPointId,
+    ListLiteralElement, ModifyRc, Param, PassedFunction, Proc, ProcLayout, SelfRecursive, Stmt,
+    UpdateModeIds,
+};
+use crate::layout::Layout;
+use crate::low_level::HigherOrder;
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_collections::all::{MutMap, MutSet};
+use roc_collections::ReferenceMatrix;
+use roc_error_macros::internal_error;
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+/// Procs with more statements than this are never inlined
+const INLINE_THRESHOLD: usize = 16;
+
+type ProcKey<'a> = (Symbol, ProcLayout<'a>);
+
+pub fn inline_procs<'a, 'i>(
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    update_mode_ids: &'i mut UpdateModeIds,
+    procs: &mut MutMap<ProcKey<'a>, Proc<'a>>,
+) {
+    let keys: std::vec::Vec<ProcKey<'a>> = procs.keys().copied().collect();
+    let key_to_index: MutMap<ProcKey<'a>, usize> =
+        keys.iter().enumerate().map(|(i, key)| (*key, i)).collect();
+
+    // rows are callers, columns are callees
+    let mut matrix = ReferenceMatrix::new(keys.len());
+    let mut max_call_spec_id = CallSpecId::BACKEND_DUMMY;
+
+    for (row, key) in keys.iter().enumerate() {
+        let mut calls = CallInfo {
+            callees: std::vec::Vec::new(),
+            max_call_spec_id: CallSpecId::BACKEND_DUMMY,
+        };
+        calls.stmt(&procs[key].body);
+
+        max_call_spec_id = max_call_spec_id.max(calls.max_call_spec_id);
+
+        for callee in calls.callees {
+            if let Some(col) = key_to_index.get(&callee) {
+                matrix.set_row_col(row, *col, true);
+            }
+        }
+    }
+
+    let mut env = Env {
+        arena,
+        home,
+        ident_ids,
+        update_mode_ids,
+        next_call_spec_id: max_call_spec_id.next(),
+        inlinable: MutSet::default(),
+    };
+
+    let sccs = matrix.strongly_connected_components_all();
+
+    for (group, _) in sccs.groups() {
+        let is_recursive = group.count_ones() > 1 || {
+            let index = group.first_one().unwrap();
+            matrix.get_row_col(index, index)
+        };
+
+        for index in group.iter_ones() {
+            let key = keys[index];
+            let mut proc = procs.remove(&key).unwrap();
+
+            let body = env.inline_stmt(procs, arena.alloc(proc.body.clone()));
+            let body = crate::const_fold::fold_constants(arena, body);
+            proc.body = body.clone();
+
+            if !is_recursive
+                && matches!(proc.is_self_recursive, SelfRecursive::NotSelfRecursive)
+                && can_inline(&proc.body, &mut 0)
+            {
+                env.inlinable.insert(key);
+            }
+
+            procs.insert(key, proc);
+        }
+    }
+}
+
+/// Whether a proc body is small enough to inline, and contains nothing that is tied to
+/// the location of the proc in the source (expects and dbgs report the region they are in)
+fn can_inline(stmt: &Stmt<'_>, size: &mut usize) -> bool {
+    use Stmt::*;
+
+    *size += 1;
+
+    if *size > INLINE_THRESHOLD {
+        return false;
+    }
+
+    match stmt {
+        Let(_, _, _, cont) | Refcounting(_, cont) => can_inline(cont, size),
+        Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            branches
+                .iter()
+                .all(|(_, _, branch)| can_inline(branch, size))
+                && can_inline(default_branch.1, size)
+        }
+        Join {
+            body, remainder, ..
+        } => can_inline(body, size) && can_inline(remainder, size),
+        Ret(_) | Jump(_, _) | Crash(_, _) => true,
+        Expect { .. } | ExpectFx { .. } | Dbg { .. } => false,
+    }
+}
+
+struct CallInfo<'a> {
+    callees: std::vec::Vec<ProcKey<'a>>,
+    max_call_spec_id: CallSpecId,
+}
+
+impl<'a> CallInfo<'a> {
+    fn stmt(&mut self, stmt: &Stmt<'a>) {
+        use Stmt::*;
+
+        match stmt {
+            Let(_, expr, _, cont) => {
+                if let Expr::Call(call) = expr {
+                    self.call(call);
+                }
+                self.stmt(cont);
+            }
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                for (_, _, branch) in branches.iter() {
+                    self.stmt(branch);
+                }
+                self.stmt(default_branch.1);
+            }
+            Join {
+                body, remainder, ..
+            } => {
+                self.stmt(body);
+                self.stmt(remainder);
+            }
+            Refcounting(_, cont) => self.stmt(cont),
+            Expect { remainder, .. } | ExpectFx { remainder, .. } | Dbg { remainder, .. } => {
+                self.stmt(remainder)
+            }
+            Ret(_) | Jump(_, _) | Crash(_, _) => {}
+        }
+    }
+
+    fn call(&mut self, call: &Call<'a>) {
+        match &call.call_type {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                specialization_id,
+            } => {
+                let proc_layout = ProcLayout {
+                    arguments: arg_layouts,
+                    result: **ret_layout,
+                    captures_niche: name.captures_niche(),
+                };
+                self.callees.push((name.name(), proc_layout));
+                self.max_call_spec_id = self.max_call_spec_id.max(*specialization_id);
+            }
+            CallType::HigherOrder(HigherOrderLowLevel {
+                passed_function, ..
+            }) => {
+                let proc_layout = ProcLayout {
+                    arguments: passed_function.argument_layouts,
+                    result: passed_function.return_layout,
+                    captures_niche: passed_function.name.captures_niche(),
+                };
+                self.callees
+                    .push((passed_function.name.name(), proc_layout));
+                self.max_call_spec_id =
+                    self.max_call_spec_id.max(passed_function.specialization_id);
+            }
+            CallType::Foreign { .. } | CallType::LowLevel { .. } => {}
+        }
+    }
+}
+
+struct Env<'a, 'i> {
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    update_mode_ids: &'i mut UpdateModeIds,
+    next_call_spec_id: CallSpecId,
+    /// Procs that have been optimized already, and can be inlined into their callers
+    inlinable: MutSet<ProcKey<'a>>,
+}
+
+/// What an inlined `ret` turns into
+#[derive(Clone, Copy)]
+enum Return<'a> {
+    /// The call was in tail position, so the callee's return is the caller's return
+    Ret,
+    /// Jump to the join point that continues the caller
+    Jump(JoinPointId),
+    /// The callee body is a straight line, so the caller's continuation is pasted in directly
+    Continue(&'a Stmt<'a>),
+}
+
+/// Renaming of the symbols and join points in a copied proc body
+#[derive(Default)]
+struct Substitutions {
+    symbols: MutMap<Symbol, Symbol>,
+    join_points: MutMap<JoinPointId, JoinPointId>,
+    /// The symbol that the callee returns is bound directly to the call's result symbol
+    returned: Option<(Symbol, Symbol)>,
+}
+
+impl Substitutions {
+    fn symbol(&self, symbol: Symbol) -> Symbol {
+        *self.symbols.get(&symbol).unwrap_or(&symbol)
+    }
+
+    fn symbols<'a>(&self, arena: &'a Bump, symbols: &[Symbol]) -> &'a [Symbol] {
+        Vec::from_iter_in(symbols.iter().map(|s| self.symbol(*s)), arena).into_bump_slice()
+    }
+}
+
+impl<'a, 'i> Env<'a, 'i> {
+    fn unique_symbol(&mut self) -> Symbol {
+        Symbol::new(self.home, self.ident_ids.gen_unique())
+    }
+
+    fn next_call_spec_id(&mut self) -> CallSpecId {
+        let id = self.next_call_spec_id;
+        self.next_call_spec_id = id.next();
+        id
+    }
+
+    fn inline_stmt(
+        &mut self,
+        procs: &MutMap<ProcKey<'a>, Proc<'a>>,
+        stmt: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        use Stmt::*;
+
+        let arena = self.arena;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                let cont = self.inline_stmt(procs, cont);
+
+                if let Expr::Call(Call {
+                    call_type:
+                        CallType::ByName {
+                            name,
+                            ret_layout,
+                            arg_layouts,
+                            ..
+                        },
+                    arguments,
+                }) = expr
+                {
+                    let key = (
+                        name.name(),
+                        ProcLayout {
+                            arguments: arg_layouts,
+                            result: **ret_layout,
+                            captures_niche: name.captures_niche(),
+                        },
+                    );
+
+                    if self.inlinable.contains(&key) {
+                        let callee = &procs[&key];
+                        return self.inline_call(callee, arguments, *symbol, *layout, cont);
+                    }
+                }
+
+                arena.alloc(Let(*symbol, expr.clone(), *layout, cont))
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let branches = Vec::from_iter_in(
+                    branches.iter().map(|(value, info, branch)| {
+                        (
+                            *value,
+                            info.clone(),
+                            self.inline_stmt(procs, branch).clone(),
+                        )
+                    }),
+                    arena,
+                );
+                let default_branch = (
+                    default_branch.0.clone(),
+                    self.inline_stmt(procs, default_branch.1),
+                );
+
+                arena.alloc(Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: branches.into_bump_slice(),
+                    default_branch,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => arena.alloc(Join {
+                id: *id,
+                parameters,
+                body: self.inline_stmt(procs, body),
+                remainder: self.inline_stmt(procs, remainder),
+            }),
+            Refcounting(modify, cont) => {
+                arena.alloc(Refcounting(*modify, self.inline_stmt(procs, cont)))
+            }
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(Expect {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: self.inline_stmt(procs, remainder),
+            }),
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: self.inline_stmt(procs, remainder),
+            }),
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => arena.alloc(Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: self.inline_stmt(procs, remainder),
+            }),
+            Ret(_) | Jump(_, _) | Crash(_, _) => stmt,
+        }
+    }
+
+    /// Replace `let result = callee arguments in cont` with a copy of the body of `callee`
+    fn inline_call(
+        &mut self,
+        callee: &Proc<'a>,
+        arguments: &[Symbol],
+        result: Symbol,
+        layout: Layout<'a>,
+        cont: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        let mut subs = Substitutions::default();
+
+        for ((_, param), argument) in callee.args.iter().zip(arguments) {
+            subs.symbols.insert(*param, *argument);
+        }
+
+        if let Stmt::Ret(returned) = cont {
+            if *returned == result {
+                return self.copy_stmt(&mut subs, &callee.body, Return::Ret);
+            }
+        }
+
+        match straight_line_return(&callee.body) {
+            Some(returned) => {
+                subs.returned = Some((returned, result));
+                self.copy_stmt(&mut subs, &callee.body, Return::Continue(cont))
+            }
+            None => {
+                let id = JoinPointId(self.unique_symbol());
+                let remainder = self.copy_stmt(&mut subs, &callee.body, Return::Jump(id));
+                let param = Param {
+                    symbol: result,
+                    borrow: false,
+                    layout,
+                };
+
+                self.arena.alloc(Stmt::Join {
+                    id,
+                    parameters: self.arena.alloc([param]),
+                    body: cont,
+                    remainder,
+                })
+            }
+        }
+    }
+
+    fn bind(&mut self, subs: &mut Substitutions, symbol: Symbol) -> Symbol {
+        let new = match subs.returned {
+            Some((returned, result)) if returned == symbol => result,
+            _ => self.unique_symbol(),
+        };
+        subs.symbols.insert(symbol, new);
+        new
+    }
+
+    fn copy_stmt(
+        &mut self,
+        subs: &mut Substitutions,
+        stmt: &Stmt<'a>,
+        ret: Return<'a>,
+    ) -> &'a Stmt<'a> {
+        use Stmt::*;
+
+        let arena = self.arena;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                let expr = self.copy_expr(subs, expr);
+                let symbol = self.bind(subs, *symbol);
+                let cont = self.copy_stmt(subs, cont, ret);
+
+                arena.alloc(Let(symbol, expr, *layout, cont))
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let branches = Vec::from_iter_in(
+                    branches.iter().map(|(value, info, branch)| {
+                        let info = copy_branch_info(subs, info);
+                        (*value, info, self.copy_stmt(subs, branch, ret).clone())
+                    }),
+                    arena,
+                );
+                let default_branch = (
+                    copy_branch_info(subs, &default_branch.0),
+                    self.copy_stmt(subs, default_branch.1, ret),
+                );
+
+                arena.alloc(Switch {
+                    cond_symbol: subs.symbol(*cond_symbol),
+                    cond_layout: *cond_layout,
+                    branches: branches.into_bump_slice(),
+                    default_branch,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Ret(symbol) => {
+                let symbol = subs.symbol(*symbol);
+
+                match ret {
+                    Return::Ret => arena.alloc(Ret(symbol)),
+                    Return::Jump(id) => arena.alloc(Jump(id, arena.alloc([symbol]))),
+                    Return::Continue(cont) => cont,
+                }
+            }
+            Refcounting(modify, cont) => {
+                let modify = match modify {
+                    ModifyRc::Inc(symbol, n) => ModifyRc::Inc(subs.symbol(*symbol), *n),
+                    ModifyRc::Dec(symbol) => ModifyRc::Dec(subs.symbol(*symbol)),
+                    ModifyRc::DecRef(symbol) => ModifyRc::DecRef(subs.symbol(*symbol)),
+                };
+
+                arena.alloc(Refcounting(modify, self.copy_stmt(subs, cont, ret)))
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let new_id = JoinPointId(self.unique_symbol());
+                subs.join_points.insert(*id, new_id);
+
+                let parameters = Vec::from_iter_in(
+                    parameters.iter().map(|param| Param {
+                        symbol: self.bind(subs, param.symbol),
+                        ..*param
+                    }),
+                    arena,
+                );
+
+                arena.alloc(Join {
+                    id: new_id,
+                    parameters: parameters.into_bump_slice(),
+                    body: self.copy_stmt(subs, body, ret),
+                    remainder: self.copy_stmt(subs, remainder, ret),
+                })
+            }
+            Jump(id, arguments) => {
+                arena.alloc(Jump(subs.join_points[id], subs.symbols(arena, arguments)))
+            }
+            Crash(symbol, tag) => arena.alloc(Crash(subs.symbol(*symbol), *tag)),
+            Expect { .. } | ExpectFx { .. } | Dbg { .. } => {
+                internal_error!("procs with expects or dbgs are never inlined")
+            }
+        }
+    }
+
+    fn copy_expr(&mut self, subs: &Substitutions, expr: &Expr<'a>) -> Expr<'a> {
+        use Expr::*;
+
+        let arena = self.arena;
+
+        match expr {
+            Literal(_) | EmptyArray | RuntimeErrorFunction(_) => expr.clone(),
+            Call(call) => Call(self.copy_call(subs, call)),
+            Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+            } => Tag {
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: subs.symbols(arena, arguments),
+            },
+            Struct(arguments) => Struct(subs.symbols(arena, arguments)),
+            StructAtIndex {
+                index,
+                field_layouts,
+                structure,
+            } => StructAtIndex {
+                index: *index,
+                field_layouts,
+                structure: subs.symbol(*structure),
+            },
+            GetTagId {
+                structure,
+                union_layout,
+            } => GetTagId {
+                structure: subs.symbol(*structure),
+                union_layout: *union_layout,
+            },
+            UnionAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => UnionAtIndex {
+                structure: subs.symbol(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            Array { elem_layout, elems } => {
+                let elems = Vec::from_iter_in(
+                    elems.iter().map(|elem| match elem {
+                        ListLiteralElement::Symbol(symbol) => {
+                            ListLiteralElement::Symbol(subs.symbol(*symbol))
+                        }
+                        ListLiteralElement::Literal(_) => *elem,
+                    }),
+                    arena,
+                );
+
+                Array {
+                    elem_layout: *elem_layout,
+                    elems: elems.into_bump_slice(),
+                }
+            }
+            ExprBox { symbol } => ExprBox {
+                symbol: subs.symbol(*symbol),
+            },
+            ExprUnbox { symbol } => ExprUnbox {
+                symbol: subs.symbol(*symbol),
+            },
+            Reuse {
+                symbol,
+                update_tag_id,
+                update_mode: _,
+                tag_layout,
+                tag_id,
+                arguments,
+            } => Reuse {
+                symbol: subs.symbol(*symbol),
+                update_tag_id: *update_tag_id,
+                update_mode: self.update_mode_ids.next_id(),
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: subs.symbols(arena, arguments),
+            },
+            Reset {
+                symbol,
+                update_mode: _,
+            } => Reset {
+                symbol: subs.symbol(*symbol),
+                update_mode: self.update_mode_ids.next_id(),
+            },
+        }
+    }
+
+    fn copy_call(&mut self, subs: &Substitutions, call: &Call<'a>) -> Call<'a> {
+        let call_type = match &call.call_type {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                specialization_id: _,
+            } => CallType::ByName {
+                name: *name,
+                ret_layout,
+                arg_layouts,
+                specialization_id: self.next_call_spec_id(),
+            },
+            CallType::Foreign { .. } => call.call_type.clone(),
+            CallType::LowLevel { op, update_mode: _ } => CallType::LowLevel {
+                op: *op,
+                update_mode: self.update_mode_ids.next_id(),
+            },
+            CallType::HigherOrder(higher_order) => {
+                let passed_function = PassedFunction {
+                    specialization_id: self.next_call_spec_id(),
+                    captured_environment: subs
+                        .symbol(higher_order.passed_function.captured_environment),
+                    ..higher_order.passed_function
+                };
+
+                CallType::HigherOrder(self.arena.alloc(HigherOrderLowLevel {
+                    op: copy_higher_order(subs, &higher_order.op),
+                    closure_env_layout: higher_order.closure_env_layout,
+                    update_mode: self.update_mode_ids.next_id(),
+                    passed_function,
+                }))
+            }
+        };
+
+        Call {
+            call_type,
+            arguments: subs.symbols(self.arena, call.arguments),
+        }
+    }
+}
+
+fn copy_higher_order(subs: &Substitutions, op: &HigherOrder) -> HigherOrder {
+    use HigherOrder::*;
+
+    match op {
+        ListMap { xs } => ListMap {
+            xs: subs.symbol(*xs),
+        },
+        ListMap2 { xs, ys } => ListMap2 {
+            xs: subs.symbol(*xs),
+            ys: subs.symbol(*ys),
+        },
+        ListMap3 { xs, ys, zs } => ListMap3 {
+            xs: subs.symbol(*xs),
+            ys: subs.symbol(*ys),
+            zs: subs.symbol(*zs),
+        },
+        ListMap4 { xs, ys, zs, ws } => ListMap4 {
+            xs: subs.symbol(*xs),
+            ys: subs.symbol(*ys),
+            zs: subs.symbol(*zs),
+            ws: subs.symbol(*ws),
+        },
+        ListSortWith { xs } => ListSortWith {
+            xs: subs.symbol(*xs),
+        },
+    }
+}
+
+fn copy_branch_info<'a>(subs: &Substitutions, info: &BranchInfo<'a>) -> BranchInfo<'a> {
+    match info {
+        BranchInfo::None => BranchInfo::None,
+        BranchInfo::Constructor {
+            scrutinee,
+            layout,
+            tag_id,
+        } => BranchInfo::Constructor {
+            scrutinee: subs.symbol(*scrutinee),
+            layout: *layout,
+            tag_id: *tag_id,
+        },
+    }
+}
+
+/// If `stmt` is a chain of `let`s that returns one of the symbols it defines, return that symbol
+fn straight_line_return(stmt: &Stmt<'_>) -> Option<Symbol> {
+    let mut bound = MutSet::default();
+    let mut current = stmt;
+
+    loop {
+        match current {
+            Stmt::Let(symbol, _, _, cont) => {
+                bound.insert(*symbol);
+                current = cont;
+            }
+            Stmt::Ret(symbol) if bound.contains(symbol) => return Some(*symbol),
+            _ => return None,
+        }
+    }
+}
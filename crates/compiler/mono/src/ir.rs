@@ -16,8 +16,8 @@ use roc_collections::VecMap;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION,
-    ROC_PRINT_RUNTIME_ERROR_GEN,
+    ROC_PRINT_IR_AFTER_INLINING, ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE,
+    ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_RUNTIME_ERROR_GEN,
 };
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::{internal_error, todo_abilities};
@@ -44,6 +44,9 @@ pub fn pretty_print_ir_symbols() -> bool {
     dbg_do!(ROC_PRINT_IR_AFTER_SPECIALIZATION, {
         return true;
     });
+    dbg_do!(ROC_PRINT_IR_AFTER_INLINING, {
+        return true;
+    });
     dbg_do!(ROC_PRINT_IR_AFTER_RESET_REUSE, {
         return true;
     });
@@ -434,6 +437,16 @@ impl<'a> Proc<'a> {
         }
     }
 
+    pub fn inline_and_fold_constants<'i>(
+        arena: &'a Bump,
+        home: ModuleId,
+        ident_ids: &'i mut IdentIds,
+        update_mode_ids: &'i mut UpdateModeIds,
+        procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    ) {
+        crate::inline::inline_procs(arena, home, ident_ids, update_mode_ids, procs);
+    }
+
     fn make_tail_recursive(&mut self, env: &mut Env<'a, '_>) {
         let mut args = Vec::with_capacity_in(self.args.len(), env.arena);
         let mut proc_args = Vec::with_capacity_in(self.args.len(), env.arena);
@@ -1852,7 +1865,7 @@ impl<'a> Call<'a> {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CallSpecId {
     id: u32,
 }
@@ -1862,6 +1875,11 @@ impl CallSpecId {
         self.id.to_ne_bytes()
     }
 
+    /// The id directly after this one, used when call sites are duplicated after specialization
+    pub(crate) fn next(self) -> Self {
+        Self { id: self.id + 1 }
+    }
+
     /// Dummy value for generating refcount helper procs in the backends
     /// This happens *after* specialization so it's safe
     pub const BACKEND_DUMMY: Self = Self { id: 0 };
@@ -2945,7 +2963,10 @@ fn pattern_to_when<'a>(
             (env.unique_symbol(), Loc::at_zero(RuntimeError(error)))
         }
 
-        AppliedTag { .. } | RecordDestructure { .. } | UnwrappedOpaque { .. } => {
+        AppliedTag { .. }
+        | RecordDestructure { .. }
+        | TupleDestructure { .. }
+        | UnwrappedOpaque { .. } => {
             let symbol = env.unique_symbol();
 
             let wrapped_body = When {
@@ -4527,7 +4548,142 @@ pub fn with_hole<'a>(
 
         EmptyRecord => let_empty_struct(assigned, hole),
 
-        Expect { .. } => unreachable!("I think this is unreachable"),
+        Tuple {
+            tuple_var, elems, ..
+        } => {
+            let sorted_elems_result = {
+                let mut layout_env = layout::Env::from_components(
+                    layout_cache,
+                    env.subs,
+                    env.arena,
+                    env.target_info,
+                );
+                layout::sort_tuple_elems(&mut layout_env, tuple_var)
+            };
+            let sorted_elems = match sorted_elems_result {
+                Ok(elems) => elems,
+                Err(_) => return runtime_error(env, "Can't create tuple with improper layout"),
+            };
+
+            let mut elems = elems.into_iter().map(Some).collect::<std::vec::Vec<_>>();
+
+            let mut elem_symbols = Vec::with_capacity_in(elems.len(), env.arena);
+            let mut can_elems = Vec::with_capacity_in(elems.len(), env.arena);
+
+            #[allow(clippy::enum_variant_names)]
+            enum Field {
+                // TODO: rename this since it can handle unspecialized expressions now too
+                FunctionOrUnspecialized(Symbol, Variable),
+                ValueSymbol,
+                Field(Variable, Loc<roc_can::expr::Expr>),
+            }
+
+            for (index, variable, _) in sorted_elems.into_iter() {
+                // TODO how should function pointers be handled here?
+                use ReuseSymbol::*;
+                let (var, loc_expr) = elems[index]
+                    .take()
+                    .unwrap_or_else(|| internal_error!("tuple element {} is missing", index));
+
+                match can_reuse_symbol(env, procs, &loc_expr.value, var) {
+                    Imported(symbol) | LocalFunction(symbol) | UnspecializedExpr(symbol) => {
+                        elem_symbols.push(symbol);
+                        can_elems.push(Field::FunctionOrUnspecialized(symbol, variable));
+                    }
+                    Value(symbol) => {
+                        let reusable = procs.get_or_insert_symbol_specialization(
+                            env,
+                            layout_cache,
+                            symbol,
+                            var,
+                        );
+                        elem_symbols.push(reusable);
+                        can_elems.push(Field::ValueSymbol);
+                    }
+                    NotASymbol => {
+                        elem_symbols.push(env.unique_symbol());
+                        can_elems.push(Field::Field(var, *loc_expr));
+                    }
+                }
+            }
+
+            // creating a tuple from the var will unpack it if it's just a single element.
+            let layout = match layout_cache.from_var(env.arena, tuple_var, env.subs) {
+                Ok(layout) => layout,
+                Err(_) => return runtime_error(env, "Can't create tuple with improper layout"),
+            };
+
+            let elem_symbols = elem_symbols.into_bump_slice();
+
+            let mut stmt = if let [only_elem] = elem_symbols {
+                let mut hole = hole.clone();
+                substitute_in_exprs(env.arena, &mut hole, assigned, *only_elem);
+                hole
+            } else {
+                Stmt::Let(assigned, Expr::Struct(elem_symbols), layout, hole)
+            };
+
+            for (opt_elem, symbol) in can_elems.into_iter().rev().zip(elem_symbols.iter().rev()) {
+                match opt_elem {
+                    Field::ValueSymbol => {
+                        // this symbol is already defined; nothing to do
+                    }
+                    Field::FunctionOrUnspecialized(symbol, variable) => {
+                        stmt = specialize_symbol(
+                            env,
+                            procs,
+                            layout_cache,
+                            Some(variable),
+                            symbol,
+                            env.arena.alloc(stmt),
+                            symbol,
+                        );
+                    }
+                    Field::Field(var, loc_expr) => {
+                        stmt = with_hole(
+                            env,
+                            loc_expr.value,
+                            var,
+                            procs,
+                            layout_cache,
+                            *symbol,
+                            env.arena.alloc(stmt),
+                        );
+                    }
+                }
+            }
+
+            stmt
+        }
+
+        Expect {
+            loc_condition,
+            loc_continuation,
+            lookups_in_cond,
+        } => {
+            // An `expect` in the middle of an expression, like in the body of a top-level
+            // `expect`: the continuation is what fills the hole
+            let rest = with_hole(
+                env,
+                loc_continuation.value,
+                variable,
+                procs,
+                layout_cache,
+                assigned,
+                hole,
+            );
+
+            specialize_expect(
+                env,
+                procs,
+                layout_cache,
+                *loc_condition,
+                &lookups_in_cond,
+                rest,
+                ExpectKind::Pure,
+            )
+        }
+
         ExpectFx { .. } => unreachable!("I think this is unreachable"),
         Dbg { .. } => unreachable!("I think this is unreachable"),
 
@@ -4894,6 +5050,83 @@ pub fn with_hole<'a>(
             stmt
         }
 
+        TupleAccess {
+            tuple_var,
+            elem_var,
+            index: accessed_index,
+            loc_expr,
+            ..
+        } => {
+            let sorted_elems_result = {
+                let mut layout_env = layout::Env::from_components(
+                    layout_cache,
+                    env.subs,
+                    env.arena,
+                    env.target_info,
+                );
+                layout::sort_tuple_elems(&mut layout_env, tuple_var)
+            };
+            let sorted_elems = match sorted_elems_result {
+                Ok(elems) => elems,
+                Err(_) => return runtime_error(env, "Can't access tuple with improper layout"),
+            };
+
+            let mut final_index = None;
+            let mut elem_layouts = Vec::with_capacity_in(sorted_elems.len(), env.arena);
+
+            for (current, (index, _, elem_layout)) in sorted_elems.into_iter().enumerate() {
+                elem_layouts.push(elem_layout);
+
+                if index == accessed_index {
+                    final_index = Some(current);
+                }
+            }
+
+            let tuple_symbol = possible_reuse_symbol_or_specialize(
+                env,
+                procs,
+                layout_cache,
+                &loc_expr.value,
+                tuple_var,
+            );
+
+            let mut stmt = match elem_layouts.as_slice() {
+                [_] => {
+                    let mut hole = hole.clone();
+                    substitute_in_exprs(env.arena, &mut hole, assigned, tuple_symbol);
+
+                    hole
+                }
+                _ => {
+                    let expr = Expr::StructAtIndex {
+                        index: final_index.expect("elem not in its own type") as u64,
+                        field_layouts: elem_layouts.into_bump_slice(),
+                        structure: tuple_symbol,
+                    };
+
+                    let layout = layout_cache
+                        .from_var(env.arena, elem_var, env.subs)
+                        .unwrap_or_else(|err| {
+                            panic!("TODO turn fn_var into a RuntimeError {:?}", err)
+                        });
+
+                    Stmt::Let(assigned, expr, layout, hole)
+                }
+            };
+
+            stmt = assign_to_symbol(
+                env,
+                procs,
+                layout_cache,
+                tuple_var,
+                *loc_expr,
+                tuple_symbol,
+                stmt,
+            );
+
+            stmt
+        }
+
         Accessor(accessor_data) => {
             let field_var = accessor_data.field_var;
             let fresh_record_symbol = env.unique_symbol();
@@ -6590,67 +6823,16 @@ pub fn from_can<'a>(
             lookups_in_cond,
         } => {
             let rest = from_can(env, variable, loc_continuation.value, procs, layout_cache);
-            let cond_symbol = env.unique_symbol();
 
-            let mut lookups = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
-            let mut lookup_variables = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
-            let mut specialized_variables = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
-
-            for ExpectLookup {
-                symbol,
-                var,
-                ability_info,
-            } in lookups_in_cond.iter().copied()
-            {
-                let symbol = match ability_info {
-                    Some(specialization_id) => late_resolve_ability_specialization(
-                        env,
-                        symbol,
-                        Some(specialization_id),
-                        var,
-                    ),
-                    None => symbol,
-                };
-
-                let expectation_subs = env
-                    .expectation_subs
-                    .as_deref_mut()
-                    .expect("if expects are compiled, their subs should be available");
-                let spec_var = expectation_subs.fresh_unnamed_flex_var();
-
-                if !env.subs.is_function(var) {
-                    // Exclude functions from lookups
-                    lookups.push(symbol);
-                    lookup_variables.push(var);
-                    specialized_variables.push(spec_var);
-                }
-            }
-
-            let specialized_variables = specialized_variables.into_bump_slice();
-
-            let mut stmt = Stmt::Expect {
-                condition: cond_symbol,
-                region: loc_condition.region,
-                lookups: lookups.into_bump_slice(),
-                variables: specialized_variables,
-                remainder: env.arena.alloc(rest),
-            };
-
-            stmt = with_hole(
+            specialize_expect(
                 env,
-                loc_condition.value,
-                Variable::BOOL,
                 procs,
                 layout_cache,
-                cond_symbol,
-                env.arena.alloc(stmt),
-            );
-
-            // Now that the condition has been specialized, export the specialized types of our
-            // lookups into the expectation subs.
-            store_specialized_expectation_lookups(env, lookup_variables, specialized_variables);
-
-            stmt
+                *loc_condition,
+                &lookups_in_cond,
+                rest,
+                ExpectKind::Pure,
+            )
         }
 
         ExpectFx {
@@ -6659,65 +6841,16 @@ pub fn from_can<'a>(
             lookups_in_cond,
         } => {
             let rest = from_can(env, variable, loc_continuation.value, procs, layout_cache);
-            let cond_symbol = env.unique_symbol();
-
-            let mut lookups = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
-            let mut lookup_variables = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
-            let mut specialized_variables = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
 
-            for ExpectLookup {
-                symbol,
-                var,
-                ability_info,
-            } in lookups_in_cond.iter().copied()
-            {
-                let symbol = match ability_info {
-                    Some(specialization_id) => late_resolve_ability_specialization(
-                        env,
-                        symbol,
-                        Some(specialization_id),
-                        var,
-                    ),
-                    None => symbol,
-                };
-
-                let expectation_subs = env
-                    .expectation_subs
-                    .as_deref_mut()
-                    .expect("if expects are compiled, their subs should be available");
-                let spec_var = expectation_subs.fresh_unnamed_flex_var();
-
-                if !env.subs.is_function(var) {
-                    // Exclude functions from lookups
-                    lookups.push(symbol);
-                    lookup_variables.push(var);
-                    specialized_variables.push(spec_var);
-                }
-            }
-
-            let specialized_variables = specialized_variables.into_bump_slice();
-
-            let mut stmt = Stmt::ExpectFx {
-                condition: cond_symbol,
-                region: loc_condition.region,
-                lookups: lookups.into_bump_slice(),
-                variables: specialized_variables,
-                remainder: env.arena.alloc(rest),
-            };
-
-            stmt = with_hole(
+            specialize_expect(
                 env,
-                loc_condition.value,
-                Variable::BOOL,
                 procs,
                 layout_cache,
-                cond_symbol,
-                env.arena.alloc(stmt),
-            );
-
-            store_specialized_expectation_lookups(env, lookup_variables, specialized_variables);
-
-            stmt
+                *loc_condition,
+                &lookups_in_cond,
+                rest,
+                ExpectKind::Fx,
+            )
         }
 
         Dbg {
@@ -6800,6 +6933,94 @@ pub fn from_can<'a>(
     }
 }
 
+#[derive(Clone, Copy)]
+enum ExpectKind {
+    Pure,
+    Fx,
+}
+
+/// Builds an `expect` (or `expect-fx`) that runs before `rest`. The symbols its condition looks
+/// up are recorded, along with their specialized types, so they can be shown if it fails.
+fn specialize_expect<'a>(
+    env: &mut Env<'a, '_>,
+    procs: &mut Procs<'a>,
+    layout_cache: &mut LayoutCache<'a>,
+    loc_condition: Loc<roc_can::expr::Expr>,
+    lookups_in_cond: &[ExpectLookup],
+    rest: Stmt<'a>,
+    kind: ExpectKind,
+) -> Stmt<'a> {
+    let cond_symbol = env.unique_symbol();
+
+    let mut lookups = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
+    let mut lookup_variables = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
+    let mut specialized_variables = Vec::with_capacity_in(lookups_in_cond.len(), env.arena);
+
+    for ExpectLookup {
+        symbol,
+        var,
+        ability_info,
+    } in lookups_in_cond.iter().copied()
+    {
+        let symbol = match ability_info {
+            Some(specialization_id) => {
+                late_resolve_ability_specialization(env, symbol, Some(specialization_id), var)
+            }
+            None => symbol,
+        };
+
+        let expectation_subs = env
+            .expectation_subs
+            .as_deref_mut()
+            .expect("if expects are compiled, their subs should be available");
+        let spec_var = expectation_subs.fresh_unnamed_flex_var();
+
+        if !env.subs.is_function(var) {
+            // Exclude functions from lookups
+            lookups.push(symbol);
+            lookup_variables.push(var);
+            specialized_variables.push(spec_var);
+        }
+    }
+
+    let specialized_variables = specialized_variables.into_bump_slice();
+    let lookups = lookups.into_bump_slice();
+    let remainder = env.arena.alloc(rest);
+
+    let stmt = match kind {
+        ExpectKind::Pure => Stmt::Expect {
+            condition: cond_symbol,
+            region: loc_condition.region,
+            lookups,
+            variables: specialized_variables,
+            remainder,
+        },
+        ExpectKind::Fx => Stmt::ExpectFx {
+            condition: cond_symbol,
+            region: loc_condition.region,
+            lookups,
+            variables: specialized_variables,
+            remainder,
+        },
+    };
+
+    let stmt = with_hole(
+        env,
+        loc_condition.value,
+        Variable::BOOL,
+        procs,
+        layout_cache,
+        cond_symbol,
+        env.arena.alloc(stmt),
+    );
+
+    // Now that the condition has been specialized, export the specialized types of our
+    // lookups into the expectation subs.
+    store_specialized_expectation_lookups(env, lookup_variables, specialized_variables);
+
+    stmt
+}
+
 fn store_specialized_expectation_lookups(
     env: &mut Env,
     lookup_variables: impl IntoIterator<Item = Variable>,
@@ -7592,6 +7813,44 @@ fn store_pattern_help<'a>(
                 }
             }
         }
+        TupleDestructure(destructs, [_single_elem]) => {
+            debug_assert_eq!(destructs.len(), 1);
+            return store_pattern_help(
+                env,
+                procs,
+                layout_cache,
+                &destructs[0].pat,
+                outer_symbol,
+                stmt,
+            );
+        }
+        TupleDestructure(destructs, sorted_elems) => {
+            let mut is_productive = false;
+            for (index, destruct) in destructs.iter().enumerate().rev() {
+                match store_tuple_destruct(
+                    env,
+                    procs,
+                    layout_cache,
+                    destruct,
+                    index as u64,
+                    outer_symbol,
+                    sorted_elems,
+                    stmt,
+                ) {
+                    StorePattern::Productive(new) => {
+                        is_productive = true;
+                        stmt = new;
+                    }
+                    StorePattern::NotProductive(new) => {
+                        stmt = new;
+                    }
+                }
+            }
+
+            if !is_productive {
+                return StorePattern::NotProductive(stmt);
+            }
+        }
         RecordDestructure(destructs, sorted_fields) => {
             let mut is_productive = false;
             for (index, destruct) in destructs.iter().enumerate().rev() {
@@ -7975,6 +8234,72 @@ fn store_newtype_pattern<'a>(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn store_tuple_destruct<'a>(
+    env: &mut Env<'a, '_>,
+    procs: &mut Procs<'a>,
+    layout_cache: &mut LayoutCache<'a>,
+    destruct: &TupleDestruct<'a>,
+    index: u64,
+    outer_symbol: Symbol,
+    sorted_elems: &'a [Layout<'a>],
+    mut stmt: Stmt<'a>,
+) -> StorePattern<'a> {
+    use Pattern::*;
+
+    let load = Expr::StructAtIndex {
+        index,
+        field_layouts: sorted_elems,
+        structure: outer_symbol,
+    };
+
+    match &destruct.pat {
+        Identifier(symbol) => {
+            let specialization_symbol = procs
+                .symbol_specializations
+                .remove_single(*symbol)
+                // Can happen when the symbol was never used under this body, and hence has no
+                // requested specialization.
+                .unwrap_or(*symbol);
+
+            stmt = Stmt::Let(
+                specialization_symbol,
+                load,
+                destruct.layout,
+                env.arena.alloc(stmt),
+            );
+        }
+        Underscore => {
+            // important that this is special-cased to do nothing: mono tuple patterns will extract
+            // all the elements, but those not bound in the source code are guarded with the
+            // underscore pattern.
+            return StorePattern::NotProductive(stmt);
+        }
+        IntLiteral(_, _)
+        | FloatLiteral(_, _)
+        | DecimalLiteral(_)
+        | EnumLiteral { .. }
+        | BitLiteral { .. }
+        | StrLiteral(_) => {
+            return StorePattern::NotProductive(stmt);
+        }
+
+        _ => {
+            let symbol = env.unique_symbol();
+
+            match store_pattern_help(env, procs, layout_cache, &destruct.pat, symbol, stmt) {
+                StorePattern::Productive(new) => {
+                    stmt = new;
+                    stmt = Stmt::Let(symbol, load, destruct.layout, env.arena.alloc(stmt));
+                }
+                StorePattern::NotProductive(stmt) => return StorePattern::NotProductive(stmt),
+            }
+        }
+    }
+
+    StorePattern::Productive(stmt)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn store_record_destruct<'a>(
     env: &mut Env<'a, '_>,
@@ -9382,6 +9707,7 @@ pub enum Pattern<'a> {
     StrLiteral(Box<str>),
 
     RecordDestructure(Vec<'a, RecordDestruct<'a>>, &'a [Layout<'a>]),
+    TupleDestructure(Vec<'a, TupleDestruct<'a>>, &'a [Layout<'a>]),
     NewtypeDestructure {
         tag_name: TagName,
         arguments: Vec<'a, (Pattern<'a>, Layout<'a>)>,
@@ -9433,6 +9759,9 @@ impl<'a> Pattern<'a> {
                         }
                     }
                 }
+                Pattern::TupleDestructure(destructs, _) => {
+                    stack.extend(destructs.iter().map(|destruct| &destruct.pat))
+                }
                 Pattern::NewtypeDestructure { arguments, .. } => {
                     stack.extend(arguments.iter().map(|(t, _)| t))
                 }
@@ -9463,6 +9792,14 @@ pub enum DestructType<'a> {
     Guard(Pattern<'a>),
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct TupleDestruct<'a> {
+    pub index: usize,
+    pub variable: Variable,
+    pub layout: Layout<'a>,
+    pub pat: Pattern<'a>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct WhenBranch<'a> {
     pub patterns: Vec<'a, Pattern<'a>>,
@@ -10193,6 +10530,72 @@ fn from_can_pattern_help<'a>(
             ))
         }
 
+        TupleDestructure {
+            whole_var,
+            destructs,
+            ..
+        } => {
+            // sorted elements based on the type
+            let sorted_elems = {
+                let mut layout_env = layout::Env::from_components(
+                    layout_cache,
+                    env.subs,
+                    env.arena,
+                    env.target_info,
+                );
+                crate::layout::sort_tuple_elems(&mut layout_env, *whole_var)
+                    .map_err(RuntimeError::from)?
+            };
+
+            // sorted elements based on the destruct
+            let mut mono_destructs = Vec::with_capacity_in(destructs.len(), env.arena);
+            let mut destructs_by_index = Vec::with_capacity_in(destructs.len(), env.arena);
+            destructs_by_index.extend(destructs.iter().map(Some));
+
+            let mut elem_layouts = Vec::with_capacity_in(sorted_elems.len(), env.arena);
+
+            // the outer loop is the sequence based on the type, so that the mono destructs are
+            // in the same order as the elements of the struct layout.
+            for (index, variable, elem_layout) in sorted_elems.into_iter() {
+                match destructs_by_index.get_mut(index).and_then(Option::take) {
+                    Some(destruct) => {
+                        // this element is destructured by the pattern
+                        let (_, loc_pattern) = &destruct.value.typ;
+                        mono_destructs.push(TupleDestruct {
+                            index,
+                            variable,
+                            layout: elem_layout,
+                            pat: from_can_pattern_help(
+                                env,
+                                procs,
+                                layout_cache,
+                                &loc_pattern.value,
+                                assignments,
+                            )?,
+                        });
+                    }
+                    None => {
+                        // this element is not destructured by the pattern
+                        // put in an underscore
+                        mono_destructs.push(TupleDestruct {
+                            index,
+                            variable,
+                            layout: elem_layout,
+                            pat: Pattern::Underscore,
+                        });
+                    }
+                }
+
+                // the layout of this element is part of the layout of the tuple
+                elem_layouts.push(elem_layout);
+            }
+
+            Ok(Pattern::TupleDestructure(
+                mono_destructs,
+                elem_layouts.into_bump_slice(),
+            ))
+        }
+
         List {
             list_var: _,
             elem_var,
@@ -1,4 +1,4 @@
-use crate::layout::{ext_var_is_empty_record, ext_var_is_empty_tag_union};
+use crate::layout::{ext_var_is_empty_record, ext_var_is_empty_tag_union, ext_var_is_empty_tuple};
 use roc_builtins::bitcode::{FloatWidth, IntWidth};
 use roc_collections::all::MutMap;
 use roc_module::symbol::Symbol;
@@ -821,6 +821,23 @@ impl Layout {
 
                 Ok(Layout::Struct(slice))
             }
+            FlatType::Tuple(elems, ext) => {
+                debug_assert!(ext_var_is_empty_tuple(subs, *ext));
+
+                let slice = Slice::reserve(layouts, elems.len());
+
+                let it = slice.indices().zip(elems.iter_all());
+                for (target_index, (_, var_index)) in it {
+                    let var = subs.variables[var_index.index as usize];
+                    let layout = Layout::from_var_help(layouts, subs, var)?;
+
+                    layouts.layouts[target_index] = layout;
+                }
+
+                layouts.sort_slice_by_alignment(slice);
+
+                Ok(Layout::Struct(slice))
+            }
             FlatType::TagUnion(union_tags, ext) => {
                 debug_assert!(ext_var_is_empty_tag_union(subs, *ext));
 
@@ -862,6 +879,7 @@ impl Layout {
                 Ok(Layout::UnionRecursive(slices))
             }
             FlatType::EmptyRecord => Ok(Layout::UNIT),
+            FlatType::EmptyTuple => Ok(Layout::UNIT),
             FlatType::EmptyTagUnion => Ok(Layout::VOID),
         }
     }
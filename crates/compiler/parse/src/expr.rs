@@ -2436,12 +2436,17 @@ fn ident_to_expr<'a>(arena: &'a Bump, src: Ident<'a>) -> Expr<'a> {
             };
 
             // The remaining items in the iterator are record field accesses,
-            // e.g. `bar` in `foo.bar.baz`, followed by `baz`
+            // e.g. `bar` in `foo.bar.baz`, followed by `baz`, or tuple element
+            // accesses, e.g. `1` in `foo.1`
             for field in iter {
                 // Wrap the previous answer in the new one, so we end up
                 // with a nested Expr. That way, `foo.bar.baz` gets represented
                 // in the AST as if it had been written (foo.bar).baz all along.
-                answer = Expr::RecordAccess(arena.alloc(answer), field);
+                if field.starts_with(|c: char| c.is_ascii_digit()) {
+                    answer = Expr::TupleAccess(arena.alloc(answer), field);
+                } else {
+                    answer = Expr::RecordAccess(arena.alloc(answer), field);
+                }
             }
 
             answer
@@ -583,19 +583,24 @@ fn chomp_access_chain<'a>(buffer: &'a [u8], parts: &mut Vec<'a, &'a str>) -> Res
 
     while let Some(b'.') = buffer.get(chomped) {
         match &buffer.get(chomped + 1..) {
-            Some(slice) => match chomp_lowercase_part(slice) {
-                Ok(name) => {
-                    let value = unsafe {
-                        std::str::from_utf8_unchecked(
-                            &buffer[chomped + 1..chomped + 1 + name.len()],
-                        )
-                    };
-                    parts.push(value);
-
-                    chomped += name.len() + 1;
+            Some(slice) => {
+                // either a record field like `.foo`, or a tuple index like `.1`
+                let part = chomp_lowercase_part(slice).or_else(|_| chomp_integer_part(slice));
+
+                match part {
+                    Ok(name) => {
+                        let value = unsafe {
+                            std::str::from_utf8_unchecked(
+                                &buffer[chomped + 1..chomped + 1 + name.len()],
+                            )
+                        };
+                        parts.push(value);
+
+                        chomped += name.len() + 1;
+                    }
+                    Err(_) => return Err(chomped as u32 + 1),
                 }
-                Err(_) => return Err(chomped as u32 + 1),
-            },
+            }
             None => return Err(chomped as u32 + 1),
         }
     }
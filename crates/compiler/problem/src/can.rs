@@ -353,6 +353,7 @@ impl Problem {
             | Problem::RuntimeError(RuntimeError::EmptySingleQuote(region))
             | Problem::RuntimeError(RuntimeError::MultipleCharsInSingleQuote(region))
             | Problem::RuntimeError(RuntimeError::DegenerateBranch(region))
+            | Problem::RuntimeError(RuntimeError::InvalidTupleIndex(_, region))
             | Problem::InvalidAliasRigid { region, .. }
             | Problem::InvalidInterpolation(region)
             | Problem::InvalidHexadecimal(region)
@@ -422,6 +423,7 @@ impl Problem {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ExtensionTypeKind {
     Record,
+    Tuple,
     TagUnion,
 }
 
@@ -579,6 +581,9 @@ pub enum RuntimeError {
     MultipleCharsInSingleQuote(Region),
 
     DegenerateBranch(Region),
+
+    /// A tuple index that does not fit in a `usize`, like `t.99999999999999999999999`
+    InvalidTupleIndex(Box<str>, Region),
 }
 
 impl RuntimeError {
@@ -1489,6 +1489,53 @@ mod solve_expr {
         infer_eq("\\rec -> rec.x", "{ x : a }* -> a");
     }
 
+    #[test]
+    fn two_elem_tuple() {
+        infer_eq("(5, 3.14)", "( Num *, Float * )");
+    }
+
+    #[test]
+    fn tuple_literal_accessor() {
+        infer_eq("(5, 3.14).0", "Num *");
+    }
+
+    #[test]
+    fn tuple_arg() {
+        infer_eq("\\tup -> tup.0", "( a )* -> a");
+    }
+
+    #[test]
+    fn tuple_accessor_function() {
+        infer_eq(".0", "( a )* -> a");
+    }
+
+    #[test]
+    fn tuple_pattern() {
+        infer_eq_without_problem(
+            indoc!(
+                r#"
+                \(x, y) -> x + y
+                "#
+            ),
+            "( Num a, Num a )* -> Num a",
+        );
+    }
+
+    #[test]
+    fn tuple_annotation() {
+        infer_eq_without_problem(
+            indoc!(
+                r#"
+                swap : (a, b) -> (b, a)
+                swap = \(x, y) -> (y, x)
+
+                swap
+                "#
+            ),
+            "( a, b ) -> ( b, a )",
+        );
+    }
+
     #[test]
     fn record_with_bound_var() {
         infer_eq(
@@ -50,6 +50,29 @@ impl TypeError {
             TypeError::WrongSpecialization { .. } => RuntimeError,
         }
     }
+
+    /// Returns the region the problem is reported at, if it has one.
+    pub fn region(&self) -> Option<Region> {
+        match self {
+            TypeError::BadExpr(region, ..)
+            | TypeError::BadPattern(region, ..)
+            | TypeError::CircularType(region, ..)
+            | TypeError::BadExprMissingAbility(region, ..)
+            | TypeError::BadPatternMissingAbility(region, ..)
+            | TypeError::StructuralSpecialization { region, .. }
+            | TypeError::WrongSpecialization { region, .. } => Some(*region),
+            TypeError::CircularDef(cycle) => cycle.first().map(|entry| entry.symbol_region),
+            TypeError::UnfulfilledAbility(Unfulfilled::OpaqueUnderivable {
+                derive_region, ..
+            }) => Some(*derive_region),
+            TypeError::UnfulfilledAbility(_) | TypeError::UnexposedLookup(_) => None,
+            TypeError::Exhaustive(error) => Some(match error {
+                roc_exhaustive::Error::Incomplete(region, ..) => *region,
+                roc_exhaustive::Error::Redundant { branch_region, .. }
+                | roc_exhaustive::Error::Unmatchable { branch_region, .. } => *branch_region,
+            }),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -28,6 +28,20 @@ test_key_eq! {
     explicit_empty_record_and_implicit_empty_record:
         v!(EMPTY_RECORD), v!({})
 
+    same_tag_union:
+        v!([ A v!(U8) v!(STR), B v!(STR) ]), v!([ A v!(U8) v!(STR), B v!(STR) ])
+    same_tag_union_tags_diff_types:
+        v!([ A v!(U8) v!(U8), B v!(U8) ]), v!([ A v!(STR) v!(STR), B v!(STR) ])
+    same_tag_union_tags_any_order:
+        v!([ A v!(U8) v!(U8), B v!(U8), C ]), v!([ C, B v!(STR), A v!(STR) v!(STR) ])
+    explicit_empty_tag_union_and_implicit_empty_tag_union:
+        v!(EMPTY_TAG_UNION), v!([])
+
+    same_recursive_tag_union:
+        v!([ Nil, Cons v!(^lst)] as lst), v!([ Nil, Cons v!(^lst)] as lst)
+    same_tag_union_and_recursive_tag_union_fields:
+        v!([ Nil, Cons v!(STR)]), v!([ Nil, Cons v!(^lst)] as lst)
+
     list_list_diff_types:
         v!(Symbol::LIST_LIST v!(STR)), v!(Symbol::LIST_LIST v!(U8))
     str_str:
@@ -41,6 +55,13 @@ test_key_neq! {
         v!({ a: v!(U8), }), v!({ b: v!(U8), })
     record_empty_vs_nonempty:
         v!(EMPTY_RECORD), v!({ a: v!(U8), })
+
+    different_tag_union_tags:
+        v!([ A v!(U8) ]), v!([ B v!(U8) ])
+    tag_union_empty_vs_nonempty:
+        v!(EMPTY_TAG_UNION), v!([ B v!(U8) ])
+    different_tag_union_tag_arities:
+        v!([ A v!(U8) ]), v!([ A v!(U8) v!(U8) ])
 }
 
 #[test]
@@ -93,6 +114,27 @@ fn derivable_record_with_record_ext() {
     );
 }
 
+#[test]
+fn derivable_tag_ext_flex_var() {
+    check_derivable(
+        Decoder,
+        v!([ A v!(STR) ]* ),
+        DeriveKey::Decoder(FlatDecodableKey::TagUnion(vec![("A".into(), 1)])),
+    );
+}
+
+#[test]
+fn derivable_tag_with_tag_ext() {
+    check_derivable(
+        Decoder,
+        v!([ B v!(STR) v!(U8) ][ A v!(STR) ]),
+        DeriveKey::Decoder(FlatDecodableKey::TagUnion(vec![
+            ("A".into(), 1),
+            ("B".into(), 2),
+        ])),
+    );
+}
+
 #[test]
 fn list() {
     derive_test(Decoder, v!(Symbol::LIST_LIST v!(STR)), |golden| {
@@ -167,3 +209,153 @@ fn record_2_fields() {
         )
     })
 }
+
+#[test]
+fn tag_one_label_zero_args() {
+    derive_test(Decoder, v!([A]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A]
+        # Decoder [A] fmt | fmt has DecoderFormatting
+        # List U8, fmt -[[custom(10)]]-> { rest : List U8, result : [Err [TooShort], Ok [A]] } | fmt has DecoderFormatting
+        # Specialization lambda sets:
+        #   @<1>: [[custom(10)]]
+        #Derived.decoder_[A 0] =
+          custom
+            \#Derived.bytes, #Derived.fmt ->
+              decodeWith
+                #Derived.bytes
+                (tag
+                  \#Derived.tag ->
+                    when #Derived.tag is
+                      "A" -> Keep A
+                      _ -> Skip
+                  \#Derived.state2, #Derived.index -> Skip
+                  \#Derived.state ->
+                    when #Derived.state is
+                      A -> Ok A)
+                #Derived.fmt
+        "###)
+    })
+}
+
+#[test]
+fn tag_two_labels() {
+    derive_test(Decoder, v!([A v!(U8) v!(STR), B]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A U8 Str, B]
+        # Decoder [A val val1, B] fmt | fmt has DecoderFormatting, val has Decoding, val1 has Decoding
+        # List U8, fmt -[[custom(26)]]-> { rest : List U8, result : [Err [TooShort], Ok [A val val1, B]] } | fmt has DecoderFormatting, val has Decoding, val1 has Decoding
+        # Specialization lambda sets:
+        #   @<1>: [[custom(26)]]
+        #Derived.decoder_[A 2,B 0] =
+          custom
+            \#Derived.bytes3, #Derived.fmt3 ->
+              decodeWith
+                #Derived.bytes3
+                (tag
+                  \#Derived.tag ->
+                    when #Derived.tag is
+                      "A" -> Keep (A (Err NoElem) (Err NoElem))
+                      "B" -> Keep B
+                      _ -> Skip
+                  \#Derived.state2, #Derived.index ->
+                    when #Derived.state2 is
+                      A #Derived.e0 #Derived.e1 ->
+                        when #Derived.index is
+                          0 ->
+                            Keep (custom
+                              \#Derived.bytes, #Derived.fmt ->
+                                when decodeWith #Derived.bytes decoder #Derived.fmt is
+                                  #Derived.rec ->
+                                    {
+                                      result: when #Derived.rec.result is
+                                          Ok #Derived.val ->
+                                            Ok (A (Ok #Derived.val) #Derived.e1)
+                                          Err #Derived.err -> Err #Derived.err,
+                                      rest: #Derived.rec.rest
+                                    })
+                          1 ->
+                            Keep (custom
+                              \#Derived.bytes2, #Derived.fmt2 ->
+                                when decodeWith #Derived.bytes2 decoder #Derived.fmt2 is
+                                  #Derived.rec2 ->
+                                    {
+                                      result: when #Derived.rec2.result is
+                                          Ok #Derived.val2 ->
+                                            Ok (A #Derived.e0 (Ok #Derived.val2))
+                                          Err #Derived.err2 -> Err #Derived.err2,
+                                      rest: #Derived.rec2.rest
+                                    })
+                          _ -> Skip
+                      B -> Skip
+                  \#Derived.state ->
+                    when #Derived.state is
+                      A (Ok #Derived.v0) (Ok #Derived.v1) ->
+                        Ok (A #Derived.v0 #Derived.v1)
+                      B -> Ok B
+                      _ -> Err TooShort)
+                #Derived.fmt3
+        "###)
+    })
+}
+
+#[test]
+fn recursive_tag_union() {
+    derive_test(Decoder, v!([Nil, Cons v!(U8) v!(^lst) ] as lst), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [Cons U8 $rec, Nil] as $rec
+        # Decoder [Cons val val1, Nil] fmt | fmt has DecoderFormatting, val has Decoding, val1 has Decoding
+        # List U8, fmt -[[custom(26)]]-> { rest : List U8, result : [Err [TooShort], Ok [Cons val val1, Nil]] } | fmt has DecoderFormatting, val has Decoding, val1 has Decoding
+        # Specialization lambda sets:
+        #   @<1>: [[custom(26)]]
+        #Derived.decoder_[Cons 2,Nil 0] =
+          custom
+            \#Derived.bytes3, #Derived.fmt3 ->
+              decodeWith
+                #Derived.bytes3
+                (tag
+                  \#Derived.tag ->
+                    when #Derived.tag is
+                      "Cons" -> Keep (Cons (Err NoElem) (Err NoElem))
+                      "Nil" -> Keep Nil
+                      _ -> Skip
+                  \#Derived.state2, #Derived.index ->
+                    when #Derived.state2 is
+                      Cons #Derived.e0 #Derived.e1 ->
+                        when #Derived.index is
+                          0 ->
+                            Keep (custom
+                              \#Derived.bytes, #Derived.fmt ->
+                                when decodeWith #Derived.bytes decoder #Derived.fmt is
+                                  #Derived.rec ->
+                                    {
+                                      result: when #Derived.rec.result is
+                                          Ok #Derived.val ->
+                                            Ok (Cons (Ok #Derived.val) #Derived.e1)
+                                          Err #Derived.err -> Err #Derived.err,
+                                      rest: #Derived.rec.rest
+                                    })
+                          1 ->
+                            Keep (custom
+                              \#Derived.bytes2, #Derived.fmt2 ->
+                                when decodeWith #Derived.bytes2 decoder #Derived.fmt2 is
+                                  #Derived.rec2 ->
+                                    {
+                                      result: when #Derived.rec2.result is
+                                          Ok #Derived.val2 ->
+                                            Ok (Cons #Derived.e0 (Ok #Derived.val2))
+                                          Err #Derived.err2 -> Err #Derived.err2,
+                                      rest: #Derived.rec2.rest
+                                    })
+                          _ -> Skip
+                      Nil -> Skip
+                  \#Derived.state ->
+                    when #Derived.state is
+                      Cons (Ok #Derived.v0) (Ok #Derived.v1) ->
+                        Ok (Cons #Derived.v0 #Derived.v1)
+                      Nil -> Ok Nil
+                      _ -> Err TooShort)
+                #Derived.fmt3
+        "###)
+    })
+}
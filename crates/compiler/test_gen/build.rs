@@ -14,6 +14,7 @@ fn main() {
     if feature_is_enabled("gen-wasm") || feature_is_enabled("gen-llvm-wasm") {
         build_wasm_test_host();
         build_wasm_linking_test_host();
+        build_wasm_relocations_test_obj();
     }
 }
 
@@ -84,6 +85,30 @@ fn build_wasm_linking_test_host() {
     ]);
 }
 
+fn build_wasm_relocations_test_obj() {
+    let source_path = PathBuf::from("src")
+        .join("helpers")
+        .join("wasm_relocations.zig");
+    let obj_path = PathBuf::from("build").join("wasm_relocations.o");
+
+    let source: &str = source_path.to_str().unwrap();
+    let obj: &str = obj_path.to_str().unwrap();
+
+    println!("cargo:rerun-if-changed={}", source);
+
+    if !Path::new("build").exists() {
+        fs::create_dir("build").unwrap();
+    }
+
+    run_zig(&[
+        "build-obj",
+        "-target",
+        "wasm32-freestanding",
+        source,
+        &format!("-femit-bin={}", obj),
+    ]);
+}
+
 fn build_wasm_test_host() {
     let mut source_path = PathBuf::new()
         .join("src")
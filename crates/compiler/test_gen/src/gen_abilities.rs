@@ -4,12 +4,25 @@ use crate::helpers::llvm::assert_evals_to;
 #[cfg(feature = "gen-wasm")]
 use crate::helpers::wasm::assert_evals_to;
 
-#[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+// Only used by tests that are skipped with debug assertions
+#[cfg(feature = "gen-dev")]
+#[allow(unused_imports)]
+use crate::helpers::dev::assert_evals_to;
+
+#[cfg(all(
+    test,
+    any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev")
+))]
+#[allow(unused_imports)]
 use indoc::indoc;
 
 #[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
 use roc_std::RocList;
-#[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+#[cfg(all(
+    test,
+    any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev")
+))]
+#[allow(unused_imports)]
 use roc_std::RocStr;
 
 #[test]
@@ -1180,6 +1193,109 @@ fn decode_record_of_record() {
     )
 }
 
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_tag_two_labels() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                when Str.toUtf8 "{\"A\":[\"ab\",10]}" |> Decode.fromBytes Json.fromUtf8 is
+                    Ok (A "ab" 10u8) -> "ab10"
+                    Ok B -> "B"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("ab10"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_tag_zero_args() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                when Str.toUtf8 "{\"B\":[]}" |> Decode.fromBytes Json.fromUtf8 is
+                    Ok (A "ab") -> "A"
+                    Ok B -> "B"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("B"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn encode_then_decode_tag() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Encode, Json] provides [main] to "./platform"
+
+            main =
+                encoded = Encode.toBytes (A "ab" [B, C "cd"]) Json.toUtf8
+                when Decode.fromBytes encoded Json.fromUtf8 is
+                    Ok (A s [B, C t]) -> Str.concat s t
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("abcd"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_recursive_tag_union() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            Tree : [Leaf, Node Tree Str Tree]
+
+            toStr : Tree -> Str
+            toStr = \tree ->
+                when tree is
+                    Leaf -> "."
+                    Node left s right -> "(\(toStr left) \(s) \(toStr right))"
+
+            main =
+                bytes = Str.toUtf8 "{\"Node\":[{\"Node\":[{\"Leaf\":[]},\"a\",{\"Leaf\":[]}]},\"b\",{\"Leaf\":[]}]}"
+                decoded : Result Tree _
+                decoded = Decode.fromBytes bytes Json.fromUtf8
+
+                when decoded is
+                    Ok tree -> toStr tree
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("((. a .) b .)"),
+        RocStr
+    )
+}
+
 #[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
 mod hash {
     #[cfg(feature = "gen-llvm")]
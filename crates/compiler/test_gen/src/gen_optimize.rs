@@ -0,0 +1,149 @@
+// Runs programs whose mono IR went through the optimizations of `OptLevel::Optimize`:
+// inlining small procs and folding constants. The IR itself is tested in test_mono;
+// these check that the backends still compile it into code that does the same thing.
+
+#[cfg(feature = "gen-dev")]
+use crate::helpers::dev::assert_optimized_evals_to;
+
+#[cfg(feature = "gen-wasm")]
+use crate::helpers::wasm::assert_optimized_evals_to;
+
+#[allow(unused_imports)]
+use indoc::indoc;
+
+#[test]
+#[cfg(any(feature = "gen-dev", feature = "gen-wasm"))]
+fn inline_small_proc() {
+    assert_optimized_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            double : I64 -> I64
+            double = \n -> n * 2
+
+            main = double 20 + double 1
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-dev", feature = "gen-wasm"))]
+fn inline_closure_with_captures() {
+    assert_optimized_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                x : I64
+                x = 40
+
+                addX = \y -> x + y
+
+                addX 2
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-dev", feature = "gen-wasm"))]
+fn inline_proc_with_join_points() {
+    assert_optimized_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            clamp : I64 -> I64
+            clamp = \n ->
+                if n < 0 then
+                    0
+                else if n > 10 then
+                    10
+                else
+                    n
+
+            main = clamp -5 + clamp 15 + clamp 3
+            "#
+        ),
+        13,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-dev", feature = "gen-wasm"))]
+fn inline_higher_order_call() {
+    assert_optimized_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            apply : (I64 -> I64), I64 -> I64
+            apply = \f, x -> f x
+
+            twice : (I64 -> I64), I64 -> I64
+            twice = \f, x -> f (f x)
+
+            main =
+                y : I64
+                y = 10
+
+                apply (\n -> n + y) 2 + twice (\n -> n * 2) 5
+            "#
+        ),
+        32,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-dev", feature = "gen-wasm"))]
+fn recursive_proc_is_not_inlined() {
+    assert_optimized_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            factorial : I64 -> I64
+            factorial = \n ->
+                if n <= 1 then
+                    1
+                else
+                    n * factorial (n - 1)
+
+            main = factorial 10
+            "#
+        ),
+        3628800,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-dev", feature = "gen-wasm"))]
+fn fold_known_tag() {
+    assert_optimized_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            withDefault : Result I64 Str, I64 -> I64
+            withDefault = \result, default ->
+                when result is
+                    Ok n -> n
+                    Err _ -> default
+
+            main = withDefault (Ok 41) 0 + withDefault (Err "nope") 1
+            "#
+        ),
+        42,
+        i64
+    );
+}
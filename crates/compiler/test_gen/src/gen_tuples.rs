@@ -0,0 +1,297 @@
+#[cfg(feature = "gen-llvm")]
+use crate::helpers::llvm::assert_evals_to;
+
+#[cfg(feature = "gen-dev")]
+use crate::helpers::dev::assert_evals_to;
+
+#[cfg(feature = "gen-wasm")]
+use crate::helpers::wasm::assert_evals_to;
+
+use indoc::indoc;
+
+#[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+use roc_std::RocStr;
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn basic_tuple() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                    ( 15, 17, 19 ).0
+                "#
+        ),
+        15,
+        i64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                    ( 15, 17, 19 ).1
+                "#
+        ),
+        17,
+        i64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                    ( 15, 17, 19 ).2
+                "#
+        ),
+        19,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn f64_tuple() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                   tup = ( 17.2, 15.1, 19.3 )
+
+                   tup.0
+                "#
+        ),
+        17.2,
+        f64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                   tup = ( 17.2, 15.1, 19.3 )
+
+                   tup.1
+                "#
+        ),
+        15.1,
+        f64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                   tup = ( 17.2, 15.1, 19.3 )
+
+                   tup.2
+                "#
+        ),
+        19.3,
+        f64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn fn_tuple() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                getRec = \x -> ("foo", x, 19)
+
+                (getRec 15).1
+                "#
+        ),
+        15,
+        i64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                tup = (15, 17, 19)
+
+                tup.2 + tup.0
+                "#
+        ),
+        34,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn int_tuple() {
+    assert_evals_to!("(17, 23).0", 17, i64);
+
+    assert_evals_to!("(17, 23).1", 23, i64);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn when_on_tuple() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                when (0x2, 0x3) is
+                    (x, y) -> x + y
+                "#
+        ),
+        5,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn when_tuple_with_guard_pattern() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                when (0x2, 1.23) is
+                    (var, _) -> var + 3
+                "#
+        ),
+        5,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn let_with_tuple_pattern() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                (x, _ ) = (0x2, 1.23)
+
+                x
+                "#
+        ),
+        2,
+        i64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                (_, y) = (0x2, 0x3)
+
+                y
+                "#
+        ),
+        3,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn tuple_literal_pattern() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                when (1, 2) is
+                    (1, x) -> x
+                    (_, _) -> 0
+                "#
+        ),
+        2,
+        i64
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                when (3, 2) is
+                    (1, x) -> x
+                    (_, _) -> 0
+                "#
+        ),
+        0,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn nested_tuple_load() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                x = (0, (0x2, 0x5, 0x6))
+
+                y = x.1
+
+                y.2
+                "#
+        ),
+        6,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn return_tuple() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                (3, 5)
+                "#
+        ),
+        (3, 5),
+        (i64, i64)
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+                (3, 5, 4)
+                "#
+        ),
+        (3, 5, 4),
+        (i64, i64, i64)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn return_tuple_float_int() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                (1.23, 0x1)
+                "#
+        ),
+        (1.23, 0x1),
+        (f64, i64)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn tuple_accessor_function() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                second = .1
+
+                second (1, 42)
+                "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn tuple_with_str() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                t = ("hello", 42)
+
+                t.0
+                "#
+        ),
+        RocStr::from("hello"),
+        RocStr
+    );
+}
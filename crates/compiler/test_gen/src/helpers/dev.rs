@@ -2,7 +2,7 @@ use libloading::Library;
 use roc_build::link::{link, LinkType};
 use roc_builtins::bitcode;
 use roc_load::{EntryPoint, ExecutionMode, LoadConfig, Threading};
-use roc_mono::ir::SingleEntryPoint;
+use roc_mono::ir::{OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
 use roc_region::all::LineInfo;
 use tempfile::tempdir;
@@ -33,6 +33,7 @@ pub fn helper(
     src: &str,
     _leak: bool,
     lazy_literals: bool,
+    opt_level: OptLevel,
 ) -> (String, Vec<roc_problem::can::Problem>, Library) {
     use std::path::PathBuf;
 
@@ -58,6 +59,7 @@ pub fn helper(
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        opt_level,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -193,6 +195,7 @@ pub fn helper(
         exposed_to_host: exposed_to_host.values.keys().copied().collect(),
         lazy_literals,
         generate_allocators: true, // Needed for testing, since we don't have a platform
+        mode: roc_gen_dev::AssemblyBackendMode::Binary,
     };
 
     let target = target_lexicon::Triple::host();
@@ -260,8 +263,13 @@ macro_rules! assert_evals_to {
         use roc_gen_dev::run_jit_function_raw;
 
         let arena = Bump::new();
-        let (main_fn_name, errors, lib) =
-            $crate::helpers::dev::helper(&arena, $src, $leak, $lazy_literals);
+        let (main_fn_name, errors, lib) = $crate::helpers::dev::helper(
+            &arena,
+            $src,
+            $leak,
+            $lazy_literals,
+            roc_mono::ir::OptLevel::Normal,
+        );
 
         let transform = |success| {
             let expected = $expected;
@@ -272,5 +280,32 @@ macro_rules! assert_evals_to {
     };
 }
 
+/// Like `assert_evals_to`, but with the mono IR optimized (inlined and constant-folded) first
+#[allow(unused_macros)]
+macro_rules! assert_optimized_evals_to {
+    ($src:expr, $expected:expr, $ty:ty) => {{
+        use bumpalo::Bump;
+        use roc_gen_dev::run_jit_function_raw;
+
+        let arena = Bump::new();
+        let (main_fn_name, errors, lib) = $crate::helpers::dev::helper(
+            &arena,
+            $src,
+            true,
+            false,
+            roc_mono::ir::OptLevel::Optimize,
+        );
+
+        let transform = |success| {
+            let expected = $expected;
+            assert_eq!(&success, &expected);
+        };
+        run_jit_function_raw!(lib, main_fn_name, $ty, transform, errors)
+    }};
+}
+
 #[allow(unused_imports)]
 pub(crate) use assert_evals_to;
+
+#[allow(unused_imports)]
+pub(crate) use assert_optimized_evals_to;
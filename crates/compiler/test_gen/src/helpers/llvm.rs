@@ -74,6 +74,7 @@ fn create_llvm_module<'a>(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        opt_level: config.opt_level,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
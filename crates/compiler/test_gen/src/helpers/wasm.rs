@@ -4,7 +4,7 @@ use bumpalo::Bump;
 use roc_collections::all::MutSet;
 use roc_gen_wasm::wasm32_result::Wasm32Result;
 use roc_gen_wasm::DEBUG_SETTINGS;
-use roc_load::{ExecutionMode, LoadConfig, Threading};
+use roc_load::{ExecutionMode, LoadConfig, OptLevel, Threading};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::DEFAULT_PALETTE_HTML;
 use roc_std::RocStr;
@@ -51,12 +51,20 @@ pub fn compile_to_wasm_bytes<'a, T: Wasm32Result>(
     arena: &'a bumpalo::Bump,
     src: &str,
     test_wrapper_type_info: PhantomData<T>,
+    opt_level: OptLevel,
+    runs_expects: bool,
 ) -> Vec<u8> {
     let platform_bytes = include_bytes!(host_bytes_path!());
     println!("Loading test host {}", host_bytes_path!());
 
-    let compiled_bytes =
-        compile_roc_to_wasm_bytes(arena, platform_bytes, src, test_wrapper_type_info);
+    let compiled_bytes = compile_roc_to_wasm_bytes(
+        arena,
+        platform_bytes,
+        src,
+        test_wrapper_type_info,
+        opt_level,
+        runs_expects,
+    );
 
     if write_final_wasm() {
         let build_dir_hash = crate::helpers::src_hash(src);
@@ -71,6 +79,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
     host_bytes: &[u8],
     src: &str,
     _test_wrapper_type_info: PhantomData<T>,
+    opt_level: OptLevel,
+    runs_expects: bool,
 ) -> Vec<u8> {
     let filename = PathBuf::from("Test.roc");
     let src_dir = PathBuf::from("fake/test/path");
@@ -92,6 +102,7 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         palette: DEFAULT_PALETTE_HTML,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        opt_level,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -129,6 +140,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         module_id,
         exposed_to_host,
         stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+        runs_expects,
+        bulk_memory: false,
     };
 
     let host_module = roc_gen_wasm::parse_host(env.arena, host_bytes).unwrap_or_else(|e| {
@@ -170,19 +183,53 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
 }
 
 #[allow(dead_code)]
-pub fn assert_evals_to_help<T>(src: &str, phantom: PhantomData<T>) -> Result<T, String>
+pub fn assert_evals_to_help<T>(
+    src: &str,
+    phantom: PhantomData<T>,
+    opt_level: OptLevel,
+) -> Result<T, String>
 where
     T: FromWasm32Memory + Wasm32Result,
 {
     let arena = bumpalo::Bump::new();
 
-    let wasm_bytes = crate::helpers::wasm::compile_to_wasm_bytes(&arena, src, phantom);
+    let wasm_bytes =
+        crate::helpers::wasm::compile_to_wasm_bytes(&arena, src, phantom, opt_level, false);
 
     run_wasm_test_bytes::<T>(TEST_WRAPPER_NAME, wasm_bytes)
 }
 
+/// A value that the Roc app passed to the test host in a `RocLookup`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostLookup {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// A call from the Roc app to `roc_dbg` or `roc_expect_failed`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostCall {
+    Dbg(HostLookup),
+    ExpectFailed {
+        module: String,
+        region: (u32, u32),
+        lookups: Vec<HostLookup>,
+    },
+}
+
+fn decode_lookup(memory: &[u8], addr: i32) -> HostLookup {
+    let name = RocStr::decode(memory, addr as _);
+    let value_ptr = read_i32(memory, addr + 12) as usize;
+    let size = read_i32(memory, addr + 16) as usize;
+    HostLookup {
+        name: name.as_str().to_string(),
+        bytes: memory[value_ptr..][..size].to_vec(),
+    }
+}
+
 struct TestDispatcher<'a> {
     wasi: WasiDispatcher<'a>,
+    host_calls: Vec<HostCall>,
 }
 
 impl<'a> ImportDispatcher for TestDispatcher<'a> {
@@ -205,6 +252,26 @@ impl<'a> ImportDispatcher for TestDispatcher<'a> {
                 tag => format!(r#"Got an invald panic tag: "{}""#, tag),
             };
             panic!("{}", msg)
+        } else if module_name == "env" && function_name == "send_dbg_to_rust" {
+            let lookup_ptr = arguments[0].expect_i32().unwrap();
+            let lookup = decode_lookup(memory, lookup_ptr);
+            self.host_calls.push(HostCall::Dbg(lookup));
+            None
+        } else if module_name == "env" && function_name == "send_expect_failed_to_rust" {
+            let module_ptr = arguments[0].expect_i32().unwrap();
+            let region_start = arguments[1].expect_i32().unwrap() as u32;
+            let region_end = arguments[2].expect_i32().unwrap() as u32;
+            let lookups_ptr = arguments[3].expect_i32().unwrap();
+            let lookup_count = arguments[4].expect_i32().unwrap();
+            let lookups = (0..lookup_count)
+                .map(|i| decode_lookup(memory, lookups_ptr + i * 20))
+                .collect();
+            self.host_calls.push(HostCall::ExpectFailed {
+                module: RocStr::decode(memory, module_ptr as _).as_str().to_string(),
+                region: (region_start, region_end),
+                lookups,
+            });
+            None
         } else {
             panic!(
                 "TestDispatcher does not implement {}.{}",
@@ -238,6 +305,7 @@ where
 {
     let dispatcher = TestDispatcher {
         wasi: wasi::WasiDispatcher::default(),
+        host_calls: vec![],
     };
     let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
     let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
@@ -248,6 +316,39 @@ where
     Ok(output)
 }
 
+/// Run a test with `dbg` and `expect` enabled, returning the calls the app made to the host
+#[allow(dead_code)]
+pub fn run_with_expects_help<T>(
+    src: &str,
+    phantom: PhantomData<T>,
+) -> Result<(T, Vec<HostCall>), String>
+where
+    T: FromWasm32Memory + Wasm32Result,
+{
+    let arena = bumpalo::Bump::new();
+
+    let wasm_bytes =
+        crate::helpers::wasm::compile_to_wasm_bytes(&arena, src, phantom, OptLevel::Normal, true);
+
+    let require_relocatable = false;
+    let module = WasmModule::preload(&arena, &wasm_bytes, require_relocatable)
+        .map_err(|e| format!("{:?}", e))?;
+
+    let dispatcher = TestDispatcher {
+        wasi: wasi::WasiDispatcher::default(),
+        host_calls: vec![],
+    };
+    let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
+    let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
+    let addr = inst
+        .call_export(TEST_WRAPPER_NAME, [])?
+        .ok_or("No return address from Wasm test")?
+        .expect_i32()
+        .map_err(|e| format!("{:?}", e))?;
+    let output = <T as FromWasm32Memory>::decode(&inst.memory, addr as u32);
+    Ok((output, inst.import_dispatcher.host_calls))
+}
+
 #[allow(dead_code)]
 pub fn assert_wasm_refcounts_help<T>(
     src: &str,
@@ -259,7 +360,8 @@ where
 {
     let arena = bumpalo::Bump::new();
 
-    let wasm_bytes = crate::helpers::wasm::compile_to_wasm_bytes(&arena, src, phantom);
+    let wasm_bytes =
+        crate::helpers::wasm::compile_to_wasm_bytes(&arena, src, phantom, OptLevel::Normal, false);
 
     let require_relocatable = false;
     let module = WasmModule::preload(&arena, &wasm_bytes, require_relocatable)
@@ -267,6 +369,7 @@ where
 
     let dispatcher = TestDispatcher {
         wasi: wasi::WasiDispatcher::default(),
+        host_calls: vec![],
     };
     let is_debug_mode = roc_debug_flags::dbg_set!(roc_debug_flags::ROC_LOG_WASM_INTERP);
     let mut inst = Instance::for_module(&arena, &module, dispatcher, is_debug_mode)?;
@@ -372,7 +475,11 @@ macro_rules! assert_evals_to {
     ($src:expr, $expected:expr, $ty:ty, $transform:expr, $ignore_problems: expr) => {{
         let phantom = std::marker::PhantomData;
         let _ = $ignore_problems; // Always ignore "problems"! One backend (LLVM) is enough to cover them.
-        match $crate::helpers::wasm::assert_evals_to_help::<$ty>($src, phantom) {
+        match $crate::helpers::wasm::assert_evals_to_help::<$ty>(
+            $src,
+            phantom,
+            roc_mono::ir::OptLevel::Normal,
+        ) {
             Err(msg) => panic!("{}", msg),
             Ok(actual) => {
                 assert_eq!($transform(actual), $expected)
@@ -381,6 +488,24 @@ macro_rules! assert_evals_to {
     }};
 }
 
+/// Like `assert_evals_to`, but with the mono IR optimized (inlined and constant-folded) first
+#[allow(unused_macros)]
+macro_rules! assert_optimized_evals_to {
+    ($src:expr, $expected:expr, $ty:ty) => {{
+        let phantom = std::marker::PhantomData;
+        match $crate::helpers::wasm::assert_evals_to_help::<$ty>(
+            $src,
+            phantom,
+            roc_mono::ir::OptLevel::Optimize,
+        ) {
+            Err(msg) => panic!("{}", msg),
+            Ok(actual) => {
+                assert_eq!(actual, $expected)
+            }
+        }
+    }};
+}
+
 #[allow(dead_code)]
 pub fn identity<T>(value: T) -> T {
     value
@@ -408,5 +533,8 @@ macro_rules! assert_refcounts {
 #[allow(unused_imports)]
 pub(crate) use assert_evals_to;
 
+#[allow(unused_imports)]
+pub(crate) use assert_optimized_evals_to;
+
 #[allow(unused_imports)]
 pub(crate) use assert_refcounts;
@@ -0,0 +1,37 @@
+// Compiled to a relocatable object file for the relocation round-trip test in wasm_linking.rs.
+// The aim is to get the compiler to emit as many different relocation types as possible.
+
+extern fn js_imported(x: i32) i32;
+
+fn double(x: i32) i32 {
+    return 2 * x;
+}
+
+fn triple(x: i32) i32 {
+    return 3 * x;
+}
+
+// Function addresses in static data
+const fn_table = [_]fn (i32) i32{ double, triple, js_imported };
+
+// Data addresses in static data, with addends
+var numbers = [_]i32{ 1, 2, 3, 4 };
+var number_ptrs = [_]*i32{ &numbers[0], &numbers[3] };
+
+export fn read_number(i: usize) i32 {
+    return number_ptrs[i % number_ptrs.len].*;
+}
+
+export fn number_address(i: usize) *i32 {
+    return &numbers[i % numbers.len];
+}
+
+export fn run(i: usize, x: i32) i32 {
+    // function addresses in code, and an indirect call
+    var local_fns = [_]fn (i32) i32{ double, triple };
+    const local_fn = local_fns[i % local_fns.len];
+
+    numbers[2] += x;
+    const table_fn = fn_table[i % fn_table.len];
+    return table_fn(x) + local_fn(x) + js_imported(numbers[2]) + read_number(i);
+}
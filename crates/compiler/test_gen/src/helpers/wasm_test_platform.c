@@ -145,3 +145,23 @@ void *roc_memset(void *str, int c, size_t n)
 {
     return memset(str, c, n);
 }
+
+//--------------------------
+
+extern void send_dbg_to_rust(void *lookup);
+
+void roc_dbg(void *lookup)
+{
+    send_dbg_to_rust(lookup);
+}
+
+//--------------------------
+
+extern void send_expect_failed_to_rust(void *module, unsigned int region_start, unsigned int region_end,
+                                       void *lookups, unsigned int lookup_count);
+
+void roc_expect_failed(void *module, unsigned int region_start, unsigned int region_end,
+                       void *lookups, unsigned int lookup_count)
+{
+    send_expect_failed_to_rust(module, region_start, region_end, lookups, lookup_count);
+}
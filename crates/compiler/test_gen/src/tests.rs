@@ -9,6 +9,7 @@ pub mod gen_compare;
 pub mod gen_dict;
 pub mod gen_list;
 pub mod gen_num;
+pub mod gen_optimize;
 pub mod gen_panic;
 pub mod gen_primitives;
 pub mod gen_records;
@@ -17,7 +18,9 @@ pub mod gen_result;
 pub mod gen_set;
 pub mod gen_str;
 pub mod gen_tags;
+pub mod gen_tuples;
 mod helpers;
+pub mod wasm_expect;
 pub mod wasm_str;
 
 #[cfg(feature = "gen-wasm")]
@@ -0,0 +1,99 @@
+// Tests for `dbg` and `expect` in the Wasm backend.
+// The test host forwards `roc_dbg` and `roc_expect_failed` to Rust, so we can check what it receives.
+#![cfg(feature = "gen-wasm")]
+
+use crate::helpers::wasm::{run_with_expects_help, HostCall, HostLookup};
+
+use indoc::indoc;
+use roc_module::ident::ModuleName;
+use std::marker::PhantomData;
+
+fn lookup(name: &str, bytes: &[u8]) -> HostLookup {
+    HostLookup {
+        name: name.to_string(),
+        bytes: bytes.to_vec(),
+    }
+}
+
+#[test]
+fn dbg_sends_lookup_to_host() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            x = 42i64
+            dbg x
+            x + 1
+        "#
+    );
+
+    let (result, host_calls) = run_with_expects_help::<i64>(src, PhantomData).unwrap();
+
+    assert_eq!(result, 43);
+    assert_eq!(
+        host_calls,
+        vec![HostCall::Dbg(lookup("x", &42i64.to_le_bytes()))]
+    );
+}
+
+#[test]
+fn failed_expect_sends_region_and_lookups_to_host() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            x = 42i64
+            y = 7u8
+            expect x + Num.toI64 y == 41
+            x + Num.toI64 y
+        "#
+    );
+
+    let (result, host_calls) = run_with_expects_help::<i64>(src, PhantomData).unwrap();
+
+    assert_eq!(result, 49);
+    assert_eq!(host_calls.len(), 1);
+
+    match &host_calls[0] {
+        HostCall::ExpectFailed {
+            module,
+            region: (start, end),
+            lookups,
+        } => {
+            assert_eq!(module, ModuleName::APP);
+            assert_eq!(
+                &src[*start as usize..*end as usize],
+                "x + Num.toI64 y == 41"
+            );
+
+            let mut lookups = lookups.clone();
+            lookups.sort_by(|a, b| a.name.cmp(&b.name));
+            assert_eq!(
+                lookups,
+                vec![lookup("x", &42i64.to_le_bytes()), lookup("y", &[7])]
+            );
+        }
+        other => panic!("Expected a failed expect, but got {:?}", other),
+    }
+}
+
+#[test]
+fn passing_expect_does_not_call_host() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            x = 42i64
+            expect x == 42
+            x
+        "#
+    );
+
+    let (result, host_calls) = run_with_expects_help::<i64>(src, PhantomData).unwrap();
+
+    assert_eq!(result, 42);
+    assert_eq!(host_calls, vec![]);
+}
@@ -19,10 +19,16 @@ use roc_mono::ir::{
 };
 use roc_mono::layout::{Builtin, CapturesNiche, LambdaName, Layout, STLayoutInterner};
 use roc_wasm_interp::{wasi, ImportDispatcher, Instance, WasiDispatcher};
-use roc_wasm_module::{Value, WasmModule};
+use roc_wasm_module::linking::{
+    IndexRelocType, OffsetRelocType, RelocationEntry, RelocationSection,
+};
+use roc_wasm_module::parse::Parse;
+use roc_wasm_module::sections::SectionId;
+use roc_wasm_module::{Serialize, Value, WasmModule};
 
 const LINKING_TEST_HOST_WASM: &str = "build/wasm_linking_test_host.wasm";
 const LINKING_TEST_HOST_NATIVE: &str = "build/wasm_linking_test_host";
+const RELOCATIONS_TEST_OBJ: &str = "build/wasm_relocations.o";
 
 fn create_symbol(home: ModuleId, ident_ids: &mut IdentIds, debug_name: &str) -> Symbol {
     let ident_id = ident_ids.add_str(debug_name);
@@ -163,6 +169,8 @@ impl<'a> BackendInputs<'a> {
             module_id,
             exposed_to_host,
             stack_bytes: Env::DEFAULT_STACK_BYTES,
+            runs_expects: false,
+            bulk_memory: false,
         };
 
         // Identifier stuff for the backend
@@ -375,3 +383,212 @@ fn test_linking_with_dce() {
         dump_filename,
     );
 }
+
+/// Find the body of a section in a Wasm binary, starting after its size
+fn find_section_body(module_bytes: &[u8], id: SectionId) -> &[u8] {
+    let mut cursor = 8;
+    while cursor < module_bytes.len() {
+        let section_id = module_bytes[cursor];
+        cursor += 1;
+        let size = u32::parse((), module_bytes, &mut cursor).unwrap() as usize;
+        if section_id == id as u8 {
+            return &module_bytes[cursor..][..size];
+        }
+        cursor += size;
+    }
+    panic!("Section {} not found", id as u8);
+}
+
+fn read_padded_leb(bytes: &[u8], len: usize, signed: bool) -> u64 {
+    let mut value = 0;
+    for (i, byte) in bytes[..len].iter().enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+    }
+    let bits = 7 * len;
+    if signed && bits < 64 && (value >> (bits - 1)) & 1 == 1 {
+        value |= u64::MAX << bits;
+    }
+    value
+}
+
+fn read_unencoded(bytes: &[u8], len: usize) -> u64 {
+    let mut buffer = [0; 8];
+    buffer[..len].copy_from_slice(&bytes[..len]);
+    u64::from_le_bytes(buffer)
+}
+
+/// Read the value of a relocation's symbol from its site in the section body.
+/// The inverse of the `apply_*_relocs` functions
+fn read_reloc_site(entry: &RelocationEntry, section_body: &[u8]) -> u32 {
+    match *entry {
+        RelocationEntry::Index {
+            type_id, offset, ..
+        } => {
+            use IndexRelocType::*;
+            let bytes = &section_body[offset as usize..];
+            let value = match type_id {
+                FunctionIndexLeb | TypeIndexLeb | GlobalIndexLeb | EventIndexLeb
+                | TableNumberLeb => read_padded_leb(bytes, 5, false),
+                TableIndexSleb | TableIndexRelSleb => read_padded_leb(bytes, 5, true),
+                TableIndexSleb64 | TableIndexRelSleb64 => read_padded_leb(bytes, 10, true),
+                TableIndexI32 | GlobalIndexI32 | FunctionIndexI32 => read_unencoded(bytes, 4),
+                TableIndexI64 => read_unencoded(bytes, 8),
+            };
+            value as u32
+        }
+        RelocationEntry::Offset {
+            type_id,
+            offset,
+            addend,
+            ..
+        } => {
+            use OffsetRelocType::*;
+            let bytes = &section_body[offset as usize..];
+            let value = match type_id {
+                MemoryAddrLeb => read_padded_leb(bytes, 5, false),
+                MemoryAddrSleb | MemoryAddrRelSleb | MemoryAddrTlsSleb => {
+                    read_padded_leb(bytes, 5, true)
+                }
+                MemoryAddrLeb64 => read_padded_leb(bytes, 10, false),
+                MemoryAddrSleb64 | MemoryAddrRelSleb64 | MemoryAddrTlsSleb64 => {
+                    read_padded_leb(bytes, 10, true)
+                }
+                MemoryAddrI32 | FunctionOffsetI32 | SectionOffsetI32 => read_unencoded(bytes, 4),
+                MemoryAddrI64 | FunctionOffsetI64 => read_unencoded(bytes, 8),
+                MemoryAddrLocrelI32 => {
+                    panic!(
+                        "{:?} depends on the site address, which we don't track",
+                        type_id
+                    )
+                }
+            };
+            (value as u32).wrapping_sub(addend as u32)
+        }
+    }
+}
+
+fn data_section_body(module: &WasmModule) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(module.data.size());
+    module.data.serialize(&mut buffer);
+    buffer.split_off(6) // section ID and padded size
+}
+
+#[test]
+fn test_relocations_round_trip() {
+    let arena = Bump::new();
+    let obj_bytes = fs::read(RELOCATIONS_TEST_OBJ).unwrap();
+    let mut module = WasmModule::preload(&arena, &obj_bytes, true).unwrap();
+
+    // Relocation offsets are relative to the original section bodies
+    let original_code = find_section_body(&obj_bytes, SectionId::Code).to_vec();
+    let original_data = find_section_body(&obj_bytes, SectionId::Data).to_vec();
+    assert_eq!(&module.code.bytes[..], &original_code);
+    assert_eq!(data_section_body(&module), original_data);
+
+    let mut seen_types = MutSet::default();
+
+    // Apply each relocation on its own, since different relocations for the same symbol
+    // can refer to different index spaces (e.g. function index and table index)
+    let code_entries = module.reloc_code.entries.iter().map(|e| (true, e.clone()));
+    let data_entries = module.reloc_data.entries.iter().map(|e| (false, e.clone()));
+    let all_entries = Vec::from_iter(code_entries.chain(data_entries));
+    for (is_code, entry) in all_entries.iter() {
+        let is_code = *is_code;
+        let (type_name, symbol_index) = match entry {
+            RelocationEntry::Index {
+                type_id,
+                symbol_index,
+                ..
+            } => (format!("{:?}", type_id), *symbol_index),
+            RelocationEntry::Offset {
+                type_id,
+                symbol_index,
+                ..
+            } => (format!("{:?}", type_id), *symbol_index),
+        };
+        seen_types.insert(type_name);
+
+        let relocs = RelocationSection {
+            name: "single",
+            target_section_index: 0,
+            entries: bumpalo::vec![in &arena; entry.clone()],
+        };
+        let is_table_index = matches!(
+            entry,
+            RelocationEntry::Index {
+                type_id: IndexRelocType::TableIndexSleb
+                    | IndexRelocType::TableIndexI32
+                    | IndexRelocType::TableIndexRelSleb
+                    | IndexRelocType::TableIndexSleb64
+                    | IndexRelocType::TableIndexI64
+                    | IndexRelocType::TableIndexRelSleb64,
+                ..
+            }
+        );
+        let is_fn_index = matches!(
+            entry,
+            RelocationEntry::Index {
+                type_id: IndexRelocType::FunctionIndexLeb | IndexRelocType::FunctionIndexI32,
+                ..
+            }
+        );
+        let mut apply = |value: u32| {
+            if is_code {
+                let bytes = &mut module.code.bytes;
+                if is_table_index {
+                    relocs.apply_table_index_relocs(bytes, symbol_index, value);
+                } else if is_fn_index {
+                    relocs.apply_fn_index_relocs(bytes, symbol_index, value);
+                } else {
+                    relocs.apply_relocs_u32(bytes, symbol_index, value);
+                }
+                module.code.bytes.to_vec()
+            } else {
+                if is_table_index {
+                    module
+                        .data
+                        .apply_table_index_relocs(&relocs, symbol_index, value);
+                } else {
+                    module.data.apply_relocs_u32(&relocs, symbol_index, value);
+                }
+                data_section_body(&module)
+            }
+        };
+
+        let original = if is_code {
+            &original_code
+        } else {
+            &original_data
+        };
+        let value = read_reloc_site(entry, original);
+
+        let changed = apply(value.wrapping_add(0x1234));
+        assert_eq!(
+            read_reloc_site(entry, &changed),
+            value.wrapping_add(0x1234),
+            "{:?}",
+            entry
+        );
+
+        let restored = apply(value);
+        assert_eq!(&restored, original, "{:?}", entry);
+    }
+
+    for expected in [
+        "FunctionIndexLeb",
+        "TableIndexSleb",
+        "TableIndexI32",
+        "TypeIndexLeb",
+        "GlobalIndexLeb",
+        "MemoryAddrLeb",
+        "MemoryAddrSleb",
+        "MemoryAddrI32",
+    ] {
+        assert!(
+            seen_types.contains(expected),
+            "Expected a {} relocation but only found {:?}",
+            expected,
+            seen_types
+        );
+    }
+}
@@ -21,6 +21,7 @@ use roc_load::LoadMonomorphizedError;
 use roc_load::Threading;
 use roc_module::symbol::Interns;
 use roc_module::symbol::Symbol;
+use roc_mono::ir::OptLevel;
 use roc_mono::ir::Proc;
 use roc_mono::ir::ProcLayout;
 use roc_mono::layout::STLayoutInterner;
@@ -76,7 +77,7 @@ fn promote_expr_to_module(src: &str) -> String {
     buffer
 }
 
-fn compiles_to_ir(test_name: &str, src: &str, mode: &str, no_check: bool) {
+fn compiles_to_ir(test_name: &str, src: &str, mode: &str, no_check: bool, optimize: bool) {
     use roc_packaging::cache::RocCacheDir;
     use std::path::PathBuf;
 
@@ -108,6 +109,11 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, no_check: bool) {
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        opt_level: if optimize {
+            OptLevel::Optimize
+        } else {
+            OptLevel::Normal
+        },
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -2220,3 +2226,88 @@ fn lambda_set_with_imported_toplevels_issue_4733() {
         "###
     )
 }
+
+#[mono_test(optimize)]
+fn optimize_inline_small_proc() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        double : I64 -> I64
+        double = \x -> x * 2
+
+        main = double 21
+        "#
+    )
+}
+
+#[mono_test(optimize)]
+fn optimize_do_not_inline_recursive_proc() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        factorial : I64, I64 -> I64
+        factorial = \n, acc ->
+            if n == 0 then acc else factorial (n - 1) (acc * n)
+
+        main = factorial 5 1
+        "#
+    )
+}
+
+#[mono_test(optimize)]
+fn optimize_fold_dead_branch() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main = if 1 + 1 == 2 then "yes" else "no"
+        "#
+    )
+}
+
+#[mono_test(optimize)]
+fn optimize_fold_wrapping_and_comparisons() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            x = Num.addWrap 255u8 1
+            y = Num.subSaturated 3u8 10
+            x == y && 1.5f64 < 2.5
+        "#
+    )
+}
+
+#[mono_test(optimize)]
+fn optimize_known_tag() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        unwrap : Result I64 I64 -> I64
+        unwrap = \result ->
+            when result is
+                Ok x -> x
+                Err e -> e
+
+        main = unwrap (Ok 42)
+        "#
+    )
+}
+
+#[mono_test(optimize)]
+fn optimize_inline_branching_proc() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        clamp : I64 -> I64
+        clamp = \x -> if x > 10 then 10 else x
+
+        main = List.map [1, 20] \n -> clamp n + 1
+        "#
+    )
+}
@@ -7,12 +7,16 @@ use quote::quote;
 #[proc_macro_attribute]
 pub fn mono_test(args: TokenStream, item: TokenStream) -> TokenStream {
     let mut no_check = false;
+    let mut optimize = false;
     let mut mode = "exec".to_owned();
     for arg in syn::parse_macro_input!(args as syn::AttributeArgs) {
         use syn::{Lit, Meta, MetaNameValue, NestedMeta};
         if matches!(&arg, NestedMeta::Meta(Meta::Path(p)) if p.is_ident("no_check")) {
             no_check = true;
         }
+        if matches!(&arg, NestedMeta::Meta(Meta::Path(p)) if p.is_ident("optimize")) {
+            optimize = true;
+        }
         if let NestedMeta::Meta(Meta::NameValue(MetaNameValue {
             path,
             eq_token: _,
@@ -40,7 +44,7 @@ pub fn mono_test(args: TokenStream, item: TokenStream) -> TokenStream {
         #[test]
         #(#attributes)*
         #visibility fn #name(#args) {
-            compiles_to_ir(#name_str, #body, &#mode, #no_check);
+            compiles_to_ir(#name_str, #body, &#mode, #no_check, #optimize);
 
         }
     };
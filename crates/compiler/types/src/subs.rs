@@ -1,7 +1,7 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 use crate::types::{
     name_type_var, AbilitySet, AliasKind, ErrorType, Polarity, RecordField, RecordFieldsError,
-    TypeExt, Uls,
+    TupleElemsError, TypeExt, Uls,
 };
 use roc_collections::all::{FnvMap, ImMap, ImSet, MutSet, SendMap};
 use roc_collections::{VecMap, VecSet};
@@ -20,6 +20,7 @@ roc_error_macros::assert_sizeof_all!(Descriptor, 5 * 8 + 4);
 roc_error_macros::assert_sizeof_all!(FlatType, 3 * 8);
 roc_error_macros::assert_sizeof_all!(UnionTags, 12);
 roc_error_macros::assert_sizeof_all!(RecordFields, 2 * 8);
+roc_error_macros::assert_sizeof_all!(TupleElems, 12);
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Mark(i32);
@@ -71,6 +72,7 @@ struct SubsHeader {
     symbol_names: u64,
     field_names: u64,
     record_fields: u64,
+    tuple_elem_indices: u64,
     variable_slices: u64,
     unspecialized_lambda_sets: u64,
     uls_of_var: u64,
@@ -86,6 +88,7 @@ impl SubsHeader {
             symbol_names: subs.symbol_names.len() as u64,
             field_names: subs.field_names.len() as u64,
             record_fields: subs.record_fields.len() as u64,
+            tuple_elem_indices: subs.tuple_elem_indices.len() as u64,
             variable_slices: subs.variable_slices.len() as u64,
             unspecialized_lambda_sets: subs.unspecialized_lambda_sets.len() as u64,
             uls_of_var: subs.uls_of_var.len() as u64,
@@ -128,6 +131,7 @@ impl Subs {
         written = bytes::serialize_slice(&self.symbol_names, writer, written)?;
         written = Self::serialize_field_names(&self.field_names, writer, written)?;
         written = bytes::serialize_slice(&self.record_fields, writer, written)?;
+        written = bytes::serialize_slice(&self.tuple_elem_indices, writer, written)?;
         written = bytes::serialize_slice(&self.variable_slices, writer, written)?;
         written = bytes::serialize_slice(&self.unspecialized_lambda_sets, writer, written)?;
         written = Self::serialize_uls_of_var(&self.uls_of_var, writer, written)?;
@@ -222,6 +226,8 @@ impl Subs {
             Self::deserialize_field_names(bytes, header.field_names as usize, offset);
         let (record_fields, offset) =
             bytes::deserialize_slice(bytes, header.record_fields as usize, offset);
+        let (tuple_elem_indices, offset) =
+            bytes::deserialize_slice(bytes, header.tuple_elem_indices as usize, offset);
         let (variable_slices, offset) =
             bytes::deserialize_slice(bytes, header.variable_slices as usize, offset);
         let (unspecialized_lambda_sets, offset) =
@@ -240,6 +246,7 @@ impl Subs {
                     symbol_names: symbol_names.to_vec(),
                     field_names,
                     record_fields: record_fields.to_vec(),
+                    tuple_elem_indices: tuple_elem_indices.to_vec(),
                     variable_slices: variable_slices.to_vec(),
                     unspecialized_lambda_sets: unspecialized_lambda_sets.to_vec(),
                     tag_name_cache: Default::default(),
@@ -291,6 +298,28 @@ impl Subs {
 
         (tag_names, offset)
     }
+
+    /// Rewrites every [Symbol] that the types in this [Subs] mention, e.g. after reading back a
+    /// module that was solved in an earlier run, where its dependencies had different [ModuleId]s.
+    pub fn remap_symbols(&mut self, f: &mut impl FnMut(Symbol) -> Symbol) {
+        for content in self.utable.contents_mut() {
+            match content {
+                Content::Alias(symbol, ..) | Content::Structure(FlatType::Apply(symbol, _)) => {
+                    *symbol = f(*symbol);
+                }
+                _ => {}
+            }
+        }
+
+        // Abilities of able variables, lambda set labels and function tags all live here
+        for symbol in self.symbol_names.iter_mut() {
+            *symbol = f(*symbol);
+        }
+
+        for Uls(_, member, _) in self.unspecialized_lambda_sets.iter_mut() {
+            *member = f(*member);
+        }
+    }
 }
 
 /// Mapping of variables to [Content::LambdaSet]s containing unspecialized lambda sets depending on
@@ -372,6 +401,7 @@ pub struct Subs {
     pub symbol_names: Vec<Symbol>,
     pub field_names: Vec<Lowercase>,
     pub record_fields: Vec<RecordField<()>>,
+    pub tuple_elem_indices: Vec<usize>,
     pub variable_slices: Vec<VariableSubsSlice>,
     pub unspecialized_lambda_sets: Vec<Uls>,
     pub tag_name_cache: TagNameCache,
@@ -507,6 +537,20 @@ impl std::ops::IndexMut<SubsIndex<RecordField<()>>> for Subs {
     }
 }
 
+impl std::ops::Index<SubsIndex<usize>> for Subs {
+    type Output = usize;
+
+    fn index(&self, index: SubsIndex<usize>) -> &Self::Output {
+        &self.tuple_elem_indices[index.index as usize]
+    }
+}
+
+impl std::ops::IndexMut<SubsIndex<usize>> for Subs {
+    fn index_mut(&mut self, index: SubsIndex<usize>) -> &mut Self::Output {
+        &mut self.tuple_elem_indices[index.index as usize]
+    }
+}
+
 impl std::ops::Index<SubsIndex<VariableSubsSlice>> for Subs {
     type Output = VariableSubsSlice;
 
@@ -718,6 +762,12 @@ impl GetSubsSlice<RecordField<()>> for Subs {
     }
 }
 
+impl GetSubsSlice<usize> for Subs {
+    fn get_subs_slice(&self, subs_slice: SubsSlice<usize>) -> &[usize] {
+        subs_slice.get_slice(&self.tuple_elem_indices)
+    }
+}
+
 impl GetSubsSlice<Lowercase> for Subs {
     fn get_subs_slice(&self, subs_slice: SubsSlice<Lowercase>) -> &[Lowercase] {
         subs_slice.get_slice(&self.field_names)
@@ -928,6 +978,21 @@ fn subs_fmt_flat_type(this: &FlatType, subs: &Subs, f: &mut fmt::Formatter) -> f
 
             write!(f, "}}<{:?}>", new_ext)
         }
+        FlatType::Tuple(elems, ext) => {
+            write!(f, "( ")?;
+
+            let (it, new_ext) = elems.sorted_iterator_and_ext(subs, *ext);
+            for (index, var) in it {
+                write!(
+                    f,
+                    "{:?} : {:?}, ",
+                    index,
+                    SubsFmtContent(subs.get_content_without_compacting(var), subs)
+                )?;
+            }
+
+            write!(f, ")<{:?}>", new_ext)
+        }
         FlatType::TagUnion(tags, ext) => {
             write!(f, "[")?;
 
@@ -967,6 +1032,7 @@ fn subs_fmt_flat_type(this: &FlatType, subs: &Subs, f: &mut fmt::Formatter) -> f
             write!(f, "]<{:?}> as <{:?}>", new_ext, rec)
         }
         FlatType::EmptyRecord => write!(f, "EmptyRecord"),
+        FlatType::EmptyTuple => write!(f, "EmptyTuple"),
         FlatType::EmptyTagUnion => write!(f, "EmptyTagUnion"),
     }
 }
@@ -1224,6 +1290,7 @@ define_const_var! {
     NULL,
 
     :pub EMPTY_RECORD,
+    :pub EMPTY_TUPLE,
     :pub EMPTY_TAG_UNION,
 
     BOOL_ENUM,
@@ -1714,6 +1781,7 @@ impl Subs {
             symbol_names,
             field_names: Vec::new(),
             record_fields: Vec::new(),
+            tuple_elem_indices: Vec::new(),
             variable_slices: vec![
                 // used for "TagOrFunction"
                 VariableSubsSlice::default(),
@@ -1732,6 +1800,10 @@ impl Subs {
             Variable::EMPTY_RECORD,
             Content::Structure(FlatType::EmptyRecord),
         );
+        subs.set_content(
+            Variable::EMPTY_TUPLE,
+            Content::Structure(FlatType::EmptyTuple),
+        );
         subs.set_content(
             Variable::EMPTY_TAG_UNION,
             Content::Structure(FlatType::EmptyTagUnion),
@@ -2517,6 +2589,7 @@ pub enum FlatType {
     Apply(Symbol, VariableSubsSlice),
     Func(VariableSubsSlice, Variable, Variable),
     Record(RecordFields, Variable),
+    Tuple(TupleElems, Variable),
     TagUnion(UnionTags, Variable),
 
     /// `A` might either be a function
@@ -2526,6 +2599,7 @@ pub enum FlatType {
 
     RecursiveTagUnion(Variable, UnionTags, Variable),
     EmptyRecord,
+    EmptyTuple,
     EmptyTagUnion,
 }
 
@@ -3225,6 +3299,168 @@ fn is_empty_record(subs: &Subs, mut var: Variable) -> bool {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct TupleElems {
+    pub length: u16,
+    pub elem_index_start: u32,
+    pub variables_start: u32,
+}
+
+pub type SortedElemsIterator<'a> = Box<dyn Iterator<Item = (usize, Variable)> + 'a>;
+
+impl TupleElems {
+    pub const fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            length: 0,
+            elem_index_start: 0,
+            variables_start: 0,
+        }
+    }
+
+    pub const fn variables(&self) -> SubsSlice<Variable> {
+        SubsSlice::new(self.variables_start, self.length)
+    }
+
+    pub const fn elem_indices(&self) -> SubsSlice<usize> {
+        SubsSlice::new(self.elem_index_start, self.length)
+    }
+
+    pub fn iter_variables(&self) -> impl Iterator<Item = SubsIndex<Variable>> {
+        let slice = SubsSlice::new(self.variables_start, self.length);
+        slice.into_iter()
+    }
+
+    pub fn compare(x: &(usize, Variable), y: &(usize, Variable)) -> std::cmp::Ordering {
+        first(x, y)
+    }
+
+    pub fn insert_into_subs<I>(subs: &mut Subs, input: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, Variable)>,
+    {
+        let elem_index_start = subs.tuple_elem_indices.len() as u32;
+        let variables_start = subs.variables.len() as u32;
+
+        let it = input.into_iter();
+        let size_hint = it.size_hint().0;
+
+        subs.variables.reserve(size_hint);
+        subs.tuple_elem_indices.reserve(size_hint);
+
+        let mut length = 0;
+        for (index, var) in it {
+            subs.tuple_elem_indices.push(index);
+            subs.variables.push(var);
+
+            length += 1;
+        }
+
+        TupleElems {
+            length,
+            elem_index_start,
+            variables_start,
+        }
+    }
+
+    #[inline(always)]
+    pub fn unsorted_iterator<'a>(
+        &'a self,
+        subs: &'a Subs,
+        ext: Variable,
+    ) -> Result<impl Iterator<Item = (usize, Variable)> + 'a, TupleElemsError> {
+        let (it, _) = crate::types::gather_tuple_elems_unsorted_iter(subs, *self, ext)?;
+
+        Ok(it)
+    }
+
+    #[inline(always)]
+    pub fn unsorted_iterator_and_ext<'a>(
+        &'a self,
+        subs: &'a Subs,
+        ext: Variable,
+    ) -> (impl Iterator<Item = (usize, Variable)> + 'a, Variable) {
+        let (it, ext) = crate::types::gather_tuple_elems_unsorted_iter(subs, *self, ext)
+            .expect("Something weird ended up in a tuple type");
+
+        (it, ext)
+    }
+
+    /// get a sorted iterator over the elements of this tuple type
+    ///
+    /// Implementation: When the tuple has an `ext` variable that is the empty tuple, then
+    /// we read the (assumed sorted) elements directly from Subs. Otherwise we have to chase the
+    /// ext var, then sort the elements.
+    #[inline(always)]
+    pub fn sorted_iterator<'a>(&'_ self, subs: &'a Subs, ext: Variable) -> SortedElemsIterator<'a> {
+        self.sorted_iterator_and_ext(subs, ext).0
+    }
+
+    #[inline(always)]
+    pub fn sorted_iterator_and_ext<'a>(
+        &'_ self,
+        subs: &'a Subs,
+        ext: Variable,
+    ) -> (SortedElemsIterator<'a>, Variable) {
+        if is_empty_tuple(subs, ext) {
+            (
+                Box::new(self.iter_all().map(move |(i1, i2)| (subs[i1], subs[i2]))),
+                ext,
+            )
+        } else {
+            let tuple_structure = crate::types::gather_tuple_elems(subs, *self, ext)
+                .expect("Something ended up weird in this tuple type");
+
+            (
+                Box::new(tuple_structure.elems.into_iter()),
+                tuple_structure.ext,
+            )
+        }
+    }
+
+    pub fn iter_all(&self) -> impl Iterator<Item = (SubsIndex<usize>, SubsIndex<Variable>)> {
+        let helper = |start| start..(start + self.length as u32);
+
+        let range1 = helper(self.elem_index_start);
+        let range2 = helper(self.variables_start);
+
+        let it = range1.into_iter().zip(range2.into_iter());
+
+        it.map(|(i1, i2)| (SubsIndex::new(i1), SubsIndex::new(i2)))
+    }
+}
+
+fn is_empty_tuple(subs: &Subs, mut var: Variable) -> bool {
+    use crate::subs::Content::*;
+    use crate::subs::FlatType::*;
+
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Structure(EmptyTuple) => return true,
+            Structure(Tuple(sub_elems, sub_ext)) => {
+                if !sub_elems.is_empty() {
+                    return false;
+                }
+
+                var = *sub_ext;
+            }
+
+            Alias(_, _, actual_var, _) => {
+                var = *actual_var;
+            }
+
+            _ => return false,
+        }
+    }
+}
+
 std::thread_local! {
     static SCRATCHPAD_FOR_OCCURS: RefCell<Option<Vec<Variable>>> = RefCell::new(Some(Vec::with_capacity(1024)));
 }
@@ -3277,6 +3513,11 @@ fn occurs(
                     let it = once(ext_var).chain(subs.get_subs_slice(slice).iter());
                     short_circuit(subs, root_var, seen, it)
                 }
+                Tuple(vars_by_elem, ext_var) => {
+                    let slice = vars_by_elem.variables();
+                    let it = once(ext_var).chain(subs.get_subs_slice(slice).iter());
+                    short_circuit(subs, root_var, seen, it)
+                }
                 TagUnion(tags, ext_var) => {
                     occurs_union(subs, root_var, seen, tags)?;
 
@@ -3291,7 +3532,7 @@ fn occurs(
 
                     short_circuit_help(subs, root_var, seen, *ext_var)
                 }
-                EmptyRecord | EmptyTagUnion => Ok(()),
+                EmptyRecord | EmptyTuple | EmptyTagUnion => Ok(()),
             },
             Alias(_, args, real_var, _) => {
                 for var_index in args.into_iter() {
@@ -3460,8 +3701,19 @@ fn explicit_substitute(
 
                         subs.set_content(in_var, Structure(Record(vars_by_field, new_ext_var)));
                     }
+                    Tuple(vars_by_elem, ext_var) => {
+                        let new_ext_var = explicit_substitute(subs, from, to, ext_var, seen);
 
-                    EmptyRecord | EmptyTagUnion => {}
+                        for index in vars_by_elem.iter_variables() {
+                            let var = subs[index];
+                            let new_var = explicit_substitute(subs, from, to, var, seen);
+                            subs[index] = new_var;
+                        }
+
+                        subs.set_content(in_var, Structure(Tuple(vars_by_elem, new_ext_var)));
+                    }
+
+                    EmptyRecord | EmptyTuple | EmptyTagUnion => {}
                 }
 
                 in_var
@@ -3644,7 +3896,9 @@ fn get_var_names(
                     accum
                 }
 
-                FlatType::EmptyRecord | FlatType::EmptyTagUnion => taken_names,
+                FlatType::EmptyRecord | FlatType::EmptyTuple | FlatType::EmptyTagUnion => {
+                    taken_names
+                }
 
                 FlatType::Record(vars_by_field, ext_var) => {
                     let mut accum = get_var_names(subs, ext_var, taken_names);
@@ -3657,6 +3911,17 @@ fn get_var_names(
 
                     accum
                 }
+                FlatType::Tuple(vars_by_elem, ext_var) => {
+                    let mut accum = get_var_names(subs, ext_var, taken_names);
+
+                    for var_index in vars_by_elem.iter_variables() {
+                        let arg_var = subs[var_index];
+
+                        accum = get_var_names(subs, arg_var, accum)
+                    }
+
+                    accum
+                }
                 FlatType::TagUnion(tags, ext_var) => {
                     let taken_names = get_var_names(subs, ext_var, taken_names);
                     get_var_names_union(subs, tags, taken_names)
@@ -3930,6 +4195,7 @@ fn flat_type_to_err_type(
         }
 
         EmptyRecord => ErrorType::Record(SendMap::default(), TypeExt::Closed),
+        EmptyTuple => ErrorType::Tuple(SendMap::default(), TypeExt::Closed),
         EmptyTagUnion => ErrorType::TagUnion(SendMap::default(), TypeExt::Closed, pol),
 
         Record(vars_by_field, ext_var) => {
@@ -3972,6 +4238,36 @@ fn flat_type_to_err_type(
             }
         }
 
+        Tuple(vars_by_elem, ext_var) => {
+            let mut err_elems = SendMap::default();
+
+            for (i1, i2) in vars_by_elem.iter_all() {
+                let index = subs[i1];
+                let var = subs[i2];
+
+                let error_type = var_to_err_type(subs, state, var, pol);
+
+                err_elems.insert(index, error_type);
+            }
+
+            match var_to_err_type(subs, state, ext_var, pol).unwrap_structural_alias() {
+                ErrorType::Tuple(sub_elems, sub_ext) => {
+                    ErrorType::Tuple(sub_elems.union(err_elems), sub_ext)
+                }
+
+                ErrorType::FlexVar(var) => {
+                    ErrorType::Tuple(err_elems, TypeExt::FlexOpen(var))
+                }
+
+                ErrorType::RigidVar(var) => {
+                    ErrorType::Tuple(err_elems, TypeExt::RigidOpen(var))
+                }
+
+                other =>
+                    panic!("Tried to convert a tuple extension to an error, but the tuple extension had the ErrorType of {:?}", other)
+            }
+        }
+
         TagUnion(tags, ext_var) => {
             let err_tags = union_tags_to_err_tags(subs, state, tags, pol);
 
@@ -4134,6 +4430,7 @@ struct StorageSubsOffsets {
     symbol_names: u32,
     field_names: u32,
     record_fields: u32,
+    tuple_elem_indices: u32,
     variable_slices: u32,
     unspecialized_lambda_sets: u32,
 }
@@ -4217,6 +4514,7 @@ impl StorageSubs {
             symbol_names: self.subs.symbol_names.len() as u32,
             field_names: self.subs.field_names.len() as u32,
             record_fields: self.subs.record_fields.len() as u32,
+            tuple_elem_indices: self.subs.tuple_elem_indices.len() as u32,
             variable_slices: self.subs.variable_slices.len() as u32,
             unspecialized_lambda_sets: self.subs.unspecialized_lambda_sets.len() as u32,
         };
@@ -4228,6 +4526,7 @@ impl StorageSubs {
             symbol_names: target.symbol_names.len() as u32,
             field_names: target.field_names.len() as u32,
             record_fields: target.record_fields.len() as u32,
+            tuple_elem_indices: target.tuple_elem_indices.len() as u32,
             variable_slices: target.variable_slices.len() as u32,
             unspecialized_lambda_sets: target.unspecialized_lambda_sets.len() as u32,
         };
@@ -4275,6 +4574,9 @@ impl StorageSubs {
         target.symbol_names.extend(self.subs.symbol_names);
         target.field_names.extend(self.subs.field_names);
         target.record_fields.extend(self.subs.record_fields);
+        target
+            .tuple_elem_indices
+            .extend(self.subs.tuple_elem_indices);
         target
             .unspecialized_lambda_sets
             .extend(self.subs.unspecialized_lambda_sets);
@@ -4314,6 +4616,10 @@ impl StorageSubs {
                 Self::offset_record_fields(offsets, *record_fields),
                 Self::offset_variable(offsets, *ext),
             ),
+            FlatType::Tuple(tuple_elems, ext) => FlatType::Tuple(
+                Self::offset_tuple_elems(offsets, *tuple_elems),
+                Self::offset_variable(offsets, *ext),
+            ),
             FlatType::TagUnion(union_tags, ext) => FlatType::TagUnion(
                 Self::offset_tag_union(offsets, *union_tags),
                 Self::offset_variable(offsets, *ext),
@@ -4329,6 +4635,7 @@ impl StorageSubs {
                 Self::offset_variable(offsets, *ext),
             ),
             FlatType::EmptyRecord => FlatType::EmptyRecord,
+            FlatType::EmptyTuple => FlatType::EmptyTuple,
             FlatType::EmptyTagUnion => FlatType::EmptyTagUnion,
         }
     }
@@ -4421,6 +4728,13 @@ impl StorageSubs {
         record_fields
     }
 
+    fn offset_tuple_elems(offsets: &StorageSubsOffsets, mut tuple_elems: TupleElems) -> TupleElems {
+        tuple_elems.elem_index_start += offsets.tuple_elem_indices;
+        tuple_elems.variables_start += offsets.variables;
+
+        tuple_elems
+    }
+
     fn offset_tag_name_slice(
         offsets: &StorageSubsOffsets,
         mut tag_names: SubsSlice<TagName>,
@@ -4615,7 +4929,7 @@ fn storage_copy_var_to_help(env: &mut StorageCopyVarToEnv<'_>, var: Variable) ->
                     Func(new_arguments, new_closure_var, new_ret_var)
                 }
 
-                same @ EmptyRecord | same @ EmptyTagUnion => same,
+                same @ EmptyRecord | same @ EmptyTuple | same @ EmptyTagUnion => same,
 
                 Record(fields, ext_var) => {
                     let record_fields = {
@@ -4652,6 +4966,36 @@ fn storage_copy_var_to_help(env: &mut StorageCopyVarToEnv<'_>, var: Variable) ->
                     Record(record_fields, storage_copy_var_to_help(env, ext_var))
                 }
 
+                Tuple(elems, ext_var) => {
+                    let tuple_elems = {
+                        let new_variables =
+                            VariableSubsSlice::reserve_into_subs(env.target, elems.len());
+
+                        let it = (new_variables.indices()).zip(elems.iter_variables());
+                        for (target_index, var_index) in it {
+                            let var = env.source[var_index];
+                            let copy_var = storage_copy_var_to_help(env, var);
+                            env.target.variables[target_index] = copy_var;
+                        }
+
+                        let elem_index_start = env.target.tuple_elem_indices.len() as u32;
+
+                        let elem_indices =
+                            &env.source.tuple_elem_indices[elems.elem_indices().indices()];
+                        env.target
+                            .tuple_elem_indices
+                            .extend(elem_indices.iter().copied());
+
+                        TupleElems {
+                            length: elems.len() as _,
+                            elem_index_start,
+                            variables_start: new_variables.start,
+                        }
+                    };
+
+                    Tuple(tuple_elems, storage_copy_var_to_help(env, ext_var))
+                }
+
                 TagUnion(tags, ext_var) => {
                     let new_ext = storage_copy_var_to_help(env, ext_var);
                     let union_tags = storage_copy_union(env, tags);
@@ -4942,7 +5286,9 @@ fn is_registered(content: &Content) -> bool {
         | Content::RigidVar(_)
         | Content::FlexAbleVar(..)
         | Content::RigidAbleVar(..) => false,
-        Content::Structure(FlatType::EmptyRecord | FlatType::EmptyTagUnion) => false,
+        Content::Structure(
+            FlatType::EmptyRecord | FlatType::EmptyTuple | FlatType::EmptyTagUnion,
+        ) => false,
 
         Content::Structure(_)
         | Content::RecursionVar { .. }
@@ -5063,7 +5409,7 @@ fn copy_import_to_help(env: &mut CopyImportEnv<'_>, max_rank: Rank, var: Variabl
                     Func(new_arguments, new_closure_var, new_ret_var)
                 }
 
-                same @ EmptyRecord | same @ EmptyTagUnion => same,
+                same @ EmptyRecord | same @ EmptyTuple | same @ EmptyTagUnion => same,
 
                 Record(fields, ext_var) => {
                     let record_fields = {
@@ -5100,6 +5446,36 @@ fn copy_import_to_help(env: &mut CopyImportEnv<'_>, max_rank: Rank, var: Variabl
                     Record(record_fields, copy_import_to_help(env, max_rank, ext_var))
                 }
 
+                Tuple(elems, ext_var) => {
+                    let tuple_elems = {
+                        let new_variables =
+                            VariableSubsSlice::reserve_into_subs(env.target, elems.len());
+
+                        let it = (new_variables.indices()).zip(elems.iter_variables());
+                        for (target_index, var_index) in it {
+                            let var = env.source[var_index];
+                            let copy_var = copy_import_to_help(env, max_rank, var);
+                            env.target.variables[target_index] = copy_var;
+                        }
+
+                        let elem_index_start = env.target.tuple_elem_indices.len() as u32;
+
+                        let elem_indices =
+                            &env.source.tuple_elem_indices[elems.elem_indices().indices()];
+                        env.target
+                            .tuple_elem_indices
+                            .extend(elem_indices.iter().copied());
+
+                        TupleElems {
+                            length: elems.len() as _,
+                            elem_index_start,
+                            variables_start: new_variables.start,
+                        }
+                    };
+
+                    Tuple(tuple_elems, copy_import_to_help(env, max_rank, ext_var))
+                }
+
                 TagUnion(tags, ext_var) => {
                     let new_ext = copy_import_to_help(env, max_rank, ext_var);
 
@@ -5398,6 +5774,7 @@ fn instantiate_rigids_help(subs: &mut Subs, max_rank: Rank, initial: Variable) {
                 }
 
                 EmptyRecord => (),
+                EmptyTuple => (),
                 EmptyTagUnion => (),
 
                 Record(fields, ext_var) => {
@@ -5407,6 +5784,13 @@ fn instantiate_rigids_help(subs: &mut Subs, max_rank: Rank, initial: Variable) {
 
                     stack.push(ext_var);
                 }
+                Tuple(elems, ext_var) => {
+                    let elems = *elems;
+                    let ext_var = *ext_var;
+                    stack.extend(var_slice!(elems.variables()));
+
+                    stack.push(ext_var);
+                }
                 TagUnion(tags, ext_var) => {
                     let tags = *tags;
                     let ext_var = *ext_var;
@@ -5517,6 +5901,10 @@ pub fn get_member_lambda_sets_at_region(subs: &Subs, var: Variable, target_regio
                     stack.extend(subs.get_subs_slice(fields.variables()));
                     stack.push(*ext);
                 }
+                FlatType::Tuple(elems, ext) => {
+                    stack.extend(subs.get_subs_slice(elems.variables()));
+                    stack.push(*ext);
+                }
                 FlatType::TagUnion(tags, ext) => {
                     stack.extend(
                         subs.get_subs_slice(tags.variables())
@@ -5537,7 +5925,7 @@ pub fn get_member_lambda_sets_at_region(subs: &Subs, var: Variable, target_regio
                     );
                     stack.push(*ext);
                 }
-                FlatType::EmptyRecord | FlatType::EmptyTagUnion => {}
+                FlatType::EmptyRecord | FlatType::EmptyTuple | FlatType::EmptyTagUnion => {}
             },
             Content::Alias(_, _, real_var, _) => {
                 stack.push(*real_var);
@@ -5589,6 +5977,12 @@ fn is_inhabited(subs: &Subs, var: Variable) -> bool {
                         stack.extend(field_vars)
                     }
                 }
+                FlatType::Tuple(elems, ext) => {
+                    if let Ok(iter) = elems.unsorted_iterator(subs, *ext) {
+                        let elem_vars = iter.map(|(_, var)| var);
+                        stack.extend(elem_vars)
+                    }
+                }
                 FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
                     let mut is_uninhabited = true;
                     // If any tag is inhabited, the union is inhabited!
@@ -5605,6 +5999,7 @@ fn is_inhabited(subs: &Subs, var: Variable) -> bool {
                 }
                 FlatType::FunctionOrTagUnion(_, _, _) => {}
                 FlatType::EmptyRecord => {}
+                FlatType::EmptyTuple => {}
                 FlatType::EmptyTagUnion => {
                     return false;
                 }
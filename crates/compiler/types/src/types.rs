@@ -1,14 +1,14 @@
 use crate::num::NumericRange;
 use crate::pretty_print::Parens;
 use crate::subs::{
-    GetSubsSlice, RecordFields, Subs, UnionTags, VarStore, Variable, VariableSubsSlice,
+    GetSubsSlice, RecordFields, Subs, TupleElems, UnionTags, VarStore, Variable, VariableSubsSlice,
 };
 use roc_collections::all::{HumanIndex, ImMap, ImSet, MutMap, MutSet, SendMap};
 use roc_collections::soa::{Index, Slice};
 use roc_collections::VecMap;
 use roc_error_macros::internal_error;
 use roc_module::called_via::CalledVia;
-use roc_module::ident::{ForeignSymbol, Lowercase, TagName};
+use roc_module::ident::{ForeignSymbol, IndexOrField, Lowercase, TagName};
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::{Interns, Symbol};
 use roc_region::all::{Loc, Region};
@@ -431,6 +431,7 @@ pub enum TypeTag {
     TagUnion(UnionTags),
     RecursiveTagUnion(Variable, UnionTags),
     Record(RecordFields),
+    Tuple(TupleElems),
 }
 
 /// Look-aside slice of types used in [Types], when the slice does not correspond to the direct
@@ -480,6 +481,9 @@ pub struct Types {
     field_types: Vec<RecordField<()>>,
     field_names: Vec<Lowercase>,
 
+    // tuples
+    tuple_elem_indices: Vec<usize>,
+
     // aliases
     type_arg_abilities: Vec<AbilitySet>, // TODO: structural sharing for `AbilitySet`s themselves
     aliases: Vec<AliasShared>,
@@ -528,6 +532,7 @@ impl Types {
             tag_names: Default::default(),
             field_types: Default::default(),
             field_names: Default::default(),
+            tuple_elem_indices: Default::default(),
             type_arg_abilities: Default::default(),
             aliases: Default::default(),
             single_tag_union_tag_names: Default::default(),
@@ -568,6 +573,19 @@ impl Types {
         (names, fields, tys)
     }
 
+    pub fn tuple_elems_slices(&self, elems: TupleElems) -> (Slice<usize>, Slice<TypeTag>) {
+        let TupleElems {
+            length,
+            elem_index_start,
+            variables_start,
+        } = elems;
+
+        let indices = Slice::new(elem_index_start, length);
+        let tys = Slice::new(variables_start, length);
+
+        (indices, tys)
+    }
+
     pub fn union_tag_slices(&self, union: UnionTags) -> (Slice<TagName>, Slice<AsideTypeSlice>) {
         let UnionTags {
             length,
@@ -840,6 +858,34 @@ impl Types {
                 let tag = TypeTag::Record(record_fields);
                 self.set_type_tag(index, tag, type_slice)
             }
+            Type::Tuple(elems, extension) => {
+                let type_slice = match extension {
+                    TypeExtension::Open(ext) => self.from_old_type(ext).as_slice(),
+                    TypeExtension::Closed => Slice::default(),
+                };
+
+                let elem_type_slice = {
+                    let slice = self.reserve_type_tags(elems.len());
+
+                    for (index, argument) in slice.into_iter().zip(elems.values()) {
+                        self.from_old_type_at(index, argument);
+                    }
+
+                    slice
+                };
+
+                let elem_indices =
+                    Slice::extend_new(&mut self.tuple_elem_indices, elems.keys().copied());
+
+                let tuple_elems = TupleElems {
+                    length: elems.len() as u16,
+                    elem_index_start: elem_indices.start() as u32,
+                    variables_start: elem_type_slice.start() as u32,
+                };
+
+                let tag = TypeTag::Tuple(tuple_elems);
+                self.set_type_tag(index, tag, type_slice)
+            }
             Type::ClosureTag {
                 name,
                 captures,
@@ -1249,6 +1295,23 @@ impl Types {
 
                     (Record(new_record_fields), new_ext_slice)
                 }
+                Tuple(elems) => {
+                    let ext_slice = self.get_type_arguments(typ);
+                    let (indices, tys) = self.tuple_elems_slices(elems);
+
+                    debug_assert_eq!(indices.len(), tys.len());
+
+                    let new_tys = defer_slice!(tys);
+                    let new_ext_slice = defer_slice!(ext_slice);
+
+                    let new_tuple_elems = TupleElems {
+                        length: indices.len() as _,
+                        elem_index_start: indices.start() as _,
+                        variables_start: new_tys.start() as _,
+                    };
+
+                    (Tuple(new_tuple_elems), new_ext_slice)
+                }
                 RangedNumber(range) => (RangedNumber(range), Default::default()),
                 Error => (Error, Default::default()),
             };
@@ -1435,6 +1498,23 @@ mod debug_types {
                         .align(),
                 )
             }
+            TypeTag::Tuple(elems) => {
+                let (indices, tys) = types.tuple_elems_slices(elems);
+                let fmt_elems = indices.into_iter().zip(tys.into_iter()).map(|(index, ty)| {
+                    f.text(types[index].to_string())
+                        .append(f.text(": "))
+                        .append(typ(types, f, Free, ty))
+                });
+                f.text("(").append(
+                    f.intersperse(fmt_elems, f.reflow(", "))
+                        .append(
+                            f.text(")")
+                                .append(ext(types, f, types.get_type_arguments(tag))),
+                        )
+                        .group()
+                        .align(),
+                )
+            }
         };
         group.group()
     }
@@ -1603,6 +1683,7 @@ impl_types_index! {
     tag_names, TagName
     field_types, RecordField<()>
     field_names, Lowercase
+    tuple_elem_indices, usize
 }
 
 impl_types_index_slice! {
@@ -1632,6 +1713,7 @@ pub enum Type {
     /// A function. The types of its arguments, size of its closure, then the type of its return value.
     Function(Vec<Type>, Box<Type>, Box<Type>),
     Record(SendMap<Lowercase, RecordField<Type>>, TypeExtension),
+    Tuple(VecMap<usize, Type>, TypeExtension),
     TagUnion(Vec<(TagName, Vec<Type>)>, TypeExtension),
     FunctionOrTagUnion(TagName, Symbol, TypeExtension),
     /// A function name that is used in our defunctionalization algorithm. For example in
@@ -1714,6 +1796,7 @@ impl Clone for Type {
                 Self::Function(arg0.clone(), arg1.clone(), arg2.clone())
             }
             Self::Record(arg0, arg1) => Self::Record(arg0.clone(), arg1.clone()),
+            Self::Tuple(arg0, arg1) => Self::Tuple(arg0.clone(), arg1.clone()),
             Self::TagUnion(arg0, arg1) => Self::TagUnion(arg0.clone(), arg1.clone()),
             Self::FunctionOrTagUnion(arg0, arg1, arg2) => {
                 Self::FunctionOrTagUnion(arg0.clone(), *arg1, arg2.clone())
@@ -2003,6 +2086,35 @@ impl fmt::Debug for Type {
                     }
                 }
             }
+            Type::Tuple(elems, ext) => {
+                write!(f, "(")?;
+
+                let mut any_written_yet = false;
+
+                for (index, elem_type) in elems.iter() {
+                    if any_written_yet {
+                        write!(f, ", ")?;
+                    } else {
+                        any_written_yet = true;
+                    }
+
+                    write!(f, "{:?}: {:?}", index, elem_type)?;
+                }
+
+                write!(f, ")")?;
+
+                match ext {
+                    TypeExtension::Closed => {
+                        // This is a closed tuple. We're done!
+                        Ok(())
+                    }
+                    TypeExtension::Open(other) => {
+                        // This is an open tuple, so print the variable
+                        // right after the ')'
+                        other.fmt(f)
+                    }
+                }
+            }
             Type::TagUnion(tags, ext) => {
                 write_tags(f, tags.iter())?;
 
@@ -2173,6 +2285,15 @@ impl Type {
                         stack.push(ext);
                     }
                 }
+                Tuple(elems, ext) => {
+                    for (_, x) in elems.iter_mut() {
+                        stack.push(x);
+                    }
+
+                    if let TypeExtension::Open(ext) = ext {
+                        stack.push(ext);
+                    }
+                }
                 Type::DelayedAlias(AliasCommon {
                     type_arguments,
                     lambda_set_variables,
@@ -2305,6 +2426,14 @@ impl Type {
                         stack.push(ext);
                     }
                 }
+                Tuple(elems, ext) => {
+                    for (_, x) in elems.iter_mut() {
+                        stack.push(x);
+                    }
+                    if let TypeExtension::Open(ext) = ext {
+                        stack.push(ext);
+                    }
+                }
                 Type::DelayedAlias(AliasCommon {
                     type_arguments,
                     lambda_set_variables,
@@ -2420,6 +2549,16 @@ impl Type {
                     TypeExtension::Closed => Ok(()),
                 }
             }
+            Tuple(elems, ext) => {
+                for (_, x) in elems.iter_mut() {
+                    x.substitute_alias(rep_symbol, rep_args, actual)?;
+                }
+
+                match ext {
+                    TypeExtension::Open(ext) => ext.substitute_alias(rep_symbol, rep_args, actual),
+                    TypeExtension::Closed => Ok(()),
+                }
+            }
             DelayedAlias(AliasCommon {
                 type_arguments,
                 lambda_set_variables: _no_aliases_in_lambda_sets,
@@ -2507,6 +2646,10 @@ impl Type {
                 Self::contains_symbol_ext(ext, rep_symbol)
                     || fields.values().any(|arg| arg.contains_symbol(rep_symbol))
             }
+            Tuple(elems, ext) => {
+                Self::contains_symbol_ext(ext, rep_symbol)
+                    || elems.values().any(|arg| arg.contains_symbol(rep_symbol))
+            }
             DelayedAlias(AliasCommon {
                 symbol,
                 type_arguments,
@@ -2580,6 +2723,12 @@ impl Type {
                         .values()
                         .any(|arg| arg.contains_variable(rep_variable))
             }
+            Tuple(elems, ext) => {
+                Self::contains_variable_ext(ext, rep_variable)
+                    || elems
+                        .values()
+                        .any(|arg| arg.contains_variable(rep_variable))
+            }
             DelayedAlias(AliasCommon { .. }) => {
                 todo!()
             }
@@ -2715,6 +2864,27 @@ impl Type {
                     );
                 }
             }
+            Tuple(elems, ext) => {
+                for (_, x) in elems.iter_mut() {
+                    x.instantiate_aliases(
+                        region,
+                        aliases,
+                        var_store,
+                        new_lambda_set_variables,
+                        new_infer_ext_vars,
+                    );
+                }
+
+                if let TypeExtension::Open(ext) = ext {
+                    ext.instantiate_aliases(
+                        region,
+                        aliases,
+                        var_store,
+                        new_lambda_set_variables,
+                        new_infer_ext_vars,
+                    );
+                }
+            }
             DelayedAlias(AliasCommon {
                 type_arguments,
                 lambda_set_variables,
@@ -3021,6 +3191,12 @@ impl Type {
                 }
                 TypeExtension::Closed => fields.values().all(|field| field.as_inner().is_narrow()),
             },
+            Type::Tuple(elems, ext) => match ext {
+                TypeExtension::Open(ext) => {
+                    elems.values().all(|elem| elem.is_narrow()) && ext.is_narrow()
+                }
+                TypeExtension::Closed => elems.values().all(|elem| elem.is_narrow()),
+            },
             Type::Function(args, clos, ret) => {
                 args.iter().all(|a| a.is_narrow()) && clos.is_narrow() && ret.is_narrow()
             }
@@ -3068,6 +3244,10 @@ fn symbols_help(initial: &Type) -> Vec<Symbol> {
                 stack.extend(ext);
                 stack.extend(fields.values().map(|field| field.as_inner()));
             }
+            Tuple(elems, ext) => {
+                stack.extend(ext);
+                stack.extend(elems.values());
+            }
             DelayedAlias(AliasCommon {
                 symbol,
                 type_arguments,
@@ -3138,6 +3318,15 @@ fn variables_help(tipe: &Type, accum: &mut ImSet<Variable>) {
                 variables_help(ext, accum);
             }
         }
+        Tuple(elems, ext) => {
+            for (_, elem) in elems.iter() {
+                variables_help(elem, accum);
+            }
+
+            if let TypeExtension::Open(ext) = ext {
+                variables_help(ext, accum);
+            }
+        }
         ClosureTag {
             name: _,
             captures,
@@ -3273,6 +3462,15 @@ fn variables_help_detailed(tipe: &Type, accum: &mut VariableDetail) {
                 variables_help_detailed(ext, accum);
             }
         }
+        Tuple(elems, ext) => {
+            for (_, elem) in elems.iter() {
+                variables_help_detailed(elem, accum);
+            }
+
+            if let TypeExtension::Open(ext) = ext {
+                variables_help_detailed(ext, accum);
+            }
+        }
         ClosureTag {
             name: _,
             captures,
@@ -3377,6 +3575,13 @@ pub struct RecordStructure {
     pub ext: Variable,
 }
 
+#[derive(Debug)]
+pub struct TupleStructure {
+    /// Invariant: these should be sorted!
+    pub elems: Vec<(usize, Variable)>,
+    pub ext: Variable,
+}
+
 #[derive(Debug)]
 pub struct TagUnionStructure<'a> {
     /// Invariant: these should be sorted!
@@ -3523,10 +3728,14 @@ pub enum Category {
 
     // records
     Record,
-    Accessor(Lowercase),
+    Accessor(IndexOrField),
     Access(Lowercase),
     DefaultValue(Lowercase), // for setting optional fields
 
+    // tuples
+    Tuple,
+    TupleAccess(usize),
+
     AbilityMemberSpecialization(Symbol),
 
     Crash,
@@ -3539,6 +3748,7 @@ pub enum Category {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PatternCategory {
     Record,
+    Tuple,
     List,
     EmptyRecord,
     PatternGuard,
@@ -3663,6 +3873,7 @@ pub enum ErrorType {
     FlexAbleVar(Lowercase, AbilitySet),
     RigidAbleVar(Lowercase, AbilitySet),
     Record(SendMap<Lowercase, RecordField<ErrorType>>, TypeExt),
+    Tuple(SendMap<usize, ErrorType>, TypeExt),
     TagUnion(SendMap<TagName, Vec<ErrorType>>, TypeExt, Polarity),
     RecursiveTagUnion(
         Box<ErrorType>,
@@ -3706,6 +3917,10 @@ impl ErrorType {
                     .for_each(|(_, t)| t.as_inner().add_names(taken));
                 ext.add_names(taken);
             }
+            Tuple(elems, ext) => {
+                elems.iter().for_each(|(_, t)| t.add_names(taken));
+                ext.add_names(taken);
+            }
             TagUnion(tags, ext, _) => {
                 tags.iter()
                     .for_each(|(_, ts)| ts.iter().for_each(|t| t.add_names(taken)));
@@ -3852,6 +4067,25 @@ fn write_error_type_help(
             buf.push('}');
             write_type_ext(ext, buf);
         }
+        Tuple(elems, ext) => {
+            buf.push('(');
+
+            let mut elems: Vec<_> = elems.into_iter().collect();
+            elems.sort_by_key(|(index, _)| *index);
+
+            let mut it = elems.into_iter().peekable();
+
+            while let Some((_, elem)) = it.next() {
+                write_error_type_help(interns, elem, buf, Parens::Unnecessary);
+
+                if it.peek().is_some() {
+                    buf.push_str(", ");
+                }
+            }
+
+            buf.push(')');
+            write_type_ext(ext, buf);
+        }
 
         other => todo!("cannot format {:?} yet", other),
     }
@@ -4002,6 +4236,25 @@ fn write_debug_error_type_help(error_type: ErrorType, buf: &mut String, parens:
             buf.push('}');
             write_type_ext(ext, buf);
         }
+        Tuple(elems, ext) => {
+            buf.push('(');
+
+            let mut elems: Vec<_> = elems.into_iter().collect();
+            elems.sort_by_key(|(index, _)| *index);
+
+            let mut it = elems.into_iter().peekable();
+
+            while let Some((_, elem)) = it.next() {
+                write_debug_error_type_help(elem, buf, Parens::Unnecessary);
+
+                if it.peek().is_some() {
+                    buf.push_str(", ");
+                }
+            }
+
+            buf.push(')');
+            write_type_ext(ext, buf);
+        }
         TagUnion(tags, ext, _pol) => {
             buf.push('[');
 
@@ -4228,6 +4481,73 @@ pub fn gather_fields(
     })
 }
 
+#[derive(Debug, Copy, Clone)]
+pub struct TupleElemsError;
+
+pub fn gather_tuple_elems_unsorted_iter(
+    subs: &Subs,
+    other_elems: TupleElems,
+    mut var: Variable,
+) -> Result<(impl Iterator<Item = (usize, Variable)> + '_, Variable), TupleElemsError> {
+    use crate::subs::Content::*;
+    use crate::subs::FlatType::*;
+
+    let mut stack = vec![other_elems];
+
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Structure(Tuple(sub_elems, sub_ext)) => {
+                stack.push(*sub_elems);
+
+                if var == Variable::EMPTY_TUPLE {
+                    break;
+                } else {
+                    var = *sub_ext;
+                }
+            }
+
+            Alias(_, _, actual_var, _) => {
+                var = *actual_var;
+            }
+
+            Structure(EmptyTuple) => break,
+            FlexVar(_) | FlexAbleVar(..) => break,
+            RigidVar(_) | RigidAbleVar(..) => break,
+
+            // Stop on errors in the tuple
+            Error => break,
+
+            _ => return Err(TupleElemsError),
+        }
+    }
+
+    let it = stack
+        .into_iter()
+        .flat_map(|elems| elems.iter_all())
+        .map(move |(i1, i2)| {
+            let elem_index: usize = subs[i1];
+            let variable = subs[i2];
+
+            (elem_index, variable)
+        });
+
+    Ok((it, var))
+}
+
+pub fn gather_tuple_elems(
+    subs: &Subs,
+    other_elems: TupleElems,
+    var: Variable,
+) -> Result<TupleStructure, TupleElemsError> {
+    let (it, ext) = gather_tuple_elems_unsorted_iter(subs, other_elems, var)?;
+
+    let mut result: Vec<_> = it.collect();
+
+    result.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(TupleStructure { elems: result, ext })
+}
+
 #[derive(Debug)]
 pub enum GatherTagsError {
     NotATagUnion(Variable),
@@ -4389,6 +4709,12 @@ fn instantiate_lambda_sets_as_unspecialized(
                     stack.push(x.as_inner_mut());
                 }
             }
+            Type::Tuple(elems, ext) => {
+                stack.extend(ext.iter_mut());
+                for (_, x) in elems.iter_mut() {
+                    stack.push(x);
+                }
+            }
             Type::TagUnion(tags, ext) | Type::RecursiveTagUnion(_, tags, ext) => {
                 stack.extend(ext.iter_mut());
                 for (_, ts) in tags {
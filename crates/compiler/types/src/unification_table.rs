@@ -53,6 +53,11 @@ impl UnificationTable {
         self.contents.is_empty()
     }
 
+    /// The content of every variable, whether or not it is a root.
+    pub fn contents_mut(&mut self) -> impl Iterator<Item = &mut Content> {
+        self.contents.iter_mut()
+    }
+
     pub fn reserve(&mut self, extra_length: usize) -> VariableSubsSlice {
         use std::iter::repeat;
 
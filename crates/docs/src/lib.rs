@@ -12,10 +12,12 @@ use roc_highlight::highlight_parser::{highlight_defs, highlight_expr};
 use roc_load::docs::DocEntry::DocDef;
 use roc_load::docs::{DocEntry, TypeAnnotation};
 use roc_load::docs::{Documentation, ModuleDocumentation, RecordField};
-use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
+use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, OptLevel, Threading};
 use roc_module::symbol::{IdentIdsByModule, Interns, ModuleId};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_parse::ast::{self, ExtractSpaces, Header};
 use roc_parse::ident::{parse_ident, Ident};
+use roc_parse::module::parse_header;
 use roc_parse::state::State;
 use roc_region::all::Region;
 use std::fs;
@@ -26,16 +28,102 @@ mod html;
 
 const BUILD_DIR: &str = "./generated-docs";
 
+/// Where links to builtin modules point when the builtins aren't part of
+/// the package being documented.
+const BUILTINS_DOCS_URL: &str = "https://www.roc-lang.org/builtins/";
+
+/// What a `package` module's header says about the package it describes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: String,
+    pub exposed_modules: Vec<String>,
+}
+
+impl PackageInfo {
+    /// Parses the header of a `package` module. Returns None if the source
+    /// doesn't parse, or if it's some other kind of module.
+    ///
+    /// The header has no version field of its own, so a version is read from
+    /// the package name instead, e.g. `package "roc/json@0.1.0"`.
+    pub fn from_source(src: &[u8]) -> Option<Self> {
+        let arena = Bump::new();
+
+        match parse_header(&arena, State::new(src)) {
+            Ok((
+                ast::Module {
+                    header: Header::Package(header),
+                    ..
+                },
+                _,
+            )) => {
+                let (name, version) = match header.name.value.as_str().rsplit_once('@') {
+                    Some((name, version)) => (name, version),
+                    None => (header.name.value.as_str(), ""),
+                };
+
+                let exposed_modules = header
+                    .exposes
+                    .item
+                    .iter()
+                    .map(|module_name| module_name.value.extract_spaces().item.as_str().to_string())
+                    .collect();
+
+                Some(PackageInfo {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    exposed_modules,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The .roc file of each exposed module, e.g. `Json.Decode` is `Json/Decode.roc`
+    /// in the directory of the package module.
+    pub fn module_paths(&self, root_dir: &Path) -> Vec<PathBuf> {
+        self.exposed_modules
+            .iter()
+            .map(|module_name| {
+                root_dir
+                    .join(module_name.replace('.', "/"))
+                    .with_extension("roc")
+            })
+            .collect()
+    }
+}
+
+/// Generates docs for the given .roc files. If one of them is a `package` module,
+/// only the modules it exposes are documented, and the package's README.md
+/// becomes the front page.
 pub fn generate_docs_html(filenames: Vec<PathBuf>) {
     let build_dir = Path::new(BUILD_DIR);
-    let loaded_modules = load_modules_for_files(filenames);
-
-    // TODO: get info from a package module; this is all hardcoded for now.
-    let package = Documentation {
-        name: "documentation".to_string(),
-        version: "".to_string(),
-        docs: "Package introduction or README.".to_string(),
-        modules: loaded_modules,
+
+    let package_module = filenames.iter().find_map(|filename| {
+        let src = fs::read(filename).ok()?;
+        let info = PackageInfo::from_source(&src)?;
+        let root_dir = filename.parent().unwrap_or_else(|| Path::new("."));
+
+        Some((info, root_dir.to_path_buf()))
+    });
+
+    let package = match package_module {
+        Some((info, root_dir)) => {
+            let readme = fs::read_to_string(root_dir.join("README.md")).unwrap_or_default();
+
+            Documentation {
+                name: info.name.clone(),
+                version: info.version.clone(),
+                docs: readme,
+                modules: load_modules_for_files(info.module_paths(&root_dir)),
+            }
+        }
+        None => Documentation {
+            name: "documentation".to_string(),
+            version: "".to_string(),
+            docs: "".to_string(),
+            modules: load_modules_for_files(filenames),
+        },
     };
 
     if !build_dir.exists() {
@@ -85,16 +173,34 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
             })
     });
 
+    let documented_modules = module_pairs
+        .clone()
+        .map(|(module, _)| module.name.as_str())
+        .collect::<Vec<&str>>();
+
+    fs::write(
+        build_dir.join("search-index.json"),
+        search_index_json(module_pairs.clone()),
+    )
+    .expect("TODO gracefully handle failing to make the search index");
+
     let template_html = include_str!("./static/index.html")
-        .replace("<!-- search.js -->", "/search.js")
-        .replace("<!-- styles.css -->", "/styles.css")
-        .replace("<!-- favicon.svg -->", "/favicon.svg")
+        .replace("<!-- search.js -->", &format!("{}search.js", base_url()))
+        .replace(
+            "<!-- search-index.json -->",
+            &format!("{}search-index.json", base_url()),
+        )
+        .replace("<!-- styles.css -->", &format!("{}styles.css", base_url()))
+        .replace(
+            "<!-- favicon.svg -->",
+            &format!("{}favicon.svg", base_url()),
+        )
         .replace(
             "<!-- Prefetch links -->",
             &module_pairs
                 .clone()
                 .map(|(module, _)| {
-                    let href = sidebar_link_url(module);
+                    let href = module_url(module.name.as_str());
 
                     format!(r#"<link rel="prefetch" href="{href}"/>"#)
                 })
@@ -104,8 +210,32 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
         .replace(
             "<!-- Module links -->",
             render_sidebar(module_pairs).as_str(),
+        )
+        .replace(
+            "<!-- Package Name and Version -->",
+            render_name_and_version(
+                escape_html(&package.name).as_str(),
+                escape_html(&package.version).as_str(),
+            )
+            .as_str(),
         );
 
+    // Write the package's front page
+    {
+        let rendered_package = template_html
+            .replace(
+                "<!-- Page title -->",
+                format!("<title>{}</title>", escape_html(&package.name)).as_str(),
+            )
+            .replace(
+                "<!-- Module Docs -->",
+                render_package_index(&package, &documented_modules).as_str(),
+            );
+
+        fs::write(build_dir.join("index.html"), rendered_package)
+            .expect("TODO gracefully handle failing to write the package's index.html");
+    }
+
     // Write each package's module docs html file
     for loaded_module in package.modules.iter() {
         for (module_id, module_docs) in loaded_module.documentation.iter() {
@@ -120,14 +250,14 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
                         "<!-- Page title -->",
                         page_title(&package, module_docs).as_str(),
                     )
-                    .replace(
-                        "<!-- Package Name and Version -->",
-                        render_name_and_version(package.name.as_str(), package.version.as_str())
-                            .as_str(),
-                    )
                     .replace(
                         "<!-- Module Docs -->",
-                        render_module_documentation(module_docs, loaded_module).as_str(),
+                        render_module_documentation(
+                            module_docs,
+                            loaded_module,
+                            &documented_modules,
+                        )
+                        .as_str(),
                     );
 
                 fs::write(module_dir.join("index.html"), rendered_module).expect(
@@ -140,17 +270,122 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
     println!("🎉 Docs generated in {}", build_dir.display());
 }
 
-fn sidebar_link_url(module: &ModuleDocumentation) -> String {
-    format!("{}{}", base_url(), module.name.as_str())
+/// e.g. "/Json/Decode" for the `Json.Decode` module, which is where its
+/// index.html gets written.
+fn module_url(module_name: &str) -> String {
+    format!("{}{}", base_url(), module_name.replace('.', "/"))
 }
 
 fn page_title(package: &Documentation, module: &ModuleDocumentation) -> String {
-    let package_name = &package.name;
+    let package_name = escape_html(&package.name);
     let module_name = &module.name;
     let title = format!("<title>{module_name} - {package_name}</title>");
     title
 }
 
+fn render_package_index(package: &Documentation, documented_modules: &[&str]) -> String {
+    let mut buf = String::new();
+
+    if !package.docs.is_empty() {
+        let markdown_options = pulldown_cmark::Options::ENABLE_TABLES;
+        let parser = pulldown_cmark::Parser::new_ext(&package.docs, markdown_options);
+
+        pulldown_cmark::html::push_html(&mut buf, parser);
+    }
+
+    buf.push_str(html_to_string("h2", vec![("class", "module-name")], "Exposed Modules").as_str());
+
+    let mut module_links = String::new();
+
+    for module_name in documented_modules {
+        let href = module_url(module_name);
+
+        module_links.push_str(
+            html_to_string(
+                "li",
+                vec![],
+                html_to_string("a", vec![("href", href.as_str())], module_name).as_str(),
+            )
+            .as_str(),
+        );
+    }
+
+    buf.push_str(
+        html_to_string("ul", vec![("class", "index-module-links")], &module_links).as_str(),
+    );
+
+    buf
+}
+
+/// A JSON array with an entry for each exposed value in each module, which
+/// static/search.js loads to search across the whole package.
+fn search_index_json<'a, I: Iterator<Item = (&'a ModuleDocumentation, Vec<String>)>>(
+    modules: I,
+) -> String {
+    let mut entries = Vec::new();
+
+    for (module, exposed_values) in modules {
+        let module_href = module_url(module.name.as_str());
+
+        for entry in &module.entries {
+            if let DocEntry::DocDef(doc_def) = entry {
+                if exposed_values.contains(&doc_def.name) {
+                    let mut type_str = String::new();
+
+                    type_annotation_to_text(0, &mut type_str, &doc_def.type_annotation, false);
+
+                    // Search results show the type on a single line
+                    let type_str = type_str.split_whitespace().collect::<Vec<_>>().join(" ");
+
+                    let mut buf = String::new();
+
+                    buf.push_str("{\"module\":");
+                    push_json_str(&mut buf, module.name.as_str());
+                    buf.push_str(",\"name\":");
+                    push_json_str(&mut buf, doc_def.name.as_str());
+                    buf.push_str(",\"type\":");
+                    push_json_str(&mut buf, type_str.as_str());
+                    buf.push_str(",\"url\":");
+                    push_json_str(&mut buf, &format!("{module_href}#{}", doc_def.name));
+                    buf.push('}');
+
+                    entries.push(buf);
+                }
+            }
+        }
+    }
+
+    format!("[{}]", entries.join(",\n"))
+}
+
+/// Escapes text from the package, like its name, so it shows up as-is in HTML
+fn escape_html(text: &str) -> String {
+    let mut buf = String::with_capacity(text.len());
+
+    // Writing to a String can't fail
+    let _ = pulldown_cmark::escape::escape_html(&mut buf, text);
+
+    buf
+}
+
+fn push_json_str(buf: &mut String, string: &str) {
+    buf.push('"');
+
+    for ch in string.chars() {
+        match ch {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => buf.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => buf.push(ch),
+        }
+    }
+
+    buf.push('"');
+}
+
 // converts plain-text code to highlighted html
 pub fn syntax_highlight_expr(code_str: &str) -> DocsResult<String> {
     let trimmed_code_str = code_str.trim_end().trim();
@@ -197,6 +432,7 @@ pub fn syntax_highlight_top_level_defs(code_str: &str) -> DocsResult<String> {
 fn render_module_documentation(
     module: &ModuleDocumentation,
     loaded_module: &LoadedModule,
+    documented_modules: &[&str],
 ) -> String {
     let mut buf = String::new();
 
@@ -204,7 +440,12 @@ fn render_module_documentation(
         html_to_string(
             "h2",
             vec![("class", "module-name")],
-            html_to_string("a", vec![("href", "/#")], module.name.as_str()).as_str(),
+            html_to_string(
+                "a",
+                vec![("href", base_url().as_str())],
+                module.name.as_str(),
+            )
+            .as_str(),
         )
         .as_str(),
     );
@@ -268,6 +509,7 @@ fn render_module_documentation(
                                 &module.scope,
                                 docs.to_string(),
                                 loaded_module,
+                                documented_modules,
                             )
                             .as_str(),
                         );
@@ -281,6 +523,7 @@ fn render_module_documentation(
                         &module.scope,
                         docs.to_string(),
                         loaded_module,
+                        documented_modules,
                     );
                     buf.push_str(markdown.as_str());
                 }
@@ -352,9 +595,7 @@ fn base_url() -> String {
 
 fn render_name_and_version(name: &str, version: &str) -> String {
     let mut buf = String::new();
-    let mut url_str = base_url();
-
-    url_str.push_str(name);
+    let url_str = base_url();
 
     buf.push_str(
         html_to_string(
@@ -365,21 +606,9 @@ fn render_name_and_version(name: &str, version: &str) -> String {
         .as_str(),
     );
 
-    let mut versions_url_str = base_url();
-
-    versions_url_str.push('/');
-    versions_url_str.push_str(name);
-    versions_url_str.push('/');
-    versions_url_str.push_str(version);
-
-    buf.push_str(
-        html_to_string(
-            "a",
-            vec![("class", "version"), ("href", versions_url_str.as_str())],
-            version,
-        )
-        .as_str(),
-    );
+    if !version.is_empty() {
+        buf.push_str(html_to_string("span", vec![("class", "version")], version).as_str());
+    }
 
     buf
 }
@@ -390,7 +619,7 @@ fn render_sidebar<'a, I: Iterator<Item = (&'a ModuleDocumentation, Vec<String>)>
     let mut buf = String::new();
 
     for (module, exposed_values) in modules {
-        let href = sidebar_link_url(module);
+        let href = module_url(module.name.as_str());
         let mut sidebar_entry_content = String::new();
 
         sidebar_entry_content.push_str(
@@ -462,6 +691,7 @@ pub fn load_modules_for_files(filenames: Vec<PathBuf>) -> Vec<LoadedModule> {
             palette: roc_reporting::report::DEFAULT_PALETTE,
             threading: Threading::AllAvailable,
             exec_mode: ExecutionMode::Check,
+            opt_level: OptLevel::Normal,
         };
         match roc_load::load_and_typecheck(
             &arena,
@@ -500,6 +730,20 @@ fn type_annotation_to_html(
     buf: &mut String,
     type_ann: &TypeAnnotation,
     needs_parens: bool,
+) {
+    let mut text = String::new();
+
+    type_annotation_to_text(indent_level, &mut text, type_ann, needs_parens);
+
+    buf.push_str(&escape_html(&text));
+}
+
+// the type as Roc code, e.g. for the search index
+fn type_annotation_to_text(
+    indent_level: usize,
+    buf: &mut String,
+    type_ann: &TypeAnnotation,
+    needs_parens: bool,
 ) {
     let is_multiline = should_be_multiline(type_ann);
     match type_ann {
@@ -534,7 +778,7 @@ fn type_annotation_to_html(
 
                     for type_value in &tag.values {
                         buf.push(' ');
-                        type_annotation_to_html(next_indent_level, buf, type_value, true);
+                        type_annotation_to_text(next_indent_level, buf, type_value, true);
                     }
 
                     if is_multiline {
@@ -553,7 +797,7 @@ fn type_annotation_to_html(
                 buf.push(']');
             }
 
-            type_annotation_to_html(indent_level, buf, extension, true);
+            type_annotation_to_text(indent_level, buf, extension, true);
         }
         TypeAnnotation::BoundVariable(var_name) => {
             buf.push_str(var_name);
@@ -569,7 +813,7 @@ fn type_annotation_to_html(
                 buf.push_str(name);
                 for part in parts {
                     buf.push(' ');
-                    type_annotation_to_html(indent_level, buf, part, true);
+                    type_annotation_to_text(indent_level, buf, part, true);
                 }
 
                 if needs_parens {
@@ -617,13 +861,13 @@ fn type_annotation_to_html(
                             type_annotation, ..
                         } => {
                             buf.push_str(" : ");
-                            type_annotation_to_html(next_indent_level, buf, type_annotation, false);
+                            type_annotation_to_text(next_indent_level, buf, type_annotation, false);
                         }
                         RecordField::OptionalField {
                             type_annotation, ..
                         } => {
                             buf.push_str(" ? ");
-                            type_annotation_to_html(next_indent_level, buf, type_annotation, false);
+                            type_annotation_to_text(next_indent_level, buf, type_annotation, false);
                         }
                         RecordField::LabelOnly { .. } => {}
                     }
@@ -646,7 +890,7 @@ fn type_annotation_to_html(
                 buf.push('}');
             }
 
-            type_annotation_to_html(indent_level, buf, extension, true);
+            type_annotation_to_text(indent_level, buf, extension, true);
         }
         TypeAnnotation::Function { args, output } => {
             let mut peekable_args = args.iter().peekable();
@@ -658,7 +902,7 @@ fn type_annotation_to_html(
                     indent(buf, indent_level + 1);
                 }
 
-                type_annotation_to_html(indent_level, buf, arg, false);
+                type_annotation_to_text(indent_level, buf, arg, false);
 
                 if peekable_args.peek().is_some() {
                     buf.push_str(", ");
@@ -678,7 +922,7 @@ fn type_annotation_to_html(
                 next_indent_level += 1;
             }
 
-            type_annotation_to_html(next_indent_level, buf, output, false);
+            type_annotation_to_text(next_indent_level, buf, output, false);
         }
         TypeAnnotation::Ability { members: _ } => {
             // TODO(abilities): fill me in
@@ -770,15 +1014,21 @@ struct DocUrl {
     title: String,
 }
 
+/// Returns None when the ident is from a module that isn't documented
+/// anywhere we know of, e.g. a module the package doesn't expose.
+#[allow(clippy::too_many_arguments)]
 fn doc_url<'a>(
     home: ModuleId,
     exposed_values: &[&str],
+    documented_modules: &[&str],
     dep_idents: &IdentIdsByModule,
     scope: &Scope,
     interns: &'a Interns,
     mut module_name: &'a str,
     ident: &str,
-) -> DocUrl {
+) -> Option<DocUrl> {
+    let module_id;
+
     if module_name.is_empty() {
         // This is an unqualified lookup, so look for the ident
         // in scope!
@@ -789,6 +1039,7 @@ fn doc_url<'a>(
                 // module - for example, if this is in scope from an
                 // unqualified import.
                 module_name = symbol.module_string(interns);
+                module_id = symbol.module_id();
             }
             Err(_) => {
                 // TODO return Err here
@@ -800,7 +1051,9 @@ fn doc_url<'a>(
         }
     } else {
         match interns.module_ids.get_id(&module_name.into()) {
-            Some(module_id) => {
+            Some(id) => {
+                module_id = id;
+
                 // You can do qualified lookups on your own module, e.g.
                 // if I'm in the Foo module, I can do a `Foo.bar` lookup.
                 if module_id == home {
@@ -821,9 +1074,6 @@ fn doc_url<'a>(
                         Some(_) => {
                             // This is a valid symbol for this dependency,
                             // so proceed using the current module's name.
-                            //
-                            // TODO: In the future, this is where we'll
-                            // incorporate the package name into the link.
                         }
                         _ => {
                             // TODO return Err here
@@ -841,19 +1091,25 @@ fn doc_url<'a>(
         }
     }
 
-    let mut url = base_url();
-
     // Example:
     //
     // module_name: "Str", ident: "join" => "/Str#join"
-    url.push_str(module_name);
+    let mut url = if documented_modules.contains(&module_name) {
+        module_url(module_name)
+    } else if module_id.is_builtin() {
+        // Link to the builtins docs when they aren't being generated alongside these
+        format!("{}{}", BUILTINS_DOCS_URL, module_name)
+    } else {
+        return None;
+    };
+
     url.push('#');
     url.push_str(ident);
 
-    DocUrl {
+    Some(DocUrl {
         url,
         title: format!("Docs for {}.{}", module_name, ident),
-    }
+    })
 }
 
 fn markdown_to_html(
@@ -861,6 +1117,7 @@ fn markdown_to_html(
     scope: &Scope,
     markdown: String,
     loaded_module: &LoadedModule,
+    documented_modules: &[&str],
 ) -> String {
     use pulldown_cmark::{BrokenLink, CodeBlockKind, CowStr, Event, LinkType, Tag::*};
 
@@ -889,12 +1146,13 @@ fn markdown_to_html(
                                 let DocUrl { url, title } = doc_url(
                                     loaded_module.module_id,
                                     exposed_values,
+                                    documented_modules,
                                     &loaded_module.dep_idents,
                                     scope,
                                     &loaded_module.interns,
                                     module_name,
                                     symbol_name,
-                                );
+                                )?;
 
                                 Some((url.into(), title.into()))
                             }
@@ -912,12 +1170,13 @@ fn markdown_to_html(
                         let DocUrl { url, title } = doc_url(
                             loaded_module.module_id,
                             exposed_values,
+                            documented_modules,
                             &loaded_module.dep_idents,
                             scope,
                             &loaded_module.interns,
                             "",
                             type_name,
-                        );
+                        )?;
 
                         Some((url.into(), title.into()))
                     }
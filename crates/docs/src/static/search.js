@@ -1,10 +1,64 @@
 (() => {
   let sidebar = document.getElementById("sidebar-nav");
   let searchBox = document.getElementById("module-search");
+  let searchResults = document.getElementById("search-results");
+
+  // Every exposed value in the package, so results can include what the
+  // sidebar doesn't show, like types.
+  let searchIndex = [];
+
+  fetch(sidebar.dataset.searchIndex)
+    .then((response) => response.json())
+    .then((entries) => {
+      searchIndex = entries;
+      search();
+    })
+    .catch(() => {
+      // Without the index, searching still filters the sidebar.
+    });
+
+  function showSearchResults(text) {
+    searchResults.replaceChildren();
+
+    if (text === "") {
+      searchResults.classList.add("hidden");
+      return;
+    }
+
+    let matches = searchIndex.filter((entry) =>
+      `${entry.module}.${entry.name}`.toLowerCase().includes(text) ||
+      entry.type.toLowerCase().includes(text)
+    );
+
+    matches.slice(0, 20).forEach((entry) => {
+      let link = document.createElement("a");
+      link.href = entry.url;
+
+      let name = document.createElement("span");
+      name.className = "search-result-name";
+      name.textContent = `${entry.module}.${entry.name}`;
+      link.appendChild(name);
+
+      if (entry.type !== "") {
+        let type = document.createElement("span");
+        type.className = "search-result-type";
+        type.textContent = ` : ${entry.type}`;
+        link.appendChild(type);
+      }
+
+      let item = document.createElement("li");
+      item.appendChild(link);
+      searchResults.appendChild(item);
+    });
+
+    searchResults.classList.toggle("hidden", matches.length === 0);
+  }
 
   function search() {
     let text = searchBox.value.toLowerCase(); // Search is case-insensitive.
 
+    showSearchResults(text);
+
     if (text === "") {
       // Un-hide everything
       sidebar.querySelectorAll(".sidebar-entry a").forEach((entry) => entry.classList.remove("hidden"));
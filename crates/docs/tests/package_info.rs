@@ -0,0 +1,59 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[cfg(test)]
+mod package_info {
+    use roc_docs::PackageInfo;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn name_version_and_exposed_modules() {
+        let src = r#"package "roc/json@0.1.0"
+    exposes [Json, Json.Decode]
+    packages {}
+"#;
+
+        assert_eq!(
+            PackageInfo::from_source(src.as_bytes()),
+            Some(PackageInfo {
+                name: "roc/json".to_string(),
+                version: "0.1.0".to_string(),
+                exposed_modules: vec!["Json".to_string(), "Json.Decode".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn no_version() {
+        let src = r#"package "roc/json" exposes [Json] packages {}"#;
+
+        let info = PackageInfo::from_source(src.as_bytes()).unwrap();
+
+        assert_eq!(info.name, "roc/json");
+        assert_eq!(info.version, "");
+    }
+
+    #[test]
+    fn not_a_package() {
+        let src = "interface Json exposes [] imports []";
+
+        assert_eq!(PackageInfo::from_source(src.as_bytes()), None);
+    }
+
+    #[test]
+    fn module_paths() {
+        let info = PackageInfo {
+            name: "roc/json".to_string(),
+            version: "".to_string(),
+            exposed_modules: vec!["Json".to_string(), "Json.Decode".to_string()],
+        };
+
+        assert_eq!(
+            info.module_paths(Path::new("pkg")),
+            vec![
+                PathBuf::from("pkg/Json.roc"),
+                PathBuf::from("pkg/Json/Decode.roc")
+            ]
+        );
+    }
+}
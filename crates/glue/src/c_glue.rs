@@ -0,0 +1,765 @@
+use crate::rust_glue::tagged_pointer_bitmask;
+use crate::types::{RocFn, RocNum, RocTagUnion, RocType, TypeId, Types};
+use indexmap::IndexMap;
+use roc_mono::layout::round_up_to_alignment;
+use roc_target::{Architecture, TargetInfo};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+pub static HEADER: &[u8] = include_bytes!("../templates/header.h");
+const INDENT: &str = "    ";
+
+pub fn emit(types_and_targets: &[(Types, TargetInfo)]) -> String {
+    // Unlike Rust's #[cfg], an #if can't go on an individual struct field, so every
+    // target gets its own complete set of declarations. Targets whose layouts turn
+    // out identical (e.g. the 64-bit ones) share a single set.
+    let mut bodies: IndexMap<String, Vec<TargetInfo>> = IndexMap::default();
+
+    for (types, target_info) in types_and_targets {
+        bodies
+            .entry(emit_target(types, *target_info))
+            .or_default()
+            .push(*target_info);
+    }
+
+    let mut buf = String::new();
+
+    if bodies.keys().all(String::is_empty) {
+        return buf;
+    }
+
+    for (index, (body, targets)) in bodies.iter().enumerate() {
+        let directive = if index == 0 { "#if" } else { "#elif" };
+        let condition = targets
+            .iter()
+            .map(|target_info| format!("defined({})", arch_to_macro(target_info.architecture)))
+            .collect::<Vec<_>>()
+            .join(" || ");
+
+        write!(buf, "\n{directive} {condition}\n{body}").unwrap();
+    }
+
+    buf.push_str(
+        "\n#else\n#error \"Roc glue was not generated for this target architecture.\"\n#endif\n",
+    );
+
+    buf
+}
+
+fn emit_target(types: &Types, target_info: TargetInfo) -> String {
+    let mut decls = Decls {
+        types,
+        target_info,
+        declared: HashSet::default(),
+        buf: String::new(),
+    };
+
+    // A recursive tag union is a pointer to a payload which contains the tag union,
+    // so the structs holding those pointers need to come before everything else.
+    for id in types.sorted_ids() {
+        decls.add_pointer_struct(id);
+    }
+
+    for id in types.sorted_ids() {
+        decls.declare(id);
+    }
+
+    decls.buf
+}
+
+struct Decls<'a> {
+    types: &'a Types,
+    target_info: TargetInfo,
+    declared: HashSet<TypeId>,
+    buf: String,
+}
+
+impl<'a> Decls<'a> {
+    fn add_pointer_struct(&mut self, id: TypeId) {
+        match self.types.get_type(id) {
+            RocType::TagUnion(
+                RocTagUnion::Recursive { name, .. } | RocTagUnion::NullableWrapped { name, .. },
+            ) => {
+                // The discriminant may be stored in the pointer's low bits, so this
+                // pointer is untyped; {name}_payload returns the real one.
+                write!(
+                    self.buf,
+                    "\nstruct {name} {{\n{INDENT}void *pointer;\n}};\n"
+                )
+                .unwrap();
+            }
+            RocType::TagUnion(
+                RocTagUnion::NonNullableUnwrapped { name, payload, .. }
+                | RocTagUnion::NullableUnwrapped {
+                    name,
+                    non_null_payload: payload,
+                    ..
+                },
+            ) => {
+                let payload_type = type_name(*payload, self.types);
+
+                write!(
+                    self.buf,
+                    "\nstruct {name} {{\n{INDENT}{payload_type} *pointer;\n}};\n"
+                )
+                .unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    fn declare(&mut self, id: TypeId) {
+        if !self.declared.insert(id) {
+            return;
+        }
+
+        let types = self.types;
+        let typ = types.get_type(id);
+
+        // In C, a type has to be complete before it can be stored inline in another type.
+        for dep in inline_deps(typ) {
+            self.declare(dep);
+        }
+
+        match typ {
+            RocType::Struct { name, fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|(label, field_id)| (escape_kw(label), *field_id));
+
+                self.add_struct(name, fields, id, false);
+            }
+            RocType::TagUnionPayload { name, fields } => {
+                // Tag union payloads have numbered fields, so we prefix them
+                // with an "f" because C doesn't allow struct fields to be numbers.
+                let fields = fields
+                    .iter()
+                    .map(|(index, field_id)| (format!("f{index}"), *field_id));
+
+                self.add_struct(name, fields, id, true);
+            }
+            RocType::TagUnion(RocTagUnion::SingleTagStruct {
+                name,
+                payload_fields,
+                ..
+            }) => {
+                let fields = payload_fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field_id)| (format!("f{index}"), *field_id));
+
+                self.add_struct(name, fields, id, false);
+            }
+            RocType::TagUnion(RocTagUnion::Enumeration { name, tags, size }) => {
+                self.add_enumeration(name, tags, *size);
+            }
+            RocType::TagUnion(RocTagUnion::NonRecursive {
+                name,
+                tags,
+                discriminant_size,
+                discriminant_offset,
+            }) => {
+                // Empty tag unions can never come up at runtime,
+                // and so don't need declared types.
+                if !tags.is_empty() {
+                    self.add_tag_union(id, name, tags, *discriminant_size, *discriminant_offset);
+                }
+            }
+            RocType::TagUnion(RocTagUnion::Recursive {
+                name,
+                tags,
+                discriminant_size,
+                discriminant_offset,
+            }) => self.add_recursive_tag_union(
+                name,
+                tags,
+                None,
+                *discriminant_size,
+                *discriminant_offset,
+            ),
+            RocType::TagUnion(RocTagUnion::NullableWrapped {
+                name,
+                index_of_null_tag,
+                tags,
+                discriminant_size,
+                discriminant_offset,
+            }) => self.add_recursive_tag_union(
+                name,
+                tags,
+                Some(*index_of_null_tag as usize),
+                *discriminant_size,
+                *discriminant_offset,
+            ),
+            RocType::TagUnion(RocTagUnion::NullableUnwrapped { name, null_tag, .. }) => {
+                write!(
+                    self.buf,
+                    r#"
+static inline bool {name}_is_{null_tag}(struct {name} tag_union) {{
+    return tag_union.pointer == NULL;
+}}
+"#
+                )
+                .unwrap();
+            }
+            RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { .. }) => {
+                // This has only one tag, so the pointer struct is all it needs.
+            }
+            RocType::RocResult(ok_id, err_id) => self.add_result(id, *ok_id, *err_id),
+            // These are declared in the header, or don't need declarations.
+            RocType::Unit
+            | RocType::EmptyTagUnion
+            | RocType::Num(_)
+            | RocType::Bool
+            | RocType::RocStr
+            | RocType::RocDict(_, _)
+            | RocType::RocSet(_)
+            | RocType::RocList(_)
+            | RocType::RocBox(_)
+            | RocType::RecursivePointer(_) => {}
+            RocType::Function(roc_fn) => self.add_function(id, roc_fn),
+        }
+    }
+
+    fn add_struct<I: Iterator<Item = (String, TypeId)>>(
+        &mut self,
+        name: &str,
+        fields: I,
+        struct_id: TypeId,
+        is_tag_union_payload: bool,
+    ) {
+        write!(self.buf, "\nstruct {name} {{\n").unwrap();
+
+        for (label, field_id) in fields {
+            // Zero-sized fields (like empty records) take up no space in Roc,
+            // and C doesn't have zero-sized types, so leave them out.
+            if self.types.size_ignoring_alignment(field_id) > 0 {
+                writeln!(
+                    self.buf,
+                    "{INDENT}{}",
+                    field_decl(&label, field_id, self.types)
+                )
+                .unwrap();
+            }
+        }
+
+        self.buf.push_str("};\n");
+
+        // Types records a payload with its tag union's layout, so only check other structs.
+        if !is_tag_union_payload {
+            self.add_layout_asserts(&format!("struct {name}"), struct_id);
+        }
+    }
+
+    fn add_function(&mut self, fn_id: TypeId, roc_fn: &RocFn) {
+        let RocFn {
+            function_name: name,
+            extern_name,
+            args,
+            ret,
+            is_toplevel,
+        } = roc_fn;
+        let types = self.types;
+        let ret_type = type_name(*ret, types);
+        let returns_void = types.size_ignoring_alignment(*ret) == 0;
+        let output_arg = if returns_void { "NULL" } else { "&output" };
+        let mut params = Vec::with_capacity(args.len() + 1);
+        let mut extern_params = Vec::with_capacity(args.len() + 2);
+        let mut extern_args = Vec::with_capacity(args.len() + 2);
+
+        if *is_toplevel {
+            // Top-level functions capture nothing, and are exposed through a generic
+            // wrapper which takes the output pointer first, then Str and List
+            // arguments by pointer and everything else by value.
+            extern_params.push(format!("{} *output", ret_type));
+            extern_args.push(output_arg.to_string());
+
+            for (index, arg_id) in args.iter().enumerate() {
+                let arg_type = type_name(*arg_id, types);
+
+                match types.get_type(*arg_id) {
+                    RocType::RocStr | RocType::RocList(_) => {
+                        params.push(format!("const {arg_type} *arg{index}"));
+                    }
+                    // C doesn't have zero-sized types, so these arguments are left out.
+                    _ if types.size_ignoring_alignment(*arg_id) == 0 => continue,
+                    _ => {
+                        let separator = if arg_type.ends_with('*') { "" } else { " " };
+
+                        params.push(format!("{arg_type}{separator}arg{index}"));
+                    }
+                }
+
+                extern_params.push(params.last().unwrap().clone());
+                extern_args.push(format!("arg{index}"));
+            }
+        } else {
+            let size = types.size_ignoring_alignment(fn_id);
+
+            // A closure is opaque to the host; all it can do is hold onto the
+            // environment the closure captured, and pass it back to Roc.
+            if size == 0 {
+                write!(
+                    self.buf,
+                    "
+struct {name};
+"
+                )
+                .unwrap();
+            } else {
+                let align = types.align(fn_id);
+
+                write!(
+                    self.buf,
+                    "
+struct {name} {{
+{INDENT}_Alignas({align}) uint8_t closure_data[{size}];
+}};
+"
+                )
+                .unwrap();
+                self.add_layout_asserts(&format!("struct {name}"), fn_id);
+            }
+
+            // The caller takes every argument by pointer, then the captured
+            // environment, and writes the return value into the output pointer at the end.
+            params.push(format!("const struct {name} *closure"));
+
+            for (index, arg_id) in args.iter().enumerate() {
+                let arg_type = type_name(*arg_id, types);
+
+                params.push(format!("const {arg_type} *arg{index}"));
+                extern_params.push(format!("const {arg_type} *arg{index}"));
+                extern_args.push(format!("arg{index}"));
+            }
+
+            extern_params.push("const void *closure_data".to_string());
+            extern_params.push(format!("{} *output", ret_type));
+            extern_args.push("closure".to_string());
+            extern_args.push(output_arg.to_string());
+        }
+
+        // Closures which weren't named with `as` have no caller, so the host can't call them.
+        let extern_name = match extern_name {
+            Some(extern_name) => extern_name,
+            None => return,
+        };
+        let fn_name = if *is_toplevel {
+            name.clone()
+        } else {
+            format!("{name}_call")
+        };
+        let params = params.join(", ");
+        let extern_params = extern_params.join(", ");
+        let extern_args = extern_args.join(", ");
+
+        write!(
+            self.buf,
+            "
+extern void {extern_name}({extern_params});
+
+static inline {ret_type} {fn_name}({params}) {{
+"
+        )
+        .unwrap();
+
+        if returns_void {
+            writeln!(self.buf, "{INDENT}{extern_name}({extern_args});").unwrap();
+        } else {
+            write!(
+                self.buf,
+                "{INDENT}{ret_type} output;
+
+{INDENT}{extern_name}({extern_args});
+
+{INDENT}return output;
+"
+            )
+            .unwrap();
+        }
+
+        self.buf.push_str(
+            "}
+",
+        );
+    }
+
+    fn add_enumeration<S: AsRef<str>>(&mut self, name: &str, tags: &[S], size: u32) {
+        // A C enum is the size of an int, so declare the constants separately from the type.
+        let repr = unsigned_int(size);
+
+        write!(self.buf, "\ntypedef {repr} {name};\n\nenum {{\n").unwrap();
+
+        for (index, tag_name) in tags.iter().enumerate() {
+            writeln!(self.buf, "{INDENT}{name}_{} = {index},", tag_name.as_ref()).unwrap();
+        }
+
+        self.buf.push_str("};\n");
+    }
+
+    fn add_tag_union(
+        &mut self,
+        union_id: TypeId,
+        name: &str,
+        tags: &[(String, Option<TypeId>)],
+        discriminant_size: u32,
+        discriminant_offset: u32,
+    ) {
+        // Roc puts the discriminant right after the largest payload, which isn't
+        // necessarily where a C struct would put it; so, like the Rust glue, the whole
+        // tag union is a `union`, and the discriminant is read at its offset.
+        let discriminant_name = format!("discriminant_{name}");
+        let tag_names: Vec<&str> = tags.iter().map(|(tag_name, _)| tag_name.as_str()).collect();
+        let size = self.types.size_rounded_to_alignment(union_id);
+
+        self.add_enumeration(&discriminant_name, &tag_names, discriminant_size);
+
+        write!(self.buf, "\nunion {name} {{\n").unwrap();
+        self.add_union_members(tags);
+        writeln!(self.buf, "{INDENT}uint8_t bytes[{size}];\n}};").unwrap();
+        self.add_layout_asserts(&format!("union {name}"), union_id);
+
+        write!(
+            self.buf,
+            r#"
+static inline {discriminant_name} {name}_discriminant(const union {name} *tag_union) {{
+    return *(const {discriminant_name} *)(tag_union->bytes + {discriminant_offset});
+}}
+
+static inline void {name}_set_discriminant(union {name} *tag_union, {discriminant_name} discriminant) {{
+    *({discriminant_name} *)(tag_union->bytes + {discriminant_offset}) = discriminant;
+}}
+"#
+        )
+        .unwrap();
+    }
+
+    fn add_recursive_tag_union(
+        &mut self,
+        name: &str,
+        tags: &[(String, Option<TypeId>)],
+        null_tag_index: Option<usize>,
+        discriminant_size: u32,
+        discriminant_offset: u32,
+    ) {
+        let discriminant_name = format!("discriminant_{name}");
+        let tag_names: Vec<&str> = tags.iter().map(|(tag_name, _)| tag_name.as_str()).collect();
+        // The null tag isn't stored anywhere, so it doesn't count toward the tags
+        // that need to fit in the pointer's unused bits.
+        let stored_tags = tags.len() - null_tag_index.iter().count();
+        let in_pointer = stores_discriminant_in_pointer(stored_tags, self.target_info);
+
+        self.add_enumeration(&discriminant_name, &tag_names, discriminant_size.max(1));
+
+        write!(self.buf, "\nunion {name}_Payload {{\n").unwrap();
+        self.add_union_members(tags);
+
+        if !in_pointer {
+            let bytes = discriminant_offset + discriminant_size;
+
+            writeln!(self.buf, "{INDENT}uint8_t bytes[{bytes}];").unwrap();
+        }
+
+        self.buf.push_str("};\n");
+
+        let null_check = match null_tag_index {
+            Some(index) => format!(
+                "if (tag_union.pointer == NULL) {{\n{INDENT}{INDENT}return {discriminant_name}_{};\n{INDENT}}}\n\n{INDENT}",
+                tags[index].0
+            ),
+            None => String::new(),
+        };
+
+        if in_pointer {
+            let bitmask = tagged_pointer_bitmask(self.target_info.architecture);
+
+            write!(
+                self.buf,
+                r#"
+static inline union {name}_Payload *{name}_payload(struct {name} tag_union) {{
+    return (union {name}_Payload *)((uintptr_t)tag_union.pointer & ~(uintptr_t){bitmask:#x});
+}}
+
+static inline {discriminant_name} {name}_discriminant(struct {name} tag_union) {{
+    {null_check}return ({discriminant_name})((uintptr_t)tag_union.pointer & {bitmask:#x});
+}}
+"#
+            )
+            .unwrap();
+        } else {
+            write!(
+                self.buf,
+                r#"
+static inline union {name}_Payload *{name}_payload(struct {name} tag_union) {{
+    return (union {name}_Payload *)tag_union.pointer;
+}}
+
+static inline {discriminant_name} {name}_discriminant(struct {name} tag_union) {{
+    {null_check}return *(const {discriminant_name} *)({name}_payload(tag_union)->bytes + {discriminant_offset});
+}}
+"#
+            )
+            .unwrap();
+        }
+    }
+
+    fn add_union_members(&mut self, tags: &[(String, Option<TypeId>)]) {
+        for (tag_name, opt_payload_id) in tags {
+            if let Some(payload_id) = opt_payload_id {
+                if self.types.size_ignoring_alignment(*payload_id) > 0 {
+                    writeln!(
+                        self.buf,
+                        "{INDENT}{}",
+                        field_decl(tag_name, *payload_id, self.types)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    fn add_result(&mut self, result_id: TypeId, ok_id: TypeId, err_id: TypeId) {
+        let name = result_name(ok_id, err_id, self.types);
+        let size = self.types.size_rounded_to_alignment(result_id);
+        let discriminant_offset = result_discriminant_offset(ok_id, err_id, self.types);
+
+        write!(self.buf, "\nunion {name} {{\n").unwrap();
+        self.add_union_members(&[
+            ("ok".to_string(), Some(ok_id)),
+            ("err".to_string(), Some(err_id)),
+        ]);
+        writeln!(self.buf, "{INDENT}uint8_t bytes[{size}];\n}};").unwrap();
+        self.add_layout_asserts(&format!("union {name}"), result_id);
+
+        // Err comes before Ok alphabetically, so Ok's discriminant is 1.
+        write!(
+            self.buf,
+            r#"
+static inline bool {name}_is_ok(const union {name} *result) {{
+    return result->bytes[{discriminant_offset}] == 1;
+}}
+"#
+        )
+        .unwrap();
+    }
+
+    fn add_layout_asserts(&mut self, c_type: &str, id: TypeId) {
+        let size = self.types.size_rounded_to_alignment(id);
+        let align = self.types.align(id);
+
+        // Roc reports an alignment of 0 for zero-sized types (e.g. a record whose
+        // fields are all closures that capture nothing), so there's nothing to check.
+        if size == 0 {
+            return;
+        }
+
+        write!(
+            self.buf,
+            "\n_Static_assert(sizeof({c_type}) == {size}, \"{c_type} should be {size} bytes\");\n\
+            _Static_assert(_Alignof({c_type}) == {align}, \"{c_type} should be {align}-byte aligned\");\n"
+        )
+        .unwrap();
+    }
+}
+
+/// The types which are stored inline in the given type (rather than behind a pointer),
+/// and which therefore need to be declared before it.
+fn inline_deps(typ: &RocType) -> Vec<TypeId> {
+    match typ {
+        RocType::Struct { fields, .. } => fields.iter().map(|(_, id)| *id).collect(),
+        RocType::TagUnionPayload { fields, .. } => fields.iter().map(|(_, id)| *id).collect(),
+        RocType::TagUnion(RocTagUnion::SingleTagStruct { payload_fields, .. }) => {
+            payload_fields.clone()
+        }
+        RocType::TagUnion(
+            RocTagUnion::NonRecursive { tags, .. }
+            | RocTagUnion::Recursive { tags, .. }
+            | RocTagUnion::NullableWrapped { tags, .. },
+        ) => tags.iter().filter_map(|(_, opt_id)| *opt_id).collect(),
+        RocType::RocResult(ok_id, err_id) => vec![*ok_id, *err_id],
+        // The call helper returns its result by value.
+        RocType::Function(RocFn { args, ret, .. }) => {
+            args.iter().copied().chain(std::iter::once(*ret)).collect()
+        }
+        // A pointer to an enumeration still needs its typedef.
+        RocType::RocBox(elem_id) => vec![*elem_id],
+        RocType::RocStr
+        | RocType::Bool
+        | RocType::Num(_)
+        | RocType::RocList(_)
+        | RocType::RocDict(_, _)
+        | RocType::RocSet(_)
+        | RocType::TagUnion(
+            RocTagUnion::Enumeration { .. }
+            | RocTagUnion::NonNullableUnwrapped { .. }
+            | RocTagUnion::NullableUnwrapped { .. },
+        )
+        | RocType::EmptyTagUnion
+        | RocType::RecursivePointer(_)
+        | RocType::Unit => Vec::new(),
+    }
+}
+
+fn type_name(id: TypeId, types: &Types) -> String {
+    match types.get_type(id) {
+        RocType::Unit | RocType::EmptyTagUnion => "void".to_string(),
+        RocType::RocStr => "struct RocStr".to_string(),
+        RocType::Bool => "bool".to_string(),
+        RocType::Num(RocNum::U8) => "uint8_t".to_string(),
+        RocType::Num(RocNum::U16) => "uint16_t".to_string(),
+        RocType::Num(RocNum::U32) => "uint32_t".to_string(),
+        RocType::Num(RocNum::U64) => "uint64_t".to_string(),
+        RocType::Num(RocNum::U128) => "struct RocU128".to_string(),
+        RocType::Num(RocNum::I8) => "int8_t".to_string(),
+        RocType::Num(RocNum::I16) => "int16_t".to_string(),
+        RocType::Num(RocNum::I32) => "int32_t".to_string(),
+        RocType::Num(RocNum::I64) => "int64_t".to_string(),
+        RocType::Num(RocNum::I128) => "struct RocI128".to_string(),
+        RocType::Num(RocNum::F32) => "float".to_string(),
+        RocType::Num(RocNum::F64) => "double".to_string(),
+        RocType::Num(RocNum::Dec) => "struct RocDec".to_string(),
+        RocType::RocList(_) => "struct RocList".to_string(),
+        // A Set is a Dict with empty values.
+        RocType::RocDict(_, _) | RocType::RocSet(_) => "struct RocDict".to_string(),
+        RocType::RocBox(elem_id) => {
+            let elem_type = type_name(*elem_id, types);
+
+            if elem_type.ends_with('*') {
+                format!("{elem_type}*")
+            } else {
+                format!("{elem_type} *")
+            }
+        }
+        RocType::RocResult(ok_id, err_id) => {
+            format!("union {}", result_name(*ok_id, *err_id, types))
+        }
+        RocType::TagUnion(RocTagUnion::Enumeration { name, .. }) => name.clone(),
+        RocType::TagUnion(RocTagUnion::NonRecursive { name, .. }) => format!("union {name}"),
+        RocType::Struct { name, .. }
+        | RocType::TagUnionPayload { name, .. }
+        | RocType::TagUnion(
+            RocTagUnion::Recursive { name, .. }
+            | RocTagUnion::NullableWrapped { name, .. }
+            | RocTagUnion::NullableUnwrapped { name, .. }
+            | RocTagUnion::NonNullableUnwrapped { name, .. }
+            | RocTagUnion::SingleTagStruct { name, .. },
+        ) => format!("struct {name}"),
+        RocType::RecursivePointer(content) => type_name(*content, types),
+        RocType::Function(roc_fn) => format!("struct {}", roc_fn.function_name),
+    }
+}
+
+/// e.g. `struct RocStr name;` - or, for a pointer, `struct Foo *name;`
+fn field_decl(label: &str, id: TypeId, types: &Types) -> String {
+    let type_str = type_name(id, types);
+    let separator = if type_str.ends_with('*') { "" } else { " " };
+
+    match types.get_type(id) {
+        // Lists are untyped in C, so note what's in them.
+        RocType::RocList(elem_id) => format!(
+            "{type_str}{separator}{label}; // elements: {}",
+            type_name(*elem_id, types)
+        ),
+        _ => format!("{type_str}{separator}{label};"),
+    }
+}
+
+/// Each combination of `ok` and `err` types gets its own declaration, e.g. `RocResult_Str_U64`
+pub(crate) fn result_name(ok_id: TypeId, err_id: TypeId, types: &Types) -> String {
+    format!(
+        "RocResult_{}_{}",
+        type_suffix(ok_id, types),
+        type_suffix(err_id, types)
+    )
+}
+
+fn type_suffix(id: TypeId, types: &Types) -> String {
+    match types.get_type(id) {
+        RocType::Unit => "Unit".to_string(),
+        RocType::EmptyTagUnion => "Never".to_string(),
+        RocType::RocStr => "Str".to_string(),
+        RocType::Bool => "Bool".to_string(),
+        RocType::Num(num) => format!("{num:?}"),
+        RocType::RocList(elem_id) => format!("List_{}", type_suffix(*elem_id, types)),
+        RocType::RocSet(elem_id) => format!("Set_{}", type_suffix(*elem_id, types)),
+        RocType::RocBox(elem_id) => format!("Box_{}", type_suffix(*elem_id, types)),
+        RocType::RocDict(key_id, val_id) => format!(
+            "Dict_{}_{}",
+            type_suffix(*key_id, types),
+            type_suffix(*val_id, types)
+        ),
+        RocType::RocResult(ok_id, err_id) => format!(
+            "Result_{}_{}",
+            type_suffix(*ok_id, types),
+            type_suffix(*err_id, types)
+        ),
+        RocType::RecursivePointer(content) => type_suffix(*content, types),
+        RocType::Struct { name, .. }
+        | RocType::TagUnionPayload { name, .. }
+        | RocType::Function(RocFn {
+            function_name: name,
+            ..
+        })
+        | RocType::TagUnion(
+            RocTagUnion::Enumeration { name, .. }
+            | RocTagUnion::NonRecursive { name, .. }
+            | RocTagUnion::Recursive { name, .. }
+            | RocTagUnion::NullableWrapped { name, .. }
+            | RocTagUnion::NullableUnwrapped { name, .. }
+            | RocTagUnion::NonNullableUnwrapped { name, .. }
+            | RocTagUnion::SingleTagStruct { name, .. },
+        ) => name.clone(),
+    }
+}
+
+/// A Result is laid out like any other non-recursive tag union: the discriminant
+/// comes after the larger of the two payloads.
+pub(crate) fn result_discriminant_offset(ok_id: TypeId, err_id: TypeId, types: &Types) -> u32 {
+    let size = types
+        .size_ignoring_alignment(ok_id)
+        .max(types.size_ignoring_alignment(err_id));
+    let align = types.align(ok_id).max(types.align(err_id));
+
+    round_up_to_alignment(size, align)
+}
+
+/// This mirrors how the compiler decides whether a recursive tag union's
+/// discriminant goes in the pointer's unused low bits, or in the heap allocation.
+pub(crate) fn stores_discriminant_in_pointer(stored_tags: usize, target_info: TargetInfo) -> bool {
+    stored_tags < target_info.ptr_width() as usize
+}
+
+fn unsigned_int(size: u32) -> &'static str {
+    match size {
+        1 => "uint8_t",
+        2 => "uint16_t",
+        4 => "uint32_t",
+        8 => "uint64_t",
+        _ => unreachable!("Invalid discriminant size {}", size),
+    }
+}
+
+fn arch_to_macro(architecture: Architecture) -> &'static str {
+    match architecture {
+        Architecture::X86_64 => "__x86_64__",
+        Architecture::X86_32 => "__i386__",
+        Architecture::Aarch64 => "__aarch64__",
+        Architecture::Aarch32 => "__arm__",
+        Architecture::Wasm32 => "__wasm32__",
+    }
+}
+
+const RESERVED_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long",
+    "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "true", "typedef", "union", "unsigned", "void", "volatile", "while",
+];
+
+/// Escape a C reserved keyword, if necessary, by adding an underscore after it.
+fn escape_kw(input: &str) -> String {
+    if RESERVED_KEYWORDS.contains(&input) {
+        format!("{input}_")
+    } else {
+        input.to_string()
+    }
+}
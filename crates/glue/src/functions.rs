@@ -0,0 +1,42 @@
+use roc_collections::MutMap;
+use roc_types::subs::Variable;
+
+#[derive(Copy, Clone, Debug, Default)]
+struct FunctionId(u64);
+
+impl FunctionId {
+    pub fn to_name(self) -> String {
+        format!("F{}", self.0)
+    }
+}
+
+/// Whenever we register a new Roc function type which wasn't given
+/// a name with `as`, give it a unique and short name (e.g. F1, F2, F3...)
+/// and then from then on, whenever we ask for that
+/// same function type, return the same name.
+#[derive(Default)]
+pub struct Functions {
+    by_variable: MutMap<Variable, FunctionId>,
+    next_id: FunctionId,
+}
+
+impl Functions {
+    pub fn get_name(&mut self, var: Variable) -> String {
+        match self.by_variable.get(&var) {
+            Some(function_id) => function_id.to_name(),
+            None => {
+                let function_id = self.next_id();
+
+                self.by_variable.insert(var, function_id);
+
+                function_id.to_name()
+            }
+        }
+    }
+
+    fn next_id(&mut self) -> FunctionId {
+        self.next_id.0 += 1;
+
+        self.next_id
+    }
+}
@@ -1,11 +1,12 @@
-use crate::rust_glue;
-use crate::types::{Env, Types};
+use crate::types::{Env, ExposedDef, Types};
+use crate::{c_glue, rust_glue, zig_glue};
 use bumpalo::Bump;
 use roc_intern::GlobalInterner;
-use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
+use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, OptLevel, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
 use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
 use roc_target::{Architecture, OperatingSystem, TargetInfo};
+use std::ffi::OsStr;
 use std::fs::File;
 use std::io::{self, ErrorKind, Write};
 use std::path::{Path, PathBuf};
@@ -21,6 +22,25 @@ impl IgnoreErrors {
     const NONE: Self = IgnoreErrors { can: false };
 }
 
+/// The languages `roc glue` can generate, chosen by the output file's extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    C,
+    Zig,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("rs") => Some(Language::Rust),
+            Some("h") => Some(Language::C),
+            Some("zig") => Some(Language::Zig),
+            _ => None,
+        }
+    }
+}
+
 pub fn generate(input_path: &Path, output_path: &Path) -> io::Result<i32> {
     match load_types(
         input_path.to_path_buf(),
@@ -38,8 +58,14 @@ pub fn generate(input_path: &Path, output_path: &Path) -> io::Result<i32> {
                 process::exit(1);
             });
 
-            let mut buf = std::str::from_utf8(rust_glue::HEADER).unwrap().to_string();
-            let body = rust_glue::emit(&types_and_targets);
+            let (header, body) = match Language::from_path(output_path) {
+                Some(Language::C) => (c_glue::HEADER, c_glue::emit(&types_and_targets)),
+                Some(Language::Zig) => (zig_glue::HEADER, zig_glue::emit(&types_and_targets)),
+                Some(Language::Rust) | None => {
+                    (rust_glue::HEADER, rust_glue::emit(&types_and_targets))
+                }
+            };
+            let mut buf = std::str::from_utf8(header).unwrap().to_string();
 
             buf.push_str(&body);
 
@@ -104,6 +130,7 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            opt_level: OptLevel::Normal,
         },
     )
     .unwrap_or_else(|problem| match problem {
@@ -131,25 +158,43 @@ pub fn load_types(
         );
     }
 
-    let variables = (0..decls.len()).filter_map(|index| {
+    let defs = (0..decls.len()).filter_map(|index| {
         use roc_can::expr::DeclarationTag::*;
 
-        match decls.declarations[index] {
-            Value | Function(_) | Recursive(_) | TailRecursive(_) => Some(decls.variables[index]),
+        let is_function = match decls.declarations[index] {
+            Value => false,
+            Function(_) | Recursive(_) | TailRecursive(_) => true,
             Destructure(_) => {
                 // figure out if we need to export non-identifier defs - when would that
                 // happen?
-                None
+                return None;
             }
             MutualRecursion { .. } => {
                 // handled by future iterations
-                None
+                return None;
             }
             Expectation | ExpectationFx => {
                 // not publicly visible
-                None
+                return None;
             }
-        }
+        };
+
+        let host_exposed_aliases = match &decls.annotations[index] {
+            Some(annotation) => annotation
+                .introduced_variables
+                .host_exposed_aliases
+                .keys()
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Some(ExposedDef {
+            symbol: decls.symbols[index].value,
+            var: decls.variables[index],
+            is_function,
+            host_exposed_aliases,
+        })
     });
 
     let layout_interner = GlobalInterner::with_capacity(128);
@@ -165,7 +210,7 @@ pub fn load_types(
         let types = {
             let mut env = Env::new(arena, subs, &interns, layout_interner.fork(), target_info);
 
-            env.vars_to_types(variables.clone())
+            env.exposed_to_types(defs.clone())
         };
 
         types_and_targets.push((types, target_info));
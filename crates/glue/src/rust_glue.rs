@@ -1,4 +1,4 @@
-use crate::types::{RocNum, RocTagUnion, RocType, TypeId, Types};
+use crate::types::{RocFn, RocNum, RocTagUnion, RocType, TypeId, Types};
 use indexmap::IndexMap;
 use roc_target::{Architecture, TargetInfo};
 use std::fmt::{Display, Write};
@@ -294,9 +294,7 @@ fn add_type(target_info: TargetInfo, id: TypeId, types: &Types, impls: &mut Impl
             // This is recursively pointing to a type that should already have been added,
             // so no extra work needs to happen.
         }
-        RocType::Function { .. } => {
-            // TODO actually generate glue functions!
-        }
+        RocType::Function(roc_fn) => add_function(target_info, roc_fn, id, types, impls),
     }
 }
 
@@ -946,7 +944,8 @@ pub struct {name} {{
                     | RocType::RocBox(_)
                     | RocType::TagUnion(_)
                     | RocType::RocResult(_, _)
-                    | RocType::RecursivePointer { .. } => {
+                    | RocType::RecursivePointer { .. }
+                    | RocType::Function(_) => {
                         owned_ret_type = type_name(*payload_id, types);
                         borrowed_ret_type = format!("&{}", owned_ret_type);
                         owned_ret = "payload".to_string();
@@ -980,7 +979,6 @@ pub struct {name} {{
                         payload_args = answer.payload_args;
                         args_to_payload = answer.args_to_payload;
                     }
-                    RocType::Function { .. } => todo!(),
                 };
 
                 {
@@ -1573,7 +1571,8 @@ pub struct {name} {{
                             | RocType::TagUnion(_)
                             | RocType::RocResult(_, _)
                             | RocType::Struct { .. }
-                            | RocType::RecursivePointer { .. } => {
+                            | RocType::RecursivePointer { .. }
+                            | RocType::Function(_) => {
                                 format!(".field({deref_str}{actual_self}.{tag_name})")
                             }
                             RocType::TagUnionPayload { fields, .. } => {
@@ -1588,7 +1587,6 @@ pub struct {name} {{
 
                                 buf.join("\n")
                             }
-                            RocType::Function { .. } => todo!(),
                         };
 
                         format!(
@@ -1719,6 +1717,152 @@ fn add_struct<S: Display>(
     add_decl(impls, None, target_info, buf);
 }
 
+fn add_function(
+    target_info: TargetInfo,
+    roc_fn: &RocFn,
+    fn_id: TypeId,
+    types: &Types,
+    impls: &mut Impls,
+) {
+    let RocFn {
+        function_name,
+        extern_name,
+        args,
+        ret,
+        is_toplevel,
+    } = roc_fn;
+    let name = escape_kw(function_name.clone());
+    let ret_type_str = type_name(*ret, types);
+
+    if *is_toplevel {
+        // Top-level functions capture nothing, so they get a plain Rust function
+        // rather than a type. The generic wrapper the compiler exposes takes the
+        // output pointer first, then Str and List arguments by pointer and
+        // everything else by value.
+        let extern_name = extern_name
+            .as_ref()
+            .expect("Top-level functions are always exposed to the host");
+        let mut params = Vec::with_capacity(args.len());
+        let mut extern_params = vec![format!("output: *mut {ret_type_str}")];
+        let mut extern_args = vec!["output.as_mut_ptr()".to_string()];
+
+        for (index, arg_id) in args.iter().enumerate() {
+            let arg_type_str = type_name(*arg_id, types);
+
+            match types.get_type(*arg_id) {
+                RocType::RocStr | RocType::RocList(_) => {
+                    params.push(format!("arg{index}: &{arg_type_str}"));
+                    extern_params.push(format!("arg{index}: *const {arg_type_str}"));
+                }
+                _ => {
+                    params.push(format!("arg{index}: {arg_type_str}"));
+                    extern_params.push(format!("arg{index}: {arg_type_str}"));
+                }
+            }
+
+            extern_args.push(format!("arg{index}"));
+        }
+
+        let params = params.join(", ");
+        let extern_params = extern_params.join(", ");
+        let extern_args = extern_args.join(", ");
+
+        add_decl(
+            impls,
+            None,
+            target_info,
+            format!(
+                r#"pub fn {name}({params}) -> {ret_type_str} {{
+    extern "C" {{
+        #[link_name = "{extern_name}"]
+        fn caller({extern_params});
+    }}
+
+    let mut output = core::mem::MaybeUninit::uninit();
+
+    unsafe {{
+        caller({extern_args});
+
+        output.assume_init()
+    }}
+}}"#
+            ),
+        );
+
+        return;
+    }
+
+    // A closure is opaque to the host; all it can do is hold onto the
+    // environment the closure captured, and pass it back to Roc.
+    let size = types.size_ignoring_alignment(fn_id);
+    let align = types.align(fn_id).max(1);
+
+    add_decl(
+        impls,
+        None,
+        target_info,
+        format!(
+            r#"#[derive(Clone, Copy, Debug, Eq, Ord, Hash, PartialEq, PartialOrd)]
+#[repr(C, align({align}))]
+pub struct {name} {{
+    closure_data: [u8; {size}],
+}}"#
+        ),
+    );
+
+    // Closures which weren't named with `as` have no caller, so the host can't call them.
+    if let Some(extern_name) = extern_name {
+        let mut params = Vec::with_capacity(args.len());
+        let mut extern_params = Vec::with_capacity(args.len() + 2);
+        let mut extern_args = Vec::with_capacity(args.len() + 2);
+
+        // The caller takes every argument by pointer, then the captured environment,
+        // and writes the return value into the output pointer at the end.
+        for (index, arg_id) in args.iter().enumerate() {
+            let arg_type_str = type_name(*arg_id, types);
+
+            params.push(format!("arg{index}: &{arg_type_str}"));
+            extern_params.push(format!("arg{index}: *const {arg_type_str}"));
+            extern_args.push(format!("arg{index}"));
+        }
+
+        extern_params.push("closure_data: *const u8".to_string());
+        extern_params.push(format!("output: *mut {ret_type_str}"));
+        extern_args.push("self.closure_data.as_ptr()".to_string());
+        extern_args.push("output.as_mut_ptr()".to_string());
+
+        let params = params
+            .iter()
+            .map(|param| format!(", {param}"))
+            .collect::<String>();
+        let extern_params = extern_params.join(", ");
+        let extern_args = extern_args.join(", ");
+
+        add_decl(
+            impls,
+            Some(format!("impl {name}")),
+            target_info,
+            format!(
+                r#"/// Calls this closure with the environment it captured.
+    pub fn call(&self{params}) -> {ret_type_str} {{
+        extern "C" {{
+            #[link_name = "{extern_name}"]
+            fn caller({extern_params});
+        }}
+
+        let mut output = core::mem::MaybeUninit::uninit();
+
+        unsafe {{
+            caller({extern_args});
+
+            output.assume_init()
+        }}
+    }}"#
+            ),
+        );
+    }
+}
+
 fn type_name(id: TypeId, types: &Types) -> String {
     match types.get_type(id) {
         RocType::Unit => "()".to_string(),
@@ -1763,7 +1907,7 @@ fn type_name(id: TypeId, types: &Types) -> String {
         | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { name, .. })
         | RocType::TagUnion(RocTagUnion::SingleTagStruct { name, .. }) => escape_kw(name.clone()),
         RocType::RecursivePointer(content) => type_name(*content, types),
-        RocType::Function { name, .. } => escape_kw(name.clone()),
+        RocType::Function(roc_fn) => escape_kw(roc_fn.function_name.clone()),
     }
 }
 
@@ -1881,7 +2025,8 @@ pub struct {name} {{
             | RocType::RocBox(_)
             | RocType::RocResult(_, _)
             | RocType::TagUnion(_)
-            | RocType::RecursivePointer { .. } => {
+            | RocType::RecursivePointer { .. }
+            | RocType::Function(_) => {
                 owned_ret_type = type_name(non_null_payload, types);
                 borrowed_ret_type = format!("&{}", owned_ret_type);
                 payload_args = format!("arg: {owned_ret_type}");
@@ -1911,7 +2056,6 @@ pub struct {name} {{
                 owned_ret_type = answer.owned_ret_type;
                 borrowed_ret_type = answer.borrowed_ret_type;
             }
-            RocType::Function { .. } => todo!(),
         };
 
         // Add a convenience constructor function for the tag with the payload, e.g.
@@ -2130,7 +2274,8 @@ pub struct {name} {{
             | RocType::RocBox(_)
             | RocType::RocResult(_, _)
             | RocType::TagUnion(_)
-            | RocType::RecursivePointer { .. } => {
+            | RocType::RecursivePointer { .. }
+            | RocType::Function(_) => {
                 format!(
                     r#"f.debug_tuple("{non_null_tag}").field(&*{extra_deref}self.pointer).finish()"#
                 )
@@ -2154,7 +2299,6 @@ pub struct {name} {{
 
                 buf.join(&format!("\n{INDENT}{INDENT}{INDENT}{INDENT}{INDENT}"))
             }
-            RocType::Function { .. } => todo!(),
         };
 
         let body = format!(
@@ -2203,7 +2347,7 @@ fn max_pointer_tagged_variants(architecture: Architecture) -> usize {
 }
 
 #[inline(always)]
-fn tagged_pointer_bitmask(architecture: Architecture) -> u8 {
+pub(crate) fn tagged_pointer_bitmask(architecture: Architecture) -> u8 {
     match architecture {
         // On a 64-bit system, pointers have 3 bits that are unused
         Architecture::X86_64 | Architecture::Aarch64 => 0b0000_0111,
@@ -2361,7 +2505,7 @@ fn cannot_derive_default(roc_type: &RocType, types: &Types) -> bool {
         | RocType::TagUnion { .. }
         | RocType::RocResult(_, _)
         | RocType::RecursivePointer { .. }
-        | RocType::Function { .. } => true,
+        | RocType::Function(_) => true,
         RocType::RocStr | RocType::Bool | RocType::Num(_) => false,
         RocType::RocList(id) | RocType::RocSet(id) | RocType::RocBox(id) => {
             cannot_derive_default(types.get_type(*id), types)
@@ -2387,7 +2531,7 @@ fn cannot_derive_copy(roc_type: &RocType, types: &Types) -> bool {
         | RocType::Bool
         | RocType::Num(_)
         | RocType::TagUnion(RocTagUnion::Enumeration { .. })
-        | RocType::Function { .. } => false,
+        | RocType::Function(_) => false,
         RocType::RocStr
         | RocType::RocList(_)
         | RocType::RocDict(_, _)
@@ -2441,7 +2585,7 @@ fn has_float_help(roc_type: &RocType, types: &Types, do_not_recurse: &[TypeId])
         | RocType::RocStr
         | RocType::Bool
         | RocType::TagUnion(RocTagUnion::Enumeration { .. })
-        | RocType::Function { .. } => false,
+        | RocType::Function(_) => false,
         RocType::RocList(id) | RocType::RocSet(id) | RocType::RocBox(id) => {
             has_float_help(types.get_type(*id), types, do_not_recurse)
         }
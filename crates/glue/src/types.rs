@@ -1,4 +1,5 @@
 use crate::enums::Enums;
+use crate::functions::Functions;
 use crate::structs::Structs;
 use bumpalo::Bump;
 use fnv::FnvHashMap;
@@ -17,7 +18,9 @@ use roc_mono::layout::{
 };
 use roc_target::TargetInfo;
 use roc_types::{
-    subs::{Content, FlatType, GetSubsSlice, Subs, UnionLabels, UnionTags, Variable},
+    subs::{
+        Content, FlatType, GetSubsSlice, Subs, UnionLabels, UnionTags, Variable, VariableSubsSlice,
+    },
     types::{AliasKind, RecordField},
 };
 use std::fmt::Display;
@@ -296,28 +299,19 @@ impl Types {
                     false
                 }
             }
-            (
-                Function {
-                    name: name_a,
-                    args: args_a,
-                    ret: ret_a,
-                },
-                Function {
-                    name: name_b,
-                    args: args_b,
-                    ret: ret_b,
-                },
-            ) => {
+            (Function(fn_a), Function(fn_b)) => {
                 // for functions, the name is actually important because two functions
                 // with the same type could have completely different implementations!
-                if name_a == name_b
-                    && args_a.len() == args_b.len()
+                if fn_a.function_name == fn_b.function_name
+                    && fn_a.extern_name == fn_b.extern_name
+                    && fn_a.is_toplevel == fn_b.is_toplevel
+                    && fn_a.args.len() == fn_b.args.len()
                     && self.is_equivalent_help(
-                        self.get_type_or_pending(*ret_a),
-                        self.get_type_or_pending(*ret_b),
+                        self.get_type_or_pending(fn_a.ret),
+                        self.get_type_or_pending(fn_b.ret),
                     )
                 {
-                    args_a.iter().zip(args_b.iter()).all(|(id_a, id_b)| {
+                    fn_a.args.iter().zip(fn_b.args.iter()).all(|(id_a, id_b)| {
                         self.is_equivalent_help(
                             self.get_type_or_pending(*id_a),
                             self.get_type_or_pending(*id_b),
@@ -355,8 +349,8 @@ impl Types {
             | (_, TagUnionPayload { .. })
             | (RecursivePointer(_), _)
             | (_, RecursivePointer(_))
-            | (Function { .. }, _)
-            | (_, Function { .. }) => false,
+            | (Function(_), _)
+            | (_, Function(_)) => false,
         }
     }
 
@@ -521,15 +515,29 @@ pub enum RocType {
     /// this would be the field of Cons containing the (recursive) StrConsList type,
     /// and the TypeId is the TypeId of StrConsList itself.
     RecursivePointer(TypeId),
-    Function {
-        name: String,
-        args: Vec<TypeId>,
-        ret: TypeId,
-    },
+    /// A Roc function. Its size and alignment are those of the environment
+    /// the function captured, which is what the host passes back to Roc
+    /// when calling it (top-level functions capture nothing).
+    Function(RocFn),
     /// A zero-sized type, such as an empty record or a single-tag union with no payload
     Unit,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RocFn {
+    /// e.g. `Fx` in `mainForHost : Task {} [] as Fx`, or the name of a top-level function
+    pub function_name: String,
+    /// The symbol the compiler generates for the host to call this function with,
+    /// e.g. `roc__mainForHost_1__Fx_caller`. Closures which weren't named with `as`
+    /// don't get one, so the host can only pass them back to Roc.
+    pub extern_name: Option<String>,
+    pub args: Vec<TypeId>,
+    pub ret: TypeId,
+    /// Top-level functions are called with just their arguments, whereas closures
+    /// also need a pointer to their captured environment.
+    pub is_toplevel: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
 pub enum RocNum {
     I8,
@@ -657,6 +665,19 @@ pub enum RocTagUnion {
     },
 }
 
+/// A definition the platform provides to the host, e.g. `mainForHost`
+#[derive(Debug, Clone)]
+pub struct ExposedDef {
+    pub symbol: Symbol,
+    pub var: Variable,
+    /// True if this is defined as a function, e.g. `mainForHost = \arg -> ...`,
+    /// rather than as a value (which may itself be a closure the host calls later).
+    pub is_function: bool,
+    /// The aliases introduced with `as` in this definition's annotation,
+    /// e.g. `Fx` in `mainForHost : Task {} [] as Fx`
+    pub host_exposed_aliases: Vec<Symbol>,
+}
+
 pub struct Env<'a> {
     arena: &'a Bump,
     subs: &'a Subs,
@@ -664,6 +685,11 @@ pub struct Env<'a> {
     interns: &'a Interns,
     struct_names: Structs,
     enum_names: Enums,
+    function_names: Functions,
+    /// The aliases which the compiler generates host-facing callers for,
+    /// e.g. `Fx` in `mainForHost : Task {} [] as Fx`, along with the name
+    /// of the definition whose annotation introduced them.
+    host_exposed_aliases: VecMap<Symbol, String>,
     pending_recursive_types: VecMap<TypeId, Variable>,
     known_recursive_types: VecMap<Variable, TypeId>,
     target: TargetInfo,
@@ -683,6 +709,8 @@ impl<'a> Env<'a> {
             interns,
             struct_names: Default::default(),
             enum_names: Default::default(),
+            function_names: Default::default(),
+            host_exposed_aliases: Default::default(),
             pending_recursive_types: Default::default(),
             known_recursive_types: Default::default(),
             layout_cache: LayoutCache::new(layout_interner, target),
@@ -705,6 +733,61 @@ impl<'a> Env<'a> {
         types
     }
 
+    pub fn exposed_to_types<I>(&mut self, defs: I) -> Types
+    where
+        I: Iterator<Item = ExposedDef>,
+    {
+        let mut types = Types::with_capacity(defs.size_hint().0, self.target);
+
+        for def in defs {
+            let def_name = def.symbol.as_str(self.interns).to_string();
+
+            for alias in def.host_exposed_aliases {
+                self.host_exposed_aliases.insert(alias, def_name.clone());
+            }
+
+            if def.is_function {
+                self.add_toplevel_function(def_name, def.var, &mut types);
+            } else {
+                self.add_type(def.var, &mut types);
+            }
+        }
+
+        self.resolve_pending_recursive_types(&mut types);
+
+        types
+    }
+
+    fn add_toplevel_function(&mut self, name: String, var: Variable, types: &mut Types) -> TypeId {
+        let layout = self
+            .layout_cache
+            .from_var(self.arena, var, self.subs)
+            .expect("Something weird ended up in the content");
+
+        match self
+            .subs
+            .get_content_without_compacting(unwrap_aliases(self.subs, var))
+        {
+            Content::Structure(FlatType::Func(args, _, ret_var)) => {
+                // Top-level functions are exposed through a generic wrapper,
+                // which writes the return value into a pointer passed as the first argument.
+                let extern_name = format!("roc__{name}_1_exposed_generic");
+
+                add_function(
+                    self,
+                    layout,
+                    *args,
+                    *ret_var,
+                    name,
+                    Some(extern_name),
+                    true,
+                    types,
+                )
+            }
+            _ => add_type_help(self, layout, var, None, types),
+        }
+    }
+
     fn add_type(&mut self, var: Variable, types: &mut Types) -> TypeId {
         roc_tracing::debug!(content=?roc_types::subs::SubsFmtContent(self.subs.get_content_without_compacting(var), self.subs), "adding type");
 
@@ -787,6 +870,21 @@ fn add_type_help<'a>(
                 RocType::Struct { name, fields }
             })
         }
+        Content::Structure(FlatType::Tuple(elems, ext)) => {
+            let it = elems
+                .unsorted_iterator(subs, *ext)
+                .expect("something weird in content");
+
+            let name = match opt_name {
+                Some(sym) => sym.as_str(env.interns).to_string(),
+                None => env.struct_names.get_name(var),
+            };
+
+            // Tuple elements are positional, just like the fields of a tag payload.
+            add_struct(env, name, it, types, layout, |name, fields| {
+                RocType::TagUnionPayload { name, fields }
+            })
+        }
         Content::Structure(FlatType::TagUnion(tags, ext_var)) => {
             debug_assert!(ext_var_is_empty_tag_union(subs, *ext_var));
 
@@ -799,10 +897,25 @@ fn add_type_help<'a>(
 
             add_tag_union(env, opt_name, tags, var, types, layout, Some(rec_root))
         }
-        Content::Structure(FlatType::Apply(symbol, _)) => match layout {
+        Content::Structure(FlatType::Apply(symbol, args)) => match layout {
             Layout::Builtin(builtin) => {
                 add_builtin_type(env, builtin, var, opt_name, types, layout)
             }
+            Layout::Boxed(elem_layout) if *symbol == Symbol::BOX_BOX_TYPE => {
+                let args = env.subs.get_subs_slice(*args);
+                debug_assert_eq!(args.len(), 1);
+
+                let elem_id = add_type_help(env, *elem_layout, args[0], None, types);
+                let box_id = types.add_anonymous(
+                    &env.layout_cache.interner,
+                    RocType::RocBox(elem_id),
+                    layout,
+                );
+
+                types.depends(box_id, elem_id);
+
+                box_id
+            }
             _ => {
                 if symbol.is_builtin() {
                     todo!(
@@ -819,52 +932,18 @@ fn add_type_help<'a>(
                 }
             }
         },
-        Content::Structure(FlatType::Func(args, closure_var, ret_var)) => {
-            let args = env.subs.get_subs_slice(*args);
-            let mut arg_type_ids = Vec::with_capacity(args.len());
-
-            for arg_var in args {
-                let arg_layout = env
-                    .layout_cache
-                    .from_var(env.arena, *arg_var, env.subs)
-                    .expect("Something weird ended up in the content");
-
-                arg_type_ids.push(add_type_help(env, arg_layout, *arg_var, None, types));
-            }
-
-            let ret_type_id = {
-                let ret_layout = env
-                    .layout_cache
-                    .from_var(env.arena, *ret_var, env.subs)
-                    .expect("Something weird ended up in the content");
-
-                add_type_help(env, ret_layout, *ret_var, None, types)
+        Content::Structure(FlatType::Func(args, _closure_var, ret_var)) => {
+            let name = match opt_name {
+                Some(sym) => sym.as_str(env.interns).to_string(),
+                None => env.function_names.get_name(var),
             };
 
-            let name = format!("TODO_roc_function_{:?}", closure_var);
-            let fn_type_id = types.add_named(
-                &env.layout_cache.interner,
-                name.clone(),
-                RocType::Function {
-                    name,
-                    args: arg_type_ids.clone(),
-                    ret: ret_type_id,
-                },
-                layout,
-            );
-
-            types.depends(fn_type_id, ret_type_id);
-
-            for arg_type_id in arg_type_ids {
-                types.depends(fn_type_id, arg_type_id);
-            }
-
-            fn_type_id
+            add_function(env, layout, *args, *ret_var, name, None, false, types)
         }
         Content::Structure(FlatType::FunctionOrTagUnion(_, _, _)) => {
             todo!()
         }
-        Content::Structure(FlatType::EmptyRecord) => {
+        Content::Structure(FlatType::EmptyRecord | FlatType::EmptyTuple) => {
             types.add_anonymous(&env.layout_cache.interner, RocType::Unit, layout)
         }
         Content::Structure(FlatType::EmptyTagUnion) => {
@@ -979,6 +1058,29 @@ fn add_type_help<'a>(
                         unreachable!()
                     }
                 }
+            } else if let Some(def_name) = env.host_exposed_aliases.get(name) {
+                // This alias was introduced with `as`, so if it's a function,
+                // the compiler generated a caller the host can invoke it through.
+                let extern_name = format!(
+                    "roc__{}_1_{}_{}_caller",
+                    def_name,
+                    name.module_string(env.interns),
+                    name.as_str(env.interns)
+                );
+
+                match subs.get_content_without_compacting(unwrap_aliases(subs, *real_var)) {
+                    Content::Structure(FlatType::Func(args, _, ret_var)) => add_function(
+                        env,
+                        layout,
+                        *args,
+                        *ret_var,
+                        name.as_str(env.interns).to_string(),
+                        Some(extern_name),
+                        false,
+                        types,
+                    ),
+                    _ => add_type_help(env, layout, *real_var, Some(*name), types),
+                }
             } else {
                 // If this was a non-builtin type alias, we can use that alias name
                 // in the generated bindings.
@@ -1008,10 +1110,79 @@ fn add_type_help<'a>(
 
             type_id
         }
-        Content::LambdaSet(_) => todo!(),
+        Content::LambdaSet(_) => {
+            // A lambda set is only ever reached through the closure variable of a
+            // function type, and add_function accounts for it using the function's layout.
+            unreachable!("Lambda sets should be handled by the function types containing them")
+        }
     }
 }
 
+/// The layout of a function type is that of its lambda set, so the size and alignment
+/// recorded here are those of the closure's captured environment.
+#[allow(clippy::too_many_arguments)]
+fn add_function<'a>(
+    env: &mut Env<'a>,
+    layout: Layout<'a>,
+    args: VariableSubsSlice,
+    ret_var: Variable,
+    function_name: String,
+    extern_name: Option<String>,
+    is_toplevel: bool,
+    types: &mut Types,
+) -> TypeId {
+    let args = env.subs.get_subs_slice(args);
+    let mut arg_type_ids = Vec::with_capacity(args.len());
+
+    for arg_var in args {
+        let arg_layout = env
+            .layout_cache
+            .from_var(env.arena, *arg_var, env.subs)
+            .expect("Something weird ended up in the content");
+
+        arg_type_ids.push(add_type_help(env, arg_layout, *arg_var, None, types));
+    }
+
+    let ret_type_id = {
+        let ret_layout = env
+            .layout_cache
+            .from_var(env.arena, ret_var, env.subs)
+            .expect("Something weird ended up in the content");
+
+        add_type_help(env, ret_layout, ret_var, None, types)
+    };
+
+    let fn_type_id = types.add_named(
+        &env.layout_cache.interner,
+        function_name.clone(),
+        RocType::Function(RocFn {
+            function_name,
+            extern_name,
+            args: arg_type_ids.clone(),
+            ret: ret_type_id,
+            is_toplevel,
+        }),
+        layout,
+    );
+
+    types.depends(fn_type_id, ret_type_id);
+
+    for arg_type_id in arg_type_ids {
+        types.depends(fn_type_id, arg_type_id);
+    }
+
+    fn_type_id
+}
+
+/// Follows type aliases (including opaque ones, like `Task`) to the type they stand for.
+fn unwrap_aliases(subs: &Subs, mut var: Variable) -> Variable {
+    while let Content::Alias(_, _, real_var, _) = subs.get_content_without_compacting(var) {
+        var = *real_var;
+    }
+
+    var
+}
+
 fn add_builtin_type<'a>(
     env: &mut Env<'a>,
     builtin: Builtin<'a>,
@@ -1465,7 +1636,15 @@ fn add_tag_union<'a>(
             }
         }
         Layout::LambdaSet(_) => {
-            todo!();
+            // e.g. `[Callback (Str -> Str)]`
+            let (tag_name, payload_fields) =
+                single_tag_payload_fields(union_tags, subs, &[layout], env, types);
+
+            RocTagUnion::SingleTagStruct {
+                name: name.clone(),
+                tag_name: tag_name.to_string(),
+                payload_fields,
+            }
         }
         Layout::RecursivePointer => {
             // A single-tag union which only wraps itself is erroneous and should have
@@ -0,0 +1,675 @@
+use crate::c_glue::{result_discriminant_offset, result_name, stores_discriminant_in_pointer};
+use crate::rust_glue::tagged_pointer_bitmask;
+use crate::types::{RocFn, RocNum, RocTagUnion, RocType, TypeId, Types};
+use indexmap::IndexMap;
+use roc_target::{Architecture, TargetInfo};
+use std::fmt::Write;
+
+pub static HEADER: &[u8] = include_bytes!("../templates/header.zig");
+const INDENT: &str = "    ";
+
+pub fn emit(types_and_targets: &[(Types, TargetInfo)]) -> String {
+    // Each target gets its own complete set of declarations, and the one for the
+    // target being compiled is brought into scope with `usingnamespace`.
+    // Targets whose layouts turn out identical (e.g. the 64-bit ones) share a set.
+    let mut bodies: IndexMap<String, Vec<TargetInfo>> = IndexMap::default();
+
+    for (types, target_info) in types_and_targets {
+        bodies
+            .entry(emit_target(types, *target_info))
+            .or_default()
+            .push(*target_info);
+    }
+
+    let mut buf = String::new();
+
+    if bodies.keys().all(String::is_empty) {
+        return buf;
+    }
+
+    buf.push_str("\npub usingnamespace switch (builtin.cpu.arch) {\n");
+
+    for (body, targets) in bodies.iter() {
+        let arches = targets
+            .iter()
+            .map(|target_info| format!(".{}", arch_to_str(target_info.architecture)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(buf, "{INDENT}{arches} => struct {{").unwrap();
+
+        // Indent the body so it sits inside the struct
+        for line in body.trim_start_matches('\n').lines() {
+            if !line.is_empty() {
+                buf.push_str(INDENT);
+                buf.push_str(INDENT);
+                buf.push_str(line);
+            }
+
+            buf.push('\n');
+        }
+
+        writeln!(buf, "{INDENT}}},").unwrap();
+    }
+
+    writeln!(
+        buf,
+        "{INDENT}else => @compileError(\"Roc glue was not generated for this target architecture.\"),\n}};"
+    )
+    .unwrap();
+
+    buf
+}
+
+fn emit_target(types: &Types, target_info: TargetInfo) -> String {
+    let mut buf = String::new();
+
+    // Unlike in C, Zig declarations can refer to each other in any order.
+    for id in types.sorted_ids() {
+        add_type(target_info, id, types, &mut buf);
+    }
+
+    buf
+}
+
+fn add_type(target_info: TargetInfo, id: TypeId, types: &Types, buf: &mut String) {
+    match types.get_type(id) {
+        RocType::Struct { name, fields } => {
+            let fields = fields
+                .iter()
+                .map(|(label, field_id)| (escape_kw(label), *field_id));
+
+            add_struct(name, fields, id, false, types, buf);
+        }
+        RocType::TagUnionPayload { name, fields } => {
+            // Tag union payloads have numbered fields, so we prefix them with an "f"
+            // so they can be accessed without @"" syntax.
+            let fields = fields
+                .iter()
+                .map(|(index, field_id)| (format!("f{index}"), *field_id));
+
+            add_struct(name, fields, id, true, types, buf);
+        }
+        RocType::TagUnion(RocTagUnion::SingleTagStruct {
+            name,
+            payload_fields,
+            ..
+        }) => {
+            let fields = payload_fields
+                .iter()
+                .enumerate()
+                .map(|(index, field_id)| (format!("f{index}"), *field_id));
+
+            add_struct(name, fields, id, false, types, buf);
+        }
+        RocType::TagUnion(RocTagUnion::Enumeration { name, tags, size }) => {
+            add_enumeration(name, tags, *size, buf);
+        }
+        RocType::TagUnion(RocTagUnion::NonRecursive {
+            name,
+            tags,
+            discriminant_size,
+            discriminant_offset,
+        }) => {
+            // Empty tag unions can never come up at runtime,
+            // and so don't need declared types.
+            if !tags.is_empty() {
+                add_tag_union(
+                    id,
+                    name,
+                    tags,
+                    *discriminant_size,
+                    *discriminant_offset,
+                    types,
+                    buf,
+                );
+            }
+        }
+        RocType::TagUnion(RocTagUnion::Recursive {
+            name,
+            tags,
+            discriminant_size,
+            discriminant_offset,
+        }) => add_recursive_tag_union(
+            target_info,
+            name,
+            tags,
+            None,
+            *discriminant_size,
+            *discriminant_offset,
+            types,
+            buf,
+        ),
+        RocType::TagUnion(RocTagUnion::NullableWrapped {
+            name,
+            index_of_null_tag,
+            tags,
+            discriminant_size,
+            discriminant_offset,
+        }) => add_recursive_tag_union(
+            target_info,
+            name,
+            tags,
+            Some(*index_of_null_tag as usize),
+            *discriminant_size,
+            *discriminant_offset,
+            types,
+            buf,
+        ),
+        RocType::TagUnion(RocTagUnion::NullableUnwrapped {
+            name,
+            null_tag,
+            non_null_payload,
+            ..
+        }) => {
+            let payload_type = type_name(*non_null_payload, types);
+
+            write!(
+                buf,
+                r#"
+pub const {name} = extern struct {{
+    pointer: ?*{payload_type},
+
+    pub fn is{null_tag}(self: {name}) bool {{
+        return self.pointer == null;
+    }}
+}};
+"#
+            )
+            .unwrap();
+        }
+        RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { name, payload, .. }) => {
+            let payload_type = type_name(*payload, types);
+
+            write!(
+                buf,
+                "\npub const {name} = extern struct {{\n{INDENT}pointer: *{payload_type},\n}};\n"
+            )
+            .unwrap();
+        }
+        RocType::RocResult(ok_id, err_id) => add_result(id, *ok_id, *err_id, types, buf),
+        // These are declared in the header, or don't need declarations.
+        RocType::Unit
+        | RocType::EmptyTagUnion
+        | RocType::Num(_)
+        | RocType::Bool
+        | RocType::RocStr
+        | RocType::RocDict(_, _)
+        | RocType::RocSet(_)
+        | RocType::RocList(_)
+        | RocType::RocBox(_)
+        | RocType::RecursivePointer(_) => {}
+        RocType::Function(roc_fn) => add_function(id, roc_fn, types, buf),
+    }
+}
+
+fn add_struct<I: Iterator<Item = (String, TypeId)>>(
+    name: &str,
+    fields: I,
+    struct_id: TypeId,
+    is_tag_union_payload: bool,
+    types: &Types,
+    buf: &mut String,
+) {
+    write!(buf, "\npub const {name} = extern struct {{\n").unwrap();
+
+    for (label, field_id) in fields {
+        // Zero-sized fields (like empty records) take up no space in Roc,
+        // and extern structs can't contain them, so leave them out.
+        if types.size_ignoring_alignment(field_id) > 0 {
+            writeln!(buf, "{INDENT}{},", field_decl(&label, field_id, types)).unwrap();
+        }
+    }
+
+    buf.push_str("};\n");
+
+    // Types records a payload with its tag union's layout, so only check other structs.
+    if !is_tag_union_payload {
+        add_layout_asserts(name, struct_id, types, buf);
+    }
+}
+
+fn add_function(fn_id: TypeId, roc_fn: &RocFn, types: &Types, buf: &mut String) {
+    let RocFn {
+        function_name: name,
+        extern_name,
+        args,
+        ret,
+        is_toplevel,
+    } = roc_fn;
+    let returns_void = types.size_ignoring_alignment(*ret) == 0;
+    let (ret_type, output_param, output_arg) = if returns_void {
+        ("void".to_string(), "?*anyopaque".to_string(), "null")
+    } else {
+        let ret_type = type_name(*ret, types);
+        let output_param = format!("*{ret_type}");
+
+        (ret_type, output_param, "&output")
+    };
+    let mut params = Vec::with_capacity(args.len() + 1);
+    let mut extern_params = Vec::with_capacity(args.len() + 2);
+    let mut extern_args = Vec::with_capacity(args.len() + 2);
+    let has_closure_data = !*is_toplevel && types.size_ignoring_alignment(fn_id) > 0;
+    let indent;
+
+    if *is_toplevel {
+        // Top-level functions capture nothing, and are exposed through a generic
+        // wrapper which takes the output pointer first, then Str and List
+        // arguments by pointer and everything else by value.
+        indent = "";
+        extern_params.push(format!("output: {output_param}"));
+        extern_args.push(output_arg.to_string());
+
+        for (index, arg_id) in args.iter().enumerate() {
+            let arg_type = type_name(*arg_id, types);
+            let param = match types.get_type(*arg_id) {
+                RocType::RocStr | RocType::RocList(_) => format!("arg{index}: *const {arg_type}"),
+                // Extern functions can't take zero-sized arguments, so these are left out.
+                _ if types.size_ignoring_alignment(*arg_id) == 0 => continue,
+                _ => format!("arg{index}: {arg_type}"),
+            };
+
+            params.push(param.clone());
+            extern_params.push(param);
+            extern_args.push(format!("arg{index}"));
+        }
+
+        buf.push('\n');
+    } else {
+        // A closure is opaque to the host; all it can do is hold onto the
+        // environment the closure captured, and pass it back to Roc.
+        indent = INDENT;
+        write!(buf, "\npub const {name} = extern struct {{\n").unwrap();
+
+        if has_closure_data {
+            let size = types.size_ignoring_alignment(fn_id);
+            let align = types.align(fn_id);
+
+            writeln!(buf, "{INDENT}closure_data: [{size}]u8 align({align}),").unwrap();
+        }
+
+        // The caller takes every argument by pointer, then the captured
+        // environment, and writes the return value into the output pointer at the end.
+        params.push(format!("self: *const {name}"));
+
+        for (index, arg_id) in args.iter().enumerate() {
+            // Pointers to zero-sized types are themselves zero-sized in Zig,
+            // so those are passed as null instead.
+            if types.size_ignoring_alignment(*arg_id) == 0 {
+                extern_params.push(format!("arg{index}: ?*const anyopaque"));
+                extern_args.push("null".to_string());
+            } else {
+                let arg_type = type_name(*arg_id, types);
+
+                params.push(format!("arg{index}: *const {arg_type}"));
+                extern_params.push(format!("arg{index}: *const {arg_type}"));
+                extern_args.push(format!("arg{index}"));
+            }
+        }
+
+        extern_params.push("closure_data: ?*const anyopaque".to_string());
+        extern_params.push(format!("output: {output_param}"));
+        extern_args.push(if has_closure_data { "self" } else { "null" }.to_string());
+        extern_args.push(output_arg.to_string());
+    }
+
+    // Closures which weren't named with `as` have no caller, so the host can't call them.
+    if let Some(extern_name) = extern_name {
+        let fn_name = if *is_toplevel { name.as_str() } else { "call" };
+        let params = params.join(", ");
+        let extern_params = extern_params.join(", ");
+        let extern_args = extern_args.join(", ");
+
+        if has_closure_data {
+            buf.push('\n');
+        }
+
+        write!(
+            buf,
+            "{indent}extern fn {extern_name}({extern_params}) void;\n\n{indent}pub fn {fn_name}({params}) {ret_type} {{\n"
+        )
+        .unwrap();
+
+        // Zig doesn't allow unused parameters.
+        if !*is_toplevel && !has_closure_data {
+            write!(buf, "{indent}{INDENT}_ = self;\n\n").unwrap();
+        }
+
+        if returns_void {
+            writeln!(buf, "{indent}{INDENT}{extern_name}({extern_args});").unwrap();
+        } else {
+            write!(
+                buf,
+                "{indent}{INDENT}var output: {ret_type} = undefined;\n\n{indent}{INDENT}{extern_name}({extern_args});\n\n{indent}{INDENT}return output;\n"
+            )
+            .unwrap();
+        }
+
+        writeln!(buf, "{indent}}}").unwrap();
+    }
+
+    if !*is_toplevel {
+        buf.push_str("};\n");
+        add_layout_asserts(name, fn_id, types, buf);
+    }
+}
+
+fn add_enumeration<S: AsRef<str>>(name: &str, tags: &[S], size: u32, buf: &mut String) {
+    let repr_bits = size * 8;
+
+    write!(buf, "\npub const {name} = enum(u{repr_bits}) {{\n").unwrap();
+
+    for (index, tag_name) in tags.iter().enumerate() {
+        writeln!(buf, "{INDENT}{} = {index},", tag_name.as_ref()).unwrap();
+    }
+
+    buf.push_str("};\n");
+}
+
+fn add_tag_union(
+    union_id: TypeId,
+    name: &str,
+    tags: &[(String, Option<TypeId>)],
+    discriminant_size: u32,
+    discriminant_offset: u32,
+    types: &Types,
+    buf: &mut String,
+) {
+    // Roc puts the discriminant right after the largest payload, which isn't
+    // necessarily where an extern struct would put it; so, like the C glue, the whole
+    // tag union is an extern union, and the discriminant is read at its offset.
+    let discriminant_name = format!("discriminant_{name}");
+    let tag_names: Vec<&str> = tags.iter().map(|(tag_name, _)| tag_name.as_str()).collect();
+    let size = types.size_rounded_to_alignment(union_id);
+    let repr_bits = discriminant_size * 8;
+
+    add_enumeration(&discriminant_name, &tag_names, discriminant_size, buf);
+
+    write!(buf, "\npub const {name} = extern union {{\n").unwrap();
+    add_union_members(tags, types, buf);
+    write!(
+        buf,
+        r#"    bytes: [{size}]u8,
+
+    pub fn discriminant(self: *const {name}) {discriminant_name} {{
+        return @intToEnum({discriminant_name}, @ptrCast(*align(1) const u{repr_bits}, &self.bytes[{discriminant_offset}]).*);
+    }}
+
+    pub fn setDiscriminant(self: *{name}, value: {discriminant_name}) void {{
+        @ptrCast(*align(1) u{repr_bits}, &self.bytes[{discriminant_offset}]).* = @enumToInt(value);
+    }}
+}};
+"#
+    )
+    .unwrap();
+    add_layout_asserts(name, union_id, types, buf);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_recursive_tag_union(
+    target_info: TargetInfo,
+    name: &str,
+    tags: &[(String, Option<TypeId>)],
+    null_tag_index: Option<usize>,
+    discriminant_size: u32,
+    discriminant_offset: u32,
+    types: &Types,
+    buf: &mut String,
+) {
+    let discriminant_name = format!("discriminant_{name}");
+    let tag_names: Vec<&str> = tags.iter().map(|(tag_name, _)| tag_name.as_str()).collect();
+    // The null tag isn't stored anywhere, so it doesn't count toward the tags
+    // that need to fit in the pointer's unused bits.
+    let stored_tags = tags.len() - null_tag_index.iter().count();
+    let in_pointer = stores_discriminant_in_pointer(stored_tags, target_info);
+    let discriminant_size = discriminant_size.max(1);
+    let repr_bits = discriminant_size * 8;
+
+    add_enumeration(&discriminant_name, &tag_names, discriminant_size, buf);
+
+    let null_check = match null_tag_index {
+        Some(index) => format!(
+            "if (self.pointer == null) return .{};\n\n{INDENT}{INDENT}",
+            tags[index].0
+        ),
+        None => String::new(),
+    };
+
+    // The discriminant may be stored in the pointer's low bits, so this
+    // pointer is untyped; payload() returns the real one.
+    if in_pointer {
+        let bitmask = tagged_pointer_bitmask(target_info.architecture);
+
+        write!(
+            buf,
+            r#"
+pub const {name} = extern struct {{
+    pointer: ?*anyopaque,
+
+    pub fn discriminant(self: {name}) {discriminant_name} {{
+        {null_check}return @intToEnum({discriminant_name}, @truncate(u{repr_bits}, @ptrToInt(self.pointer) & {bitmask:#b}));
+    }}
+
+    pub fn payload(self: {name}) *{name}_Payload {{
+        return @intToPtr(*{name}_Payload, @ptrToInt(self.pointer) & ~@as(usize, {bitmask:#b}));
+    }}
+}};
+"#
+        )
+        .unwrap();
+    } else {
+        write!(
+            buf,
+            r#"
+pub const {name} = extern struct {{
+    pointer: ?*anyopaque,
+
+    pub fn discriminant(self: {name}) {discriminant_name} {{
+        {null_check}return @intToEnum({discriminant_name}, @ptrCast(*align(1) const u{repr_bits}, &self.payload().bytes[{discriminant_offset}]).*);
+    }}
+
+    pub fn payload(self: {name}) *{name}_Payload {{
+        return @ptrCast(*{name}_Payload, @alignCast(@alignOf({name}_Payload), self.pointer.?));
+    }}
+}};
+"#
+        )
+        .unwrap();
+    }
+
+    write!(buf, "\npub const {name}_Payload = extern union {{\n").unwrap();
+    add_union_members(tags, types, buf);
+
+    if !in_pointer {
+        let bytes = discriminant_offset + discriminant_size;
+
+        writeln!(buf, "{INDENT}bytes: [{bytes}]u8,").unwrap();
+    }
+
+    buf.push_str("};\n");
+}
+
+fn add_union_members(tags: &[(String, Option<TypeId>)], types: &Types, buf: &mut String) {
+    for (tag_name, opt_payload_id) in tags {
+        if let Some(payload_id) = opt_payload_id {
+            if types.size_ignoring_alignment(*payload_id) > 0 {
+                writeln!(buf, "{INDENT}{},", field_decl(tag_name, *payload_id, types)).unwrap();
+            }
+        }
+    }
+}
+
+fn add_result(result_id: TypeId, ok_id: TypeId, err_id: TypeId, types: &Types, buf: &mut String) {
+    let name = result_name(ok_id, err_id, types);
+    let size = types.size_rounded_to_alignment(result_id);
+    let discriminant_offset = result_discriminant_offset(ok_id, err_id, types);
+
+    write!(buf, "\npub const {name} = extern union {{\n").unwrap();
+    add_union_members(
+        &[
+            ("ok".to_string(), Some(ok_id)),
+            ("err".to_string(), Some(err_id)),
+        ],
+        types,
+        buf,
+    );
+
+    // Err comes before Ok alphabetically, so Ok's discriminant is 1.
+    write!(
+        buf,
+        r#"    bytes: [{size}]u8,
+
+    pub fn isOk(self: *const {name}) bool {{
+        return self.bytes[{discriminant_offset}] == 1;
+    }}
+}};
+"#
+    )
+    .unwrap();
+    add_layout_asserts(&name, result_id, types, buf);
+}
+
+fn add_layout_asserts(name: &str, id: TypeId, types: &Types, buf: &mut String) {
+    let size = types.size_rounded_to_alignment(id);
+    let align = types.align(id);
+
+    // Roc reports an alignment of 0 for zero-sized types (e.g. a record whose
+    // fields are all closures that capture nothing), so there's nothing to check.
+    if size == 0 {
+        return;
+    }
+
+    write!(
+        buf,
+        "\ncomptime {{\n{INDENT}assert(@sizeOf({name}) == {size});\n{INDENT}assert(@alignOf({name}) == {align});\n}}\n"
+    )
+    .unwrap();
+}
+
+fn type_name(id: TypeId, types: &Types) -> String {
+    match types.get_type(id) {
+        RocType::Unit | RocType::EmptyTagUnion => "void".to_string(),
+        RocType::RocStr => "RocStr".to_string(),
+        RocType::Bool => "bool".to_string(),
+        RocType::Num(RocNum::U8) => "u8".to_string(),
+        RocType::Num(RocNum::U16) => "u16".to_string(),
+        RocType::Num(RocNum::U32) => "u32".to_string(),
+        RocType::Num(RocNum::U64) => "u64".to_string(),
+        RocType::Num(RocNum::U128) => "u128".to_string(),
+        RocType::Num(RocNum::I8) => "i8".to_string(),
+        RocType::Num(RocNum::I16) => "i16".to_string(),
+        RocType::Num(RocNum::I32) => "i32".to_string(),
+        RocType::Num(RocNum::I64) => "i64".to_string(),
+        RocType::Num(RocNum::I128) => "i128".to_string(),
+        RocType::Num(RocNum::F32) => "f32".to_string(),
+        RocType::Num(RocNum::F64) => "f64".to_string(),
+        RocType::Num(RocNum::Dec) => "RocDec".to_string(),
+        RocType::RocList(elem_id) => format!("RocList({})", type_name(*elem_id, types)),
+        // A Set is a Dict with empty values.
+        RocType::RocDict(_, _) | RocType::RocSet(_) => "RocDict".to_string(),
+        RocType::RocBox(elem_id) => match types.get_type(*elem_id) {
+            RocType::Unit | RocType::EmptyTagUnion => "*anyopaque".to_string(),
+            _ => format!("*{}", type_name(*elem_id, types)),
+        },
+        RocType::RocResult(ok_id, err_id) => result_name(*ok_id, *err_id, types),
+        RocType::Struct { name, .. }
+        | RocType::TagUnionPayload { name, .. }
+        | RocType::Function(RocFn {
+            function_name: name,
+            ..
+        })
+        | RocType::TagUnion(
+            RocTagUnion::Enumeration { name, .. }
+            | RocTagUnion::NonRecursive { name, .. }
+            | RocTagUnion::Recursive { name, .. }
+            | RocTagUnion::NullableWrapped { name, .. }
+            | RocTagUnion::NullableUnwrapped { name, .. }
+            | RocTagUnion::NonNullableUnwrapped { name, .. }
+            | RocTagUnion::SingleTagStruct { name, .. },
+        ) => name.clone(),
+        RocType::RecursivePointer(content) => type_name(*content, types),
+    }
+}
+
+/// e.g. `name: RocStr`
+fn field_decl(label: &str, id: TypeId, types: &Types) -> String {
+    let type_str = type_name(id, types);
+
+    match types.get_type(id) {
+        // The C ABI aligns 128-bit integers to 16 bytes on every target,
+        // which isn't what Zig does on all of them.
+        RocType::Num(RocNum::I128 | RocNum::U128) => format!("{label}: {type_str} align(16)"),
+        _ => format!("{label}: {type_str}"),
+    }
+}
+
+fn arch_to_str(architecture: Architecture) -> &'static str {
+    match architecture {
+        Architecture::X86_64 => "x86_64",
+        Architecture::X86_32 => "i386",
+        Architecture::Aarch64 => "aarch64",
+        Architecture::Aarch32 => "arm",
+        Architecture::Wasm32 => "wasm32",
+    }
+}
+
+const RESERVED_KEYWORDS: &[&str] = &[
+    "addrspace",
+    "align",
+    "allowzero",
+    "and",
+    "anyframe",
+    "anytype",
+    "asm",
+    "async",
+    "await",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "linksection",
+    "noalias",
+    "noinline",
+    "nosuspend",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "try",
+    "union",
+    "unreachable",
+    "usingnamespace",
+    "var",
+    "volatile",
+    "while",
+];
+
+/// Escape a Zig reserved keyword, if necessary, using @"" syntax.
+fn escape_kw(input: &str) -> String {
+    if RESERVED_KEYWORDS.contains(&input) {
+        format!("@\"{input}\"")
+    } else {
+        input.to_string()
+    }
+}
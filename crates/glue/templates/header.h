@@ -0,0 +1,43 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+#pragma once
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+struct RocStr {
+    char *bytes;
+    size_t len;
+    size_t capacity;
+};
+
+// The type of a list's elements is noted in a comment wherever the list is used.
+struct RocList {
+    void *elements;
+    size_t length;
+    size_t capacity;
+};
+
+// Dict and Set are opaque; use the Roc Dict and Set functions to work with them.
+struct RocDict {
+    struct RocList data;
+    struct RocList data_indices;
+    struct RocList metadata;
+    size_t size;
+};
+
+struct RocI128 {
+    _Alignas(16) uint64_t lo;
+    int64_t hi;
+};
+
+struct RocU128 {
+    _Alignas(16) uint64_t lo;
+    uint64_t hi;
+};
+
+// A fixed-point decimal, stored as an I128 scaled by 10^18.
+struct RocDec {
+    struct RocI128 num;
+};
@@ -0,0 +1,32 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+const std = @import("std");
+const builtin = @import("builtin");
+const assert = std.debug.assert;
+
+pub const RocStr = extern struct {
+    bytes: ?[*]u8,
+    len: usize,
+    capacity: usize,
+};
+
+pub fn RocList(comptime T: type) type {
+    return extern struct {
+        elements: ?[*]T,
+        length: usize,
+        capacity: usize,
+    };
+}
+
+/// Dict and Set are opaque; use the Roc Dict and Set functions to work with them.
+pub const RocDict = extern struct {
+    data: RocList(u8),
+    data_indices: RocList(usize),
+    metadata: RocList(i8),
+    size: usize,
+};
+
+/// A fixed-point decimal, stored as an I128 scaled by 10^18.
+pub const RocDec = extern struct {
+    num: i128 align(16),
+};
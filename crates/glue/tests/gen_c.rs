@@ -0,0 +1,593 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[macro_use]
+extern crate indoc;
+
+mod helpers;
+
+#[cfg(test)]
+mod test_gen_c {
+    use crate::helpers::generate_c_bindings;
+
+    #[test]
+    fn basic_record_aliased() {
+        let module = indoc!(
+            r#"
+            MyRcd : { a : U64, b : I128 }
+
+            main : MyRcd
+            main = { a: 1u64, b: 2i128 }
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__aarch64__) || defined(__wasm32__) || defined(__i386__) || defined(__x86_64__)
+
+                struct MyRcd {
+                    struct RocI128 b;
+                    uint64_t a;
+                };
+
+                _Static_assert(sizeof(struct MyRcd) == 32, "struct MyRcd should be 32 bytes");
+                _Static_assert(_Alignof(struct MyRcd) == 16, "struct MyRcd should be 16-byte aligned");
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn nested_record_aliased() {
+        let module = indoc!(
+            r#"
+            Outer : { x : Inner, y : Str, z : List U8 }
+
+            Inner : { a : U16, b : F32 }
+
+            main : Outer
+            main = { x: { a: 5, b: 24 }, y: "foo", z: [1, 2] }
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__wasm32__) || defined(__i386__)
+
+                struct Inner {
+                    float b;
+                    uint16_t a;
+                };
+
+                _Static_assert(sizeof(struct Inner) == 8, "struct Inner should be 8 bytes");
+                _Static_assert(_Alignof(struct Inner) == 4, "struct Inner should be 4-byte aligned");
+
+                struct Outer {
+                    struct Inner x;
+                    struct RocStr y;
+                    struct RocList z; // elements: uint8_t
+                };
+
+                _Static_assert(sizeof(struct Outer) == 32, "struct Outer should be 32 bytes");
+                _Static_assert(_Alignof(struct Outer) == 4, "struct Outer should be 4-byte aligned");
+
+                #elif defined(__aarch64__) || defined(__x86_64__)
+
+                struct Inner {
+                    float b;
+                    uint16_t a;
+                };
+
+                _Static_assert(sizeof(struct Inner) == 8, "struct Inner should be 8 bytes");
+                _Static_assert(_Alignof(struct Inner) == 4, "struct Inner should be 4-byte aligned");
+
+                struct Outer {
+                    struct RocStr y;
+                    struct RocList z; // elements: uint8_t
+                    struct Inner x;
+                };
+
+                _Static_assert(sizeof(struct Outer) == 56, "struct Outer should be 56 bytes");
+                _Static_assert(_Alignof(struct Outer) == 8, "struct Outer should be 8-byte aligned");
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn non_recursive_tag_union() {
+        let module = indoc!(
+            r#"
+            NonRecursive : [Foo Str, Bar U128, Blah I32, Baz]
+
+            main : NonRecursive
+            main = Foo "blah"
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__wasm32__) || defined(__i386__)
+
+                typedef uint8_t discriminant_NonRecursive;
+
+                enum {
+                    discriminant_NonRecursive_Bar = 0,
+                    discriminant_NonRecursive_Baz = 1,
+                    discriminant_NonRecursive_Blah = 2,
+                    discriminant_NonRecursive_Foo = 3,
+                };
+
+                union NonRecursive {
+                    struct RocU128 Bar;
+                    int32_t Blah;
+                    struct RocStr Foo;
+                    uint8_t bytes[32];
+                };
+
+                _Static_assert(sizeof(union NonRecursive) == 32, "union NonRecursive should be 32 bytes");
+                _Static_assert(_Alignof(union NonRecursive) == 16, "union NonRecursive should be 16-byte aligned");
+
+                static inline discriminant_NonRecursive NonRecursive_discriminant(const union NonRecursive *tag_union) {
+                    return *(const discriminant_NonRecursive *)(tag_union->bytes + 16);
+                }
+
+                static inline void NonRecursive_set_discriminant(union NonRecursive *tag_union, discriminant_NonRecursive discriminant) {
+                    *(discriminant_NonRecursive *)(tag_union->bytes + 16) = discriminant;
+                }
+
+                #elif defined(__aarch64__) || defined(__x86_64__)
+
+                typedef uint8_t discriminant_NonRecursive;
+
+                enum {
+                    discriminant_NonRecursive_Bar = 0,
+                    discriminant_NonRecursive_Baz = 1,
+                    discriminant_NonRecursive_Blah = 2,
+                    discriminant_NonRecursive_Foo = 3,
+                };
+
+                union NonRecursive {
+                    struct RocU128 Bar;
+                    int32_t Blah;
+                    struct RocStr Foo;
+                    uint8_t bytes[48];
+                };
+
+                _Static_assert(sizeof(union NonRecursive) == 48, "union NonRecursive should be 48 bytes");
+                _Static_assert(_Alignof(union NonRecursive) == 16, "union NonRecursive should be 16-byte aligned");
+
+                static inline discriminant_NonRecursive NonRecursive_discriminant(const union NonRecursive *tag_union) {
+                    return *(const discriminant_NonRecursive *)(tag_union->bytes + 32);
+                }
+
+                static inline void NonRecursive_set_discriminant(union NonRecursive *tag_union, discriminant_NonRecursive discriminant) {
+                    *(discriminant_NonRecursive *)(tag_union->bytes + 32) = discriminant;
+                }
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn recursive_tag_union() {
+        let module = indoc!(
+            r#"
+            Expr : [Sym Str, Add Expr Expr]
+
+            main : Expr
+            main = Add (Sym "x") (Sym "y")
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__wasm32__) || defined(__i386__)
+
+                struct Expr {
+                    void *pointer;
+                };
+
+                struct Expr_Add {
+                    struct Expr f0;
+                    struct Expr f1;
+                };
+
+                struct Expr_Sym {
+                    struct RocStr f0;
+                };
+
+                typedef uint8_t discriminant_Expr;
+
+                enum {
+                    discriminant_Expr_Add = 0,
+                    discriminant_Expr_Sym = 1,
+                };
+
+                union Expr_Payload {
+                    struct Expr_Add Add;
+                    struct Expr_Sym Sym;
+                };
+
+                static inline union Expr_Payload *Expr_payload(struct Expr tag_union) {
+                    return (union Expr_Payload *)((uintptr_t)tag_union.pointer & ~(uintptr_t)0x3);
+                }
+
+                static inline discriminant_Expr Expr_discriminant(struct Expr tag_union) {
+                    return (discriminant_Expr)((uintptr_t)tag_union.pointer & 0x3);
+                }
+
+                #elif defined(__aarch64__) || defined(__x86_64__)
+
+                struct Expr {
+                    void *pointer;
+                };
+
+                struct Expr_Add {
+                    struct Expr f0;
+                    struct Expr f1;
+                };
+
+                struct Expr_Sym {
+                    struct RocStr f0;
+                };
+
+                typedef uint8_t discriminant_Expr;
+
+                enum {
+                    discriminant_Expr_Add = 0,
+                    discriminant_Expr_Sym = 1,
+                };
+
+                union Expr_Payload {
+                    struct Expr_Add Add;
+                    struct Expr_Sym Sym;
+                };
+
+                static inline union Expr_Payload *Expr_payload(struct Expr tag_union) {
+                    return (union Expr_Payload *)((uintptr_t)tag_union.pointer & ~(uintptr_t)0x7);
+                }
+
+                static inline discriminant_Expr Expr_discriminant(struct Expr tag_union) {
+                    return (discriminant_Expr)((uintptr_t)tag_union.pointer & 0x7);
+                }
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn nullable_unwrapped_tag_union() {
+        let module = indoc!(
+            r#"
+            StrConsList : [Nil, Cons Str StrConsList]
+
+            main : StrConsList
+            main = Cons "Hello, " (Cons "World!" Nil)
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__aarch64__) || defined(__wasm32__) || defined(__i386__) || defined(__x86_64__)
+
+                struct StrConsList {
+                    struct StrConsList_Cons *pointer;
+                };
+
+                static inline bool StrConsList_is_Nil(struct StrConsList tag_union) {
+                    return tag_union.pointer == NULL;
+                }
+
+                struct StrConsList_Cons {
+                    struct RocStr f0;
+                    struct StrConsList f1;
+                };
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn nullable_wrapped_tag_union() {
+        let module = indoc!(
+            r#"
+            Tree : [Empty, Leaf Str, Node Tree Tree]
+
+            main : Tree
+            main = Node Empty (Leaf "x")
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__wasm32__) || defined(__i386__)
+
+                struct Tree {
+                    void *pointer;
+                };
+
+                struct Tree_Leaf {
+                    struct RocStr f0;
+                };
+
+                struct Tree_Node {
+                    struct Tree f0;
+                    struct Tree f1;
+                };
+
+                typedef uint8_t discriminant_Tree;
+
+                enum {
+                    discriminant_Tree_Empty = 0,
+                    discriminant_Tree_Leaf = 1,
+                    discriminant_Tree_Node = 2,
+                };
+
+                union Tree_Payload {
+                    struct Tree_Leaf Leaf;
+                    struct Tree_Node Node;
+                };
+
+                static inline union Tree_Payload *Tree_payload(struct Tree tag_union) {
+                    return (union Tree_Payload *)((uintptr_t)tag_union.pointer & ~(uintptr_t)0x3);
+                }
+
+                static inline discriminant_Tree Tree_discriminant(struct Tree tag_union) {
+                    if (tag_union.pointer == NULL) {
+                        return discriminant_Tree_Empty;
+                    }
+
+                    return (discriminant_Tree)((uintptr_t)tag_union.pointer & 0x3);
+                }
+
+                #elif defined(__aarch64__) || defined(__x86_64__)
+
+                struct Tree {
+                    void *pointer;
+                };
+
+                struct Tree_Leaf {
+                    struct RocStr f0;
+                };
+
+                struct Tree_Node {
+                    struct Tree f0;
+                    struct Tree f1;
+                };
+
+                typedef uint8_t discriminant_Tree;
+
+                enum {
+                    discriminant_Tree_Empty = 0,
+                    discriminant_Tree_Leaf = 1,
+                    discriminant_Tree_Node = 2,
+                };
+
+                union Tree_Payload {
+                    struct Tree_Leaf Leaf;
+                    struct Tree_Node Node;
+                };
+
+                static inline union Tree_Payload *Tree_payload(struct Tree tag_union) {
+                    return (union Tree_Payload *)((uintptr_t)tag_union.pointer & ~(uintptr_t)0x7);
+                }
+
+                static inline discriminant_Tree Tree_discriminant(struct Tree tag_union) {
+                    if (tag_union.pointer == NULL) {
+                        return discriminant_Tree_Empty;
+                    }
+
+                    return (discriminant_Tree)((uintptr_t)tag_union.pointer & 0x7);
+                }
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn result_and_box() {
+        let module = indoc!(
+            r#"
+            Rcd : { ok : Result Str U64, b : Box U8, c : Dict Str U8 }
+
+            main : Rcd
+            main = { ok: Ok "x", b: Box.box 1, c: Dict.empty }
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__wasm32__)
+
+                union RocResult_Str_U64 {
+                    struct RocStr ok;
+                    uint64_t err;
+                    uint8_t bytes[24];
+                };
+
+                _Static_assert(sizeof(union RocResult_Str_U64) == 24, "union RocResult_Str_U64 should be 24 bytes");
+                _Static_assert(_Alignof(union RocResult_Str_U64) == 8, "union RocResult_Str_U64 should be 8-byte aligned");
+
+                static inline bool RocResult_Str_U64_is_ok(const union RocResult_Str_U64 *result) {
+                    return result->bytes[16] == 1;
+                }
+
+                struct Rcd {
+                    union RocResult_Str_U64 ok;
+                    uint8_t *b;
+                    struct RocDict c;
+                };
+
+                _Static_assert(sizeof(struct Rcd) == 72, "struct Rcd should be 72 bytes");
+                _Static_assert(_Alignof(struct Rcd) == 8, "struct Rcd should be 8-byte aligned");
+
+                #elif defined(__aarch64__) || defined(__x86_64__)
+
+                union RocResult_Str_U64 {
+                    struct RocStr ok;
+                    uint64_t err;
+                    uint8_t bytes[32];
+                };
+
+                _Static_assert(sizeof(union RocResult_Str_U64) == 32, "union RocResult_Str_U64 should be 32 bytes");
+                _Static_assert(_Alignof(union RocResult_Str_U64) == 8, "union RocResult_Str_U64 should be 8-byte aligned");
+
+                static inline bool RocResult_Str_U64_is_ok(const union RocResult_Str_U64 *result) {
+                    return result->bytes[24] == 1;
+                }
+
+                struct Rcd {
+                    uint8_t *b;
+                    struct RocDict c;
+                    union RocResult_Str_U64 ok;
+                };
+
+                _Static_assert(sizeof(struct Rcd) == 120, "struct Rcd should be 120 bytes");
+                _Static_assert(_Alignof(struct Rcd) == 8, "struct Rcd should be 8-byte aligned");
+
+                #elif defined(__i386__)
+
+                union RocResult_Str_U64 {
+                    struct RocStr ok;
+                    uint64_t err;
+                    uint8_t bytes[16];
+                };
+
+                _Static_assert(sizeof(union RocResult_Str_U64) == 16, "union RocResult_Str_U64 should be 16 bytes");
+                _Static_assert(_Alignof(union RocResult_Str_U64) == 4, "union RocResult_Str_U64 should be 4-byte aligned");
+
+                static inline bool RocResult_Str_U64_is_ok(const union RocResult_Str_U64 *result) {
+                    return result->bytes[12] == 1;
+                }
+
+                struct Rcd {
+                    uint8_t *b;
+                    struct RocDict c;
+                    union RocResult_Str_U64 ok;
+                };
+
+                _Static_assert(sizeof(struct Rcd) == 60, "struct Rcd should be 60 bytes");
+                _Static_assert(_Alignof(struct Rcd) == 4, "struct Rcd should be 4-byte aligned");
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn closure_aliased() {
+        let module = indoc!(
+            r#"
+            main : ({} -> Str) as Fx
+            main = (\p -> \{} -> Str.concat p "!") "x"
+        "#
+        );
+
+        assert_eq!(
+            generate_c_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #if defined(__arm__) || defined(__wasm32__) || defined(__i386__)
+
+                struct Fx {
+                    _Alignas(4) uint8_t closure_data[12];
+                };
+
+                _Static_assert(sizeof(struct Fx) == 12, "struct Fx should be 12 bytes");
+                _Static_assert(_Alignof(struct Fx) == 4, "struct Fx should be 4-byte aligned");
+
+                extern void roc__main_1__Fx_caller(const void *arg0, const void *closure_data, struct RocStr *output);
+
+                static inline struct RocStr Fx_call(const struct Fx *closure, const void *arg0) {
+                    struct RocStr output;
+
+                    roc__main_1__Fx_caller(arg0, closure, &output);
+
+                    return output;
+                }
+
+                #elif defined(__aarch64__) || defined(__x86_64__)
+
+                struct Fx {
+                    _Alignas(8) uint8_t closure_data[24];
+                };
+
+                _Static_assert(sizeof(struct Fx) == 24, "struct Fx should be 24 bytes");
+                _Static_assert(_Alignof(struct Fx) == 8, "struct Fx should be 8-byte aligned");
+
+                extern void roc__main_1__Fx_caller(const void *arg0, const void *closure_data, struct RocStr *output);
+
+                static inline struct RocStr Fx_call(const struct Fx *closure, const void *arg0) {
+                    struct RocStr output;
+
+                    roc__main_1__Fx_caller(arg0, closure, &output);
+
+                    return output;
+                }
+
+                #else
+                #error "Roc glue was not generated for this target architecture."
+                #endif
+            "#
+            )
+        );
+    }
+}
@@ -233,4 +233,110 @@ mod test_gen_rs {
             )
         );
     }
+
+    #[test]
+    fn closure_aliased() {
+        let module = indoc!(
+            r#"
+            main : ({} -> Str) as Fx
+            main = (\p -> \{} -> Str.concat p "!") "x"
+        "#
+        );
+
+        assert_eq!(
+            generate_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #[cfg(any(
+                    target_arch = "arm",
+                    target_arch = "wasm32",
+                    target_arch = "x86"
+                ))]
+                #[derive(Clone, Copy, Debug, Eq, Ord, Hash, PartialEq, PartialOrd)]
+                #[repr(C, align(4))]
+                pub struct Fx {
+                    closure_data: [u8; 12],
+                }
+
+                #[cfg(any(
+                    target_arch = "aarch64",
+                    target_arch = "x86_64"
+                ))]
+                #[derive(Clone, Copy, Debug, Eq, Ord, Hash, PartialEq, PartialOrd)]
+                #[repr(C, align(8))]
+                pub struct Fx {
+                    closure_data: [u8; 24],
+                }
+
+                impl Fx {
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "aarch64",
+                        target_arch = "wasm32",
+                        target_arch = "x86",
+                        target_arch = "x86_64"
+                    ))]
+                    /// Calls this closure with the environment it captured.
+                    pub fn call(&self, arg0: &()) -> roc_std::RocStr {
+                        extern "C" {
+                            #[link_name = "roc__main_1__Fx_caller"]
+                            fn caller(arg0: *const (), closure_data: *const u8, output: *mut roc_std::RocStr);
+                        }
+
+                        let mut output = core::mem::MaybeUninit::uninit();
+
+                        unsafe {
+                            caller(arg0, self.closure_data.as_ptr(), output.as_mut_ptr());
+
+                            output.assume_init()
+                        }
+                    }
+                }
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn toplevel_function() {
+        let module = indoc!(
+            r#"
+            main : Str, U64 -> Str
+            main = \s, n -> Str.concat s (Num.toStr n)
+        "#
+        );
+
+        assert_eq!(
+            generate_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                #[cfg(any(
+                    target_arch = "arm",
+                    target_arch = "aarch64",
+                    target_arch = "wasm32",
+                    target_arch = "x86",
+                    target_arch = "x86_64"
+                ))]
+                pub fn main(arg0: &roc_std::RocStr, arg1: u64) -> roc_std::RocStr {
+                    extern "C" {
+                        #[link_name = "roc__main_1_exposed_generic"]
+                        fn caller(output: *mut roc_std::RocStr, arg0: *const roc_std::RocStr, arg1: u64);
+                    }
+
+                    let mut output = core::mem::MaybeUninit::uninit();
+
+                    unsafe {
+                        caller(output.as_mut_ptr(), arg0, arg1);
+
+                        output.assume_init()
+                    }
+                }
+            "#
+            )
+        );
+    }
 }
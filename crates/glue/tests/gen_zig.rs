@@ -0,0 +1,306 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[macro_use]
+extern crate indoc;
+
+mod helpers;
+
+#[cfg(test)]
+mod test_gen_zig {
+    use crate::helpers::generate_zig_bindings;
+
+    #[test]
+    fn basic_record_aliased() {
+        let module = indoc!(
+            r#"
+            MyRcd : { a : U64, b : I128 }
+
+            main : MyRcd
+            main = { a: 1u64, b: 2i128 }
+        "#
+        );
+
+        assert_eq!(
+            generate_zig_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                pub usingnamespace switch (builtin.cpu.arch) {
+                    .arm, .aarch64, .wasm32, .i386, .x86_64 => struct {
+                        pub const MyRcd = extern struct {
+                            b: i128 align(16),
+                            a: u64,
+                        };
+
+                        comptime {
+                            assert(@sizeOf(MyRcd) == 32);
+                            assert(@alignOf(MyRcd) == 16);
+                        }
+                    },
+                    else => @compileError("Roc glue was not generated for this target architecture."),
+                };
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn recursive_tag_union() {
+        let module = indoc!(
+            r#"
+            Expr : [Sym Str, Add Expr Expr]
+
+            main : Expr
+            main = Add (Sym "x") (Sym "y")
+        "#
+        );
+
+        assert_eq!(
+            generate_zig_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                pub usingnamespace switch (builtin.cpu.arch) {
+                    .arm, .wasm32, .i386 => struct {
+                        pub const discriminant_Expr = enum(u8) {
+                            Add = 0,
+                            Sym = 1,
+                        };
+
+                        pub const Expr = extern struct {
+                            pointer: ?*anyopaque,
+
+                            pub fn discriminant(self: Expr) discriminant_Expr {
+                                return @intToEnum(discriminant_Expr, @truncate(u8, @ptrToInt(self.pointer) & 0b11));
+                            }
+
+                            pub fn payload(self: Expr) *Expr_Payload {
+                                return @intToPtr(*Expr_Payload, @ptrToInt(self.pointer) & ~@as(usize, 0b11));
+                            }
+                        };
+
+                        pub const Expr_Payload = extern union {
+                            Add: Expr_Add,
+                            Sym: Expr_Sym,
+                        };
+
+                        pub const Expr_Sym = extern struct {
+                            f0: RocStr,
+                        };
+
+                        pub const Expr_Add = extern struct {
+                            f0: Expr,
+                            f1: Expr,
+                        };
+                    },
+                    .aarch64, .x86_64 => struct {
+                        pub const discriminant_Expr = enum(u8) {
+                            Add = 0,
+                            Sym = 1,
+                        };
+
+                        pub const Expr = extern struct {
+                            pointer: ?*anyopaque,
+
+                            pub fn discriminant(self: Expr) discriminant_Expr {
+                                return @intToEnum(discriminant_Expr, @truncate(u8, @ptrToInt(self.pointer) & 0b111));
+                            }
+
+                            pub fn payload(self: Expr) *Expr_Payload {
+                                return @intToPtr(*Expr_Payload, @ptrToInt(self.pointer) & ~@as(usize, 0b111));
+                            }
+                        };
+
+                        pub const Expr_Payload = extern union {
+                            Add: Expr_Add,
+                            Sym: Expr_Sym,
+                        };
+
+                        pub const Expr_Sym = extern struct {
+                            f0: RocStr,
+                        };
+
+                        pub const Expr_Add = extern struct {
+                            f0: Expr,
+                            f1: Expr,
+                        };
+                    },
+                    else => @compileError("Roc glue was not generated for this target architecture."),
+                };
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn result_and_box() {
+        let module = indoc!(
+            r#"
+            Rcd : { ok : Result Str U64, b : Box U8, c : Dict Str U8 }
+
+            main : Rcd
+            main = { ok: Ok "x", b: Box.box 1, c: Dict.empty }
+        "#
+        );
+
+        assert_eq!(
+            generate_zig_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                pub usingnamespace switch (builtin.cpu.arch) {
+                    .arm, .wasm32 => struct {
+                        pub const Rcd = extern struct {
+                            ok: RocResult_Str_U64,
+                            b: *u8,
+                            c: RocDict,
+                        };
+
+                        comptime {
+                            assert(@sizeOf(Rcd) == 72);
+                            assert(@alignOf(Rcd) == 8);
+                        }
+
+                        pub const RocResult_Str_U64 = extern union {
+                            ok: RocStr,
+                            err: u64,
+                            bytes: [24]u8,
+
+                            pub fn isOk(self: *const RocResult_Str_U64) bool {
+                                return self.bytes[16] == 1;
+                            }
+                        };
+
+                        comptime {
+                            assert(@sizeOf(RocResult_Str_U64) == 24);
+                            assert(@alignOf(RocResult_Str_U64) == 8);
+                        }
+                    },
+                    .aarch64, .x86_64 => struct {
+                        pub const Rcd = extern struct {
+                            b: *u8,
+                            c: RocDict,
+                            ok: RocResult_Str_U64,
+                        };
+
+                        comptime {
+                            assert(@sizeOf(Rcd) == 120);
+                            assert(@alignOf(Rcd) == 8);
+                        }
+
+                        pub const RocResult_Str_U64 = extern union {
+                            ok: RocStr,
+                            err: u64,
+                            bytes: [32]u8,
+
+                            pub fn isOk(self: *const RocResult_Str_U64) bool {
+                                return self.bytes[24] == 1;
+                            }
+                        };
+
+                        comptime {
+                            assert(@sizeOf(RocResult_Str_U64) == 32);
+                            assert(@alignOf(RocResult_Str_U64) == 8);
+                        }
+                    },
+                    .i386 => struct {
+                        pub const Rcd = extern struct {
+                            b: *u8,
+                            c: RocDict,
+                            ok: RocResult_Str_U64,
+                        };
+
+                        comptime {
+                            assert(@sizeOf(Rcd) == 60);
+                            assert(@alignOf(Rcd) == 4);
+                        }
+
+                        pub const RocResult_Str_U64 = extern union {
+                            ok: RocStr,
+                            err: u64,
+                            bytes: [16]u8,
+
+                            pub fn isOk(self: *const RocResult_Str_U64) bool {
+                                return self.bytes[12] == 1;
+                            }
+                        };
+
+                        comptime {
+                            assert(@sizeOf(RocResult_Str_U64) == 16);
+                            assert(@alignOf(RocResult_Str_U64) == 4);
+                        }
+                    },
+                    else => @compileError("Roc glue was not generated for this target architecture."),
+                };
+            "#
+            )
+        );
+    }
+
+    #[test]
+    fn closure_aliased() {
+        let module = indoc!(
+            r#"
+            main : ({} -> Str) as Fx
+            main = (\p -> \{} -> Str.concat p "!") "x"
+        "#
+        );
+
+        assert_eq!(
+            generate_zig_bindings(module)
+                .strip_prefix('\n')
+                .unwrap_or_default(),
+            indoc!(
+                r#"
+                pub usingnamespace switch (builtin.cpu.arch) {
+                    .arm, .wasm32, .i386 => struct {
+                        pub const Fx = extern struct {
+                            closure_data: [12]u8 align(4),
+
+                            extern fn roc__main_1__Fx_caller(arg0: ?*const anyopaque, closure_data: ?*const anyopaque, output: *RocStr) void;
+
+                            pub fn call(self: *const Fx) RocStr {
+                                var output: RocStr = undefined;
+
+                                roc__main_1__Fx_caller(null, self, &output);
+
+                                return output;
+                            }
+                        };
+
+                        comptime {
+                            assert(@sizeOf(Fx) == 12);
+                            assert(@alignOf(Fx) == 4);
+                        }
+                    },
+                    .aarch64, .x86_64 => struct {
+                        pub const Fx = extern struct {
+                            closure_data: [24]u8 align(8),
+
+                            extern fn roc__main_1__Fx_caller(arg0: ?*const anyopaque, closure_data: ?*const anyopaque, output: *RocStr) void;
+
+                            pub fn call(self: *const Fx) RocStr {
+                                var output: RocStr = undefined;
+
+                                roc__main_1__Fx_caller(null, self, &output);
+
+                                return output;
+                            }
+                        };
+
+                        comptime {
+                            assert(@sizeOf(Fx) == 24);
+                            assert(@alignOf(Fx) == 8);
+                        }
+                    },
+                    else => @compileError("Roc glue was not generated for this target architecture."),
+                };
+            "#
+            )
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use roc_glue::load::{load_types, IgnoreErrors};
-use roc_glue::rust_glue;
+use roc_glue::types::Types;
+use roc_glue::{c_glue, rust_glue, zig_glue};
 use roc_load::Threading;
+use roc_target::TargetInfo;
 use std::env;
 use std::fs::File;
 use std::io::Write;
@@ -8,6 +10,20 @@ use std::path::PathBuf;
 
 #[allow(dead_code)]
 pub fn generate_bindings(decl_src: &str) -> String {
+    rust_glue::emit(&load_decls(decl_src))
+}
+
+#[allow(dead_code)]
+pub fn generate_c_bindings(decl_src: &str) -> String {
+    c_glue::emit(&load_decls(decl_src))
+}
+
+#[allow(dead_code)]
+pub fn generate_zig_bindings(decl_src: &str) -> String {
+    zig_glue::emit(&load_decls(decl_src))
+}
+
+fn load_decls(decl_src: &str) -> Vec<(Types, TargetInfo)> {
     use tempfile::tempdir;
 
     let mut src = indoc!(
@@ -25,27 +41,23 @@ pub fn generate_bindings(decl_src: &str) -> String {
 
     src.push_str(decl_src);
 
-    let pairs = {
-        let dir = tempdir().expect("Unable to create tempdir");
-        let filename = PathBuf::from("platform.roc");
-        let file_path = dir.path().join(filename);
-        let full_file_path = file_path.clone();
-        let mut file = File::create(file_path).unwrap();
-        writeln!(file, "{}", &src).unwrap();
-
-        let result = load_types(
-            full_file_path,
-            Threading::Single,
-            // required `nothing` is unused; that error is okay
-            IgnoreErrors { can: true },
-        );
+    let dir = tempdir().expect("Unable to create tempdir");
+    let filename = PathBuf::from("platform.roc");
+    let file_path = dir.path().join(filename);
+    let full_file_path = file_path.clone();
+    let mut file = File::create(file_path).unwrap();
+    writeln!(file, "{}", &src).unwrap();
 
-        dir.close().expect("Unable to close tempdir");
+    let result = load_types(
+        full_file_path,
+        Threading::Single,
+        // required `nothing` is unused; that error is okay
+        IgnoreErrors { can: true },
+    );
 
-        result.expect("had problems loading")
-    };
+    dir.close().expect("Unable to close tempdir");
 
-    rust_glue::emit(&pairs)
+    result.expect("had problems loading")
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,213 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+use roc_can::expr::Declarations;
+use roc_can::traverse::{find_closest_type_at, find_declaration_region, find_symbol_at};
+use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, OptLevel, Threading};
+use roc_module::symbol::{Interns, ModuleId};
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_problem::Severity;
+use roc_region::all::{LineInfo, Position, Region};
+use roc_reporting::report::{
+    can_problem, type_problem, CiWrite, RenderTarget, Report, RocDocAllocator, DEFAULT_PALETTE,
+};
+use roc_target::TargetInfo;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::Subs;
+use serde_json::{json, Value};
+
+use crate::convert::region_to_lsp;
+
+/// The result of loading and type-checking a module from disk.
+pub struct Analysis {
+    /// LSP `Diagnostic`s for every non-builtin module that was loaded, including ones without
+    /// any, so that stale diagnostics get cleared.
+    pub diagnostics: Vec<(PathBuf, Vec<Value>)>,
+    /// The checked module, if loading got as far as type-checking it.
+    pub module: Option<AnalyzedModule>,
+}
+
+/// Everything needed to answer queries about a type-checked module.
+pub struct AnalyzedModule {
+    module_id: ModuleId,
+    interns: Interns,
+    subs: Subs,
+    declarations: Declarations,
+    /// The source the module was checked against, which may be older than the editor's buffer.
+    pub source: Box<str>,
+}
+
+impl AnalyzedModule {
+    /// The region and printed type of the innermost expression or pattern at `position`.
+    pub fn type_at(&mut self, position: Position) -> Option<(Region, String)> {
+        let (region, var) = find_closest_type_at(position, &self.declarations)?;
+        let printed = name_and_print_var(
+            var,
+            &mut self.subs,
+            self.module_id,
+            &self.interns,
+            DebugPrint::NOTHING,
+        );
+
+        Some((region, printed))
+    }
+
+    /// The region where the symbol at `position` is introduced. Only symbols defined in this
+    /// module can be found, since we only keep this module's declarations around.
+    pub fn definition_at(&self, position: Position) -> Option<Region> {
+        let symbol = find_symbol_at(position, &self.declarations)?.value;
+
+        if symbol.module_id() != self.module_id {
+            return None;
+        }
+
+        find_declaration_region(symbol, &self.declarations)
+    }
+}
+
+pub fn analyze(path: &Path) -> Analysis {
+    // The compiler still panics on some malformed programs; that shouldn't take the
+    // whole editor session down with it.
+    match catch_unwind(AssertUnwindSafe(|| analyze_help(path))) {
+        Ok(analysis) => analysis,
+        Err(_) => file_problem(
+            path,
+            "The compiler crashed while checking this file. This is a bug in the compiler!"
+                .to_string(),
+        ),
+    }
+}
+
+fn analyze_help(path: &Path) -> Analysis {
+    let arena = Bump::new();
+
+    let load_config = LoadConfig {
+        // only used for generating errors, so hardcoding should be fine
+        target_info: TargetInfo::default_x86_64(),
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::AllAvailable,
+        exec_mode: ExecutionMode::Check,
+        opt_level: OptLevel::Normal,
+    };
+
+    let loaded = roc_load::load_and_typecheck(
+        &arena,
+        path.to_path_buf(),
+        Default::default(),
+        RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+        load_config,
+    );
+
+    match loaded {
+        Ok(loaded) => analyze_loaded(loaded),
+        Err(problem) => {
+            let message = match problem {
+                LoadingProblem::FormattedReport(report) => report,
+                LoadingProblem::FileProblem { filename, error } => {
+                    format!("I could not read {}: {:?}", filename.display(), error)
+                }
+                other => format!("{:?}", other),
+            };
+
+            file_problem(path, message)
+        }
+    }
+}
+
+fn analyze_loaded(mut loaded: LoadedModule) -> Analysis {
+    let mut diagnostics = Vec::with_capacity(loaded.sources.len());
+
+    for (home, (module_path, src)) in loaded.sources.iter() {
+        if home.is_builtin() {
+            // Builtins are not files the editor can show
+            continue;
+        }
+
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, *home, &loaded.interns);
+
+        let mut module_diagnostics = Vec::new();
+
+        for problem in loaded.can_problems.remove(home).unwrap_or_default() {
+            let region = problem.region().unwrap_or_default();
+            let report = can_problem(&alloc, &lines, module_path.clone(), problem);
+
+            module_diagnostics.push(report_diagnostic(src, region, report));
+        }
+
+        for problem in loaded.type_problems.remove(home).unwrap_or_default() {
+            let region = problem.region().unwrap_or_default();
+
+            if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
+                module_diagnostics.push(report_diagnostic(src, region, report));
+            }
+        }
+
+        diagnostics.push((module_path.clone(), module_diagnostics));
+    }
+
+    let module_id = loaded.module_id;
+    let module = match (
+        loaded.declarations_by_id.remove(&module_id),
+        loaded.sources.remove(&module_id),
+    ) {
+        (Some(declarations), Some((_, source))) => Some(AnalyzedModule {
+            module_id,
+            interns: loaded.interns,
+            subs: loaded.solved.into_inner(),
+            declarations,
+            source,
+        }),
+        _ => None,
+    };
+
+    Analysis {
+        diagnostics,
+        module,
+    }
+}
+
+/// An analysis that got no further than reporting one problem for the whole file.
+fn file_problem(path: &Path, message: String) -> Analysis {
+    let range = region_to_lsp("", Region::zero());
+
+    Analysis {
+        diagnostics: vec![(
+            path.to_path_buf(),
+            vec![diagnostic(range, Severity::RuntimeError, message)],
+        )],
+        module: None,
+    }
+}
+
+fn report_diagnostic(src: &str, region: Region, report: Report) -> Value {
+    let mut body = String::new();
+
+    report
+        .doc
+        .1
+        .render_raw(70, &mut CiWrite::new(&mut body))
+        .expect("<buffer is not a utf-8 encoded string>");
+
+    let message = format!("{}\n\n{}", report.title, body.trim_end());
+
+    diagnostic(region_to_lsp(src, region), report.severity, message)
+}
+
+fn diagnostic(range: Value, severity: Severity, message: String) -> Value {
+    // DiagnosticSeverity in the LSP: 1 is Error, 2 is Warning
+    let severity = match severity {
+        Severity::RuntimeError => 1,
+        Severity::Warning => 2,
+    };
+
+    json!({
+        "range": range,
+        "severity": severity,
+        "source": "roc",
+        "message": message,
+    })
+}
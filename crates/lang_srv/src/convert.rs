@@ -0,0 +1,142 @@
+//! Conversions between the compiler's byte offsets and file paths, and the LSP's
+//! (line, UTF-16 code unit) positions and `file://` URIs.
+
+use roc_region::all::{Position, Region};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Converts an LSP position to a byte offset into `src`. Positions past the end of a line are
+/// clamped to the end of that line, as the protocol requires.
+pub fn position_from_lsp(src: &str, line: u32, character: u32) -> Position {
+    let mut line_start = 0;
+
+    for _ in 0..line {
+        match src[line_start..].find('\n') {
+            Some(index) => line_start += index + 1,
+            None => return Position::new(src.len() as u32),
+        }
+    }
+
+    let line_end = src[line_start..]
+        .find('\n')
+        .map_or(src.len(), |index| line_start + index);
+
+    let mut offset = line_start;
+    let mut utf16_column = 0;
+
+    for ch in src[line_start..line_end].chars() {
+        if utf16_column >= character as usize {
+            break;
+        }
+
+        utf16_column += ch.len_utf16();
+        offset += ch.len_utf8();
+    }
+
+    Position::new(offset as u32)
+}
+
+/// Converts a byte offset into `src` to an LSP `Position` object.
+pub fn position_to_lsp(src: &str, pos: Position) -> Value {
+    let offset = (pos.offset as usize).min(src.len());
+    let before = &src[..offset];
+
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+
+    json!({ "line": line, "character": character })
+}
+
+/// Converts a region of `src` to an LSP `Range` object.
+pub fn region_to_lsp(src: &str, region: Region) -> Value {
+    json!({
+        "start": position_to_lsp(src, region.start()),
+        "end": position_to_lsp(src, region.end()),
+    })
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' && tail.len() >= 2 {
+            let hex = std::str::from_utf8(&tail[..2]).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+
+    // On Windows, URIs look like file:///C:/dir/file.roc
+    #[cfg(windows)]
+    let path = path.strip_prefix('/').unwrap_or(&path).to_string();
+
+    Some(PathBuf::from(path))
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+
+    #[cfg(windows)]
+    let path = format!("/{}", path.replace('\\', "/"));
+
+    let mut uri = String::from("file://");
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_count_utf16_code_units() {
+        // "é" is 2 bytes and 1 UTF-16 code unit; "𝔸" is 4 bytes and 2 UTF-16 code units
+        let src = "x = 1\ns = \"é𝔸\" |> f\n";
+        let f_offset = src.find('f').unwrap() as u32;
+
+        let lsp = position_to_lsp(src, Position::new(f_offset));
+        assert_eq!(lsp, json!({ "line": 1, "character": 13 }));
+
+        assert_eq!(position_from_lsp(src, 1, 13), Position::new(f_offset));
+    }
+
+    #[test]
+    fn positions_are_clamped() {
+        let src = "ab\ncd";
+
+        assert_eq!(position_from_lsp(src, 0, 99), Position::new(2));
+        assert_eq!(position_from_lsp(src, 9, 0), Position::new(5));
+        assert_eq!(
+            position_to_lsp(src, Position::new(99)),
+            json!({ "line": 1, "character": 2 })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn uris() {
+        let path = Path::new("/home/me/my roc/Main.roc");
+        let uri = path_to_uri(path);
+
+        assert_eq!(uri, "file:///home/me/my%20roc/Main.roc");
+        assert_eq!(uri_to_path(&uri).as_deref(), Some(path));
+        assert_eq!(uri_to_path("untitled:Untitled-1"), None);
+    }
+}
@@ -0,0 +1,71 @@
+use bumpalo::Bump;
+use roc_fmt::def::fmt_defs;
+use roc_fmt::module::fmt_module;
+use roc_fmt::spaces::RemoveSpaces;
+use roc_fmt::{Ast, Buf};
+use roc_parse::{
+    module::{self, module_defs},
+    parser::{Parser, SyntaxError},
+    state::State,
+};
+
+fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
+    let (module, state) = module::parse_header(arena, State::new(src.as_bytes()))
+        .map_err(|e| SyntaxError::Header(e.problem))?;
+
+    let (_, defs, _) = module_defs().parse(arena, state, 0).map_err(|(_, e)| e)?;
+
+    Ok(Ast { module, defs })
+}
+
+fn fmt_all<'a>(buf: &mut Buf<'a>, ast: &'a Ast) {
+    fmt_module(buf, &ast.module);
+
+    fmt_defs(buf, &ast.defs, 0);
+
+    buf.fmt_end_of_file();
+}
+
+/// Formats a whole module. Returns `None` if it does not parse, or if formatting would
+/// change its meaning; unlike `roc format`, an editor should just leave the file alone then.
+pub fn format_module(src: &str) -> Option<String> {
+    let arena = Bump::new();
+
+    let ast = arena.alloc(parse_all(&arena, src).ok()?);
+    let mut buf = Buf::new_in(&arena);
+    fmt_all(&mut buf, ast);
+
+    let reparsed_ast = parse_all(&arena, buf.as_str()).ok()?;
+
+    // Compare Debug output, for the same reason as `roc format` does
+    if format!("{:?}", ast.remove_spaces(&arena))
+        != format!("{:?}", reparsed_ast.remove_spaces(&arena))
+    {
+        return None;
+    }
+
+    Some(buf.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_module;
+
+    #[test]
+    fn formats_module() {
+        let src = "interface Foo exposes [x] imports []\n\nx =   1+2\n";
+
+        assert_eq!(
+            format_module(src).as_deref(),
+            Some("interface Foo exposes [x] imports []\n\nx = 1 + 2\n")
+        );
+    }
+
+    #[test]
+    fn leaves_syntax_errors_alone() {
+        assert_eq!(
+            format_module("interface Foo exposes [x] imports []\n\nx = (\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! A language server for Roc. It speaks the Language Server Protocol over stdio, and offers
+//! diagnostics (on open and save), hover types, go to definition, and formatting.
+mod analysis;
+mod convert;
+mod format;
+mod rpc;
+mod server;
+
+pub use server::Server;
+
+use std::io;
+
+/// Serves a single client over stdin and stdout until it sends `exit`, returning the
+/// process exit code the protocol asks for.
+pub fn run() -> io::Result<i32> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut reader = stdin.lock();
+    let mut writer = stdout.lock();
+
+    let mut server = Server::default();
+
+    while let Some(message) = rpc::read_message(&mut reader)? {
+        for outgoing in server.handle_message(message) {
+            rpc::write_message(&mut writer, &outgoing)?;
+        }
+
+        if let Some(exit_code) = server.exit_code() {
+            return Ok(exit_code);
+        }
+    }
+
+    // The client went away without asking us to exit
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,111 @@
+//! Reading and writing JSON-RPC messages using the base protocol of the Language Server
+//! Protocol: a `Content-Length` header, a blank line, and then the JSON body.
+
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+fn invalid_data(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Reads the next message, or returns `None` if the input ended cleanly between messages.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line)? == 0 {
+            return match content_length {
+                None => Ok(None),
+                Some(_) => Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+        }
+
+        let header = line.trim_end();
+
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+
+            continue;
+        }
+
+        match header.split_once(':') {
+            Some((name, value)) if name.eq_ignore_ascii_case("Content-Length") => {
+                content_length = Some(value.trim().parse::<usize>().map_err(invalid_data)?);
+            }
+            Some(_) => {
+                // Content-Type is the only other header, and it always means utf-8 JSON
+            }
+            None => return Err(invalid_data(format!("Malformed header: {:?}", header))),
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap()];
+    reader.read_exact(&mut body)?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(invalid_data)
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_vec(message).map_err(invalid_data)?;
+
+    write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let messages = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ];
+
+        let mut buf = Vec::new();
+        for message in messages.iter() {
+            write_message(&mut buf, message).unwrap();
+        }
+
+        let mut reader = buf.as_slice();
+        assert_eq!(
+            read_message(&mut reader).unwrap().as_ref(),
+            Some(&messages[0])
+        );
+        assert_eq!(
+            read_message(&mut reader).unwrap().as_ref(),
+            Some(&messages[1])
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn extra_headers() {
+        let body = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+        let input = format!(
+            "content-length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
+            body.len(),
+            body
+        );
+
+        let message = read_message(&mut input.as_bytes()).unwrap().unwrap();
+        assert_eq!(message["method"], "exit");
+    }
+
+    #[test]
+    fn truncated_body() {
+        let input = "Content-Length: 100\r\n\r\n{}";
+
+        assert!(read_message(&mut input.as_bytes()).is_err());
+    }
+}
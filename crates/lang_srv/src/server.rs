@@ -0,0 +1,268 @@
+use std::path::PathBuf;
+
+use roc_collections::MutMap;
+use roc_region::all::Position;
+use serde_json::{json, Value};
+
+use crate::analysis::{analyze, AnalyzedModule};
+use crate::convert::{path_to_uri, position_from_lsp, position_to_lsp, region_to_lsp, uri_to_path};
+use crate::format::format_module;
+
+// JSON-RPC error codes
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+struct Document {
+    path: PathBuf,
+    /// The editor's current contents, including unsaved changes
+    text: String,
+    /// The last analysis of the file on disk
+    module: Option<AnalyzedModule>,
+}
+
+/// The state of a language server session. Messages go in, and the responses and
+/// notifications to send back to the client come out.
+#[derive(Default)]
+pub struct Server {
+    documents: MutMap<String, Document>,
+    shutdown_requested: bool,
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    /// Set once the client has sent `exit`, to the code the process should exit with.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn handle_message(&mut self, message: Value) -> Vec<Value> {
+        let method = match message.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => {
+                // A response to a request of ours; we never send any.
+                return Vec::new();
+            }
+        };
+        let params = message.get("params").unwrap_or(&Value::Null);
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match self.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, msg)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": msg },
+                    }),
+                };
+
+                vec![response]
+            }
+            None => self.handle_notification(method, params),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shutdown_requested {
+            return Err((INVALID_REQUEST, "The server is shutting down".to_string()));
+        }
+
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        // 1 is Full: the client sends the whole document on every change
+                        "change": 1,
+                        "save": { "includeText": false },
+                    },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "documentFormattingProvider": true,
+                },
+                "serverInfo": {
+                    "name": "roc_lang_srv",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let (document, position) = self.document_position(params)?;
+
+                let hover = document.module.as_mut().and_then(|module| {
+                    let (region, printed) = module.type_at(position)?;
+
+                    Some(json!({
+                        "contents": {
+                            "kind": "markdown",
+                            "value": format!("```roc\n{}\n```", printed),
+                        },
+                        "range": region_to_lsp(&module.source, region),
+                    }))
+                });
+
+                Ok(hover.unwrap_or(Value::Null))
+            }
+            "textDocument/definition" => {
+                let (document, position) = self.document_position(params)?;
+                let uri = path_to_uri(&document.path);
+
+                let location = document.module.as_ref().and_then(|module| {
+                    let region = module.definition_at(position)?;
+
+                    Some(json!({ "uri": uri, "range": region_to_lsp(&module.source, region) }))
+                });
+
+                Ok(location.unwrap_or(Value::Null))
+            }
+            "textDocument/formatting" => {
+                let uri = text_document_uri(params)?;
+                let document = self.document_mut(uri)?;
+
+                let edits = match format_module(&document.text) {
+                    Some(formatted) if formatted != document.text => {
+                        let end = Position::new(document.text.len() as u32);
+
+                        json!([{
+                            "range": {
+                                "start": { "line": 0, "character": 0 },
+                                "end": position_to_lsp(&document.text, end),
+                            },
+                            "newText": formatted,
+                        }])
+                    }
+                    _ => json!([]),
+                };
+
+                Ok(edits)
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method {}", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str();
+
+        match (method, uri) {
+            ("exit", _) => {
+                self.exit_code = Some(if self.shutdown_requested { 0 } else { 1 });
+
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let path = match uri_to_path(uri) {
+                    Some(path) => path,
+                    None => return Vec::new(),
+                };
+                let text = params["textDocument"]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+
+                self.documents.insert(
+                    uri.to_string(),
+                    Document {
+                        path,
+                        text,
+                        module: None,
+                    },
+                );
+
+                self.analyze(uri)
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // With full sync, the last change holds the whole document
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+
+                if let (Ok(document), Some(text)) = (self.document_mut(uri), text) {
+                    document.text = text.to_string();
+                }
+
+                Vec::new()
+            }
+            ("textDocument/didSave", Some(uri)) => self.analyze(uri),
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(uri);
+
+                vec![publish_diagnostics(uri, Vec::new())]
+            }
+            _ => {
+                // "initialized", and anything else we can safely ignore
+                Vec::new()
+            }
+        }
+    }
+
+    /// Type-checks an open document as it is on disk, and publishes diagnostics for it and
+    /// every module it imports.
+    fn analyze(&mut self, uri: &str) -> Vec<Value> {
+        let document = match self.document_mut(uri) {
+            Ok(document) => document,
+            Err(_) => return Vec::new(),
+        };
+
+        let analysis = analyze(&document.path);
+        document.module = analysis.module;
+
+        analysis
+            .diagnostics
+            .into_iter()
+            .map(|(path, diagnostics)| publish_diagnostics(&path_to_uri(&path), diagnostics))
+            .collect()
+    }
+
+    fn document_mut(&mut self, uri: &str) -> Result<&mut Document, (i64, String)> {
+        self.documents
+            .get_mut(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("{} is not open", uri)))
+    }
+
+    /// The document and position a `TextDocumentPositionParams` refers to. The position is
+    /// resolved against the source of the last analysis, so that it lines up with the regions
+    /// the compiler knows about.
+    fn document_position(
+        &mut self,
+        params: &Value,
+    ) -> Result<(&mut Document, Position), (i64, String)> {
+        let uri = text_document_uri(params)?;
+        let line = params["position"]["line"].as_u64();
+        let character = params["position"]["character"].as_u64();
+
+        let document = self.document_mut(uri)?;
+
+        match (line, character) {
+            (Some(line), Some(character)) => {
+                let source = match &document.module {
+                    Some(module) => &module.source,
+                    None => document.text.as_str(),
+                };
+                let position = position_from_lsp(source, line as u32, character as u32);
+
+                Ok((document, position))
+            }
+            _ => Err((INVALID_PARAMS, "Missing position".to_string())),
+        }
+    }
+}
+
+fn text_document_uri(params: &Value) -> Result<&str, (i64, String)> {
+    params["textDocument"]["uri"]
+        .as_str()
+        .ok_or_else(|| (INVALID_PARAMS, "Missing textDocument.uri".to_string()))
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
@@ -0,0 +1,163 @@
+use crate::convert::path_to_uri;
+use crate::Server;
+use serde_json::{json, Value};
+use std::fs;
+
+fn request(server: &mut Server, id: i64, method: &str, params: Value) -> Value {
+    let mut responses = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    }));
+
+    assert_eq!(responses.len(), 1);
+    let response = responses.pop().unwrap();
+    assert_eq!(response["id"], id);
+
+    response
+}
+
+fn notify(server: &mut Server, method: &str, params: Value) -> Vec<Value> {
+    server.handle_message(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+}
+
+/// Opens a module written to a temporary directory, returning its URI and the diagnostics
+/// published for it.
+fn open(server: &mut Server, dir: &tempfile::TempDir, src: &str) -> (String, Value) {
+    let path = dir.path().join("Test.roc");
+    fs::write(&path, src).unwrap();
+    let uri = path_to_uri(&path);
+
+    let notifications = notify(
+        server,
+        "textDocument/didOpen",
+        json!({
+            "textDocument": { "uri": uri, "languageId": "roc", "version": 1, "text": src },
+        }),
+    );
+
+    let published = notifications
+        .into_iter()
+        .find(|n| n["params"]["uri"] == uri)
+        .expect("no diagnostics published for the opened file");
+    assert_eq!(published["method"], "textDocument/publishDiagnostics");
+
+    (uri, published["params"]["diagnostics"].clone())
+}
+
+const SRC: &str = indoc::indoc!(
+    r#"
+    interface Test
+        exposes [main]
+        imports []
+
+    double = \n -> n * 2
+
+    main = double 21
+    "#
+);
+
+#[test]
+fn initialize_and_shutdown() {
+    let mut server = Server::default();
+
+    let response = request(&mut server, 1, "initialize", json!({ "capabilities": {} }));
+    let capabilities = &response["result"]["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["documentFormattingProvider"], true);
+
+    let response = request(&mut server, 2, "textDocument/rename", json!({}));
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response = request(&mut server, 3, "shutdown", Value::Null);
+    assert_eq!(response["result"], Value::Null);
+    assert_eq!(server.exit_code(), None);
+
+    notify(&mut server, "exit", Value::Null);
+    assert_eq!(server.exit_code(), Some(0));
+}
+
+#[test]
+fn hover_and_definition() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut server = Server::default();
+
+    let (uri, diagnostics) = open(&mut server, &dir, SRC);
+    assert_eq!(diagnostics, json!([]));
+
+    // `double` in `main = double 21`
+    let position =
+        json!({ "textDocument": { "uri": uri }, "position": { "line": 6, "character": 9 } });
+
+    let response = request(&mut server, 1, "textDocument/hover", position.clone());
+    assert_eq!(
+        response["result"]["contents"]["value"],
+        "```roc\nNum a -> Num a\n```"
+    );
+    assert_eq!(
+        response["result"]["range"],
+        json!({ "start": { "line": 6, "character": 7 }, "end": { "line": 6, "character": 13 } })
+    );
+
+    let response = request(&mut server, 2, "textDocument/definition", position);
+    assert_eq!(
+        response["result"],
+        json!({
+            "uri": uri,
+            "range": { "start": { "line": 4, "character": 0 }, "end": { "line": 4, "character": 6 } },
+        })
+    );
+}
+
+#[test]
+fn diagnostics() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut server = Server::default();
+
+    let src = SRC.replace("double 21", "doubel 21");
+    let (_, diagnostics) = open(&mut server, &dir, &src);
+
+    let diagnostics = diagnostics.as_array().unwrap();
+    let unrecognized = diagnostics
+        .iter()
+        .find(|d| d["severity"] == 1)
+        .expect("no error reported");
+    assert!(unrecognized["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("UNRECOGNIZED NAME"));
+    assert_eq!(
+        unrecognized["range"],
+        json!({ "start": { "line": 6, "character": 7 }, "end": { "line": 6, "character": 13 } })
+    );
+}
+
+#[test]
+fn formatting() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut server = Server::default();
+    let (uri, _) = open(&mut server, &dir, SRC);
+
+    // Formatting works on the editor's contents, even when they haven't been saved
+    let unformatted = SRC.replace("n * 2", "n*2");
+    notify(
+        &mut server,
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": uri, "version": 2 },
+            "contentChanges": [{ "text": unformatted }],
+        }),
+    );
+
+    let params = json!({ "textDocument": { "uri": uri }, "options": {} });
+    let response = request(&mut server, 1, "textDocument/formatting", params);
+    assert_eq!(
+        response["result"],
+        json!([{
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 7, "character": 0 } },
+            "newText": SRC,
+        }])
+    );
+}
@@ -0,0 +1,232 @@
+//! Reading and patching the aarch64 instructions that refer to other code and data.
+//!
+//! Every aarch64 instruction is 4 bytes, and an address in one is always relative to the
+//! instruction itself: `B`/`BL` take an offset in words, `ADRP` an offset in 4KB pages, and
+//! the `ADD`/`LDR`/`STR` that follow an `ADRP` take the low 12 bits of the address.
+use object::elf;
+
+/// `nop`
+pub(crate) const NOP: u32 = 0xd503_201f;
+
+const BRANCH26_MASK: u32 = 0x7c00_0000;
+const BRANCH26: u32 = 0x1400_0000; // B and BL, which only differ in the top bit
+const IMM26_MASK: u32 = 0x03ff_ffff;
+
+/// The target of a `B` or `BL` instruction at `address`, or None if it's some other instruction
+pub(crate) fn branch26_target(instruction: u32, address: u64) -> Option<u64> {
+    if instruction & BRANCH26_MASK == BRANCH26 {
+        // sign-extend the 26-bit word offset
+        let words = ((instruction & IMM26_MASK) << 6) as i32 >> 6;
+
+        Some(address.wrapping_add((words as i64 * 4) as u64))
+    } else {
+        None
+    }
+}
+
+/// The `B` or `BL` instruction, with its target changed to be `offset` bytes away from it
+pub(crate) fn set_branch26(instruction: u32, offset: i64) -> Result<u32, String> {
+    let words = signed_field(offset, 4, 26, "B/BL")?;
+
+    Ok((instruction & !IMM26_MASK) | words)
+}
+
+/// `adrp xd, <the page of target>`, for an instruction at `address`
+pub(crate) fn adrp(rd: u32, address: u64, target: u64) -> Result<u32, String> {
+    set_adr(
+        0x9000_0000 | rd,
+        page_offset(address as i64, target as i64),
+        "ADRP",
+    )
+}
+
+/// `add xd, xn, #imm12`
+pub(crate) fn add_imm12(rd: u32, rn: u32, imm12: u32) -> u32 {
+    0x9100_0000 | ((imm12 & 0xfff) << 10) | (rn << 5) | rd
+}
+
+/// `br xn`
+pub(crate) fn br(rn: u32) -> u32 {
+    0xd61f_0000 | (rn << 5)
+}
+
+/// Applies a relocation that changes part of an instruction, e.g. `R_AARCH64_CALL26`, where
+/// `target` is the address the relocation refers to (S + A) and `place` is the address of the
+/// instruction (P). Returns the patched instruction.
+///
+/// Loads from the GOT are relaxed into computing the address directly, since an app's
+/// symbols are at fixed offsets from its code once it's linked into the host.
+pub(crate) fn relocate(
+    r_type: u32,
+    instruction: u32,
+    target: i64,
+    place: i64,
+) -> Result<u32, String> {
+    let lo12 = (target & 0xfff) as u32;
+
+    match r_type {
+        elf::R_AARCH64_CALL26 | elf::R_AARCH64_JUMP26 => set_branch26(instruction, target - place),
+        elf::R_AARCH64_CONDBR19 => {
+            let words = signed_field(target - place, 4, 19, "B.cond")?;
+
+            Ok((instruction & !(0x7ffff << 5)) | (words << 5))
+        }
+        elf::R_AARCH64_ADR_PREL_LO21 => set_adr(instruction, target - place, "ADR"),
+        elf::R_AARCH64_ADR_PREL_PG_HI21
+        | elf::R_AARCH64_ADR_PREL_PG_HI21_NC
+        | elf::R_AARCH64_ADR_GOT_PAGE => set_adr(instruction, page_offset(place, target), "ADRP"),
+        elf::R_AARCH64_ADD_ABS_LO12_NC => Ok(set_imm12(instruction, lo12)),
+        elf::R_AARCH64_LDST8_ABS_LO12_NC => Ok(set_imm12(instruction, lo12)),
+        elf::R_AARCH64_LDST16_ABS_LO12_NC => scaled_imm12(instruction, lo12, 1),
+        elf::R_AARCH64_LDST32_ABS_LO12_NC => scaled_imm12(instruction, lo12, 2),
+        elf::R_AARCH64_LDST64_ABS_LO12_NC => scaled_imm12(instruction, lo12, 3),
+        elf::R_AARCH64_LDST128_ABS_LO12_NC => scaled_imm12(instruction, lo12, 4),
+        elf::R_AARCH64_LD64_GOT_LO12_NC => {
+            // `ldr xt, [xn, #:got_lo12:sym]` becomes `add xt, xn, #:lo12:sym`
+            const LDR_X_UNSIGNED_OFFSET: u32 = 0xf940_0000;
+
+            if instruction & 0xffc0_0000 != LDR_X_UNSIGNED_OFFSET {
+                return Err(format!(
+                    "expected an LDR for R_AARCH64_LD64_GOT_LO12_NC, but found {instruction:#010x}"
+                ));
+            }
+
+            Ok(add_imm12(
+                instruction & 0x1f,
+                (instruction >> 5) & 0x1f,
+                lo12,
+            ))
+        }
+        other => Err(format!(
+            "aarch64 relocation type {other} is not yet supported"
+        )),
+    }
+}
+
+/// How many 4KB pages away the page of `target` is from the page of `place`
+fn page_offset(place: i64, target: i64) -> i64 {
+    (target & !0xfff) - (place & !0xfff)
+}
+
+/// Sets the 21-bit immediate of an `ADR` (in bytes) or `ADRP` (in pages)
+fn set_adr(instruction: u32, offset: i64, name: &str) -> Result<u32, String> {
+    let imm21 = if instruction & 0x8000_0000 == 0 {
+        signed_field(offset, 1, 21, name)?
+    } else {
+        signed_field(offset, 0x1000, 21, name)?
+    };
+
+    let immlo = imm21 & 0b11;
+    let immhi = imm21 >> 2;
+
+    Ok((instruction & !((0b11 << 29) | (0x7ffff << 5))) | (immlo << 29) | (immhi << 5))
+}
+
+fn set_imm12(instruction: u32, imm12: u32) -> u32 {
+    (instruction & !(0xfff << 10)) | ((imm12 & 0xfff) << 10)
+}
+
+/// Loads and stores of 2^scale bytes count their offset in units of that size
+fn scaled_imm12(instruction: u32, lo12: u32, scale: u32) -> Result<u32, String> {
+    if lo12 & ((1 << scale) - 1) != 0 {
+        return Err(format!(
+            "the address {lo12:#x} is not aligned to {} bytes for a load or store",
+            1 << scale
+        ));
+    }
+
+    Ok(set_imm12(instruction, lo12 >> scale))
+}
+
+/// `value / unit` as a signed field of `bits` bits, if it fits exactly
+fn signed_field(value: i64, unit: i64, bits: u32, name: &str) -> Result<u32, String> {
+    let max = 1i64 << (bits - 1);
+
+    if value % unit != 0 {
+        return Err(format!(
+            "{name} offset {value:#x} is not a multiple of {unit}"
+        ));
+    }
+
+    let units = value / unit;
+
+    if units < -max || units >= max {
+        return Err(format!("{name} offset {value:#x} is out of range"));
+    }
+
+    Ok((units as u32) & ((1 << bits) - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The expected instructions are from `llvm-mc -triple=aarch64 -show-encoding`
+
+    #[test]
+    fn branches() {
+        // bl #0x100
+        assert_eq!(branch26_target(0x9400_0040, 0x1000), Some(0x1100));
+        // b #-0x8
+        assert_eq!(branch26_target(0x17ff_fffe, 0x1000), Some(0xff8));
+        // ret
+        assert_eq!(branch26_target(0xd65f_03c0, 0x1000), None);
+
+        assert_eq!(set_branch26(0x9400_0000, -0x8), Ok(0x97ff_fffe));
+        assert!(set_branch26(0x9400_0000, 1 << 27).is_err());
+        assert!(set_branch26(0x9400_0000, 2).is_err());
+    }
+
+    #[test]
+    fn plt_stub() {
+        // adrp x16, #0x3000 (the page 3 pages after the one the instruction is in)
+        assert_eq!(adrp(16, 0x10ff8, 0x13abc), Ok(0xf000_0010));
+        // adrp x16, #-0x1000
+        assert_eq!(adrp(16, 0x10000, 0xf004), Ok(0xf0ff_fff0));
+        // add x16, x16, #0xabc
+        assert_eq!(add_imm12(16, 16, 0xabc), 0x912a_f210);
+        // br x16
+        assert_eq!(br(16), 0xd61f_0200);
+    }
+
+    #[test]
+    fn relocations() {
+        // adrp x0, #0 and add x0, x0, #0
+        let (adrp_x0, add_x0) = (0x9000_0000, 0x9100_0000);
+
+        assert_eq!(
+            relocate(elf::R_AARCH64_ADR_PREL_PG_HI21, adrp_x0, 0x2_1234, 0x1_0000),
+            Ok(0xb000_0080) // adrp x0, #0x11000
+        );
+        assert_eq!(
+            relocate(elf::R_AARCH64_ADD_ABS_LO12_NC, add_x0, 0x2_1234, 0x1_0004),
+            Ok(0x9108_d000) // add x0, x0, #0x234
+        );
+        assert_eq!(
+            relocate(elf::R_AARCH64_CALL26, 0x9400_0000, 0x2000, 0x1000),
+            Ok(0x9400_0400) // bl #0x1000
+        );
+        assert_eq!(
+            // ldr x1, [x2]
+            relocate(
+                elf::R_AARCH64_LDST64_ABS_LO12_NC,
+                0xf940_0041,
+                0x2_0238,
+                0x1_0000
+            ),
+            Ok(0xf941_1c41) // ldr x1, [x2, #0x238]
+        );
+        assert!(relocate(elf::R_AARCH64_LDST64_ABS_LO12_NC, 0xf940_0041, 0x2_0234, 0).is_err());
+        assert_eq!(
+            // ldr x3, [x3] becomes add x3, x3, #0x234
+            relocate(
+                elf::R_AARCH64_LD64_GOT_LO12_NC,
+                0xf940_0063,
+                0x2_1234,
+                0x1_0000
+            ),
+            Ok(0x9108_d063)
+        );
+        assert!(relocate(elf::R_AARCH64_TLSDESC_CALL, 0, 0, 0).is_err());
+    }
+}
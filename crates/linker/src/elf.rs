@@ -15,6 +15,7 @@ use std::os::raw::c_char;
 use std::path::Path;
 use std::time::{Duration, Instant};
 
+use crate::aarch64;
 use crate::metadata::{self, Metadata, VirtualOffset};
 
 use crate::{
@@ -24,14 +25,119 @@ use crate::{
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
-// TODO: Analyze if this offset is always correct.
-const PLT_ADDRESS_OFFSET: u64 = 0x10;
+/// The instruction sets of the ELF hosts we can do surgery on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Machine {
+    X86_64,
+    Aarch64,
+}
+
+impl Machine {
+    fn of(elf_data: &[u8]) -> Self {
+        let file_header = load_struct_inplace::<elf::FileHeader64<LE>>(elf_data, 0);
+
+        match file_header.e_machine.get(NativeEndian) {
+            elf::EM_X86_64 => Machine::X86_64,
+            elf::EM_AARCH64 => Machine::Aarch64,
+            other => {
+                user_error!("The surgical linker does not support ELF hosts for machine type {other} yet.\nPlease use `--linker=legacy` for now.");
+            }
+        }
+    }
+
+    /// The code at the start of the PLT that calls the dynamic linker, before the stubs for
+    /// each function
+    fn plt_header_size(self) -> u64 {
+        match self {
+            Machine::X86_64 => 16,
+            Machine::Aarch64 => 32,
+        }
+    }
+
+    // TODO: Analyze if this size is always correct (e.g. with IBT or BTI, PLT entries get bigger).
+    fn plt_entry_size(self) -> u64 {
+        16
+    }
+
+    fn r_glob_dat(self) -> u32 {
+        match self {
+            Machine::X86_64 => elf::R_X86_64_GLOB_DAT,
+            Machine::Aarch64 => elf::R_AARCH64_GLOB_DAT,
+        }
+    }
+
+    fn r_jump_slot(self) -> u32 {
+        match self {
+            Machine::X86_64 => elf::R_X86_64_JUMP_SLOT,
+            Machine::Aarch64 => elf::R_AARCH64_JUMP_SLOT,
+        }
+    }
+
+    fn r_relative(self) -> u32 {
+        match self {
+            Machine::X86_64 => elf::R_X86_64_RELATIVE,
+            Machine::Aarch64 => elf::R_AARCH64_RELATIVE,
+        }
+    }
+
+    fn r_irelative(self) -> u32 {
+        match self {
+            Machine::X86_64 => elf::R_X86_64_IRELATIVE,
+            Machine::Aarch64 => elf::R_AARCH64_IRELATIVE,
+        }
+    }
+
+    fn r_abs64(self) -> u32 {
+        match self {
+            Machine::X86_64 => elf::R_X86_64_64,
+            Machine::Aarch64 => elf::R_AARCH64_ABS64,
+        }
+    }
+
+    /// Whether a dynamic relocation of this type against an app function has to become a
+    /// relative one, because the dynamic linker would otherwise look the function up by name
+    /// when loading the executable. The app's functions don't end up in the executable's symbol
+    /// hash table, so that lookup would fail.
+    ///
+    /// Jump slots are only resolved at load time with BIND_NOW (e.g. `-z now` for full RELRO).
+    /// Otherwise they're resolved the first time the PLT stub is called, which never happens
+    /// because the stubs get replaced by jumps to the app.
+    fn is_app_symbol_relocation(self, r_type: u32, bind_now: bool) -> bool {
+        r_type == self.r_glob_dat()
+            || r_type == self.r_abs64()
+            || (bind_now && r_type == self.r_jump_slot())
+    }
+
+    /// Everything after the program headers gets shifted by a multiple of this, to make room for
+    /// more program headers.
+    fn shift_alignment(self) -> u64 {
+        match self {
+            Machine::X86_64 => MIN_SECTION_ALIGNMENT as u64,
+            // ADRP addresses are relative to the 4KB page the instruction is in, so the code using
+            // them has to move by whole pages to keep pointing at the same data.
+            Machine::Aarch64 => 0x1000,
+        }
+    }
+
+    /// For a relocation that patches part of an instruction rather than a whole number,
+    /// its ELF relocation type.
+    fn instruction_relocation(self, relocation: &object::Relocation) -> Option<u32> {
+        match (self, relocation.kind()) {
+            (Machine::Aarch64, RelocationKind::PltRelative) if relocation.size() == 26 => {
+                Some(elf::R_AARCH64_CALL26)
+            }
+            (Machine::Aarch64, RelocationKind::Elf(r_type)) => Some(r_type),
+            _ => None,
+        }
+    }
+}
 
 struct ElfDynamicDeps {
     got_app_syms: Vec<(String, usize)>,
     got_sections: Vec<(usize, usize)>,
     dynamic_lib_count: usize,
     shared_lib_index: usize,
+    bind_now: bool,
 }
 
 // struct MachoDynamicDeps {
@@ -101,13 +207,18 @@ fn collect_roc_definitions<'a>(object: &object::File<'a, &'a [u8]>) -> MutMap<St
 }
 
 struct Surgeries<'a> {
+    machine: Machine,
     surgeries: MutMap<String, Vec<metadata::SurgeryEntry>>,
     app_func_addresses: MutMap<u64, &'a str>,
     indirect_warning_given: bool,
 }
 
 impl<'a> Surgeries<'a> {
-    fn new(application_symbols: &[Symbol], app_func_addresses: MutMap<u64, &'a str>) -> Self {
+    fn new(
+        machine: Machine,
+        application_symbols: &[Symbol],
+        app_func_addresses: MutMap<u64, &'a str>,
+    ) -> Self {
         let mut surgeries = MutMap::default();
 
         // for each symbol that the host expects from the application
@@ -118,6 +229,7 @@ impl<'a> Surgeries<'a> {
         }
 
         Self {
+            machine,
             surgeries,
             app_func_addresses,
             indirect_warning_given: false,
@@ -178,7 +290,32 @@ impl<'a> Surgeries<'a> {
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
-        let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
+
+        match self.machine {
+            Machine::X86_64 => self.append_x86_64_branches(
+                object_bytes,
+                sec,
+                file_offset,
+                compressed,
+                &data,
+                verbose,
+            ),
+            Machine::Aarch64 => {
+                self.append_aarch64_branches(sec, file_offset, compressed, &data, verbose)
+            }
+        }
+    }
+
+    fn append_x86_64_branches(
+        &mut self,
+        object_bytes: &[u8],
+        sec: &Section,
+        file_offset: u64,
+        compressed: bool,
+        data: &[u8],
+        verbose: bool,
+    ) {
+        let mut decoder = Decoder::with_ip(64, data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
         while decoder.can_decode() {
@@ -269,6 +406,56 @@ impl<'a> Surgeries<'a> {
             }
         }
     }
+
+    /// On aarch64, calls and tail calls to app functions are `BL`s and `B`s to their PLT stubs.
+    /// Calls through a function pointer load it from the GOT instead, which the dynamic
+    /// relocations take care of.
+    fn append_aarch64_branches(
+        &mut self,
+        sec: &Section,
+        file_offset: u64,
+        compressed: bool,
+        data: &[u8],
+        verbose: bool,
+    ) {
+        for (i, bytes) in data.chunks_exact(4).enumerate() {
+            let address = sec.address() + 4 * i as u64;
+            let instruction = u32::from_le_bytes(bytes.try_into().unwrap());
+
+            let func_name = match aarch64::branch26_target(instruction, address)
+                .and_then(|target| self.app_func_addresses.get(&target))
+            {
+                Some(func_name) => *func_name,
+                None => continue,
+            };
+
+            if compressed {
+                internal_error!(
+                    "Surgical linking does not work with compressed text sections: {:+x?}",
+                    sec
+                );
+            }
+
+            let offset = file_offset + 4 * i as u64;
+
+            if verbose {
+                println!(
+                    "Found branch from {:+x} to {}, at file offset {:+x}: {:#010x}",
+                    address, func_name, offset, instruction
+                );
+            }
+
+            // The whole instruction gets patched, since the offset is only part of it
+            self.surgeries
+                .get_mut(func_name)
+                .unwrap()
+                .push(metadata::SurgeryEntry {
+                    file_offset: offset,
+                    virtual_offset: VirtualOffset::Relative(address),
+                    size: 4,
+                });
+        }
+    }
 }
 
 /// Constructs a `metadata::Metadata` from a host executable binary, and writes it to disk
@@ -291,6 +478,8 @@ pub(crate) fn preprocess_elf(
         }
     };
 
+    let machine = Machine::of(exec_data);
+
     let mut md = metadata::Metadata {
         roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
         ..Default::default()
@@ -328,29 +517,38 @@ pub(crate) fn preprocess_elf(
     // procedures/functions whose address isn't known in the time of linking, and is left
     // to be resolved by the dynamic linker at run time.
     let symbol_and_plt_processing_start = Instant::now();
-    let plt_section_name = ".plt";
-    let (plt_address, plt_offset) = match exec_obj.section_by_name(plt_section_name) {
-        Some(section) => {
-            let file_offset = match section.compressed_file_range() {
-                Ok(
-                    range @ CompressedFileRange {
-                        format: CompressionFormat::None,
-                        ..
-                    },
-                ) => range.offset,
-                _ => {
-                    internal_error!("Surgical linking does not work with compressed plt section");
-                }
-            };
-            (section.address(), file_offset)
-        }
-        None => {
-            internal_error!("Failed to find PLT section. Probably an malformed executable.");
-        }
+    //
+    // With Intel CET (e.g. `-fcf-protection`), calls go through the stubs in `.plt.sec` instead,
+    // which don't start with a header. A host that only calls functions through the GOT
+    // (e.g. built with `-fno-plt`) might not have a PLT at all.
+    let plt = match exec_obj.section_by_name(".plt.sec") {
+        Some(section) => Some((section, 0)),
+        None => exec_obj
+            .section_by_name(".plt")
+            .map(|section| (section, machine.plt_header_size())),
     };
+    let plt = plt.map(|(section, header_size)| {
+        let file_offset = match section.compressed_file_range() {
+            Ok(
+                range @ CompressedFileRange {
+                    format: CompressionFormat::None,
+                    ..
+                },
+            ) => range.offset,
+            _ => {
+                internal_error!("Surgical linking does not work with compressed plt section");
+            }
+        };
+        (section.address(), file_offset, header_size)
+    });
     if verbose {
-        println!("PLT Address: {:+x}", plt_address);
-        println!("PLT File Offset: {:+x}", plt_offset);
+        match plt {
+            Some((plt_address, plt_offset, _)) => {
+                println!("PLT Address: {:+x}", plt_address);
+                println!("PLT File Offset: {:+x}", plt_offset);
+            }
+            None => println!("No PLT"),
+        }
     }
 
     let app_syms: Vec<_> = exec_obj
@@ -367,17 +565,22 @@ pub(crate) fn preprocess_elf(
                 }
             })
             .filter_map(|(_, reloc)| {
-                if let RelocationKind::Elf(7) = reloc.kind() {
+                if reloc.kind() == RelocationKind::Elf(machine.r_jump_slot()) {
                     Some(reloc)
                 } else {
                     None
                 }
             });
     for (i, reloc) in plt_relocs.enumerate() {
+        let (plt_address, plt_offset, plt_header_size) = match plt {
+            Some(plt) => plt,
+            None => break,
+        };
         for symbol in app_syms.iter() {
             if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
-                let func_address = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_address;
-                let func_offset = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_offset;
+                let plt_entry_offset = plt_header_size + i as u64 * machine.plt_entry_size();
+                let func_address = plt_entry_offset + plt_address;
+                let func_offset = plt_entry_offset + plt_offset;
                 app_func_addresses.insert(func_address, symbol.name().unwrap());
                 md.plt_addresses.insert(
                     symbol.name().unwrap().to_string(),
@@ -408,7 +611,7 @@ pub(crate) fn preprocess_elf(
     // look at the text (i.e. code) sections and see collect work needs to be done
     let text_disassembly_start = Instant::now();
 
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
+    let mut surgeries = Surgeries::new(machine, &app_syms, app_func_addresses);
     surgeries.append_text_sections(exec_data, &exec_obj, verbose);
     md.surgeries = surgeries.surgeries;
 
@@ -426,8 +629,9 @@ pub(crate) fn preprocess_elf(
                 got_sections,
                 dynamic_lib_count,
                 shared_lib_index,
+                bind_now,
             } = scan_elf_dynamic_deps(
-                &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
+                &exec_obj, machine, &mut md, &app_syms, shared_lib, exec_data, verbose,
             );
 
             scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
@@ -437,12 +641,14 @@ pub(crate) fn preprocess_elf(
             // TODO little endian
             gen_elf_le(
                 exec_data,
+                machine,
                 &mut md,
                 preprocessed_path,
                 &got_app_syms,
                 &got_sections,
                 dynamic_lib_count,
                 shared_lib_index,
+                bind_now,
                 verbose,
             )
         }
@@ -506,12 +712,14 @@ pub(crate) fn preprocess_elf(
 #[allow(clippy::too_many_arguments)]
 fn gen_elf_le(
     exec_data: &[u8],
+    machine: Machine,
     md: &mut metadata::Metadata,
     preprocessed_path: &Path,
     got_app_syms: &[(String, usize)],
     got_sections: &[(usize, usize)],
     dynamic_lib_count: usize,
     shared_lib_index: usize,
+    bind_now: bool,
     verbose: bool,
 ) -> MmapMut {
     let exec_header = load_struct_inplace::<elf::FileHeader64<LE>>(exec_data, 0);
@@ -536,7 +744,7 @@ fn gen_elf_le(
     let added_header_count = 2;
     md.added_byte_count = ph_ent_size as u64 * added_header_count;
     md.added_byte_count = md.added_byte_count
-        + (MIN_SECTION_ALIGNMENT as u64 - md.added_byte_count % MIN_SECTION_ALIGNMENT as u64);
+        + (machine.shift_alignment() - md.added_byte_count % machine.shift_alignment());
     let ph_end = ph_offset as usize + ph_num as usize * ph_ent_size as usize;
     let physical_shift_start = ph_end as u64;
 
@@ -672,20 +880,27 @@ fn gen_elf_le(
             let r_offset = rel.r_offset.get(NativeEndian);
             if virtual_shift_start <= r_offset {
                 rel.r_offset = endian::U64::new(LE, r_offset + md.added_byte_count);
-                // Deal with potential adjusts to absolute jumps.
-                // TODO: Verify other relocation types.
-                if rel.r_type(LE, false) == elf::R_X86_64_RELATIVE {
-                    let r_addend = rel.r_addend.get(LE);
+            }
+            // Deal with potential adjusts to absolute addresses, e.g. pointers to functions and
+            // data in position-independent executables. What moves is decided by where the
+            // address points, not by where it's stored.
+            // TODO: Verify other relocation types.
+            let r_type = rel.r_type(NativeEndian, false);
+            if r_type == machine.r_relative() || r_type == machine.r_irelative() {
+                let r_addend = rel.r_addend.get(LE);
+                if virtual_shift_start as i64 <= r_addend {
                     rel.r_addend.set(LE, r_addend + md.added_byte_count as i64);
                 }
             }
             // If the relocation goes to a roc function, we need to surgically link it and change it to relative.
-            let r_type = rel.r_type(NativeEndian, false);
-            if r_type == elf::R_X86_64_GLOB_DAT {
+            if machine.is_app_symbol_relocation(r_type, bind_now) {
                 let r_sym = rel.r_sym(NativeEndian, false);
                 for (name, index) in got_app_syms.iter() {
                     if *index as u32 == r_sym {
-                        rel.set_r_info(LE, false, 0, elf::R_X86_64_RELATIVE);
+                        if rel.r_addend.get(LE) != 0 {
+                            user_error!("The surgical linker does not support relocations to an offset from {name} yet.\nPlease use `--linker=legacy` for now.");
+                        }
+                        rel.set_r_info(LE, false, 0, machine.r_relative());
                         let addend_addr = sec_offset as usize
                             + i * mem::size_of::<elf::Rela64<LE>>()
                             // This 16 skips the first 2 fields and gets to the addend field.
@@ -814,6 +1029,7 @@ fn gen_elf_le(
 
 fn scan_elf_dynamic_deps(
     exec_obj: &object::File,
+    machine: Machine,
     md: &mut metadata::Metadata,
     app_syms: &[Symbol],
     shared_lib: &Path,
@@ -856,6 +1072,7 @@ fn scan_elf_dynamic_deps(
 
     let mut dyn_lib_index = 0;
     let mut shared_lib_index = None;
+    let mut bind_now = false;
     loop {
         let dyn_tag = u64::from_le_bytes(
             <[u8; 8]>::try_from(
@@ -863,16 +1080,22 @@ fn scan_elf_dynamic_deps(
             )
             .unwrap(),
         );
+        let dyn_val = u64::from_le_bytes(
+            <[u8; 8]>::try_from(
+                &exec_data
+                    [dyn_offset + dyn_lib_index * 16 + 8..dyn_offset + dyn_lib_index * 16 + 16],
+            )
+            .unwrap(),
+        );
         if dyn_tag == 0 {
             break;
+        } else if dyn_tag == elf::DT_BIND_NOW as u64
+            || (dyn_tag == elf::DT_FLAGS as u64 && dyn_val & elf::DF_BIND_NOW as u64 != 0)
+            || (dyn_tag == elf::DT_FLAGS_1 as u64 && dyn_val & elf::DF_1_NOW as u64 != 0)
+        {
+            bind_now = true;
         } else if dyn_tag == 1 {
-            let dynstr_off = u64::from_le_bytes(
-                <[u8; 8]>::try_from(
-                    &exec_data
-                        [dyn_offset + dyn_lib_index * 16 + 8..dyn_offset + dyn_lib_index * 16 + 16],
-                )
-                .unwrap(),
-            ) as usize;
+            let dynstr_off = dyn_val as usize;
             let c_buf: *const c_char = dynstr_data[dynstr_off..].as_ptr() as *const i8;
             let c_str = unsafe { CStr::from_ptr(c_buf) }.to_str().unwrap();
             if Path::new(c_str).file_name() == shared_lib_filename {
@@ -960,7 +1183,12 @@ fn scan_elf_dynamic_deps(
         }
     })
     .filter_map(|(_, reloc)| {
-        if let RelocationKind::Elf(6) = reloc.kind() {
+        let r_type = match reloc.kind() {
+            RelocationKind::Elf(r_type) => r_type,
+            RelocationKind::Absolute if reloc.size() == 64 => machine.r_abs64(),
+            _ => return None,
+        };
+        if machine.is_app_symbol_relocation(r_type, bind_now) {
             for symbol in app_syms.iter() {
                 if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                     return Some((symbol.name().unwrap().to_string(), symbol.index().0));
@@ -971,11 +1199,16 @@ fn scan_elf_dynamic_deps(
     })
     .collect();
 
+    if verbose {
+        println!("Binds symbols when loaded: {}", bind_now);
+    }
+
     ElfDynamicDeps {
         got_app_syms,
         got_sections,
         dynamic_lib_count,
         shared_lib_index,
+        bind_now,
     }
 }
 
@@ -1079,6 +1312,7 @@ fn surgery_elf_help(
     if !elf64 || !litte_endian {
         internal_error!("Only 64bit little endian elf currently supported for surgery");
     }
+    let machine = Machine::of(exec_mmap);
     let exec_header = load_struct_inplace::<elf::FileHeader64<LE>>(exec_mmap, 0);
 
     let ph_offset = exec_header.e_phoff.get(NativeEndian);
@@ -1272,6 +1506,28 @@ fn surgery_elf_help(
                     if let Some(target_offset) = target_offset {
                         let virt_base = section_virtual_offset as usize + rel.0 as usize;
                         let base = section_offset as usize + rel.0 as usize;
+
+                        if let Some(r_type) = machine.instruction_relocation(&rel.1) {
+                            let bytes = &mut exec_mmap[base..][..4];
+                            let instruction = u32::from_le_bytes((&*bytes).try_into().unwrap());
+                            let patched = aarch64::relocate(
+                                r_type,
+                                instruction,
+                                target_offset + rel.1.addend(),
+                                virt_base as i64,
+                            )
+                            .unwrap_or_else(|problem| {
+                                internal_error!("Failed to relocate {:+x?}: {problem}", rel)
+                            });
+                            if verbose {
+                                println!(
+                                    "\t\tPatched instruction at {base:+x} (virt: {virt_base:+x}): {instruction:#010x} -> {patched:#010x}",
+                                );
+                            }
+                            bytes.copy_from_slice(&patched.to_le_bytes());
+                            continue;
+                        }
+
                         let target: i64 = match rel.1.kind() {
                             RelocationKind::Relative | RelocationKind::PltRelative => {
                                 target_offset - virt_base as i64 + rel.1.addend()
@@ -1446,6 +1702,27 @@ fn surgery_elf_help(
                 VirtualOffset::Absolute => 0,
             };
             match s.size {
+                4 if machine == Machine::Aarch64 => {
+                    // A B or BL instruction; see Surgeries::append_aarch64_branches
+                    let file_offset = (s.file_offset + md.added_byte_count) as usize;
+                    let bytes = &mut exec_mmap[file_offset..][..4];
+                    let instruction = u32::from_le_bytes((&*bytes).try_into().unwrap());
+                    let target = func_virt_offset as i64 - surgery_virt_offset;
+                    match aarch64::set_branch26(instruction, target) {
+                        Ok(patched) => {
+                            if verbose {
+                                println!("\tTarget Jump: {:+x}", target);
+                            }
+                            bytes.copy_from_slice(&patched.to_le_bytes());
+                        }
+                        Err(problem) => {
+                            // The call keeps going through the PLT stub, which gets patched below
+                            if verbose {
+                                println!("\tLeaving the branch to the PLT: {problem}");
+                            }
+                        }
+                    }
+                }
                 4 => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
                     if verbose {
@@ -1475,18 +1752,42 @@ fn surgery_elf_help(
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
             let plt_off = (*plt_off + md.added_byte_count) as usize;
             let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
-            if verbose {
-                println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
-                println!("\tTarget Jump: {:+x}", target);
-            }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+            match machine {
+                Machine::X86_64 => {
+                    let jmp_inst_len = 5;
+                    let target =
+                        (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                    if verbose {
+                        println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
+                        println!("\tTarget Jump: {:+x}", target);
+                    }
+                    let data = target.to_le_bytes();
+                    exec_mmap[plt_off] = 0xE9;
+                    exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
+                    for i in jmp_inst_len..machine.plt_entry_size() as usize {
+                        exec_mmap[plt_off + i] = 0x90;
+                    }
+                }
+                Machine::Aarch64 => {
+                    // adrp x16, func; add x16, x16, :lo12:func; br x16
+                    // (x16 is the register that PLT stubs are allowed to clobber)
+                    let adrp = aarch64::adrp(16, plt_vaddr, func_virt_offset)
+                        .unwrap_or_else(|problem| internal_error!("{func_name}: {problem}"));
+                    let stub = [
+                        adrp,
+                        aarch64::add_imm12(16, 16, (func_virt_offset & 0xfff) as u32),
+                        aarch64::br(16),
+                        aarch64::NOP,
+                    ];
+                    if verbose {
+                        println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
+                        println!("\tPLT stub: {:#010x?}", stub);
+                    }
+                    for (i, instruction) in stub.iter().enumerate() {
+                        exec_mmap[plt_off + 4 * i..][..4]
+                            .copy_from_slice(&instruction.to_le_bytes());
+                    }
+                }
             }
         }
 
@@ -1574,8 +1875,9 @@ mod tests {
         )
     }
 
+    /// `host_flags` are extra flags for building the host, e.g. for the linker
     #[allow(dead_code)]
-    fn zig_host_app_help(dir: &Path, target: &Triple) {
+    fn zig_host_app_help(dir: &Path, target: &Triple, host_flags: &[&str]) {
         let host_zig = indoc!(
             r#"
             const std = @import("std");
@@ -1600,6 +1902,7 @@ mod tests {
         );
 
         let zig = std::env::var("ROC_ZIG").unwrap_or_else(|_| "zig".into());
+        let zig_target = format!("{}-linux-gnu", target.architecture);
 
         std::fs::write(dir.join("host.zig"), host_zig.as_bytes()).unwrap();
         std::fs::write(dir.join("app.zig"), app_zig.as_bytes()).unwrap();
@@ -1612,7 +1915,7 @@ mod tests {
                 "app.zig",
                 "-fPIC",
                 "-target",
-                "x86_64-linux-gnu",
+                &zig_target,
                 "-OReleaseFast",
             ])
             .output()
@@ -1641,7 +1944,7 @@ mod tests {
                 .collect()
         };
 
-        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names).unwrap();
+        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names, target).unwrap();
         std::fs::write(dir.join("libapp.so"), dylib_bytes).unwrap();
 
         // now we can compile the host (it uses libapp.so, hence the order here)
@@ -1654,9 +1957,10 @@ mod tests {
                 "-fPIE",
                 "-lc",
                 "-target",
-                "x86_64-linux-gnu",
+                &zig_target,
                 "-OReleaseFast",
             ])
+            .args(host_flags)
             .output()
             .unwrap();
 
@@ -1700,7 +2004,11 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let dir = dir.path();
 
-        zig_host_app_help(dir, &Triple::from_str("x86_64-unknown-linux-musl").unwrap());
+        zig_host_app_help(
+            dir,
+            &Triple::from_str("x86_64-unknown-linux-musl").unwrap(),
+            &[],
+        );
 
         let output = std::process::Command::new(&dir.join("final"))
             .current_dir(dir)
@@ -1720,4 +2028,122 @@ mod tests {
 
         assert_eq!("Hello foo\n", output);
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_full_relro() {
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        // The defaults for Rust hosts: a position-independent executable, and the dynamic linker
+        // resolves every symbol at startup so that the GOT can be made read-only afterwards.
+        zig_host_app_help(
+            dir,
+            &Triple::from_str("x86_64-unknown-linux-gnu").unwrap(),
+            &["-z", "relro", "-z", "now"],
+        );
+
+        let final_bytes = std::fs::read(dir.join("final")).unwrap();
+        let final_obj = object::File::parse(&*final_bytes).unwrap();
+
+        assert_eq!(final_obj.kind(), object::ObjectKind::Dynamic);
+
+        // The dynamic linker must not try to look up roc_magic1 by name anymore
+        let roc_magic1 = final_obj
+            .dynamic_symbols()
+            .find(|sym| sym.name() == Ok("roc_magic1"))
+            .unwrap();
+        let app_relocations = final_obj
+            .dynamic_relocations()
+            .unwrap()
+            .filter(|(_, reloc)| reloc.target() == RelocationTarget::Symbol(roc_magic1.index()))
+            .count();
+
+        assert_eq!(app_relocations, 0);
+
+        let output = std::process::Command::new(&dir.join("final"))
+            .current_dir(dir)
+            .output()
+            .unwrap();
+
+        if !output.status.success() {
+            use std::io::Write;
+
+            std::io::stdout().write_all(&output.stdout).unwrap();
+            std::io::stderr().write_all(&output.stderr).unwrap();
+
+            panic!("app.exe failed");
+        }
+
+        assert_eq!("Hello foo\n", String::from_utf8_lossy(&output.stdout));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_aarch64() {
+        use object::ObjectSegment;
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        zig_host_app_help(
+            dir,
+            &Triple::from_str("aarch64-unknown-linux-gnu").unwrap(),
+            &[],
+        );
+
+        let final_bytes = std::fs::read(dir.join("final")).unwrap();
+        let final_obj = object::File::parse(&*final_bytes).unwrap();
+
+        assert_eq!(final_obj.architecture(), object::Architecture::Aarch64);
+
+        // roc_magic1 is now defined in the executable, in the app's new text segment
+        let roc_magic1 = final_obj
+            .dynamic_symbols()
+            .find(|sym| sym.name() == Ok("roc_magic1"))
+            .unwrap();
+        let app_text = final_obj
+            .segments()
+            .last()
+            .map(|segment| segment.address()..segment.address() + segment.size())
+            .unwrap();
+
+        assert!(app_text.contains(&roc_magic1.address()));
+
+        // and the host calls it directly, rather than through its PLT stub
+        let calls_roc_magic1 = final_obj
+            .sections()
+            .filter(|sec| sec.name() == Ok(".text"))
+            .flat_map(|sec| {
+                let data = sec.data().unwrap().to_vec();
+                let address = sec.address();
+
+                data.chunks_exact(4)
+                    .enumerate()
+                    .filter_map(|(i, bytes)| {
+                        let instruction = u32::from_le_bytes(bytes.try_into().unwrap());
+
+                        aarch64::branch26_target(instruction, address + 4 * i as u64)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .any(|target| target == roc_magic1.address());
+
+        assert!(calls_roc_magic1);
+
+        // Without an aarch64 machine, the executable can still run in qemu's user mode.
+        // (With a dynamically linked glibc, QEMU_LD_PREFIX has to point at an aarch64 sysroot.)
+        let qemu = std::env::var("ROC_QEMU_AARCH64").unwrap_or_else(|_| "qemu-aarch64".into());
+
+        if let Ok(output) = std::process::Command::new(&qemu)
+            .arg(dir.join("final"))
+            .current_dir(dir)
+            .output()
+        {
+            assert_eq!("Hello foo\n", String::from_utf8_lossy(&output.stdout));
+        }
+    }
 }
@@ -1,10 +1,20 @@
 use object::{elf, Endianness};
+use target_lexicon::Triple;
 
 use crate::pe::next_multiple_of;
 
-pub fn create_dylib_elf64(custom_names: &[String]) -> object::read::Result<Vec<u8>> {
+pub fn create_dylib_elf64(
+    custom_names: &[String],
+    target: &Triple,
+) -> object::read::Result<Vec<u8>> {
     let endian = Endianness::Little;
 
+    // the host gets linked against this, so it has to be for the host's machine
+    let e_machine = match target.architecture {
+        target_lexicon::Architecture::Aarch64(_) => elf::EM_AARCH64,
+        _ => elf::EM_X86_64,
+    };
+
     let mut out_data = Vec::new();
     let mut writer = object::write::elf::Writer::new(endian, true, &mut out_data);
 
@@ -145,7 +155,7 @@ pub fn create_dylib_elf64(custom_names: &[String]) -> object::read::Result<Vec<u
             os_abi: 0,
             abi_version: 0,
             e_type: 3,
-            e_machine: 62,
+            e_machine,
             e_entry: 0x1000,
             e_flags: 0,
         })
@@ -14,7 +14,7 @@ pub(crate) use pe::APP_DLL;
 
 pub fn generate(target: &Triple, custom_names: &[String]) -> object::read::Result<Vec<u8>> {
     match target.binary_format {
-        target_lexicon::BinaryFormat::Elf => elf64::create_dylib_elf64(custom_names),
+        target_lexicon::BinaryFormat::Elf => elf64::create_dylib_elf64(custom_names, target),
         target_lexicon::BinaryFormat::Macho => macho::create_dylib_macho(custom_names, target),
         target_lexicon::BinaryFormat::Coff => Ok(pe::synthetic_dll(custom_names)),
         other => unimplemented!("dylib creation for {:?}", other),
@@ -55,6 +55,26 @@ mod tests {
         check_exports(&target);
     }
 
+    #[test]
+    fn check_exports_elf64_aarch64() {
+        let target = target_lexicon::Triple {
+            architecture: target_lexicon::Architecture::Aarch64(
+                target_lexicon::Aarch64Architecture::Aarch64,
+            ),
+            operating_system: target_lexicon::OperatingSystem::Linux,
+            binary_format: target_lexicon::BinaryFormat::Elf,
+            ..target_lexicon::Triple::host()
+        };
+
+        check_exports(&target);
+
+        // a host can only link against a stub lib for its own machine
+        let bytes = generate(&target, &["foo".to_string()]).unwrap();
+        let object = object::File::parse(bytes.as_slice()).unwrap();
+
+        assert_eq!(object.architecture(), object::Architecture::Aarch64);
+    }
+
     #[test]
     fn check_exports_coff() {
         // NOTE: this does not work
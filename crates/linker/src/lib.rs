@@ -16,6 +16,7 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use target_lexicon::Triple;
 
+mod aarch64;
 mod elf;
 mod macho;
 mod pe;
@@ -27,7 +28,8 @@ pub fn supported(link_type: LinkType, target: &Triple) -> bool {
     if let LinkType::Executable = link_type {
         match target {
             Triple {
-                architecture: target_lexicon::Architecture::X86_64,
+                architecture:
+                    target_lexicon::Architecture::X86_64 | target_lexicon::Architecture::Aarch64(_),
                 operating_system: target_lexicon::OperatingSystem::Linux,
                 binary_format: target_lexicon::BinaryFormat::Elf,
                 ..
@@ -133,6 +135,7 @@ pub fn generate_stub_lib(
             palette: DEFAULT_PALETTE,
             threading: Threading::AllAvailable,
             exec_mode: ExecutionMode::Executable,
+            opt_level: OptLevel::Normal,
         },
     )
     .unwrap_or_else(|problem| todo!("{:?}", problem));
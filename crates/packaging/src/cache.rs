@@ -1,12 +1,28 @@
 #[cfg(not(target_family = "wasm"))]
 use crate::https::{self, PackageMetadata, Problem};
+#[cfg(not(target_family = "wasm"))]
+use crate::mirror::Mirrors;
 use roc_error_macros::internal_error;
+#[cfg(not(target_family = "wasm"))]
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use std::{
     fs,
     path::{Path, PathBuf},
 };
+#[cfg(not(target_family = "wasm"))]
+use walkdir::WalkDir;
+
+pub(crate) const MAX_DOWNLOAD_BYTES: u64 = 32 * 1_000_000_000; // GB
 
-const MAX_DOWNLOAD_BYTES: u64 = 32 * 1_000_000_000; // GB
+/// Whether a package in a module header is a URL to download, rather than a local path.
+pub fn is_package_url(package: &str) -> bool {
+    package.starts_with("https://") || package.starts_with("file://")
+}
 
 #[derive(Copy, Clone, Debug)]
 pub enum RocCacheDir<'a> {
@@ -19,11 +35,25 @@ pub enum RocCacheDir<'a> {
     Temp(&'a tempfile::TempDir),
 }
 
+impl RocCacheDir<'_> {
+    /// Where type-checked modules get cached, next to the packages dir (e.g. ~/.cache/roc/types).
+    /// Returns None if we're not supposed to touch the cache at all.
+    pub fn types_dir(&self) -> Option<PathBuf> {
+        match self {
+            RocCacheDir::Persistent(packages_dir) => Some(packages_dir.with_file_name("types")),
+            RocCacheDir::Disallowed => None,
+            #[cfg(test)]
+            RocCacheDir::Temp(temp_dir) => Some(temp_dir.path().join("types")),
+        }
+    }
+}
+
 /// Accepts either a path to the Roc cache dir, or else a TempDir. If a TempDir, always download
 /// into that dir. If the cache dir on the filesystem, then look into it to see if we already
 /// have an entry for the given URL. If we do, return its info. If we don't already have it, then:
 ///
-/// - Download and decompress the compressed tarball from the given URL
+/// - Download and decompress the compressed tarball from the given URL, or from where the
+///   mirrors in ROC_PACKAGE_MIRROR redirect it to
 /// - Verify its bytes against the hash in the URL
 /// - Extract the tarball's contents into the appropriate cache directory
 ///
@@ -47,59 +77,79 @@ pub fn install_package<'a>(
             // e.g. ~/.cache/roc/example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE
             let dest_dir = parent_dir.join(content_hash);
 
-            if dest_dir.exists() {
-                // If the cache dir exists already, we assume it has the correct contents
+            // Another roc process may be installing this same package. Whichever one gets the
+            // lock first installs it, and the others then find it installed.
+            fs::create_dir_all(&parent_dir).map_err(Problem::IoErr)?;
+
+            let mut lock = package_lock(&dest_dir).map_err(Problem::IoErr)?;
+            let _guard = lock.write().map_err(Problem::IoErr)?;
+            let record_path = dest_dir.with_extension(RECORD_EXTENSION);
+
+            if record_path.exists() {
+                // The package was completely installed, so we assume it has the correct contents
                 // (it's a cache, after all!) and return without downloading anything.
+                // `roc cache verify` is what checks that assumption.
+                let _ = filetime::set_file_mtime(&record_path, filetime::FileTime::now());
+
+                return Ok((dest_dir, root_module_filename));
+            }
+
+            if dest_dir.exists() {
+                // Without a record, this is what's left of an install that didn't finish
+                // (or of one by a roc from before records), so start over.
+                fs::remove_dir_all(&dest_dir).map_err(Problem::IoErr)?;
+            }
+
+            let mirrors = Mirrors::from_env().map_err(Problem::IoErr)?;
+            let download_url = mirrors.redirect(url);
+
+            // Download into a tempdir; only move it to dest_dir if hash verification passes.
+            eprintln!(
+                "Downloading \u{001b}[36m{download_url}\u{001b}[0m\n    into {}\n",
+                cache_dir.display()
+            );
+            let tempdir = tempfile::tempdir().map_err(Problem::IoErr)?;
+            let tempdir_path = tempdir.path();
+            let downloaded_hash =
+                https::download_and_hash(&download_url, tempdir_path, MAX_DOWNLOAD_BYTES)?;
+
+            // Download the tarball into memory and verify it.
+            // The tarball name is the hash of its contents.
+            if downloaded_hash == content_hash {
+                // Now that we've verified the hash, rename the tempdir to the real dir.
+
+                // This rename should be super cheap if it succeeds - just an inode change.
+                if fs::rename(tempdir_path, &dest_dir).is_err() {
+                    // If the rename failed, try a recursive copy -
+                    // it could have failed due to std::io::ErrorKind::CrossesDevices
+                    // (e.g. if the source an destination directories are on different disks)
+                    // which as of this implementation is nightly-only
+                    // https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.CrossesDevices                       match io_err.kind() {
+                    // but if that's what happened, this should work!
+
+                    // fs_extra::dir::copy needs the destination directory to exist already.
+                    fs::create_dir(&dest_dir).map_err(Problem::IoErr)?;
+                    fs_extra::dir::copy(
+                        tempdir_path,
+                        &dest_dir,
+                        &fs_extra::dir::CopyOptions {
+                            content_only: true,
+                            ..Default::default()
+                        },
+                    )
+                    .map_err(Problem::FsExtraErr)?;
+                }
+
+                // The record goes last, since it's what marks the install as finished.
+                write_record(&dest_dir, url).map_err(Problem::IoErr)?;
+
+                // The package's files are now in the cache. We're done!
                 Ok((dest_dir, root_module_filename))
             } else {
-                // Download into a tempdir; only move it to dest_dir if hash verification passes.
-                println!(
-                    "Downloading \u{001b}[36m{url}\u{001b}[0m\n    into {}\n",
-                    cache_dir.display()
-                );
-                let tempdir = tempfile::tempdir().map_err(Problem::IoErr)?;
-                let tempdir_path = tempdir.path();
-                let downloaded_hash =
-                    https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?;
-
-                // Download the tarball into memory and verify it.
-                // The tarball name is the hash of its contents.
-                if downloaded_hash == content_hash {
-                    // Now that we've verified the hash, rename the tempdir to the real dir.
-
-                    // Create the destination dir's parent dir, since it may not exist yet.
-                    fs::create_dir_all(parent_dir).map_err(Problem::IoErr)?;
-
-                    // This rename should be super cheap if it succeeds - just an inode change.
-                    if fs::rename(tempdir_path, &dest_dir).is_err() {
-                        // If the rename failed, try a recursive copy -
-                        // it could have failed due to std::io::ErrorKind::CrossesDevices
-                        // (e.g. if the source an destination directories are on different disks)
-                        // which as of this implementation is nightly-only
-                        // https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.CrossesDevices                       match io_err.kind() {
-                        // but if that's what happened, this should work!
-
-                        // fs_extra::dir::copy needs the destination directory to exist already.
-                        fs::create_dir(&dest_dir).map_err(Problem::IoErr)?;
-                        fs_extra::dir::copy(
-                            tempdir_path,
-                            &dest_dir,
-                            &fs_extra::dir::CopyOptions {
-                                content_only: true,
-                                ..Default::default()
-                            },
-                        )
-                        .map_err(Problem::FsExtraErr)?;
-                    }
-
-                    // The package's files are now in the cache. We're done!
-                    Ok((dest_dir, root_module_filename))
-                } else {
-                    Err(Problem::InvalidContentHash {
-                        expected: content_hash.to_string(),
-                        actual: downloaded_hash,
-                    })
-                }
+                Err(Problem::InvalidContentHash {
+                    expected: content_hash.to_string(),
+                    actual: downloaded_hash,
+                })
             }
         }
         RocCacheDir::Disallowed => {
@@ -113,6 +163,352 @@ pub fn install_package<'a>(
     }
 }
 
+/// Next to each installed package directory there's a record of it, e.g.
+/// `jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.files`. Its first line is the URL the package
+/// was installed from, and each line after that is the hash and path of one of its files,
+/// taken right after the download was verified against the hash in the URL. The record is
+/// written last, so a package directory without one wasn't completely installed.
+#[cfg(not(target_family = "wasm"))]
+const RECORD_EXTENSION: &str = "files";
+
+/// Next to each package directory, e.g. `jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.lock`.
+/// Lock files are never removed, because another process might be waiting on one.
+#[cfg(not(target_family = "wasm"))]
+const LOCK_EXTENSION: &str = "lock";
+
+#[cfg(not(target_family = "wasm"))]
+fn package_lock(package_dir: &Path) -> io::Result<fd_lock::RwLock<File>> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .open(package_dir.with_extension(LOCK_EXTENSION))?;
+
+    Ok(fd_lock::RwLock::new(file))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write_record(package_dir: &Path, url: &str) -> io::Result<()> {
+    let mut record = format!("{url}\n");
+
+    for (path, hash) in file_hashes(package_dir)? {
+        record.push_str(&format!("{hash}  {path}\n"));
+    }
+
+    // Write it somewhere else first, so a record is never only partly there
+    let record_path = package_dir.with_extension(RECORD_EXTENSION);
+    let tmp_path = record_path.with_extension("tmp");
+
+    fs::write(&tmp_path, record)?;
+    fs::rename(&tmp_path, &record_path)
+}
+
+/// The hash of every file in the directory, by its path relative to the directory
+#[cfg(not(target_family = "wasm"))]
+fn file_hashes(dir: &Path) -> io::Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+
+    for entry in WalkDir::new(dir) {
+        let entry = entry?;
+
+        if entry.file_type().is_file() {
+            let path = entry.path().strip_prefix(dir).unwrap();
+            let hash = base64_url::encode(blake3::hash(&fs::read(entry.path())?).as_bytes());
+
+            hashes.insert(path.to_string_lossy().replace('\\', "/"), hash);
+        }
+    }
+
+    Ok(hashes)
+}
+
+/// A package directory in the cache
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Clone)]
+pub struct CachedPackage {
+    pub dir: PathBuf,
+    /// Where it was installed from, or None if it wasn't completely installed
+    pub url: Option<String>,
+    /// The total size of its files
+    pub bytes: u64,
+    /// When a build last used it (or when it was installed)
+    pub last_used: Option<SystemTime>,
+}
+
+/// The directory names of packages are their content hashes: base64url-encoded BLAKE3 hashes.
+#[cfg(not(target_family = "wasm"))]
+fn is_content_hash(name: &str) -> bool {
+    name.len() == 43
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+/// Every package in the cache, sorted by directory
+#[cfg(not(target_family = "wasm"))]
+pub fn list(cache_dir: &Path) -> io::Result<Vec<CachedPackage>> {
+    let mut packages = Vec::new();
+
+    if !cache_dir.exists() {
+        return Ok(packages);
+    }
+
+    let mut entries = WalkDir::new(cache_dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter();
+
+    while let Some(entry) = entries.next() {
+        let entry = entry?;
+
+        if !entry.file_type().is_dir() || !is_content_hash(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+
+        // Don't look for packages inside of packages
+        entries.skip_current_dir();
+
+        let dir = entry.into_path();
+        let record_path = dir.with_extension(RECORD_EXTENSION);
+        let (url, last_used) = match fs::read_to_string(&record_path) {
+            Ok(record) => (
+                record.lines().next().map(|url| url.to_string()),
+                fs::metadata(&record_path)?.modified().ok(),
+            ),
+            Err(_) => (None, None),
+        };
+        let mut bytes = 0;
+
+        for file in WalkDir::new(&dir) {
+            let file = file?;
+
+            if file.file_type().is_file() {
+                bytes += file.metadata()?.len();
+            }
+        }
+
+        packages.push(CachedPackage {
+            dir,
+            url,
+            bytes,
+            last_used,
+        });
+    }
+
+    Ok(packages)
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// Its files are exactly the ones in its record
+    Intact,
+    /// It has no record, so its install didn't finish, or an older roc installed it
+    Partial,
+    /// Its files were changed since it was installed. Each list has paths relative to the
+    /// package directory.
+    Tampered {
+        changed: Vec<String>,
+        missing: Vec<String>,
+        added: Vec<String>,
+    },
+}
+
+/// Re-hash the package's files, and compare them to the ones recorded when it was installed.
+///
+/// This is a weaker check than the one `install_package` does. The content hash in the directory
+/// name is the hash of the downloaded tarball, which isn't kept around, so it can't be checked
+/// again. The record is only as trustworthy as the cache dir it's in: this catches files that
+/// were accidentally edited, deleted or added, but anything that can write to the cache can
+/// rewrite the record to match.
+#[cfg(not(target_family = "wasm"))]
+pub fn verify(package: &CachedPackage) -> io::Result<Verification> {
+    let lock = package_lock(&package.dir)?;
+    let _guard = lock.read()?;
+
+    let record = match fs::read_to_string(package.dir.with_extension(RECORD_EXTENSION)) {
+        Ok(record) => record,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Verification::Partial),
+        Err(err) => return Err(err),
+    };
+
+    let mut actual = file_hashes(&package.dir)?;
+    let mut changed = Vec::new();
+    let mut missing = Vec::new();
+
+    for line in record.lines().skip(1) {
+        let (expected_hash, path) = line.split_once("  ").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the record of {} is malformed", package.dir.display()),
+            )
+        })?;
+
+        match actual.remove(path) {
+            Some(hash) if hash == expected_hash => {}
+            Some(_) => changed.push(path.to_string()),
+            None => missing.push(path.to_string()),
+        }
+    }
+
+    let added: Vec<String> = actual.into_keys().collect();
+
+    if changed.is_empty() && missing.is_empty() && added.is_empty() {
+        Ok(Verification::Intact)
+    } else {
+        Ok(Verification::Tampered {
+            changed,
+            missing,
+            added,
+        })
+    }
+}
+
+/// Removes the package from the cache, so the next build that needs it downloads it again.
+/// Returns false without removing anything if another roc process is using it right now.
+#[cfg(not(target_family = "wasm"))]
+pub fn remove(package: &CachedPackage) -> io::Result<bool> {
+    let mut lock = package_lock(&package.dir)?;
+
+    let _guard = match lock.try_write() {
+        Ok(guard) => guard,
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    // The record goes first, so if this gets interrupted, what's left is a partial install
+    match fs::remove_file(package.dir.with_extension(RECORD_EXTENSION)) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    fs::remove_dir_all(&package.dir)?;
+
+    Ok(true)
+}
+
+/// Which packages `roc cache clean` keeps. With neither limit, it removes every package.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CleanLimits {
+    /// Remove packages that haven't been used for this long
+    pub older_than: Option<Duration>,
+    /// Then remove the least recently used packages until the rest fit in this many bytes
+    pub max_bytes: Option<u64>,
+}
+
+/// Removes packages from the cache according to the limits, as well as any partial installs.
+/// Returns the packages it removed. Packages that are in use by another roc process are skipped.
+#[cfg(not(target_family = "wasm"))]
+pub fn clean(cache_dir: &Path, limits: CleanLimits) -> io::Result<Vec<CachedPackage>> {
+    let now = SystemTime::now();
+    let mut to_remove = Vec::new();
+    let mut to_keep = Vec::new();
+
+    for package in list(cache_dir)? {
+        let expired = match (package.last_used, limits.older_than) {
+            (None, _) => true,
+            (Some(_), None) => limits.max_bytes.is_none(),
+            (Some(last_used), Some(max_age)) => {
+                now.duration_since(last_used).unwrap_or_default() > max_age
+            }
+        };
+
+        if expired {
+            to_remove.push(package);
+        } else {
+            to_keep.push(package);
+        }
+    }
+
+    if let Some(max_bytes) = limits.max_bytes {
+        // Least recently used first
+        to_keep.sort_by_key(|package| package.last_used);
+
+        let mut total: u64 = to_keep.iter().map(|package| package.bytes).sum();
+
+        for package in to_keep {
+            if total <= max_bytes {
+                break;
+            }
+
+            total -= package.bytes;
+            to_remove.push(package);
+        }
+    }
+
+    let mut removed = Vec::new();
+
+    for package in to_remove {
+        if remove(&package)? {
+            removed.push(package);
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Marks an entry of the type cache (see [RocCacheDir::types_dir]) as used, so that
+/// [clean_types] removes it after the ones that weren't used since.
+#[cfg(not(target_family = "wasm"))]
+pub fn touch_type_entry(path: &Path) {
+    let _ = filetime::set_file_mtime(path, filetime::FileTime::now());
+}
+
+/// Removes entries from the type cache in `types_dir` according to the limits, least recently
+/// used first. Unlike packages, entries can always be removed, since they're only ever replaced
+/// as a whole. Returns how many entries it removed.
+#[cfg(not(target_family = "wasm"))]
+pub fn clean_types(types_dir: &Path, limits: CleanLimits) -> io::Result<usize> {
+    let now = SystemTime::now();
+    let mut entries = Vec::new();
+
+    let dir = match fs::read_dir(types_dir) {
+        Ok(dir) => dir,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+
+    for entry in dir {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        if metadata.is_file() {
+            let last_used = metadata.modified().unwrap_or(UNIX_EPOCH);
+            entries.push((last_used, metadata.len(), entry.path()));
+        }
+    }
+
+    // Least recently used first
+    entries.sort_unstable();
+
+    let mut total: u64 = entries.iter().map(|(_, bytes, _)| bytes).sum();
+    let mut removed = 0;
+
+    for (last_used, bytes, path) in entries {
+        let expired = match limits.older_than {
+            Some(max_age) => now.duration_since(last_used).unwrap_or_default() > max_age,
+            None => limits.max_bytes.is_none(),
+        };
+        let too_big = matches!(limits.max_bytes, Some(max_bytes) if total > max_bytes);
+
+        if !expired && !too_big {
+            continue;
+        }
+
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            // Another roc process got to it first
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        total -= bytes;
+    }
+
+    Ok(removed)
+}
+
 #[cfg(windows)]
 // e.g. the "Roc" in %APPDATA%\\Roc
 const ROC_CACHE_DIR_NAME: &str = "Roc";
@@ -187,3 +583,124 @@ pub fn roc_cache_dir() -> PathBuf {
 pub fn roc_cache_dir() -> PathBuf {
     PathBuf::from(".cache").join(ROC_CACHE_DIR_NAME)
 }
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn install_verify_and_clean() {
+    use crate::tarball::{self, Compression};
+
+    let package_dir = tempfile::tempdir().unwrap();
+    let package_main = package_dir.path().join("main.roc");
+
+    fs::write(
+        &package_main,
+        "package \"test/pkg\" exposes [] packages {}\n",
+    )
+    .unwrap();
+
+    let filename = tarball::build(&package_main, Compression::Uncompressed).unwrap();
+    let url = format!("file://{}", package_dir.path().join(&filename).display());
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let (installed_dir, _) =
+        install_package(RocCacheDir::Persistent(cache_dir.path()), &url).unwrap();
+
+    let packages = list(cache_dir.path()).unwrap();
+
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].dir, installed_dir);
+    assert_eq!(packages[0].url.as_deref(), Some(url.as_str()));
+    assert_eq!(verify(&packages[0]).unwrap(), Verification::Intact);
+
+    fs::write(installed_dir.join("main.roc"), "package \"test/evil\"").unwrap();
+    fs::write(installed_dir.join("Extra.roc"), "").unwrap();
+
+    assert_eq!(
+        verify(&packages[0]).unwrap(),
+        Verification::Tampered {
+            changed: vec!["main.roc".to_string()],
+            missing: Vec::new(),
+            added: vec!["Extra.roc".to_string()],
+        }
+    );
+
+    // Without its record, the package counts as partly installed, so it gets installed again
+    fs::remove_file(installed_dir.with_extension(RECORD_EXTENSION)).unwrap();
+
+    assert_eq!(verify(&packages[0]).unwrap(), Verification::Partial);
+
+    install_package(RocCacheDir::Persistent(cache_dir.path()), &url).unwrap();
+
+    assert_eq!(verify(&packages[0]).unwrap(), Verification::Intact);
+
+    // It was just used, so it's kept unless the cache has to be smaller than it
+    let keep_recent = CleanLimits {
+        older_than: Some(Duration::from_secs(60 * 60)),
+        max_bytes: None,
+    };
+
+    assert!(clean(cache_dir.path(), keep_recent).unwrap().is_empty());
+    assert_eq!(
+        clean(
+            cache_dir.path(),
+            CleanLimits {
+                max_bytes: Some(0),
+                ..keep_recent
+            }
+        )
+        .unwrap()
+        .len(),
+        1
+    );
+    assert!(list(cache_dir.path()).unwrap().is_empty());
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn clean_types_least_recently_used_first() {
+    use filetime::FileTime;
+
+    let types_dir = tempfile::tempdir().unwrap();
+    let entry = |name: &str, days_ago: i64| {
+        let path = types_dir.path().join(name);
+        fs::write(&path, [0; 100]).unwrap();
+
+        let used = FileTime::from_unix_time(FileTime::now().unix_seconds() - days_ago * 86_400, 0);
+        filetime::set_file_mtime(&path, used).unwrap();
+
+        path
+    };
+
+    let oldest = entry("oldest", 3);
+    let old = entry("old", 2);
+    let recent = entry("recent", 0);
+
+    // Reading an entry counts as using it
+    touch_type_entry(&oldest);
+
+    let removed = clean_types(
+        types_dir.path(),
+        CleanLimits {
+            older_than: None,
+            max_bytes: Some(200),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(removed, 1);
+    assert!(!old.exists());
+    assert!(oldest.exists() && recent.exists());
+
+    // Without any limits, everything goes
+    assert_eq!(
+        clean_types(types_dir.path(), CleanLimits::default()).unwrap(),
+        2
+    );
+    assert_eq!(fs::read_dir(types_dir.path()).unwrap().count(), 0);
+
+    // A cache that was never written to is fine too
+    assert_eq!(
+        clean_types(&types_dir.path().join("missing"), CleanLimits::default()).unwrap(),
+        0
+    );
+}
@@ -1,4 +1,5 @@
 use std::{
+    fs::File,
     io::{self, Read},
     path::Path,
 };
@@ -35,6 +36,7 @@ pub enum UrlProblem {
     MissingTarExt,
     InvalidFragment(String),
     MissingHash,
+    /// The URL started with neither https:// nor file://
     MissingHttps,
 }
 
@@ -48,9 +50,13 @@ impl<'a> TryFrom<&'a str> for PackageMetadata<'a> {
 
 impl<'a> PackageMetadata<'a> {
     fn new(url: &'a str) -> Result<Self, UrlProblem> {
-        // First, verify that the URL starts with https://
-        let without_protocol = match url.split_once("https://") {
-            Some((_, without_protocol)) => without_protocol,
+        // First, verify that the URL starts with https:// (or file://, for local tarballs)
+        let without_protocol = match url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("file://"))
+        {
+            // A file:// URL has an absolute path, which becomes a relative cache_subdir
+            Some(without_protocol) => without_protocol.trim_start_matches('/'),
             None => {
                 return Err(UrlProblem::MissingHttps);
             }
@@ -123,6 +129,49 @@ pub fn download_and_hash(
     dest_dir: &Path,
     max_download_bytes: u64,
 ) -> Result<String, Problem> {
+    let (reader, encoding) = open(url, max_download_bytes)?;
+
+    decompress_into(dest_dir, encoding, reader)
+}
+
+/// Like [download_and_hash], but also saves the tarball itself to `dest_file`, as it was
+/// downloaded. Returns the hash of its contents, which get unpacked into `dest_dir`.
+pub fn download_tarball(
+    url: &str,
+    dest_file: &Path,
+    dest_dir: &Path,
+    max_download_bytes: u64,
+) -> Result<String, Problem> {
+    let (mut reader, encoding) = open(url, max_download_bytes)?;
+
+    {
+        let mut file = File::create(dest_file).map_err(Problem::IoErr)?;
+
+        io::copy(&mut reader, &mut file).map_err(Problem::IoErr)?;
+    }
+
+    let file = File::open(dest_file).map_err(Problem::IoErr)?;
+
+    decompress_into(dest_dir, encoding, file)
+}
+
+/// Start reading the tarball at the given https:// or file:// URL.
+fn open(url: &str, max_download_bytes: u64) -> Result<(Box<dyn Read>, Encoding), Problem> {
+    if let Some(path) = url.strip_prefix("file://") {
+        // The fragment names the root module, so it isn't part of the path
+        let path = path.rsplit_once('#').map_or(path, |(path, _)| path);
+        let file = File::open(path).map_err(Problem::IoErr)?;
+
+        return Ok((
+            Box::new(file.take(max_download_bytes)),
+            Encoding::new("", url)?,
+        ));
+    }
+
+    if !url.starts_with("https://") {
+        return Err(Problem::InvalidUrl(UrlProblem::MissingHttps));
+    }
+
     // TODO apparently it really improves performance to construct a Client once and then reuse it,
     // instead of making a new Client for every request.
     // Per https://github.com/seanmonstar/reqwest/issues/1454#issuecomment-1026076701
@@ -153,7 +202,7 @@ pub fn download_and_hash(
 
     // Use .take to prevent a malicious server from sending back bytes
     // until system resources are exhausted!
-    decompress_into(dest_dir, encoding, resp.take(max_download_bytes))
+    Ok((Box::new(resp.take(max_download_bytes)), encoding))
 }
 
 /// The content encodings we support
@@ -214,6 +263,29 @@ fn encoding_from_tar_br() {
     assert_eq!(Encoding::Brotli, actual);
 }
 
+#[test]
+fn metadata_from_file_url() {
+    let metadata = PackageMetadata::new(
+        "file:///srv/roc/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br#pkg.roc",
+    )
+    .unwrap();
+
+    assert_eq!(metadata.cache_subdir, "srv/roc");
+    assert_eq!(
+        metadata.content_hash,
+        "jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE"
+    );
+    assert_eq!(metadata.root_module_filename, Some("pkg.roc"));
+}
+
+#[test]
+fn metadata_requires_https_or_file() {
+    assert!(matches!(
+        PackageMetadata::new("http://example.com/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar"),
+        Err(UrlProblem::MissingHttps)
+    ));
+}
+
 fn hash_and_unpack(dest_dir: &Path, reader: impl Read) -> Result<String, Problem> {
     let mut hash_reader = HashReader::new(reader);
 
@@ -1,4 +1,7 @@
 pub mod cache;
 #[cfg(not(target_family = "wasm"))]
 pub mod https;
+pub mod mirror;
 pub mod tarball;
+#[cfg(not(target_family = "wasm"))]
+pub mod vendor;
@@ -0,0 +1,136 @@
+//! Redirects package URLs somewhere else, like a directory of tarballs on a build machine
+//! without network access. Packages are still verified against the hash in their original
+//! URL, so a mirror can't change what a package contains.
+use std::{borrow::Cow, env, fs, io, path::Path};
+
+/// Either a mirror URL that has every tarball (e.g. `file:///srv/roc-packages`),
+/// or the path to a file of redirects (see [Mirrors::parse]).
+pub const ROC_PACKAGE_MIRROR: &str = "ROC_PACKAGE_MIRROR";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Mirrors {
+    redirects: Vec<Redirect>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Redirect {
+    /// URLs that start with `from` start with `to` instead
+    Prefix { from: String, to: String },
+    /// Every tarball is looked up by its filename at this URL
+    Everything(String),
+}
+
+impl Mirrors {
+    /// Reads the mirrors from [ROC_PACKAGE_MIRROR], if it's set.
+    pub fn from_env() -> io::Result<Self> {
+        match env::var(ROC_PACKAGE_MIRROR) {
+            Ok(value) if value.starts_with("https://") || value.starts_with("file://") => {
+                Ok(Mirrors {
+                    redirects: vec![Redirect::Everything(value)],
+                })
+            }
+            Ok(value) if !value.is_empty() => Self::parse(&fs::read_to_string(Path::new(&value))?),
+            _ => Ok(Mirrors::default()),
+        }
+    }
+
+    /// Each line of a redirects file is either an upstream URL prefix followed by its
+    /// replacement, or a single mirror URL that has every tarball. Later lines only apply to
+    /// URLs that earlier ones don't match, and `#` starts a comment.
+    ///
+    /// ```text
+    /// # Our own packages are on the file server
+    /// https://github.com/our-org/ file:///mnt/packages/our-org/
+    /// file:///mnt/packages/upstream
+    /// ```
+    pub fn parse(src: &str) -> io::Result<Self> {
+        let mut redirects = Vec::new();
+
+        for (index, line) in src.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(before, _)| before);
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => {}
+                [mirror] => redirects.push(Redirect::Everything(mirror.to_string())),
+                [from, to] => redirects.push(Redirect::Prefix {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                }),
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "line {} of the package mirrors has more than two URLs: {}",
+                            index + 1,
+                            line.trim()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(Mirrors { redirects })
+    }
+
+    /// Where to actually get the tarball at this URL from.
+    pub fn redirect<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        for redirect in self.redirects.iter() {
+            match redirect {
+                Redirect::Prefix { from, to } => {
+                    if let Some(rest) = url.strip_prefix(from.as_str()) {
+                        return Cow::Owned(format!("{to}{rest}"));
+                    }
+                }
+                Redirect::Everything(mirror) => {
+                    return Cow::Owned(format!(
+                        "{}/{}",
+                        mirror.trim_end_matches('/'),
+                        tarball_filename(url)
+                    ));
+                }
+            }
+        }
+
+        Cow::Borrowed(url)
+    }
+}
+
+/// The last part of a package URL, e.g. `jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br`
+pub fn tarball_filename(url: &str) -> &str {
+    let without_fragment = url.rsplit_once('#').map_or(url, |(before, _)| before);
+
+    without_fragment
+        .rsplit_once('/')
+        .map_or(without_fragment, |(_, filename)| filename)
+}
+
+#[test]
+fn redirect_prefix_then_everything() {
+    let mirrors = Mirrors::parse(
+        "# comment\n\nhttps://example.com/ours/ file:///mnt/ours/\nfile:///mnt/all/ # the rest\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        mirrors.redirect("https://example.com/ours/abc.tar.br#pkg.roc"),
+        "file:///mnt/ours/abc.tar.br#pkg.roc"
+    );
+    assert_eq!(
+        mirrors.redirect("https://example.com/theirs/def.tar.gz#pkg.roc"),
+        "file:///mnt/all/def.tar.gz"
+    );
+}
+
+#[test]
+fn no_redirects() {
+    assert_eq!(
+        Mirrors::default().redirect("https://example.com/abc.tar"),
+        "https://example.com/abc.tar"
+    );
+}
+
+#[test]
+fn too_many_urls() {
+    assert!(Mirrors::parse("a b c").is_err());
+}
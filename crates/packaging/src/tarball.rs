@@ -1,17 +1,25 @@
 use brotli::enc::BrotliEncoderParams;
 use bumpalo::Bump;
 use flate2::write::GzEncoder;
-use roc_parse::ast::{Header, Module};
-use roc_parse::header::PlatformHeader;
+use roc_parse::ast::{ExtractSpaces, Header, Module};
+use roc_parse::header::{ImportsEntry, PlatformHeader};
 use roc_parse::module::parse_header;
 use roc_parse::state::State;
+use std::collections::BTreeSet;
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, Read, Write};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use tar;
 use walkdir::WalkDir;
 
+/// Every package archive has this, listing the other files and their hashes.
+pub const MANIFEST_FILENAME: &str = "manifest.txt";
+
+/// Next to a package module, this lists the other files the package ships, one file or
+/// directory per line. Lines starting with `#` are comments.
+pub const ASSETS_FILENAME: &str = "assets.txt";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Compression {
     Brotli,
@@ -66,6 +74,201 @@ pub fn build(path_to_main: &Path, compression: Compression) -> io::Result<String
 
     write_archive(path_to_main, &mut archive_bytes)?;
 
+    write_compressed(path_to_main, archive_bytes, compression)
+}
+
+/// A package archive from [build_package].
+#[derive(Debug)]
+pub struct Bundle {
+    /// The archive's filename, which is the hash of its contents plus the extension
+    pub filename: String,
+    /// Everything in the archive, relative to the package module's directory.
+    /// The manifest comes last.
+    pub files: Vec<PathBuf>,
+    /// The modules the package exposes, relative to the package module's directory
+    pub exposed_modules: Vec<PathBuf>,
+}
+
+/// Given a path to a `package` module, write an archive of the whole package next to it:
+/// the package module, the modules it exposes and every module they import from this
+/// package, the files listed in its [ASSETS_FILENAME], and a manifest of all of those.
+pub fn build_package(path_to_main: &Path, compression: Compression) -> io::Result<Bundle> {
+    let root_dir = path_to_main.parent().unwrap_or_else(|| Path::new(""));
+    let arena = Bump::new();
+    let mut buf = Vec::new();
+
+    let exposed_modules: Vec<PathBuf> = match read_header(&arena, &mut buf, path_to_main)?.header {
+        Header::Package(header) => header
+            .exposes
+            .item
+            .iter()
+            .map(|name| module_path(name.value.extract_spaces().item.as_str()))
+            .collect(),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is not a package module, so I can't bundle it as a package.",
+                    path_to_main.display()
+                ),
+            ));
+        }
+    };
+
+    let mut files = BTreeSet::new();
+
+    files.insert(path_to_main.strip_prefix(root_dir).unwrap().to_path_buf());
+
+    // Find every module the exposed ones import from this package
+    let mut stack = exposed_modules.clone();
+
+    while let Some(module) = stack.pop() {
+        if !files.insert(module.clone()) {
+            continue;
+        }
+
+        let arena = Bump::new();
+        let mut buf = Vec::new();
+
+        if let Header::Interface(header) =
+            read_header(&arena, &mut buf, &root_dir.join(&module))?.header
+        {
+            for entry in header.imports.item.iter() {
+                if let ImportsEntry::Module(name, _) = entry.value.extract_spaces().item {
+                    let imported = module_path(name.as_str());
+
+                    // Anything that isn't here is a builtin; if it's really missing,
+                    // checking the archive will report it.
+                    if root_dir.join(&imported).is_file() {
+                        stack.push(imported);
+                    }
+                }
+            }
+        }
+    }
+
+    let assets_path = root_dir.join(ASSETS_FILENAME);
+
+    if assets_path.is_file() {
+        files.insert(PathBuf::from(ASSETS_FILENAME));
+
+        let canonical_root = fs::canonicalize(root_dir)?;
+        let outside_package = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} lists {line}, but assets have to be inside the package's directory.",
+                    assets_path.display()
+                ),
+            )
+        };
+
+        for line in fs::read_to_string(&assets_path)?.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // No absolute paths or `..`, which could reach outside the package
+            if !Path::new(line)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+            {
+                return Err(outside_package(line));
+            }
+
+            let asset = root_dir.join(line);
+
+            // A symlink could still lead outside of it
+            if let Ok(canonical) = fs::canonicalize(&asset) {
+                if !canonical.starts_with(&canonical_root) {
+                    return Err(outside_package(line));
+                }
+            }
+
+            if asset.is_dir() {
+                for entry in WalkDir::new(&asset) {
+                    let entry = entry?;
+
+                    if entry.path().is_file() {
+                        if !fs::canonicalize(entry.path())?.starts_with(&canonical_root) {
+                            return Err(outside_package(line));
+                        }
+
+                        files.insert(entry.path().strip_prefix(root_dir).unwrap().to_path_buf());
+                    }
+                }
+            } else if asset.is_file() {
+                files.insert(PathBuf::from(line));
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "{} lists {line}, but there's no such file or directory.",
+                        assets_path.display()
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut files: Vec<PathBuf> = files.into_iter().collect();
+    let mut manifest = String::new();
+    let mut archive_bytes = Vec::new();
+
+    {
+        let mut builder = tar::Builder::new(&mut archive_bytes);
+
+        for file in files.iter() {
+            let path = root_dir.join(file);
+            let hash = base64_url::encode(blake3::hash(&fs::read(&path)?).as_bytes());
+
+            manifest.push_str(&format!("{hash}  {}\n", archive_path(file)));
+            builder.append_path_with_name(&path, file)?;
+        }
+
+        let mut header = tar::Header::new_gnu();
+
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, MANIFEST_FILENAME, manifest.as_bytes())?;
+        builder.finish()?;
+    }
+
+    files.push(PathBuf::from(MANIFEST_FILENAME));
+
+    Ok(Bundle {
+        filename: write_compressed(path_to_main, archive_bytes, compression)?,
+        files,
+        exposed_modules,
+    })
+}
+
+/// e.g. `Json/Decode.roc` for `Json.Decode`
+fn module_path(module_name: &str) -> PathBuf {
+    let mut path: PathBuf = module_name.split('.').collect();
+
+    path.set_extension("roc");
+
+    path
+}
+
+/// The path as it's stored in an archive, with forward slashes on every OS
+fn archive_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Hash the archive, then write it next to `path_to_main` with that hash as its filename.
+fn write_compressed(
+    path_to_main: &Path,
+    archive_bytes: Vec<u8>,
+    compression: Compression,
+) -> io::Result<String> {
     // Now that we have our compressed archive, get its BLAKE3 hash
     // and base64url encode it. Use base64url encoding because:
     // - It's more concise than hex encoding, so the URL can be shorter
@@ -268,9 +471,120 @@ fn read_header<'a>(
     // (We can't use that for the parser state and still return Module<'a> unfortunately.)
     let arena_buf = bumpalo::collections::Vec::from_iter_in(buf.iter().copied(), arena);
     let parse_state = State::new(arena_buf.into_bump_slice());
-    let (module, _) = parse_header(arena, parse_state).unwrap_or_else(|_err| {
-        todo!(); // TODO report a nice error and exit 1 - or maybe just return Err, for better testability?
-    });
+    let (module, _) = parse_header(arena, parse_state).map_err(|_err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("The header of {} doesn't parse.", path.display()),
+        )
+    })?;
 
     Ok(module)
 }
+
+#[test]
+fn build_package_with_imports_and_assets() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let write = |path: &str, contents: &str| {
+        let path = root.join(path);
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+
+    write(
+        "main.roc",
+        "package \"test/json\" exposes [Json] packages {}\n",
+    );
+    write(
+        "Json.roc",
+        "interface Json exposes [] imports [Json.Helpers, Dict]\n",
+    );
+    write(
+        "Json/Helpers.roc",
+        "interface Json.Helpers exposes [] imports []\n",
+    );
+    write("Unused.roc", "interface Unused exposes [] imports []\n");
+    write(ASSETS_FILENAME, "# Test data\ndata\n");
+    write("data/numbers.json", "[1, 2, 3]\n");
+
+    let bundle = build_package(&root.join("main.roc"), Compression::Uncompressed).unwrap();
+    // Paths are sorted by their components, so a directory comes before a file with a longer name
+    let expected_files: Vec<PathBuf> = [
+        "Json/Helpers.roc",
+        "Json.roc",
+        ASSETS_FILENAME,
+        "data/numbers.json",
+        "main.roc",
+        MANIFEST_FILENAME,
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+
+    assert_eq!(bundle.files, expected_files);
+    assert_eq!(bundle.exposed_modules, vec![PathBuf::from("Json.roc")]);
+
+    let archive = File::open(root.join(&bundle.filename)).unwrap();
+    let mut archive = tar::Archive::new(archive);
+    let mut manifest = String::new();
+    let mut archived_files = Vec::new();
+
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_path_buf();
+
+        if path == Path::new(MANIFEST_FILENAME) {
+            entry.read_to_string(&mut manifest).unwrap();
+        }
+
+        archived_files.push(path);
+    }
+
+    assert_eq!(archived_files, expected_files);
+
+    let hash = base64_url::encode(blake3::hash(b"[1, 2, 3]\n").as_bytes());
+
+    assert!(manifest.contains(&format!("{hash}  data/numbers.json\n")));
+    assert_eq!(manifest.lines().count(), expected_files.len() - 1);
+}
+
+#[test]
+fn build_package_rejects_assets_outside_package() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("package");
+    let outside = dir.path().join("outside");
+
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("secret.txt"), "").unwrap();
+    fs::write(
+        root.join("main.roc"),
+        "package \"test/json\" exposes [] packages {}\n",
+    )
+    .unwrap();
+
+    let mut lines = vec![
+        outside.display().to_string(),
+        "../outside".to_string(),
+        "data/../../outside/secret.txt".to_string(),
+    ];
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&outside, root.join("linked")).unwrap();
+        lines.push("linked".to_string());
+    }
+
+    for line in lines {
+        fs::write(root.join(ASSETS_FILENAME), format!("{line}\n")).unwrap();
+
+        let err = build_package(&root.join("main.roc"), Compression::Uncompressed).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(
+            err.to_string().contains(&format!("lists {line},")),
+            "{line}: {err}"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! `roc package vendor` copies the tarballs of all the packages a project depends on into one
+//! directory. Pointing ROC_PACKAGE_MIRROR at that directory (as a file:// URL) then lets the
+//! project build without a network connection.
+use crate::cache::{is_package_url, MAX_DOWNLOAD_BYTES};
+use crate::https::{self, PackageMetadata, Problem};
+use crate::mirror::{tarball_filename, Mirrors};
+use bumpalo::Bump;
+use roc_parse::ast::{Collection, ExtractSpaces, Header, Spaced};
+use roc_parse::header::{PackageEntry, To};
+use roc_parse::module::parse_header;
+use roc_parse::state::State;
+use roc_region::all::Loc;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Saves the tarball of every package that `root_module` depends on into `dest_dir`, including
+/// the packages that those packages depend on. Tarballs that are already there are verified
+/// instead of downloaded again. Returns the URLs of the packages, in the order they were found.
+pub fn vendor(
+    root_module: &Path,
+    dest_dir: &Path,
+    mirrors: &Mirrors,
+) -> Result<Vec<String>, Problem> {
+    fs::create_dir_all(dest_dir).map_err(Problem::IoErr)?;
+
+    let mut urls = Vec::new();
+    let mut visited_modules = HashSet::new();
+    let mut stack = vec![root_module.to_path_buf()];
+
+    // Each package gets unpacked, to find the packages it depends on in turn
+    let mut unpacked_dirs = Vec::new();
+
+    while let Some(module_path) = stack.pop() {
+        if !visited_modules.insert(module_path.clone()) {
+            continue;
+        }
+
+        let module_dir = module_path.parent().unwrap_or_else(|| Path::new("."));
+
+        for package in packages_in_header(&module_path)? {
+            if !is_package_url(&package) {
+                // Local packages aren't vendored, but they can depend on packages that are.
+                // One that's missing is for the build to report.
+                let local_module = module_dir.join(&package);
+
+                if local_module.is_file() {
+                    stack.push(local_module);
+                }
+
+                continue;
+            }
+
+            if urls.contains(&package) {
+                continue;
+            }
+
+            let PackageMetadata {
+                content_hash,
+                root_module_filename,
+                ..
+            } = PackageMetadata::try_from(package.as_str()).map_err(Problem::InvalidUrl)?;
+            let dest_file = dest_dir.join(tarball_filename(&package));
+            let unpacked_dir = tempfile::tempdir().map_err(Problem::IoErr)?;
+
+            let hash = if dest_file.exists() {
+                let file_url = format!("file://{}", dest_file.display());
+
+                https::download_and_hash(&file_url, unpacked_dir.path(), MAX_DOWNLOAD_BYTES)?
+            } else {
+                let download_url = mirrors.redirect(&package);
+
+                eprintln!(
+                    "Downloading \u{001b}[36m{download_url}\u{001b}[0m\n    into {}\n",
+                    dest_dir.display()
+                );
+
+                https::download_tarball(
+                    &download_url,
+                    &dest_file,
+                    unpacked_dir.path(),
+                    MAX_DOWNLOAD_BYTES,
+                )?
+            };
+
+            if hash != content_hash {
+                // Don't leave a tarball behind that would fail every build using this directory
+                let _ = fs::remove_file(&dest_file);
+
+                return Err(Problem::InvalidContentHash {
+                    expected: content_hash.to_string(),
+                    actual: hash,
+                });
+            }
+
+            stack.push(
+                unpacked_dir
+                    .path()
+                    .join(root_module_filename.unwrap_or("main.roc")),
+            );
+            unpacked_dirs.push(unpacked_dir);
+            urls.push(package);
+        }
+    }
+
+    Ok(urls)
+}
+
+/// The packages named in a module's header, e.g. its platform and the `packages { .. }`
+fn packages_in_header(path: &Path) -> Result<Vec<String>, Problem> {
+    let arena = Bump::new();
+    let src = arena.alloc_slice_copy(&fs::read(path).map_err(Problem::IoErr)?);
+
+    let module = match parse_header(&arena, State::new(src)) {
+        Ok((module, _)) => module,
+        Err(_) => {
+            return Err(Problem::IoErr(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the header of {} doesn't parse", path.display()),
+            )));
+        }
+    };
+
+    let names = match module.header {
+        Header::App(header) => {
+            let mut names = header
+                .packages
+                .map(|packages| package_names(packages.item))
+                .unwrap_or_default();
+
+            if let To::NewPackage(name) = header.provides.to.value {
+                names.push(name.as_str().to_string());
+            }
+
+            names
+        }
+        Header::Package(header) => package_names(header.packages.item),
+        Header::Platform(header) => package_names(header.packages.item),
+        Header::Interface(_) | Header::Hosted(_) => Vec::new(),
+    };
+
+    Ok(names)
+}
+
+fn package_names<'a>(packages: Collection<'a, Loc<Spaced<'a, PackageEntry<'a>>>>) -> Vec<String> {
+    packages
+        .iter()
+        .map(|entry| {
+            entry
+                .value
+                .extract_spaces()
+                .item
+                .package_name
+                .value
+                .as_str()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn vendor_from_mirror() {
+    use crate::tarball::{self, Compression};
+
+    let package_dir = tempfile::tempdir().unwrap();
+    let package_main = package_dir.path().join("main.roc");
+
+    fs::write(
+        &package_main,
+        "package \"test/pkg\" exposes [Greeting] packages {}\n",
+    )
+    .unwrap();
+    fs::write(
+        package_dir.path().join("Greeting.roc"),
+        "interface Greeting exposes [greeting] imports []\n\ngreeting = \"Hi\"\n",
+    )
+    .unwrap();
+
+    let filename = tarball::build(&package_main, Compression::Brotli).unwrap();
+    let url = format!("https://example.com/packages/{filename}");
+
+    let app_dir = tempfile::tempdir().unwrap();
+    let app_main = app_dir.path().join("main.roc");
+
+    fs::write(
+        &app_main,
+        format!(
+            "app \"test\"\n    packages {{ pkg: \"{url}\", local: \"local/main.roc\" }}\n    imports []\n    provides [main] to \"./platform\"\n\nmain = 1\n"
+        ),
+    )
+    .unwrap();
+    fs::create_dir(app_dir.path().join("local")).unwrap();
+    fs::write(
+        app_dir.path().join("local").join("main.roc"),
+        format!("package \"test/local\" exposes [] packages {{ pkg: \"{url}\" }}\n"),
+    )
+    .unwrap();
+
+    // example.com doesn't have the package, but the mirror does
+    let mirrors = Mirrors::parse(&format!("file://{}", package_dir.path().display())).unwrap();
+    let vendor_dir = app_dir.path().join("vendor");
+
+    assert_eq!(
+        vendor(&app_main, &vendor_dir, &mirrors).unwrap(),
+        vec![url.clone()]
+    );
+    assert!(vendor_dir.join(&filename).is_file());
+
+    // The second time, the vendored tarball is verified without any mirrors
+    assert_eq!(
+        vendor(&app_main, &vendor_dir, &Mirrors::default()).unwrap(),
+        vec![url]
+    );
+}
@@ -12,34 +12,45 @@ use roc_mono::ir::OptLevel;
 use roc_mono::layout::Layout;
 use roc_parse::ast::Expr;
 use roc_repl_eval::eval::jit_to_ast;
-use roc_repl_eval::gen::{compile_to_mono, format_answer, Problems, ReplOutput};
+use roc_repl_eval::gen::{compile_to_mono, format_answer, Problems, ReplImports, ReplOutput};
 use roc_repl_eval::{ReplApp, ReplAppMemory};
 use roc_reporting::report::DEFAULT_PALETTE;
 use roc_std::RocStr;
 use roc_target::TargetInfo;
 use roc_types::pretty_print::{name_and_print_var, DebugPrint};
 use roc_types::subs::Subs;
+use std::time::{Duration, Instant};
 use target_lexicon::Triple;
 
+/// How long an expression took to compile, and then to run
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EvalTimes {
+    pub compile: Duration,
+    pub run: Duration,
+}
+
 pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
+    imports: &ReplImports,
     defs: I,
     src: &str,
     target: Triple,
     opt_level: OptLevel,
-) -> (Option<ReplOutput>, Problems) {
+) -> (Option<ReplOutput>, Problems, EvalTimes) {
     let arena = Bump::new();
     let target_info = TargetInfo::from(&target);
+    let compile_start = Instant::now();
+    let mut times = EvalTimes::default();
 
     let mut loaded;
     let problems;
 
-    match compile_to_mono(&arena, defs, src, target_info, DEFAULT_PALETTE) {
+    match compile_to_mono(&arena, imports, defs, src, target_info, DEFAULT_PALETTE) {
         (Some(mono), probs) => {
             loaded = mono;
             problems = probs;
         }
         (None, probs) => {
-            return (None, probs);
+            return (None, probs, times);
         }
     };
 
@@ -63,7 +74,7 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
             let empty_vec: Vec<String> = Vec::new(); // rustc can't infer the type of this Vec.
             debug_assert_ne!(problems.errors, empty_vec, "Got no errors but also no valid layout for the generated main function in the repl!");
 
-            return (None, problems);
+            return (None, problems, times);
         }
     };
 
@@ -74,6 +85,9 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
 
     let mut app = CliApp { lib };
 
+    times.compile = compile_start.elapsed();
+
+    let run_start = Instant::now();
     let expr = jit_to_ast(
         &arena,
         &mut app,
@@ -85,6 +99,9 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
         layout_interner.into_global().fork(),
         target_info,
     );
+
+    times.run = run_start.elapsed();
+
     let expr_str = format_answer(&arena, expr).to_string();
 
     (
@@ -93,9 +110,39 @@ pub fn gen_and_eval_llvm<'a, I: Iterator<Item = &'a str>>(
             expr_type: expr_type_str,
         }),
         problems,
+        times,
     )
 }
 
+/// Type-checks `src` without running it, and returns its pretty-printed type.
+pub fn check_type<'a, I: Iterator<Item = &'a str>>(
+    imports: &ReplImports,
+    defs: I,
+    src: &str,
+    target: Triple,
+) -> (Option<String>, Problems) {
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&target);
+
+    match compile_to_mono(&arena, imports, defs, src, target_info, DEFAULT_PALETTE) {
+        (Some(mut loaded), problems) => {
+            let (_, main_fn_var) = loaded.exposed_to_host.values.iter().next().unwrap();
+            let main_fn_var = *main_fn_var;
+
+            let expr_type_str = name_and_print_var(
+                main_fn_var,
+                &mut loaded.subs,
+                loaded.module_id,
+                &loaded.interns,
+                DebugPrint::NOTHING,
+            );
+
+            (Some(expr_type_str), problems)
+        }
+        (None, problems) => (None, problems),
+    }
+}
+
 struct CliApp {
     lib: Library,
 }
@@ -1,4 +1,4 @@
-use crate::cli_gen::gen_and_eval_llvm;
+use crate::cli_gen::{check_type, gen_and_eval_llvm, EvalTimes};
 use crate::colors::{BLUE, END_COL, GREEN, PINK};
 use bumpalo::Bump;
 use const_format::concatcp;
@@ -12,11 +12,12 @@ use roc_parse::parser::{EWhen, Either};
 use roc_parse::state::State;
 use roc_parse::{join_alias_to_body, join_ann_to_body};
 use roc_region::all::Loc;
-use roc_repl_eval::gen::{Problems, ReplOutput};
+use roc_repl_eval::gen::{Problems, ReplImports, ReplOutput};
 use rustyline::highlight::{Highlighter, PromptInfo};
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
 use rustyline_derive::{Completer, Helper, Hinter};
 use std::borrow::Cow;
+use std::path::Path;
 use target_lexicon::Triple;
 
 pub const PROMPT: &str = concatcp!(BLUE, "»", END_COL, " ");
@@ -59,6 +60,22 @@ pub const TIPS: &str = concatcp!(
     BLUE,
     "  - ",
     END_COL,
+    ":type <expr> shows the type of an expression without running it\n\n",
+    BLUE,
+    "  - ",
+    END_COL,
+    ":time <expr> shows how long an expression takes to compile and run\n\n",
+    BLUE,
+    "  - ",
+    END_COL,
+    ":load path/to/Module.roc imports an interface module, and :reload checks it again after edits\n\n",
+    BLUE,
+    "  - ",
+    END_COL,
+    ":defs lists the definitions so far\n\n",
+    BLUE,
+    "  - ",
+    END_COL,
     ":help"
 );
 
@@ -74,6 +91,7 @@ pub struct ReplState {
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
     last_auto_ident: u64,
+    imports: ReplImports,
 }
 
 impl Default for ReplState {
@@ -89,6 +107,7 @@ impl ReplState {
             past_defs: Default::default(),
             past_def_idents: Default::default(),
             last_auto_ident: 0,
+            imports: Default::default(),
         }
     }
 
@@ -124,11 +143,26 @@ impl ReplState {
                 // TODO add link to repl tutorial(does not yet exist).
                 Ok(TIPS.to_string())
             }
+            ParseOutcome::Type(src) => Ok(self.type_of(src)),
+            ParseOutcome::Time(src) => Ok(self.time(src, dimensions)),
+            ParseOutcome::Load(path) => Ok(self.load(path)),
+            ParseOutcome::Reload => Ok(self.reload()),
+            ParseOutcome::Defs => Ok(self.defs()),
             ParseOutcome::Exit => Err(0),
         }
     }
 
     pub fn eval_and_format(&mut self, src: &str, dimensions: Option<(usize, usize)>) -> String {
+        self.eval(src, dimensions).0
+    }
+
+    /// Like [ReplState::eval_and_format], but also returns how long the expression took to
+    /// compile and run, if it got that far.
+    fn eval(
+        &mut self,
+        src: &str,
+        dimensions: Option<(usize, usize)>,
+    ) -> (String, Option<EvalTimes>) {
         let arena = Bump::new();
         let pending_past_def;
         let mut opt_var_name;
@@ -156,7 +190,7 @@ impl ReplState {
 
                         // Return early without running eval, since standalone annotations
                         // cannnot be evaluated as expressions.
-                        return String::new();
+                        return (String::new(), None);
                     }
                     ValueDef::Body(
                         Loc {
@@ -236,20 +270,28 @@ impl ReplState {
 
                 // Return early without running eval, since none of these
                 // can be evaluated as expressions.
-                return String::new();
+                return (String::new(), None);
             }
-            ParseOutcome::Empty | ParseOutcome::Help | ParseOutcome::Exit => unreachable!(),
+            ParseOutcome::Empty
+            | ParseOutcome::Help
+            | ParseOutcome::Type(_)
+            | ParseOutcome::Time(_)
+            | ParseOutcome::Load(_)
+            | ParseOutcome::Reload
+            | ParseOutcome::Defs
+            | ParseOutcome::Exit => unreachable!(),
         };
 
         // Record e.g. "val1" as a past def, unless our input was exactly the name of
         // an existing identifer (e.g. I just typed "val1" into the prompt - there's no
         // need to reassign "val1" to "val2" just because I wanted to see what its value was!)
-        let (output, problems) =
+        let (output, problems, times) =
             match opt_var_name.or_else(|| self.past_def_idents.get(src.trim()).cloned()) {
                 Some(existing_ident) => {
                     opt_var_name = Some(existing_ident);
 
                     gen_and_eval_llvm(
+                        &self.imports,
                         self.past_defs.iter().map(|def| def.src.as_str()),
                         src,
                         Triple::host(),
@@ -257,7 +299,8 @@ impl ReplState {
                     )
                 }
                 None => {
-                    let (output, problems) = gen_and_eval_llvm(
+                    let (output, problems, times) = gen_and_eval_llvm(
+                        &self.imports,
                         self.past_defs.iter().map(|def| def.src.as_str()),
                         src,
                         Triple::host(),
@@ -276,7 +319,7 @@ impl ReplState {
                         opt_var_name = None;
                     }
 
-                    (output, problems)
+                    (output, problems, times)
                 }
             };
 
@@ -284,7 +327,151 @@ impl ReplState {
             self.add_past_def(ident, src);
         }
 
-        format_output(output, problems, opt_var_name, dimensions)
+        let opt_times = if output.is_some() && problems.errors.is_empty() {
+            Some(times)
+        } else {
+            None
+        };
+
+        (
+            format_output(output, problems, opt_var_name, dimensions),
+            opt_times,
+        )
+    }
+
+    /// `:type <expr>` prints the type of the expression, without running it or naming it.
+    fn type_of(&self, src: &str) -> String {
+        let (opt_type, problems) = check_type(
+            &self.imports,
+            self.past_defs.iter().map(|def| def.src.as_str()),
+            src,
+            Triple::host(),
+        );
+        let has_errors = !problems.errors.is_empty();
+        let mut buf = format_output(None, problems, None, None);
+
+        if let (Some(expr_type), false) = (opt_type, has_errors) {
+            buf.push('\n');
+            buf.push_str(src.trim());
+            buf.push_str(PINK);
+            buf.push_str(" : ");
+            buf.push_str(END_COL);
+            buf.push_str(&expr_type);
+            buf.push('\n');
+        }
+
+        buf
+    }
+
+    /// `:time <expr>` evaluates the expression as usual, and then reports how long it took
+    /// to compile and to run.
+    fn time(&mut self, src: &str, dimensions: Option<(usize, usize)>) -> String {
+        let (mut buf, opt_times) = self.eval(src, dimensions);
+
+        if let Some(EvalTimes { compile, run }) = opt_times {
+            buf.push_str(&format!(
+                "\n{PINK}compiled in{END_COL} {compile:.2?}{PINK}, ran in{END_COL} {run:.2?}\n"
+            ));
+        }
+
+        buf
+    }
+
+    /// `:load path/to/Module.roc` imports an interface module into the session. All loaded
+    /// modules have to be in the same directory, since that's where their imports come from.
+    fn load(&mut self, path: &str) -> String {
+        let path = Path::new(path);
+        let module_name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) if path.extension().and_then(|ext| ext.to_str()) == Some("roc") => name,
+            _ => {
+                return format!(
+                    "\nI can only load .roc files, like {BLUE}:load path/to/Module.roc{END_COL}\n"
+                )
+            }
+        };
+        let src_dir = match path.canonicalize() {
+            Ok(full_path) => full_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            Err(err) => return format!("\nI couldn't load {}: {}\n", path.display(), err),
+        };
+
+        if !self.imports.modules.is_empty() && self.imports.src_dir != src_dir {
+            return format!(
+                "\nI can only load modules from {}, because that's where the modules I already loaded are.\n",
+                self.imports.src_dir.display()
+            );
+        }
+
+        if self.imports.modules.iter().any(|name| name == module_name) {
+            return self.reload();
+        }
+
+        let previous = self.imports.clone();
+
+        self.imports.src_dir = src_dir;
+        self.imports.modules.push(module_name.to_string());
+
+        match self.check_imports() {
+            Ok(()) => format!("\nLoaded {GREEN}{module_name}{END_COL}\n"),
+            Err(report) => {
+                // Don't keep a module around that breaks every expression from now on
+                self.imports = previous;
+
+                report
+            }
+        }
+    }
+
+    /// `:reload` checks the loaded modules again. They're read from disk for every expression
+    /// anyway, so this is mostly to see any new problems right away.
+    fn reload(&mut self) -> String {
+        if self.imports.modules.is_empty() {
+            return format!(
+                "\nNo modules are loaded yet. Try {BLUE}:load path/to/Module.roc{END_COL}\n"
+            );
+        }
+
+        match self.check_imports() {
+            Ok(()) => format!(
+                "\nReloaded {GREEN}{}{END_COL}\n",
+                self.imports.modules.join(", ")
+            ),
+            Err(report) => report,
+        }
+    }
+
+    fn check_imports(&self) -> Result<(), String> {
+        let (_, problems) = check_type(
+            &self.imports,
+            self.past_defs.iter().map(|def| def.src.as_str()),
+            "{}",
+            Triple::host(),
+        );
+
+        if problems.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format_output(None, problems, None, None))
+        }
+    }
+
+    /// `:defs` lists the definitions entered so far, including the automatically named ones.
+    fn defs(&self) -> String {
+        if self.past_defs.is_empty() {
+            return "\nNo definitions yet.\n".to_string();
+        }
+
+        let mut buf = String::new();
+
+        for PastDef { src, .. } in self.past_defs.iter() {
+            buf.push('\n');
+            buf.push_str(src.trim_end());
+            buf.push('\n');
+        }
+
+        buf
     }
 
     fn next_auto_ident(&mut self) -> u64 {
@@ -310,13 +497,34 @@ enum ParseOutcome<'a> {
     SyntaxErr,
     Empty,
     Help,
+    Type(&'a str),
+    Time(&'a str),
+    Load(&'a str),
+    Reload,
+    Defs,
     Exit,
 }
 
 fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
+    // The argument keeps its trailing newline, so it can be incomplete like any other input
+    if let Some((command, arg)) = line.trim_start().split_once(char::is_whitespace) {
+        let arg = arg.trim_start();
+
+        if !arg.is_empty() {
+            match command.to_lowercase().as_str() {
+                ":type" => return ParseOutcome::Type(arg),
+                ":time" => return ParseOutcome::Time(arg),
+                ":load" => return ParseOutcome::Load(arg.trim_end()),
+                _ => {}
+            }
+        }
+    }
+
     match line.trim().to_lowercase().as_str() {
         "" => ParseOutcome::Empty,
-        ":help" => ParseOutcome::Help,
+        ":help" | ":type" | ":time" | ":load" => ParseOutcome::Help,
+        ":reload" => ParseOutcome::Reload,
+        ":defs" => ParseOutcome::Defs,
         ":exit" | ":quit" | ":q" => ParseOutcome::Exit,
         _ => {
             let src_bytes = line.as_bytes();
@@ -509,8 +717,12 @@ pub fn is_incomplete(input: &str) -> bool {
             // them until they enter a blank line!
             !input.ends_with('\n')
         }
+        ParseOutcome::Type(src) | ParseOutcome::Time(src) => is_incomplete(src),
         ParseOutcome::Empty
         | ParseOutcome::Help
+        | ParseOutcome::Load(_)
+        | ParseOutcome::Reload
+        | ParseOutcome::Defs
         | ParseOutcome::Exit
         | ParseOutcome::ValueDef(_)
         | ParseOutcome::TypeDef(_)
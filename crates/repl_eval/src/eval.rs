@@ -17,7 +17,9 @@ use roc_parse::ast::{AssignedField, Collection, Expr, Pattern, StrLiteral};
 use roc_region::all::{Loc, Region};
 use roc_std::RocDec;
 use roc_target::TargetInfo;
-use roc_types::subs::{Content, FlatType, GetSubsSlice, RecordFields, Subs, UnionTags, Variable};
+use roc_types::subs::{
+    Content, FlatType, GetSubsSlice, RecordFields, Subs, TupleElems, UnionTags, Variable,
+};
 
 use crate::{ReplApp, ReplAppMemory};
 
@@ -429,6 +431,9 @@ fn jit_to_ast_help<'a, A: ReplApp<'a>>(
                 Content::Structure(FlatType::EmptyRecord) => {
                     struct_to_ast(env, mem, addr, RecordFields::empty())
                 }
+                Content::Structure(FlatType::Tuple(elems, _)) => {
+                    struct_to_ast_tuple(env, mem, addr, *elems)
+                }
                 Content::Structure(FlatType::TagUnion(tags, _)) => {
                     let (tag_name, payload_vars) = unpack_single_element_tag_union(env.subs, *tags);
 
@@ -607,6 +612,9 @@ fn addr_to_ast<'a, M: ReplAppMemory>(
             Content::Structure(FlatType::Record(fields, _)) => {
                 struct_to_ast(env, mem, addr, *fields)
             }
+            Content::Structure(FlatType::Tuple(elems, _)) => {
+                struct_to_ast_tuple(env, mem, addr, *elems)
+            }
             Content::Structure(FlatType::TagUnion(tags, _)) => {
                 debug_assert_eq!(tags.len(), 1);
 
@@ -1093,6 +1101,79 @@ fn struct_to_ast<'a, 'env, M: ReplAppMemory>(
     }
 }
 
+fn struct_to_ast_tuple<'a, 'env, M: ReplAppMemory>(
+    env: &mut Env<'a, 'env>,
+    mem: &'a M,
+    addr: usize,
+    tuple_elems: TupleElems,
+) -> Expr<'a> {
+    let arena = env.arena;
+    let subs = env.subs;
+    let target_info = env.target_info;
+
+    let mut elems = Vec::with_capacity_in(tuple_elems.len(), arena);
+
+    for (index, elem_var) in tuple_elems.sorted_iterator(subs, Variable::EMPTY_TUPLE) {
+        let elem_layout = env
+            .layout_cache
+            .from_var(arena, elem_var, env.subs)
+            .unwrap();
+
+        elems.push((index, elem_var, elem_layout));
+    }
+
+    // The elements are stored in the order of the struct layout (by descending alignment, then
+    // index), but we want to render them in the order of the tuple type.
+    let mut in_memory_order: Vec<_> = Vec::from_iter_in(0..elems.len(), arena);
+    in_memory_order.sort_by(|a, b| {
+        let (index1, _, layout1) = &elems[*a];
+        let (index2, _, layout2) = &elems[*b];
+
+        layout::cmp_fields(
+            &env.layout_cache.interner,
+            index1,
+            layout1,
+            index2,
+            layout2,
+            target_info,
+        )
+    });
+
+    let mut elem_addrs = Vec::from_iter_in(std::iter::repeat(addr).take(elems.len()), arena);
+    let mut elem_addr = addr;
+
+    for position in in_memory_order {
+        elem_addrs[position] = elem_addr;
+
+        // Advance the element pointer to the next element.
+        elem_addr += elems[position]
+            .2
+            .stack_size(&env.layout_cache.interner, target_info) as usize;
+    }
+
+    let mut output = Vec::with_capacity_in(elems.len(), arena);
+
+    for ((_, elem_var, elem_layout), elem_addr) in elems.into_iter().zip(elem_addrs) {
+        let loc_expr = &*arena.alloc(Loc {
+            value: addr_to_ast(
+                env,
+                mem,
+                elem_addr,
+                &elem_layout,
+                WhenRecursive::Unreachable,
+                elem_var,
+            ),
+            region: Region::zero(),
+        });
+
+        output.push(loc_expr);
+    }
+
+    let output = output.into_bump_slice();
+
+    Expr::Tuple(Collection::with_items(output))
+}
+
 fn unpack_single_element_tag_union(subs: &Subs, tags: UnionTags) -> (&TagName, &[Variable]) {
     let (tag_name_index, payload_vars_index) = tags.iter_all().next().unwrap();
 
@@ -1,5 +1,5 @@
 use bumpalo::Bump;
-use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
+use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, OptLevel, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
 use roc_problem::Severity;
 use roc_reporting::report::Palette;
@@ -44,16 +44,30 @@ impl Problems {
     }
 }
 
+/// Interface modules that the REPL session imports, e.g. after `:load path/to/Json.roc`
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ReplImports {
+    /// The directory the modules are in, and where their own imports are looked up
+    pub src_dir: PathBuf,
+    pub modules: Vec<String>,
+}
+
 pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
     arena: &'a Bump,
+    imports: &ReplImports,
     defs: I,
     expr: &str,
     target_info: TargetInfo,
     palette: Palette,
 ) -> (Option<MonomorphizedModule<'a>>, Problems) {
     let filename = PathBuf::from("");
-    let src_dir = PathBuf::from("fake/test/path");
-    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, defs, expr);
+    let src_dir = if imports.modules.is_empty() {
+        PathBuf::from("fake/test/path")
+    } else {
+        imports.src_dir.clone()
+    };
+    let (bytes_before_expr, module_src) =
+        promote_expr_to_module(arena, &imports.modules, defs, expr);
     let exposed_types = Default::default();
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -68,6 +82,7 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             palette,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Executable,
+            opt_level: OptLevel::Normal,
         },
     );
 
@@ -94,6 +109,7 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
     };
 
     let MonomorphizedModule {
+        module_id: repl_module_id,
         interns,
         sources,
         can_problems,
@@ -116,7 +132,8 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             continue;
         }
 
-        let line_info = LineInfo::new(module_src);
+        let is_repl_module = *home == *repl_module_id;
+        let line_info = LineInfo::new(if is_repl_module { module_src } else { &src[..] });
         let src_lines: Vec<&str> = src.split('\n').collect();
 
         // Report parsing and canonicalization problems
@@ -127,7 +144,10 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             // because they must be part of the defs (excluding the most renently added def,
             // if that's the one being evaluated) and therefore not things we should show.
             // This filters out things like shadowing warnings and unused def warnings.
-            if problem.region().unwrap_or_default().end().offset as usize >= bytes_before_expr {
+            // Problems in modules loaded into the session are always shown.
+            if !is_repl_module
+                || problem.region().unwrap_or_default().end().offset as usize >= bytes_before_expr
+            {
                 let report = can_problem(&alloc, &line_info, module_path.clone(), problem);
                 let severity = report.severity;
                 let mut buf = String::new();
@@ -169,6 +189,7 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
 
 fn promote_expr_to_module<'a, 'i, I: Iterator<Item = &'i str>>(
     arena: &'a Bump,
+    imports: &[String],
     defs: I,
     expr: &str,
 ) -> (usize, &'a str) {
@@ -176,7 +197,15 @@ fn promote_expr_to_module<'a, 'i, I: Iterator<Item = &'i str>>(
     const REPL_MODULE_MAIN_DEF: &str = "replOutput =\n";
     const INDENT: &str = "    ";
 
-    let mut buffer = bumpalo::collections::string::String::from_str_in(REPL_MODULE_HEADER, arena);
+    let mut buffer = bumpalo::collections::string::String::new_in(arena);
+
+    if imports.is_empty() {
+        buffer.push_str(REPL_MODULE_HEADER);
+    } else {
+        buffer.push_str("app \"app\" imports [");
+        buffer.push_str(&imports.join(", "));
+        buffer.push_str("] provides [replOutput] to \"./platform\"\n\n");
+    }
 
     for line in defs {
         // don't indent the defs
@@ -17,6 +17,8 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+pub mod report;
+#[cfg(not(windows))]
 pub mod run;
 
 #[cfg(not(windows))]
@@ -101,11 +103,47 @@ mod test {
     use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
     use target_lexicon::Triple;
 
-    use crate::run::expect_mono_module_to_dylib;
+    use crate::run::{
+        expect_mono_module_to_dylib, expect_mono_module_to_dylib_dev, ExpectBackend, ExpectOptions,
+    };
 
     use super::*;
 
     fn run_expect_test(source: &str, expected: &str) {
+        run_expect_test_with(source, expected, ExpectBackend::Llvm)
+    }
+
+    fn run_expect_test_with(source: &str, expected: &str, backend: ExpectBackend) {
+        run_expect_test_with_options(source, expected, backend, ExpectOptions::default());
+    }
+
+    fn run_expect_test_with_options(
+        source: &str,
+        expected: &str,
+        backend: ExpectBackend,
+        options: ExpectOptions<'_>,
+    ) -> crate::run::ExpectSummary {
+        run_expect_test_with_mode(source, expected, backend, options, ExecutionMode::Test)
+    }
+
+    /// Like `roc test --doc`, which also runs the examples in doc comments
+    fn run_doc_test(source: &str, expected: &str) {
+        run_expect_test_with_mode(
+            source,
+            expected,
+            ExpectBackend::Llvm,
+            ExpectOptions::default(),
+            ExecutionMode::DocTest,
+        );
+    }
+
+    fn run_expect_test_with_mode(
+        source: &str,
+        expected: &str,
+        backend: ExpectBackend,
+        options: ExpectOptions<'_>,
+        exec_mode: ExecutionMode,
+    ) -> crate::run::ExpectSummary {
         let arena = bumpalo::Bump::new();
         let arena = &arena;
 
@@ -126,7 +164,8 @@ mod test {
             render: RenderTarget::ColorTerminal,
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
-            exec_mode: ExecutionMode::Test,
+            exec_mode,
+            opt_level,
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -150,13 +189,16 @@ mod test {
 
         let interns = loaded.interns.clone();
 
-        let (lib, expects, layout_interner) = expect_mono_module_to_dylib(
-            arena,
-            target.clone(),
-            loaded,
-            opt_level,
-            LlvmBackendMode::CliTest,
-        )
+        let (lib, expects, layout_interner) = match backend {
+            ExpectBackend::Llvm => expect_mono_module_to_dylib(
+                arena,
+                target.clone(),
+                loaded,
+                opt_level,
+                LlvmBackendMode::CliTest,
+            ),
+            ExpectBackend::Dev => expect_mono_module_to_dylib_dev(arena, target.clone(), loaded),
+        }
         .unwrap();
 
         let arena = &bumpalo::Bump::new();
@@ -173,7 +215,7 @@ mod test {
         unsafe { set_shared_buffer((shared_buffer.as_mut_ptr(), BUFFER_SIZE), &mut result) };
 
         let mut writer = Vec::with_capacity(1024);
-        let (_failed, _passed) = crate::run::run_expects_with_memory(
+        let summary = crate::run::run_expects_with_memory(
             &mut writer,
             RenderTarget::ColorTerminal,
             arena,
@@ -182,6 +224,7 @@ mod test {
             &lib,
             &mut expectations,
             expects,
+            options,
             &mut memory,
         )
         .unwrap();
@@ -209,6 +252,8 @@ mod test {
         } else {
             assert_eq!(actual, expected);
         }
+
+        summary
     }
 
     #[test]
@@ -248,6 +293,131 @@ mod test {
         );
     }
 
+    #[test]
+    fn filter_by_source() {
+        run_expect_test_with_options(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect 1 == 2
+
+                expect 2 == 3
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                7│  expect 2 == 3
+                    ^^^^^^^^^^^^^
+                "#
+            ),
+            ExpectBackend::Llvm,
+            ExpectOptions {
+                filter: Some("2 == 3"),
+                fail_fast: false,
+            },
+        );
+    }
+
+    #[test]
+    fn fail_fast() {
+        run_expect_test_with_options(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect 1 == 2
+
+                expect 2 == 3
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│  expect 1 == 2
+                    ^^^^^^^^^^^^^
+                "#
+            ),
+            ExpectBackend::Llvm,
+            ExpectOptions {
+                filter: None,
+                fail_fast: true,
+            },
+        );
+    }
+
+    #[test]
+    fn filter_and_fail_fast() {
+        let summary = run_expect_test_with_options(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect 1 == 1
+
+                expect 2 == 3
+
+                expect 1 + 1 == 3
+
+                expect 2 + 2 == 5
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                7│  expect 2 == 3
+                    ^^^^^^^^^^^^^
+                "#
+            ),
+            ExpectBackend::Llvm,
+            ExpectOptions {
+                filter: Some("== 3"),
+                fail_fast: true,
+            },
+        );
+
+        // `expect 2 + 2 == 5` was filtered out, so it doesn't count as skipped as well
+        assert_eq!(summary.filtered_out, 2);
+        assert_eq!(summary.failed(), 1);
+        assert_eq!(summary.skipped, 1);
+    }
+
+    #[test]
+    fn doc_test_fail() {
+        run_doc_test(
+            indoc!(
+                r#"
+                interface Test exposes [len] imports []
+
+                ## ```roc
+                ## [1, 2] |> len # ==> 2
+                ## len [] # ==> 1
+                ## ```
+                len : List U8 -> Nat
+                len = \list -> List.len list
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                4│>  ## [1, 2] |> len # ==> 2
+                5│>  ## len [] # ==> 1
+                "#
+            ),
+        );
+    }
+
     #[test]
     fn lookup_integer() {
         run_expect_test(
@@ -286,6 +456,248 @@ mod test {
         );
     }
 
+    #[test]
+    fn equals_fail_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect 1 == 2
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│  expect 1 == 2
+                    ^^^^^^^^^^^^^
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
+    #[test]
+    fn lookup_integer_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    a = 1u8
+                    b = 2u8
+
+                    a == b
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│>  expect
+                6│>      a = 1u8
+                7│>      b = 2u8
+                8│>
+                9│>      a == b
+
+                When it failed, these variables had these values:
+
+                a : U8
+                a = 1
+
+                b : U8
+                b = 2
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
+    #[test]
+    fn lookup_list_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    items = [1u8, 2]
+
+                    List.len items == 3
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│>  expect
+                6│>      items = [1u8, 2]
+                7│>
+                8│>      List.len items == 3
+
+                When it failed, these variables had these values:
+
+                items : List U8
+                items = [1, 2]
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
+    #[test]
+    fn lookup_record_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    record = { name: "roc", items: [1u8, 2] }
+
+                    List.len record.items == 3
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│>  expect
+                6│>      record = { name: "roc", items: [1u8, 2] }
+                7│>
+                8│>      List.len record.items == 3
+
+                When it failed, these variables had these values:
+
+                record : { items : List U8, name : Str }
+                record = { items: [1, 2], name: "roc" }
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
+    #[test]
+    fn lookup_nested_list_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    lists = [[1u8, 2], [], [3]]
+                    names = ["a", "bc"]
+
+                    List.len lists == List.len names
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│>  expect
+                6│>      lists = [[1u8, 2], [], [3]]
+                7│>      names = ["a", "bc"]
+                8│>
+                9│>      List.len lists == List.len names
+
+                When it failed, these variables had these values:
+
+                lists : List (List U8)
+                lists = [[1, 2], [], [3]]
+
+                names : List Str
+                names = ["a", "bc"]
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
+    #[test]
+    fn lookup_clone_result_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    a : Result Str Str
+                    a = Ok "a string so long that it cannot be short"
+
+                    Result.isErr a
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│>  expect
+                6│>      a : Result Str Str
+                7│>      a = Ok "a string so long that it cannot be short"
+                8│>
+                9│>      Result.isErr a
+
+                When it failed, these variables had these values:
+
+                a : Result Str Str
+                a = Ok "a string so long that it cannot be short"
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
+    #[test]
+    fn lookup_list_of_results_dev() {
+        run_expect_test_with(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    results : List (Result U8 Str)
+                    results = [Ok 1, Err "a string so long that it cannot be short"]
+
+                    List.len results == 3
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                5│>  expect
+                6│>      results : List (Result U8 Str)
+                7│>      results = [Ok 1, Err "a string so long that it cannot be short"]
+                8│>
+                9│>      List.len results == 3
+
+                When it failed, these variables had these values:
+
+                results : List (Result U8 Str)
+                results = [Ok 1, Err "a string so long that it cannot be short"]
+                "#
+            ),
+            ExpectBackend::Dev,
+        );
+    }
+
     #[test]
     fn lookup_list_of_strings() {
         run_expect_test(
@@ -0,0 +1,201 @@
+//! Machine-readable reports of a `roc test` run, for CI dashboards.
+use std::fmt::Write;
+
+use serde_json::{json, Value};
+
+use crate::run::{ExpectOutcome, ExpectSummary};
+
+/// A JUnit XML document, with one `<testsuite>` per module and one `<testcase>` per expect.
+pub fn junit_report(summary: &ExpectSummary) -> String {
+    let mut modules: Vec<(&str, Vec<&ExpectOutcome>)> = Vec::new();
+
+    for outcome in summary.outcomes.iter() {
+        match modules
+            .iter_mut()
+            .find(|(name, _)| *name == outcome.module_name)
+        {
+            Some((_, outcomes)) => outcomes.push(outcome),
+            None => modules.push((&outcome.module_name, vec![outcome])),
+        }
+    }
+
+    let seconds = |outcomes: &[&ExpectOutcome]| {
+        outcomes
+            .iter()
+            .map(|outcome| outcome.duration.as_secs_f64())
+            .sum::<f64>()
+    };
+    let failures =
+        |outcomes: &[&ExpectOutcome]| outcomes.iter().filter(|outcome| !outcome.passed()).count();
+
+    let all: Vec<&ExpectOutcome> = summary.outcomes.iter().collect();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    writeln!(
+        xml,
+        "<testsuites name=\"roc test\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+        all.len(),
+        failures(&all),
+        summary.skipped,
+        seconds(&all),
+    )
+    .unwrap();
+
+    for (module_name, outcomes) in modules {
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.6}\">",
+            escape_xml(module_name),
+            outcomes.len(),
+            failures(&outcomes),
+            seconds(&outcomes),
+        )
+        .unwrap();
+
+        for outcome in outcomes {
+            write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\" time=\"{:.6}\"",
+                escape_xml(&outcome.name),
+                escape_xml(&outcome.module_name),
+                escape_xml(&outcome.path.to_string_lossy()),
+                outcome.line,
+                outcome.duration.as_secs_f64(),
+            )
+            .unwrap();
+
+            match &outcome.failure {
+                None => xml.push_str("/>\n"),
+                Some(failure) => {
+                    writeln!(
+                        xml,
+                        ">\n      <failure message=\"This expectation failed\">{}</failure>\n    </testcase>",
+                        escape_xml(failure),
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+/// A JSON document listing every expect that ran.
+pub fn json_report(summary: &ExpectSummary) -> Value {
+    json!({
+        "passed": summary.passed(),
+        "failed": summary.failed(),
+        "filtered_out": summary.filtered_out,
+        "skipped": summary.skipped,
+        "expects": summary
+            .outcomes
+            .iter()
+            .map(|outcome| json!({
+                "module": outcome.module_name,
+                "name": outcome.name,
+                "file": outcome.path,
+                "line": outcome.line,
+                "duration_ms": outcome.duration.as_secs_f64() * 1000.0,
+                "passed": outcome.passed(),
+                "failure": outcome.failure,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0 can't represent most control characters, even escaped
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn outcome(module_name: &str, name: &str, failure: Option<&str>) -> ExpectOutcome {
+        ExpectOutcome {
+            module_name: module_name.to_string(),
+            name: name.to_string(),
+            path: PathBuf::from(format!("{module_name}.roc")),
+            line: 3,
+            duration: Duration::from_millis(2),
+            failure: failure.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn junit_groups_by_module() {
+        let summary = ExpectSummary {
+            outcomes: vec![
+                outcome("Parser", "expect parse \"1\" == Ok 1", None),
+                outcome("Lexer", "expect lex \"\" == []", None),
+                outcome("Parser", "expect 1 < 2", Some("This expectation failed")),
+            ],
+            filtered_out: 0,
+            skipped: 1,
+        };
+
+        assert_eq!(
+            junit_report(&summary),
+            indoc::indoc!(
+                r#"
+                <?xml version="1.0" encoding="UTF-8"?>
+                <testsuites name="roc test" tests="3" failures="1" skipped="1" time="0.006000">
+                  <testsuite name="Parser" tests="2" failures="1" time="0.004000">
+                    <testcase name="expect parse &quot;1&quot; == Ok 1" classname="Parser" file="Parser.roc" line="3" time="0.002000"/>
+                    <testcase name="expect 1 &lt; 2" classname="Parser" file="Parser.roc" line="3" time="0.002000">
+                      <failure message="This expectation failed">This expectation failed</failure>
+                    </testcase>
+                  </testsuite>
+                  <testsuite name="Lexer" tests="1" failures="0" time="0.002000">
+                    <testcase name="expect lex &quot;&quot; == []" classname="Lexer" file="Lexer.roc" line="3" time="0.002000"/>
+                  </testsuite>
+                </testsuites>
+                "#
+            )
+            .trim_start()
+        );
+    }
+
+    #[test]
+    fn json_counts() {
+        let summary = ExpectSummary {
+            outcomes: vec![
+                outcome("Parser", "expect 1 == 1", None),
+                outcome("Parser", "expect 1 == 2", Some("This expectation failed")),
+            ],
+            filtered_out: 4,
+            skipped: 0,
+        };
+
+        let report = json_report(&summary);
+
+        assert_eq!(report["passed"], 1);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(report["filtered_out"], 4);
+        assert_eq!(report["expects"][1]["failure"], "This expectation failed");
+        assert_eq!(report["expects"][0]["failure"], Value::Null);
+    }
+}
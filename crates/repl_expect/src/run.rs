@@ -1,17 +1,19 @@
 use std::{
     os::unix::process::parent_id,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, AtomicU32},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use bumpalo::collections::Vec as BumpVec;
 use bumpalo::Bump;
 use inkwell::context::Context;
-use roc_build::link::llvm_module_to_dylib;
+use roc_build::link::{dev_object_to_dylib, llvm_module_to_dylib};
 use roc_can::expr::ExpectLookup;
-use roc_collections::{MutSet, VecMap};
+use roc_collections::{MutMap, MutSet, VecMap};
 use roc_error_macros::internal_error;
 use roc_gen_llvm::{
     llvm::{build::LlvmBackendMode, externs::add_default_roc_externs},
@@ -21,8 +23,11 @@ use roc_gen_llvm::{
 use roc_intern::{GlobalInterner, SingleThreadedInterner};
 use roc_load::{Expectations, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
-use roc_mono::{ir::OptLevel, layout::Layout};
-use roc_region::all::Region;
+use roc_mono::{
+    ir::{CrashTag, OptLevel},
+    layout::{Layout, LayoutIds},
+};
+use roc_region::all::{LineInfo, Region};
 use roc_reporting::{error::expect::Renderer, report::RenderTarget};
 use roc_target::TargetInfo;
 use roc_types::subs::Subs;
@@ -137,7 +142,7 @@ pub fn run_inline_expects<'a, W: std::io::Write>(
     let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
     let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
 
-    run_expects_with_memory(
+    let summary = run_expects_with_memory(
         writer,
         render_target,
         arena,
@@ -146,8 +151,11 @@ pub fn run_inline_expects<'a, W: std::io::Write>(
         lib,
         expectations,
         expects,
+        ExpectOptions::default(),
         &mut memory,
-    )
+    )?;
+
+    Ok((summary.failed(), summary.passed()))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -160,7 +168,8 @@ pub fn run_toplevel_expects<'a, W: std::io::Write>(
     lib: &libloading::Library,
     expectations: &mut VecMap<ModuleId, Expectations>,
     expects: ExpectFunctions<'_>,
-) -> std::io::Result<(usize, usize)> {
+    options: ExpectOptions<'_>,
+) -> std::io::Result<ExpectSummary> {
     let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
     let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
 
@@ -173,10 +182,143 @@ pub fn run_toplevel_expects<'a, W: std::io::Write>(
         lib,
         expectations,
         expects,
+        options,
         &mut memory,
     )
 }
 
+/// Which top-level expects to run, and when to stop.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExpectOptions<'f> {
+    /// Only run the expects whose module name, or first line of source, contains this.
+    pub filter: Option<&'f str>,
+    /// Skip the remaining expects after the first one fails.
+    pub fail_fast: bool,
+}
+
+impl ExpectOptions<'_> {
+    fn includes(&self, outcome: &ExpectOutcome) -> bool {
+        match self.filter {
+            None => true,
+            Some(pattern) => {
+                outcome.module_name.contains(pattern) || outcome.name.contains(pattern)
+            }
+        }
+    }
+}
+
+/// How one top-level expect went.
+#[derive(Debug, Clone)]
+pub struct ExpectOutcome {
+    pub module_name: String,
+    /// The first line of the expect's source, e.g. `expect List.len [] == 0`
+    pub name: String,
+    pub path: PathBuf,
+    /// The (1-based) line the expect starts on
+    pub line: u32,
+    pub duration: Duration,
+    /// The failure as it was printed, minus the colors. `None` if the expect passed.
+    pub failure: Option<String>,
+}
+
+impl ExpectOutcome {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExpectSummary {
+    /// The expects that ran, in the order they ran in
+    pub outcomes: Vec<ExpectOutcome>,
+    /// How many expects the filter excluded
+    pub filtered_out: usize,
+    /// How many expects were not run because an earlier one failed
+    pub skipped: usize,
+}
+
+impl ExpectSummary {
+    pub fn failed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| !outcome.passed())
+            .count()
+    }
+
+    pub fn passed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.passed())
+            .count()
+    }
+}
+
+/// Names and places expects, reading each module's source only once.
+#[derive(Default)]
+struct ExpectDescriber {
+    sources: MutMap<ModuleId, (String, LineInfo)>,
+}
+
+impl ExpectDescriber {
+    fn describe(
+        &mut self,
+        interns: &Interns,
+        expectations: &VecMap<ModuleId, Expectations>,
+        expect: &ToplevelExpect<'_>,
+    ) -> ExpectOutcome {
+        let module_id = expect.symbol.module_id();
+        let path = expectations.get(&module_id).unwrap().path.to_owned();
+
+        let (source, line_info) = self.sources.entry(module_id).or_insert_with(|| {
+            let source = std::fs::read_to_string(&path).unwrap_or_default();
+            let line_info = LineInfo::new(&source);
+
+            (source, line_info)
+        });
+
+        let start = expect.region.start().offset as usize;
+        let end = expect.region.end().offset as usize;
+        let name = source
+            .get(start..end)
+            .and_then(|text| text.lines().next())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        ExpectOutcome {
+            module_name: interns.module_name(module_id).as_str().to_string(),
+            name,
+            path,
+            line: line_info.convert_region(expect.region).start.line + 1,
+            duration: Duration::ZERO,
+            failure: None,
+        }
+    }
+}
+
+/// Run one expect (via `run`), capturing what it prints so the failure can be reported later.
+fn record_outcome<W: std::io::Write>(
+    writer: &mut W,
+    mut outcome: ExpectOutcome,
+    run: impl FnOnce(&mut Vec<u8>) -> std::io::Result<bool>,
+) -> std::io::Result<ExpectOutcome> {
+    let mut buffer = Vec::new();
+
+    let start = Instant::now();
+    let passed = run(&mut buffer)?;
+    outcome.duration = start.elapsed();
+
+    writer.write_all(&buffer)?;
+
+    if !passed {
+        let plain = strip_ansi_escapes::strip(&buffer)?;
+
+        outcome.failure = Some(String::from_utf8_lossy(&plain).trim().to_string());
+    }
+
+    Ok(outcome)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
     writer: &mut W,
@@ -187,52 +329,108 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
     lib: &libloading::Library,
     expectations: &mut VecMap<ModuleId, Expectations>,
     expects: ExpectFunctions<'_>,
+    options: ExpectOptions<'_>,
     memory: &mut ExpectMemory,
-) -> std::io::Result<(usize, usize)> {
-    let mut failed = 0;
-    let mut passed = 0;
-
-    for expect in expects.fx {
-        let result = run_expect_fx(
-            writer,
-            render_target,
-            arena,
-            interns,
-            layout_interner,
-            lib,
-            expectations,
-            memory,
-            expect,
-        )?;
-
-        match result {
-            true => passed += 1,
-            false => failed += 1,
+) -> std::io::Result<ExpectSummary> {
+    let mut summary = ExpectSummary::default();
+    let mut describer = ExpectDescriber::default();
+
+    // Filter first, so that expects the filter excludes aren't counted as skipped too
+    let fx = filter_expects(
+        &mut describer,
+        interns,
+        expectations,
+        options,
+        &expects.fx,
+        &mut summary,
+    );
+    let pure = filter_expects(
+        &mut describer,
+        interns,
+        expectations,
+        options,
+        &expects.pure,
+        &mut summary,
+    );
+    let total = fx.len() + pure.len();
+
+    for (outcome, expect) in fx {
+        let outcome = record_outcome(writer, outcome, |buffer| {
+            run_expect_fx(
+                buffer,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expects.backend,
+                expectations,
+                memory,
+                expect,
+            )
+        })?;
+
+        let stop = options.fail_fast && !outcome.passed();
+        summary.outcomes.push(outcome);
+
+        if stop {
+            summary.skipped = total - summary.outcomes.len();
+            return Ok(summary);
         }
     }
 
     memory.set_shared_buffer(lib);
 
-    for expect in expects.pure {
-        let result = run_expect_pure(
-            writer,
-            render_target,
-            arena,
-            interns,
-            layout_interner,
-            lib,
-            expectations,
-            memory,
-            expect,
-        )?;
-
-        match result {
-            true => passed += 1,
-            false => failed += 1,
+    for (outcome, expect) in pure {
+        let outcome = record_outcome(writer, outcome, |buffer| {
+            run_expect_pure(
+                buffer,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expects.backend,
+                expectations,
+                memory,
+                expect,
+            )
+        })?;
+
+        let stop = options.fail_fast && !outcome.passed();
+        summary.outcomes.push(outcome);
+
+        if stop {
+            summary.skipped = total - summary.outcomes.len();
+            return Ok(summary);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Describe each expect, leaving out the ones the filter excludes and counting them instead.
+fn filter_expects<'e>(
+    describer: &mut ExpectDescriber,
+    interns: &Interns,
+    expectations: &VecMap<ModuleId, Expectations>,
+    options: ExpectOptions<'_>,
+    expects: &[ToplevelExpect<'e>],
+    summary: &mut ExpectSummary,
+) -> Vec<(ExpectOutcome, ToplevelExpect<'e>)> {
+    let mut included = Vec::with_capacity(expects.len());
+
+    for expect in expects {
+        let outcome = describer.describe(interns, expectations, expect);
+
+        if options.includes(&outcome) {
+            included.push((outcome, *expect));
+        } else {
+            summary.filtered_out += 1;
         }
     }
 
-    Ok((failed, passed))
+    included
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -243,15 +441,14 @@ fn run_expect_pure<'a, W: std::io::Write>(
     interns: &'a Interns,
     layout_interner: &Arc<GlobalInterner<'a, Layout<'a>>>,
     lib: &libloading::Library,
+    backend: ExpectBackend,
     expectations: &mut VecMap<ModuleId, Expectations>,
     shared_memory: &mut ExpectMemory,
     expect: ToplevelExpect<'_>,
 ) -> std::io::Result<bool> {
-    use roc_gen_llvm::try_run_jit_function;
-
     let sequence = ExpectSequence::new(shared_memory.ptr.cast());
 
-    let result: Result<(), (String, _)> = try_run_jit_function!(lib, expect.name, (), |v: ()| v);
+    let result = call_expect(lib, backend, expect.name);
 
     let shared_memory_ptr: *const u8 = shared_memory.ptr.cast();
 
@@ -301,6 +498,7 @@ fn run_expect_fx<'a, W: std::io::Write>(
     interns: &'a Interns,
     layout_interner: &Arc<GlobalInterner<'a, Layout<'a>>>,
     lib: &libloading::Library,
+    backend: ExpectBackend,
     expectations: &mut VecMap<ModuleId, Expectations>,
     parent_memory: &mut ExpectMemory,
     expect: ToplevelExpect<'_>,
@@ -313,16 +511,13 @@ fn run_expect_fx<'a, W: std::io::Write>(
         0 => unsafe {
             // we are the child
 
-            use roc_gen_llvm::try_run_jit_function;
-
             let mut child_memory = parent_memory.reuse_mmap().unwrap();
 
             let sequence = ExpectSequence::new(child_memory.ptr);
 
             child_memory.set_shared_buffer(lib);
 
-            let result: Result<(), (String, _)> =
-                try_run_jit_function!(lib, expect.name, (), |v: ()| v);
+            let result = call_expect(lib, backend, expect.name);
 
             if let Err((msg, _)) = result {
                 panic!("roc panic {}", msg);
@@ -690,10 +885,45 @@ pub struct ToplevelExpect<'a> {
     pub region: Region,
 }
 
+/// The backend that generated the expect functions, which determines how they are called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectBackend {
+    /// Each expect is wrapped to return a `RocCallResult`, so crashes are caught.
+    Llvm,
+    /// Each expect is a plain C function; a crash ends the process.
+    Dev,
+}
+
 #[derive(Debug)]
 pub struct ExpectFunctions<'a> {
     pub pure: BumpVec<'a, ToplevelExpect<'a>>,
     pub fx: BumpVec<'a, ToplevelExpect<'a>>,
+    pub backend: ExpectBackend,
+}
+
+fn call_expect(
+    lib: &libloading::Library,
+    backend: ExpectBackend,
+    name: &str,
+) -> Result<(), (String, CrashTag)> {
+    match backend {
+        ExpectBackend::Llvm => {
+            use roc_gen_llvm::try_run_jit_function;
+
+            try_run_jit_function!(lib, name, (), |v: ()| v)
+        }
+        ExpectBackend::Dev => {
+            unsafe {
+                let expect: libloading::Symbol<unsafe extern "C" fn()> = lib
+                    .get(name.as_bytes())
+                    .unwrap_or_else(|_| internal_error!("the expect `{}` was not exposed", name));
+
+                expect();
+            }
+
+            Ok(())
+        }
+    }
 }
 
 pub fn expect_mono_module_to_dylib<'a>(
@@ -794,6 +1024,7 @@ pub fn expect_mono_module_to_dylib<'a>(
     let expects = ExpectFunctions {
         pure: expects_pure,
         fx: expects_fx,
+        backend: ExpectBackend::Llvm,
     };
 
     env.dibuilder.finalize();
@@ -821,3 +1052,79 @@ pub fn expect_mono_module_to_dylib<'a>(
 
     llvm_module_to_dylib(env.module, &target, opt_level).map(|lib| (lib, expects, layout_interner))
 }
+
+/// Like [`expect_mono_module_to_dylib`], but generates the code with the dev backend.
+pub fn expect_mono_module_to_dylib_dev<'a>(
+    arena: &'a Bump,
+    target: Triple,
+    loaded: MonomorphizedModule<'a>,
+) -> Result<
+    (
+        libloading::Library,
+        ExpectFunctions<'a>,
+        SingleThreadedInterner<'a, Layout<'a>>,
+    ),
+    libloading::Error,
+> {
+    let MonomorphizedModule {
+        module_id,
+        toplevel_expects,
+        procedures,
+        mut interns,
+        layout_interner,
+        ..
+    } = loaded;
+
+    // The dev backend names exposed procedures like this, see `build_proc_symbol`.
+    let mut layout_ids = LayoutIds::default();
+    let mut expose = |(symbol, region): (Symbol, Region)| {
+        let (_, proc_layout) = procedures
+            .keys()
+            .find(|(proc_symbol, _)| *proc_symbol == symbol)
+            .unwrap_or_else(|| internal_error!("no procedure for the expect {:?}", symbol));
+
+        let name = layout_ids
+            .get_toplevel(symbol, proc_layout)
+            .to_exposed_symbol_string(symbol, &interns);
+
+        ToplevelExpect {
+            name: arena.alloc_str(&name),
+            symbol,
+            region,
+        }
+    };
+
+    let expects_pure =
+        BumpVec::from_iter_in(toplevel_expects.pure.into_iter().map(&mut expose), arena);
+    let expects_fx = BumpVec::from_iter_in(toplevel_expects.fx.into_iter().map(&mut expose), arena);
+
+    let exposed_to_host = expects_pure
+        .iter()
+        .chain(expects_fx.iter())
+        .map(|expect| expect.symbol)
+        .collect();
+
+    let env = roc_gen_dev::Env {
+        arena,
+        layout_interner: &layout_interner,
+        module_id,
+        exposed_to_host,
+        lazy_literals: true,
+        // the test runner has no platform to provide them
+        generate_allocators: true,
+        mode: roc_gen_dev::AssemblyBackendMode::Test,
+    };
+
+    let module_object = roc_gen_dev::build_module(&env, &mut interns, &target, procedures);
+    let object_bytes = module_object
+        .write()
+        .expect("failed to build output object");
+
+    let expects = ExpectFunctions {
+        pure: expects_pure,
+        fx: expects_fx,
+        backend: ExpectBackend::Dev,
+    };
+
+    dev_object_to_dylib(&object_bytes, &target).map(|lib| (lib, expects, layout_interner))
+}
@@ -1,5 +1,6 @@
 use indoc::indoc;
 use roc_repl_cli::repl_state::{is_incomplete, ReplState, TIPS};
+use roc_test_utils::TmpDir;
 
 // These are tests of the REPL state machine. They work without actually
 // running the CLI, and without using rustyline, and instead verify
@@ -101,6 +102,116 @@ fn standalone_annotation() {
     assert_eq!(state.step(&input, None), Ok(String::new()));
 }
 
+#[test]
+fn type_meta_command() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+    assert!(!is_incomplete(":type x + 1"));
+    assert_eq!(
+        stripped(state.step(":type x + 1", None)),
+        Ok("x + 1 : Num *".to_string())
+    );
+
+    // `:type` doesn't run or name anything, so this is still val1
+    complete("x * 2", &mut state, Ok(("10 : Num *", "val1")));
+}
+
+#[test]
+fn incomplete_type_meta_command() {
+    let mut input = ":type when 1 is".to_string();
+
+    incomplete(&mut input);
+}
+
+#[test]
+fn defs_meta_command() {
+    let mut state = ReplState::new();
+
+    assert_eq!(
+        stripped(state.step(":defs", None)),
+        Ok("No definitions yet.".to_string())
+    );
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+    complete("x + 1", &mut state, Ok(("6 : Num *", "val1")));
+
+    assert_eq!(
+        stripped(state.step(":defs", None)),
+        Ok("x = 5\n\nval1 = x + 1".to_string())
+    );
+}
+
+#[test]
+fn time_meta_command() {
+    let mut state = ReplState::new();
+    let output = stripped(state.step(":time 1 + 1", None)).unwrap();
+
+    assert!(output.starts_with("2 : Num *"), "{}", output);
+    assert!(output.contains("compiled in"), "{}", output);
+    assert!(output.contains("ran in"), "{}", output);
+
+    complete("val1", &mut state, Ok(("2 : Num *", "val1")));
+}
+
+#[test]
+fn load_and_reload_meta_commands() {
+    let dir = TmpDir::new("tmp/repl_load_and_reload");
+    let module_path = dir.path().join("Greeting.roc");
+    let write_module = |body: &str| {
+        std::fs::write(
+            &module_path,
+            format!("interface Greeting exposes [greeting] imports []\n\n{body}\n"),
+        )
+        .unwrap()
+    };
+
+    write_module("greeting = \"Hello\"");
+
+    let mut state = ReplState::new();
+    let load = format!(":load {}", module_path.display());
+
+    assert_eq!(
+        stripped(state.step(&load, None)),
+        Ok("Loaded Greeting".to_string())
+    );
+    complete(
+        "Greeting.greeting",
+        &mut state,
+        Ok((r#""Hello" : Str"#, "val1")),
+    );
+
+    write_module("greeting = \"Hi\"");
+
+    assert_eq!(
+        stripped(state.step(":reload", None)),
+        Ok("Reloaded Greeting".to_string())
+    );
+    complete(
+        "Greeting.greeting",
+        &mut state,
+        Ok((r#""Hi" : Str"#, "val2")),
+    );
+}
+
+#[test]
+fn load_missing_module() {
+    let output = stripped(ReplState::new().step(":load NotThere.roc", None)).unwrap();
+
+    assert!(
+        output.starts_with("I couldn't load NotThere.roc"),
+        "{}",
+        output
+    );
+}
+
+/// strip the ANSI escape codes from the output of a step
+fn stripped(result: Result<String, i32>) -> Result<String, i32> {
+    result.map(|string| {
+        std::string::String::from_utf8(strip_ansi_escapes::strip(string.trim()).unwrap()).unwrap()
+    })
+}
+
 /// validate and step the given input, then check the Result vs the output
 /// with ANSI escape codes stripped.
 fn complete(input: &str, state: &mut ReplState, expected_step_result: Result<(&str, &str), i32>) {
@@ -144,9 +255,5 @@ fn incomplete(input: &mut String) {
 fn error(input: &str, state: &mut ReplState, expected_step_result: String) {
     assert!(!is_incomplete(input));
 
-    let escaped = state.step(input, None).map(|string| {
-        std::string::String::from_utf8(strip_ansi_escapes::strip(string.trim()).unwrap()).unwrap()
-    });
-
-    assert_eq!(Ok(expected_step_result), escaped);
+    assert_eq!(Ok(expected_step_result), stripped(state.step(input, None)));
 }
@@ -179,6 +179,7 @@ pub async fn entrypoint_from_js(src: String) -> Result<String, String> {
     // See the variable by the same name in the CLI REPL for how to do this!
     let mono = match compile_to_mono(
         arena,
+        &Default::default(),
         std::iter::empty(),
         &src,
         target_info,
@@ -237,6 +238,8 @@ pub async fn entrypoint_from_js(src: String) -> Result<String, String> {
             layout_interner: &layout_interner,
             module_id,
             stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+            runs_expects: false,
+            bulk_memory: false,
             exposed_to_host: exposed_to_host
                 .values
                 .keys()
@@ -1,9 +1,13 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use roc_collections::MutMap;
 use roc_module::symbol::{Interns, ModuleId};
-use roc_region::all::LineInfo;
+use roc_problem::Severity;
+use roc_region::all::{LineColumn, LineColumnRegion, LineInfo};
 use roc_solve_problem::TypeError;
+use serde_json::{json, Value};
+
+use crate::report::RenderTarget;
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Problems {
@@ -22,7 +26,212 @@ impl Problems {
     }
 }
 
+/// How problems get printed on the command line.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// The reports people read, with colors
+    #[default]
+    Text,
+    /// One JSON document listing every problem
+    Json,
+    /// A SARIF 2.1.0 log, which code review tools know how to annotate
+    Sarif,
+}
+
+impl ReportFormat {
+    /// How the loader should render the reports it cannot hand back as problems.
+    pub fn render_target(self) -> RenderTarget {
+        match self {
+            ReportFormat::Text => RenderTarget::ColorTerminal,
+            ReportFormat::Json | ReportFormat::Sarif => RenderTarget::Json,
+        }
+    }
+}
+
+/// A problem as a structured record, for tools rather than people.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub filename: PathBuf,
+    pub region: Option<LineColumnRegion>,
+    pub severity: Severity,
+    pub title: String,
+    /// The report without its header, rendered without colors
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Lines and columns are 1-based, and the end column is one past the last character.
+    pub fn to_json(&self) -> Value {
+        let position = |pos: LineColumn| json!({ "line": pos.line + 1, "column": pos.column + 1 });
+
+        json!({
+            "file": self.filename.to_string_lossy(),
+            "start": self.region.map(|region| position(region.start())),
+            "end": self.region.map(|region| position(region.end())),
+            "severity": severity_name(self.severity),
+            "title": self.title,
+            "message": self.message,
+        })
+    }
+
+    /// Recovers the diagnostic from a report the loader had to render by itself, like a
+    /// parse error. That is JSON when loading with `RenderTarget::Json`, but some reports
+    /// are always text, and those get attributed to the whole of `filename`.
+    pub fn from_formatted_report(report: &str, filename: &Path) -> Self {
+        serde_json::from_str(report)
+            .ok()
+            .and_then(|value| Self::from_json(&value))
+            .unwrap_or_else(|| Diagnostic {
+                filename: filename.to_path_buf(),
+                region: None,
+                severity: Severity::RuntimeError,
+                title: String::new(),
+                message: report.trim().to_string(),
+            })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let position = |pos: &Value| {
+            Some(LineColumn {
+                line: (pos["line"].as_u64()? as u32).checked_sub(1)?,
+                column: (pos["column"].as_u64()? as u32).checked_sub(1)?,
+            })
+        };
+
+        let region = match (position(&value["start"]), position(&value["end"])) {
+            (Some(start), Some(end)) => Some(LineColumnRegion::new(start, end)),
+            _ => None,
+        };
+
+        let severity = match value["severity"].as_str()? {
+            "error" => Severity::RuntimeError,
+            "warning" => Severity::Warning,
+            _ => return None,
+        };
+
+        Some(Diagnostic {
+            filename: PathBuf::from(value["file"].as_str()?),
+            region,
+            severity,
+            title: value["title"].as_str()?.to_string(),
+            message: value["message"].as_str()?.to_string(),
+        })
+    }
+
+    fn to_sarif(&self) -> Value {
+        let mut location = json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": sarif_uri(&self.filename) },
+            },
+        });
+
+        if let Some(region) = self.region {
+            location["physicalLocation"]["region"] = json!({
+                "startLine": region.start().line + 1,
+                "startColumn": region.start().column + 1,
+                "endLine": region.end().line + 1,
+                "endColumn": region.end().column + 1,
+            });
+        }
+
+        json!({
+            "ruleId": self.title,
+            "level": severity_name(self.severity),
+            "message": { "text": self.message },
+            "locations": [location],
+        })
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::RuntimeError => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// SARIF wants URI references, which always use forward slashes.
+fn sarif_uri(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Renders diagnostics as a single document in the given (non-text) format.
+pub fn diagnostics_document(format: ReportFormat, diagnostics: &[Diagnostic]) -> Value {
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+
+    match format {
+        ReportFormat::Text | ReportFormat::Json => json!({
+            "errors": count(Severity::RuntimeError),
+            "warnings": count(Severity::Warning),
+            "diagnostics": diagnostics.iter().map(Diagnostic::to_json).collect::<Vec<_>>(),
+        }),
+        ReportFormat::Sarif => json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "roc",
+                        "informationUri": "https://www.roc-lang.org",
+                    },
+                },
+                "results": diagnostics.iter().map(Diagnostic::to_sarif).collect::<Vec<_>>(),
+            }],
+        }),
+    }
+}
+
+/// Every can and type problem as a diagnostic, ordered by file and position.
+pub fn problem_diagnostics(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+) -> Vec<Diagnostic> {
+    use crate::report::{can_problem, type_problem, RocDocAllocator};
+
+    let mut diagnostics = Vec::new();
+
+    for (home, (module_path, src)) in sources.iter() {
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, *home, interns);
+
+        for problem in can_problems.remove(home).unwrap_or_default() {
+            let region = problem.region().map(|region| lines.convert_region(region));
+            let report = can_problem(&alloc, &lines, module_path.clone(), problem);
+
+            diagnostics.push(report.into_diagnostic(&alloc, region));
+        }
+
+        for problem in type_problems.remove(home).unwrap_or_default() {
+            let region = problem.region().map(|region| lines.convert_region(region));
+
+            if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
+                diagnostics.push(report.into_diagnostic(&alloc, region));
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| {
+        let start = diagnostic.region.map(|region| region.start());
+
+        (
+            diagnostic.filename.clone(),
+            start.map(|pos| (pos.line, pos.column)),
+        )
+    });
+
+    diagnostics
+}
+
 pub fn report_problems(
+    format: ReportFormat,
     total_problems: usize,
     sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
     interns: &Interns,
@@ -31,6 +240,21 @@ pub fn report_problems(
 ) -> Problems {
     use crate::report::{can_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
     use roc_problem::Severity::*;
+
+    if format != ReportFormat::Text {
+        let diagnostics = problem_diagnostics(sources, interns, can_problems, type_problems);
+
+        println!("{}", diagnostics_document(format, &diagnostics));
+
+        return Problems {
+            errors: diagnostics
+                .iter()
+                .filter(|d| d.severity == RuntimeError)
+                .count(),
+            warnings: diagnostics.iter().filter(|d| d.severity == Warning).count(),
+        };
+    }
+
     let palette = DEFAULT_PALETTE;
 
     // This will often over-allocate total memory, but it means we definitely
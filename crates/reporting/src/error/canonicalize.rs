@@ -606,6 +606,7 @@ pub fn can_problem<'b>(
         Problem::InvalidExtensionType { region, kind } => {
             let (kind_str, can_only_contain) = match kind {
                 ExtensionTypeKind::Record => ("record", "a type variable or another record"),
+                ExtensionTypeKind::Tuple => ("tuple", "a type variable or another tuple"),
                 ExtensionTypeKind::TagUnion => {
                     ("tag union", "a type variable or another tag union")
                 }
@@ -2113,6 +2114,18 @@ fn pretty_runtime_error<'b>(
 
             title = "DEGENERATE BRANCH";
         }
+        RuntimeError::InvalidTupleIndex(index, region) => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This tuple index is too big: "),
+                    alloc.text(index.to_string()),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.reflow("Tuples are indexed by their position, starting at 0."),
+            ]);
+
+            title = SYNTAX_PROBLEM;
+        }
     }
 
     (doc, title)
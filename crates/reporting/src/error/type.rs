@@ -8,7 +8,7 @@ use roc_collections::VecMap;
 use roc_error_macros::internal_error;
 use roc_exhaustive::{CtorName, ListArity};
 use roc_module::called_via::{BinOp, CalledVia};
-use roc_module::ident::{IdentStr, Lowercase, TagName};
+use roc_module::ident::{IdentStr, IndexOrField, Lowercase, TagName};
 use roc_module::symbol::Symbol;
 use roc_problem::Severity;
 use roc_region::all::{LineInfo, Region};
@@ -1683,14 +1683,29 @@ fn format_category<'b>(
             alloc.text(" of type:"),
         ),
 
+        Tuple => (
+            alloc.concat([this_is, alloc.text(" a tuple")]),
+            alloc.text(" of type:"),
+        ),
+
         Accessor(field) => (
             alloc.concat([
                 alloc.text(format!("{}his ", t)),
-                alloc.record_field(field.to_owned()),
+                match field {
+                    IndexOrField::Field(field) => alloc.record_field(field.to_owned()),
+                    IndexOrField::Index(index) => alloc.tuple_elem(*index),
+                },
                 alloc.text(" value"),
             ]),
             alloc.text(" is a:"),
         ),
+        TupleAccess(index) => (
+            alloc.concat([
+                alloc.text(format!("{}he value at ", t)),
+                alloc.tuple_elem(*index),
+            ]),
+            alloc.text(" is a:"),
+        ),
         Access(field) => (
             alloc.concat([
                 alloc.text(format!("{}he value at ", t)),
@@ -2002,6 +2017,7 @@ fn add_pattern_category<'b>(
 
     let rest = match category {
         Record => alloc.reflow(" record values of type:"),
+        Tuple => alloc.reflow(" tuple values of type:"),
         EmptyRecord => alloc.reflow(" an empty record:"),
         PatternGuard => alloc.reflow(" a pattern guard of type:"),
         PatternDefault => alloc.reflow(" an optional field of type:"),
@@ -2415,6 +2431,22 @@ fn to_doc_help<'b>(
             )
         }
 
+        Tuple(elems_map, ext) => {
+            let mut elems = elems_map.into_iter().collect::<Vec<_>>();
+            elems.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            report_text::tuple(
+                alloc,
+                elems
+                    .into_iter()
+                    .map(|(_, value)| {
+                        to_doc_help(ctx, gen_usages, alloc, Parens::Unnecessary, value)
+                    })
+                    .collect(),
+                record_ext_to_doc(alloc, ext),
+            )
+        }
+
         TagUnion(tags_map, ext, pol) => {
             let mut tags = tags_map
                 .into_iter()
@@ -2509,6 +2541,10 @@ fn count_generated_name_usages<'a>(
                 stack.extend(fields.values().map(|f| (f.as_inner(), only_unseen)));
                 ext_stack.push((ext, only_unseen));
             }
+            Tuple(elems, ext) => {
+                stack.extend(elems.values().map(|t| (t, only_unseen)));
+                ext_stack.push((ext, only_unseen));
+            }
             TagUnion(tags, ext, _) => {
                 stack.extend(tags.values().flatten().map(|t| (t, only_unseen)));
                 ext_stack.push((ext, only_unseen));
@@ -3478,6 +3514,34 @@ mod report_text {
         }
     }
 
+    pub fn tuple<'b>(
+        alloc: &'b RocDocAllocator<'b>,
+        elems: Vec<RocDocBuilder<'b>>,
+        opt_ext: Option<RocDocBuilder<'b>>,
+    ) -> RocDocBuilder<'b> {
+        let ext_doc = if let Some(t) = opt_ext {
+            t
+        } else {
+            alloc.nil()
+        };
+
+        if elems.is_empty() {
+            alloc.text("()").append(ext_doc)
+        } else {
+            let starts =
+                std::iter::once(alloc.reflow("( ")).chain(std::iter::repeat(alloc.reflow(", ")));
+
+            let elems_doc = alloc.concat(
+                elems
+                    .into_iter()
+                    .zip(starts)
+                    .map(|(elem, start)| start.append(elem)),
+            );
+
+            elems_doc.append(alloc.reflow(" )")).append(ext_doc)
+        }
+    }
+
     pub fn to_suggestion_record<'b>(
         alloc: &'b RocDocAllocator<'b>,
         f: (Lowercase, RecordField<ErrorType>),
@@ -3884,6 +3948,7 @@ fn type_problem_to_pretty<'b>(
                 RigidVar(y) | RigidAbleVar(y, _) => bad_double_rigid(x, y),
                 Function(_, _, _) => rigid_able_vs_concrete(x, alloc.reflow("a function value")),
                 Record(_, _) => rigid_able_vs_concrete(x, alloc.reflow("a record value")),
+                Tuple(_, _) => rigid_able_vs_concrete(x, alloc.reflow("a tuple value")),
                 TagUnion(_, _, _) | RecursiveTagUnion(_, _, _, _) => {
                     rigid_able_vs_concrete(x, alloc.reflow("a tag value"))
                 }
@@ -3972,6 +4037,7 @@ fn type_problem_to_pretty<'b>(
                 RigidVar(y) | RigidAbleVar(y, _) => bad_double_rigid(x, y),
                 Function(_, _, _) => bad_rigid_var(x, alloc.reflow("a function value")),
                 Record(_, _) => bad_rigid_var(x, alloc.reflow("a record value")),
+                Tuple(_, _) => bad_rigid_var(x, alloc.reflow("a tuple value")),
                 TagUnion(_, _, _) | RecursiveTagUnion(_, _, _, _) => {
                     bad_rigid_var(x, alloc.reflow("a tag value"))
                 }
@@ -4453,6 +4519,16 @@ fn pattern_to_doc_help<'b>(
                         .append(alloc.intersperse(arg_docs, alloc.reflow(", ")))
                         .append(" }")
                 }
+                RenderAs::Tuple => {
+                    let arg_docs = args
+                        .into_iter()
+                        .map(|v| pattern_to_doc_help(alloc, v, false));
+
+                    alloc
+                        .text("( ")
+                        .append(alloc.intersperse(arg_docs, alloc.reflow(", ")))
+                        .append(" )")
+                }
                 RenderAs::Tag | RenderAs::Opaque => {
                     let ctor = &union.alternatives[tag_id.0 as usize];
                     match &ctor.name {
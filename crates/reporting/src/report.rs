@@ -3,6 +3,7 @@ use roc_module::ident::{Lowercase, ModuleName, TagName, Uppercase};
 use roc_module::symbol::{Interns, ModuleId, PQModuleName, PackageQualified, Symbol};
 use roc_problem::Severity;
 use roc_region::all::LineColumnRegion;
+use std::cell::Cell;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use ven_pretty::{BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnnotated};
@@ -104,6 +105,8 @@ pub fn pretty_header_with_path(title: &str, path: &Path) -> String {
 pub enum RenderTarget {
     ColorTerminal,
     Generic,
+    /// One JSON object per report, in the shape of a [`crate::cli::Diagnostic`]
+    Json,
 }
 
 /// A textual report.
@@ -125,6 +128,41 @@ impl<'b> Report<'b> {
         match target {
             RenderTarget::Generic => self.render_ci(buf, alloc),
             RenderTarget::ColorTerminal => self.render_color_terminal(buf, alloc, palette),
+            RenderTarget::Json => self.render_json(buf, alloc),
+        }
+    }
+
+    /// Render as a JSON object for tools to consume.
+    pub fn render_json(self, buf: &mut String, alloc: &'b RocDocAllocator<'b>) {
+        buf.push_str(&self.into_diagnostic(alloc, None).to_json().to_string());
+    }
+
+    /// The report as a structured record. Unlike the other renderings, the message
+    /// does not repeat the title and filename, since the record has fields for those.
+    ///
+    /// Without an explicit `region`, this uses the first region the report points at.
+    pub fn into_diagnostic(
+        self,
+        alloc: &'b RocDocAllocator<'b>,
+        region: Option<LineColumnRegion>,
+    ) -> crate::cli::Diagnostic {
+        let err_msg = "<buffer is not a utf-8 encoded string>";
+        let mut message = String::new();
+
+        self.doc
+            .1
+            .render_raw(70, &mut CiWrite::new(&mut message))
+            .expect(err_msg);
+
+        // Always take it, so the next report built with this allocator starts afresh
+        let first_region = alloc.take_first_region();
+
+        crate::cli::Diagnostic {
+            filename: self.filename,
+            region: region.or(first_region),
+            severity: self.severity,
+            title: self.title,
+            message: message.trim_end().to_string(),
         }
     }
 
@@ -288,6 +326,8 @@ pub struct RocDocAllocator<'a> {
     pub src_lines: &'a [&'a str],
     pub home: ModuleId,
     pub interns: &'a Interns,
+    /// The first region a report built with this allocator has pointed at
+    first_region: Cell<Option<LineColumnRegion>>,
 }
 
 pub type RocDocBuilder<'b> = DocBuilder<'b, RocDocAllocator<'b>, Annotation>;
@@ -324,6 +364,19 @@ impl<'a> RocDocAllocator<'a> {
             home,
             src_lines,
             interns,
+            first_region: Cell::new(None),
+        }
+    }
+
+    /// The first region pointed at since this was last called, so that machine-readable
+    /// output can locate reports that only carry their regions inside their docs.
+    pub fn take_first_region(&self) -> Option<LineColumnRegion> {
+        self.first_region.take()
+    }
+
+    fn point_at(&self, region: LineColumnRegion) {
+        if self.first_region.get().is_none() {
+            self.first_region.set(Some(region));
         }
     }
 
@@ -437,6 +490,11 @@ impl<'a> RocDocAllocator<'a> {
             .annotate(Annotation::RecordField)
     }
 
+    pub fn tuple_elem(&'a self, index: usize) -> DocBuilder<'a, Self, Annotation> {
+        self.text(format!(".{}", index))
+            .annotate(Annotation::TupleElem)
+    }
+
     pub fn module(&'a self, module_id: ModuleId) -> DocBuilder<'a, Self, Annotation> {
         let name = self.interns.module_name(module_id);
         let name = if name.is_empty() {
@@ -523,6 +581,8 @@ impl<'a> RocDocAllocator<'a> {
         debug_assert!(region.contains(&sub_region1));
         debug_assert!(region.contains(&sub_region2));
 
+        self.point_at(sub_region1);
+
         // if true, the final line of the snippet will be some ^^^ that point to the region where
         // the problem is. Otherwise, the snippet will have a > on the lines that are in the region
         // where the problem is.
@@ -637,6 +697,8 @@ impl<'a> RocDocAllocator<'a> {
     ) -> DocBuilder<'a, Self, Annotation> {
         // debug_assert!(region.contains(&sub_region));
 
+        self.point_at(sub_region);
+
         // If the outer region takes more than 1 full screen (~60 lines), only show the inner region
         if region.end().line.saturating_sub(region.start().line) > 60 {
             // If the inner region contains the outer region (or if they are the same),
@@ -814,6 +876,7 @@ pub enum Annotation {
     Keyword,
     Tag,
     RecordField,
+    TupleElem,
     TypeVariable,
     Alias,
     Opaque,
@@ -910,7 +973,8 @@ where
             Url => {
                 self.write_str("<")?;
             }
-            Tag | Keyword | RecordField | Symbol | Typo | TypoSuggestion | TypeVariable
+            Tag | Keyword | RecordField | TupleElem | Symbol | Typo | TypoSuggestion
+            | TypeVariable
                 if !self.in_type_block && !self.in_code_block =>
             {
                 self.write_str("`")?;
@@ -945,7 +1009,8 @@ where
                 Url => {
                     self.write_str(">")?;
                 }
-                Tag | Keyword | RecordField | Symbol | Typo | TypoSuggestion | TypeVariable
+                Tag | Keyword | RecordField | TupleElem | Symbol | Typo | TypoSuggestion
+                | TypeVariable
                     if !self.in_type_block && !self.in_code_block =>
                 {
                     self.write_str("`")?;
@@ -1037,7 +1102,7 @@ where
             ParserSuggestion => {
                 self.write_str(self.palette.parser_suggestion)?;
             }
-            TypeBlock | InlineTypeBlock | Tag | RecordField => { /* nothing yet */ }
+            TypeBlock | InlineTypeBlock | Tag | RecordField | TupleElem => { /* nothing yet */ }
         }
         self.style_stack.push(*annotation);
         Ok(())
@@ -1055,7 +1120,9 @@ where
                     self.write_str(self.palette.reset)?;
                 }
 
-                TypeBlock | InlineTypeBlock | Tag | Opaque | RecordField => { /* nothing yet */ }
+                TypeBlock | InlineTypeBlock | Tag | Opaque | RecordField | TupleElem => {
+                    /* nothing yet */
+                }
             },
         }
         Ok(())
@@ -13,7 +13,9 @@ mod test_reporting {
     use indoc::indoc;
     use roc_can::abilities::AbilitiesStore;
     use roc_can::expr::PendingDerives;
-    use roc_load::{self, ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
+    use roc_load::{
+        self, ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, OptLevel, Threading,
+    };
     use roc_module::symbol::{Interns, ModuleId};
     use roc_packaging::cache::RocCacheDir;
     use roc_parse::module::parse_header;
@@ -95,6 +97,15 @@ mod test_reporting {
         subdir: &str,
         arena: &'a Bump,
         src: &'a str,
+    ) -> (String, Result<LoadedModule, LoadingProblem<'a>>) {
+        run_load_and_infer_rendering(subdir, arena, src, RenderTarget::Generic)
+    }
+
+    fn run_load_and_infer_rendering<'a>(
+        subdir: &str,
+        arena: &'a Bump,
+        src: &'a str,
+        render: RenderTarget,
     ) -> (String, Result<LoadedModule, LoadingProblem<'a>>) {
         use std::fs::File;
         use std::io::Write;
@@ -124,10 +135,11 @@ mod test_reporting {
             writeln!(file, "{}", module_src).unwrap();
             let load_config = LoadConfig {
                 target_info: roc_target::TargetInfo::default_x86_64(),
-                render: RenderTarget::Generic,
+                render,
                 palette: DEFAULT_PALETTE,
                 threading: Threading::Single,
                 exec_mode: ExecutionMode::Check,
+                opt_level: OptLevel::Normal,
             };
             let result = roc_load::load_and_typecheck(
                 arena,
@@ -1386,6 +1398,57 @@ mod test_reporting {
     "###
     );
 
+    test_report!(
+        tuple_elem_mismatch,
+        indoc!(
+            r#"
+            bar = ( "hello", 0x3 )
+
+            f : ( Num.Int *, Str ) -> [Yes, No]
+            f = \_ -> Yes
+
+            f bar
+            "#
+        ),
+        @r###"
+    ── TYPE MISMATCH ───────────────────────────────────────── /code/proj/Main.roc ─
+
+    This 1st argument to `f` has an unexpected type:
+
+    9│      f bar
+              ^^^
+
+    This `bar` value is a:
+
+        ( Str, Int * )
+
+    But `f` needs its 1st argument to be:
+
+        ( Int *, Str )
+    "###
+    );
+
+    test_report!(
+        tuple_index_too_big,
+        indoc!(
+            r#"
+            t = ( "hello", 0x3 )
+
+            t.99999999999999999999999
+            "#
+        ),
+        @r###"
+    ── SYNTAX PROBLEM ──────────────────────────────────────── /code/proj/Main.roc ─
+
+    This tuple index is too big: 99999999999999999999999
+
+    6│      t.99999999999999999999999
+            ^^^^^^^^^^^^^^^^^^^^^^^^^
+
+    Tuples are indexed by their position, starting at 0.
+    "###
+    );
+
     test_report!(
         tag_mismatch,
         indoc!(
@@ -5581,14 +5644,19 @@ All branches in an `if` must have the same type!
             "#
         ),
         @r###"
-    ── SYNTAX PROBLEM ──────────────────────────────────────── /code/proj/Main.roc ─
+    ── UNRECOGNIZED NAME ───────────────────────────────────── /code/proj/Main.roc ─
 
-    I trying to parse a record field access here:
+    Nothing is named `foo` in this scope.
 
     4│      foo.100
-                ^
+            ^^^^^^^
 
-    So I expect to see a lowercase letter next, like .name or .height.
+    Did you mean one of these?
+
+        Box
+        Bool
+        U8
+        F64
     "###
     );
 
@@ -12817,4 +12885,98 @@ I recommend using camelCase. It's the standard style in Roc code!
     @r###"
     "###
     );
+
+    #[test]
+    fn problems_as_json() {
+        use roc_reporting::cli::{diagnostics_document, problem_diagnostics, ReportFormat};
+        use serde_json::json;
+
+        let arena = Bump::new();
+        let src = indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                unused = 1
+
+                doubel 21
+            "#
+        );
+
+        let (_, result) = run_load_and_infer("problems_as_json", &arena, src);
+        let mut loaded = result.unwrap();
+        let home = loaded.module_id;
+        let sources: roc_collections::MutMap<_, _> = loaded
+            .sources
+            .remove(&home)
+            .into_iter()
+            .map(|s| (home, s))
+            .collect();
+
+        let diagnostics = problem_diagnostics(
+            &sources,
+            &loaded.interns,
+            &mut loaded.can_problems,
+            &mut loaded.type_problems,
+        );
+
+        let document = diagnostics_document(ReportFormat::Json, &diagnostics);
+        assert_eq!(document["errors"], 1);
+        assert_eq!(document["warnings"], 1);
+
+        let unused = &document["diagnostics"][0];
+        assert_eq!(unused["title"], "UNUSED DEFINITION");
+        assert_eq!(unused["severity"], "warning");
+        assert_eq!(unused["start"], json!({ "line": 4, "column": 5 }));
+        assert_eq!(unused["end"], json!({ "line": 4, "column": 11 }));
+
+        let unrecognized = &document["diagnostics"][1];
+        assert_eq!(unrecognized["title"], "UNRECOGNIZED NAME");
+        assert_eq!(unrecognized["severity"], "error");
+        assert_eq!(unrecognized["start"], json!({ "line": 6, "column": 5 }));
+        assert!(unrecognized["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Nothing is named `doubel` in this scope."));
+
+        let sarif = diagnostics_document(ReportFormat::Sarif, &diagnostics);
+        let result = &sarif["runs"][0]["results"][1];
+        assert_eq!(result["ruleId"], "UNRECOGNIZED NAME");
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"],
+            json!({ "startLine": 6, "startColumn": 5, "endLine": 6, "endColumn": 11 })
+        );
+    }
+
+    #[test]
+    fn parse_problem_as_json() {
+        use roc_reporting::cli::Diagnostic;
+
+        let arena = Bump::new();
+        let src = indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                f = \x -> x +
+
+                f 1
+            "#
+        );
+
+        let (_, result) =
+            run_load_and_infer_rendering("parse_problem_as_json", &arena, src, RenderTarget::Json);
+
+        let report = match result {
+            Err(LoadingProblem::FormattedReport(report)) => report,
+            other => panic!("expected a parse problem, got {:?}", other.map(|_| ())),
+        };
+
+        let diagnostic = Diagnostic::from_formatted_report(&report, &PathBuf::from("Other.roc"));
+        assert_eq!(diagnostic.severity, Severity::RuntimeError);
+        assert!(diagnostic.filename.ends_with("Test.roc"));
+        assert!(!diagnostic.title.is_empty());
+        assert_eq!(diagnostic.region.unwrap().start().line, 3);
+    }
 }
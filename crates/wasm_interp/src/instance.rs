@@ -1,16 +1,18 @@
 use bumpalo::{collections::Vec, Bump};
 use std::fmt::{self, Write};
+use std::io;
 use std::iter::{self, once, Iterator};
 
-use roc_wasm_module::opcodes::OpCode;
+use roc_wasm_module::opcodes::{MiscOp, OpCode};
 use roc_wasm_module::parse::{Parse, SkipBytes};
-use roc_wasm_module::sections::{ImportDesc, MemorySection, SignatureParamsIter};
+use roc_wasm_module::sections::{DataMode, ImportDesc, MemorySection, SignatureParamsIter};
 use roc_wasm_module::{ExportType, WasmModule};
 use roc_wasm_module::{Value, ValueType};
 
 use crate::frame::Frame;
+use crate::profile::{FunctionProfile, Profiler};
 use crate::value_store::ValueStore;
-use crate::{Error, ImportDispatcher};
+use crate::{Error, ImportDispatcher, Limits};
 
 #[derive(Debug)]
 pub enum Action {
@@ -64,6 +66,14 @@ pub struct Instance<'a, I: ImportDispatcher> {
     pub import_dispatcher: I,
     /// Temporary storage for import arguments
     import_arguments: Vec<'a, Value>,
+    /// Data segments that have been dropped with `data.drop`
+    dropped_data_segments: Vec<'a, u32>,
+    /// Resource limits for the program
+    limits: Limits,
+    /// Number of instructions we can still execute, if there's a fuel limit
+    fuel_remaining: Option<u64>,
+    /// Instruction counts per call stack, if profiling is enabled
+    profiler: Option<Profiler>,
     /// temporary storage for output using the --debug option
     debug_string: Option<String>,
 }
@@ -94,6 +104,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             import_count: 0,
             import_dispatcher,
             import_arguments: Vec::new_in(arena),
+            dropped_data_segments: Vec::new_in(arena),
+            limits: Limits::default(),
+            fuel_remaining: None,
+            profiler: None,
             debug_string: Some(String::new()),
         }
     }
@@ -162,10 +176,44 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             import_count,
             import_dispatcher,
             import_arguments: Vec::new_in(arena),
+            dropped_data_segments: Vec::new_in(arena),
+            limits: Limits::default(),
+            fuel_remaining: None,
+            profiler: None,
             debug_string,
         })
     }
 
+    /// Set resource limits for the program. This also refills the fuel.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.fuel_remaining = limits.fuel;
+    }
+
+    /// Count executed instructions for each function and call stack.
+    /// Counts accumulate over all calls to exported functions.
+    pub fn enable_profiling(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+
+    /// Total number of instructions executed while profiling
+    pub fn profile_total(&self) -> Option<u64> {
+        self.profiler.as_ref().map(|p| p.total_cost())
+    }
+
+    /// Profile for each function, most expensive first. Returns `None` if profiling is not enabled.
+    pub fn profile_summary(&self) -> Option<std::vec::Vec<FunctionProfile>> {
+        self.profiler.as_ref().map(|p| p.summary(self.module))
+    }
+
+    /// Write the profile in the "folded stacks" format used by flamegraph tools
+    pub fn write_profile_folded<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self.profiler.as_ref() {
+            Some(p) => p.write_folded(self.module, writer),
+            None => Ok(()),
+        }
+    }
+
     pub fn call_export<A>(&mut self, fn_name: &str, arg_values: A) -> Result<Option<Value>, String>
     where
         A: IntoIterator<Item = Value>,
@@ -320,9 +368,19 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             ty: BlockType::FunctionBody(fn_index),
             vstack: self.value_store.depth(),
         });
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.reset_stack();
+            profiler.enter(fn_index);
+        }
 
         loop {
-            match self.execute_next_instruction(module) {
+            let result = self.consume_fuel().and_then(|()| {
+                if let Some(profiler) = self.profiler.as_mut() {
+                    profiler.count_instruction();
+                }
+                self.execute_next_instruction(module)
+            });
+            match result {
                 Ok(Action::Continue) => {}
                 Ok(Action::Break) => {
                     break;
@@ -345,6 +403,17 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         Ok(return_value)
     }
 
+    fn consume_fuel(&mut self) -> Result<(), Error> {
+        match self.fuel_remaining.as_mut() {
+            Some(0) => Err(Error::OutOfFuel(self.limits.fuel.unwrap_or(0))),
+            Some(fuel) => {
+                *fuel -= 1;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
     fn fetch_immediate_u32(&mut self, module: &WasmModule<'a>) -> u32 {
         let x = u32::parse((), &module.code.bytes, &mut self.program_counter).unwrap();
         if let Some(debug_string) = self.debug_string.as_mut() {
@@ -375,6 +444,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         };
         self.value_store.truncate(new_stack_depth);
 
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.exit();
+        }
+
         // Resume executing at the next instruction in the caller function
         let new_block_len = locals_block_index; // don't need a -1 because one is a length and the other is an index!
         self.blocks.truncate(new_block_len);
@@ -534,10 +607,22 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             if let Some(return_val) = optional_return_val {
                 self.value_store.push(return_val);
             }
+            if let Some(profiler) = self.profiler.as_mut() {
+                // Count the call, but the host's code is not part of the profile
+                profiler.enter(fn_index);
+                profiler.exit();
+            }
             if let Some(debug_string) = self.debug_string.as_mut() {
                 write!(debug_string, " {}.{}", import.module, import.name).unwrap();
             }
         } else {
+            if let Some(max_call_depth) = self.limits.max_call_depth {
+                // previous frames, plus the current one, plus the one we're about to enter
+                if self.previous_frames.len() + 2 > max_call_depth {
+                    return Err(Error::CallDepthLimit(max_call_depth));
+                }
+            }
+
             let return_addr = self.program_counter;
             // set PC to start of function bytes
             let internal_fn_index = fn_index - self.import_count;
@@ -564,6 +649,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             std::mem::swap(&mut swap_frame, &mut self.current_frame);
             self.previous_frames.push(swap_frame);
 
+            if let Some(profiler) = self.profiler.as_mut() {
+                profiler.enter(fn_index);
+            }
+
             self.blocks.push(Block {
                 ty: BlockType::FunctionBody(fn_index),
                 vstack: self.value_store.depth(),
@@ -645,6 +734,11 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                         .find(|entry| entry.addr == addr);
                     if let Some(entry) = cache_result {
                         self.program_counter = entry.target as usize;
+                        let skipped_op = OpCode::from(module.code.bytes[self.program_counter - 1]);
+                        if skipped_op == END {
+                            // `if` without `else`
+                            self.blocks.pop();
+                        }
                     } else {
                         let target_depth = self.blocks.len();
                         let mut depth = target_depth;
@@ -950,6 +1044,15 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 let old_bytes = self.memory.len() as u32;
                 let old_pages = old_bytes / MemorySection::PAGE_SIZE as u32;
                 let grow_pages = self.value_store.pop_u32()?;
+
+                if let Some(max) = self.limits.max_memory_bytes {
+                    let requested =
+                        old_bytes as u64 + grow_pages as u64 * MemorySection::PAGE_SIZE as u64;
+                    if requested > max as u64 {
+                        return Err(Error::MemoryLimit { requested, max });
+                    }
+                }
+
                 let grow_bytes = grow_pages * MemorySection::PAGE_SIZE;
                 let new_bytes = old_bytes + grow_bytes;
 
@@ -1692,6 +1795,13 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 self.value_store
                     .push(Value::F64(f64::from_ne_bytes(x.to_ne_bytes())));
             }
+
+            MISC => {
+                let misc_op =
+                    MiscOp::parse((), &module.code.bytes, &mut self.program_counter).unwrap();
+                self.write_debug(misc_op);
+                self.execute_misc_instruction(misc_op, module)?;
+            }
         }
 
         if let Some(debug_string) = &self.debug_string {
@@ -1717,6 +1827,118 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         Ok(action)
     }
 
+    fn execute_misc_instruction(
+        &mut self,
+        misc_op: MiscOp,
+        module: &WasmModule<'a>,
+    ) -> Result<(), Error> {
+        use MiscOp::*;
+
+        match misc_op {
+            // Rust's float-to-int casts saturate and convert NaN to zero, just like the Wasm spec.
+            I32TRUNCSATSF32 => {
+                let arg = self.value_store.pop_f32()?;
+                self.value_store.push(Value::I32(arg as i32));
+            }
+            I32TRUNCSATUF32 => {
+                let arg = self.value_store.pop_f32()?;
+                self.value_store.push(Value::from(arg as u32));
+            }
+            I32TRUNCSATSF64 => {
+                let arg = self.value_store.pop_f64()?;
+                self.value_store.push(Value::I32(arg as i32));
+            }
+            I32TRUNCSATUF64 => {
+                let arg = self.value_store.pop_f64()?;
+                self.value_store.push(Value::from(arg as u32));
+            }
+            I64TRUNCSATSF32 => {
+                let arg = self.value_store.pop_f32()?;
+                self.value_store.push(Value::I64(arg as i64));
+            }
+            I64TRUNCSATUF32 => {
+                let arg = self.value_store.pop_f32()?;
+                self.value_store.push(Value::from(arg as u64));
+            }
+            I64TRUNCSATSF64 => {
+                let arg = self.value_store.pop_f64()?;
+                self.value_store.push(Value::I64(arg as i64));
+            }
+            I64TRUNCSATUF64 => {
+                let arg = self.value_store.pop_f64()?;
+                self.value_store.push(Value::from(arg as u64));
+            }
+
+            MEMORYINIT => {
+                let data_index = self.fetch_immediate_u32(module);
+                let _memory_index = self.fetch_immediate_u32(module);
+                let size = self.value_store.pop_u32()? as usize;
+                let src = self.value_store.pop_u32()? as usize;
+                let dst = self.value_store.pop_u32()? as usize;
+
+                let (mode, init) = module.data.segment(data_index).unwrap();
+                // Active segments are implicitly dropped after they're loaded at instantiation
+                let is_dropped = matches!(mode, DataMode::Active { .. })
+                    || self.dropped_data_segments.contains(&data_index);
+                let available = if is_dropped { &[] } else { init };
+
+                if src + size > available.len() || dst + size > self.memory.len() {
+                    panic!(
+                        "memory.init out of bounds: copying {} bytes from offset {} of data segment {} to address {}",
+                        size, src, data_index, dst
+                    );
+                }
+                self.memory[dst..][..size].copy_from_slice(&available[src..][..size]);
+            }
+            DATADROP => {
+                let data_index = self.fetch_immediate_u32(module);
+                if !self.dropped_data_segments.contains(&data_index) {
+                    self.dropped_data_segments.push(data_index);
+                }
+            }
+            MEMORYCOPY => {
+                let _dst_memory_index = self.fetch_immediate_u32(module);
+                let _src_memory_index = self.fetch_immediate_u32(module);
+                let size = self.value_store.pop_u32()? as usize;
+                let src = self.value_store.pop_u32()? as usize;
+                let dst = self.value_store.pop_u32()? as usize;
+                if src + size > self.memory.len() || dst + size > self.memory.len() {
+                    panic!(
+                        "memory.copy out of bounds: copying {} bytes from address {} to address {}",
+                        size, src, dst
+                    );
+                }
+                self.memory.copy_within(src..src + size, dst);
+            }
+            MEMORYFILL => {
+                let _memory_index = self.fetch_immediate_u32(module);
+                let size = self.value_store.pop_u32()? as usize;
+                let value = self.value_store.pop_u32()? as u8;
+                let dst = self.value_store.pop_u32()? as usize;
+                if dst + size > self.memory.len() {
+                    panic!(
+                        "memory.fill out of bounds: filling {} bytes at address {}",
+                        size, dst
+                    );
+                }
+                self.memory[dst..][..size].fill(value);
+            }
+            ELEMDROP => {
+                // We only support MVP tables, which are initialised once and never modified.
+                // Dropping a segment makes no observable difference unless we had table.init.
+                let _elem_index = self.fetch_immediate_u32(module);
+            }
+            TABLEINIT | TABLECOPY => {
+                panic!(
+                    "{:?} is not supported. This Wasm interpreter only supports MVP tables.",
+                    misc_op
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn debug_values_and_blocks(&self, label: &str) {
         eprintln!("\n========== {} ==========", label);
@@ -1,11 +1,13 @@
 mod frame;
 mod instance;
+mod profile;
 mod tests;
 mod value_store;
 pub mod wasi;
 
 // Main external interface
 pub use instance::Instance;
+pub use profile::FunctionProfile;
 pub use wasi::{WasiDispatcher, WasiFile};
 
 pub use roc_wasm_module::Value;
@@ -61,6 +63,18 @@ impl<'a> ImportDispatcher for DefaultImportDispatcher<'a> {
     }
 }
 
+/// Limits on the resources a program can use, so that a buggy program can't hang the host.
+/// `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of instructions to execute ("fuel"), shared across all calls on an Instance
+    pub fuel: Option<u64>,
+    /// Maximum size of the memory in bytes. Growing beyond this is an error.
+    pub max_memory_bytes: Option<u32>,
+    /// Maximum number of nested function calls
+    pub max_call_depth: Option<usize>,
+}
+
 /// Errors that can happen while interpreting the program
 /// All of these cause a WebAssembly stack trace to be dumped
 #[derive(Debug, PartialEq)]
@@ -68,6 +82,9 @@ pub(crate) enum Error {
     Type(ValueType, ValueType),
     StackEmpty,
     UnreachableOp,
+    OutOfFuel(u64),
+    MemoryLimit { requested: u64, max: u32 },
+    CallDepthLimit(usize),
 }
 
 impl Error {
@@ -91,6 +108,24 @@ impl Error {
                     file_offset
                 )
             }
+            Error::OutOfFuel(fuel) => {
+                format!(
+                    "ERROR: I ran out of fuel at file offset {:#x}, after executing {} instructions.\n",
+                    file_offset, fuel
+                )
+            }
+            Error::MemoryLimit { requested, max } => {
+                format!(
+                    "ERROR: I hit the memory limit at file offset {:#x}. The program tried to grow memory to {} bytes, but the limit is {} bytes.\n",
+                    file_offset, requested, max
+                )
+            }
+            Error::CallDepthLimit(max) => {
+                format!(
+                    "ERROR: I hit the call depth limit at file offset {:#x}. The program made more than {} nested calls.\n",
+                    file_offset, max
+                )
+            }
         }
     }
 }
@@ -4,14 +4,22 @@ use clap::{Arg, Command};
 use std::fs;
 use std::io;
 use std::iter::once;
+use std::path::Path;
 use std::process;
 
-use roc_wasm_interp::{DefaultImportDispatcher, Instance};
+use roc_wasm_interp::{DefaultImportDispatcher, Instance, Limits};
 use roc_wasm_module::WasmModule;
 
 pub const FLAG_FUNCTION: &str = "function";
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_HEX: &str = "hex";
+pub const FLAG_DIR: &str = "dir";
+pub const FLAG_ENV: &str = "env";
+pub const FLAG_INHERIT_ENV: &str = "inherit-env";
+pub const FLAG_FUEL: &str = "fuel";
+pub const FLAG_MAX_MEMORY: &str = "max-memory";
+pub const FLAG_MAX_CALL_DEPTH: &str = "max-call-depth";
+pub const FLAG_PROFILE: &str = "profile";
 pub const WASM_FILE: &str = "WASM_FILE";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 
@@ -38,6 +46,53 @@ fn main() -> io::Result<()> {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_dir = Arg::new(FLAG_DIR)
+        .long(FLAG_DIR)
+        .help("Give the app access to a host directory, as HOST_DIR or GUEST_DIR::HOST_DIR.\nThe app can't access any files outside of these directories.")
+        .takes_value(true)
+        .multiple_occurrences(true)
+        .required(false);
+
+    let flag_env = Arg::new(FLAG_ENV)
+        .long(FLAG_ENV)
+        .help("Set an environment variable for the app, as NAME=VALUE")
+        .takes_value(true)
+        .multiple_occurrences(true)
+        .required(false);
+
+    let flag_inherit_env = Arg::new(FLAG_INHERIT_ENV)
+        .long(FLAG_INHERIT_ENV)
+        .help("Pass all of this process's environment variables through to the app")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_fuel = Arg::new(FLAG_FUEL)
+        .long(FLAG_FUEL)
+        .help("Stop with an error after executing this many instructions")
+        .takes_value(true)
+        .validator(|s| s.parse::<u64>())
+        .required(false);
+
+    let flag_max_memory = Arg::new(FLAG_MAX_MEMORY)
+        .long(FLAG_MAX_MEMORY)
+        .help("Stop with an error if the app tries to grow its memory beyond this many bytes")
+        .takes_value(true)
+        .validator(|s| s.parse::<u32>())
+        .required(false);
+
+    let flag_max_call_depth = Arg::new(FLAG_MAX_CALL_DEPTH)
+        .long(FLAG_MAX_CALL_DEPTH)
+        .help("Stop with an error if the app makes more than this many nested function calls")
+        .takes_value(true)
+        .validator(|s| s.parse::<usize>())
+        .required(false);
+
+    let flag_profile = Arg::new(FLAG_PROFILE)
+        .long(FLAG_PROFILE)
+        .help("Count the instructions executed in each function. Print a summary, and write the call stacks to this file in the \"folded\" format used by flamegraph tools.")
+        .takes_value(true)
+        .required(false);
+
     let wasm_file_to_run = Arg::new(WASM_FILE)
         .help("The .wasm file to run")
         .required(true);
@@ -52,6 +107,13 @@ fn main() -> io::Result<()> {
         .arg(flag_function)
         .arg(flag_debug)
         .arg(flag_hex)
+        .arg(flag_dir)
+        .arg(flag_env)
+        .arg(flag_inherit_env)
+        .arg(flag_fuel)
+        .arg(flag_max_memory)
+        .arg(flag_max_call_depth)
+        .arg(flag_profile)
         .arg(wasm_file_to_run)
         .trailing_var_arg(true)
         .arg(args_for_app);
@@ -62,6 +124,7 @@ fn main() -> io::Result<()> {
     let start_fn_name = matches.get_one::<String>(FLAG_FUNCTION).unwrap();
     let is_debug_mode = matches.get_flag(FLAG_DEBUG);
     let is_hex_format = matches.get_flag(FLAG_HEX);
+    let profile_path = matches.get_one::<String>(FLAG_PROFILE);
     let start_arg_strings = matches.get_many::<String>(ARGS_FOR_APP).unwrap_or_default();
     let wasm_path = matches.get_one::<String>(WASM_FILE).unwrap();
     // WASI expects the .wasm file to be argv[0]
@@ -70,6 +133,33 @@ fn main() -> io::Result<()> {
         .map(|s| s.as_bytes());
     let wasi_argv = Vec::from_iter_in(wasi_argv_iter, &arena);
 
+    let mut env_strings: std::vec::Vec<String> = if matches.get_flag(FLAG_INHERIT_ENV) {
+        std::env::vars()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect()
+    } else {
+        vec![]
+    };
+    env_strings.extend(
+        matches
+            .get_many::<String>(FLAG_ENV)
+            .unwrap_or_default()
+            .cloned(),
+    );
+    let wasi_env = Vec::from_iter_in(env_strings.iter().map(|s| s.as_bytes()), &arena);
+
+    let limits = Limits {
+        fuel: matches
+            .get_one::<String>(FLAG_FUEL)
+            .and_then(|s| s.parse::<u64>().ok()),
+        max_memory_bytes: matches
+            .get_one::<String>(FLAG_MAX_MEMORY)
+            .and_then(|s| s.parse::<u32>().ok()),
+        max_call_depth: matches
+            .get_one::<String>(FLAG_MAX_CALL_DEPTH)
+            .and_then(|s| s.parse::<usize>().ok()),
+    };
+
     // Load the WebAssembly binary file
 
     let module_bytes = fs::read(wasm_path)?;
@@ -89,17 +179,40 @@ fn main() -> io::Result<()> {
 
     // Create an execution instance
 
-    let dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    let mut dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    dispatcher.wasi.env = &wasi_env;
+    for dir in matches.get_many::<String>(FLAG_DIR).unwrap_or_default() {
+        let (guest_path, host_path) = dir.split_once("::").unwrap_or((dir, dir));
+        if let Err(e) = dispatcher
+            .wasi
+            .preopen_dir(guest_path, Path::new(host_path))
+        {
+            eprintln!("I couldn't open the directory {}: {}", host_path, e);
+            process::exit(1);
+        }
+    }
     let mut inst =
         Instance::for_module(&arena, &module, dispatcher, is_debug_mode).unwrap_or_else(|e| {
             eprintln!("{}", e);
             process::exit(2);
         });
+    inst.set_limits(limits);
+    if profile_path.is_some() {
+        inst.enable_profiling();
+    }
 
     // Run
 
     let result = inst.call_export_from_cli(&module, start_fn_name, &wasi_argv);
 
+    // Report the profile, even if the program failed
+
+    if let Some(path) = profile_path {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        inst.write_profile_folded(&mut file)?;
+        print_profile_summary(&inst);
+    }
+
     // Print out return value, if any
 
     match result {
@@ -119,3 +232,31 @@ fn main() -> io::Result<()> {
 
     Ok(())
 }
+
+fn print_profile_summary(inst: &Instance<DefaultImportDispatcher>) {
+    const MAX_ROWS: usize = 30;
+
+    let total = inst.profile_total().unwrap_or(0);
+    let summary = inst.profile_summary().unwrap_or_default();
+    let percent = |cost: u64| 100.0 * cost as f64 / total.max(1) as f64;
+
+    eprintln!("\nExecuted {} instructions\n", total);
+    eprintln!(
+        "{:>12} {:>6} {:>12} {:>6} {:>10}  function",
+        "self", "%", "inclusive", "%", "calls"
+    );
+    for f in summary.iter().take(MAX_ROWS) {
+        eprintln!(
+            "{:>12} {:>6.2} {:>12} {:>6.2} {:>10}  {}",
+            f.self_cost,
+            percent(f.self_cost),
+            f.inclusive_cost,
+            percent(f.inclusive_cost),
+            f.calls,
+            f.name
+        );
+    }
+    if summary.len() > MAX_ROWS {
+        eprintln!("...and {} more functions", summary.len() - MAX_ROWS);
+    }
+}
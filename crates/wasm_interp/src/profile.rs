@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::io;
+
+use roc_wasm_module::WasmModule;
+
+/// Cost of each function in the profile, measured in executed instructions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionProfile {
+    pub fn_index: u32,
+    pub name: String,
+    pub calls: u64,
+    /// Instructions executed in the function itself
+    pub self_cost: u64,
+    /// Instructions executed in the function and everything it called.
+    /// Recursive calls are only counted once.
+    pub inclusive_cost: u64,
+}
+
+/// Deterministic profiler that counts executed instructions rather than measuring time.
+/// Call stacks are stored as a tree, so that recording an instruction is just an increment.
+#[derive(Debug)]
+pub(crate) struct Profiler {
+    /// For each node in the call tree, its parent and function index.
+    /// Node 0 is the root, outside of any function, and has a dummy entry.
+    nodes: Vec<(usize, u32)>,
+    /// Instructions executed directly in each node
+    node_costs: Vec<u64>,
+    /// Number of times each node was entered
+    node_calls: Vec<u64>,
+    /// Look up a child node from its parent and function index
+    children: HashMap<(usize, u32), usize>,
+    current: usize,
+}
+
+impl Profiler {
+    const ROOT: usize = 0;
+
+    pub fn new() -> Self {
+        Profiler {
+            nodes: vec![(Self::ROOT, u32::MAX)],
+            node_costs: vec![0],
+            node_calls: vec![0],
+            children: HashMap::new(),
+            current: Self::ROOT,
+        }
+    }
+
+    /// Start a new call stack, e.g. when calling an export.
+    /// If the previous call trapped, we never returned from its functions.
+    pub fn reset_stack(&mut self) {
+        self.current = Self::ROOT;
+    }
+
+    pub fn enter(&mut self, fn_index: usize) {
+        let key = (self.current, fn_index as u32);
+        let node = match self.children.get(&key) {
+            Some(node) => *node,
+            None => {
+                let node = self.nodes.len();
+                self.nodes.push(key);
+                self.node_costs.push(0);
+                self.node_calls.push(0);
+                self.children.insert(key, node);
+                node
+            }
+        };
+        self.node_calls[node] += 1;
+        self.current = node;
+    }
+
+    pub fn exit(&mut self) {
+        self.current = self.nodes[self.current].0;
+    }
+
+    pub fn count_instruction(&mut self) {
+        self.node_costs[self.current] += 1;
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        self.node_costs.iter().sum()
+    }
+
+    fn node_fn_index(&self, node: usize) -> u32 {
+        self.nodes[node].1
+    }
+
+    fn ancestors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(node), move |n| {
+            if *n == Self::ROOT {
+                None
+            } else {
+                Some(self.nodes[*n].0)
+            }
+        })
+        .skip(1)
+        .take_while(|n| *n != Self::ROOT)
+    }
+
+    /// Per-function summary, most expensive first
+    pub fn summary(&self, module: &WasmModule) -> Vec<FunctionProfile> {
+        // Children are always created after their parents, so a reverse pass sees children first
+        let mut subtree_costs = self.node_costs.clone();
+        for node in (1..self.nodes.len()).rev() {
+            let parent = self.nodes[node].0;
+            subtree_costs[parent] += subtree_costs[node];
+        }
+
+        let names = FunctionNames::new(module);
+        let mut by_function: HashMap<u32, FunctionProfile> = HashMap::new();
+        for (node, &(_, fn_index)) in self.nodes.iter().enumerate().skip(1) {
+            let entry = by_function
+                .entry(fn_index)
+                .or_insert_with(|| FunctionProfile {
+                    fn_index,
+                    name: names.get(fn_index),
+                    calls: 0,
+                    self_cost: 0,
+                    inclusive_cost: 0,
+                });
+            entry.calls += self.node_calls[node];
+            entry.self_cost += self.node_costs[node];
+
+            let is_recursive = self
+                .ancestors(node)
+                .any(|a| self.node_fn_index(a) == fn_index);
+            if !is_recursive {
+                entry.inclusive_cost += subtree_costs[node];
+            }
+        }
+
+        let mut summary: Vec<FunctionProfile> = by_function.into_values().collect();
+        summary.sort_by(|a, b| {
+            (b.self_cost, b.inclusive_cost, a.fn_index).cmp(&(
+                a.self_cost,
+                a.inclusive_cost,
+                b.fn_index,
+            ))
+        });
+        summary
+    }
+
+    /// Write the call stacks in the "folded" format used by flamegraph tools.
+    /// One line per call stack, with function names separated by semicolons, then the cost.
+    pub fn write_folded<W: io::Write>(
+        &self,
+        module: &WasmModule,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let names = FunctionNames::new(module);
+        let mut lines = Vec::with_capacity(self.nodes.len());
+        for (node, &cost) in self.node_costs.iter().enumerate().skip(1) {
+            if cost == 0 {
+                continue;
+            }
+            let mut stack: Vec<String> = std::iter::once(node)
+                .chain(self.ancestors(node))
+                .map(|n| names.get(self.node_fn_index(n)).replace(';', ":"))
+                .collect();
+            stack.reverse();
+            lines.push(format!("{} {}", stack.join(";"), cost));
+        }
+        // Sort for reproducible output
+        lines.sort();
+        for line in lines {
+            writeln!(writer, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+struct FunctionNames<'m> {
+    names: HashMap<u32, &'m str>,
+}
+
+impl<'m> FunctionNames<'m> {
+    fn new(module: &'m WasmModule) -> Self {
+        let names = module.names.function_names.iter().copied().collect();
+        FunctionNames { names }
+    }
+
+    fn get(&self, fn_index: u32) -> String {
+        match self.names.get(&fn_index) {
+            Some(name) => name.to_string(),
+            None => format!("func[{}]", fn_index),
+        }
+    }
+}
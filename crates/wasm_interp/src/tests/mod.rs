@@ -6,13 +6,16 @@ mod test_f32;
 mod test_f64;
 mod test_i32;
 mod test_i64;
+mod test_limits;
 mod test_mem;
+mod test_profile;
+mod test_wasi;
 
 use crate::{DefaultImportDispatcher, Instance};
 use bumpalo::{collections::Vec, Bump};
 use roc_wasm_module::{
-    opcodes::OpCode, Export, ExportType, SerialBuffer, Serialize, Signature, Value, ValueType,
-    WasmModule,
+    opcodes::{MiscOp, OpCode},
+    Export, ExportType, SerialBuffer, Serialize, Signature, Value, ValueType, WasmModule,
 };
 
 pub fn default_state(arena: &Bump) -> Instance<DefaultImportDispatcher> {
@@ -53,6 +56,31 @@ pub fn const_value(buf: &mut Vec<'_, u8>, value: Value) {
 pub fn test_op_example<A>(op: OpCode, args: A, expected: Value)
 where
     A: IntoIterator<Item = Value>,
+{
+    test_op_example_help(
+        &format!("{:?}", op),
+        |buf| op.serialize(buf),
+        args,
+        expected,
+    )
+}
+
+pub fn test_misc_op_example<A>(op: MiscOp, args: A, expected: Value)
+where
+    A: IntoIterator<Item = Value>,
+{
+    test_op_example_help(
+        &format!("{:?}", op),
+        |buf| op.serialize(buf),
+        args,
+        expected,
+    )
+}
+
+fn test_op_example_help<F, A>(op_name: &str, write_op: F, args: A, expected: Value)
+where
+    F: FnOnce(&mut Vec<'_, u8>),
+    A: IntoIterator<Item = Value>,
 {
     let arena = Bump::new();
     let mut module = WasmModule::new(&arena);
@@ -65,7 +93,7 @@ where
         for arg in args {
             const_value(buf, arg);
         }
-        buf.push(op as u8);
+        write_op(buf);
         buf.push(OpCode::END as u8); // end function
 
         buf.overwrite_padded_u32(func_len_index, (buf.len() - start) as u32);
@@ -85,7 +113,7 @@ where
 
     // Dump the generated module to a file (this is mainly for debugging the test itself)
     if std::env::var("DEBUG_WASM_INTERP_TEST").is_ok() {
-        let filename = format!("/tmp/{:?}.wasm", op);
+        let filename = format!("/tmp/{}.wasm", op_name);
         println!("\nDumping test module to {}\n", &filename);
         let mut outfile_buf = Vec::new_in(&arena);
         module.serialize(&mut outfile_buf);
@@ -180,6 +180,61 @@ fn test_if_else_help(condition: i32, expected: i32) {
     assert_eq!(result, Value::I32(expected));
 }
 
+#[test]
+fn test_if_without_else_skipped_twice() {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+
+    let signature = Signature {
+        param_types: bumpalo::vec![in &arena],
+        ret_type: Some(ValueType::I32),
+    };
+    let local_types = [(1, ValueType::I32)];
+    create_exported_function_with_locals(&mut module, "test", signature, &local_types, |buf| {
+        // i32.const 0
+        buf.push(OpCode::I32CONST as u8);
+        buf.encode_i32(0);
+
+        // if <blocktype>
+        buf.push(OpCode::IF as u8);
+        buf.push(ValueType::VOID as u8);
+
+        // i32.const 111
+        buf.push(OpCode::I32CONST as u8);
+        buf.encode_i32(111);
+
+        // local.set 0
+        buf.push(OpCode::SETLOCAL as u8);
+        buf.encode_u32(0);
+
+        // end
+        buf.push(OpCode::END as u8);
+
+        // i32.const 222
+        buf.push(OpCode::I32CONST as u8);
+        buf.encode_i32(222);
+
+        // end function
+        buf.push(OpCode::END as u8);
+    });
+
+    let is_debug_mode = false;
+    let mut inst = Instance::for_module(
+        &arena,
+        &module,
+        DefaultImportDispatcher::default(),
+        is_debug_mode,
+    )
+    .unwrap();
+
+    // The second time, the interpreter finds where the `if` ends in its branch cache
+    for _ in 0..2 {
+        let result = inst.call_export("test", []).unwrap();
+
+        assert_eq!(result, Some(Value::I32(222)));
+    }
+}
+
 #[test]
 fn test_br() {
     let start_fn_name = "test";
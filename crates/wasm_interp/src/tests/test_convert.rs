@@ -1,7 +1,7 @@
 #![cfg(test)]
 
-use super::test_op_example;
-use roc_wasm_module::{opcodes::OpCode::*, Value};
+use super::{test_misc_op_example, test_op_example};
+use roc_wasm_module::{opcodes::MiscOp, opcodes::OpCode::*, Value};
 
 #[test]
 fn test_i32wrapi64() {
@@ -247,3 +247,67 @@ fn test_f64reinterpreti64() {
         Value::F64(0.01171875),
     );
 }
+
+#[test]
+fn test_i32truncsatsf32() {
+    use MiscOp::I32TRUNCSATSF32;
+    test_misc_op_example(I32TRUNCSATSF32, [Value::F32(-2.9)], Value::I32(-2));
+    test_misc_op_example(I32TRUNCSATSF32, [Value::F32(1e10)], Value::I32(i32::MAX));
+    test_misc_op_example(I32TRUNCSATSF32, [Value::F32(-1e10)], Value::I32(i32::MIN));
+    test_misc_op_example(I32TRUNCSATSF32, [Value::F32(f32::NAN)], Value::I32(0));
+}
+
+#[test]
+fn test_i32truncsatuf32() {
+    use MiscOp::I32TRUNCSATUF32;
+    test_misc_op_example(I32TRUNCSATUF32, [Value::F32(2.9)], Value::I32(2));
+    test_misc_op_example(I32TRUNCSATUF32, [Value::F32(1e10)], Value::I32(-1));
+    test_misc_op_example(I32TRUNCSATUF32, [Value::F32(-1.0)], Value::I32(0));
+}
+
+#[test]
+fn test_i32truncsatsf64() {
+    use MiscOp::I32TRUNCSATSF64;
+    test_misc_op_example(I32TRUNCSATSF64, [Value::F64(-2.9)], Value::I32(-2));
+    test_misc_op_example(I32TRUNCSATSF64, [Value::F64(1e10)], Value::I32(i32::MAX));
+    test_misc_op_example(I32TRUNCSATSF64, [Value::F64(f64::NAN)], Value::I32(0));
+}
+
+#[test]
+fn test_i32truncsatuf64() {
+    use MiscOp::I32TRUNCSATUF64;
+    test_misc_op_example(I32TRUNCSATUF64, [Value::F64(1e10)], Value::I32(-1));
+    test_misc_op_example(
+        I32TRUNCSATUF64,
+        [Value::F64(f64::NEG_INFINITY)],
+        Value::I32(0),
+    );
+}
+
+#[test]
+fn test_i64truncsatsf32() {
+    use MiscOp::I64TRUNCSATSF32;
+    test_misc_op_example(I64TRUNCSATSF32, [Value::F32(-2.9)], Value::I64(-2));
+    test_misc_op_example(I64TRUNCSATSF32, [Value::F32(1e30)], Value::I64(i64::MAX));
+}
+
+#[test]
+fn test_i64truncsatuf32() {
+    use MiscOp::I64TRUNCSATUF32;
+    test_misc_op_example(I64TRUNCSATUF32, [Value::F32(1e30)], Value::I64(-1));
+    test_misc_op_example(I64TRUNCSATUF32, [Value::F32(f32::NAN)], Value::I64(0));
+}
+
+#[test]
+fn test_i64truncsatsf64() {
+    use MiscOp::I64TRUNCSATSF64;
+    test_misc_op_example(I64TRUNCSATSF64, [Value::F64(-1e30)], Value::I64(i64::MIN));
+    test_misc_op_example(I64TRUNCSATSF64, [Value::F64(f64::NAN)], Value::I64(0));
+}
+
+#[test]
+fn test_i64truncsatuf64() {
+    use MiscOp::I64TRUNCSATUF64;
+    test_misc_op_example(I64TRUNCSATUF64, [Value::F64(2.9)], Value::I64(2));
+    test_misc_op_example(I64TRUNCSATUF64, [Value::F64(-2.9)], Value::I64(0));
+}
@@ -0,0 +1,101 @@
+#![cfg(test)]
+
+use super::create_exported_function_no_locals;
+use crate::{DefaultImportDispatcher, Instance, Limits};
+use bumpalo::{collections::Vec, Bump};
+use roc_wasm_module::{
+    opcodes::OpCode, sections::MemorySection, SerialBuffer, Signature, ValueType, WasmModule,
+};
+
+fn run_with_limits<F>(limits: Limits, write_instructions: F) -> Result<(), String>
+where
+    F: FnOnce(&mut Vec<'_, u8>),
+{
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+    module.memory = MemorySection::new(&arena, MemorySection::PAGE_SIZE);
+
+    let signature = Signature {
+        param_types: bumpalo::vec![in &arena],
+        ret_type: None,
+    };
+    create_exported_function_no_locals(&mut module, "test", signature, |buf| {
+        write_instructions(buf);
+        buf.append_u8(OpCode::END as u8);
+    });
+
+    let mut inst =
+        Instance::for_module(&arena, &module, DefaultImportDispatcher::default(), false).unwrap();
+    inst.set_limits(limits);
+    inst.call_export("test", []).map(|_| ())
+}
+
+fn write_infinite_loop(buf: &mut Vec<'_, u8>) {
+    buf.append_u8(OpCode::LOOP as u8);
+    buf.append_u8(ValueType::VOID as u8);
+    buf.append_u8(OpCode::BR as u8);
+    buf.encode_u32(0);
+    buf.append_u8(OpCode::END as u8);
+}
+
+fn write_infinite_recursion(buf: &mut Vec<'_, u8>) {
+    buf.append_u8(OpCode::CALL as u8);
+    buf.encode_u32(0);
+}
+
+fn write_memory_grow(buf: &mut Vec<'_, u8>) {
+    buf.append_u8(OpCode::I32CONST as u8);
+    buf.encode_i32(2);
+    buf.append_u8(OpCode::GROWMEMORY as u8);
+    buf.encode_u32(0);
+    buf.append_u8(OpCode::DROP as u8);
+}
+
+#[test]
+fn test_fuel() {
+    let limits = Limits {
+        fuel: Some(1000),
+        ..Default::default()
+    };
+    let message = run_with_limits(limits, write_infinite_loop).unwrap_err();
+    assert!(message.contains("ran out of fuel"), "{}", message);
+    assert!(message.contains("after executing 1000 instructions"));
+    // stack trace
+    assert!(message.contains("func[0]"), "{}", message);
+}
+
+#[test]
+fn test_fuel_enough() {
+    let limits = Limits {
+        fuel: Some(10),
+        ..Default::default()
+    };
+    assert_eq!(run_with_limits(limits, |_| {}), Ok(()));
+}
+
+#[test]
+fn test_max_call_depth() {
+    let limits = Limits {
+        max_call_depth: Some(100),
+        ..Default::default()
+    };
+    let message = run_with_limits(limits, write_infinite_recursion).unwrap_err();
+    assert!(message.contains("call depth limit"), "{}", message);
+    assert_eq!(message.matches("func[0]").count(), 100);
+}
+
+#[test]
+fn test_max_memory() {
+    let too_small = Limits {
+        max_memory_bytes: Some(2 * MemorySection::PAGE_SIZE),
+        ..Default::default()
+    };
+    let message = run_with_limits(too_small, write_memory_grow).unwrap_err();
+    assert!(message.contains("memory limit"), "{}", message);
+
+    let big_enough = Limits {
+        max_memory_bytes: Some(3 * MemorySection::PAGE_SIZE),
+        ..Default::default()
+    };
+    assert_eq!(run_with_limits(big_enough, write_memory_grow), Ok(()));
+}
@@ -2,9 +2,9 @@ use super::create_exported_function_no_locals;
 use crate::{DefaultImportDispatcher, Instance};
 use bumpalo::{collections::Vec, Bump};
 use roc_wasm_module::{
-    opcodes::OpCode,
+    opcodes::{MiscOp, OpCode},
     sections::{DataMode, DataSegment, MemorySection},
-    ConstExpr, SerialBuffer, Signature, Value, ValueType, WasmModule,
+    ConstExpr, SerialBuffer, Serialize, Signature, Value, ValueType, WasmModule,
 };
 
 #[test]
@@ -446,3 +446,128 @@ fn test_i64store32() {
         &[0xf0, 0xde, 0xbc, 0x9a, 0x00, 0x00, 0x00, 0x00]
     );
 }
+
+fn test_bulk_memory<'a, F>(
+    arena: &'a Bump,
+    module: &'a mut WasmModule<'a>,
+    write_instructions: F,
+) -> Vec<'a, u8>
+where
+    F: FnOnce(&mut Vec<'a, u8>),
+{
+    module.memory = MemorySection::new(arena, MemorySection::PAGE_SIZE);
+    module.data.append_segment(DataSegment {
+        mode: DataMode::Active {
+            offset: ConstExpr::I32(0x10),
+        },
+        init: Vec::from_iter_in("abcdefgh".bytes(), arena),
+    });
+    module.data.append_segment(DataSegment {
+        mode: DataMode::Passive,
+        init: Vec::from_iter_in("ijklmnop".bytes(), arena),
+    });
+
+    let signature = Signature {
+        param_types: bumpalo::vec![in arena],
+        ret_type: None,
+    };
+    create_exported_function_no_locals(module, "test", signature, |buf| {
+        write_instructions(buf);
+        buf.append_u8(OpCode::END as u8);
+    });
+
+    let is_debug_mode = false;
+    let mut inst = Instance::for_module(
+        arena,
+        module,
+        DefaultImportDispatcher::default(),
+        is_debug_mode,
+    )
+    .unwrap();
+    inst.call_export("test", []).unwrap();
+
+    inst.memory
+}
+
+fn i32_consts(buf: &mut Vec<'_, u8>, values: &[i32]) {
+    for value in values {
+        buf.append_u8(OpCode::I32CONST as u8);
+        buf.encode_i32(*value);
+    }
+}
+
+#[test]
+fn test_memory_copy() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    // Overlapping copy, 2 bytes to the right
+    let memory = test_bulk_memory(&arena, module, |buf| {
+        i32_consts(buf, &[0x12, 0x10, 6]);
+        MiscOp::MEMORYCOPY.serialize(buf);
+        buf.encode_u32(0);
+        buf.encode_u32(0);
+    });
+
+    assert_eq!(&memory[0x10..][..8], "ababcdef".as_bytes());
+}
+
+#[test]
+fn test_memory_fill() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let memory = test_bulk_memory(&arena, module, |buf| {
+        i32_consts(buf, &[0x12, 'z' as i32, 3]);
+        MiscOp::MEMORYFILL.serialize(buf);
+        buf.encode_u32(0);
+    });
+
+    assert_eq!(&memory[0x10..][..8], "abzzzfgh".as_bytes());
+}
+
+#[test]
+fn test_memory_init() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let memory = test_bulk_memory(&arena, module, |buf| {
+        i32_consts(buf, &[0x20, 2, 4]);
+        MiscOp::MEMORYINIT.serialize(buf);
+        buf.encode_u32(1); // data segment index
+        buf.encode_u32(0);
+    });
+
+    // Passive segments are not loaded on instantiation
+    assert_eq!(&memory[0x1e..][..8], &[0, 0, b'k', b'l', b'm', b'n', 0, 0]);
+}
+
+#[test]
+#[should_panic(expected = "memory.init out of bounds")]
+fn test_memory_init_after_data_drop() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    test_bulk_memory(&arena, module, |buf| {
+        MiscOp::DATADROP.serialize(buf);
+        buf.encode_u32(1);
+
+        i32_consts(buf, &[0x20, 0, 1]);
+        MiscOp::MEMORYINIT.serialize(buf);
+        buf.encode_u32(1);
+        buf.encode_u32(0);
+    });
+}
+
+#[test]
+#[should_panic(expected = "memory.fill out of bounds")]
+fn test_memory_fill_oob() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    test_bulk_memory(&arena, module, |buf| {
+        i32_consts(buf, &[MemorySection::PAGE_SIZE as i32 - 1, 0, 2]);
+        MiscOp::MEMORYFILL.serialize(buf);
+        buf.encode_u32(0);
+    });
+}
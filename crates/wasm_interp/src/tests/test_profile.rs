@@ -0,0 +1,149 @@
+#![cfg(test)]
+
+use super::create_exported_function_no_locals;
+use crate::{DefaultImportDispatcher, Instance};
+use bumpalo::{collections::Vec, Bump};
+use roc_wasm_module::{opcodes::OpCode, SerialBuffer, Signature, Value, ValueType, WasmModule};
+
+fn profile_module<'a>(
+    arena: &'a Bump,
+    module: &'a WasmModule<'a>,
+) -> Instance<'a, DefaultImportDispatcher<'a>> {
+    let mut inst =
+        Instance::for_module(arena, module, DefaultImportDispatcher::default(), false).unwrap();
+    inst.enable_profiling();
+    inst.call_export("main", []).unwrap();
+    inst
+}
+
+fn folded(inst: &Instance<DefaultImportDispatcher>) -> String {
+    let mut buffer = std::vec::Vec::new();
+    inst.write_profile_folded(&mut buffer).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[test]
+fn test_profile_calls() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let no_args = || Signature {
+        param_types: Vec::new_in(&arena),
+        ret_type: None,
+    };
+    create_exported_function_no_locals(module, "main", no_args(), |buf| {
+        buf.append_u8(OpCode::CALL as u8);
+        buf.encode_u32(1);
+        buf.append_u8(OpCode::CALL as u8);
+        buf.encode_u32(1);
+        buf.append_u8(OpCode::END as u8);
+    });
+    create_exported_function_no_locals(module, "helper", no_args(), |buf| {
+        buf.append_u8(OpCode::I32CONST as u8);
+        buf.encode_i32(1);
+        buf.append_u8(OpCode::DROP as u8);
+        buf.append_u8(OpCode::END as u8);
+    });
+    module.names.append_function(0, "main");
+    // function 1 has no name
+
+    let inst = profile_module(&arena, module);
+
+    assert_eq!(inst.profile_total(), Some(9));
+
+    let summary = inst.profile_summary().unwrap();
+    assert_eq!(summary.len(), 2);
+    assert_eq!(summary[0].name, "func[1]");
+    assert_eq!(
+        (
+            summary[0].calls,
+            summary[0].self_cost,
+            summary[0].inclusive_cost
+        ),
+        (2, 6, 6)
+    );
+    assert_eq!(summary[1].name, "main");
+    assert_eq!(
+        (
+            summary[1].calls,
+            summary[1].self_cost,
+            summary[1].inclusive_cost
+        ),
+        (1, 3, 9)
+    );
+
+    assert_eq!(folded(&inst), "main 3\nmain;func[1] 6\n");
+}
+
+#[test]
+fn test_profile_recursion() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    create_exported_function_no_locals(
+        module,
+        "main",
+        Signature {
+            param_types: Vec::new_in(&arena),
+            ret_type: None,
+        },
+        |buf| {
+            buf.append_u8(OpCode::I32CONST as u8);
+            buf.encode_i32(2);
+            buf.append_u8(OpCode::CALL as u8);
+            buf.encode_u32(1);
+            buf.append_u8(OpCode::END as u8);
+        },
+    );
+    // Count down to zero
+    create_exported_function_no_locals(
+        module,
+        "countdown",
+        Signature {
+            param_types: bumpalo::vec![in &arena; ValueType::I32],
+            ret_type: None,
+        },
+        |buf| {
+            buf.append_u8(OpCode::GETLOCAL as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::IF as u8);
+            buf.append_u8(ValueType::VOID as u8);
+            buf.append_u8(OpCode::GETLOCAL as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::I32CONST as u8);
+            buf.encode_i32(1);
+            buf.append_u8(OpCode::I32SUB as u8);
+            buf.append_u8(OpCode::CALL as u8);
+            buf.encode_u32(1);
+            buf.append_u8(OpCode::END as u8);
+            buf.append_u8(OpCode::END as u8);
+        },
+    );
+    module.names.append_function(0, "main");
+    module.names.append_function(1, "countdown");
+
+    let inst = profile_module(&arena, module);
+    let total = inst.profile_total().unwrap();
+    let summary = inst.profile_summary().unwrap();
+    let main = summary.iter().find(|f| f.name == "main").unwrap();
+    let countdown = summary.iter().find(|f| f.name == "countdown").unwrap();
+
+    assert_eq!(main.self_cost, 3);
+    assert_eq!(main.inclusive_cost, total);
+    assert_eq!(countdown.calls, 3);
+    // Recursive calls must not be counted more than once
+    assert_eq!(countdown.inclusive_cost, total - main.self_cost);
+    assert_eq!(countdown.self_cost, countdown.inclusive_cost);
+
+    let folded = folded(&inst);
+    assert!(
+        folded.contains("\nmain;countdown;countdown;countdown "),
+        "{}",
+        folded
+    );
+
+    // Counts accumulate over calls
+    let mut inst = inst;
+    inst.call_export("countdown", [Value::I32(0)]).unwrap();
+    assert!(inst.profile_total().unwrap() > total);
+}
@@ -0,0 +1,358 @@
+use crate::wasi::{Errno, WasiDispatcher};
+use crate::Value;
+use std::fs;
+
+const SUCCESS: Option<Value> = Some(Value::I32(Errno::Success as i32));
+const PREOPEN_FD: i32 = 3;
+
+// Addresses in the test memory
+const PTR_PATH: usize = 0x100;
+const PTR_IOVS: usize = 0x200;
+const PTR_OUT: usize = 0x300;
+const PTR_BUF: usize = 0x400;
+
+const RIGHTS_FD_READ: i64 = 1 << 1;
+const RIGHTS_FD_WRITE: i64 = 1 << 6;
+const OFLAGS_CREAT: i32 = 1;
+const LOOKUPFLAGS_SYMLINK_FOLLOW: i32 = 1;
+
+fn errno(e: Errno) -> Option<Value> {
+    Some(Value::I32(e as i32))
+}
+
+fn read_u32(memory: &[u8], addr: usize) -> u32 {
+    u32::from_le_bytes(memory[addr..][..4].try_into().unwrap())
+}
+
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    u64::from_le_bytes(memory[addr..][..8].try_into().unwrap())
+}
+
+fn set_path(memory: &mut [u8], path: &str) -> [Value; 2] {
+    memory[PTR_PATH..][..path.len()].copy_from_slice(path.as_bytes());
+    [Value::I32(PTR_PATH as i32), Value::I32(path.len() as i32)]
+}
+
+fn set_iov(memory: &mut [u8], len: usize) {
+    memory[PTR_IOVS..][..4].copy_from_slice(&(PTR_BUF as u32).to_le_bytes());
+    memory[PTR_IOVS + 4..][..4].copy_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn path_open(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    path: &str,
+    oflags: i32,
+    rights: i64,
+) -> Result<i32, Option<Value>> {
+    path_open_with_lookup_flags(wasi, memory, path, 0, oflags, rights)
+}
+
+fn path_open_with_lookup_flags(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    path: &str,
+    lookup_flags: i32,
+    oflags: i32,
+    rights: i64,
+) -> Result<i32, Option<Value>> {
+    let [ptr_path, path_len] = set_path(memory, path);
+    let result = wasi.dispatch(
+        "path_open",
+        &[
+            Value::I32(PREOPEN_FD),
+            Value::I32(lookup_flags),
+            ptr_path,
+            path_len,
+            Value::I32(oflags),
+            Value::I64(rights),
+            Value::I64(0),
+            Value::I32(0),
+            Value::I32(PTR_OUT as i32),
+        ],
+        memory,
+    );
+    if result == SUCCESS {
+        Ok(read_u32(memory, PTR_OUT) as i32)
+    } else {
+        Err(result)
+    }
+}
+
+fn setup() -> (tempfile::TempDir, WasiDispatcher<'static>, Vec<u8>) {
+    let dir = tempfile::tempdir().unwrap();
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", dir.path()).unwrap();
+    (dir, wasi, vec![0; 0x1000])
+}
+
+#[test]
+fn test_prestat() {
+    let (_dir, mut wasi, mut memory) = setup();
+
+    let result = wasi.dispatch(
+        "fd_prestat_get",
+        &[Value::I32(PREOPEN_FD), Value::I32(PTR_OUT as i32)],
+        &mut memory,
+    );
+    assert_eq!(result, SUCCESS);
+    assert_eq!(read_u32(&memory, PTR_OUT), 0);
+    assert_eq!(read_u32(&memory, PTR_OUT + 4), 1);
+
+    let result = wasi.dispatch(
+        "fd_prestat_dir_name",
+        &[
+            Value::I32(PREOPEN_FD),
+            Value::I32(PTR_BUF as i32),
+            Value::I32(1),
+        ],
+        &mut memory,
+    );
+    assert_eq!(result, SUCCESS);
+    assert_eq!(memory[PTR_BUF], b'.');
+
+    let result = wasi.dispatch(
+        "fd_prestat_get",
+        &[Value::I32(PREOPEN_FD + 1), Value::I32(PTR_OUT as i32)],
+        &mut memory,
+    );
+    assert_eq!(result, errno(Errno::Badf));
+}
+
+#[test]
+fn test_write_seek_read() {
+    let (dir, mut wasi, mut memory) = setup();
+
+    let fd = path_open(
+        &mut wasi,
+        &mut memory,
+        "hello.txt",
+        OFLAGS_CREAT,
+        RIGHTS_FD_READ | RIGHTS_FD_WRITE,
+    )
+    .unwrap();
+
+    memory[PTR_BUF..][..5].copy_from_slice(b"hello");
+    set_iov(&mut memory, 5);
+    let iov_args = [
+        Value::I32(fd),
+        Value::I32(PTR_IOVS as i32),
+        Value::I32(1),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(wasi.dispatch("fd_write", &iov_args, &mut memory), SUCCESS);
+    assert_eq!(read_u32(&memory, PTR_OUT), 5);
+
+    let seek_args = [
+        Value::I32(fd),
+        Value::I64(1),
+        Value::I32(0),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(wasi.dispatch("fd_seek", &seek_args, &mut memory), SUCCESS);
+    assert_eq!(read_u64(&memory, PTR_OUT), 1);
+
+    memory[PTR_BUF..][..5].fill(0);
+    set_iov(&mut memory, 5);
+    assert_eq!(wasi.dispatch("fd_read", &iov_args, &mut memory), SUCCESS);
+    assert_eq!(read_u32(&memory, PTR_OUT), 4);
+    assert_eq!(&memory[PTR_BUF..][..4], b"ello");
+
+    assert_eq!(
+        wasi.dispatch("fd_close", &[Value::I32(fd)], &mut memory),
+        SUCCESS
+    );
+    assert_eq!(
+        wasi.dispatch("fd_close", &[Value::I32(fd)], &mut memory),
+        errno(Errno::Badf)
+    );
+
+    assert_eq!(fs::read(dir.path().join("hello.txt")).unwrap(), b"hello");
+}
+
+#[test]
+fn test_open_missing_file() {
+    let (_dir, mut wasi, mut memory) = setup();
+    let result = path_open(&mut wasi, &mut memory, "missing.txt", 0, RIGHTS_FD_READ);
+    assert_eq!(result, Err(errno(Errno::Noent)));
+}
+
+#[test]
+fn test_sandbox_escape() {
+    let (_dir, mut wasi, mut memory) = setup();
+
+    for path in ["../outside.txt", "/etc/passwd", "sub/../../outside.txt"] {
+        let result = path_open(&mut wasi, &mut memory, path, OFLAGS_CREAT, RIGHTS_FD_WRITE);
+        assert_eq!(result, Err(errno(Errno::Notcapable)), "{}", path);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sandbox_escape_via_symlink() {
+    let (dir, mut wasi, mut memory) = setup();
+    std::os::unix::fs::symlink("/", dir.path().join("root")).unwrap();
+
+    let result = path_open(&mut wasi, &mut memory, "root/etc", 0, RIGHTS_FD_READ);
+    assert_eq!(result, Err(errno(Errno::Notcapable)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sandbox_escape_creating_through_symlink() {
+    let (dir, mut wasi, mut memory) = setup();
+    let outside = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(outside.path(), dir.path().join("link_to_outside")).unwrap();
+    std::os::unix::fs::symlink(
+        outside.path().join("dangling.txt"),
+        dir.path().join("dangling_link"),
+    )
+    .unwrap();
+
+    for path in ["link_to_outside/new.txt", "dangling_link"] {
+        let result = path_open_with_lookup_flags(
+            &mut wasi,
+            &mut memory,
+            path,
+            LOOKUPFLAGS_SYMLINK_FOLLOW,
+            OFLAGS_CREAT,
+            RIGHTS_FD_WRITE,
+        );
+        assert_eq!(result, Err(errno(Errno::Notcapable)), "{}", path);
+    }
+    assert!(!outside.path().join("new.txt").exists());
+    assert!(!outside.path().join("dangling.txt").exists());
+}
+
+#[test]
+fn test_create_in_new_subdirectory_path() {
+    let (dir, mut wasi, mut memory) = setup();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+
+    let result = path_open_with_lookup_flags(
+        &mut wasi,
+        &mut memory,
+        "sub/new.txt",
+        LOOKUPFLAGS_SYMLINK_FOLLOW,
+        OFLAGS_CREAT,
+        RIGHTS_FD_WRITE,
+    );
+    assert!(result.is_ok());
+    assert!(dir.path().join("sub/new.txt").exists());
+}
+
+#[test]
+fn test_fd_renumber_to_itself() {
+    let (dir, mut wasi, mut memory) = setup();
+    fs::write(dir.path().join("hello.txt"), "hello").unwrap();
+    let fd = path_open(&mut wasi, &mut memory, "hello.txt", 0, RIGHTS_FD_READ).unwrap();
+
+    assert_eq!(
+        wasi.dispatch(
+            "fd_renumber",
+            &[Value::I32(fd), Value::I32(fd)],
+            &mut memory
+        ),
+        SUCCESS
+    );
+
+    set_iov(&mut memory, 5);
+    let iov_args = [
+        Value::I32(fd),
+        Value::I32(PTR_IOVS as i32),
+        Value::I32(1),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(wasi.dispatch("fd_read", &iov_args, &mut memory), SUCCESS);
+    assert_eq!(&memory[PTR_BUF..][..5], b"hello");
+}
+
+#[test]
+fn test_readdir() {
+    let (dir, mut wasi, mut memory) = setup();
+    fs::write(dir.path().join("b.txt"), "").unwrap();
+    fs::create_dir(dir.path().join("a")).unwrap();
+
+    let result = wasi.dispatch(
+        "fd_readdir",
+        &[
+            Value::I32(PREOPEN_FD),
+            Value::I32(PTR_BUF as i32),
+            Value::I32(0x400),
+            Value::I64(0),
+            Value::I32(PTR_OUT as i32),
+        ],
+        &mut memory,
+    );
+    assert_eq!(result, SUCCESS);
+
+    let bufused = read_u32(&memory, PTR_OUT) as usize;
+    let mut names = vec![];
+    let mut offset = 0;
+    while offset < bufused {
+        let dirent = &memory[PTR_BUF + offset..];
+        let namlen = read_u32(dirent, 16) as usize;
+        names.push(String::from_utf8(dirent[24..][..namlen].to_vec()).unwrap());
+        offset += 24 + namlen;
+    }
+    assert_eq!(names, [".", "..", "a", "b.txt"]);
+}
+
+#[test]
+fn test_environ() {
+    let env: &[&[u8]] = &[b"A=1", b"BB=22"];
+    let mut wasi = WasiDispatcher::default();
+    wasi.env = env;
+    let mut memory = vec![0; 0x1000];
+
+    let result = wasi.dispatch(
+        "environ_sizes_get",
+        &[Value::I32(PTR_OUT as i32), Value::I32(PTR_OUT as i32 + 4)],
+        &mut memory,
+    );
+    assert_eq!(result, SUCCESS);
+    assert_eq!(read_u32(&memory, PTR_OUT), 2);
+    assert_eq!(read_u32(&memory, PTR_OUT + 4), 10);
+
+    let result = wasi.dispatch(
+        "environ_get",
+        &[Value::I32(PTR_OUT as i32), Value::I32(PTR_BUF as i32)],
+        &mut memory,
+    );
+    assert_eq!(result, SUCCESS);
+    assert_eq!(read_u32(&memory, PTR_OUT), PTR_BUF as u32);
+    assert_eq!(read_u32(&memory, PTR_OUT + 4), PTR_BUF as u32 + 4);
+    assert_eq!(&memory[PTR_BUF..][..10], b"A=1\0BB=22\0");
+}
+
+#[test]
+fn test_clock_time_get() {
+    let mut wasi = WasiDispatcher::default();
+    let mut memory = vec![0; 0x1000];
+
+    let realtime = 0;
+    let result = wasi.dispatch(
+        "clock_time_get",
+        &[
+            Value::I32(realtime),
+            Value::I64(1),
+            Value::I32(PTR_OUT as i32),
+        ],
+        &mut memory,
+    );
+    assert_eq!(result, SUCCESS);
+    // Some time after 2020-01-01
+    assert!(read_u64(&memory, PTR_OUT) > 1_577_836_800_000_000_000);
+
+    let invalid_clock = 99;
+    let result = wasi.dispatch(
+        "clock_time_get",
+        &[
+            Value::I32(invalid_clock),
+            Value::I64(1),
+            Value::I32(PTR_OUT as i32),
+        ],
+        &mut memory,
+    );
+    assert_eq!(result, errno(Errno::Inval));
+}
@@ -1,14 +1,20 @@
 use rand::prelude::*;
 use roc_wasm_module::Value;
-use std::io::{self, Read, StderrLock, StdoutLock, Write};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, StderrLock, StdoutLock, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::exit;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 pub const MODULE_NAME: &str = "wasi_snapshot_preview1";
 
 pub struct WasiDispatcher<'a> {
     pub args: &'a [&'a [u8]],
+    /// Environment variables, each formatted as `KEY=VALUE`
+    pub env: &'a [&'a [u8]],
     pub rng: ThreadRng,
     pub files: Vec<WasiFile>,
+    start_time: Instant,
 }
 
 impl Default for WasiDispatcher<'_> {
@@ -22,12 +28,34 @@ pub enum WasiFile {
     WriteOnly(Vec<u8>),
     ReadWrite(Vec<u8>),
     HostSystemFile,
+    HostFile(HostFile),
+    HostDir(HostDir),
+    Closed,
+}
+
+/// A file on the host filesystem, opened by the app using `path_open`
+pub struct HostFile {
+    pub file: File,
+    pub readable: bool,
+    pub writable: bool,
+    pub fdflags: u16,
+}
+
+/// A directory on the host filesystem.
+/// Paths opened relative to it can never escape `sandbox_root`, which is the host path
+/// of the preopened directory it was reached from.
+pub struct HostDir {
+    /// The name the app sees for a preopened directory. `None` if the app opened it itself.
+    pub preopen_name: Option<String>,
+    pub path: PathBuf,
+    pub sandbox_root: PathBuf,
 }
 
 enum WriteLock<'a> {
     StdOut(StdoutLock<'a>),
     Stderr(StderrLock<'a>),
     RegularFile(&'a mut Vec<u8>),
+    HostFile(&'a mut File),
 }
 
 /// Implementation of WASI syscalls
@@ -38,13 +66,33 @@ impl<'a> WasiDispatcher<'a> {
     pub fn new(args: &'a [&'a [u8]]) -> Self {
         WasiDispatcher {
             args,
+            env: &[],
             rng: thread_rng(),
             files: vec![
                 WasiFile::HostSystemFile,
                 WasiFile::HostSystemFile,
                 WasiFile::HostSystemFile,
             ],
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Give the app access to a directory on the host, which it will see as `guest_path`.
+    /// The app can open anything inside `host_path`, but nothing outside of it.
+    pub fn preopen_dir(&mut self, guest_path: &str, host_path: &Path) -> io::Result<()> {
+        let path = host_path.canonicalize()?;
+        if !path.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", host_path.display()),
+            ));
         }
+        self.files.push(WasiFile::HostDir(HostDir {
+            preopen_name: Some(guest_path.to_string()),
+            path: path.clone(),
+            sandbox_root: path,
+        }));
+        Ok(())
     }
 
     pub fn dispatch(
@@ -58,18 +106,11 @@ impl<'a> WasiDispatcher<'a> {
         match function_name {
             "args_get" => {
                 // uint8_t ** argv,
-                let mut ptr_ptr_argv = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_ptr_argv = arguments[0].expect_i32().unwrap() as usize;
                 // uint8_t * argv_buf
-                let mut ptr_argv_buf = arguments[1].expect_i32().unwrap() as usize;
-
-                for arg in self.args {
-                    write_u32(memory, ptr_ptr_argv, ptr_argv_buf as u32);
-                    let bytes_target = &mut memory[ptr_argv_buf..][..arg.len()];
-                    bytes_target.copy_from_slice(arg);
-                    memory[ptr_argv_buf + arg.len()] = 0; // C string zero termination
-                    ptr_argv_buf += arg.len() + 1;
-                    ptr_ptr_argv += 4;
-                }
+                let ptr_argv_buf = arguments[1].expect_i32().unwrap() as usize;
+
+                write_string_list(memory, self.args, ptr_ptr_argv, ptr_argv_buf);
 
                 success_code
             }
@@ -81,29 +122,288 @@ impl<'a> WasiDispatcher<'a> {
                 // size of string arguments buffer
                 let ptr_argv_buf_size = arguments[1].expect_i32().unwrap() as usize;
 
-                let argc = self.args.len() as u32;
-                write_u32(memory, ptr_argc, argc);
+                write_string_list_sizes(memory, self.args, ptr_argc, ptr_argv_buf_size);
+
+                success_code
+            }
+            "environ_get" => {
+                // uint8_t ** environ,
+                let ptr_ptr_environ = arguments[0].expect_i32().unwrap() as usize;
+                // uint8_t * environ_buf
+                let ptr_environ_buf = arguments[1].expect_i32().unwrap() as usize;
+
+                write_string_list(memory, self.env, ptr_ptr_environ, ptr_environ_buf);
+
+                success_code
+            }
+            "environ_sizes_get" => {
+                // number of environment variables
+                let ptr_count = arguments[0].expect_i32().unwrap() as usize;
+                // size of environment variable buffer
+                let ptr_buf_size = arguments[1].expect_i32().unwrap() as usize;
+
+                write_string_list_sizes(memory, self.env, ptr_count, ptr_buf_size);
+
+                success_code
+            }
+            "clock_res_get" => {
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // Out param: resolution in nanoseconds
+                let ptr_resolution = arguments[1].expect_i32().unwrap() as usize;
+
+                if clock_id > ClockId::ThreadCputime as i32 || clock_id < 0 {
+                    return Some(Value::I32(Errno::Inval as i32));
+                }
+                // Rust's SystemTime and Instant both have nanosecond precision on all the hosts we support
+                write_u64(memory, ptr_resolution, 1);
+
+                success_code
+            }
+            "clock_time_get" => {
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // Maximum lag, which we can ignore since we're always exact
+                let _precision = arguments[1].expect_i64().unwrap();
+                // Out param: time in nanoseconds
+                let ptr_time = arguments[2].expect_i32().unwrap() as usize;
+
+                let nanos = match clock_id {
+                    x if x == ClockId::Realtime as i32 => SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_nanos(),
+                    // We don't have a portable way to measure CPU time, so we approximate
+                    // it with elapsed time. We're single-threaded so the two are close.
+                    x if x == ClockId::Monotonic as i32
+                        || x == ClockId::ProcessCputime as i32
+                        || x == ClockId::ThreadCputime as i32 =>
+                    {
+                        self.start_time.elapsed().as_nanos()
+                    }
+                    _ => return Some(Value::I32(Errno::Inval as i32)),
+                };
+                write_u64(memory, ptr_time, nanos as u64);
+
+                success_code
+            }
+            "fd_advise" => {
+                // Advice is only a hint for performance, so we're free to ignore it.
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                to_wasi_result(self.get_file(fd).map(|_| ()))
+            }
+            "fd_allocate" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let offset = arguments[1].expect_i64().unwrap() as u64;
+                let len = arguments[2].expect_i64().unwrap() as u64;
+                let min_size = offset + len;
+
+                let result = match self.get_file_mut(fd) {
+                    Ok(WasiFile::HostFile(HostFile { file, .. })) => file
+                        .metadata()
+                        .and_then(|meta| {
+                            if meta.len() < min_size {
+                                file.set_len(min_size)
+                            } else {
+                                Ok(())
+                            }
+                        })
+                        .map_err(Errno::from),
+                    Ok(WasiFile::WriteOnly(content) | WasiFile::ReadWrite(content)) => {
+                        if (content.len() as u64) < min_size {
+                            content.resize(min_size as usize, 0);
+                        }
+                        Ok(())
+                    }
+                    Ok(WasiFile::HostDir(_)) => Err(Errno::Isdir),
+                    Ok(_) => Err(Errno::Badf),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_close" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let result = self.get_file(fd).map(|_| ());
+                if result.is_ok() {
+                    self.files[fd] = WasiFile::Closed;
+                }
+                to_wasi_result(result)
+            }
+            "fd_datasync" | "fd_sync" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let result = match self.get_file(fd) {
+                    Ok(WasiFile::HostFile(HostFile { file, .. })) => {
+                        let synced = if function_name == "fd_sync" {
+                            file.sync_all()
+                        } else {
+                            file.sync_data()
+                        };
+                        synced.map_err(Errno::from)
+                    }
+                    Ok(WasiFile::HostDir(_)) => Ok(()),
+                    Ok(WasiFile::HostSystemFile) => Err(Errno::Inval),
+                    Ok(_) => Ok(()), // in-memory files are always in sync
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_fdstat_get" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: struct fdstat
+                let ptr_fdstat = arguments[1].expect_i32().unwrap() as usize;
+
+                let (filetype, fdflags, rights_base, rights_inheriting) = match self.get_file(fd) {
+                    Ok(WasiFile::HostSystemFile) => (Filetype::CharacterDevice, 0, RIGHTS_ALL, 0),
+                    Ok(WasiFile::ReadOnly(_)) => {
+                        (Filetype::RegularFile, 0, RIGHTS_ALL & !RIGHTS_FD_WRITE, 0)
+                    }
+                    Ok(WasiFile::WriteOnly(_)) => {
+                        (Filetype::RegularFile, 0, RIGHTS_ALL & !RIGHTS_FD_READ, 0)
+                    }
+                    Ok(WasiFile::ReadWrite(_)) => (Filetype::RegularFile, 0, RIGHTS_ALL, 0),
+                    Ok(WasiFile::HostFile(f)) => {
+                        let mut rights = RIGHTS_ALL;
+                        if !f.readable {
+                            rights &= !RIGHTS_FD_READ;
+                        }
+                        if !f.writable {
+                            rights &= !RIGHTS_FD_WRITE;
+                        }
+                        (Filetype::RegularFile, f.fdflags, rights, 0)
+                    }
+                    Ok(WasiFile::HostDir(_)) => (Filetype::Directory, 0, RIGHTS_ALL, RIGHTS_ALL),
+                    Ok(WasiFile::Closed) => unreachable!(),
+                    Err(e) => return Some(Value::I32(e as i32)),
+                };
 
-                let argv_buf_size: u32 = self.args.iter().map(|a| 1 + a.len() as u32).sum();
-                write_u32(memory, ptr_argv_buf_size, argv_buf_size);
+                // struct fdstat {
+                //     u8  fs_filetype;         // offset 0
+                //     u16 fs_flags;            // offset 2
+                //     u64 fs_rights_base;      // offset 8
+                //     u64 fs_rights_inheriting // offset 16
+                // }
+                memory[ptr_fdstat..][..24].fill(0);
+                memory[ptr_fdstat] = filetype as u8;
+                memory[ptr_fdstat + 2..][..2].copy_from_slice(&fdflags.to_le_bytes());
+                write_u64(memory, ptr_fdstat + 8, rights_base);
+                write_u64(memory, ptr_fdstat + 16, rights_inheriting);
 
                 success_code
             }
-            "environ_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "environ_sizes_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "clock_res_get" => success_code, // this dummy implementation seems to be good enough for some functions
-            "clock_time_get" => success_code,
-            "fd_advise" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_allocate" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_close" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_datasync" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_set_flags" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_set_rights" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_set_size" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_pread" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_fdstat_set_flags" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let flags = arguments[1].expect_i32().unwrap() as u16;
+
+                // Rust's standard library can't change the flags of a file that's already open
+                let result = match self.get_file(fd) {
+                    Ok(WasiFile::HostFile(f)) if f.fdflags == flags => Ok(()),
+                    Ok(_) if flags == 0 => Ok(()),
+                    Ok(_) => Err(Errno::Notsup),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_fdstat_set_rights" => {
+                // Rights can only ever be removed, and we don't enforce them anyway.
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                to_wasi_result(self.get_file(fd).map(|_| ()))
+            }
+            "fd_filestat_get" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: struct filestat
+                let ptr_filestat = arguments[1].expect_i32().unwrap() as usize;
+
+                let result = match self.get_file(fd) {
+                    Ok(WasiFile::HostSystemFile) => {
+                        write_filestat_zeroed(memory, ptr_filestat, Filetype::CharacterDevice, 0);
+                        Ok(())
+                    }
+                    Ok(
+                        WasiFile::ReadOnly(content)
+                        | WasiFile::WriteOnly(content)
+                        | WasiFile::ReadWrite(content),
+                    ) => {
+                        let size = content.len() as u64;
+                        write_filestat_zeroed(memory, ptr_filestat, Filetype::RegularFile, size);
+                        Ok(())
+                    }
+                    Ok(WasiFile::HostFile(HostFile { file, .. })) => file
+                        .metadata()
+                        .map(|meta| write_filestat(memory, ptr_filestat, &meta))
+                        .map_err(Errno::from),
+                    Ok(WasiFile::HostDir(dir)) => fs::metadata(&dir.path)
+                        .map(|meta| write_filestat(memory, ptr_filestat, &meta))
+                        .map_err(Errno::from),
+                    Ok(WasiFile::Closed) => unreachable!(),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_filestat_set_size" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let size = arguments[1].expect_i64().unwrap() as u64;
+
+                let result = match self.get_file_mut(fd) {
+                    Ok(WasiFile::HostFile(HostFile { file, .. })) => {
+                        file.set_len(size).map_err(Errno::from)
+                    }
+                    Ok(WasiFile::WriteOnly(content) | WasiFile::ReadWrite(content)) => {
+                        content.resize(size as usize, 0);
+                        Ok(())
+                    }
+                    Ok(WasiFile::HostDir(_)) => Err(Errno::Isdir),
+                    Ok(_) => Err(Errno::Inval),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_filestat_set_times" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let atim = arguments[1].expect_i64().unwrap() as u64;
+                let mtim = arguments[2].expect_i64().unwrap() as u64;
+                let fst_flags = arguments[3].expect_i32().unwrap() as u16;
+
+                let result = match self.get_file(fd) {
+                    Ok(WasiFile::HostFile(HostFile { file, .. })) => {
+                        set_file_times(file, atim, mtim, fst_flags)
+                    }
+                    Ok(WasiFile::HostDir(dir)) => {
+                        set_path_times(&dir.path, atim, mtim, fst_flags, true)
+                    }
+                    Ok(_) => Err(Errno::Notsup),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_pread" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Array of IO vectors
+                let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
+                // Length of array
+                let iovs_len = arguments[2].expect_i32().unwrap() as usize;
+                // Offset within the file. The file's own position is not changed.
+                let offset = arguments[3].expect_i64().unwrap() as u64;
+                // Out param: number of bytes read
+                let ptr_nread = arguments[4].expect_i32().unwrap() as usize;
+
+                let result = match self.get_file_mut(fd) {
+                    Ok(WasiFile::HostFile(HostFile {
+                        file,
+                        readable: true,
+                        ..
+                    })) => with_temporary_position(file, offset, |file| {
+                        read_iovs(memory, ptr_iovs, iovs_len, file)
+                    }),
+                    Ok(WasiFile::ReadOnly(content) | WasiFile::ReadWrite(content)) => {
+                        let start = (offset as usize).min(content.len());
+                        read_iovs(memory, ptr_iovs, iovs_len, &mut &content[start..])
+                            .map_err(Errno::from)
+                    }
+                    Ok(WasiFile::HostDir(_)) => Err(Errno::Isdir),
+                    Ok(WasiFile::HostSystemFile) => Err(Errno::Spipe),
+                    Ok(_) => Err(Errno::Badf),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result.map(|n_read| write_u32(memory, ptr_nread, n_read as u32)))
+            }
             "fd_prestat_get" => {
                 // The preopened file descriptor to query
                 let fd = arguments[0].expect_i32().unwrap() as usize;
@@ -111,19 +411,73 @@ impl<'a> WasiDispatcher<'a> {
                 //  preopen type: 4 bytes, where 0=dir is the only one supported, it seems
                 //  preopen name length: 4 bytes
                 let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
-                memory[ptr_buf..][..8].copy_from_slice(&0u64.to_le_bytes());
-                if fd < self.files.len() {
-                    success_code
-                } else {
-                    println!("WASI warning: file descriptor {} does not exist", fd);
-                    Some(Value::I32(Errno::Badf as i32))
+
+                // WASI libc calls this on every file descriptor from 3 upwards, until it gets Badf
+                match self.files.get(fd) {
+                    Some(WasiFile::HostDir(HostDir {
+                        preopen_name: Some(name),
+                        ..
+                    })) => {
+                        write_u32(memory, ptr_buf, 0);
+                        write_u32(memory, ptr_buf + 4, name.len() as u32);
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
                 }
             }
             "fd_prestat_dir_name" => {
-                // We're not giving names to any of our files so just return success
-                success_code
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the name of the directory, without a null terminator
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                match self.files.get(fd) {
+                    Some(WasiFile::HostDir(HostDir {
+                        preopen_name: Some(name),
+                        ..
+                    })) => {
+                        if path_len < name.len() {
+                            return Some(Value::I32(Errno::Nametoolong as i32));
+                        }
+                        memory[ptr_path..][..name.len()].copy_from_slice(name.as_bytes());
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
+                }
+            }
+            "fd_pwrite" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Array of IO vectors
+                let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
+                // Length of array
+                let iovs_len = arguments[2].expect_i32().unwrap() as usize;
+                // Offset within the file. The file's own position is not changed.
+                let offset = arguments[3].expect_i64().unwrap() as u64;
+                // Out param: number of bytes written
+                let ptr_nwritten = arguments[4].expect_i32().unwrap() as usize;
+
+                let result = match self.get_file_mut(fd) {
+                    Ok(WasiFile::HostFile(HostFile {
+                        file,
+                        writable: true,
+                        ..
+                    })) => with_temporary_position(file, offset, |file| {
+                        write_iovs(memory, ptr_iovs, iovs_len, file)
+                    }),
+                    Ok(WasiFile::WriteOnly(content) | WasiFile::ReadWrite(content)) => {
+                        let mut cursor = io::Cursor::new(content);
+                        cursor.set_position(offset);
+                        write_iovs(memory, ptr_iovs, iovs_len, &mut cursor).map_err(Errno::from)
+                    }
+                    Ok(WasiFile::HostDir(_)) => Err(Errno::Isdir),
+                    Ok(WasiFile::HostSystemFile) => Err(Errno::Spipe),
+                    Ok(_) => Err(Errno::Badf),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(
+                    result.map(|n_written| write_u32(memory, ptr_nwritten, n_written as u32)),
+                )
             }
-            "fd_pwrite" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_read" => {
                 use WasiFile::*;
 
@@ -132,57 +486,95 @@ impl<'a> WasiDispatcher<'a> {
                 // Array of IO vectors
                 let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
                 // Length of array
-                let iovs_len = arguments[2].expect_i32().unwrap();
+                let iovs_len = arguments[2].expect_i32().unwrap() as usize;
                 // Out param: number of bytes read
                 let ptr_nread = arguments[3].expect_i32().unwrap() as usize;
 
-                // https://man7.org/linux/man-pages/man2/readv.2.html
-                // struct iovec {
-                //     void  *iov_base;    /* Starting address */
-                //     size_t iov_len;     /* Number of bytes to transfer */
-                // };
-
-                let mut n_read: usize = 0;
-                match self.files.get(fd) {
+                let result = match self.files.get_mut(fd) {
                     Some(ReadOnly(content) | ReadWrite(content)) => {
-                        for _ in 0..iovs_len {
-                            let iov_base = read_u32(memory, ptr_iovs) as usize;
-                            let iov_len = read_i32(memory, ptr_iovs + 4) as usize;
-                            let remaining = content.len() - n_read;
-                            let len = remaining.min(iov_len);
-                            if len == 0 {
-                                break;
-                            }
-                            memory[iov_base..][..len].copy_from_slice(&content[n_read..][..len]);
-                            n_read += len;
-                        }
+                        read_iovs(memory, ptr_iovs, iovs_len, &mut content.as_slice())
+                            .map_err(Errno::from)
                     }
                     Some(HostSystemFile) if fd == 0 => {
-                        let mut stdin = io::stdin();
-                        for _ in 0..iovs_len {
-                            let iov_base = read_u32(memory, ptr_iovs) as usize;
-                            let iov_len = read_i32(memory, ptr_iovs + 4) as usize;
-                            match stdin.read(&mut memory[iov_base..][..iov_len]) {
-                                Ok(n) => {
-                                    n_read += n;
-                                }
-                                Err(_) => {
-                                    break;
-                                }
-                            }
-                        }
+                        let mut stdin = io::stdin().lock();
+                        read_iovs(memory, ptr_iovs, iovs_len, &mut stdin).map_err(Errno::from)
                     }
-                    _ => return Some(Value::I32(Errno::Badf as i32)),
+                    Some(HostFile(self::HostFile {
+                        file,
+                        readable: true,
+                        ..
+                    })) => read_iovs(memory, ptr_iovs, iovs_len, file).map_err(Errno::from),
+                    Some(HostDir(_)) => Err(Errno::Isdir),
+                    _ => Err(Errno::Badf),
                 };
 
-                memory[ptr_nread..][..4].copy_from_slice(&(n_read as u32).to_le_bytes());
-                success_code
+                to_wasi_result(result.map(|n_read| write_u32(memory, ptr_nread, n_read as u32)))
+            }
+            "fd_readdir" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Buffer where directory entries are stored
+                let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+                let buf_len = arguments[2].expect_i32().unwrap() as usize;
+                // Index of the first entry to read
+                let cookie = arguments[3].expect_i64().unwrap() as u64;
+                // Out param: number of bytes stored in the buffer
+                let ptr_bufused = arguments[4].expect_i32().unwrap() as usize;
+
+                let result = match self.get_file(fd) {
+                    Ok(WasiFile::HostDir(dir)) => read_dir_entries(&dir.path).map(|entries| {
+                        let buf = &mut memory[ptr_buf..][..buf_len];
+                        let bufused = write_dirents(buf, &entries, cookie);
+                        write_u32(memory, ptr_bufused, bufused as u32);
+                    }),
+                    Ok(_) => Err(Errno::Notdir),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result)
+            }
+            "fd_renumber" => {
+                let fd_from = arguments[0].expect_i32().unwrap() as usize;
+                let fd_to = arguments[1].expect_i32().unwrap() as usize;
+
+                let result = self.get_file(fd_from).and(self.get_file(fd_to)).map(|_| ());
+                // Renumbering a descriptor to itself is a no-op, like `dup2` with equal fds
+                if result.is_ok() && fd_from != fd_to {
+                    self.files.swap(fd_from, fd_to);
+                    self.files[fd_from] = WasiFile::Closed;
+                }
+                to_wasi_result(result)
+            }
+            "fd_seek" | "fd_tell" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let (seek_from, newoffset_arg) = if function_name == "fd_tell" {
+                    (Ok(SeekFrom::Current(0)), 1)
+                } else {
+                    // The number of bytes to move
+                    let offset = arguments[1].expect_i64().unwrap();
+                    // The base from which the offset is relative
+                    let whence = arguments[2].expect_i32().unwrap();
+                    let seek_from = match whence {
+                        0 => Ok(SeekFrom::Start(offset as u64)),
+                        1 => Ok(SeekFrom::Current(offset)),
+                        2 => Ok(SeekFrom::End(offset)),
+                        _ => Err(Errno::Inval),
+                    };
+                    (seek_from, 3)
+                };
+                // Out param: the new offset of the file descriptor, relative to the start of the file
+                let ptr_newoffset = arguments[newoffset_arg].expect_i32().unwrap() as usize;
+
+                let result = match self.get_file_mut(fd) {
+                    Ok(WasiFile::HostFile(HostFile { file, .. })) => {
+                        seek_from.and_then(|pos| file.seek(pos).map_err(Errno::from))
+                    }
+                    Ok(WasiFile::HostSystemFile) => Err(Errno::Spipe),
+                    Ok(WasiFile::HostDir(_)) => Err(Errno::Isdir),
+                    // In-memory files don't keep track of a position
+                    Ok(_) => Err(Errno::Notsup),
+                    Err(e) => Err(e),
+                };
+                to_wasi_result(result.map(|pos| write_u64(memory, ptr_newoffset, pos)))
             }
-            "fd_readdir" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_renumber" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_seek" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_sync" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_tell" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_write" => {
                 use WasiFile::*;
 
@@ -206,19 +598,26 @@ impl<'a> WasiDispatcher<'a> {
                     Some(WriteOnly(content) | ReadWrite(content)) => {
                         WriteLock::RegularFile(content)
                     }
+                    Some(HostFile(self::HostFile {
+                        file,
+                        writable: true,
+                        ..
+                    })) => WriteLock::HostFile(file),
+                    Some(HostDir(_)) => return Some(Value::I32(Errno::Isdir as i32)),
                     _ => return Some(Value::I32(Errno::Badf as i32)),
                 };
 
                 let mut n_written: i32 = 0;
                 let mut negative_length_count = 0;
-                for _ in 0..iovs_len {
+                for i in 0..iovs_len as usize {
                     // https://man7.org/linux/man-pages/man2/readv.2.html
                     // struct iovec {
                     //     void  *iov_base;    /* Starting address */
                     //     size_t iov_len;     /* Number of bytes to transfer */
                     // };
-                    let iov_base = read_u32(memory, ptr_iovs) as usize;
-                    let iov_len = read_i32(memory, ptr_iovs + 4);
+                    let ptr_iov = ptr_iovs + 8 * i;
+                    let iov_base = read_u32(memory, ptr_iov) as usize;
+                    let iov_len = read_i32(memory, ptr_iov + 4);
                     if iov_len < 0 {
                         // I found negative-length iov's when I implemented this in JS for the web REPL (see wasi.js)
                         // I'm not sure why, but this solution worked, and it's the same WASI libc - there's only one.
@@ -228,18 +627,19 @@ impl<'a> WasiDispatcher<'a> {
                     }
                     let bytes = &memory[iov_base..][..iov_len as usize];
 
-                    match &mut write_lock {
-                        WriteLock::StdOut(stdout) => {
-                            n_written += stdout.write(bytes).unwrap() as i32;
-                        }
-                        WriteLock::Stderr(stderr) => {
-                            n_written += stderr.write(bytes).unwrap() as i32;
-                        }
+                    let write_result = match &mut write_lock {
+                        WriteLock::StdOut(stdout) => stdout.write_all(bytes),
+                        WriteLock::Stderr(stderr) => stderr.write_all(bytes),
                         WriteLock::RegularFile(content) => {
                             content.extend_from_slice(bytes);
-                            n_written += bytes.len() as i32;
+                            Ok(())
                         }
+                        WriteLock::HostFile(file) => file.write_all(bytes),
+                    };
+                    if let Err(e) = write_result {
+                        return Some(Value::I32(Errno::from(e) as i32));
                     }
+                    n_written += bytes.len() as i32;
                 }
 
                 write_i32(memory, ptr_nwritten, n_written);
@@ -253,23 +653,198 @@ impl<'a> WasiDispatcher<'a> {
 
                 success_code
             }
-            "path_create_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_link" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_open" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_readlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_remove_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_rename" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_symlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_unlink_file" => todo!("WASI {}({:?})", function_name, arguments),
-            "poll_oneoff" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_create_directory" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::create_dir(path).map_err(Errno::from));
+                to_wasi_result(result)
+            }
+            "path_filestat_get" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let lookup_flags = arguments[1].expect_i32().unwrap() as u32;
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                // Out param: struct filestat
+                let ptr_filestat = arguments[4].expect_i32().unwrap() as usize;
+
+                let follow = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, follow)
+                    .and_then(|path| {
+                        let meta = if follow {
+                            fs::metadata(path)
+                        } else {
+                            fs::symlink_metadata(path)
+                        };
+                        meta.map_err(Errno::from)
+                    })
+                    .map(|meta| write_filestat(memory, ptr_filestat, &meta));
+                to_wasi_result(result)
+            }
+            "path_filestat_set_times" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let lookup_flags = arguments[1].expect_i32().unwrap() as u32;
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                let atim = arguments[4].expect_i64().unwrap() as u64;
+                let mtim = arguments[5].expect_i64().unwrap() as u64;
+                let fst_flags = arguments[6].expect_i32().unwrap() as u16;
+
+                let follow = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, follow)
+                    .and_then(|path| set_path_times(&path, atim, mtim, fst_flags, follow));
+                to_wasi_result(result)
+            }
+            "path_link" => {
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                let old_lookup_flags = arguments[1].expect_i32().unwrap() as u32;
+                let ptr_old_path = arguments[2].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[3].expect_i32().unwrap() as usize;
+                let new_fd = arguments[4].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[5].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[6].expect_i32().unwrap() as usize;
+
+                let follow = old_lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+                let result = self
+                    .resolve_path(old_fd, memory, ptr_old_path, old_path_len, follow)
+                    .and_then(|old_path| {
+                        let new_path =
+                            self.resolve_path(new_fd, memory, ptr_new_path, new_path_len, false)?;
+                        fs::hard_link(old_path, new_path).map_err(Errno::from)
+                    });
+                to_wasi_result(result)
+            }
+            "path_open" => {
+                let dirfd = arguments[0].expect_i32().unwrap() as usize;
+                let lookup_flags = arguments[1].expect_i32().unwrap() as u32;
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                let oflags = arguments[4].expect_i32().unwrap() as u16;
+                let rights_base = arguments[5].expect_i64().unwrap() as u64;
+                let _rights_inheriting = arguments[6].expect_i64().unwrap() as u64;
+                let fdflags = arguments[7].expect_i32().unwrap() as u16;
+                // Out param: the new file descriptor
+                let ptr_fd = arguments[8].expect_i32().unwrap() as usize;
+
+                let follow = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+                let result = self
+                    .resolve_path(dirfd, memory, ptr_path, path_len, follow)
+                    .and_then(|path| self.open(dirfd, path, oflags, rights_base, fdflags))
+                    .map(|file| {
+                        let fd = self.insert_file(file);
+                        write_u32(memory, ptr_fd, fd as u32);
+                    });
+                to_wasi_result(result)
+            }
+            "path_readlink" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+                // Buffer for the contents of the link
+                let ptr_buf = arguments[3].expect_i32().unwrap() as usize;
+                let buf_len = arguments[4].expect_i32().unwrap() as usize;
+                // Out param: number of bytes written to the buffer
+                let ptr_bufused = arguments[5].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::read_link(path).map_err(Errno::from))
+                    .and_then(|target| {
+                        let target = target.to_str().ok_or(Errno::Ilseq)?.as_bytes();
+                        // Like POSIX readlink, silently truncate if the buffer is too small
+                        let len = target.len().min(buf_len);
+                        memory[ptr_buf..][..len].copy_from_slice(&target[..len]);
+                        write_u32(memory, ptr_bufused, len as u32);
+                        Ok(())
+                    });
+                to_wasi_result(result)
+            }
+            "path_remove_directory" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::remove_dir(path).map_err(Errno::from));
+                to_wasi_result(result)
+            }
+            "path_rename" => {
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_old_path = arguments[1].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[2].expect_i32().unwrap() as usize;
+                let new_fd = arguments[3].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[4].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[5].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(old_fd, memory, ptr_old_path, old_path_len, false)
+                    .and_then(|old_path| {
+                        let new_path =
+                            self.resolve_path(new_fd, memory, ptr_new_path, new_path_len, false)?;
+                        fs::rename(old_path, new_path).map_err(Errno::from)
+                    });
+                to_wasi_result(result)
+            }
+            "path_symlink" => {
+                let ptr_old_path = arguments[0].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[1].expect_i32().unwrap() as usize;
+                let fd = arguments[2].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[3].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[4].expect_i32().unwrap() as usize;
+
+                // The link target is stored as-is. It's checked against the sandbox whenever it's followed.
+                let result = std::str::from_utf8(&memory[ptr_old_path..][..old_path_len])
+                    .map_err(|_| Errno::Ilseq)
+                    .and_then(|target| {
+                        let link =
+                            self.resolve_path(fd, memory, ptr_new_path, new_path_len, false)?;
+                        create_symlink(Path::new(target), &link)
+                    });
+                to_wasi_result(result)
+            }
+            "path_unlink_file" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::remove_file(path).map_err(Errno::from));
+                to_wasi_result(result)
+            }
+            "poll_oneoff" => {
+                // Array of subscriptions
+                let ptr_in = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: array of events
+                let ptr_out = arguments[1].expect_i32().unwrap() as usize;
+                let nsubscriptions = arguments[2].expect_i32().unwrap() as usize;
+                // Out param: number of events stored
+                let ptr_nevents = arguments[3].expect_i32().unwrap() as usize;
+
+                if nsubscriptions == 0 {
+                    return Some(Value::I32(Errno::Inval as i32));
+                }
+                let nevents = self.poll_oneoff(memory, ptr_in, ptr_out, nsubscriptions);
+                write_u32(memory, ptr_nevents, nevents as u32);
+
+                success_code
+            }
             "proc_exit" => {
                 let exit_code = arguments[0].expect_i32().unwrap();
                 exit(exit_code);
             }
-            "proc_raise" => todo!("WASI {}({:?})", function_name, arguments),
-            "sched_yield" => todo!("WASI {}({:?})", function_name, arguments),
+            // Removed from later versions of WASI, and not used by WASI libc
+            "proc_raise" => Some(Value::I32(Errno::Nosys as i32)),
+            "sched_yield" => {
+                std::thread::yield_now();
+                success_code
+            }
             "random_get" => {
                 // A pointer to a buffer where the random bytes will be written
                 let ptr_buf = arguments[0].expect_i32().unwrap() as usize;
@@ -280,12 +855,529 @@ impl<'a> WasiDispatcher<'a> {
                 }
                 success_code
             }
-            "sock_recv" => todo!("WASI {}({:?})", function_name, arguments),
-            "sock_send" => todo!("WASI {}({:?})", function_name, arguments),
-            "sock_shutdown" => todo!("WASI {}({:?})", function_name, arguments),
+            // We never create any sockets, so any file descriptor passed in must be something else.
+            "sock_accept" | "sock_recv" | "sock_send" | "sock_shutdown" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let errno = match self.get_file(fd) {
+                    Ok(_) => Errno::Notsock,
+                    Err(e) => e,
+                };
+                Some(Value::I32(errno as i32))
+            }
             _ => panic!("Unknown WASI function {}({:?})", function_name, arguments),
         }
     }
+
+    /// Look up an open file descriptor
+    fn get_file(&self, fd: usize) -> Result<&WasiFile, Errno> {
+        match self.files.get(fd) {
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
+            Some(file) => Ok(file),
+        }
+    }
+
+    fn get_file_mut(&mut self, fd: usize) -> Result<&mut WasiFile, Errno> {
+        match self.files.get_mut(fd) {
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
+            Some(file) => Ok(file),
+        }
+    }
+
+    /// Store a newly opened file, reusing the lowest closed file descriptor if there is one
+    fn insert_file(&mut self, file: WasiFile) -> usize {
+        match self
+            .files
+            .iter()
+            .position(|f| matches!(f, WasiFile::Closed))
+        {
+            Some(fd) => {
+                self.files[fd] = file;
+                fd
+            }
+            None => {
+                self.files.push(file);
+                self.files.len() - 1
+            }
+        }
+    }
+
+    /// Convert a path from the app into a host path, making sure it stays inside the sandbox.
+    /// If `follow` is false and the path is a symlink, the link itself is checked rather than its target.
+    fn resolve_path(
+        &self,
+        dirfd: usize,
+        memory: &[u8],
+        ptr_path: usize,
+        path_len: usize,
+        follow: bool,
+    ) -> Result<PathBuf, Errno> {
+        let dir = match self.get_file(dirfd)? {
+            WasiFile::HostDir(dir) => dir,
+            _ => return Err(Errno::Notdir),
+        };
+        let path_str =
+            std::str::from_utf8(&memory[ptr_path..][..path_len]).map_err(|_| Errno::Ilseq)?;
+
+        let mut resolved = dir.path.clone();
+        for component in Path::new(path_str).components() {
+            match component {
+                Component::Normal(name) => resolved.push(name),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                    if !resolved.starts_with(&dir.sandbox_root) {
+                        return Err(Errno::Notcapable);
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => return Err(Errno::Notcapable),
+            }
+        }
+
+        // Symlinks inside the sandbox could still point outside of it
+        let to_check = if follow {
+            resolved.as_path()
+        } else {
+            resolved.parent().unwrap_or(&resolved)
+        };
+        check_in_sandbox(to_check, &dir.sandbox_root)?;
+
+        Ok(resolved)
+    }
+
+    fn open(
+        &self,
+        dirfd: usize,
+        path: PathBuf,
+        oflags: u16,
+        rights_base: u64,
+        fdflags: u16,
+    ) -> Result<WasiFile, Errno> {
+        let sandbox_root = match self.get_file(dirfd)? {
+            WasiFile::HostDir(dir) => dir.sandbox_root.clone(),
+            _ => return Err(Errno::Notdir),
+        };
+        let create = oflags & OFLAGS_CREAT != 0;
+        let exclusive = oflags & OFLAGS_EXCL != 0;
+
+        if oflags & OFLAGS_DIRECTORY != 0 || (path.is_dir() && !(create && exclusive)) {
+            if create && exclusive {
+                return Err(Errno::Exist);
+            }
+            let meta = fs::metadata(&path)?;
+            if !meta.is_dir() {
+                return Err(Errno::Notdir);
+            }
+            if rights_base & RIGHTS_FD_WRITE != 0 && oflags & OFLAGS_DIRECTORY == 0 {
+                return Err(Errno::Isdir);
+            }
+            return Ok(WasiFile::HostDir(HostDir {
+                preopen_name: None,
+                path: path.canonicalize()?,
+                sandbox_root,
+            }));
+        }
+
+        let readable = rights_base & RIGHTS_FD_READ != 0;
+        let writable = rights_base & RIGHTS_FD_WRITE != 0;
+        let append = fdflags & FDFLAGS_APPEND != 0;
+        let file = OpenOptions::new()
+            .read(readable || !writable)
+            .write(writable && !append)
+            .append(append)
+            .create(create)
+            .create_new(create && exclusive)
+            .truncate(oflags & OFLAGS_TRUNC != 0)
+            .open(&path)?;
+
+        Ok(WasiFile::HostFile(HostFile {
+            file,
+            readable: readable || !writable,
+            writable: writable || append,
+            fdflags,
+        }))
+    }
+
+    /// Wait for at least one of the subscribed events, write them out, and return how many there were.
+    /// Files are always ready to read and write, so we only ever need to wait for clocks.
+    fn poll_oneoff(
+        &mut self,
+        memory: &mut [u8],
+        ptr_in: usize,
+        ptr_out: usize,
+        nsubscriptions: usize,
+    ) -> usize {
+        // struct subscription {
+        //     u64 userdata;                // offset 0
+        //     u8  tag;                     // offset 8
+        //     union {                      // offset 16
+        //         struct { u32 id; u64 timeout; u64 precision; u16 flags; } clock;
+        //         struct { u32 fd; } fd_read, fd_write;
+        //     }
+        // }                                // size 48
+        const SUBSCRIPTION_SIZE: usize = 48;
+        // struct event {
+        //     u64 userdata;                // offset 0
+        //     u16 error;                   // offset 8
+        //     u8  type;                    // offset 10
+        //     struct { u64 nbytes; u16 flags; } fd_readwrite; // offset 16
+        // }                                // size 32
+        const EVENT_SIZE: usize = 32;
+
+        let now = self.start_time.elapsed();
+        let mut fd_events = Vec::new();
+        let mut clock_events: Vec<(u64, Duration)> = Vec::new();
+
+        for i in 0..nsubscriptions {
+            let ptr_sub = ptr_in + i * SUBSCRIPTION_SIZE;
+            let userdata = read_u64(memory, ptr_sub);
+            let tag = memory[ptr_sub + 8];
+            match tag {
+                EVENTTYPE_CLOCK => {
+                    let clock_id = read_u32(memory, ptr_sub + 16);
+                    let timeout = read_u64(memory, ptr_sub + 24);
+                    let flags = read_u16(memory, ptr_sub + 40);
+                    let is_absolute = flags & SUBCLOCKFLAGS_ABSTIME != 0;
+
+                    let wait = if !is_absolute {
+                        Duration::from_nanos(timeout)
+                    } else if clock_id == ClockId::Realtime as u32 {
+                        let since_epoch = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default();
+                        Duration::from_nanos(timeout).saturating_sub(since_epoch)
+                    } else {
+                        Duration::from_nanos(timeout).saturating_sub(now)
+                    };
+                    clock_events.push((userdata, wait));
+                }
+                EVENTTYPE_FD_READ | EVENTTYPE_FD_WRITE => {
+                    let fd = read_u32(memory, ptr_sub + 16) as usize;
+                    let errno = match self.get_file(fd) {
+                        Ok(_) => Errno::Success,
+                        Err(e) => e,
+                    };
+                    fd_events.push((userdata, errno, tag));
+                }
+                _ => fd_events.push((userdata, Errno::Inval, tag)),
+            }
+        }
+
+        let mut events: Vec<(u64, Errno, u8)> = fd_events;
+        if events.is_empty() {
+            // Sleep until the earliest timeout, then report every clock that has expired by then
+            let min_wait = clock_events.iter().map(|(_, wait)| *wait).min().unwrap();
+            std::thread::sleep(min_wait);
+            for (userdata, wait) in clock_events {
+                if wait <= min_wait {
+                    events.push((userdata, Errno::Success, EVENTTYPE_CLOCK));
+                }
+            }
+        }
+
+        for (i, (userdata, errno, event_type)) in events.iter().enumerate() {
+            let ptr_event = ptr_out + i * EVENT_SIZE;
+            memory[ptr_event..][..EVENT_SIZE].fill(0);
+            write_u64(memory, ptr_event, *userdata);
+            memory[ptr_event + 8..][..2].copy_from_slice(&(*errno as u16).to_le_bytes());
+            memory[ptr_event + 10] = *event_type;
+        }
+
+        events.len()
+    }
+}
+
+/// Write a list of strings as an array of pointers to null-terminated strings, like `argv`
+fn write_string_list(memory: &mut [u8], strings: &[&[u8]], ptr_ptrs: usize, ptr_buf: usize) {
+    let mut ptr_ptr = ptr_ptrs;
+    let mut ptr_str = ptr_buf;
+    for s in strings {
+        write_u32(memory, ptr_ptr, ptr_str as u32);
+        let bytes_target = &mut memory[ptr_str..][..s.len()];
+        bytes_target.copy_from_slice(s);
+        memory[ptr_str + s.len()] = 0; // C string zero termination
+        ptr_str += s.len() + 1;
+        ptr_ptr += 4;
+    }
+}
+
+fn write_string_list_sizes(
+    memory: &mut [u8],
+    strings: &[&[u8]],
+    ptr_count: usize,
+    ptr_buf_size: usize,
+) {
+    write_u32(memory, ptr_count, strings.len() as u32);
+    let buf_size: u32 = strings.iter().map(|s| 1 + s.len() as u32).sum();
+    write_u32(memory, ptr_buf_size, buf_size);
+}
+
+/// Read into an array of IO vectors, stopping early if the reader runs out of data
+fn read_iovs(
+    memory: &mut [u8],
+    ptr_iovs: usize,
+    iovs_len: usize,
+    reader: &mut impl Read,
+) -> io::Result<usize> {
+    // https://man7.org/linux/man-pages/man2/readv.2.html
+    // struct iovec {
+    //     void  *iov_base;    /* Starting address */
+    //     size_t iov_len;     /* Number of bytes to transfer */
+    // };
+    let mut n_read = 0;
+    for i in 0..iovs_len {
+        let ptr_iov = ptr_iovs + 8 * i;
+        let iov_base = read_u32(memory, ptr_iov) as usize;
+        let iov_len = read_u32(memory, ptr_iov + 4) as usize;
+        let n = reader.read(&mut memory[iov_base..][..iov_len])?;
+        n_read += n;
+        if n < iov_len {
+            break;
+        }
+    }
+    Ok(n_read)
+}
+
+fn write_iovs(
+    memory: &[u8],
+    ptr_iovs: usize,
+    iovs_len: usize,
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    let mut n_written = 0;
+    for i in 0..iovs_len {
+        let ptr_iov = ptr_iovs + 8 * i;
+        let iov_base = read_u32(memory, ptr_iov) as usize;
+        let iov_len = read_u32(memory, ptr_iov + 4) as usize;
+        writer.write_all(&memory[iov_base..][..iov_len])?;
+        n_written += iov_len;
+    }
+    Ok(n_written)
+}
+
+/// Do some IO at a given offset in a file, then restore the file's position
+fn with_temporary_position(
+    file: &mut File,
+    offset: u64,
+    io_fn: impl FnOnce(&mut File) -> io::Result<usize>,
+) -> Result<usize, Errno> {
+    let original_position = file.stream_position()?;
+    file.seek(SeekFrom::Start(offset))?;
+    let result = io_fn(file);
+    file.seek(SeekFrom::Start(original_position))?;
+    Ok(result?)
+}
+
+/// Directory entries in the order the app will see them, with `.` and `..` first
+fn read_dir_entries(path: &Path) -> Result<Vec<(Vec<u8>, u64, Filetype)>, Errno> {
+    let mut entries = vec![
+        (b".".to_vec(), 0, Filetype::Directory),
+        (b"..".to_vec(), 0, Filetype::Directory),
+    ];
+    let mut children = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_str().ok_or(Errno::Ilseq)?.as_bytes().to_vec();
+        let meta = entry.path().symlink_metadata()?;
+        children.push((name, inode(&meta), Filetype::from(&meta)));
+    }
+    // Sort so that cookies refer to the same entries between calls
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+    entries.extend(children);
+    Ok(entries)
+}
+
+/// Write as many directory entries as will fit into the buffer, starting from the one at index `cookie`.
+/// The last entry may be truncated, which tells WASI libc to call again with a bigger buffer.
+fn write_dirents(buf: &mut [u8], entries: &[(Vec<u8>, u64, Filetype)], cookie: u64) -> usize {
+    // struct dirent {
+    //     u64 d_next;     // offset 0
+    //     u64 d_ino;      // offset 8
+    //     u32 d_namlen;   // offset 16
+    //     u8  d_type;     // offset 20
+    // }                   // size 24, followed by the name
+    const DIRENT_SIZE: usize = 24;
+
+    let mut bufused = 0;
+    for (index, (name, ino, filetype)) in entries.iter().enumerate().skip(cookie as usize) {
+        let mut dirent = [0; DIRENT_SIZE];
+        dirent[0..8].copy_from_slice(&(index as u64 + 1).to_le_bytes());
+        dirent[8..16].copy_from_slice(&ino.to_le_bytes());
+        dirent[16..20].copy_from_slice(&(name.len() as u32).to_le_bytes());
+        dirent[20] = *filetype as u8;
+
+        for bytes in [&dirent[..], name.as_slice()] {
+            let len = bytes.len().min(buf.len() - bufused);
+            buf[bufused..][..len].copy_from_slice(&bytes[..len]);
+            bufused += len;
+        }
+        if bufused == buf.len() {
+            break;
+        }
+    }
+    bufused
+}
+
+fn write_filestat(memory: &mut [u8], ptr_filestat: usize, meta: &Metadata) {
+    fn nanos(time: io::Result<SystemTime>) -> u64 {
+        time.ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64)
+    }
+
+    write_filestat_zeroed(memory, ptr_filestat, Filetype::from(meta), meta.len());
+    write_u64(memory, ptr_filestat, device(meta));
+    write_u64(memory, ptr_filestat + 8, inode(meta));
+    write_u64(memory, ptr_filestat + 24, link_count(meta));
+    write_u64(memory, ptr_filestat + 40, nanos(meta.accessed()));
+    write_u64(memory, ptr_filestat + 48, nanos(meta.modified()));
+    write_u64(memory, ptr_filestat + 56, nanos(meta.modified()));
+}
+
+fn write_filestat_zeroed(memory: &mut [u8], ptr_filestat: usize, filetype: Filetype, size: u64) {
+    // struct filestat {
+    //     u64 dev;        // offset 0
+    //     u64 ino;        // offset 8
+    //     u8  filetype;   // offset 16
+    //     u64 nlink;      // offset 24
+    //     u64 size;       // offset 32
+    //     u64 atim;       // offset 40
+    //     u64 mtim;       // offset 48
+    //     u64 ctim;       // offset 56
+    // }                   // size 64
+    memory[ptr_filestat..][..64].fill(0);
+    memory[ptr_filestat + 16] = filetype as u8;
+    write_u64(memory, ptr_filestat + 24, 1);
+    write_u64(memory, ptr_filestat + 32, size);
+}
+
+#[cfg(unix)]
+fn device(meta: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::dev(meta)
+}
+
+#[cfg(not(unix))]
+fn device(_meta: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(unix)]
+fn inode(meta: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::ino(meta)
+}
+
+#[cfg(not(unix))]
+fn inode(_meta: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(unix)]
+fn link_count(meta: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::nlink(meta)
+}
+
+#[cfg(not(unix))]
+fn link_count(_meta: &Metadata) -> u64 {
+    1
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> Result<(), Errno> {
+    Ok(std::os::unix::fs::symlink(target, link)?)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _link: &Path) -> Result<(), Errno> {
+    Err(Errno::Nosys)
+}
+
+/// Convert WASI timestamps to the `timespec` pair used by `futimens` and `utimensat`
+#[cfg(unix)]
+fn timespecs(atim: u64, mtim: u64, fst_flags: u16) -> [libc::timespec; 2] {
+    let timespec = |time: u64, set_flag: u16, now_flag: u16| {
+        if fst_flags & now_flag != 0 {
+            libc::timespec {
+                tv_sec: 0,
+                tv_nsec: libc::UTIME_NOW,
+            }
+        } else if fst_flags & set_flag != 0 {
+            libc::timespec {
+                tv_sec: (time / 1_000_000_000) as libc::time_t,
+                tv_nsec: (time % 1_000_000_000) as _,
+            }
+        } else {
+            libc::timespec {
+                tv_sec: 0,
+                tv_nsec: libc::UTIME_OMIT,
+            }
+        }
+    };
+    [
+        timespec(atim, FSTFLAGS_ATIM, FSTFLAGS_ATIM_NOW),
+        timespec(mtim, FSTFLAGS_MTIM, FSTFLAGS_MTIM_NOW),
+    ]
+}
+
+#[cfg(unix)]
+fn set_file_times(file: &File, atim: u64, mtim: u64, fst_flags: u16) -> Result<(), Errno> {
+    use std::os::unix::io::AsRawFd;
+
+    let times = timespecs(atim, mtim, fst_flags);
+    // Safety: the file descriptor is valid for as long as we have a reference to the File
+    match unsafe { libc::futimens(file.as_raw_fd(), times.as_ptr()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error().into()),
+    }
+}
+
+#[cfg(unix)]
+fn set_path_times(
+    path: &Path,
+    atim: u64,
+    mtim: u64,
+    fst_flags: u16,
+    follow: bool,
+) -> Result<(), Errno> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|_| Errno::Inval)?;
+    let times = timespecs(atim, mtim, fst_flags);
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+    // Safety: the path is a valid C string and `times` has the two elements utimensat expects
+    match unsafe { libc::utimensat(libc::AT_FDCWD, c_path.as_ptr(), times.as_ptr(), flags) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error().into()),
+    }
+}
+
+#[cfg(not(unix))]
+fn set_file_times(_file: &File, _atim: u64, _mtim: u64, _fst_flags: u16) -> Result<(), Errno> {
+    Err(Errno::Nosys)
+}
+
+#[cfg(not(unix))]
+fn set_path_times(
+    _path: &Path,
+    _atim: u64,
+    _mtim: u64,
+    _fst_flags: u16,
+    _follow: bool,
+) -> Result<(), Errno> {
+    Err(Errno::Nosys)
+}
+
+fn to_wasi_result(result: Result<(), Errno>) -> Option<Value> {
+    let errno = match result {
+        Ok(()) => Errno::Success,
+        Err(e) => e,
+    };
+    Some(Value::I32(errno as i32))
+}
+
+fn read_u16(memory: &[u8], addr: usize) -> u16 {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(&memory[addr..][..2]);
+    u16::from_le_bytes(bytes)
 }
 
 fn read_u32(memory: &[u8], addr: usize) -> u32 {
@@ -300,14 +1392,175 @@ fn read_i32(memory: &[u8], addr: usize) -> i32 {
     i32::from_le_bytes(bytes)
 }
 
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&memory[addr..][..8]);
+    u64::from_le_bytes(bytes)
+}
+
 fn write_u32(memory: &mut [u8], addr: usize, value: u32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
 
+/// Check that `path` really is inside `sandbox_root` once symlinks are resolved.
+/// Anything past the deepest existing ancestor is made of normal components only,
+/// since `resolve_path` rejects the rest, so canonicalizing that ancestor is enough.
+fn check_in_sandbox(path: &Path, sandbox_root: &Path) -> Result<(), Errno> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+        .ok_or(Errno::Notcapable)?;
+
+    // A dangling symlink could point anywhere, so it fails to canonicalize and is rejected too
+    match existing.canonicalize() {
+        Ok(canonical) if canonical.starts_with(sandbox_root) => Ok(()),
+        _ => Err(Errno::Notcapable),
+    }
+}
+
 fn write_i32(memory: &mut [u8], addr: usize, value: i32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
 
+fn write_u64(memory: &mut [u8], addr: usize, value: u64) {
+    memory[addr..][..8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Lookup flag: follow the final component of the path if it's a symlink
+const LOOKUPFLAGS_SYMLINK_FOLLOW: u32 = 1 << 0;
+
+/// Open flags for `path_open`
+const OFLAGS_CREAT: u16 = 1 << 0;
+const OFLAGS_DIRECTORY: u16 = 1 << 1;
+const OFLAGS_EXCL: u16 = 1 << 2;
+const OFLAGS_TRUNC: u16 = 1 << 3;
+
+/// File descriptor flag: data written to the file is always appended to its end
+const FDFLAGS_APPEND: u16 = 1 << 0;
+
+/// Flags for the `*_set_times` functions
+const FSTFLAGS_ATIM: u16 = 1 << 0;
+const FSTFLAGS_ATIM_NOW: u16 = 1 << 1;
+const FSTFLAGS_MTIM: u16 = 1 << 2;
+const FSTFLAGS_MTIM_NOW: u16 = 1 << 3;
+
+/// Rights we care about. We report the others but don't check any of them.
+const RIGHTS_FD_READ: u64 = 1 << 1;
+const RIGHTS_FD_WRITE: u64 = 1 << 6;
+const RIGHTS_ALL: u64 = (1 << 29) - 1;
+
+/// Event types for `poll_oneoff`
+const EVENTTYPE_CLOCK: u8 = 0;
+const EVENTTYPE_FD_READ: u8 = 1;
+const EVENTTYPE_FD_WRITE: u8 = 2;
+const SUBCLOCKFLAGS_ABSTIME: u16 = 1 << 0;
+
+/// Identifiers for clocks.
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ClockId {
+    /// The clock measuring real time. Time value zero corresponds with 1970-01-01T00:00:00Z.
+    Realtime,
+    /// The store-wide monotonic clock, which is defined as a clock measuring real time,
+    /// whose value cannot be adjusted and which cannot have negative clock jumps.
+    Monotonic,
+    /// The CPU-time clock associated with the current process.
+    ProcessCputime,
+    /// The CPU-time clock associated with the current thread.
+    ThreadCputime,
+}
+
+/// The type of a file descriptor or file.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Filetype {
+    /// The type of the file descriptor or file is unknown or is different from any of the other types specified.
+    Unknown,
+    /// The file descriptor or file refers to a block device inode.
+    BlockDevice,
+    /// The file descriptor or file refers to a character device inode.
+    CharacterDevice,
+    /// The file descriptor or file refers to a directory inode.
+    Directory,
+    /// The file descriptor or file refers to a regular file inode.
+    RegularFile,
+    /// The file descriptor or file refers to a datagram socket.
+    SocketDgram,
+    /// The file descriptor or file refers to a byte-stream socket.
+    SocketStream,
+    /// The file refers to a symbolic link inode.
+    SymbolicLink,
+}
+
+impl From<&Metadata> for Filetype {
+    fn from(meta: &Metadata) -> Self {
+        let file_type = meta.file_type();
+        if file_type.is_dir() {
+            Filetype::Directory
+        } else if file_type.is_file() {
+            Filetype::RegularFile
+        } else if file_type.is_symlink() {
+            Filetype::SymbolicLink
+        } else {
+            Filetype::Unknown
+        }
+    }
+}
+
+impl From<io::Error> for Errno {
+    fn from(e: io::Error) -> Self {
+        #[cfg(unix)]
+        {
+            if let Some(code) = e.raw_os_error() {
+                return match code {
+                    libc::EPERM => Errno::Perm,
+                    libc::ENOENT => Errno::Noent,
+                    libc::EINTR => Errno::Intr,
+                    libc::EIO => Errno::Io,
+                    libc::EBADF => Errno::Badf,
+                    libc::EAGAIN => Errno::Again,
+                    libc::EACCES => Errno::Access,
+                    libc::EBUSY => Errno::Busy,
+                    libc::EEXIST => Errno::Exist,
+                    libc::EXDEV => Errno::Xdev,
+                    libc::ENOTDIR => Errno::Notdir,
+                    libc::EISDIR => Errno::Isdir,
+                    libc::EINVAL => Errno::Inval,
+                    libc::ENFILE => Errno::Nfile,
+                    libc::EMFILE => Errno::Mfile,
+                    libc::ETXTBSY => Errno::Txtbsy,
+                    libc::EFBIG => Errno::Fbig,
+                    libc::ENOSPC => Errno::Nospc,
+                    libc::ESPIPE => Errno::Spipe,
+                    libc::EROFS => Errno::Rofs,
+                    libc::EMLINK => Errno::Mlink,
+                    libc::EPIPE => Errno::Pipe,
+                    libc::ENAMETOOLONG => Errno::Nametoolong,
+                    libc::ENOSYS => Errno::Nosys,
+                    libc::ENOTEMPTY => Errno::Notempty,
+                    libc::ELOOP => Errno::Loop,
+                    libc::EOVERFLOW => Errno::Overflow,
+                    libc::ENOTSUP => Errno::Notsup,
+                    libc::EDQUOT => Errno::Dquot,
+                    _ => Errno::Io,
+                };
+            }
+        }
+
+        match e.kind() {
+            io::ErrorKind::NotFound => Errno::Noent,
+            io::ErrorKind::PermissionDenied => Errno::Access,
+            io::ErrorKind::AlreadyExists => Errno::Exist,
+            io::ErrorKind::InvalidInput => Errno::Inval,
+            io::ErrorKind::Interrupted => Errno::Intr,
+            io::ErrorKind::WouldBlock => Errno::Again,
+            io::ErrorKind::BrokenPipe => Errno::Pipe,
+            io::ErrorKind::Unsupported => Errno::Notsup,
+            _ => Errno::Io,
+        }
+    }
+}
+
 /// Error codes returned by functions.
 /// Not all of these error codes are returned by the functions provided by this
 /// API; some are used in higher-level library layers, and others are provided
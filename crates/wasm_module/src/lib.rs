@@ -17,9 +17,9 @@ use bumpalo::{collections::Vec, Bump};
 use self::linking::{IndexRelocType, LinkingSection, RelocationSection, WasmObjectSymbol};
 use self::parse::{Parse, ParseError};
 use self::sections::{
-    CodeSection, DataSection, ElementSection, ExportSection, FunctionSection, GlobalSection,
-    ImportDesc, ImportSection, MemorySection, NameSection, OpaqueSection, Section, SectionId,
-    TableSection, TypeSection,
+    CodeSection, DataCountSection, DataSection, ElementSection, ExportSection, FunctionSection,
+    GlobalSection, ImportDesc, ImportSection, MemorySection, NameSection, OpaqueSection, Section,
+    SectionId, TableSection, TypeSection,
 };
 pub use self::serialize::{SerialBuffer, Serialize};
 
@@ -93,6 +93,7 @@ impl<'a> WasmModule<'a> {
         self.export.serialize(buffer);
         self.start.serialize(buffer);
         self.element.serialize(buffer);
+        DataCountSection(&self.data).serialize(buffer);
         self.code.serialize(buffer);
         self.data.serialize(buffer);
         self.names.serialize(buffer);
@@ -141,9 +142,10 @@ impl<'a> WasmModule<'a> {
         let export = ExportSection::parse(arena, bytes, &mut cursor)?;
         let start = OpaqueSection::parse((arena, SectionId::Start), bytes, &mut cursor)?;
         let element = ElementSection::parse(arena, bytes, &mut cursor)?;
-        let _data_count = OpaqueSection::parse((arena, SectionId::DataCount), bytes, &mut cursor)?;
+        let data_count = OpaqueSection::parse((arena, SectionId::DataCount), bytes, &mut cursor)?;
         let code = CodeSection::parse(arena, bytes, &mut cursor)?;
-        let data = DataSection::parse(arena, bytes, &mut cursor)?;
+        let mut data = DataSection::parse(arena, bytes, &mut cursor)?;
+        data.has_count_section = data_count.size() > 0;
         let linking = LinkingSection::parse(arena, bytes, &mut cursor)?;
         let reloc_code = RelocationSection::parse((arena, "reloc.CODE"), bytes, &mut cursor)?;
         let reloc_data = RelocationSection::parse((arena, "reloc.DATA"), bytes, &mut cursor)?;
@@ -239,7 +241,15 @@ impl<'a> WasmModule<'a> {
                 .iter()
                 .flat_map(|seg| seg.fn_indices.iter().copied())
                 .map(|fn_index| {
-                    let sig = self.function.signatures[fn_index as usize - import_count];
+                    let sig = if (fn_index as usize) < import_count {
+                        // The host can take the address of a JS import too
+                        match self.import.imports[fn_index as usize].description {
+                            ImportDesc::Func { signature_index } => signature_index,
+                            _ => internal_error!("Function #{} is not an import", fn_index),
+                        }
+                    } else {
+                        self.function.signatures[fn_index as usize - import_count]
+                    };
                     (fn_index, sig)
                 }),
             arena,
@@ -311,8 +321,7 @@ impl<'a> WasmModule<'a> {
                 .linking
                 .find_and_reindex_imported_fn(old_index as u32, new_index as u32)
                 .unwrap();
-            self.reloc_code
-                .apply_relocs_u32(&mut self.code.bytes, sym_index, new_index as u32);
+            self.relocate_fn(sym_index, old_index as u32, new_index as u32);
         }
 
         //
@@ -454,11 +463,34 @@ impl<'a> WasmModule<'a> {
             .map(|sym_index| {
                 self.reloc_code
                     .apply_relocs_u32(&mut self.code.bytes, sym_index as u32, value);
+                self.data
+                    .apply_relocs_u32(&self.reloc_data, sym_index as u32, value);
 
                 sym_index as u32
             })
     }
 
+    /// Update all references to a function symbol that has moved to a new function index.
+    /// Calls refer to the function index itself. If the host takes the function's address, then code
+    /// and data refer to its slot in the function table instead. The slot stays the same, but the
+    /// table entry has to point at the new index.
+    fn relocate_fn(&mut self, sym_index: u32, old_fn_index: u32, new_fn_index: u32) {
+        self.reloc_code
+            .apply_fn_index_relocs(&mut self.code.bytes, sym_index, new_fn_index);
+
+        self.element.reindex_fn(old_fn_index, new_fn_index);
+
+        if self.reloc_code.has_table_index_relocs(sym_index)
+            || self.reloc_data.has_table_index_relocs(sym_index)
+        {
+            let table_index = self.element.get_or_insert_fn(new_fn_index) as u32;
+            self.reloc_code
+                .apply_table_index_relocs(&mut self.code.bytes, sym_index, table_index);
+            self.data
+                .apply_table_index_relocs(&self.reloc_data, sym_index, table_index);
+        }
+    }
+
     /// Linking steps for host-to-app functions like `roc__mainForHost_1_exposed`
     /// (See further explanation in the gen_wasm README)
     /// - Remove the target function from the ImportSection. It's not a JS import but the host declared it as one.
@@ -520,8 +552,7 @@ impl<'a> WasmModule<'a> {
                 .unwrap();
 
             // Update calls to use the app function instead of the host import
-            self.reloc_code
-                .apply_relocs_u32(&mut self.code.bytes, host_sym_index, app_fn_index);
+            self.relocate_fn(host_sym_index, host_fn_index as u32, app_fn_index);
 
             if swap_import_index != host_import_index {
                 // get the name using the old host import index because we already swapped it!
@@ -534,11 +565,7 @@ impl<'a> WasmModule<'a> {
                     .unwrap();
 
                 // Update calls to the swapped JS import
-                self.reloc_code.apply_relocs_u32(
-                    &mut self.code.bytes,
-                    swap_sym_index,
-                    host_fn_index as u32,
-                );
+                self.relocate_fn(swap_sym_index, swap_fn_index as u32, host_fn_index as u32);
 
                 // Update the name in the debug info
                 if let Some((_, debug_name)) = self
@@ -854,3 +881,179 @@ pub struct WasmDebugSettings {
 pub const DEBUG_SETTINGS: WasmDebugSettings = WasmDebugSettings {
     skip_dead_code_elim: false && cfg!(debug_assertions),
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linking::{RelocationEntry, WASM_SYM_UNDEFINED};
+    use crate::sections::{DataMode, DataSegment, ElementSegment, Import};
+    use crate::serialize::SerialBuffer;
+
+    const JS_UNUSED: u32 = 0;
+    const ROC_MAIN: u32 = 1;
+    const JS_POINTEE: u32 = 2;
+    const HOST_MAIN: u32 = 3;
+    const APP_MAIN: u32 = 4;
+
+    // Byte offsets of relocation sites in the code and data sections
+    const FN_PTR_OFFSET: u32 = 4;
+    const TYPE_OFFSET: u32 = 10;
+    const CALL_OFFSET: u32 = 17;
+    const DATA_FN_PTR_OFFSET: u32 = 6;
+
+    /// A host that takes the address of a JS import, in code and in static data:
+    ///
+    /// ```c
+    /// extern int js_unused();
+    /// extern int roc__main_1_exposed();
+    /// extern int js_pointee();
+    ///
+    /// int (*data_fn_ptr)() = &js_pointee;
+    ///
+    /// int host_main() {
+    ///     int (*fn_ptr)() = &js_pointee;
+    ///     return fn_ptr() + roc__main_1_exposed();
+    /// }
+    /// ```
+    fn host_taking_address_of_import(arena: &Bump) -> WasmModule {
+        let mut module = WasmModule::new(arena);
+
+        for name in ["js_unused", "roc__main_1_exposed", "js_pointee"] {
+            module.import.imports.push(Import {
+                module: "env",
+                name,
+                description: ImportDesc::Func { signature_index: 0 },
+            });
+        }
+        module.function.signatures.push(0);
+        module.names.function_names.extend([
+            (JS_UNUSED, "js_unused"),
+            (ROC_MAIN, "roc__main_1_exposed"),
+            (JS_POINTEE, "js_pointee"),
+            (HOST_MAIN, "host_main"),
+        ]);
+
+        let symbols = &mut module.linking.symbol_table;
+        for index in [JS_UNUSED, ROC_MAIN, JS_POINTEE] {
+            symbols.push(SymInfo::Function(WasmObjectSymbol::ImplicitlyNamed {
+                flags: WASM_SYM_UNDEFINED,
+                index,
+            }));
+        }
+        symbols.push(SymInfo::Function(WasmObjectSymbol::ExplicitlyNamed {
+            flags: 0,
+            index: HOST_MAIN,
+            name: "host_main",
+        }));
+
+        // host_main is in table slot 1, and js_pointee in slot 2
+        module.element.segments.push(ElementSegment {
+            offset: ConstExpr::I32(1),
+            fn_indices: bumpalo::vec![in arena; HOST_MAIN, JS_POINTEE],
+        });
+
+        let code = &mut module.code;
+        code.bytes.push(1); // function count
+        code.function_offsets.push(code.bytes.len() as u32);
+        code.function_count = 1;
+        code.bytes.push(22); // function size
+        code.bytes.push(0); // no locals
+        OpCode::I32CONST.serialize(&mut code.bytes);
+        code.bytes.encode_padded_u32(2);
+        OpCode::CALLINDIRECT.serialize(&mut code.bytes);
+        code.bytes.encode_padded_u32(0);
+        code.bytes.push(0); // table index
+        OpCode::CALL.serialize(&mut code.bytes);
+        code.bytes.encode_padded_u32(ROC_MAIN);
+        OpCode::I32ADD.serialize(&mut code.bytes);
+        OpCode::END.serialize(&mut code.bytes);
+
+        let relocs = &mut module.reloc_code.entries;
+        relocs.push(RelocationEntry::Index {
+            type_id: IndexRelocType::TableIndexSleb,
+            offset: FN_PTR_OFFSET,
+            symbol_index: JS_POINTEE,
+        });
+        relocs.push(RelocationEntry::Index {
+            type_id: IndexRelocType::TypeIndexLeb,
+            offset: TYPE_OFFSET,
+            symbol_index: 0,
+        });
+        relocs.push(RelocationEntry::Index {
+            type_id: IndexRelocType::FunctionIndexLeb,
+            offset: CALL_OFFSET,
+            symbol_index: ROC_MAIN,
+        });
+
+        let mut data = DataSection::new(arena);
+        data.append_segment(DataSegment {
+            mode: DataMode::active_at(16),
+            init: bumpalo::vec![in arena; 2, 0, 0, 0],
+        });
+        let mut buffer = std::vec::Vec::with_capacity(data.size());
+        data.serialize(&mut buffer);
+        module.data = DataSection::parse(arena, &buffer, &mut 0).unwrap();
+        module.reloc_data.entries.push(RelocationEntry::Index {
+            type_id: IndexRelocType::TableIndexI32,
+            offset: DATA_FN_PTR_OFFSET,
+            symbol_index: JS_POINTEE,
+        });
+
+        // The app's main function, as if gen_wasm had appended it to the host
+        let code = &mut module.code;
+        code.function_offsets.push(code.bytes.len() as u32);
+        code.function_count += 1;
+        code.bytes
+            .extend([4, 0, OpCode::I32CONST as u8, 42, OpCode::END as u8]);
+        module.function.signatures.push(0);
+
+        module
+    }
+
+    fn read_code_u32(module: &WasmModule, offset: u32) -> u32 {
+        u32::parse((), &module.code.bytes, &mut (offset as usize)).unwrap()
+    }
+
+    fn read_data_fn_ptr(module: &WasmModule) -> u32 {
+        let (_, init) = module.data.segment(0).unwrap();
+        u32::from_le_bytes(init[0..4].try_into().unwrap())
+    }
+
+    fn table<'a>(module: &'a WasmModule) -> &'a [u32] {
+        &module.element.segments[0].fn_indices
+    }
+
+    #[test]
+    fn test_link_host_taking_address_of_import() {
+        let arena = &Bump::new();
+        let mut module = host_taking_address_of_import(arena);
+
+        module.link_host_to_app_calls(
+            arena,
+            bumpalo::vec![in arena; ("roc__main_1_exposed", APP_MAIN)],
+        );
+
+        // js_pointee was swapped into the slot of the app function's import
+        let import_names = Vec::from_iter_in(module.import.imports.iter().map(|i| i.name), arena);
+        assert_eq!(&import_names, &["js_unused", "js_pointee"]);
+
+        // The call goes to the app, and the table entry to the new index of js_pointee
+        assert_eq!(read_code_u32(&module, CALL_OFFSET), APP_MAIN);
+        assert_eq!(table(&module), &[HOST_MAIN, 1]);
+
+        // Function pointers still refer to the same table slot
+        assert_eq!(read_code_u32(&module, FN_PTR_OFFSET), 2);
+        assert_eq!(read_data_fn_ptr(&module), 2);
+
+        // js_unused is dead, so js_pointee moves again. It's only reachable through the table.
+        let mut called_fns = BitVec::repeat(false, APP_MAIN as usize + 1);
+        called_fns.set(HOST_MAIN as usize, true);
+        module.eliminate_dead_code(arena, called_fns);
+
+        let import_names = Vec::from_iter_in(module.import.imports.iter().map(|i| i.name), arena);
+        assert_eq!(&import_names, &["js_pointee"]);
+        assert_eq!(table(&module), &[HOST_MAIN, 0]);
+        assert_eq!(read_code_u32(&module, FN_PTR_OFFSET), 2);
+        assert_eq!(read_data_fn_ptr(&module), 2);
+    }
+}
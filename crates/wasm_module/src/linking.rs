@@ -3,7 +3,10 @@ use bumpalo::Bump;
 
 use super::parse::{parse_fixed_size_items, Parse, ParseError, SkipBytes};
 use super::sections::SectionId;
-use super::serialize::{overwrite_padded_i32, overwrite_padded_u32};
+use super::serialize::{
+    overwrite_padded_i32, overwrite_padded_i64, overwrite_padded_u32, overwrite_padded_u64,
+};
+use roc_error_macros::internal_error;
 
 /*******************************************************************
  *
@@ -29,6 +32,9 @@ pub enum IndexRelocType {
     GlobalIndexLeb = 7,
     /// an event index encoded as a 5-byte [varuint32]. Used for the immediate argument of a `throw` and `if_except`   instruction.
     EventIndexLeb = 10,
+    /// a function table index encoded as a 5-byte [varint32], relative to the `__table_base` global.
+    /// Used in position independent code (`-fPIC`), where absolute table indices are not known at link time.
+    TableIndexRelSleb = 12,
     /// a global index encoded as [uint32].
     GlobalIndexI32 = 13,
     /// the 64-bit counterpart of  `R_WASM_TABLE_INDEX_SLEB`. A function table index encoded as a 10-byte [varint64].
@@ -39,6 +45,11 @@ pub enum IndexRelocType {
     TableIndexI64 = 19,
     /// a table number encoded as a 5-byte [varuint32]. Used for the table immediate argument in the table.*   instructions.
     TableNumberLeb = 20,
+    /// the 64-bit counterpart of `R_WASM_TABLE_INDEX_REL_SLEB`. A function table index encoded as a 10-byte [varint64],
+    /// relative to the `__table_base` global.
+    TableIndexRelSleb64 = 24,
+    /// a function index encoded as a [uint32]. Used in custom sections for function annotations.
+    FunctionIndexI32 = 26,
 }
 
 impl IndexRelocType {
@@ -50,13 +61,39 @@ impl IndexRelocType {
             6 => Some(Self::TypeIndexLeb),
             7 => Some(Self::GlobalIndexLeb),
             10 => Some(Self::EventIndexLeb),
+            12 => Some(Self::TableIndexRelSleb),
             13 => Some(Self::GlobalIndexI32),
             18 => Some(Self::TableIndexSleb64),
             19 => Some(Self::TableIndexI64),
             20 => Some(Self::TableNumberLeb),
+            24 => Some(Self::TableIndexRelSleb64),
+            26 => Some(Self::FunctionIndexI32),
             _ => None,
         }
     }
+
+    fn target(self) -> RelocTarget {
+        use IndexRelocType::*;
+        match self {
+            FunctionIndexLeb | FunctionIndexI32 => RelocTarget::FunctionIndex,
+            TableIndexSleb | TableIndexI32 | TableIndexRelSleb | TableIndexSleb64
+            | TableIndexI64 | TableIndexRelSleb64 => RelocTarget::TableIndex,
+            TypeIndexLeb | GlobalIndexLeb | EventIndexLeb | GlobalIndexI32 | TableNumberLeb => {
+                RelocTarget::Other
+            }
+        }
+    }
+}
+
+/// What a relocation refers to. A function symbol can be referred to both by its function index
+/// (e.g. in a `call` instruction) and by its slot in the function table (when its address is taken).
+/// These are different numbers, so they have to be updated separately.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub(crate) enum RelocTarget {
+    FunctionIndex,
+    TableIndex,
+    /// Memory addresses, offsets, and indices of globals, types, events and tables
+    Other,
 }
 
 #[repr(u8)]
@@ -75,6 +112,9 @@ pub enum OffsetRelocType {
     FunctionOffsetI32 = 8,
     /// a byte offset from start of the specified section encoded as a [uint32].
     SectionOffsetI32 = 9,
+    /// a linear memory index encoded as a 5-byte [varint32], relative to the `__memory_base` global.
+    /// Used in position independent code (`-fPIC`), where absolute memory addresses are not known at link time.
+    MemoryAddrRelSleb = 11,
     /// the 64-bit counterpart of `R_WASM_MEMORY_ADDR_LEB`. A 64-bit linear memory index encoded as a 10-byte [varuint64],
     /// Used for the immediate argument of a `load` or `store` instruction on a 64-bit linear memory array.
     MemoryAddrLeb64 = 14,
@@ -84,6 +124,21 @@ pub enum OffsetRelocType {
     /// the 64-bit counterpart of `R_WASM_MEMORY_ADDR`. A 64-bit linear memory index encoded as a [uint64],
     /// e.g. taking the 64-bit address of a C++ global in a static data initializer.
     MemoryAddrI64 = 16,
+    /// the 64-bit counterpart of `R_WASM_MEMORY_ADDR_REL_SLEB`. A 64-bit linear memory index encoded as a 10-byte [varint64],
+    /// relative to the `__memory_base` global.
+    MemoryAddrRelSleb64 = 17,
+    /// a byte offset from the `__tls_base` global encoded as a 5-byte [varint32].
+    /// Used for the immediate argument of a `i32.const` instruction, e.g. the address of a thread-local variable.
+    MemoryAddrTlsSleb = 21,
+    /// the 64-bit counterpart of `R_WASM_FUNCTION_OFFSET_I32`. A byte offset within code section for the specific function
+    /// encoded as a [uint64].
+    FunctionOffsetI64 = 22,
+    /// a byte offset between the relocation site and a linear memory index, encoded as a [uint32].
+    /// Used for pointer-relative addressing in static data initializers.
+    MemoryAddrLocrelI32 = 23,
+    /// the 64-bit counterpart of `R_WASM_MEMORY_ADDR_TLS_SLEB`. A byte offset from the `__tls_base` global
+    /// encoded as a 10-byte [varint64].
+    MemoryAddrTlsSleb64 = 25,
 }
 
 impl OffsetRelocType {
@@ -94,9 +149,15 @@ impl OffsetRelocType {
             5 => Some(Self::MemoryAddrI32),
             8 => Some(Self::FunctionOffsetI32),
             9 => Some(Self::SectionOffsetI32),
+            11 => Some(Self::MemoryAddrRelSleb),
             14 => Some(Self::MemoryAddrLeb64),
             15 => Some(Self::MemoryAddrSleb64),
             16 => Some(Self::MemoryAddrI64),
+            17 => Some(Self::MemoryAddrRelSleb64),
+            21 => Some(Self::MemoryAddrTlsSleb),
+            22 => Some(Self::FunctionOffsetI64),
+            23 => Some(Self::MemoryAddrLocrelI32),
+            25 => Some(Self::MemoryAddrTlsSleb64),
             _ => None,
         }
     }
@@ -166,22 +227,104 @@ impl<'a> RelocationSection<'a> {
         }
     }
 
+    /// Apply relocations for a symbol to the bytes of the target section.
+    /// `value` is the symbol's new address, offset or index. For relocation types that are relative
+    /// to a base global (`__memory_base` or `__tls_base`), it should be relative to that base.
+    /// Function indices and table slots are not touched. Use [Self::apply_fn_index_relocs]
+    /// and [Self::apply_table_index_relocs] for those.
     pub fn apply_relocs_u32(&self, section_bytes: &mut [u8], sym_index: u32, value: u32) {
+        self.apply_relocs_help(
+            section_bytes,
+            0,
+            sym_index,
+            RelocTarget::Other,
+            value,
+            |_| None,
+        )
+    }
+
+    /// Apply the relocations that refer to a function symbol by its function index, e.g. in `call` instructions.
+    pub fn apply_fn_index_relocs(&self, section_bytes: &mut [u8], sym_index: u32, fn_index: u32) {
+        self.apply_relocs_help(
+            section_bytes,
+            0,
+            sym_index,
+            RelocTarget::FunctionIndex,
+            fn_index,
+            |_| None,
+        )
+    }
+
+    /// Apply the relocations that refer to a function symbol by its slot in the function table,
+    /// i.e. wherever the host takes the function's address.
+    pub fn apply_table_index_relocs(
+        &self,
+        section_bytes: &mut [u8],
+        sym_index: u32,
+        table_index: u32,
+    ) {
+        self.apply_relocs_help(
+            section_bytes,
+            0,
+            sym_index,
+            RelocTarget::TableIndex,
+            table_index,
+            |_| None,
+        )
+    }
+
+    /// Check whether anything in the target section takes the address of a function symbol
+    pub fn has_table_index_relocs(&self, sym_index: u32) -> bool {
+        self.entries.iter().any(|entry| match entry {
+            RelocationEntry::Index {
+                type_id,
+                symbol_index,
+                ..
+            } => *symbol_index == sym_index && type_id.target() == RelocTarget::TableIndex,
+            RelocationEntry::Offset { .. } => false,
+        })
+    }
+
+    /// `offset_base` is the relocation offset of `section_bytes[0]`.
+    /// Only the relocations that refer to `target` are applied.
+    /// `site_address` returns the memory address of a byte in `section_bytes`, if it has one.
+    /// It's only needed for position-relative relocations, which can only occur in data segments.
+    pub(crate) fn apply_relocs_help<F>(
+        &self,
+        section_bytes: &mut [u8],
+        offset_base: u32,
+        sym_index: u32,
+        target: RelocTarget,
+        value: u32,
+        site_address: F,
+    ) where
+        F: Fn(usize) -> Option<u32>,
+    {
         for entry in self.entries.iter() {
             match entry {
                 RelocationEntry::Index {
                     type_id,
                     offset,
                     symbol_index,
-                } if *symbol_index == sym_index => {
+                } if *symbol_index == sym_index && type_id.target() == target => {
                     use IndexRelocType::*;
-                    let idx = *offset as usize;
+                    let idx = (*offset - offset_base) as usize;
+                    let bytes = &mut section_bytes[idx..];
                     match type_id {
                         FunctionIndexLeb | TypeIndexLeb | GlobalIndexLeb | EventIndexLeb
-                        | TableNumberLeb => {
-                            overwrite_padded_u32(&mut section_bytes[idx..], value);
+                        | TableNumberLeb => overwrite_padded_u32(bytes, value),
+                        TableIndexSleb | TableIndexRelSleb => {
+                            overwrite_padded_i32(bytes, value as i32);
+                        }
+                        TableIndexI32 | GlobalIndexI32 | FunctionIndexI32 => {
+                            bytes[0..4].copy_from_slice(&value.to_le_bytes());
+                        }
+                        TableIndexSleb64 | TableIndexRelSleb64 => {
+                            overwrite_padded_i64(bytes, value as i64);
+                        }
+                        TableIndexI64 => {
+                            bytes[0..8].copy_from_slice(&(value as u64).to_le_bytes());
                         }
-                        _ => todo!("Linking relocation type {:?}", type_id),
                     }
                 }
                 RelocationEntry::Offset {
@@ -189,17 +332,37 @@ impl<'a> RelocationSection<'a> {
                     offset,
                     symbol_index,
                     addend,
-                } if *symbol_index == sym_index => {
+                } if *symbol_index == sym_index && target == RelocTarget::Other => {
                     use OffsetRelocType::*;
-                    let idx = *offset as usize;
+                    let idx = (*offset - offset_base) as usize;
+                    let value32 = value.wrapping_add(*addend as u32);
+                    let value64 = (value as u64).wrapping_add(*addend as i64 as u64);
+                    let bytes = &mut section_bytes[idx..];
                     match type_id {
-                        MemoryAddrLeb => {
-                            overwrite_padded_u32(&mut section_bytes[idx..], value + *addend as u32);
+                        MemoryAddrLeb => overwrite_padded_u32(bytes, value32),
+                        MemoryAddrSleb | MemoryAddrRelSleb | MemoryAddrTlsSleb => {
+                            overwrite_padded_i32(bytes, value32 as i32);
+                        }
+                        MemoryAddrI32 | FunctionOffsetI32 | SectionOffsetI32 => {
+                            bytes[0..4].copy_from_slice(&value32.to_le_bytes());
+                        }
+                        MemoryAddrLocrelI32 => {
+                            let site = site_address(idx).unwrap_or_else(|| {
+                                internal_error!(
+                                    "Relocation type {:?} at offset {} is not in a data segment",
+                                    type_id,
+                                    offset
+                                )
+                            });
+                            bytes[0..4].copy_from_slice(&value32.wrapping_sub(site).to_le_bytes());
                         }
-                        MemoryAddrSleb => {
-                            overwrite_padded_i32(&mut section_bytes[idx..], value as i32 + *addend);
+                        MemoryAddrLeb64 => overwrite_padded_u64(bytes, value64),
+                        MemoryAddrSleb64 | MemoryAddrRelSleb64 | MemoryAddrTlsSleb64 => {
+                            overwrite_padded_i64(bytes, value64 as i64);
+                        }
+                        MemoryAddrI64 | FunctionOffsetI64 => {
+                            bytes[0..8].copy_from_slice(&value64.to_le_bytes());
                         }
-                        _ => todo!("Linking relocation type {:?}", type_id),
                     }
                 }
                 _ => {}
@@ -630,20 +793,25 @@ impl<'a> LinkingSection<'a> {
 impl<'a> Parse<&'a Bump> for LinkingSection<'a> {
     fn parse(arena: &'a Bump, bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
         let cursor_reset = *cursor;
-        if *cursor >= bytes.len() || bytes[*cursor] != SectionId::Custom as u8 {
-            *cursor = cursor_reset;
-            return Ok(LinkingSection::new(arena));
-        }
-        *cursor += 1;
-        let body_size = u32::parse((), bytes, cursor)?;
-        let section_end = *cursor + body_size as usize;
 
-        // Don't fail if it's the wrong section. Let the WasmModule validate presence/absence of sections
-        let actual_name = <&'a str>::parse(arena, bytes, cursor)?;
-        if actual_name != Self::NAME {
-            *cursor = cursor_reset;
-            return Ok(LinkingSection::new(arena));
-        }
+        // Object files with debug info have DWARF custom sections before the linking section.
+        // We don't keep them, so skip over any custom sections until we find the right one.
+        let section_end = loop {
+            if *cursor >= bytes.len() || bytes[*cursor] != SectionId::Custom as u8 {
+                // Don't fail if the section is missing. Let the WasmModule validate presence/absence of sections
+                *cursor = cursor_reset;
+                return Ok(LinkingSection::new(arena));
+            }
+            *cursor += 1;
+            let body_size = u32::parse((), bytes, cursor)?;
+            let section_end = *cursor + body_size as usize;
+
+            let actual_name = <&'a str>::parse(arena, bytes, cursor)?;
+            if actual_name == Self::NAME {
+                break section_end;
+            }
+            *cursor = section_end;
+        };
 
         let linking_version = bytes[*cursor];
         if linking_version != LINKING_VERSION {
@@ -689,3 +857,172 @@ impl<'a> Parse<&'a Bump> for LinkingSection<'a> {
         Ok(section)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_one(entry: RelocationEntry, value: u32) -> [u8; 12] {
+        let arena = &Bump::new();
+        let target = match &entry {
+            RelocationEntry::Index { type_id, .. } => type_id.target(),
+            RelocationEntry::Offset { .. } => RelocTarget::Other,
+        };
+        let mut relocs = RelocationSection::new(arena, "reloc.CODE");
+        relocs.entries.push(entry);
+        let mut bytes = [0xaa; 12];
+        relocs.apply_relocs_help(&mut bytes[1..], 0, 7, target, value, |_| None);
+        bytes
+    }
+
+    #[test]
+    fn test_apply_index_relocs() {
+        use IndexRelocType::*;
+        let leb: &[u8] = &[0xac, 0x82, 0x80, 0x80, 0x00];
+        let leb64: &[u8] = &[0xac, 0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        let u32_le: &[u8] = &[0x2c, 0x01, 0, 0];
+        let u64_le: &[u8] = &[0x2c, 0x01, 0, 0, 0, 0, 0, 0];
+        let cases = [
+            (FunctionIndexLeb, leb),
+            (TableIndexSleb, leb),
+            (TableIndexI32, u32_le),
+            (TypeIndexLeb, leb),
+            (GlobalIndexLeb, leb),
+            (EventIndexLeb, leb),
+            (TableIndexRelSleb, leb),
+            (GlobalIndexI32, u32_le),
+            (TableIndexSleb64, leb64),
+            (TableIndexI64, u64_le),
+            (TableNumberLeb, leb),
+            (TableIndexRelSleb64, leb64),
+            (FunctionIndexI32, u32_le),
+        ];
+        for (type_id, expected) in cases {
+            let entry = RelocationEntry::Index {
+                type_id,
+                offset: 0,
+                symbol_index: 7,
+            };
+            let bytes = apply_one(entry, 300);
+            assert_eq!(&bytes[1..][..expected.len()], expected, "{:?}", type_id);
+            // Bytes outside the relocation site are untouched
+            assert_eq!(bytes[0], 0xaa, "{:?}", type_id);
+            assert!(
+                bytes[1 + expected.len()..].iter().all(|b| *b == 0xaa),
+                "{:?}",
+                type_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_offset_relocs() {
+        use OffsetRelocType::*;
+        // 3 + (-5) = -2
+        let leb: &[u8] = &[0xfe, 0xff, 0xff, 0xff, 0x0f];
+        let sleb: &[u8] = &[0xfe, 0xff, 0xff, 0xff, 0x7f];
+        let leb64: &[u8] = &[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        let sleb64: &[u8] = &[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+        let u32_le: &[u8] = &[0xfe, 0xff, 0xff, 0xff];
+        let u64_le: &[u8] = &[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        let cases = [
+            (MemoryAddrLeb, leb),
+            (MemoryAddrSleb, sleb),
+            (MemoryAddrI32, u32_le),
+            (FunctionOffsetI32, u32_le),
+            (SectionOffsetI32, u32_le),
+            (MemoryAddrRelSleb, sleb),
+            (MemoryAddrLeb64, leb64),
+            (MemoryAddrSleb64, sleb64),
+            (MemoryAddrI64, u64_le),
+            (MemoryAddrRelSleb64, sleb64),
+            (MemoryAddrTlsSleb, sleb),
+            (FunctionOffsetI64, u64_le),
+            (MemoryAddrTlsSleb64, sleb64),
+        ];
+        for (type_id, expected) in cases {
+            let entry = RelocationEntry::Offset {
+                type_id,
+                offset: 0,
+                symbol_index: 7,
+                addend: -5,
+            };
+            let bytes = apply_one(entry, 3);
+            assert_eq!(&bytes[1..][..expected.len()], expected, "{:?}", type_id);
+            assert_eq!(bytes[0], 0xaa, "{:?}", type_id);
+            assert!(
+                bytes[1 + expected.len()..].iter().all(|b| *b == 0xaa),
+                "{:?}",
+                type_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_relocs_other_symbols() {
+        let entry = RelocationEntry::Index {
+            type_id: IndexRelocType::FunctionIndexLeb,
+            offset: 0,
+            symbol_index: 6,
+        };
+        let bytes = apply_one(entry, 300);
+        assert!(bytes.iter().all(|b| *b == 0xaa));
+    }
+
+    #[test]
+    fn test_apply_relocs_by_target() {
+        let arena = &Bump::new();
+        let mut relocs = RelocationSection::new(arena, "reloc.CODE");
+        for (offset, type_id) in [
+            IndexRelocType::FunctionIndexLeb,
+            IndexRelocType::TableIndexSleb,
+            IndexRelocType::GlobalIndexLeb,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            relocs.entries.push(RelocationEntry::Index {
+                type_id,
+                offset: 5 * offset as u32,
+                symbol_index: 7,
+            });
+        }
+        let mut bytes = [0x80, 0x80, 0x80, 0x80, 0x00].repeat(3);
+        let read_all =
+            |bytes: &[u8]| [0, 5, 10].map(|i| u32::parse((), bytes, &mut { i }).unwrap());
+
+        relocs.apply_fn_index_relocs(&mut bytes, 7, 1);
+        assert_eq!(read_all(&bytes), [1, 0, 0]);
+
+        relocs.apply_table_index_relocs(&mut bytes, 7, 2);
+        assert_eq!(read_all(&bytes), [1, 2, 0]);
+
+        relocs.apply_relocs_u32(&mut bytes, 7, 3);
+        assert_eq!(read_all(&bytes), [1, 2, 3]);
+
+        assert!(relocs.has_table_index_relocs(7));
+        assert!(!relocs.has_table_index_relocs(6));
+    }
+
+    #[test]
+    fn test_parse_all_reloc_types() {
+        for type_id in 0..=26u8 {
+            let bytes = [type_id, 0x01, 0x02, 0x7b];
+            let mut cursor = 0;
+            let entry = RelocationEntry::parse((), &bytes, &mut cursor).unwrap();
+            let (parsed_type, has_addend) = match entry {
+                RelocationEntry::Index { type_id, .. } => (type_id as u8, false),
+                RelocationEntry::Offset {
+                    type_id, addend, ..
+                } => {
+                    assert_eq!(addend, -5);
+                    (type_id as u8, true)
+                }
+            };
+            assert_eq!(parsed_type, type_id);
+            assert_eq!(cursor, if has_addend { 4 } else { 3 });
+        }
+        let mut cursor = 0;
+        assert!(RelocationEntry::parse((), &[27, 0, 0], &mut cursor).is_err());
+    }
+}
@@ -183,6 +183,9 @@ pub enum OpCode {
     I64REINTERPRETF64 = 0xbd,
     F32REINTERPRETI32 = 0xbe,
     F64REINTERPRETI64 = 0xbf,
+
+    /// Prefix for the instructions in [MiscOp]
+    MISC = 0xfc,
 }
 
 impl From<u8> for OpCode {
@@ -191,6 +194,67 @@ impl From<u8> for OpCode {
     }
 }
 
+/// Instructions from the non-trapping float-to-int and bulk memory proposals.
+/// In the binary, each of these is the [OpCode::MISC] prefix, followed by this sub-opcode
+/// as a LEB-128 u32.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MiscOp {
+    I32TRUNCSATSF32 = 0x00,
+    I32TRUNCSATUF32 = 0x01,
+    I32TRUNCSATSF64 = 0x02,
+    I32TRUNCSATUF64 = 0x03,
+    I64TRUNCSATSF32 = 0x04,
+    I64TRUNCSATUF32 = 0x05,
+    I64TRUNCSATSF64 = 0x06,
+    I64TRUNCSATUF64 = 0x07,
+
+    MEMORYINIT = 0x08,
+    DATADROP = 0x09,
+    MEMORYCOPY = 0x0a,
+    MEMORYFILL = 0x0b,
+    TABLEINIT = 0x0c,
+    ELEMDROP = 0x0d,
+    TABLECOPY = 0x0e,
+}
+
+impl TryFrom<u32> for MiscOp {
+    type Error = String;
+
+    fn try_from(x: u32) -> Result<Self, Self::Error> {
+        use MiscOp::*;
+
+        let op = match x {
+            0x00 => I32TRUNCSATSF32,
+            0x01 => I32TRUNCSATUF32,
+            0x02 => I32TRUNCSATSF64,
+            0x03 => I32TRUNCSATUF64,
+            0x04 => I64TRUNCSATSF32,
+            0x05 => I64TRUNCSATUF32,
+            0x06 => I64TRUNCSATSF64,
+            0x07 => I64TRUNCSATUF64,
+            0x08 => MEMORYINIT,
+            0x09 => DATADROP,
+            0x0a => MEMORYCOPY,
+            0x0b => MEMORYFILL,
+            0x0c => TABLEINIT,
+            0x0d => ELEMDROP,
+            0x0e => TABLECOPY,
+            _ => return Err(format!("Unknown Wasm instruction 0xfc 0x{:02x}", x)),
+        };
+
+        Ok(op)
+    }
+}
+
+impl Parse<()> for MiscOp {
+    fn parse(_: (), bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
+        let offset = *cursor;
+        let x = u32::parse((), bytes, cursor)?;
+        MiscOp::try_from(x).map_err(|message| ParseError { offset, message })
+    }
+}
+
 /// The format of the *immediate* operands of an operator
 /// Immediates appear directly in the byte stream after the opcode,
 /// rather than being popped off the value stack. These are the possible forms.
@@ -257,6 +321,9 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
             NoImmediate
         }
 
+        // The immediates depend on the sub-opcode that follows. See misc_immediates_for.
+        MISC => NoImmediate,
+
         // Catch-all in case of an invalid cast from u8 to OpCode while parsing binary
         // (rustc keeps this code, I verified in Compiler Explorer)
         #[allow(unreachable_patterns)]
@@ -266,6 +333,31 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
     Ok(imm)
 }
 
+fn misc_immediates_for(op: MiscOp) -> OpImmediates {
+    use MiscOp::*;
+    use OpImmediates::*;
+
+    match op {
+        I32TRUNCSATSF32 | I32TRUNCSATUF32 | I32TRUNCSATSF64 | I32TRUNCSATUF64 | I64TRUNCSATSF32
+        | I64TRUNCSATUF32 | I64TRUNCSATSF64 | I64TRUNCSATUF64 => NoImmediate,
+
+        // data segment index, memory index
+        MEMORYINIT => Leb32x2,
+        // data segment index
+        DATADROP => Leb32x1,
+        // destination and source memory indices
+        MEMORYCOPY => Leb32x2,
+        // memory index
+        MEMORYFILL => Leb32x1,
+        // element segment index, table index
+        TABLEINIT => Leb32x2,
+        // element segment index
+        ELEMDROP => Leb32x1,
+        // destination and source table indices
+        TABLECOPY => Leb32x2,
+    }
+}
+
 impl SkipBytes for OpCode {
     fn skip_bytes(bytes: &[u8], cursor: &mut usize) -> Result<(), ParseError> {
         use OpImmediates::*;
@@ -274,39 +366,42 @@ impl SkipBytes for OpCode {
 
         let opcode: OpCode = OpCode::from(opcode_byte);
         // will return Err if transmute was invalid
-        let immediates = immediates_for(opcode).map_err(|message| ParseError {
+        let mut immediates = immediates_for(opcode).map_err(|message| ParseError {
             message,
             offset: *cursor,
         })?;
 
+        if opcode == OpCode::MISC {
+            // Skip the prefix and the sub-opcode. Immediates depend on the sub-opcode.
+            *cursor += 1;
+            let misc_op = MiscOp::parse((), bytes, cursor)?;
+            immediates = misc_immediates_for(misc_op);
+        } else {
+            *cursor += 1;
+        }
+
         match immediates {
-            NoImmediate => {
-                *cursor += 1;
-            }
+            NoImmediate => {}
             Byte1 => {
-                *cursor += 1 + 1;
+                *cursor += 1;
             }
             Bytes4 => {
-                *cursor += 1 + 4;
+                *cursor += 4;
             }
             Bytes8 => {
-                *cursor += 1 + 8;
+                *cursor += 8;
             }
             Leb32x1 => {
-                *cursor += 1;
                 u32::skip_bytes(bytes, cursor)?;
             }
             Leb64x1 => {
-                *cursor += 1;
                 u64::skip_bytes(bytes, cursor)?;
             }
             Leb32x2 => {
-                *cursor += 1;
                 u32::skip_bytes(bytes, cursor)?;
                 u32::skip_bytes(bytes, cursor)?;
             }
             BrTable => {
-                *cursor += 1;
                 let n_labels = 1 + u32::parse((), bytes, cursor)?;
                 for _ in 0..n_labels {
                     u32::skip_bytes(bytes, cursor)?;
@@ -322,3 +417,10 @@ impl Serialize for OpCode {
         (*self as u8).serialize(buffer)
     }
 }
+
+impl Serialize for MiscOp {
+    fn serialize<T: crate::SerialBuffer>(&self, buffer: &mut T) {
+        buffer.append_u8(OpCode::MISC as u8);
+        buffer.encode_u32(*self as u32);
+    }
+}
@@ -7,8 +7,8 @@ use roc_error_macros::internal_error;
 
 use crate::{Value, DUMMY_FUNCTION};
 
-use super::linking::{LinkingSection, SymInfo, WasmObjectSymbol};
-use super::opcodes::OpCode;
+use super::linking::{LinkingSection, RelocTarget, RelocationSection, SymInfo, WasmObjectSymbol};
+use super::opcodes::{MiscOp, OpCode};
 use super::parse::{Parse, ParseError, SkipBytes};
 use super::serialize::{SerialBuffer, Serialize, MAX_SIZE_ENCODED_U32};
 use super::ValueType;
@@ -34,8 +34,8 @@ pub enum SectionId {
     Element = 9,
     Code = 10,
     Data = 11,
-    /// DataCount section is unused. Only needed for single-pass validation of
-    /// memory.init and data.drop, which we don't use
+    /// DataCount section is only needed for single-pass validation of
+    /// memory.init and data.drop. We keep it if the host has one.
     DataCount = 12,
 }
 
@@ -1306,6 +1306,18 @@ impl<'a> ElementSection<'a> {
         }
     }
 
+    /// Point every table slot that holds `old_fn_index` at `new_fn_index` instead,
+    /// for a function that has been moved during linking.
+    pub fn reindex_fn(&mut self, old_fn_index: u32, new_fn_index: u32) {
+        for seg in self.segments.iter_mut() {
+            for fn_index in seg.fn_indices.iter_mut() {
+                if *fn_index == old_fn_index {
+                    *fn_index = new_fn_index;
+                }
+            }
+        }
+    }
+
     /// Number of elements in the table
     pub fn max_table_index(&self) -> u32 {
         let mut result = 0;
@@ -1447,6 +1459,34 @@ impl<'a> CodeSection<'a> {
             dead_import_dummy_count: 0,
         })
     }
+
+    /// Check whether any function uses `memory.copy` or `memory.fill` from the bulk memory proposal.
+    /// If the host uses them, then its runtime supports them, so the app code can use them too.
+    pub fn uses_bulk_memory(&self) -> Result<bool, ParseError> {
+        for fn_offset in self.function_offsets.iter() {
+            let mut cursor = *fn_offset as usize;
+            let fn_length = u32::parse((), &self.bytes, &mut cursor)? as usize;
+            let fn_end = cursor + fn_length;
+
+            let local_groups = u32::parse((), &self.bytes, &mut cursor)?;
+            for _ in 0..local_groups {
+                u32::skip_bytes(&self.bytes, &mut cursor)?;
+                cursor += 1; // value type
+            }
+
+            while cursor < fn_end {
+                if self.bytes[cursor] == OpCode::MISC as u8 {
+                    let mut misc_cursor = cursor + 1;
+                    let misc_op = MiscOp::parse((), &self.bytes, &mut misc_cursor)?;
+                    if matches!(misc_op, MiscOp::MEMORYCOPY | MiscOp::MEMORYFILL) {
+                        return Ok(true);
+                    }
+                }
+                OpCode::skip_bytes(&self.bytes, &mut cursor)?;
+            }
+        }
+        Ok(false)
+    }
 }
 
 impl<'a> Serialize for CodeSection<'a> {
@@ -1544,8 +1584,14 @@ impl Serialize for DataSegment<'_> {
 #[derive(Debug)]
 pub struct DataSection<'a> {
     pub end_addr: u32,
+    /// Whether to emit a DataCount section. Validators require it if any code uses
+    /// `memory.init` or `data.drop`, so we keep it whenever the host module had one.
+    pub has_count_section: bool,
     count: u32,
     bytes: Vec<'a, u8>,
+    /// Relocation offsets are from the start of the section body, which includes the
+    /// segment count, but `bytes` doesn't. This is the length of the encoded count.
+    reloc_offset_base: u32,
 }
 
 impl<'a> DataSection<'a> {
@@ -1554,13 +1600,21 @@ impl<'a> DataSection<'a> {
     pub fn new(arena: &'a Bump) -> Self {
         DataSection {
             end_addr: 0,
+            has_count_section: false,
             count: 0,
             bytes: Vec::new_in(arena),
+            reloc_offset_base: 0,
         }
     }
 
+    /// Size in bytes, including the DataCount section if any
     pub fn size(&self) -> usize {
-        MAX_SIZE_SECTION_HEADER + self.bytes.len()
+        let count_section_size = if self.has_count_section {
+            MAX_SIZE_SECTION_HEADER + MAX_SIZE_ENCODED_U32
+        } else {
+            0
+        };
+        MAX_SIZE_SECTION_HEADER + self.bytes.len() + count_section_size
     }
 
     pub fn append_segment(&mut self, segment: DataSegment<'a>) -> u32 {
@@ -1570,21 +1624,45 @@ impl<'a> DataSection<'a> {
         index
     }
 
+    /// Look up a data segment by index, returning its mode and initialisation bytes
+    pub fn segment(&self, index: u32) -> Result<(DataMode, &[u8]), String> {
+        if index >= self.count {
+            return Err(format!(
+                "Data segment {} does not exist. There are only {}.",
+                index, self.count
+            ));
+        }
+        let mut cursor = 0;
+        for i in 0..self.count {
+            let mode =
+                DataMode::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{:?}", e))?;
+            let len32 = u32::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{:?}", e))?;
+            let len = len32 as usize;
+            if i == index {
+                return Ok((mode, &self.bytes[cursor..][..len]));
+            }
+            cursor += len;
+        }
+        unreachable!()
+    }
+
     pub fn load_into(&self, memory: &mut [u8]) -> Result<(), String> {
         let mut cursor = 0;
         for _ in 0..self.count {
             let mode =
                 DataMode::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{:?}", e))?;
+            let len32 = u32::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{:?}", e))?;
+            let len = len32 as usize;
             let start = match mode {
                 DataMode::Active {
                     offset: ConstExpr::I32(addr),
                 } => addr as usize,
                 _ => {
+                    // Passive segments are only loaded by `memory.init`
+                    cursor += len;
                     continue;
                 }
             };
-            let len32 = u32::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{:?}", e))?;
-            let len = len32 as usize;
             let mut target_slice = &mut memory[start..][..len];
             target_slice
                 .write(&self.bytes[cursor..][..len])
@@ -1593,6 +1671,62 @@ impl<'a> DataSection<'a> {
         }
         Ok(())
     }
+
+    /// Apply relocations for a symbol to the data segments.
+    /// See [RelocationSection::apply_relocs_u32]
+    pub fn apply_relocs_u32(&mut self, relocs: &RelocationSection, sym_index: u32, value: u32) {
+        self.apply_relocs_help(relocs, sym_index, RelocTarget::Other, value)
+    }
+
+    /// Apply the relocations for a function's table slot to the data segments,
+    /// e.g. a function pointer in a static initializer.
+    /// See [RelocationSection::apply_table_index_relocs]
+    pub fn apply_table_index_relocs(
+        &mut self,
+        relocs: &RelocationSection,
+        sym_index: u32,
+        table_index: u32,
+    ) {
+        self.apply_relocs_help(relocs, sym_index, RelocTarget::TableIndex, table_index)
+    }
+
+    fn apply_relocs_help(
+        &mut self,
+        relocs: &RelocationSection,
+        sym_index: u32,
+        target: RelocTarget,
+        value: u32,
+    ) {
+        // Where each active segment's data is in `bytes`, and its address in memory
+        let mut segments = std::vec::Vec::with_capacity(self.count as usize);
+        let mut cursor = 0;
+        for _ in 0..self.count {
+            let mode = DataMode::parse((), &self.bytes, &mut cursor).unwrap();
+            let len = u32::parse((), &self.bytes, &mut cursor).unwrap() as usize;
+            if let DataMode::Active {
+                offset: ConstExpr::I32(addr),
+            } = mode
+            {
+                segments.push((cursor..cursor + len, addr as u32));
+            }
+            cursor += len;
+        }
+        let site_address = |index: usize| {
+            segments
+                .iter()
+                .find(|(range, _)| range.contains(&index))
+                .map(|(range, addr)| addr + (index - range.start) as u32)
+        };
+
+        relocs.apply_relocs_help(
+            &mut self.bytes,
+            self.reloc_offset_base,
+            sym_index,
+            target,
+            value,
+            site_address,
+        );
+    }
 }
 
 impl<'a> Parse<&'a Bump> for DataSection<'a> {
@@ -1600,12 +1734,23 @@ impl<'a> Parse<&'a Bump> for DataSection<'a> {
         if *cursor >= module_bytes.len() {
             return Ok(DataSection {
                 end_addr: 0,
+                has_count_section: false,
                 count: 0,
                 bytes: Vec::<u8>::new_in(arena),
+                reloc_offset_base: 0,
             });
         }
+        let section_start = *cursor;
         let (count, range) = parse_section(Self::ID, module_bytes, cursor)?;
 
+        let reloc_offset_base = if range.is_empty() {
+            0
+        } else {
+            let mut count_start = section_start + 1;
+            u32::skip_bytes(module_bytes, &mut count_start)?;
+            (range.start - count_start) as u32
+        };
+
         let end = range.end;
         let mut bytes = Vec::<u8>::with_capacity_in(range.len() * 2, arena);
         bytes.extend_from_slice(&module_bytes[range]);
@@ -1629,8 +1774,10 @@ impl<'a> Parse<&'a Bump> for DataSection<'a> {
 
         Ok(DataSection {
             end_addr: end_addr as u32,
+            has_count_section: false,
             count,
             bytes,
+            reloc_offset_base,
         })
     }
 }
@@ -1641,6 +1788,29 @@ impl<'a> Serialize for DataSection<'a> {
     }
 }
 
+/*******************************************************************
+ *
+ * DataCount section
+ *
+ * Just the number of data segments. It goes before the Code section
+ * but its value comes from the Data section, which may have grown
+ * since we parsed it, so we don't store the count separately.
+ *
+ *******************************************************************/
+
+pub struct DataCountSection<'a, 'd>(pub &'d DataSection<'a>);
+
+impl Serialize for DataCountSection<'_, '_> {
+    fn serialize<B: SerialBuffer>(&self, buffer: &mut B) {
+        let data = self.0;
+        if data.has_count_section {
+            let header_indices = write_section_header(buffer, SectionId::DataCount);
+            buffer.encode_u32(data.count);
+            update_section_size(buffer, header_indices);
+        }
+    }
+}
+
 /*******************************************************************
  *
  * Opaque section
@@ -1920,4 +2090,104 @@ mod tests {
         }
         test_assert_types_preload(arena, &section);
     }
+
+    #[test]
+    fn test_code_section_uses_bulk_memory() {
+        let arena = &Bump::new();
+
+        let mut fn_without = Vec::new_in(arena);
+        fn_without.push(0); // no locals
+        MiscOp::I32TRUNCSATSF64.serialize(&mut fn_without);
+        OpCode::END.serialize(&mut fn_without);
+
+        let mut fn_with = Vec::new_in(arena);
+        fn_with.push(1); // one group of locals
+        fn_with.encode_u32(2);
+        fn_with.push(ValueType::I32 as u8);
+        OpCode::I32CONST.serialize(&mut fn_with);
+        fn_with.encode_i32(0);
+        MiscOp::MEMORYFILL.serialize(&mut fn_with);
+        fn_with.push(0);
+        OpCode::END.serialize(&mut fn_with);
+
+        let mut section = CodeSection::new(arena);
+        for (i, function) in [&fn_without, &fn_with].into_iter().enumerate() {
+            if i == 1 {
+                assert!(!section.uses_bulk_memory().unwrap());
+            }
+            section.function_offsets.push(section.bytes.len() as u32);
+            section.bytes.encode_u32(function.len() as u32);
+            section.bytes.extend_from_slice(function);
+            section.function_count += 1;
+        }
+        assert!(section.uses_bulk_memory().unwrap());
+    }
+
+    #[test]
+    fn test_data_section_segment() {
+        let arena = &Bump::new();
+        let mut section = DataSection::new(arena);
+        section.append_segment(DataSegment {
+            mode: DataMode::active_at(16),
+            init: bumpalo::vec![in arena; 1, 2, 3],
+        });
+        section.append_segment(DataSegment {
+            mode: DataMode::Passive,
+            init: bumpalo::vec![in arena; 4, 5],
+        });
+
+        let (mode, init) = section.segment(1).unwrap();
+        assert!(matches!(mode, DataMode::Passive));
+        assert_eq!(init, &[4, 5]);
+        assert!(section.segment(2).is_err());
+
+        let mut memory = [0; 24];
+        section.load_into(&mut memory).unwrap();
+        assert_eq!(&memory[16..], &[1, 2, 3, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_data_section_relocs() {
+        use crate::linking::{OffsetRelocType, RelocationEntry};
+
+        let arena = &Bump::new();
+        let mut original = DataSection::new(arena);
+        original.append_segment(DataSegment {
+            mode: DataMode::active_at(16),
+            init: bumpalo::vec![in arena; 0; 8],
+        });
+        original.append_segment(DataSegment {
+            mode: DataMode::active_at(1024),
+            init: bumpalo::vec![in arena; 0; 8],
+        });
+        let mut buffer = std::vec::Vec::with_capacity(original.size());
+        original.serialize(&mut buffer);
+        let mut section = DataSection::parse(arena, &buffer, &mut 0).unwrap();
+
+        // Offsets are from the start of the section body, including the segment count.
+        // Each segment header is a mode byte, an `i32.const` expression and a length.
+        let first_data_offset = 1 + 5;
+        let second_data_offset = first_data_offset + 8 + 6;
+        let mut relocs = RelocationSection::new(arena, "reloc.DATA");
+        relocs.entries.push(RelocationEntry::Offset {
+            type_id: OffsetRelocType::MemoryAddrI32,
+            offset: first_data_offset + 2,
+            symbol_index: 0,
+            addend: 4,
+        });
+        relocs.entries.push(RelocationEntry::Offset {
+            type_id: OffsetRelocType::MemoryAddrLocrelI32,
+            offset: second_data_offset + 2,
+            symbol_index: 0,
+            addend: 0,
+        });
+        section.apply_relocs_u32(&relocs, 0, 20);
+
+        let (_, first) = section.segment(0).unwrap();
+        assert_eq!(first, &[0, 0, 24, 0, 0, 0, 0, 0]);
+
+        // Target address 20, relative to the relocation site at address 1026
+        let (_, second) = section.segment(1).unwrap();
+        assert_eq!(&second[2..6], &(-1006i32).to_le_bytes());
+    }
 }
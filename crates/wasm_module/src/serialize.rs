@@ -155,6 +155,24 @@ pub fn overwrite_padded_u32(buffer: &mut [u8], value: u32) {
     buffer[4] = x as u8;
 }
 
+pub fn overwrite_padded_i64(buffer: &mut [u8], value: i64) {
+    let mut x = value;
+    for byte in buffer.iter_mut().take(9) {
+        *byte = 0x80 | ((x & 0x7f) as u8);
+        x >>= 7;
+    }
+    buffer[9] = (x & 0x7f) as u8;
+}
+
+pub fn overwrite_padded_u64(buffer: &mut [u8], value: u64) {
+    let mut x = value;
+    for byte in buffer.iter_mut().take(9) {
+        *byte = 0x80 | ((x & 0x7f) as u8);
+        x >>= 7;
+    }
+    buffer[9] = x as u8;
+}
+
 pub trait SerialBuffer: Debug {
     fn append_u8(&mut self, b: u8);
     fn overwrite_u8(&mut self, index: usize, b: u8);
@@ -19,6 +19,7 @@ EnvFormat := {} has [
              string: envString,
              list: envList,
              record: envRecord,
+             tag: envTag,
          },
      ]
 
@@ -93,3 +94,8 @@ envList = \decodeElem -> Decode.custom \bytes, @EnvFormat {} ->
 envRecord : _, (_, _ -> [Keep (Decoder _ _), Skip]), (_ -> _) -> Decoder _ _
 envRecord = \_initialState, _stepField, _finalizer -> Decode.custom \bytes, @EnvFormat {} ->
         { result: Err TooShort, rest: bytes }
+
+# Tags have no meaningful representation in environment variables.
+envTag : (_ -> [Keep _, Skip]), (_, _ -> [Keep (Decoder _ _), Skip]), (_ -> _) -> Decoder _ _
+envTag = \_stepTag, _stepElem, _finalizer -> Decode.custom \bytes, @EnvFormat {} ->
+        { result: Err TooShort, rest: bytes }